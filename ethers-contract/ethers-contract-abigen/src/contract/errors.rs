@@ -139,6 +139,17 @@ impl Context {
             }
         }
 
+        impl #ethers_contract::ContractRevert for #enum_name {
+            fn valid_selector(selector: #ethers_core::types::Selector) -> bool {
+                #(
+                    if selector == <#variants as #ethers_contract::EthError>::selector() {
+                        return true
+                    }
+                )*
+                false
+            }
+        }
+
         impl ::std::fmt::Display for #enum_name {
             fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
                 match self {
@@ -3,8 +3,10 @@
 use crate::utils;
 use ethers_core::macros::ethers_core_crate;
 use proc_macro2::{Ident, Literal, TokenStream};
-use quote::{quote, quote_spanned};
-use syn::{parse::Error, spanned::Spanned, Data, DeriveInput, Fields, Variant};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse::Error, spanned::Spanned, AttrStyle, Data, DeriveInput, Fields, Meta, NestedMeta, Variant,
+};
 
 /// Generates the tokenize implementation
 pub fn derive_tokenizeable_impl(input: &DeriveInput) -> Result<TokenStream, Error> {
@@ -67,7 +69,17 @@ pub fn derive_tokenizeable_impl(input: &DeriveInput) -> Result<TokenStream, Erro
             }
             Fields::Unit => return Ok(tokenize_unit_type(&input.ident)),
         },
-        Data::Enum(ref data) => return tokenize_enum(name, data.variants.iter()),
+        Data::Enum(ref data) => {
+            let carries_data = data
+                .variants
+                .iter()
+                .any(|v| v.fields.len() > 1 || matches!(v.fields, Fields::Named(_)));
+            return if carries_data || parse_enum_tagged(input)? {
+                tokenize_tagged_enum(name, data.variants.iter())
+            } else {
+                tokenize_enum(name, data.variants.iter())
+            }
+        }
         Data::Union(_) => {
             return Err(Error::new(input.span(), "EthAbiType cannot be derived for unions"))
         }
@@ -259,3 +271,168 @@ fn tokenize_enum<'a>(
         impl #ethers_core::abi::TokenizableItem for #enum_name {}
     })
 }
+
+/// Returns `true` if the enum is annotated with `#[ethabitype(tagged)]`
+fn parse_enum_tagged(input: &DeriveInput) -> Result<bool, Error> {
+    let mut tagged = false;
+    for a in input.attrs.iter() {
+        if let AttrStyle::Outer = a.style {
+            if let Ok(Meta::List(meta)) = a.parse_meta() {
+                if meta.path.is_ident("ethabitype") {
+                    for n in meta.nested.iter() {
+                        match n {
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("tagged") => {
+                                tagged = true;
+                            }
+                            _ => {
+                                return Err(Error::new(
+                                    n.span(),
+                                    "unrecognized ethabitype parameter",
+                                ))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(tagged)
+}
+
+/// Derive for an enum whose variants carry data
+///
+/// Every variant is encoded as a `(uint8, bytes)` tuple of the variant's index and the ABI encoded
+/// fields of the variant.
+fn tokenize_tagged_enum<'a>(
+    enum_name: &Ident,
+    variants: impl Iterator<Item = &'a Variant> + 'a,
+) -> Result<TokenStream, Error> {
+    let ethers_core = ethers_core_crate();
+
+    let mut into_tokens = TokenStream::new();
+    let mut from_tokens = TokenStream::new();
+    for (idx, variant) in variants.into_iter().enumerate() {
+        let var_ident = &variant.ident;
+        if idx > u8::MAX as usize {
+            return Err(Error::new(
+                variant.span(),
+                "EthAbiType cannot be derived for enums with more than 256 variants",
+            ))
+        }
+        let tag = Literal::u8_unsuffixed(idx as u8);
+        let tag_value = Literal::u8_suffixed(idx as u8);
+        let params = variant.fields.iter().map(|f| {
+            let ty = &f.ty;
+            quote_spanned! { f.span() => <#ty as #ethers_core::abi::AbiType>::param_type() }
+        });
+        let values = variant.fields.iter().map(|f| {
+            quote_spanned! { f.span() =>
+                #ethers_core::abi::Tokenizable::from_token(
+                    iter.next().expect("The iter is guaranteed to be something due to the decoding")
+                )?
+            }
+        });
+        let names: Vec<_> = variant
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.ident.clone().unwrap_or_else(|| format_ident!("field{}", i)))
+            .collect();
+        let (pattern, init) = match variant.fields {
+            Fields::Named(_) => (quote! { { #(#names,)* } }, quote! { { #(#names: #values,)* } }),
+            Fields::Unnamed(_) => (quote! { ( #(#names,)* ) }, quote! { ( #(#values,)* ) }),
+            Fields::Unit => (quote! {}, quote! {}),
+        };
+
+        into_tokens.extend(quote! {
+            #enum_name::#var_ident #pattern => {
+                let data = #ethers_core::abi::encode(&[
+                    #(#ethers_core::abi::Tokenizable::into_token(#names),)*
+                ]);
+                #ethers_core::abi::Token::Tuple(::std::vec![
+                    #ethers_core::abi::Token::Uint(#tag_value.into()),
+                    #ethers_core::abi::Token::Bytes(data),
+                ])
+            }
+        });
+        let decode = if variant.fields.is_empty() {
+            let msg = format!("Expected no data for {enum_name}::{var_ident}, got {{:?}}");
+            quote! {
+                if !data.is_empty() {
+                    return Err(#ethers_core::abi::InvalidOutputType(::std::format!(#msg, data)))
+                }
+            }
+        } else {
+            quote! {
+                let tokens = #ethers_core::abi::decode(&[#(#params,)*], &data)
+                    .map_err(|err| #ethers_core::abi::InvalidOutputType(err.to_string()))?;
+                let mut iter = tokens.into_iter();
+            }
+        };
+        from_tokens.extend(quote! {
+            #tag => {
+                #decode
+                Ok(#enum_name::#var_ident #init)
+            }
+        });
+    }
+
+    Ok(quote! {
+        impl #ethers_core::abi::AbiType for #enum_name {
+            fn param_type() -> #ethers_core::abi::ParamType {
+                #ethers_core::abi::ParamType::Tuple(::std::vec![
+                    #ethers_core::abi::ParamType::Uint(8),
+                    #ethers_core::abi::ParamType::Bytes,
+                ])
+            }
+        }
+
+        impl #ethers_core::abi::AbiArrayType for #enum_name {}
+
+        impl #ethers_core::abi::Tokenizable for #enum_name {
+            fn from_token(token: #ethers_core::abi::Token) -> ::std::result::Result<Self, #ethers_core::abi::InvalidOutputType>
+            where
+                Self: Sized,
+            {
+                let (tag, data) = match token {
+                    #ethers_core::abi::Token::Tuple(tokens) if tokens.len() == 2 => {
+                        let mut iter = tokens.into_iter();
+                        match (iter.next(), iter.next()) {
+                            (Some(tag), Some(#ethers_core::abi::Token::Bytes(data))) => {
+                                (<u8 as #ethers_core::abi::Tokenizable>::from_token(tag)?, data)
+                            }
+                            (tag, data) => {
+                                return Err(#ethers_core::abi::InvalidOutputType(::std::format!(
+                                    "Expected (uint8, bytes), got ({:?}, {:?})",
+                                    tag,
+                                    data
+                                )))
+                            }
+                        }
+                    }
+                    token => {
+                        return Err(#ethers_core::abi::InvalidOutputType(::std::format!(
+                            "Expected Tuple, got {:?}",
+                            token
+                        )))
+                    }
+                };
+                match tag {
+                    #from_tokens
+                    tag => Err(#ethers_core::abi::InvalidOutputType(::std::format!(
+                        "Unknown variant {}",
+                        tag
+                    ))),
+                }
+            }
+
+            fn into_token(self) -> #ethers_core::abi::Token {
+                match self {
+                    #into_tokens
+                }
+            }
+        }
+
+        impl #ethers_core::abi::TokenizableItem for #enum_name {}
+    })
+}
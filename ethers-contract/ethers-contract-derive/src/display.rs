@@ -4,7 +4,10 @@ use crate::utils;
 use ethers_core::{abi::ParamType, macros::ethers_core_crate};
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{parse::Error, spanned::Spanned, Data, DeriveInput, Fields, Index};
+use syn::{
+    parse::Error, spanned::Spanned, AttrStyle, Data, DeriveInput, Field, Fields, Index, Lit, Meta,
+    NestedMeta,
+};
 
 /// Derive `fmt::Display` for the given type
 pub(crate) fn derive_eth_display_impl(input: DeriveInput) -> Result<TokenStream, Error> {
@@ -31,7 +34,55 @@ pub(crate) fn derive_eth_display_impl(input: DeriveInput) -> Result<TokenStream,
             let idx = Index::from(idx);
             quote! {#idx}
         });
-        let tokens = if let Ok(param) = utils::find_parameter_type(&field.ty) {
+        let format = parse_field_format(field)?;
+        let param = utils::find_parameter_type(&field.ty);
+        let tokens = if let Some(units) = format.units {
+            match param {
+                Ok(ParamType::Uint(_) | ParamType::Int(_)) => {
+                    quote! {
+                        let formatted = #ethers_core::utils::format_units(self.#ident, #units)
+                            .map_err(|_| ::std::fmt::Error)?;
+                        f.write_str(&formatted)?;
+                    }
+                }
+                _ => {
+                    return Err(Error::new(
+                        field.span(),
+                        "ethdisplay units can only be used for integer fields",
+                    ))
+                }
+            }
+        } else if format.hex {
+            let bytes = match param {
+                Ok(ParamType::Uint(_) | ParamType::Int(_)) => None,
+                Ok(ParamType::Bytes | ParamType::FixedBytes(_)) => Some(quote! { &self.#ident }),
+                Ok(ParamType::Array(ty) | ParamType::FixedArray(ty, _))
+                    if *ty == ParamType::Uint(8) =>
+                {
+                    Some(quote! { &self.#ident[..] })
+                }
+                _ => {
+                    return Err(Error::new(
+                        field.span(),
+                        "ethdisplay hex can only be used for integer and bytes fields",
+                    ))
+                }
+            };
+            match bytes {
+                // only show the first and last 4 bytes of longer values
+                Some(bytes) => quote! {
+                    let hex = #hex_encode(#bytes);
+                    if hex.len() > 16 {
+                        write!(f, "0x{}...{}", &hex[..8], &hex[hex.len() - 8..])?;
+                    } else {
+                        write!(f, "0x{}", hex)?;
+                    }
+                },
+                None => quote! {
+                    write!(f, "{:#x}", self.#ident)?;
+                },
+            }
+        } else if let Ok(param) = param {
             match param {
                 ParamType::Address | ParamType::Uint(_) | ParamType::Int(_) => {
                     quote! {
@@ -101,3 +152,59 @@ pub(crate) fn derive_eth_display_impl(input: DeriveInput) -> Result<TokenStream,
         }
     })
 }
+
+/// The formatting options of a field, set with `#[ethdisplay(..)]`
+#[derive(Default)]
+struct FieldFormat {
+    /// Format integers as hex and bytes as truncated hex
+    hex: bool,
+    /// Format integers in these units
+    units: Option<TokenStream>,
+}
+
+fn parse_field_format(field: &Field) -> Result<FieldFormat, Error> {
+    let mut format = FieldFormat::default();
+    for a in field.attrs.iter() {
+        if let AttrStyle::Outer = a.style {
+            if let Ok(Meta::List(meta)) = a.parse_meta() {
+                if meta.path.is_ident("ethdisplay") {
+                    for n in meta.nested.iter() {
+                        match n {
+                            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("hex") => {
+                                format.hex = true;
+                            }
+                            NestedMeta::Meta(Meta::NameValue(meta))
+                                if meta.path.is_ident("units") =>
+                            {
+                                let units = match meta.lit {
+                                    Lit::Int(ref lit) => {
+                                        let units = lit.base10_parse::<u32>()?;
+                                        quote! { #units }
+                                    }
+                                    Lit::Str(ref lit) => quote! { #lit },
+                                    _ => {
+                                        return Err(Error::new(
+                                            meta.lit.span(),
+                                            "units must be an integer or a string",
+                                        ))
+                                    }
+                                };
+                                format.units = Some(units);
+                            }
+                            _ => {
+                                return Err(Error::new(
+                                    n.span(),
+                                    "unrecognized ethdisplay parameter",
+                                ))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if format.hex && format.units.is_some() {
+        return Err(Error::new(field.span(), "ethdisplay hex and units are mutually exclusive"))
+    }
+    Ok(format)
+}
@@ -9,7 +9,7 @@ use syn::{
 };
 
 use ethers_core::{
-    abi::{Event, EventExt, EventParam, HumanReadableParser},
+    abi::{Event, EventExt, EventParam, HumanReadableParser, ParamType},
     macros::{ethers_contract_crate, ethers_core_crate},
 };
 use hex::FromHex;
@@ -105,9 +105,62 @@ pub(crate) fn derive_eth_event_impl(input: DeriveInput) -> Result<TokenStream, E
 
     let tokenize_impl = abi_ty::derive_tokenizeable_impl(&input)?;
 
+    let topic_matchers_impl = derive_hashed_topic_matchers(&input, &event)?;
+
     Ok(quote! {
         #tokenize_impl
         #ethevent_impl
+        #topic_matchers_impl
+    })
+}
+
+/// Generates a `matches_<field>` helper for every indexed `string` or `bytes` field.
+///
+/// The topics of these fields only contain the keccak256 hash of the value, so the helper hashes a
+/// candidate value and compares it with the topic.
+fn derive_hashed_topic_matchers(input: &DeriveInput, event: &Event) -> Result<TokenStream, Error> {
+    let fields = match input.data {
+        Data::Struct(ref data) => match data.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => return Ok(quote! {}),
+        },
+        _ => return Ok(quote! {}),
+    };
+    let ethers_core = ethers_core_crate();
+
+    let mut matchers = Vec::new();
+    for (field, param) in fields.iter().zip(event.inputs.iter()) {
+        let (_, indexed) = parse_field_attributes(field)?;
+        if !(indexed || param.indexed) ||
+            !matches!(param.kind, ParamType::String | ParamType::Bytes)
+        {
+            continue
+        }
+        let field_name = field.ident.as_ref().expect("named field");
+        let field_str = field_name.to_string();
+        let field_str = field_str.trim_start_matches("r#");
+        let fn_name = utils::ident(&format!("matches_{field_str}"));
+        let doc = format!(
+            "Returns `true` if the keccak256 hash of `value` matches the indexed `{field_str}` topic"
+        );
+        matchers.push(quote! {
+            #[doc = #doc]
+            pub fn #fn_name(&self, value: impl AsRef<[u8]>) -> bool {
+                AsRef::<[u8]>::as_ref(&self.#field_name) == #ethers_core::utils::keccak256(value.as_ref())
+            }
+        });
+    }
+
+    if matchers.is_empty() {
+        return Ok(quote! {})
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #( #matchers )*
+        }
     })
 }
 
@@ -109,7 +109,26 @@ pub fn abigen(input: TokenStream) -> TokenStream {
 ///
 /// This derive macro automatically adds a type bound `field: Tokenizable` for
 /// each field type.
-#[proc_macro_derive(EthAbiType)]
+///
+/// Enums with only unit variants are encoded as `uint8`, and enums whose variants hold a single
+/// unnamed field are encoded as the field itself. Enums with variants that hold multiple or named
+/// fields, or enums annotated with `#[ethabitype(tagged)]`, are encoded as a `(uint8, bytes)`
+/// tuple of the variant's index and the ABI encoded fields of the variant.
+///
+/// # Example
+///
+/// ```ignore
+/// use ethers_contract::EthAbiType;
+/// use ethers_core::types::*;
+///
+/// #[derive(Debug, Clone, EthAbiType)]
+/// enum Action {
+///     Transfer { to: Address, amount: U256 },
+///     Burn(U256),
+///     Pause,
+/// }
+/// ```
+#[proc_macro_derive(EthAbiType, attributes(ethabitype))]
 pub fn derive_abi_type(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     match abi_ty::derive_tokenizeable_impl(&input) {
@@ -155,6 +174,12 @@ pub fn derive_abi_codec(input: TokenStream) -> TokenStream {
 /// The fields of the structure are formatted comma separated, like `self.0,
 /// self.1, self.2,...`
 ///
+/// The formatting of a field can be changed with `#[ethdisplay(..)]`:
+///  - `#[ethdisplay(units = 18)]` or `#[ethdisplay(units = "gwei")]` formats an integer in the
+///    given units, see `ethers_core::utils::format_units`
+///  - `#[ethdisplay(hex)]` formats an integer as hex, and bytes as hex truncated to their first and
+///    last 4 bytes
+///
 /// # Example
 ///
 /// ```ignore
@@ -170,6 +195,10 @@ pub fn derive_abi_codec(input: TokenStream) -> TokenStream {
 ///     arr_u8: [u8; 32],
 ///     arr_u16: [u16; 32],
 ///     v: Vec<u8>,
+///     #[ethdisplay(units = 18)]
+///     amount: U256,
+///     #[ethdisplay(hex)]
+///     data: Bytes,
 /// }
 /// let val = MyStruct {..};
 /// format!("{}", val);
@@ -206,6 +235,10 @@ pub fn derive_eth_display(input: TokenStream) -> TokenStream {
 /// - `indexed`: flag to mark a field as an indexed event input
 /// - `name`: override the name of an indexed event input, default is the rust field name
 ///
+/// Indexed `string` and `bytes` inputs are only stored as the keccak256 hash of their value, for
+/// these a `matches_<field>(value)` method is generated, which hashes `value` and compares it
+/// with the topic. This requires the `abi` attribute, since the field itself is a `H256`.
+///
 /// # Example
 /// ```ignore
 /// use ethers_contract::EthCall;
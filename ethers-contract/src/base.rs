@@ -181,7 +181,7 @@ impl BaseContract {
         decode_function_data_raw(function, bytes, false)
     }
 
-    fn get_from_signature(&self, signature: Selector) -> Result<&Function, AbiError> {
+    pub(crate) fn get_from_signature(&self, signature: Selector) -> Result<&Function, AbiError> {
         Ok(self
             .methods
             .get(&signature)
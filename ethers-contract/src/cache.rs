@@ -0,0 +1,153 @@
+use crate::{base::BaseContract, contract::ContractInstance};
+
+use ethers_core::{
+    abi::Abi,
+    types::{Address, H256},
+    utils::keccak256,
+};
+use ethers_providers::Middleware;
+
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// A registry of [`BaseContract`]s keyed by contract address and ABI hash.
+///
+/// Creating a [`ContractInstance`] with [`ContractInstance::new`] clones the ABI and rebuilds the
+/// method lookup table every time. Services that create many instances for the same contracts can
+/// use a `ContractCache` instead, so that all instances for the same address and ABI share a
+/// single parsed [`BaseContract`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use ethers_core::{abi::parse_abi, types::Address};
+/// # use ethers_providers::{Provider, Http};
+/// # use ethers_contract::{Contract, ContractCache};
+/// # use std::{convert::TryFrom, sync::Arc};
+/// # fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Arc::new(Provider::<Http>::try_from("http://localhost:8545")?);
+/// let abi = parse_abi(&["function totalSupply() external view returns (uint256)"])?;
+/// let address = "0x6B175474E89094C44Da98b954EedeAC495271d0F".parse::<Address>()?;
+///
+/// let cache = ContractCache::new();
+/// let a: Contract<_> = cache.contract(address, &abi, client.clone());
+/// let b: Contract<_> = cache.contract(address, &abi, client);
+/// assert_eq!(cache.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ContractCache {
+    contracts: RwLock<HashMap<(Address, H256), Arc<BaseContract>>>,
+}
+
+impl ContractCache {
+    /// Creates a new, empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the hash used to identify the given ABI in the cache, which is the keccak256 hash
+    /// of its JSON representation.
+    pub fn abi_hash(abi: &Abi) -> H256 {
+        let json = serde_json::to_vec(abi).expect("ABI is always serializable");
+        keccak256(json).into()
+    }
+
+    /// Returns the shared [`BaseContract`] for the given address and ABI, parsing and inserting
+    /// it if it is not yet cached.
+    pub fn base_contract(&self, address: Address, abi: &Abi) -> Arc<BaseContract> {
+        self.base_contract_with_hash(address, Self::abi_hash(abi), || abi.clone())
+    }
+
+    /// Same as [`Self::base_contract`], but with a precomputed [ABI hash](Self::abi_hash). The ABI
+    /// is only requested if there is no cached entry for the key.
+    pub fn base_contract_with_hash(
+        &self,
+        address: Address,
+        abi_hash: H256,
+        abi: impl FnOnce() -> Abi,
+    ) -> Arc<BaseContract> {
+        let key = (address, abi_hash);
+        if let Some(base) = self.contracts.read().unwrap().get(&key) {
+            return base.clone()
+        }
+        self.contracts
+            .write()
+            .unwrap()
+            .entry(key)
+            .or_insert_with(|| Arc::new(BaseContract::from(abi())))
+            .clone()
+    }
+
+    /// Returns a new [`ContractInstance`] for the given address and ABI which shares its
+    /// [`BaseContract`] with all other instances created by this cache for the same key.
+    pub fn contract<B, M>(&self, address: Address, abi: &Abi, client: B) -> ContractInstance<B, M>
+    where
+        B: Borrow<M>,
+        M: Middleware,
+    {
+        ContractInstance::from_shared(address, self.base_contract(address, abi), client)
+    }
+
+    /// Removes all cached entries for the given address, returning how many were removed.
+    pub fn remove(&self, address: Address) -> usize {
+        let mut contracts = self.contracts.write().unwrap();
+        let len = contracts.len();
+        contracts.retain(|(addr, _), _| *addr != address);
+        len - contracts.len()
+    }
+
+    /// Removes all cached entries
+    pub fn clear(&self) {
+        self.contracts.write().unwrap().clear();
+    }
+
+    /// Returns the number of cached entries
+    pub fn len(&self) -> usize {
+        self.contracts.read().unwrap().len()
+    }
+
+    /// Returns `true` if the cache has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Contract;
+    use ethers_core::abi::parse_abi;
+    use ethers_providers::Provider;
+
+    #[test]
+    fn shares_base_contract_per_address_and_abi() {
+        let (provider, _) = Provider::mocked();
+        let client = Arc::new(provider);
+        let abi = parse_abi(&["function totalSupply() external view returns (uint256)"]).unwrap();
+        let other = parse_abi(&["function owner() external view returns (address)"]).unwrap();
+        let cache = ContractCache::new();
+
+        let a: Contract<_> = cache.contract(Address::repeat_byte(1), &abi, client.clone());
+        let b: Contract<_> = cache.contract(Address::repeat_byte(1), &abi, client.clone());
+        assert_eq!(cache.len(), 1);
+        assert!(std::ptr::eq(&*a, &*b));
+
+        let c: Contract<_> = cache.contract(Address::repeat_byte(2), &abi, client.clone());
+        let d: Contract<_> = cache.contract(Address::repeat_byte(1), &other, client);
+        assert_eq!(cache.len(), 3);
+        assert!(!std::ptr::eq(&*a, &*c));
+        assert!(!std::ptr::eq(&*a, &*d));
+        assert_eq!(d.address(), Address::repeat_byte(1));
+        assert!(d.abi().function("owner").is_ok());
+
+        assert_eq!(cache.remove(Address::repeat_byte(1)), 2);
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
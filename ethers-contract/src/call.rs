@@ -1,21 +1,31 @@
 #![allow(clippy::return_self_not_must_use)]
 
-use super::base::{decode_function_data, AbiError};
+use super::{
+    base::{decode_function_data, AbiError, BaseContract},
+    error::ContractRevert,
+    trace::DecodedCallFrame,
+};
 use ethers_core::{
     abi::{AbiDecode, AbiEncode, Detokenize, Function, InvalidOutputType, Tokenizable},
     types::{
-        transaction::eip2718::TypedTransaction, Address, BlockId, Bytes, Selector,
+        transaction::{
+            eip2718::TypedTransaction,
+            eip2930::{AccessListWithGasUsed, Eip2930TransactionRequest},
+        },
+        Address, BlockId, BlockNumber, Bytes, GethDebugBuiltInTracerType, GethDebugTracerType,
+        GethDebugTracingCallOptions, GethTrace, GethTraceFrame, Selector, TransactionReceipt,
         TransactionRequest, U256,
     },
     utils::id,
 };
 use ethers_providers::{
-    call_raw::{CallBuilder, RawCall},
-    Middleware, PendingTransaction, ProviderError,
+    call_raw::{spoof, CallBuilder, RawCall},
+    interval, EscalationPolicy, Middleware, PendingTransaction, ProviderError, StreamExt,
 };
 
 use std::{
     borrow::{Borrow, Cow},
+    collections::HashMap,
     fmt::Debug,
     future::{Future, IntoFuture},
     marker::PhantomData,
@@ -70,6 +80,116 @@ pub enum ContractError<M: Middleware> {
     /// receipt
     #[error("Contract was not deployed")]
     ContractNotDeployed,
+
+    /// Thrown if a simulated call reverted, holding the revert data, see
+    /// [`FunctionCall::send_checked`]
+    #[error("Contract call reverted with data: {0}")]
+    Revert(Bytes),
+}
+
+impl<M: Middleware> ContractError<M>
+where
+    M::Error: 'static,
+{
+    /// Returns the revert data if this error was caused by a reverted call and the node included
+    /// the revert data in its error response.
+    ///
+    /// Middleware errors are searched for a [`ProviderError`] along their
+    /// [`source`](std::error::Error::source) chain.
+    pub fn as_revert_data(&self) -> Option<Bytes> {
+        match self {
+            ContractError::Revert(data) => Some(data.clone()),
+            ContractError::ProviderError(err) => err.as_revert_data(),
+            ContractError::MiddlewareError(err) => {
+                let mut next: Option<&(dyn std::error::Error + 'static)> = Some(err);
+                while let Some(err) = next {
+                    if let Some(err) = err.downcast_ref::<ProviderError>() {
+                        return err.as_revert_data()
+                    }
+                    next = err.source();
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes the revert data of a reverted call as `T`, see [`Self::as_revert_data`].
+    ///
+    /// Returns `None` if this error was not caused by a revert or the revert data is not one of
+    /// the errors represented by `T`.
+    ///
+    /// ```no_run
+    /// # use ethers_contract::{ContractCall, StringOrPanic};
+    /// # use ethers_core::types::U256;
+    /// # use ethers_providers::{Http, Provider};
+    /// # async fn foo(call: ContractCall<Provider<Http>, U256>) {
+    /// if let Err(err) = call.call().await {
+    ///     if let Some(StringOrPanic::RevertString(reason)) = err.as_revert::<StringOrPanic>() {
+    ///         println!("reverted: {reason}");
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn as_revert<T: ContractRevert>(&self) -> Option<T> {
+        T::decode_with_selector(&self.as_revert_data()?)
+    }
+}
+
+/// Configures how [`FunctionCall::send_with_escalation`] replaces a transaction that is not
+/// included in time.
+pub struct FeeEscalation {
+    policy: EscalationPolicy,
+    blocks: u64,
+    max_escalations: usize,
+}
+
+impl FeeEscalation {
+    /// Creates a new escalation with the given policy, which maps the originally filled fee and
+    /// the number of the escalation (starting at 1) to the new fee.
+    ///
+    /// By default, a replacement is sent after 3 blocks without inclusion, at most 3 times.
+    pub fn new(policy: impl Fn(U256, usize) -> U256 + Send + Sync + 'static) -> Self {
+        Self { policy: Box::new(policy), blocks: 3, max_escalations: 3 }
+    }
+
+    /// Sets the number of blocks to wait for inclusion before sending a replacement
+    pub fn blocks(mut self, blocks: u64) -> Self {
+        self.blocks = blocks;
+        self
+    }
+
+    /// Sets the maximum number of replacements to send
+    pub fn max_escalations(mut self, max_escalations: usize) -> Self {
+        self.max_escalations = max_escalations;
+        self
+    }
+
+    /// Bumps the fees of `tx` for the given escalation, based on the fees of `original`
+    fn escalate(&self, tx: &mut TypedTransaction, original: &TypedTransaction, escalation: usize) {
+        match (tx, original) {
+            (TypedTransaction::Eip1559(tx), TypedTransaction::Eip1559(original)) => {
+                tx.max_fee_per_gas =
+                    original.max_fee_per_gas.map(|fee| (self.policy)(fee, escalation));
+                tx.max_priority_fee_per_gas =
+                    original.max_priority_fee_per_gas.map(|fee| (self.policy)(fee, escalation));
+            }
+            (tx, original) => {
+                if let Some(gas_price) = original.gas_price() {
+                    tx.set_gas_price((self.policy)(gas_price, escalation));
+                }
+            }
+        }
+    }
+}
+
+impl Debug for FeeEscalation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FeeEscalation")
+            .field("blocks", &self.blocks)
+            .field("max_escalations", &self.max_escalations)
+            .finish_non_exhaustive()
+    }
 }
 
 /// `ContractCall` is a [`FunctionCall`] object with an [`std::sync::Arc`] middleware.
@@ -89,6 +209,8 @@ pub struct FunctionCall<B, M, D> {
     pub function: Function,
     /// Optional block number to be used when calculating the transaction's gas and nonce
     pub block: Option<BlockId>,
+    /// Optional state override set to be applied when executing the call via `eth_call`
+    pub state: Option<spoof::State>,
     pub(crate) client: B,
     pub(crate) datatype: PhantomData<D>,
     pub(crate) _m: PhantomData<M>,
@@ -103,6 +225,7 @@ where
             tx: self.tx.clone(),
             function: self.function.clone(),
             block: self.block,
+            state: self.state.clone(),
             client: self.client.clone(),
             datatype: self.datatype,
             _m: self._m,
@@ -158,6 +281,43 @@ where
         self.block = Some(block.into());
         self
     }
+
+    /// Sets the [state override set](https://geth.ethereum.org/docs/rpc/ns-eth#3-object---state-override-set)
+    /// which is applied when the call is executed via [`call`](Self::call) or
+    /// [`call_raw`](Self::call_raw).
+    ///
+    /// This replaces any previously set overrides, see [`Self::state_override`] to modify the
+    /// overrides of a single account instead.
+    ///
+    /// Note that not all client implementations will support this as a parameter.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ethers_core::types::{Address, U256};
+    /// # use ethers_contract::ContractCall;
+    /// # use ethers_providers::{Middleware, call_raw::spoof};
+    /// # async fn foo<M: Middleware + 'static>(call: ContractCall<M, U256>, holder: Address) -> Result<(), Box<dyn std::error::Error>> {
+    /// // pretend `holder` has 100 ETH while executing the call
+    /// let state = spoof::balance(holder, U256::exp10(20));
+    /// let res = call.from(holder).state(state).call().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn state(mut self, state: spoof::State) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Modifies the state override of the account at `address` with the given closure,
+    /// inserting an empty override for the account first if none exists.
+    pub fn state_override<F>(mut self, address: Address, f: F) -> Self
+    where
+        F: FnOnce(&mut spoof::Account),
+    {
+        f(self.state.get_or_insert_with(spoof::state).account(address));
+        self
+    }
 }
 
 impl<B, M, D> FunctionCall<B, M, D>
@@ -180,6 +340,37 @@ where
             .map_err(ContractError::MiddlewareError)
     }
 
+    /// Generates an access list for the underlying transaction via `eth_createAccessList`,
+    /// attaches it to the transaction and sets the `gas` field to the gas estimated with the
+    /// access list applied.
+    ///
+    /// The gas used reported by `eth_createAccessList` is not a usable gas limit, since refunds
+    /// and the 63/64 rule of calls make transactions need more gas than they use.
+    ///
+    /// Legacy transactions cannot carry an access list, so they are converted to EIP-2930
+    /// transactions.
+    pub async fn with_access_list_from_node(mut self) -> Result<Self, ContractError<M>> {
+        let AccessListWithGasUsed { access_list, .. } = self
+            .client
+            .borrow()
+            .create_access_list(&self.tx, self.block)
+            .await
+            .map_err(ContractError::MiddlewareError)?;
+
+        self.tx = match self.tx {
+            TypedTransaction::Legacy(inner) => {
+                TypedTransaction::Eip2930(Eip2930TransactionRequest::new(inner, access_list))
+            }
+            mut other => {
+                other.set_access_list(access_list);
+                other
+            }
+        };
+        let gas = self.estimate_gas().await?;
+        self.tx.set_gas(gas);
+        Ok(self)
+    }
+
     /// Queries the blockchain via an `eth_call` for the provided transaction.
     ///
     /// If executed on a non-state mutating smart contract function (i.e. `view`, `pure`)
@@ -188,14 +379,20 @@ where
     /// If executed on a mutating smart contract function, it will do a "dry run" of the call
     /// and return the return type of the transaction without mutating the state
     ///
+    /// If a [state override set](Self::state) was configured, the call is executed directly
+    /// against the underlying provider with the given overrides applied.
+    ///
     /// Note: this function _does not_ send a transaction from your account
     pub async fn call(&self) -> Result<D, ContractError<M>> {
-        let bytes = self
-            .client
-            .borrow()
-            .call(&self.tx, self.block)
-            .await
-            .map_err(ContractError::MiddlewareError)?;
+        let bytes = if self.state.is_some() {
+            self.call_raw_bytes().await.map_err(ContractError::ProviderError)?
+        } else {
+            self.client
+                .borrow()
+                .call(&self.tx, self.block)
+                .await
+                .map_err(ContractError::MiddlewareError)?
+        };
 
         // decode output
         let data = decode_function_data(&self.function, &bytes, false)?;
@@ -226,12 +423,43 @@ where
     ///
     /// Note: this function _does not_ send a transaction from your account
     pub fn call_raw_bytes(&self) -> CallBuilder<'_, M::Provider> {
-        let call = self.client.borrow().provider().call_raw(&self.tx);
+        let mut call = self.client.borrow().provider().call_raw(&self.tx);
         if let Some(block) = self.block {
-            call.block(block)
-        } else {
-            call
+            call = call.block(block);
+        }
+        if let Some(ref state) = self.state {
+            call = call.state(state);
         }
+        call
+    }
+
+    /// Executes the call with `debug_traceCall` and the `callTracer`, and returns the resulting
+    /// call tree with the calldata, return data and revert reasons of every call decoded against
+    /// the ABIs in `abis`, keyed by contract address.
+    ///
+    /// The top level call is decoded with this call's function, unless the contract is in `abis`.
+    ///
+    /// Note: this function _does not_ send a transaction from your account
+    pub async fn trace(
+        &self,
+        abis: &HashMap<Address, BaseContract>,
+    ) -> Result<DecodedCallFrame, ContractError<M>> {
+        let mut opts = GethDebugTracingCallOptions::default();
+        opts.tracing_options.tracer =
+            Some(GethDebugTracerType::BuiltInTracer(GethDebugBuiltInTracerType::CallTracer));
+        let trace = self
+            .client
+            .borrow()
+            .debug_trace_call(self.tx.clone(), self.block, opts)
+            .await
+            .map_err(ContractError::MiddlewareError)?;
+        let frame = match trace {
+            GethTrace::Known(GethTraceFrame::CallTracer(frame)) => frame,
+            other => serde_json::to_value(other)
+                .and_then(serde_json::from_value)
+                .map_err(|err| ContractError::ProviderError(err.into()))?,
+        };
+        Ok(DecodedCallFrame::decode(frame, abis, Some(&self.function)))
     }
 
     /// Signs and broadcasts the provided transaction
@@ -242,6 +470,128 @@ where
             .await
             .map_err(ContractError::MiddlewareError)
     }
+
+    /// Simulates the transaction with `eth_call` at the pending block, and only signs and
+    /// broadcasts it if the simulation succeeds.
+    ///
+    /// If the simulation reverts and the node included the revert data in its response, this
+    /// returns a [`ContractError::Revert`] which can be decoded with [`ContractError::as_revert`].
+    ///
+    /// ```no_run
+    /// # use ethers_contract::{ContractCall, StringOrPanic};
+    /// # use ethers_providers::{Http, Provider};
+    /// # async fn foo(call: ContractCall<Provider<Http>, ()>) {
+    /// match call.send_checked().await {
+    ///     Ok(pending) => println!("sent {:?}", *pending),
+    ///     Err(err) => match err.as_revert::<StringOrPanic>() {
+    ///         Some(revert) => println!("not sent, would revert with: {revert}"),
+    ///         None => println!("not sent: {err}"),
+    ///     },
+    /// }
+    /// # }
+    /// ```
+    pub async fn send_checked(
+        &self,
+    ) -> Result<PendingTransaction<'_, M::Provider>, ContractError<M>>
+    where
+        M::Error: 'static,
+    {
+        let simulation = self.client.borrow().call(&self.tx, Some(BlockNumber::Pending.into()));
+        if let Err(err) = simulation.await {
+            let err = ContractError::MiddlewareError(err);
+            return Err(err.as_revert_data().map(ContractError::Revert).unwrap_or(err))
+        }
+        self.send().await
+    }
+
+    /// Signs and broadcasts the provided transaction, and resends it with the same nonce and
+    /// fees bumped according to `escalation` every time it is not included within the configured
+    /// number of blocks.
+    ///
+    /// Returns the receipt of whichever of the sent transactions was included. If broadcasting a
+    /// replacement fails, e.g. because an earlier transaction was included in the meantime, no
+    /// further replacements are sent and this keeps waiting for the already sent transactions.
+    ///
+    /// Transactions without a nonce get the pending nonce of their sender, or of the default
+    /// sender of the middleware, e.g. its signer. Fails with
+    /// [`ProviderError::SignerUnavailable`] if there is neither.
+    ///
+    /// ```no_run
+    /// # use ethers_contract::{ContractCall, FeeEscalation};
+    /// # use ethers_providers::{Http, Provider};
+    /// # async fn foo(call: ContractCall<Provider<Http>, ()>) -> Result<(), Box<dyn std::error::Error>> {
+    /// // bump fees by 12.5% after every 2 blocks without inclusion
+    /// let escalation = FeeEscalation::new(|fee, n| fee * 1125u64.pow(n as u32) / 1000u64.pow(n as u32))
+    ///     .blocks(2);
+    /// let receipt = call.send_with_escalation(escalation).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_with_escalation(
+        &self,
+        escalation: FeeEscalation,
+    ) -> Result<TransactionReceipt, ContractError<M>> {
+        let client = self.client.borrow();
+
+        // fill the transaction once, so that all replacements share the nonce and gas limit
+        let mut original = self.tx.clone();
+        client
+            .fill_transaction(&mut original, self.block)
+            .await
+            .map_err(ContractError::MiddlewareError)?;
+        if original.nonce().is_none() {
+            // the nonce of another account would make the replacements fail or replace nothing
+            let from = original
+                .from()
+                .copied()
+                .or_else(|| client.default_sender())
+                .ok_or(ContractError::ProviderError(ProviderError::SignerUnavailable))?;
+            original.set_from(from);
+            let nonce = client
+                .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                .await
+                .map_err(ContractError::MiddlewareError)?;
+            original.set_nonce(nonce);
+        }
+
+        let mut tx = original.clone();
+        let mut sent = Vec::new();
+        let mut escalations = 0;
+        loop {
+            match client.send_transaction(tx.clone(), self.block).await {
+                Ok(pending) => sent.push(*pending),
+                Err(err) if sent.is_empty() => return Err(ContractError::MiddlewareError(err)),
+                // keep waiting for the transactions that are already in flight
+                Err(_) => escalations = escalation.max_escalations,
+            }
+            let sent_at =
+                client.get_block_number().await.map_err(ContractError::MiddlewareError)?;
+
+            let mut ticks = interval(client.provider().get_interval());
+            loop {
+                ticks.next().await;
+                for tx_hash in sent.iter().rev() {
+                    if let Some(receipt) = client
+                        .get_transaction_receipt(*tx_hash)
+                        .await
+                        .map_err(ContractError::MiddlewareError)?
+                    {
+                        return Ok(receipt)
+                    }
+                }
+                if escalations < escalation.max_escalations {
+                    let block =
+                        client.get_block_number().await.map_err(ContractError::MiddlewareError)?;
+                    if block >= sent_at + escalation.blocks {
+                        break
+                    }
+                }
+            }
+
+            escalations += 1;
+            escalation.escalate(&mut tx, &original, escalations);
+        }
+    }
 }
 
 /// [`FunctionCall`] can be turned into [`Future`] automatically with `.await`.
@@ -6,10 +6,10 @@ use crate::{
 };
 use ethers_core::{
     abi::{Abi, Detokenize, Error, EventExt, Function, Tokenize},
-    types::{Address, Filter, Selector, ValueOrArray},
+    types::{Address, Bytes, Filter, Selector, ValueOrArray},
 };
 use ethers_providers::Middleware;
-use std::{borrow::Borrow, fmt::Debug, marker::PhantomData, sync::Arc};
+use std::{borrow::Borrow, convert::TryInto, fmt::Debug, marker::PhantomData, sync::Arc};
 
 #[cfg(not(feature = "legacy"))]
 use ethers_core::types::Eip1559TransactionRequest;
@@ -177,7 +177,7 @@ pub type Contract<M> = ContractInstance<std::sync::Arc<M>, M>;
 #[derive(Debug)]
 pub struct ContractInstance<B, M> {
     address: Address,
-    base_contract: BaseContract,
+    base_contract: Arc<BaseContract>,
     client: B,
     _m: PhantomData<M>,
 }
@@ -260,7 +260,20 @@ where
 {
     /// Creates a new contract from the provided client, abi and address
     pub fn new(address: impl Into<Address>, abi: impl Into<BaseContract>, client: B) -> Self {
-        Self { base_contract: abi.into(), client, address: address.into(), _m: PhantomData }
+        Self::from_shared(address, Arc::new(abi.into()), client)
+    }
+
+    /// Creates a new contract from the provided client, address and an already shared
+    /// [`BaseContract`], without cloning the ABI.
+    ///
+    /// See [`ContractCache`](crate::ContractCache) for reusing the same `BaseContract` across many
+    /// instances.
+    pub fn from_shared(
+        address: impl Into<Address>,
+        base_contract: Arc<BaseContract>,
+        client: B,
+    ) -> Self {
+        Self { base_contract, client, address: address.into(), _m: PhantomData }
     }
 
     /// Returns a new contract instance using the provided client
@@ -329,7 +342,14 @@ where
         args: T,
     ) -> Result<FunctionCall<B, M, D>, AbiError> {
         let data = encode_function_data(function, args)?;
+        Ok(self.method_func_with_data(function, data))
+    }
 
+    fn method_func_with_data<D: Detokenize>(
+        &self,
+        function: &Function,
+        data: Bytes,
+    ) -> FunctionCall<B, M, D> {
         #[cfg(feature = "legacy")]
         let tx = TransactionRequest {
             to: Some(self.address.into()),
@@ -345,14 +365,15 @@ where
 
         let tx = tx.into();
 
-        Ok(FunctionCall {
+        FunctionCall {
             tx,
             client: self.client.clone(),
             block: None,
+            state: None,
             function: function.to_owned(),
             datatype: PhantomData,
             _m: self._m,
-        })
+        }
     }
 
     /// Returns a transaction builder for the selected function signature. This should be
@@ -384,6 +405,26 @@ where
         self.method_func(function, args)
     }
 
+    /// Returns a transaction builder for already ABI encoded calldata, e.g. produced by another
+    /// tool. The function is looked up by the calldata's selector, so that the output of a call is
+    /// still decoded into `D`.
+    pub fn method_raw<D: Detokenize>(
+        &self,
+        calldata: Bytes,
+    ) -> Result<FunctionCall<B, M, D>, AbiError> {
+        let signature: Selector = calldata
+            .get(..4)
+            .and_then(|selector| selector.try_into().ok())
+            .ok_or(AbiError::WrongSelector)?;
+        let function = self
+            .base_contract
+            .methods
+            .get(&signature)
+            .map(|(name, index)| &self.base_contract.abi.functions[name][*index])
+            .ok_or_else(|| Error::InvalidName(hex::encode(signature)))?;
+        Ok(self.method_func_with_data(function, calldata))
+    }
+
     /// Returns a new contract instance at `address`.
     ///
     /// Clones `self` internally
@@ -1,9 +1,9 @@
 use ethers_core::{
-    abi::{AbiDecode, AbiEncode, Tokenizable},
-    types::Selector,
+    abi::{AbiDecode, AbiEncode, AbiError, Tokenizable},
+    types::{Selector, U256},
     utils::id,
 };
-use std::borrow::Cow;
+use std::{borrow::Cow, convert::TryInto, fmt};
 
 /// A helper trait for types that represents a custom error type
 pub trait EthError: Tokenizable + AbiDecode + AbiEncode + Send + Sync {
@@ -18,3 +18,86 @@ pub trait EthError: Tokenizable + AbiDecode + AbiEncode + Send + Sync {
         id(Self::abi_signature())
     }
 }
+
+/// A helper trait for types that represent the revert data of a contract call.
+///
+/// This is implemented for every [`EthError`], for the errors enum generated by `abigen!` and for
+/// [`StringOrPanic`], see also [`ContractError::as_revert`](crate::ContractError::as_revert).
+pub trait ContractRevert: AbiDecode + AbiEncode + Send + Sync {
+    /// Decodes the revert data into this type, returns `None` if the data does not start with a
+    /// selector known to this type or can't be decoded.
+    fn decode_with_selector(data: &[u8]) -> Option<Self> {
+        let selector = data.get(..4)?.try_into().ok()?;
+        if !Self::valid_selector(selector) {
+            return None
+        }
+        Self::decode(data).ok()
+    }
+
+    /// Returns `true` if the selector belongs to one of the errors represented by this type
+    fn valid_selector(selector: Selector) -> bool;
+}
+
+impl<T: EthError> ContractRevert for T {
+    fn valid_selector(selector: Selector) -> bool {
+        selector == T::selector()
+    }
+}
+
+/// The selector of `Error(string)`
+const REVERT_STRING_SELECTOR: Selector = [0x08, 0xc3, 0x79, 0xa0];
+
+/// The selector of `Panic(uint256)`
+const PANIC_SELECTOR: Selector = [0x4e, 0x48, 0x7b, 0x71];
+
+/// The revert data emitted by Solidity itself when a call fails without a custom error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StringOrPanic {
+    /// Emitted as `Error(string)` by `require(cond, reason)` and `revert(reason)`
+    RevertString(String),
+    /// Emitted as `Panic(uint256)` on failed asserts, arithmetic overflows, out-of-bounds
+    /// accesses etc., with the panic code as defined by Solidity
+    Panic(U256),
+}
+
+impl AbiDecode for StringOrPanic {
+    fn decode(bytes: impl AsRef<[u8]>) -> Result<Self, AbiError> {
+        let bytes = bytes.as_ref();
+        if bytes.len() < 4 {
+            return Err(AbiError::WrongSelector)
+        }
+        let (selector, data) = bytes.split_at(4);
+        if selector == REVERT_STRING_SELECTOR {
+            Ok(StringOrPanic::RevertString(String::decode(data)?))
+        } else if selector == PANIC_SELECTOR {
+            Ok(StringOrPanic::Panic(U256::decode(data)?))
+        } else {
+            Err(AbiError::WrongSelector)
+        }
+    }
+}
+
+impl AbiEncode for StringOrPanic {
+    fn encode(self) -> Vec<u8> {
+        let (selector, data) = match self {
+            StringOrPanic::RevertString(reason) => (REVERT_STRING_SELECTOR, reason.encode()),
+            StringOrPanic::Panic(code) => (PANIC_SELECTOR, code.encode()),
+        };
+        selector.iter().copied().chain(data).collect()
+    }
+}
+
+impl ContractRevert for StringOrPanic {
+    fn valid_selector(selector: Selector) -> bool {
+        selector == REVERT_STRING_SELECTOR || selector == PANIC_SELECTOR
+    }
+}
+
+impl fmt::Display for StringOrPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StringOrPanic::RevertString(reason) => f.write_str(reason),
+            StringOrPanic::Panic(code) => write!(f, "panic code {code:#x}"),
+        }
+    }
+}
@@ -1,11 +1,18 @@
 #![allow(clippy::return_self_not_must_use)]
 
-use crate::{log::LogMeta, stream::EventStream, ContractError, EthLogDecode};
+use crate::{
+    log::LogMeta,
+    stream::{BoxEventStream, EventStream},
+    ContractError, EthLogDecode,
+};
 use ethers_core::{
     abi::{Address, Detokenize, Error as AbiError, RawLog},
-    types::{BlockNumber, Filter, Log, Topic, ValueOrArray, H256},
+    types::{BlockNumber, Filter, FilterBlockOption, Log, Topic, ValueOrArray, H256, U256, U64},
+};
+use ethers_providers::{
+    FilterWatcher, LogQueryError, Middleware, PubsubClient, SubscribeOrPoll, SubscriptionStream,
 };
-use ethers_providers::{FilterWatcher, Middleware, PubsubClient, SubscriptionStream};
+use futures_util::{future::Either, stream::StreamExt};
 use std::{
     borrow::{Borrow, Cow},
     marker::PhantomData,
@@ -133,6 +140,13 @@ where
         self.filter = self.filter.address(address);
         self
     }
+
+    /// Sets the filter's addresses, replacing the address of the contract this event was created
+    /// from, so that the logs of many deployments of the same contract can be queried or streamed
+    /// at once.
+    pub fn addresses(self, addresses: impl IntoIterator<Item = Address>) -> Self {
+        self.address(ValueOrArray::Array(addresses.into_iter().collect()))
+    }
 }
 
 impl<B, M, D> Event<B, M, D>
@@ -219,6 +233,129 @@ where
             }),
         ))
     }
+
+    /// Returns a stream that first yields all historical events starting at `block`, fetched in
+    /// pages of `page_size` blocks, and then continues with live events, via `eth_subscribe` if
+    /// the transport supports it and by polling a filter otherwise.
+    ///
+    /// The live stream is installed before the historical events are fetched, so no event is
+    /// missed in between. Live events which have already been yielded as part of the historical
+    /// events are skipped, based on their block number and log index.
+    pub async fn stream_from<T: Into<BlockNumber>>(
+        &self,
+        block: T,
+        page_size: u64,
+    ) -> Result<BoxEventStream<'_, D, ContractError<M>>, ContractError<M>> {
+        let provider = self.provider.borrow();
+        let live = provider
+            .subscribe_or_watch_logs(&self.filter.clone().select(FilterBlockOption::default()))
+            .await
+            .map_err(ContractError::MiddlewareError)?;
+        let historical_filter = self
+            .filter
+            .clone()
+            .select(FilterBlockOption::Range { from_block: Some(block.into()), to_block: None });
+        let historical = provider.get_logs_paginated(&historical_filter, page_size);
+
+        let logs = historical.map(Either::Left).chain(live.map(Either::Right)).scan(
+            None,
+            |watermark: &mut Option<(U64, U256)>, log| {
+                let next = match log {
+                    Either::Left(Ok(log)) => {
+                        if let (Some(block), Some(index)) = (log.block_number, log.log_index) {
+                            *watermark = Some((block, index));
+                        }
+                        Some(Ok(log))
+                    }
+                    Either::Left(Err(
+                        LogQueryError::LoadLastBlockError(err) | LogQueryError::LoadLogsError(err),
+                    )) => Some(Err(ContractError::ProviderError(err))),
+                    Either::Right(log) => {
+                        let seen = match (*watermark, log.block_number, log.log_index) {
+                            (Some(last), Some(block), Some(index)) => (block, index) <= last,
+                            _ => false,
+                        };
+                        (!seen).then(|| Ok(log))
+                    }
+                };
+                futures_util::future::ready(Some(next))
+            },
+        );
+        Ok(Box::pin(
+            logs.filter_map(futures_util::future::ready)
+                .map(|log| log.and_then(|log| Ok(parse_log(log)?))),
+        ))
+    }
+
+    /// As [`Self::stream`], but also yields the undecoded [`Log`] alongside each decoded event.
+    pub async fn stream_with_raw(
+        &self,
+    ) -> Result<
+        // Wraps the FilterWatcher with a mapping to the event
+        EventStream<'_, FilterWatcher<'_, M::Provider, Log>, (Log, D), ContractError<M>>,
+        ContractError<M>,
+    > {
+        let filter = self
+            .provider
+            .borrow()
+            .watch(&self.filter)
+            .await
+            .map_err(ContractError::MiddlewareError)?;
+        Ok(EventStream::new(
+            filter.id,
+            filter,
+            Box::new(move |log| {
+                let event =
+                    D::decode_log(&RawLog { topics: log.topics.clone(), data: log.data.to_vec() })?;
+                Ok((log, event))
+            }),
+        ))
+    }
+
+    /// Returns a subscription for the event if the provider's transport supports
+    /// `eth_subscribe`, and falls back to polling a filter like [`Self::stream`] otherwise.
+    ///
+    /// Unlike [`Self::subscribe`], this does not require a `PubsubClient`, so the same code works
+    /// for both Websocket and HTTP providers.
+    pub async fn subscribe_or_poll(
+        &self,
+    ) -> Result<
+        // Wraps the SubscribeOrPoll stream with a mapping to the event
+        EventStream<'_, SubscribeOrPoll<'_, M::Provider, Log>, D, ContractError<M>>,
+        ContractError<M>,
+    > {
+        let filter = self
+            .provider
+            .borrow()
+            .subscribe_or_watch_logs(&self.filter)
+            .await
+            .map_err(ContractError::MiddlewareError)?;
+        Ok(EventStream::new(filter.id(), filter, Box::new(move |log| Ok(parse_log(log)?))))
+    }
+
+    /// As [`Self::subscribe_or_poll`], but does not discard [`Log`] metadata.
+    pub async fn subscribe_or_poll_with_meta(
+        &self,
+    ) -> Result<
+        // Wraps the SubscribeOrPoll stream with a mapping to the event
+        EventStream<'_, SubscribeOrPoll<'_, M::Provider, Log>, (D, LogMeta), ContractError<M>>,
+        ContractError<M>,
+    > {
+        let filter = self
+            .provider
+            .borrow()
+            .subscribe_or_watch_logs(&self.filter)
+            .await
+            .map_err(ContractError::MiddlewareError)?;
+        Ok(EventStream::new(
+            filter.id(),
+            filter,
+            Box::new(move |log| {
+                let meta = LogMeta::from(&log);
+                Ok((parse_log(log)?, meta))
+            }),
+        ))
+    }
 }
 
 impl<B, M, D> Event<B, M, D>
@@ -1,11 +1,12 @@
 use crate::{ContractError, ContractInstance};
 
 use ethers_core::{
-    abi::{Abi, Token, Tokenize},
+    abi::{self, Abi, Token, Tokenize},
     types::{
-        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, NameOrAddress,
-        TransactionReceipt, TransactionRequest, U256, U64,
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, Log, NameOrAddress,
+        TransactionReceipt, TransactionRequest, H256, U256, U64,
     },
+    utils::{get_create3_address, id, keccak256},
 };
 use ethers_providers::{
     call_raw::{CallBuilder, RawCall},
@@ -31,6 +32,34 @@ pub type ContractDeployer<M, C> = ContractDeploymentTx<Arc<M>, M, C>;
 /// For full usage docs, see [`DeploymentTxFactory`].
 pub type ContractFactory<M> = DeploymentTxFactory<Arc<M>, M>;
 
+/// The outcome of a successful contract deployment, see [`Deployer::send_with_details`].
+#[derive(Debug, Clone)]
+pub struct DeploymentDetails<C> {
+    /// The deployed contract
+    pub contract: C,
+    /// The receipt of the deployment transaction
+    pub receipt: TransactionReceipt,
+    /// The address of the deployed contract
+    pub address: Address,
+    /// The account that sent the deployment transaction
+    pub deployer: Address,
+    /// The logs emitted by the deployed contract while executing its constructor
+    pub constructor_events: Vec<Log>,
+}
+
+impl<C> DeploymentDetails<C> {
+    /// Converts the deployed contract into another type, keeping all other details
+    pub fn map_contract<T>(self, f: impl FnOnce(C) -> T) -> DeploymentDetails<T> {
+        DeploymentDetails {
+            contract: f(self.contract),
+            receipt: self.receipt,
+            address: self.address,
+            deployer: self.deployer,
+            constructor_events: self.constructor_events,
+        }
+    }
+}
+
 /// Helper which manages the deployment transaction of a smart contract. It
 /// wraps a deployment transaction, and retrieves the contract address output
 /// by it.
@@ -138,6 +167,13 @@ where
         self
     }
 
+    /// Deploys the contract through the CREATE3 `factory` with the given `sender` and `salt`, see
+    /// [`Deployer::create3`]
+    pub fn create3(mut self, factory: Address, sender: Address, salt: H256) -> Self {
+        self.deployer = self.deployer.create3(factory, sender, salt);
+        self
+    }
+
     /// Dry runs the deployment of the contract
     ///
     /// Note: this function _does not_ send a transaction from your account
@@ -171,6 +207,14 @@ where
         Ok((C::from(contract), receipt))
     }
 
+    /// Broadcasts the contract deployment transaction and after waiting for it to
+    /// be sufficiently confirmed (default: 1), it returns the [`DeploymentDetails`] with a new
+    /// instance of the contract type at the deployed contract's address.
+    pub async fn send_with_details(self) -> Result<DeploymentDetails<C>, ContractError<M>> {
+        let details = self.deployer.send_with_details().await?;
+        Ok(details.map_contract(C::from))
+    }
+
     /// Returns a reference to the deployer's ABI
     pub fn abi(&self) -> &Abi {
         self.deployer.abi()
@@ -192,6 +236,8 @@ pub struct Deployer<B, M> {
     client: B,
     confs: usize,
     block: BlockNumber,
+    /// The address of the contract, if deployed via [`Deployer::create3`]
+    create3: Option<Address>,
     _m: PhantomData<M>,
 }
 
@@ -206,6 +252,7 @@ where
             client: self.client.clone(),
             confs: self.confs,
             block: self.block,
+            create3: self.create3,
             _m: PhantomData,
         }
     }
@@ -239,6 +286,34 @@ where
         self
     }
 
+    /// Deploys the contract through a CREATE3 factory instead of a contract creation transaction,
+    /// so that its address only depends on the sender and `salt`, not on the contract's init code.
+    ///
+    /// This turns the deployment transaction into a call of `deploy(bytes32 salt, bytes
+    /// creationCode)` on `factory`, which must derive the CREATE3 salt as
+    /// `keccak256(abi.encodePacked(msg.sender, salt))`, like the widely deployed
+    /// [CREATE3Factory](https://github.com/ZeframLou/create3-factory). The resulting address is
+    /// `get_create3_address(factory, keccak256(sender ++ salt))`, see
+    /// [`get_create3_address`](ethers_core::utils::get_create3_address).
+    ///
+    /// `sender` is the `msg.sender` of the factory: the sender of the transaction if it calls the
+    /// factory directly, or e.g. the Safe or forwarder if the transaction is relayed. Sending the
+    /// deployment fails with [`ContractError::ContractNotDeployed`] if there is no code at the
+    /// resulting address afterwards.
+    ///
+    /// This must be called after all changes to the deployment transaction's `data`.
+    pub fn create3(mut self, factory: Address, sender: Address, salt: H256) -> Self {
+        let init_code = self.tx.data().cloned().unwrap_or_default();
+        let args =
+            abi::encode(&[Token::FixedBytes(salt.0.to_vec()), Token::Bytes(init_code.to_vec())]);
+        let data: Vec<u8> = id("deploy(bytes32,bytes)").iter().copied().chain(args).collect();
+        self.tx.set_to(factory);
+        self.tx.set_data(data.into());
+        let salt = keccak256([sender.as_bytes(), salt.as_bytes()].concat());
+        self.create3 = Some(get_create3_address(factory, salt));
+        self
+    }
+
     /// Dry runs the deployment of the contract
     ///
     /// Note: this function _does not_ send a transaction from your account
@@ -290,12 +365,51 @@ where
             .await
             .map_err(|_| ContractError::ContractNotDeployed)?
             .ok_or(ContractError::ContractNotDeployed)?;
-        let address = receipt.contract_address.ok_or(ContractError::ContractNotDeployed)?;
+        let address = match self.create3 {
+            Some(address) => {
+                if receipt.status == Some(0u64.into()) {
+                    return Err(ContractError::ContractNotDeployed)
+                }
+                let block = receipt.block_number.map(|number| BlockNumber::Number(number).into());
+                let code = self
+                    .client
+                    .borrow()
+                    .get_code(address, block)
+                    .await
+                    .map_err(ContractError::MiddlewareError)?;
+                if code.is_empty() {
+                    return Err(ContractError::ContractNotDeployed)
+                }
+                address
+            }
+            None => receipt.contract_address.ok_or(ContractError::ContractNotDeployed)?,
+        };
 
         let contract = ContractInstance::new(address, self.abi.clone(), self.client.clone());
         Ok((contract, receipt))
     }
 
+    /// Broadcasts the contract deployment transaction and after waiting for it to
+    /// be sufficiently confirmed (default: 1), it returns the [`DeploymentDetails`] with the
+    /// [`Contract`](crate::Contract) struct at the deployed contract's address, the
+    /// corresponding [`TransactionReceipt`](ethers_core::types::TransactionReceipt) and the
+    /// events emitted by the contract's constructor.
+    pub async fn send_with_details(
+        self,
+    ) -> Result<DeploymentDetails<ContractInstance<B, M>>, ContractError<M>> {
+        let (contract, receipt) = self.send_with_receipt().await?;
+        let address = contract.address();
+        let constructor_events =
+            receipt.logs.iter().filter(|log| log.address == address).cloned().collect();
+        Ok(DeploymentDetails {
+            contract,
+            address,
+            deployer: receipt.from,
+            constructor_events,
+            receipt,
+        })
+    }
+
     /// Returns a reference to the deployer's ABI
     pub fn abi(&self) -> &Abi {
         &self.abi
@@ -410,6 +524,7 @@ where
             tx,
             confs: 1,
             block: BlockNumber::Latest,
+            create3: None,
             _m: PhantomData,
         })
     }
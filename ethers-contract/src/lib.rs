@@ -8,14 +8,22 @@ pub use contract::{Contract, ContractInstance};
 mod base;
 pub use base::{decode_function_data, encode_function_data, AbiError, BaseContract};
 
+mod cache;
+pub use cache::ContractCache;
+
+mod trace;
+pub use trace::DecodedCallFrame;
+
 mod call;
-pub use call::{ContractCall, ContractError, EthCall, FunctionCall};
+pub use call::{ContractCall, ContractError, EthCall, FeeEscalation, FunctionCall};
 
 mod error;
-pub use error::EthError;
+pub use error::{ContractRevert, EthError, StringOrPanic};
 
 mod factory;
-pub use factory::{ContractDeployer, ContractDeploymentTx, ContractFactory, DeploymentTxFactory};
+pub use factory::{
+    ContractDeployer, ContractDeploymentTx, ContractFactory, DeploymentDetails, DeploymentTxFactory,
+};
 
 mod event;
 pub use event::{EthEvent, Event};
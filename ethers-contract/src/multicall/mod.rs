@@ -5,6 +5,7 @@ use ethers_core::{
 };
 
 use ethers_providers::{Middleware, PendingTransaction};
+use futures_util::stream::{self, StreamExt};
 use std::{convert::TryFrom, fmt, sync::Arc};
 
 pub mod multicall_contract;
@@ -99,6 +100,12 @@ pub enum MulticallError<M: Middleware> {
 
     #[error("Illegal revert: Multicall2 call reverted when it wasn't allowed to.")]
     IllegalRevert,
+
+    #[error("Call {0} of the multicall reverted and has no return value.")]
+    CallReverted(usize),
+
+    #[error("Cannot decode the results of {0} calls as a tuple, the maximum is 16.")]
+    TooManyCalls(usize),
 }
 
 pub type Result<T, M> = std::result::Result<T, MulticallError<M>>;
@@ -616,12 +623,8 @@ impl<M: Middleware> Multicall<M> {
     /// # Errors
     ///
     /// Returns a [`MulticallError`] if there are any errors in the RPC call or while detokenizing
-    /// the tokens back to the expected return type.
-    ///
-    /// # Panics
-    ///
-    /// If more than the maximum number of supported calls are added (16). The maximum limit is
-    /// constrained due to tokenization/detokenization support for tuples.
+    /// the tokens back to the expected return type, and [`MulticallError::TooManyCalls`] if more
+    /// than 16 calls were added, the maximum that tuples can be detokenized into.
     ///
     /// # Examples
     ///
@@ -649,8 +652,62 @@ impl<M: Middleware> Multicall<M> {
     /// # }
     /// ```
     pub async fn call<D: Detokenize>(&self) -> Result<D, M> {
-        assert!(self.calls.len() <= 16, "Cannot decode more than 16 calls");
+        if self.calls.len() > 16 {
+            return Err(MulticallError::TooManyCalls(self.calls.len()))
+        }
+        let tokens = self.call_raw().await?;
+        let tokens = vec![Token::Tuple(tokens)];
+        let data = D::from_tokens(tokens).map_err(ContractError::DetokenizationError)?;
+        Ok(data)
+    }
+
+    /// Queries the Ethereum blockchain using `eth_call`, but via the Multicall contract, and
+    /// returns the calls' results as a typed tuple.
+    ///
+    /// Unlike [`Self::call`], the results are not wrapped with their success status for version 2
+    /// and above, so the return type is the same for every version.
+    ///
+    /// Note: this method _does not_ send a transaction from your account.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MulticallError`] if there are any errors in the RPC call or while detokenizing
+    /// the tokens back to the expected return type, [`MulticallError::CallReverted`] if a call
+    /// that was allowed to fail reverted, and [`MulticallError::TooManyCalls`] if more than 16
+    /// calls were added.
+    ///
+    /// # Examples
+    ///
+    /// The return type must be annotated while calling this method:
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use ethers_core::types::{U256, Address};
+    /// # use ethers_providers::{Provider, Http};
+    /// # use ethers_contract::Multicall;
+    /// # use std::convert::TryFrom;
+    /// #
+    /// # let client = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// #
+    /// # let multicall = Multicall::new(client, None).await?;
+    /// // If the Solidity function calls has the following return types:
+    /// // 1. `returns (uint256)`
+    /// // 2. `returns (address)`
+    /// // 3. `returns (bool)`
+    /// let (supply, owner, paused): (U256, Address, bool) = multicall.call_typed().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call_typed<D: Detokenize>(&self) -> Result<D, M> {
+        if self.calls.len() > 16 {
+            return Err(MulticallError::TooManyCalls(self.calls.len()))
+        }
         let tokens = self.call_raw().await?;
+        let tokens = tokens
+            .into_iter()
+            .enumerate()
+            .map(|(index, token)| self.unwrap_result(index, token))
+            .collect::<Result<Vec<Token>, M>>()?;
         let tokens = vec![Token::Tuple(tokens)];
         let data = D::from_tokens(tokens).map_err(ContractError::DetokenizationError)?;
         Ok(data)
@@ -695,6 +752,105 @@ impl<M: Middleware> Multicall<M> {
         Ok(res?)
     }
 
+    /// Queries the Ethereum blockchain for a large number of calls that return the same data type,
+    /// splitting them into chunks of `chunk_size` calls and sending up to `concurrency` multicalls
+    /// at once.
+    ///
+    /// This is useful for reading the same view function of many contracts, for example the
+    /// reserves of thousands of pools, which would exceed the gas or response size limits of a
+    /// single multicall. Calls that are already added to this instance are ignored, but its
+    /// address, version and block are used for every chunk.
+    ///
+    /// The results are returned in the same order as the `calls`. Every call is allowed to fail,
+    /// a reverted call results in a [`MulticallError::CallReverted`] with the index of the call.
+    /// Note that with [`MulticallVersion::Multicall`] a single revert fails the whole chunk.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`MulticallError`] if the RPC call of any of the chunks fails.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// # use ethers_core::{abi::Abi, types::{Address, U256}};
+    /// # use ethers_providers::{Provider, Http};
+    /// # use ethers_contract::{Contract, Multicall};
+    /// # use std::{convert::TryFrom, sync::Arc};
+    /// #
+    /// # let client = Arc::new(Provider::<Http>::try_from("http://localhost:8545")?);
+    /// # let abi: Abi = serde_json::from_str("")?;
+    /// # let pools: Vec<Address> = vec![];
+    /// # let multicall = Multicall::new(client.clone(), None).await?;
+    /// let calls = pools.iter().map(|pool| {
+    ///     Contract::new(*pool, abi.clone(), client.clone())
+    ///         .method::<_, (u128, u128, u32)>("getReserves", ())
+    ///         .unwrap()
+    /// });
+    /// // 500 calls per multicall, at most 4 multicalls in flight
+    /// let reserves = multicall.call_batched(calls, 500, 4).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call_batched<D: Detokenize>(
+        &self,
+        calls: impl IntoIterator<Item = ContractCall<M, D>>,
+        chunk_size: usize,
+        concurrency: usize,
+    ) -> Result<Vec<Result<D, M>>, M> {
+        assert!(chunk_size > 0, "chunk size must be greater than 0");
+
+        let mut chunks = Vec::new();
+        let mut calls = calls.into_iter().peekable();
+        while calls.peek().is_some() {
+            let mut multicall = self.clone();
+            multicall.clear_calls().add_calls(true, calls.by_ref().take(chunk_size));
+            chunks.push(multicall);
+        }
+
+        let chunks: Vec<_> = stream::iter(chunks)
+            .map(|multicall| async move { multicall.call_raw().await })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut results = Vec::new();
+        for tokens in chunks {
+            for token in tokens? {
+                let token = self.unwrap_result(results.len(), token);
+                results.push(token.and_then(|token| {
+                    D::from_tokens(vec![token])
+                        .map_err(|err| ContractError::DetokenizationError(err).into())
+                }));
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Unwraps the `(success, value)` result of the call at `index` returned by Multicall2 and
+    /// Multicall3, failing if the call reverted.
+    fn unwrap_result(&self, index: usize, token: Token) -> Result<Token, M> {
+        match self.version {
+            MulticallVersion::Multicall => Ok(token),
+            MulticallVersion::Multicall2 | MulticallVersion::Multicall3 => match token {
+                // (bool, (...))
+                Token::Tuple(mut result) if result.len() == 2 => {
+                    let value = result.pop().expect("has two elements");
+                    match result.pop() {
+                        Some(Token::Bool(true)) => Ok(value),
+                        _ => Err(MulticallError::CallReverted(index)),
+                    }
+                }
+                _ => Err(MulticallError::CallReverted(index)),
+            },
+        }
+    }
+
     /// Queries the Ethereum blockchain using `eth_call`, but via the Multicall contract and
     /// without detokenization.
     ///
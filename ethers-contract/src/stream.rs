@@ -1,17 +1,27 @@
 use crate::LogMeta;
 use ethers_core::types::{Log, U256};
 use futures_util::{
-    future::Either,
+    future::{Either, Future},
     stream::{Stream, StreamExt},
 };
 use pin_project::pin_project;
 use std::{
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
+use tokio::sync::broadcast;
 
 type MapEvent<'a, R, E> = Box<dyn Fn(Log) -> Result<R, E> + 'a + Send + Sync>;
 
+/// A boxed stream of decoded events, returned by [`Event::stream_from`](crate::Event::stream_from)
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxEventStream<'a, R, E> = Pin<Box<dyn Stream<Item = Result<R, E>> + Send + 'a>>;
+
+/// A boxed stream of decoded events, returned by [`Event::stream_from`](crate::Event::stream_from)
+#[cfg(target_arch = "wasm32")]
+pub type BoxEventStream<'a, R, E> = Pin<Box<dyn Stream<Item = Result<R, E>> + 'a>>;
+
 #[pin_project]
 /// Generic wrapper around Log streams, mapping their content to a specific
 /// deserialized log struct.
@@ -126,6 +136,42 @@ where
             st.map(Either::Right),
         )))
     }
+
+    /// Shares this stream between multiple independent subscribers, without creating a new filter
+    /// or subscription for every one of them.
+    ///
+    /// Returns an [`EventBroadcast`] handle to create subscribers with, and the future that drives
+    /// this stream and forwards every event to all subscribers. The future must be polled, i.e.
+    /// awaited or spawned, for the subscribers to receive events. It completes once there are
+    /// neither handles nor subscribers left.
+    ///
+    /// Every subscriber buffers up to `capacity` events, a subscriber that falls further behind
+    /// misses events according to the handle's [`LagPolicy`].
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// # async fn test<M: ethers_providers::Middleware>(contract: ethers_contract::Contract<M>) {
+    /// # use futures_util::stream::StreamExt;
+    /// let stream = contract.event::<Transfer>().stream().await.unwrap();
+    /// let (events, driver) = stream.broadcast(128);
+    ///
+    /// let first = events.subscribe().for_each(|transfer| async move { println!("{transfer:?}") });
+    /// let second = events.subscribe().for_each(|transfer| async move { println!("{transfer:?}") });
+    ///
+    /// futures_util::join!(driver, first, second);
+    /// # }
+    /// ```
+    pub fn broadcast(self, capacity: usize) -> (EventBroadcast<R, E>, impl Future<Output = ()> + 'a)
+    where
+        R: Clone,
+    {
+        broadcast(self, capacity)
+    }
 }
 
 pub type SelectEither<'a, L, R> = Pin<Box<dyn Stream<Item = Either<L, R>> + 'a>>;
@@ -186,6 +232,17 @@ where
             st.map(Either::Right),
         )))
     }
+
+    /// See `EventStream::broadcast`
+    pub fn broadcast(
+        self,
+        capacity: usize,
+    ) -> (EventBroadcast<(R, LogMeta), E>, impl Future<Output = ()> + 'a)
+    where
+        R: Clone,
+    {
+        broadcast(self, capacity)
+    }
 }
 
 impl<'a, T, R, E> Stream for EventStreamMeta<'a, T, R, E>
@@ -207,3 +264,95 @@ where
         }
     }
 }
+
+/// Determines what a subscriber of an [`EventBroadcast`] does when it falls behind and events it
+/// has not received yet are dropped from its buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Skip the dropped events and continue with the oldest event that is still buffered.
+    #[default]
+    Skip,
+    /// End the subscriber's stream.
+    Close,
+}
+
+/// A handle to an event stream that is shared between multiple subscribers, created with
+/// [`EventStream::broadcast`].
+///
+/// Decoding errors are shared between all subscribers, which is why they are wrapped in an `Arc`.
+pub struct EventBroadcast<R, E> {
+    sender: Arc<broadcast::Sender<Result<R, Arc<E>>>>,
+    lag: LagPolicy,
+}
+
+impl<R, E> Clone for EventBroadcast<R, E> {
+    fn clone(&self) -> Self {
+        Self { sender: self.sender.clone(), lag: self.lag }
+    }
+}
+
+impl<R, E> std::fmt::Debug for EventBroadcast<R, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventBroadcast")
+            .field("subscribers", &self.sender.receiver_count())
+            .field("lag", &self.lag)
+            .finish()
+    }
+}
+
+impl<R, E> EventBroadcast<R, E>
+where
+    R: Clone + Send + 'static,
+    E: Send + Sync + 'static,
+{
+    /// Sets the [`LagPolicy`] of the subscribers created with this handle.
+    #[must_use]
+    pub fn lag_policy(mut self, lag: LagPolicy) -> Self {
+        self.lag = lag;
+        self
+    }
+
+    /// Returns a new stream that yields all events that are received after this call.
+    pub fn subscribe(&self) -> impl Stream<Item = Result<R, Arc<E>>> + Send + 'static {
+        let lag = self.lag;
+        futures_util::stream::unfold(self.sender.subscribe(), move |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(_)) if lag == LagPolicy::Skip => {
+                        continue
+                    }
+                    Err(_) => return None,
+                }
+            }
+        })
+    }
+
+    /// Returns the number of active subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+fn broadcast<'a, S, R, E>(
+    mut stream: S,
+    capacity: usize,
+) -> (EventBroadcast<R, E>, impl Future<Output = ()> + 'a)
+where
+    S: Stream<Item = Result<R, E>> + Unpin + 'a,
+    R: Clone + 'a,
+    E: 'a,
+{
+    let (sender, _) = broadcast::channel(capacity);
+    let sender = Arc::new(sender);
+    let handle = EventBroadcast { sender: sender.clone(), lag: LagPolicy::default() };
+    let driver = async move {
+        while let Some(event) = stream.next().await {
+            // there are no subscribers left and no handles to create new ones
+            if sender.send(event.map_err(Arc::new)).is_err() && Arc::strong_count(&sender) == 1 {
+                break
+            }
+        }
+    };
+    (handle, driver)
+}
@@ -0,0 +1,125 @@
+use crate::{base::BaseContract, ContractRevert, StringOrPanic};
+
+use ethers_core::{
+    abi::{Function, Token},
+    types::{Address, Bytes, CallFrame, U256},
+};
+
+use std::{collections::HashMap, convert::TryInto};
+
+/// A call frame of the `callTracer`, with its input, output and revert data decoded against the
+/// ABI of the called contract, see [`FunctionCall::trace`](crate::FunctionCall::trace).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedCallFrame {
+    /// The type of the call, e.g. `CALL`, `STATICCALL`, `DELEGATECALL` or `CREATE`
+    pub typ: String,
+    /// The caller
+    pub from: Address,
+    /// The called contract
+    pub to: Option<Address>,
+    /// The value sent with the call
+    pub value: Option<U256>,
+    /// The gas used by the call, including its sub-calls
+    pub gas_used: U256,
+    /// The raw calldata
+    pub input: Bytes,
+    /// The raw return data, or the revert data if the call failed
+    pub output: Option<Bytes>,
+    /// The error reported by the node if the call failed, e.g. `execution reverted`
+    pub error: Option<String>,
+    /// The called function, if the ABI of the called contract is known
+    pub function: Option<Function>,
+    /// The decoded arguments of the call
+    pub decoded_input: Option<Vec<Token>>,
+    /// The decoded return values of the call
+    pub decoded_output: Option<Vec<Token>>,
+    /// The decoded revert reason, if the call reverted with a string, a panic or a custom error of
+    /// the called contract
+    pub revert: Option<String>,
+    /// The sub-calls made by this call
+    pub calls: Vec<DecodedCallFrame>,
+}
+
+impl DecodedCallFrame {
+    /// Decodes the `frame` and all of its sub-calls against the ABIs in `abis`.
+    ///
+    /// `root` is used for the top level call if its callee is not in `abis`.
+    pub(crate) fn decode(
+        frame: CallFrame,
+        abis: &HashMap<Address, BaseContract>,
+        root: Option<&Function>,
+    ) -> Self {
+        let to = frame.to.as_ref().and_then(|to| to.as_address()).copied();
+        let base = to.and_then(|to| abis.get(&to));
+        let function = frame
+            .input
+            .get(..4)
+            .and_then(|selector| selector.try_into().ok())
+            .and_then(|selector| match base {
+                Some(base) => base.get_from_signature(selector).ok(),
+                None => root.filter(|function| function.short_signature() == selector),
+            })
+            .cloned();
+
+        let decoded_input =
+            function.as_ref().and_then(|function| function.decode_input(&frame.input[4..]).ok());
+        let (decoded_output, revert) = match (&frame.output, &frame.error) {
+            (Some(output), None) => {
+                (function.as_ref().and_then(|function| function.decode_output(output).ok()), None)
+            }
+            (Some(output), Some(_)) => (None, decode_revert(output, base)),
+            (None, _) => (None, None),
+        };
+
+        let calls = frame
+            .calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| Self::decode(call, abis, None))
+            .collect();
+
+        Self {
+            typ: frame.typ,
+            from: frame.from,
+            to,
+            value: frame.value,
+            gas_used: frame.gas_used,
+            input: frame.input,
+            output: frame.output,
+            error: frame.error,
+            function,
+            decoded_input,
+            decoded_output,
+            revert,
+            calls,
+        }
+    }
+
+    /// Returns `true` if this call failed
+    pub fn is_error(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Returns the innermost failed call of this call tree, which is usually the origin of a
+    /// revert that bubbled up through the calls above it.
+    pub fn failed_call(&self) -> Option<&DecodedCallFrame> {
+        if !self.is_error() {
+            return None
+        }
+        Some(self.calls.iter().rev().find_map(|call| call.failed_call()).unwrap_or(self))
+    }
+}
+
+/// Decodes the revert data as `Error(string)`, `Panic(uint256)` or one of the custom errors of the
+/// called contract
+fn decode_revert(data: &[u8], base: Option<&BaseContract>) -> Option<String> {
+    if let Some(revert) = StringOrPanic::decode_with_selector(data) {
+        return Some(revert.to_string())
+    }
+    let selector = data.get(..4)?;
+    let error =
+        base?.abi().errors().find(|error| error.signature().as_bytes().starts_with(selector))?;
+    let tokens = error.decode(&data[4..]).ok()?;
+    let args = tokens.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+    Some(format!("{}({args})", error.name))
+}
@@ -1,7 +1,7 @@
 #![cfg(feature = "abigen")]
 #![allow(unused)]
 //! Test cases to validate the `abigen!` macro
-use ethers_contract::{abigen, Abigen, EthCall, EthEvent};
+use ethers_contract::{abigen, Abigen, ContractRevert, EthCall, EthError, EthEvent, StringOrPanic};
 use ethers_core::{
     abi::{AbiDecode, AbiEncode, Address, Tokenizable},
     types::{transaction::eip2718::TypedTransaction, Chain, Eip1559TransactionRequest, U256},
@@ -656,6 +656,12 @@ fn can_gen_seaport() {
         consideration_index: U256::zero(),
         shortfall_amount: U256::zero(),
     });
+
+    let encoded = err.clone().encode();
+    assert!(SeaportErrors::valid_selector(ConsiderationNotMet::selector()));
+    assert_eq!(SeaportErrors::decode_with_selector(&encoded), Some(err));
+    let revert_string = StringOrPanic::RevertString("reverted".to_string()).encode();
+    assert_eq!(SeaportErrors::decode_with_selector(&revert_string), None);
 }
 
 #[test]
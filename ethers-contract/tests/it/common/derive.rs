@@ -2,7 +2,7 @@ use ethers_contract::{
     abigen, EthAbiCodec, EthAbiType, EthCall, EthDisplay, EthError, EthEvent, EthLogDecode,
 };
 use ethers_core::{
-    abi::{AbiDecode, AbiEncode, RawLog, Tokenizable},
+    abi::{AbiDecode, AbiEncode, AbiType, ParamType, RawLog, Token, Tokenizable},
     types::{Address, Bytes, H160, H256, I256, U128, U256},
 };
 
@@ -397,6 +397,34 @@ fn eth_display_works() {
     assert_eq!(val, format!("{item}"));
 }
 
+#[test]
+fn eth_display_with_format_attributes() {
+    #[derive(Debug, Clone, EthAbiType, EthDisplay)]
+    struct Transfer {
+        #[ethdisplay(units = 18)]
+        amount: U256,
+        #[ethdisplay(units = "gwei")]
+        fee: I256,
+        #[ethdisplay(hex)]
+        id: U256,
+        #[ethdisplay(hex)]
+        hash: H256,
+        #[ethdisplay(hex)]
+        short: Bytes,
+    }
+    let item = Transfer {
+        amount: U256::exp10(18) * 3 / 2,
+        fee: I256::from(-2_500_000_000i64),
+        id: 255u64.into(),
+        hash: H256::from_low_u64_be(0xabcd),
+        short: vec![1, 2, 3].into(),
+    };
+    assert_eq!(
+        format!("{item}"),
+        "1.500000000000000000, -2.500000000, 0xff, 0x00000000...0000abcd, 0x010203"
+    );
+}
+
 #[test]
 fn eth_display_works_for_human_readable() {
     ethers_contract::abigen!(
@@ -482,6 +510,62 @@ fn can_derive_for_enum() {
     assert_eq!(ActionChoices::GoLeft, ActionChoices::from_token(token).unwrap());
 }
 
+#[test]
+fn can_derive_for_data_carrying_enum() {
+    #[derive(Debug, Clone, PartialEq, Eq, EthAbiType, EthAbiCodec)]
+    enum Action {
+        Transfer { to: Address, amount: U256 },
+        Swap(Address, Address, U256),
+        Burn(U256),
+        Pause,
+    }
+    assert_tokenizeable::<Action>();
+    assert_eq!(Action::param_type(), ParamType::Tuple(vec![ParamType::Uint(8), ParamType::Bytes]));
+
+    let values = vec![
+        Action::Transfer { to: Address::repeat_byte(1), amount: 100u64.into() },
+        Action::Swap(Address::repeat_byte(2), Address::repeat_byte(3), 5u64.into()),
+        Action::Burn(7u64.into()),
+        Action::Pause,
+    ];
+    for val in values.clone() {
+        assert_eq!(Action::from_token(val.clone().into_token()).unwrap(), val);
+        assert_eq!(Action::decode(val.clone().encode()).unwrap(), val);
+    }
+    assert_eq!(Vec::<Action>::decode(values.clone().encode()).unwrap(), values);
+
+    let encoded = Action::Burn(7u64.into()).encode();
+    assert_eq!(encoded, (2u8, Bytes::from(U256::from(7u64).encode())).encode());
+    assert!(Action::decode((4u8, Bytes::default()).encode()).is_err());
+
+    #[derive(Debug, Clone, PartialEq, Eq, EthAbiType)]
+    #[ethabitype(tagged)]
+    enum Wrapped {
+        Amount(U256),
+        Owner(Address),
+    }
+    let token = Wrapped::Owner(Address::repeat_byte(1)).into_token();
+    assert_eq!(
+        token,
+        Token::Tuple(vec![
+            Token::Uint(1u64.into()),
+            Token::Bytes(Address::repeat_byte(1).encode())
+        ])
+    );
+    assert_eq!(Wrapped::from_token(token).unwrap(), Wrapped::Owner(Address::repeat_byte(1)));
+
+    #[derive(Debug, Clone, PartialEq, Eq, EthAbiType)]
+    #[ethabitype(tagged)]
+    enum State {
+        On,
+        Off,
+    }
+    let token = State::Off.into_token();
+    assert_eq!(State::from_token(token).unwrap(), State::Off);
+    let token = Token::Tuple(vec![Token::Uint(1u64.into()), Token::Bytes(vec![1])]);
+    assert!(State::from_token(token).is_err());
+}
+
 #[test]
 fn can_derive_abi_codec() {
     #[derive(Debug, Clone, PartialEq, Eq, EthAbiType, EthAbiCodec)]
@@ -669,3 +753,37 @@ fn derives_abi_name() {
         "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".parse().unwrap()
     );
 }
+
+#[test]
+fn can_match_hashed_indexed_topics() {
+    #[derive(Debug, EthEvent)]
+    #[ethevent(abi = "NameRegistered(string,bytes,uint256)")]
+    struct NameRegistered {
+        #[ethevent(indexed)]
+        name: H256,
+        #[ethevent(indexed)]
+        data: H256,
+        value: U256,
+    }
+
+    let event = NameRegistered {
+        name: ethers_core::utils::keccak256("vitalik").into(),
+        data: ethers_core::utils::keccak256([0xde, 0xad]).into(),
+        value: U256::one(),
+    };
+    assert!(event.matches_name("vitalik"));
+    assert!(!event.matches_name("satoshi"));
+    assert!(event.matches_data(Bytes::from(vec![0xde, 0xad])));
+
+    abigen!(
+        Registrar,
+        r#"[
+            event NameRegistered(string indexed name, uint256 value)
+        ]"#,
+    );
+    let event = NameRegisteredFilter {
+        name: ethers_core::utils::keccak256("vitalik").into(),
+        value: U256::one(),
+    };
+    assert!(event.matches_name(b"vitalik"));
+}
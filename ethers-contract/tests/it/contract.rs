@@ -9,7 +9,7 @@ mod eth_tests {
     use ethers_contract::{ContractInstance, EthEvent, LogMeta, Multicall, MulticallVersion};
     use ethers_core::{
         abi::{encode, Detokenize, Token, Tokenizable},
-        types::{transaction::eip712::Eip712, Address, BlockId, Bytes, H160, I256, U256},
+        types::{transaction::eip712::Eip712, Address, BlockId, Bytes, Log, H160, I256, U256, U64},
         utils::{keccak256, Anvil},
     };
     use ethers_derive_eip712::*;
@@ -119,6 +119,13 @@ mod eth_tests {
         let (contract, receipt) = deployer.clone().send_with_receipt().await.unwrap();
         assert_eq!(receipt.contract_address.unwrap(), contract.address());
 
+        let details = deployer.clone().send_with_details().await.unwrap();
+        assert_eq!(details.receipt.contract_address.unwrap(), details.address);
+        assert_eq!(details.contract.address(), details.address);
+        assert_eq!(details.deployer, addrs[0]);
+        assert_eq!(details.constructor_events.len(), 1);
+        assert_eq!(details.constructor_events[0].topics[0], ValueChanged::signature());
+
         let get_value = contract.method::<_, String>("getValue", ()).unwrap();
         let last_sender = contract.method::<_, Address>("lastSender", ()).unwrap();
 
@@ -166,6 +173,58 @@ mod eth_tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn deploy_with_create3() {
+        use ethers_contract::ContractError;
+        use ethers_core::{
+            types::{Transaction, TransactionReceipt},
+            utils::get_create3_address,
+        };
+
+        let (provider, mock) = Provider::mocked();
+        let client = Arc::new(provider.interval(Duration::from_millis(1u64)));
+        let bytecode = Bytes::from(vec![0x60, 0x80]);
+        let factory = ContractFactory::new(ethers_core::abi::Abi::default(), bytecode, client);
+
+        // the factory is called through a relayer, not by the sender of the transaction
+        let (create3_factory, relayer, salt) =
+            (Address::repeat_byte(1), Address::repeat_byte(5), H256::repeat_byte(2));
+        let mut deployer =
+            factory.deploy(()).unwrap().legacy().create3(create3_factory, relayer, salt);
+        deployer.tx.set_gas(100_000u64).set_gas_price(1u64);
+        assert_eq!(deployer.tx.to_addr(), Some(&create3_factory));
+        let call = encode(&[Token::FixedBytes(salt.0.to_vec()), Token::Bytes(vec![0x60, 0x80])]);
+        let data = deployer.tx.data().unwrap();
+        assert_eq!(data[..4], ethers_core::utils::id("deploy(bytes32,bytes)"));
+        assert_eq!(data[4..], call);
+
+        let receipt = TransactionReceipt {
+            from: Address::repeat_byte(3),
+            status: Some(1u64.into()),
+            block_number: Some(1u64.into()),
+            ..Default::default()
+        };
+        let tx = Transaction { block_number: Some(1u64.into()), ..Default::default() };
+        let send = |code: Vec<u8>| {
+            mock.push::<Bytes, Bytes>(code.into()).unwrap();
+            mock.push(receipt.clone()).unwrap();
+            mock.push(tx.clone()).unwrap();
+            mock.push(H256::repeat_byte(4)).unwrap();
+            deployer.clone().send()
+        };
+
+        let contract = send(vec![0x60]).await.unwrap();
+        let expected = get_create3_address(
+            create3_factory,
+            keccak256([relayer.as_bytes(), salt.as_bytes()].concat()),
+        );
+        assert_eq!(contract.address(), expected);
+
+        // nothing was deployed at the address, e.g. because of a wrong sender
+        let err = send(vec![]).await.unwrap_err();
+        assert!(matches!(err, ContractError::ContractNotDeployed), "{}", err);
+    }
+
     #[tokio::test]
     #[cfg(feature = "abigen")]
     async fn get_past_events() {
@@ -238,6 +297,99 @@ mod eth_tests {
         assert_eq!(meta.transaction_index, 0.into());
     }
 
+    #[tokio::test]
+    async fn stream_events_with_raw_log() {
+        let (provider, mock) = Provider::mocked();
+        let client = Arc::new(provider.interval(Duration::from_millis(10u64)));
+        let contract = ethers_contract::Contract::new(
+            Address::zero(),
+            ethers_core::abi::Abi::default(),
+            client,
+        );
+
+        let (old_author, new_author) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let log = Log {
+            address: Address::zero(),
+            topics: vec![ValueChanged::signature(), old_author.into(), new_author.into()],
+            data: encode(&["old".to_string().into_token(), "new".to_string().into_token()]).into(),
+            ..Default::default()
+        };
+        mock.push::<Vec<Log>, _>(vec![log.clone()]).unwrap();
+        mock.push(U256::from(1u64)).unwrap();
+
+        let event = contract.event::<ValueChanged>();
+        let mut stream = event.stream_with_raw().await.unwrap();
+        let (raw, decoded) = stream.next().await.unwrap().unwrap();
+        assert_eq!(raw, log);
+        assert_eq!(decoded.old_author, old_author);
+        assert_eq!(decoded.new_author, new_author);
+        assert_eq!(decoded.old_value, "old");
+        assert_eq!(decoded.new_value, "new");
+    }
+
+    #[tokio::test]
+    async fn query_events_of_many_addresses() {
+        let (provider, mock) = Provider::mocked();
+        let contract = ethers_contract::Contract::new(
+            Address::zero(),
+            ethers_core::abi::Abi::default(),
+            Arc::new(provider),
+        );
+
+        let pools = vec![Address::repeat_byte(1), Address::repeat_byte(2)];
+        let log = |address: Address, new_value: &str| Log {
+            address,
+            topics: vec![ValueChanged::signature(), H256::zero(), H256::zero()],
+            data: encode(&[String::new().into_token(), new_value.to_string().into_token()]).into(),
+            block_number: Some(1u64.into()),
+            block_hash: Some(H256::zero()),
+            transaction_hash: Some(H256::zero()),
+            transaction_index: Some(0u64.into()),
+            log_index: Some(0u64.into()),
+            ..Default::default()
+        };
+        mock.push::<Vec<Log>, _>(vec![log(pools[0], "a"), log(pools[1], "b")]).unwrap();
+
+        let event = contract.event::<ValueChanged>().addresses(pools.clone());
+        let events = event.query_with_meta().await.unwrap();
+        let addresses: Vec<_> = events.iter().map(|(_, meta)| meta.address).collect();
+        assert_eq!(addresses, pools);
+
+        let filter = Filter::new().event(&ValueChanged::abi_signature()).address(pools);
+        mock.assert_request("eth_getLogs", [&filter]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stream_events_from_block() {
+        let (provider, mock) = Provider::mocked();
+        let client = Arc::new(provider.interval(Duration::from_millis(10u64)));
+        let contract = ethers_contract::Contract::new(
+            Address::zero(),
+            ethers_core::abi::Abi::default(),
+            client,
+        );
+
+        let value_changed = |block: u64, new_value: &str| Log {
+            address: Address::zero(),
+            topics: vec![ValueChanged::signature(), H256::zero(), H256::zero()],
+            data: encode(&[String::new().into_token(), new_value.to_string().into_token()]).into(),
+            block_number: Some(block.into()),
+            log_index: Some(U256::zero()),
+            ..Default::default()
+        };
+
+        // responses are popped in reverse order
+        mock.push::<Vec<Log>, _>(vec![value_changed(11, "b"), value_changed(12, "c")]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![value_changed(10, "a"), value_changed(11, "b")]).unwrap();
+        mock.push(U64::from(12u64)).unwrap();
+        mock.push(U256::from(1u64)).unwrap();
+
+        let event = contract.event::<ValueChanged>();
+        let stream = event.stream_from(10u64, 5).await.unwrap();
+        let values: Vec<_> = stream.take(3).map(|event| event.unwrap().new_value).collect().await;
+        assert_eq!(values, ["a", "b", "c"]);
+    }
+
     #[tokio::test]
     async fn call_past_state() {
         let (abi, bytecode) = compile_contract("SimpleStorage", "SimpleStorage.sol");
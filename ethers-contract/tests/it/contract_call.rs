@@ -19,3 +19,452 @@ async fn contract_call_into_future_is_send() {
 
     assert!(is_send(contract.cache().into_future()));
 }
+
+/// Returns the DAI contract with its `balanceOf` function on a mocked provider, and a holder of DAI
+fn balance_of() -> (
+    ethers_contract::Contract<Provider<ethers_providers::MockProvider>>,
+    ethers_providers::MockProvider,
+    Address,
+) {
+    use ethers_contract::Contract;
+    use ethers_core::abi::parse_abi;
+
+    let abi = parse_abi(&["function balanceOf(address) external view returns (uint256)"]).unwrap();
+    let token = "0x6B175474E89094C44Da98b954EedeAC495271d0F".parse::<Address>().unwrap();
+    let holder = "0x295a70b2de5e3953354a6a8344e616ed314d7251".parse::<Address>().unwrap();
+
+    let (provider, mock) = Provider::mocked();
+    (Contract::new(token, abi, Arc::new(provider)), mock, holder)
+}
+
+#[tokio::test]
+async fn contract_call_with_state_overrides() {
+    use ethers_core::{
+        abi::AbiEncode,
+        types::{BigEndianHash, Bytes, H256, U256},
+    };
+    use ethers_providers::call_raw::spoof;
+
+    let (contract, mock, holder) = balance_of();
+    let token = contract.address();
+
+    let balance = U256::exp10(18);
+    mock.push::<Bytes, Bytes>(balance.encode().into()).unwrap();
+
+    let slot = H256::from_low_u64_be(2);
+    let call = contract
+        .method::<_, U256>("balanceOf", holder)
+        .unwrap()
+        .state_override(token, |account| {
+            account.store(slot, H256::from_uint(&balance));
+        })
+        .state_override(holder, |account| {
+            account.balance(U256::exp10(20));
+        });
+    assert_eq!(call.call().await.unwrap(), balance);
+
+    let mut state = spoof::storage(token, slot, H256::from_uint(&balance));
+    state.account(holder).balance(U256::exp10(20));
+    mock.assert_request("eth_call", (&call.tx, "latest", &state)).unwrap();
+}
+
+#[tokio::test]
+async fn contract_call_with_access_list_from_node() {
+    use ethers_core::types::{
+        transaction::eip2930::{AccessList, AccessListItem, AccessListWithGasUsed},
+        H256, U256,
+    };
+
+    let (contract, mock, holder) = balance_of();
+    let token = contract.address();
+
+    let access_list = AccessList(vec![AccessListItem {
+        address: token,
+        storage_keys: vec![H256::from_low_u64_be(2)],
+    }]);
+    let gas = U256::from(35_000u64);
+    mock.push(gas).unwrap();
+    let gas_used = U256::from(30_000u64);
+    mock.push(AccessListWithGasUsed { access_list: access_list.clone(), gas_used }).unwrap();
+
+    let call = contract.method::<_, U256>("balanceOf", holder).unwrap();
+    let tx = call.tx.clone();
+    let call = call.with_access_list_from_node().await.unwrap();
+    mock.assert_request("eth_createAccessList", (&tx, "latest")).unwrap();
+
+    // the gas is estimated with the access list, not taken from the gas used
+    let mut estimated = tx;
+    estimated.set_access_list(access_list.clone());
+    mock.assert_request("eth_estimateGas", [&estimated]).unwrap();
+    assert_eq!(call.tx.access_list(), Some(&access_list));
+    assert_eq!(call.tx.gas(), Some(&gas));
+}
+
+#[test]
+fn contract_error_as_revert() {
+    use ethers_contract::{ContractError, StringOrPanic};
+    use ethers_core::{abi::AbiEncode, types::U256};
+    use ethers_providers::{JsonRpcError, MockProvider, ProviderError};
+
+    let reason = StringOrPanic::RevertString("insufficient balance".to_string());
+    let rpc_err = JsonRpcError {
+        code: 3,
+        message: "execution reverted: insufficient balance".to_string(),
+        data: Some(format!("0x{}", hex::encode(reason.clone().encode())).into()),
+    };
+    let err = ContractError::<Provider<MockProvider>>::MiddlewareError(
+        ProviderError::JsonRpcClientError(Box::new(rpc_err)),
+    );
+    assert_eq!(err.as_revert::<StringOrPanic>(), Some(reason));
+
+    let panic = StringOrPanic::Panic(U256::from(0x11));
+    let err = ContractError::<Provider<MockProvider>>::ProviderError(
+        ProviderError::JsonRpcClientError(Box::new(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(format!("0x{}", hex::encode(panic.clone().encode())).into()),
+        })),
+    );
+    assert_eq!(err.as_revert::<StringOrPanic>(), Some(panic));
+
+    let err = ContractError::<Provider<MockProvider>>::ContractNotDeployed;
+    assert_eq!(err.as_revert::<StringOrPanic>(), None);
+}
+
+#[tokio::test]
+async fn contract_method_raw() {
+    use ethers_core::{
+        abi::AbiEncode,
+        types::{Bytes, U256},
+    };
+
+    let (contract, mock, holder) = balance_of();
+    let token = contract.address();
+
+    let calldata = contract.encode("balanceOf", holder).unwrap();
+    let call = contract.method_raw::<U256>(calldata.clone()).unwrap();
+    assert_eq!(call.calldata(), Some(calldata));
+
+    let balance = U256::exp10(18);
+    mock.push::<Bytes, Bytes>(balance.encode().into()).unwrap();
+    assert_eq!(call.call().await.unwrap(), balance);
+
+    assert!(contract.method_raw::<U256>(vec![0xde, 0xad, 0xbe, 0xef].into()).is_err());
+    assert!(contract.method_raw::<U256>(vec![0xde, 0xad].into()).is_err());
+}
+
+#[cfg(feature = "abigen")]
+#[tokio::test]
+async fn multicall_call_typed() {
+    use ethers_contract::{Contract, Multicall, MulticallError};
+    use ethers_core::{
+        abi::{encode, parse_abi, AbiEncode, Token},
+        types::{Bytes, U256},
+    };
+
+    let abi = parse_abi(&[
+        "function totalSupply() external view returns (uint256)",
+        "function owner() external view returns (address)",
+        "function paused() external view returns (bool)",
+    ])
+    .unwrap();
+    let (provider, mock) = Provider::mocked();
+    let client = Arc::new(provider);
+    let contract = Contract::new(Address::repeat_byte(1), abi, client.clone());
+    let owner = Address::repeat_byte(2);
+
+    let mut multicall = Multicall::new(client, Some(Address::repeat_byte(3))).await.unwrap();
+    multicall
+        .add_call(contract.method::<_, U256>("totalSupply", ()).unwrap(), false)
+        .add_call(contract.method::<_, Address>("owner", ()).unwrap(), true)
+        .add_call(contract.method::<_, bool>("paused", ()).unwrap(), false);
+
+    let results = |owner_ok: bool| -> Bytes {
+        encode(&[Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(U256::from(42).encode())]),
+            Token::Tuple(vec![Token::Bool(owner_ok), Token::Bytes(owner.encode())]),
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(false.encode())]),
+        ])])
+        .into()
+    };
+
+    mock.push::<Bytes, Bytes>(results(true)).unwrap();
+    let (supply, got_owner, paused): (U256, Address, bool) = multicall.call_typed().await.unwrap();
+    assert_eq!(supply, U256::from(42));
+    assert_eq!(got_owner, owner);
+    assert!(!paused);
+
+    mock.push::<Bytes, Bytes>(results(false)).unwrap();
+    let err = multicall.call_typed::<(U256, Address, bool)>().await.unwrap_err();
+    assert!(matches!(err, MulticallError::CallReverted(1)));
+
+    // tuples of more than 16 results can't be decoded
+    for _ in 0..14 {
+        multicall.add_call(contract.method::<_, bool>("paused", ()).unwrap(), false);
+    }
+    let err = multicall.call_typed::<(U256, Address, bool)>().await.unwrap_err();
+    assert!(matches!(err, MulticallError::TooManyCalls(17)));
+}
+
+#[cfg(feature = "abigen")]
+#[tokio::test]
+async fn multicall_call_batched() {
+    use ethers_contract::{Contract, Multicall, MulticallError};
+    use ethers_core::{
+        abi::{encode, parse_abi, AbiEncode, Token},
+        types::{Bytes, U256},
+    };
+
+    let abi = parse_abi(&["function totalSupply() external view returns (uint256)"]).unwrap();
+    let (provider, mock) = Provider::mocked();
+    let client = Arc::new(provider);
+    let multicall = Multicall::new(client.clone(), Some(Address::repeat_byte(0xff))).await.unwrap();
+
+    let calls = (1..=5u8).map(|i| {
+        Contract::new(Address::repeat_byte(i), abi.clone(), client.clone())
+            .method::<_, U256>("totalSupply", ())
+            .unwrap()
+    });
+
+    let results = |supplies: &[Option<u64>]| -> Bytes {
+        let results = supplies
+            .iter()
+            .map(|supply| match supply {
+                Some(supply) => Token::Tuple(vec![
+                    Token::Bool(true),
+                    Token::Bytes(U256::from(*supply).encode()),
+                ]),
+                None => Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![])]),
+            })
+            .collect();
+        encode(&[Token::Array(results)]).into()
+    };
+
+    // responses are returned in reverse order
+    mock.push::<Bytes, Bytes>(results(&[Some(5)])).unwrap();
+    mock.push::<Bytes, Bytes>(results(&[None, Some(4)])).unwrap();
+    mock.push::<Bytes, Bytes>(results(&[Some(1), Some(2)])).unwrap();
+
+    let supplies = multicall.call_batched(calls, 2, 2).await.unwrap();
+    assert_eq!(supplies.len(), 5);
+    for (i, supply) in [1u64, 2, 0, 4, 5].iter().enumerate() {
+        if i == 2 {
+            assert!(matches!(supplies[i], Err(MulticallError::CallReverted(2))));
+        } else {
+            assert_eq!(*supplies[i].as_ref().unwrap(), U256::from(*supply));
+        }
+    }
+}
+
+#[tokio::test]
+async fn contract_call_send_with_escalation() {
+    use ethers_contract::{Contract, FeeEscalation};
+    use ethers_core::{
+        abi::parse_abi,
+        types::{TransactionReceipt, H256, U256, U64},
+    };
+    use std::time::Duration;
+
+    let abi = parse_abi(&["function setValue(uint256) external"]).unwrap();
+    let (provider, mock) = Provider::mocked();
+    let provider = provider.interval(Duration::from_millis(1));
+    let contract = Contract::new(Address::repeat_byte(1), abi, Arc::new(provider));
+
+    let mut call = contract
+        .method::<_, ()>("setValue", U256::one())
+        .unwrap()
+        .from(Address::repeat_byte(2))
+        .gas(50_000);
+    call.tx.set_nonce(7);
+    let inner = call.tx.as_eip1559_mut().unwrap();
+    inner.max_fee_per_gas = Some(100.into());
+    inner.max_priority_fee_per_gas = Some(10.into());
+
+    let (first, replacement) = (H256::repeat_byte(3), H256::repeat_byte(4));
+    let receipt = TransactionReceipt { transaction_hash: replacement, ..Default::default() };
+    mock.push(receipt.clone()).unwrap();
+    mock.push(U64::from(12)).unwrap();
+    mock.push(replacement).unwrap();
+    mock.push(U64::from(12)).unwrap();
+    mock.push::<Option<TransactionReceipt>, _>(None).unwrap();
+    mock.push(U64::from(11)).unwrap();
+    mock.push::<Option<TransactionReceipt>, _>(None).unwrap();
+    mock.push(U64::from(10)).unwrap();
+    mock.push(first).unwrap();
+
+    let escalation = FeeEscalation::new(|fee, n| fee + fee * n / 2).blocks(2);
+    assert_eq!(call.send_with_escalation(escalation).await.unwrap(), receipt);
+
+    let mut escalated = call.tx.clone();
+    let inner = escalated.as_eip1559_mut().unwrap();
+    inner.max_fee_per_gas = Some(150.into());
+    inner.max_priority_fee_per_gas = Some(15.into());
+
+    mock.assert_request("eth_sendTransaction", [&call.tx]).unwrap();
+    mock.assert_request("eth_blockNumber", ()).unwrap();
+    mock.assert_request("eth_getTransactionReceipt", [first]).unwrap();
+    mock.assert_request("eth_blockNumber", ()).unwrap();
+    mock.assert_request("eth_getTransactionReceipt", [first]).unwrap();
+    mock.assert_request("eth_blockNumber", ()).unwrap();
+    mock.assert_request("eth_sendTransaction", [&escalated]).unwrap();
+    mock.assert_request("eth_blockNumber", ()).unwrap();
+    mock.assert_request("eth_getTransactionReceipt", [replacement]).unwrap();
+}
+
+#[tokio::test]
+async fn contract_call_send_with_escalation_requires_sender() {
+    use ethers_contract::{Contract, ContractError, FeeEscalation};
+    use ethers_core::{abi::parse_abi, types::U256};
+    use ethers_providers::ProviderError;
+
+    let abi = parse_abi(&["function setValue(uint256) external"]).unwrap();
+    let (provider, mock) = Provider::mocked();
+    let contract = Contract::new(Address::repeat_byte(1), abi, Arc::new(provider));
+
+    let mut call = contract.method::<_, ()>("setValue", U256::one()).unwrap().gas(50_000);
+    let inner = call.tx.as_eip1559_mut().unwrap();
+    inner.max_fee_per_gas = Some(100.into());
+    inner.max_priority_fee_per_gas = Some(10.into());
+
+    let err = call.send_with_escalation(FeeEscalation::new(|fee, _| fee)).await.unwrap_err();
+    assert!(matches!(err, ContractError::ProviderError(ProviderError::SignerUnavailable)));
+    assert!(mock.assert_request("eth_getTransactionCount", ()).is_err());
+}
+
+#[tokio::test]
+async fn contract_call_trace() {
+    use ethers_contract::{BaseContract, Contract};
+    use ethers_core::{
+        abi::{parse_abi, AbiEncode, Token},
+        types::{CallFrame, U256},
+        utils::id,
+    };
+    use std::collections::HashMap;
+
+    let token_abi = parse_abi(&["function transfer(address,uint256) external returns (bool)"]);
+    let vault_abi = parse_abi(&[
+        "function withdraw(uint256) external",
+        "error Insufficient(uint256 available)",
+    ])
+    .unwrap();
+    let (token, vault) = (Address::repeat_byte(1), Address::repeat_byte(2));
+    let (provider, mock) = Provider::mocked();
+    let contract = Contract::new(token, token_abi.unwrap(), Arc::new(provider));
+
+    let call = contract.method::<_, bool>("transfer", (vault, U256::from(5u64))).unwrap().legacy();
+    let revert: Vec<u8> = [&id("Insufficient(uint256)")[..], &U256::from(3u64).encode()].concat();
+    let withdraw: Vec<u8> = [&id("withdraw(uint256)")[..], &U256::from(5u64).encode()].concat();
+    let frame = CallFrame {
+        typ: "CALL".to_string(),
+        input: call.calldata().unwrap(),
+        to: Some(token.into()),
+        output: Some(revert.clone().into()),
+        error: Some("execution reverted".to_string()),
+        calls: Some(vec![CallFrame {
+            typ: "CALL".to_string(),
+            from: token,
+            to: Some(vault.into()),
+            input: withdraw.into(),
+            output: Some(revert.into()),
+            error: Some("execution reverted".to_string()),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    };
+    mock.push(frame).unwrap();
+
+    let abis = HashMap::from([(vault, BaseContract::from(vault_abi))]);
+    let trace = call.trace(&abis).await.unwrap();
+    assert_eq!(trace.function.as_ref().unwrap().name, "transfer");
+    assert_eq!(
+        trace.decoded_input.as_ref().unwrap(),
+        &[Token::Address(vault), Token::Uint(5u64.into())]
+    );
+    // the token's ABI does not know the vault's error
+    assert_eq!(trace.revert, None);
+
+    let failed = trace.failed_call().unwrap();
+    assert_eq!(failed.to, Some(vault));
+    assert_eq!(failed.function.as_ref().unwrap().name, "withdraw");
+    assert_eq!(failed.revert.as_deref(), Some("Insufficient(3)"));
+}
+
+#[tokio::test]
+async fn contract_call_send_checked() {
+    use ethers_contract::{Contract, ContractError, StringOrPanic};
+    use ethers_core::{
+        abi::{parse_abi, AbiEncode},
+        types::{BlockNumber, Bytes, H256, U256},
+    };
+
+    let abi = parse_abi(&["function setValue(uint256) external"]).unwrap();
+    let (provider, mock) = Provider::mocked();
+    let contract = Contract::new(Address::repeat_byte(1), abi, Arc::new(provider));
+    let call = contract
+        .method::<_, ()>("setValue", U256::one())
+        .unwrap()
+        .legacy()
+        .from(Address::repeat_byte(2))
+        .gas(50_000)
+        .gas_price(1);
+
+    // the simulation succeeds, so the transaction is sent
+    mock.push(H256::repeat_byte(3)).unwrap();
+    mock.push::<Bytes, Bytes>(Bytes::default()).unwrap();
+    assert_eq!(*call.send_checked().await.unwrap(), H256::repeat_byte(3));
+    mock.assert_request("eth_call", (&call.tx, BlockNumber::Pending)).unwrap();
+    mock.assert_request("eth_sendTransaction", [&call.tx]).unwrap();
+
+    // the simulation fails, so nothing is sent
+    mock.push(true).unwrap();
+    assert!(call.send_checked().await.is_err());
+    mock.assert_request("eth_call", (&call.tx, BlockNumber::Pending)).unwrap();
+    assert!(mock.assert_request("eth_sendTransaction", [&call.tx]).is_err());
+
+    let reason = StringOrPanic::RevertString("not allowed".to_string());
+    let err = ContractError::<Provider<ethers_providers::MockProvider>>::Revert(
+        reason.clone().encode().into(),
+    );
+    assert_eq!(err.as_revert::<StringOrPanic>(), Some(reason));
+}
+
+#[tokio::test]
+async fn event_stream_broadcast() {
+    use ethers_contract::stream::{EventStream, LagPolicy};
+    use ethers_core::types::{Log, U256, U64};
+    use futures_util::{future, stream, StreamExt};
+
+    let event_stream = |count: u64| {
+        let logs =
+            (0..count).map(|n| Log { block_number: Some(U64::from(n)), ..Default::default() });
+        EventStream::new(
+            U256::zero(),
+            stream::iter(logs),
+            Box::new(|log: Log| -> Result<u64, ()> { Ok(log.block_number.unwrap().as_u64()) }),
+        )
+    };
+
+    // every subscriber receives every event
+    let (events, driver) = event_stream(3).broadcast(4);
+    let first = events.subscribe().map(Result::unwrap).take(3).collect::<Vec<_>>();
+    let second = events.subscribe().map(Result::unwrap).take(3).collect::<Vec<_>>();
+    assert_eq!(events.subscriber_count(), 2);
+    let received =
+        match future::select(Box::pin(driver), Box::pin(future::join(first, second))).await {
+            future::Either::Right((received, _)) => received,
+            future::Either::Left(_) => panic!("driver completed"),
+        };
+    assert_eq!(received, (vec![0, 1, 2], vec![0, 1, 2]));
+
+    // a lagging subscriber skips the dropped events
+    let (events, driver) = event_stream(3).broadcast(1);
+    let skipping = events.subscribe().map(Result::unwrap).take(1).collect::<Vec<_>>();
+    let closing = events.clone().lag_policy(LagPolicy::Close).subscribe().collect::<Vec<_>>();
+    let received =
+        match future::select(Box::pin(driver), Box::pin(future::join(skipping, closing))).await {
+            future::Either::Right((received, _)) => received,
+            future::Either::Left(_) => panic!("driver completed"),
+        };
+    assert_eq!(received.0, vec![2]);
+    assert!(received.1.is_empty());
+}
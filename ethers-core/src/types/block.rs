@@ -170,6 +170,58 @@ impl<TX> Block<TX> {
         }
     }
 
+    /// Computes the hash of the header of this block from its fields, i.e. the keccak256 hash of
+    /// its RLP encoding.
+    ///
+    /// Unlike [`Self::hash`], which is just what the node claims, this can be compared against a
+    /// trusted block hash to check the fields returned by an untrusted node. The header fields
+    /// added after London (`withdrawalsRoot`, `blobGasUsed`, `excessBlobGas`,
+    /// `parentBeaconBlockRoot` and `requestsHash`) are read from the [`other`](Self::other)
+    /// fields.
+    #[cfg(not(feature = "celo"))]
+    pub fn header_hash(&self) -> H256 {
+        let mut fields = rlp::RlpStream::new();
+        fields.begin_unbounded_list();
+        fields.append(&self.parent_hash);
+        fields.append(&self.uncles_hash);
+        fields.append(&self.author.unwrap_or_default());
+        fields.append(&self.state_root);
+        fields.append(&self.transactions_root);
+        fields.append(&self.receipts_root);
+        fields.append(&self.logs_bloom.unwrap_or_default());
+        fields.append(&self.difficulty);
+        fields.append(&self.number.unwrap_or_default());
+        fields.append(&self.gas_limit);
+        fields.append(&self.gas_used);
+        fields.append(&self.timestamp);
+        fields.append(&self.extra_data.as_ref());
+        fields.append(&self.mix_hash.unwrap_or_default());
+        fields.append(&self.nonce.unwrap_or_default());
+        if let Some(base_fee_per_gas) = self.base_fee_per_gas {
+            fields.append(&base_fee_per_gas);
+            // the fields of later forks are only present if all previous ones are
+            let root = |key| self.other.get_deserialized::<H256>(key).and_then(Result::ok);
+            let gas = |key| self.other.get_deserialized::<U64>(key).and_then(Result::ok);
+            if let Some(withdrawals_root) = root("withdrawalsRoot") {
+                fields.append(&withdrawals_root);
+                if let (Some(blob_gas_used), Some(excess_blob_gas)) =
+                    (gas("blobGasUsed"), gas("excessBlobGas"))
+                {
+                    fields.append(&blob_gas_used);
+                    fields.append(&excess_blob_gas);
+                    if let Some(parent_beacon_block_root) = root("parentBeaconBlockRoot") {
+                        fields.append(&parent_beacon_block_root);
+                        if let Some(requests_hash) = root("requestsHash") {
+                            fields.append(&requests_hash);
+                        }
+                    }
+                }
+            }
+        }
+        fields.finalize_unbounded_list();
+        H256(crate::utils::keccak256(fields.out()))
+    }
+
     /// Parse [`Self::timestamp`] into a [`DateTime<Utc>`].
     ///
     /// # Errors
@@ -665,6 +717,73 @@ impl fmt::Display for BlockNumber {
     }
 }
 
+/// Overrides of the header fields of the block a call is executed in, as accepted by
+/// `debug_traceCall` and `eth_simulateV1`.
+///
+/// Unset fields are taken from the block the call is executed on top of.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_randao: Option<H256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_base_fee: Option<U256>,
+}
+
+impl BlockOverrides {
+    #[must_use]
+    pub fn number(mut self, number: impl Into<U64>) -> Self {
+        self.number = Some(number.into());
+        self
+    }
+
+    #[must_use]
+    pub fn time(mut self, time: impl Into<U64>) -> Self {
+        self.time = Some(time.into());
+        self
+    }
+
+    #[must_use]
+    pub fn gas_limit(mut self, gas_limit: impl Into<U64>) -> Self {
+        self.gas_limit = Some(gas_limit.into());
+        self
+    }
+
+    #[must_use]
+    pub fn fee_recipient(mut self, fee_recipient: Address) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    #[must_use]
+    pub fn prev_randao(mut self, prev_randao: H256) -> Self {
+        self.prev_randao = Some(prev_randao);
+        self
+    }
+
+    #[must_use]
+    pub fn base_fee_per_gas(mut self, base_fee_per_gas: impl Into<U256>) -> Self {
+        self.base_fee_per_gas = Some(base_fee_per_gas.into());
+        self
+    }
+
+    #[must_use]
+    pub fn blob_base_fee(mut self, blob_base_fee: impl Into<U256>) -> Self {
+        self.blob_base_fee = Some(blob_base_fee.into());
+        self
+    }
+}
+
 #[cfg(test)]
 #[cfg(not(feature = "celo"))]
 mod tests {
@@ -818,6 +937,38 @@ mod tests {
         assert_eq!(block.base_fee_per_gas, Some(U256::from(7)));
     }
 
+    #[test]
+    #[cfg(not(feature = "celo"))]
+    fn computes_header_hash() {
+        // the mainnet genesis block
+        let genesis: Block<TxHash> = serde_json::from_value(serde_json::json!({
+            "difficulty": "0x400000000",
+            "extraData": "0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa",
+            "gasLimit": "0x1388",
+            "gasUsed": "0x0",
+            "hash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+            "logsBloom": format!("0x{}", "0".repeat(512)),
+            "miner": "0x0000000000000000000000000000000000000000",
+            "mixHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "nonce": "0x0000000000000042",
+            "number": "0x0",
+            "parentHash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "receiptsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "sha3Uncles": "0x1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347",
+            "stateRoot": "0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544",
+            "timestamp": "0x0",
+            "transactions": [],
+            "transactionsRoot": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+            "uncles": []
+        }))
+        .unwrap();
+        assert_eq!(Some(genesis.header_hash()), genesis.hash);
+
+        let mut tampered = genesis.clone();
+        tampered.state_root = H256::repeat_byte(1);
+        assert_ne!(Some(tampered.header_hash()), genesis.hash);
+    }
+
     #[test]
     fn test_next_block_base_fee() {
         // <https://etherscan.io/block/14402566>
@@ -0,0 +1,153 @@
+//! Types of the [Engine API](https://github.com/ethereum/execution-apis/tree/main/src/engine)
+//! used by consensus clients to drive an execution client
+
+use crate::types::{Address, Bloom, Bytes, H256, H64, U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// Identifier of a payload build process, returned by `engine_forkchoiceUpdated`
+pub type PayloadId = H64;
+
+/// A validator withdrawal from the consensus layer
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier of the withdrawal
+    pub index: U64,
+    /// Index of the withdrawing validator
+    pub validator_index: U64,
+    /// Recipient of the withdrawn ether
+    pub address: Address,
+    /// Withdrawn amount in Gwei
+    pub amount: U64,
+}
+
+/// The execution payload of a block, see
+/// [`ExecutionPayloadV3`](https://github.com/ethereum/execution-apis/blob/main/src/engine/cancun.md#executionpayloadv3)
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionPayloadV3 {
+    pub parent_hash: H256,
+    pub fee_recipient: Address,
+    pub state_root: H256,
+    pub receipts_root: H256,
+    pub logs_bloom: Bloom,
+    pub prev_randao: H256,
+    pub block_number: U64,
+    pub gas_limit: U64,
+    pub gas_used: U64,
+    pub timestamp: U64,
+    pub extra_data: Bytes,
+    pub base_fee_per_gas: U256,
+    pub block_hash: H256,
+    /// RLP encoded transactions
+    pub transactions: Vec<Bytes>,
+    pub withdrawals: Vec<Withdrawal>,
+    pub blob_gas_used: U64,
+    pub excess_blob_gas: U64,
+}
+
+/// The blocks the consensus layer considers the head, safe and finalized block
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceState {
+    pub head_block_hash: H256,
+    pub safe_block_hash: H256,
+    pub finalized_block_hash: H256,
+}
+
+/// The attributes of a payload the execution client should start building, see
+/// [`PayloadAttributesV3`](https://github.com/ethereum/execution-apis/blob/main/src/engine/cancun.md#payloadattributesv3)
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadAttributesV3 {
+    pub timestamp: U64,
+    pub prev_randao: H256,
+    pub suggested_fee_recipient: Address,
+    pub withdrawals: Vec<Withdrawal>,
+    pub parent_beacon_block_root: H256,
+}
+
+/// Whether the execution client considers a payload valid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadValidity {
+    Valid,
+    Invalid,
+    Syncing,
+    Accepted,
+    InvalidBlockHash,
+}
+
+/// The result of validating a payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayloadStatus {
+    pub status: PayloadValidity,
+    /// The hash of the most recent valid block in the branch of the payload
+    pub latest_valid_hash: Option<H256>,
+    pub validation_error: Option<String>,
+}
+
+/// The response to `engine_forkchoiceUpdated`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkchoiceUpdated {
+    pub payload_status: PayloadStatus,
+    /// Set if payload attributes were provided and the build process started
+    pub payload_id: Option<PayloadId>,
+}
+
+/// The blobs of the transactions of a payload, along with their KZG commitments and proofs
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobsBundleV1 {
+    pub commitments: Vec<Bytes>,
+    pub proofs: Vec<Bytes>,
+    pub blobs: Vec<Bytes>,
+}
+
+/// The response to `engine_getPayloadV3`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPayloadV3Response {
+    pub execution_payload: ExecutionPayloadV3,
+    /// The expected value received by the fee recipient in wei
+    pub block_value: U256,
+    pub blobs_bundle: BlobsBundleV1,
+    /// Whether the consensus client should use a locally built payload over the builder's
+    pub should_override_builder: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_forkchoice_updated() {
+        let s = r#"{"payloadStatus":{"status":"VALID","latestValidHash":"0x3b8fb240d288781d4aac94d3fd16809ee413bc99294a085798a589dae51ddd4a","validationError":null},"payloadId":"0xa247243752eb10b4"}"#;
+        let updated: ForkchoiceUpdated = serde_json::from_str(s).unwrap();
+        assert_eq!(updated.payload_status.status, PayloadValidity::Valid);
+        assert_eq!(updated.payload_id, Some("0xa247243752eb10b4".parse().unwrap()));
+        assert_eq!(serde_json::to_string(&updated).unwrap(), s);
+
+        let status: PayloadStatus = serde_json::from_str(
+            r#"{"status":"INVALID_BLOCK_HASH","latestValidHash":null,"validationError":"invalid block hash"}"#,
+        )
+        .unwrap();
+        assert_eq!(status.status, PayloadValidity::InvalidBlockHash);
+    }
+
+    #[test]
+    fn serde_execution_payload() {
+        let payload = ExecutionPayloadV3 {
+            block_number: 1u64.into(),
+            transactions: vec![Bytes::from(vec![0x02, 0x01])],
+            withdrawals: vec![Withdrawal { index: 1u64.into(), ..Default::default() }],
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&payload).unwrap();
+        assert_eq!(value["blockNumber"], "0x1");
+        assert_eq!(value["withdrawals"][0]["validatorIndex"], "0x0");
+        assert_eq!(value["blobGasUsed"], "0x0");
+        assert_eq!(serde_json::from_value::<ExecutionPayloadV3>(value).unwrap(), payload);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{types::U256, utils::from_int_or_hex};
+use crate::{
+    types::{Chain, U256},
+    utils::from_int_or_hex,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -17,3 +20,177 @@ pub struct FeeHistory {
     #[serde(default)]
     pub reward: Vec<Vec<U256>>,
 }
+
+impl FeeHistory {
+    /// Estimates the max fee per gas and max priority fee per gas of an EIP-1559 transaction
+    /// from this fee history, according to the `strategy`.
+    ///
+    /// The fee history must have been fetched with the strategy's `reward_percentile` as its
+    /// only reward percentile.
+    pub fn estimate_eip1559_fees(&self, strategy: &FeeHistoryStrategy) -> (U256, U256) {
+        let mut rewards: Vec<U256> = self
+            .reward
+            .iter()
+            .filter_map(|reward| reward.first().copied())
+            .filter(|reward| !reward.is_zero())
+            .collect();
+        rewards.sort();
+        let max_priority_fee_per_gas = rewards
+            .get(rewards.len() / 2)
+            .copied()
+            .unwrap_or_default()
+            .max(strategy.min_priority_fee);
+
+        // the last base fee is the one of the block after the newest block of the history
+        let base_fee_per_gas = self.base_fee_per_gas.last().copied().unwrap_or_default();
+        let max_fee_per_gas =
+            base_fee_per_gas * strategy.base_fee_multiplier / 100u64 + max_priority_fee_per_gas;
+
+        (max_fee_per_gas, max_priority_fee_per_gas)
+    }
+}
+
+/// Strategy for estimating EIP-1559 fees from the `eth_feeHistory` of recent blocks.
+///
+/// The priority fee is the median of the `reward_percentile` of the priority fees that were paid
+/// in each block, and the max fee leaves room for the next base fee to rise by
+/// `base_fee_multiplier` percent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeHistoryStrategy {
+    /// The number of past blocks to fetch the fee history of.
+    pub block_count: u64,
+    /// The percentile of the priority fees paid in each block, e.g. `50.0` for the median fee.
+    pub reward_percentile: f64,
+    /// The lower bound of the estimated max priority fee per gas.
+    pub min_priority_fee: U256,
+    /// The percentage of the next base fee added to the max fee per gas, e.g. `200` to stay
+    /// valid even if the base fee doubles.
+    pub base_fee_multiplier: u64,
+}
+
+impl Default for FeeHistoryStrategy {
+    fn default() -> Self {
+        Self::new(crate::utils::EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE)
+    }
+}
+
+impl FeeHistoryStrategy {
+    /// Returns a strategy that uses the given percentile of the priority fees of the last
+    /// [`EIP1559_FEE_ESTIMATION_PAST_BLOCKS`](crate::utils::EIP1559_FEE_ESTIMATION_PAST_BLOCKS)
+    /// blocks, and allows the base fee to double.
+    pub fn new(reward_percentile: f64) -> Self {
+        Self {
+            block_count: crate::utils::EIP1559_FEE_ESTIMATION_PAST_BLOCKS,
+            reward_percentile,
+            min_priority_fee: U256::zero(),
+            base_fee_multiplier: 200,
+        }
+    }
+
+    /// Returns a strategy for transactions that can wait, using the 10th percentile.
+    pub fn slow() -> Self {
+        Self::new(10.0)
+    }
+
+    /// Returns a strategy for regular transactions, using the 50th percentile.
+    pub fn standard() -> Self {
+        Self::new(50.0)
+    }
+
+    /// Returns a strategy for transactions that should be included as soon as possible, using the
+    /// 90th percentile.
+    pub fn fast() -> Self {
+        Self::new(90.0)
+    }
+
+    /// Sets the number of past blocks to fetch the fee history of.
+    #[must_use]
+    pub fn block_count(mut self, block_count: u64) -> Self {
+        self.block_count = block_count;
+        self
+    }
+
+    /// Sets the lower bound of the estimated max priority fee per gas.
+    #[must_use]
+    pub fn min_priority_fee(mut self, fee: impl Into<U256>) -> Self {
+        self.min_priority_fee = fee.into();
+        self
+    }
+
+    /// Sets the percentage of the next base fee added to the max fee per gas.
+    #[must_use]
+    pub fn base_fee_multiplier(mut self, percent: u64) -> Self {
+        self.base_fee_multiplier = percent;
+        self
+    }
+}
+
+/// How the max priority fee per gas is estimated when the node doesn't serve
+/// `eth_maxPriorityFeePerGas`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PriorityFeeFallback {
+    /// Always use this fee.
+    Fixed(U256),
+    /// Use the priority fee estimated from the fee history of recent blocks.
+    FeeHistory(FeeHistoryStrategy),
+    /// Use the median of the priority fees paid by the transactions of the last `n` blocks.
+    BlocksMedian(u64),
+}
+
+impl Default for PriorityFeeFallback {
+    fn default() -> Self {
+        Self::FeeHistory(FeeHistoryStrategy::standard())
+    }
+}
+
+impl PriorityFeeFallback {
+    /// Returns the fallback that suits the chain with the given id.
+    ///
+    /// Polygon enforces a minimum priority fee of 30 gwei, Arbitrum ignores priority fees, every
+    /// other chain uses the [default](Self::default).
+    pub fn for_chain(chain_id: u64) -> Self {
+        match Chain::try_from(chain_id) {
+            Ok(Chain::Polygon | Chain::PolygonMumbai) => Self::Fixed(U256::from(30_000_000_000u64)),
+            Ok(
+                Chain::Arbitrum |
+                Chain::ArbitrumTestnet |
+                Chain::ArbitrumGoerli |
+                Chain::ArbitrumNova,
+            ) => Self::Fixed(U256::zero()),
+            _ => Self::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_fees_from_history() {
+        let history = FeeHistory {
+            base_fee_per_gas: vec![90.into(), 95.into(), 100.into()],
+            gas_used_ratio: vec![0.5, 0.6],
+            oldest_block: 1.into(),
+            reward: vec![vec![3.into()], vec![0.into()], vec![1.into()], vec![2.into()]],
+        };
+
+        // the empty block is ignored, the median of [1, 2, 3] is 2
+        let strategy = FeeHistoryStrategy::standard();
+        assert_eq!(history.estimate_eip1559_fees(&strategy), (202.into(), 2.into()));
+
+        let strategy = strategy.min_priority_fee(5).base_fee_multiplier(150);
+        assert_eq!(history.estimate_eip1559_fees(&strategy), (155.into(), 5.into()));
+    }
+
+    #[test]
+    fn priority_fee_fallback_for_chain() {
+        assert_eq!(
+            PriorityFeeFallback::for_chain(137),
+            PriorityFeeFallback::Fixed(30_000_000_000u64.into())
+        );
+        assert_eq!(PriorityFeeFallback::for_chain(42161), PriorityFeeFallback::Fixed(0.into()));
+        assert_eq!(PriorityFeeFallback::for_chain(1), PriorityFeeFallback::default());
+        assert_eq!(PriorityFeeFallback::for_chain(123_456_789), PriorityFeeFallback::default());
+    }
+}
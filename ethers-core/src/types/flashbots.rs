@@ -0,0 +1,423 @@
+//! Types of the [Flashbots](https://docs.flashbots.net/flashbots-auction/advanced/rpc-endpoint)
+//! and [MEV-Share](https://github.com/flashbots/mev-share) bundle RPC
+
+use crate::types::{
+    serde_helpers::deserialize_stringified_numeric, Address, BlockNumber, Bytes, Log, H256, U256,
+    U64,
+};
+use serde::{Deserialize, Serialize};
+
+/// A bundle of transactions that is only included atomically, in the given order, sent with
+/// `eth_sendBundle`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleRequest {
+    /// Signed, RLP encoded transactions
+    pub txs: Vec<Bytes>,
+    /// The block the bundle is valid for
+    pub block_number: U64,
+    /// The earliest timestamp at which the bundle is valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_timestamp: Option<u64>,
+    /// The latest timestamp at which the bundle is valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_timestamp: Option<u64>,
+    /// Transactions that are allowed to revert without invalidating the bundle
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reverting_tx_hashes: Vec<H256>,
+    /// Identifier to replace or cancel the bundle with a later bundle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement_uuid: Option<String>,
+}
+
+impl BundleRequest {
+    /// Creates a bundle of the given transactions targeting the given block
+    pub fn new(txs: Vec<Bytes>, block_number: impl Into<U64>) -> Self {
+        Self { txs, block_number: block_number.into(), ..Default::default() }
+    }
+}
+
+/// The response to `eth_sendBundle` and `mev_sendBundle`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendBundleResponse {
+    pub bundle_hash: H256,
+}
+
+/// A bundle sent to the MEV-Share matchmaker with `mev_sendBundle`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MevBundle {
+    /// The version of the bundle format, currently `v0.1`
+    pub version: String,
+    /// The blocks the bundle may be included in
+    pub inclusion: Inclusion,
+    /// The transactions and bundles of the bundle
+    pub body: Vec<BundleItem>,
+    /// Refunds the bundle requires to be valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validity: Option<Validity>,
+    /// What is shared about the bundle and with whom
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<Privacy>,
+}
+
+impl MevBundle {
+    /// The current version of the bundle format
+    pub const VERSION: &'static str = "v0.1";
+
+    /// Creates a bundle of the given items that may be included in `inclusion`
+    pub fn new(inclusion: Inclusion, body: Vec<BundleItem>) -> Self {
+        Self { version: Self::VERSION.to_string(), inclusion, body, validity: None, privacy: None }
+    }
+}
+
+/// The range of blocks a bundle may be included in
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inclusion {
+    /// The first block the bundle is valid for
+    pub block: U64,
+    /// The last block the bundle is valid for, only `block` if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_block: Option<U64>,
+}
+
+impl Inclusion {
+    /// Targets the given block only
+    pub fn at_block(block: impl Into<U64>) -> Self {
+        Self { block: block.into(), max_block: None }
+    }
+
+    /// Targets all blocks from `block` to `max_block`, inclusive
+    pub fn range(block: impl Into<U64>, max_block: impl Into<U64>) -> Self {
+        Self { block: block.into(), max_block: Some(max_block.into()) }
+    }
+}
+
+/// An entry of the body of a [`MevBundle`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BundleItem {
+    /// A transaction shared by the matchmaker, referenced by its hash
+    #[serde(rename_all = "camelCase")]
+    Hash { hash: H256 },
+    /// A signed, RLP encoded transaction
+    #[serde(rename_all = "camelCase")]
+    Tx { tx: Bytes, can_revert: bool },
+    /// A nested bundle
+    Bundle { bundle: Box<MevBundle> },
+}
+
+/// Refunds a [`MevBundle`] requires to be valid
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Validity {
+    /// The share of the bundle's value refunded to the senders of body entries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refund: Vec<Refund>,
+    /// How the refund of the bundle is split among addresses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refund_config: Vec<RefundConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Refund {
+    /// The index of the body entry the refund goes to
+    pub body_idx: u64,
+    /// The percentage of the bundle's value that is refunded
+    pub percent: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundConfig {
+    pub address: Address,
+    /// The percentage of the refund that goes to `address`
+    pub percent: u64,
+}
+
+/// What the matchmaker shares about a [`MevBundle`] and with which builders
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Privacy {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<PrivacyHint>,
+    /// The builders the bundle may be sent to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub builders: Vec<String>,
+}
+
+/// Data about a bundle that is shared with searchers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyHint {
+    Calldata,
+    ContractAddress,
+    Logs,
+    FunctionSelector,
+    Hash,
+    TxHash,
+}
+
+/// The parameters of `flashbots_getBundleStats`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleStatsRequest {
+    pub bundle_hash: H256,
+    /// The block the bundle targeted
+    pub block_number: U64,
+}
+
+/// The status of a bundle as returned by `flashbots_getBundleStats`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleStats {
+    #[serde(default)]
+    pub is_simulated: bool,
+    #[serde(default)]
+    pub is_sent_to_miners: bool,
+    #[serde(default)]
+    pub is_high_priority: bool,
+    /// When the bundle was simulated, as RFC 3339 timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub simulated_at: Option<String>,
+    /// When the bundle was received, as RFC 3339 timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<String>,
+    /// When the bundle was sent to builders, as RFC 3339 timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sent_to_miners_at: Option<String>,
+}
+
+/// A bundle simulated on top of a given state with `eth_callBundle`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleRequest {
+    /// Signed, RLP encoded transactions
+    pub txs: Vec<Bytes>,
+    /// The block the bundle would be included in
+    pub block_number: U64,
+    /// The block whose state the bundle is simulated on
+    pub state_block_number: BlockNumber,
+    /// The timestamp of the simulated block, that of the block after the state block if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+}
+
+impl CallBundleRequest {
+    /// Creates a simulation of the transactions in the given block on top of the latest state
+    pub fn new(txs: Vec<Bytes>, block_number: impl Into<U64>) -> Self {
+        Self {
+            txs,
+            block_number: block_number.into(),
+            state_block_number: BlockNumber::Latest,
+            timestamp: None,
+        }
+    }
+}
+
+/// The result of `eth_callBundle`
+///
+/// Amounts are in wei.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleResponse {
+    pub bundle_hash: H256,
+    /// The effective gas price of the bundle, `coinbase_diff / total_gas_used`
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub bundle_gas_price: U256,
+    /// How much the balance of the coinbase changed
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub coinbase_diff: U256,
+    /// The ether transferred to the coinbase directly by the transactions
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub eth_sent_to_coinbase: U256,
+    /// The gas fees paid to the coinbase
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub gas_fees: U256,
+    /// The results of the transactions, in order
+    pub results: Vec<CallBundleTxResult>,
+    pub state_block_number: u64,
+    pub total_gas_used: u64,
+}
+
+/// The result of a single transaction of a bundle simulated with `eth_callBundle`
+///
+/// Amounts are in wei.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleTxResult {
+    pub tx_hash: H256,
+    pub from_address: Address,
+    /// The receiver, `None` for contract creations
+    #[serde(default)]
+    pub to_address: Option<Address>,
+    pub gas_used: u64,
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub gas_price: U256,
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub gas_fees: U256,
+    /// How much the balance of the coinbase changed because of this transaction
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub coinbase_diff: U256,
+    #[serde(deserialize_with = "deserialize_stringified_numeric")]
+    pub eth_sent_to_coinbase: U256,
+    /// The return data of the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Bytes>,
+    /// The error the transaction failed with, e.g. `execution reverted`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The revert reason of the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert: Option<String>,
+}
+
+impl CallBundleTxResult {
+    /// Returns whether the transaction executed without error
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Overrides of the block a bundle is simulated in with `mev_simBundle`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimBundleOverrides {
+    /// The block whose state the bundle is simulated on, the inclusion block's parent if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_block: Option<BlockNumber>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coinbase: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee: Option<U256>,
+}
+
+/// The result of `mev_simBundle`
+///
+/// Amounts are in wei.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimBundleResponse {
+    pub success: bool,
+    /// Why the simulation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The block whose state the bundle was simulated on
+    pub state_block: U64,
+    /// The effective gas price of the bundle for the builder
+    pub mev_gas_price: U256,
+    /// The value the bundle pays to the builder
+    pub profit: U256,
+    /// The part of the profit that can be refunded
+    pub refundable_value: U256,
+    pub gas_used: U64,
+    /// The logs of the body entries, in order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logs: Option<Vec<SimBundleLogs>>,
+}
+
+/// The logs of an entry of a simulated bundle, `tx_logs` for transactions and `bundle_logs` for
+/// nested bundles
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimBundleLogs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_logs: Option<Vec<Log>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_logs: Option<Vec<SimBundleLogs>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serde_bundle_request() {
+        let bundle = BundleRequest::new(vec![Bytes::from(vec![0x02, 0xf8])], 17_000_000u64);
+        assert_eq!(
+            serde_json::to_value(&bundle).unwrap(),
+            json!({"txs": ["0x02f8"], "blockNumber": "0x1036640"})
+        );
+    }
+
+    #[test]
+    fn serde_mev_bundle() {
+        let mut bundle = MevBundle::new(
+            Inclusion::range(1u64, 3u64),
+            vec![
+                BundleItem::Hash { hash: H256::repeat_byte(1) },
+                BundleItem::Tx { tx: Bytes::from(vec![0x02]), can_revert: false },
+            ],
+        );
+        bundle.privacy =
+            Some(Privacy { hints: vec![PrivacyHint::TxHash], builders: vec!["flashbots".into()] });
+        let value = serde_json::to_value(&bundle).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "version": "v0.1",
+                "inclusion": {"block": "0x1", "maxBlock": "0x3"},
+                "body": [
+                    {"hash": format!("{:?}", H256::repeat_byte(1))},
+                    {"tx": "0x02", "canRevert": false}
+                ],
+                "privacy": {"hints": ["tx_hash"], "builders": ["flashbots"]}
+            })
+        );
+        assert_eq!(serde_json::from_value::<MevBundle>(value).unwrap(), bundle);
+    }
+
+    #[test]
+    fn deserialize_call_bundle_response() {
+        let s = r#"{
+            "bundleGasPrice": "476190476193",
+            "bundleHash": "0x73b1e258c7a42fd0230b2fd05529c5d4b6fcb66c227783f8bece8aeacdd1db2e",
+            "coinbaseDiff": "20000000000126000",
+            "ethSentToCoinbase": "20000000000000000",
+            "gasFees": "126000",
+            "results": [
+                {
+                    "coinbaseDiff": "10000000000063000",
+                    "ethSentToCoinbase": "10000000000000000",
+                    "fromAddress": "0x02A727155aeF8609c9f7F2179b2a1f560B39F5A0",
+                    "gasFees": "63000",
+                    "gasPrice": "476190476193",
+                    "gasUsed": 21000,
+                    "toAddress": "0x73625f59CAdc5009Cb458B751b3E7b6b48C06f2C",
+                    "txHash": "0x669b4704a7d993a946cdd6e2f95233f308ce0c4649d2e04944e8299efcaa098a",
+                    "value": "0x"
+                },
+                {
+                    "coinbaseDiff": "10000000000063000",
+                    "ethSentToCoinbase": "10000000000000000",
+                    "fromAddress": "0x02A727155aeF8609c9f7F2179b2a1f560B39F5A0",
+                    "gasFees": "63000",
+                    "gasPrice": "476190476193",
+                    "gasUsed": 21000,
+                    "toAddress": "0x73625f59CAdc5009Cb458B751b3E7b6b48C06f2C",
+                    "txHash": "0xa839ee83465657cac01adc1d50d96c1b586ed498120a84a64749c0034b4f19fa",
+                    "error": "execution reverted",
+                    "revert": "not enough liquidity"
+                }
+            ],
+            "stateBlockNumber": 5221585,
+            "totalGasUsed": 42000
+        }"#;
+        let res: CallBundleResponse = serde_json::from_str(s).unwrap();
+        assert_eq!(res.coinbase_diff, U256::from(20000000000126000u64));
+        assert_eq!(res.total_gas_used, 42000);
+        assert!(res.results[0].is_success());
+        assert!(!res.results[1].is_success());
+        assert_eq!(res.results[1].revert.as_deref(), Some("not enough liquidity"));
+    }
+}
@@ -0,0 +1,205 @@
+//! Fields that L2 nodes add to transactions and receipts.
+//!
+//! They are captured in the [`OtherFields`] of [`Transaction`] and [`TransactionReceipt`] and
+//! can be read with the chain specific accessors, e.g.
+//! [`TransactionReceipt::optimism_fields`].
+
+use crate::types::{OtherFields, Transaction, TransactionReceipt, H256, U256, U64};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The type of Optimism deposit transactions, which are derived from L1 and not signed
+pub const OPTIMISM_DEPOSIT_TX_TYPE: u64 = 0x7e;
+
+/// The fields of [Optimism deposit transactions](https://specs.optimism.io/protocol/deposits.html)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimismTransactionFields {
+    /// Uniquely identifies the origin of the deposit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<H256>,
+    /// The ETH minted on L2 for the deposit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<U256>,
+    /// Whether the transaction is exempt from the L2 gas limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_system_tx: Option<bool>,
+}
+
+/// The L1 data fee and deposit fields of receipts of Optimism and other OP stack chains.
+///
+/// The total cost of a transaction is its L2 execution cost, `gasUsed * effectiveGasPrice`, plus
+/// the [`l1_fee`](Self::l1_fee) for posting its data to L1.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimismReceiptFields {
+    /// The fee paid for posting the transaction's data to L1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee: Option<U256>,
+    /// The L1 base fee the [`l1_fee`](Self::l1_fee) was computed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_gas_price: Option<U256>,
+    /// The L1 gas the transaction's data was charged for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_gas_used: Option<U256>,
+    /// The decimal multiplier of the L1 fee before the Ecotone upgrade, e.g. `"0.684"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee_scalar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_base_fee_scalar: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_blob_base_fee: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_blob_base_fee_scalar: Option<U256>,
+    /// The nonce of the sender of a deposit transaction when it was included
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit_nonce: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit_receipt_version: Option<U64>,
+}
+
+/// The gas fields of [Arbitrum](https://docs.arbitrum.io/build-decentralized-apps/arbitrum-vs-ethereum/rpc-methods#receipts)
+/// receipts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArbitrumReceiptFields {
+    /// The part of `gasUsed` that paid for posting the transaction's data to L1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used_for_l1: Option<U256>,
+    /// The L1 block number the transaction's block was based on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_block_number: Option<U64>,
+}
+
+/// Deserializes `T` from the other fields, returns `None` if none of its fields are present
+fn extract<T: DeserializeOwned + Default + PartialEq>(other: &OtherFields) -> Option<T> {
+    other.clone().deserialize_into().ok().filter(|fields| *fields != T::default())
+}
+
+impl Transaction {
+    /// Returns true if this is an Optimism deposit transaction
+    pub fn is_optimism_deposit(&self) -> bool {
+        self.transaction_type == Some(OPTIMISM_DEPOSIT_TX_TYPE.into())
+    }
+
+    /// Returns the deposit fields of an Optimism deposit transaction
+    pub fn optimism_fields(&self) -> Option<OptimismTransactionFields> {
+        extract(&self.other)
+    }
+}
+
+impl TransactionReceipt {
+    /// Returns the L1 fee and deposit fields of a receipt of an OP stack chain
+    pub fn optimism_fields(&self) -> Option<OptimismReceiptFields> {
+        extract(&self.other)
+    }
+
+    /// Returns the L1 gas fields of a receipt of an Arbitrum chain
+    pub fn arbitrum_fields(&self) -> Option<ArbitrumReceiptFields> {
+        extract(&self.other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn optimism_receipt_fields() {
+        let receipt: TransactionReceipt = serde_json::from_str(
+            r#"{
+                "blockHash": "0x3f0c2cdb8e4c95a5d7a3f1b1de1a4fa2f1a3ab7ae5d56b84a4d5e6c5f6f7e8d9",
+                "blockNumber": "0x6b0c5f5",
+                "contractAddress": null,
+                "cumulativeGasUsed": "0x1a6b0",
+                "effectiveGasPrice": "0xf4594",
+                "from": "0x6887246668a3b87f54deb3b94ba47a6f63f32985",
+                "gasUsed": "0x5208",
+                "l1BaseFeeScalar": "0x558",
+                "l1BlobBaseFee": "0x1",
+                "l1BlobBaseFeeScalar": "0xc5fc5",
+                "l1Fee": "0x2c6a0a5e5f",
+                "l1GasPrice": "0x1e5d1d8f4",
+                "l1GasUsed": "0x640",
+                "logs": [],
+                "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                "status": "0x1",
+                "to": "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984",
+                "transactionHash": "0x4ee7f6fd2e1c3446e45e0e1c4a1ba8b4bd6d59d1c6b2a0c5c8ec0a3f0c5c8ec0",
+                "transactionIndex": "0x3",
+                "type": "0x2"
+            }"#,
+        )
+        .unwrap();
+        let fields = receipt.optimism_fields().unwrap();
+        assert_eq!(fields.l1_fee, Some(0x2c6a0a5e5fu64.into()));
+        assert_eq!(fields.l1_gas_used, Some(0x640.into()));
+        assert_eq!(fields.l1_base_fee_scalar, Some(0x558.into()));
+        assert_eq!(fields.deposit_nonce, None);
+        assert_eq!(receipt.arbitrum_fields(), None);
+
+        // the fields are kept when the receipt is serialized again
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(json["l1Fee"], "0x2c6a0a5e5f");
+    }
+
+    #[test]
+    fn arbitrum_receipt_fields() {
+        let receipt: TransactionReceipt = serde_json::from_str(
+            r#"{
+                "blockHash": "0x5d6f5c4a3b2c1d0e9f8a7b6c5d4e3f2a1b0c9d8e7f6a5b4c3d2e1f0a9b8c7d6e",
+                "blockNumber": "0x9a2b3c4",
+                "contractAddress": null,
+                "cumulativeGasUsed": "0x0",
+                "effectiveGasPrice": "0x989680",
+                "from": "0x6887246668a3b87f54deb3b94ba47a6f63f32985",
+                "gasUsed": "0x2d7ef",
+                "gasUsedForL1": "0x8a1c",
+                "l1BlockNumber": "0x12a05f2",
+                "logs": [],
+                "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
+                "status": "0x1",
+                "to": "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984",
+                "transactionHash": "0x8b6c1f2d3e4a5b6c7d8e9f0a1b2c3d4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0c",
+                "transactionIndex": "0x1",
+                "type": "0x2"
+            }"#,
+        )
+        .unwrap();
+        let fields = receipt.arbitrum_fields().unwrap();
+        assert_eq!(fields.gas_used_for_l1, Some(0x8a1c.into()));
+        assert_eq!(fields.l1_block_number, Some(0x12a05f2.into()));
+        assert_eq!(receipt.optimism_fields(), None);
+    }
+
+    #[test]
+    fn optimism_deposit_transaction() {
+        let tx: Transaction = serde_json::from_str(
+            r#"{
+                "blockHash": "0x3f0c2cdb8e4c95a5d7a3f1b1de1a4fa2f1a3ab7ae5d56b84a4d5e6c5f6f7e8d9",
+                "blockNumber": "0x6b0c5f5",
+                "from": "0xdeaddeaddeaddeaddeaddeaddeaddeaddead0001",
+                "gas": "0xf4240",
+                "gasPrice": "0x0",
+                "hash": "0x1d2c3b4a5f6e7d8c9b0a1f2e3d4c5b6a7f8e9d0c1b2a3f4e5d6c7b8a9f0e1d2c",
+                "input": "0x440a5e20",
+                "isSystemTx": false,
+                "mint": "0x0",
+                "nonce": "0x6b0c5f6",
+                "r": "0x0",
+                "s": "0x0",
+                "sourceHash": "0x2a3b4c5d6e7f8a9b0c1d2e3f4a5b6c7d8e9f0a1b2c3d4e5f6a7b8c9d0e1f2a3b",
+                "to": "0x4200000000000000000000000000000000000015",
+                "transactionIndex": "0x0",
+                "type": "0x7e",
+                "v": "0x0",
+                "value": "0x0"
+            }"#,
+        )
+        .unwrap();
+        assert!(tx.is_optimism_deposit());
+        let fields = tx.optimism_fields().unwrap();
+        assert_eq!(fields.mint, Some(U256::zero()));
+        assert_eq!(fields.is_system_tx, Some(false));
+        assert!(fields.source_hash.is_some());
+    }
+}
@@ -36,7 +36,7 @@ mod bytes;
 pub use self::bytes::{deserialize_bytes, serialize_bytes, Bytes, ParseBytesError};
 
 mod block;
-pub use block::{Block, BlockId, BlockNumber, TimeError};
+pub use block::{Block, BlockId, BlockNumber, BlockOverrides, TimeError};
 
 #[cfg(feature = "celo")]
 pub use block::Randomness;
@@ -59,6 +59,8 @@ pub use txpool::*;
 mod trace;
 pub use trace::*;
 
+pub mod spoof;
+
 mod chain;
 pub use chain::*;
 
@@ -69,10 +71,27 @@ pub use proof::*;
 mod fee;
 pub use fee::*;
 
+mod engine;
+pub use engine::*;
+
+mod user_operation;
+pub use user_operation::*;
+
+mod flashbots;
+pub use flashbots::*;
+
+mod otterscan;
+pub use otterscan::*;
+
 mod other;
 pub use other::OtherFields;
 
+#[cfg(not(feature = "celo"))]
+mod l2;
+#[cfg(not(feature = "celo"))]
+pub use l2::*;
+
 pub mod serde_helpers;
 
 mod syncing;
-pub use syncing::{SyncProgress, SyncingStatus};
+pub use syncing::{StagedSyncProgress, SyncProgress, SyncStage, SyncingStatus};
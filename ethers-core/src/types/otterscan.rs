@@ -0,0 +1,91 @@
+//! Types of the [Otterscan](https://github.com/otterscan/otterscan/blob/develop/docs/custom-jsonrpc.md)
+//! `ots_` RPC methods served by Erigon and other nodes that index the history of addresses
+
+use crate::types::{Address, Transaction, TransactionReceipt, H256};
+use serde::{Deserialize, Serialize};
+
+/// The creator of a contract and the transaction that deployed it, returned by
+/// `ots_getContractCreator`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractCreator {
+    /// The hash of the transaction that deployed the contract
+    pub hash: H256,
+    /// The address that deployed the contract, a factory contract if it was deployed by one
+    pub creator: Address,
+}
+
+/// A page of the transactions of an address, returned by `ots_searchTransactionsBefore` and
+/// `ots_searchTransactionsAfter`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionsWithReceipts {
+    /// The transactions, from the most recent to the oldest
+    pub txs: Vec<Transaction>,
+    /// The receipts of [`txs`](Self::txs), in the same order
+    pub receipts: Vec<OtsReceipt>,
+    /// Whether this page holds the most recent transactions of the address
+    pub first_page: bool,
+    /// Whether this page holds the oldest transactions of the address
+    pub last_page: bool,
+}
+
+/// A transaction receipt along with the timestamp of its block
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtsReceipt {
+    #[serde(flatten)]
+    pub receipt: TransactionReceipt,
+    /// The timestamp of the block that included the transaction
+    pub timestamp: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserializes_transactions_with_receipts() {
+        let page: TransactionsWithReceipts = serde_json::from_value(serde_json::json!({
+            "txs": [{
+                "blockHash": "0x3f0c2cdb8e4c95a5d7a3f1b1de1a4fa2f1a3ab7ae5d56b84a4d5e6c5f6f7e8d9",
+                "blockNumber": "0x10",
+                "from": "0x6887246668a3b87f54deb3b94ba47a6f63f32985",
+                "gas": "0x5208",
+                "gasPrice": "0x3b9aca00",
+                "hash": "0x4ee7f6fd2e1c3446e45e0e1c4a1ba8b4bd6d59d1c6b2a0c5c8ec0a3f0c5c8ec0",
+                "input": "0x",
+                "nonce": "0x2",
+                "r": "0x1",
+                "s": "0x1",
+                "to": "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984",
+                "transactionIndex": "0x0",
+                "type": "0x0",
+                "v": "0x25",
+                "value": "0x1"
+            }],
+            "receipts": [{
+                "blockHash": "0x3f0c2cdb8e4c95a5d7a3f1b1de1a4fa2f1a3ab7ae5d56b84a4d5e6c5f6f7e8d9",
+                "blockNumber": "0x10",
+                "contractAddress": null,
+                "cumulativeGasUsed": "0x5208",
+                "effectiveGasPrice": "0x3b9aca00",
+                "from": "0x6887246668a3b87f54deb3b94ba47a6f63f32985",
+                "gasUsed": "0x5208",
+                "logs": [],
+                "logsBloom": format!("0x{}", "0".repeat(512)),
+                "status": "0x1",
+                "timestamp": 1700000000,
+                "to": "0x1f9840a85d5af5bf1d1762f925bdaddc4201f984",
+                "transactionHash": "0x4ee7f6fd2e1c3446e45e0e1c4a1ba8b4bd6d59d1c6b2a0c5c8ec0a3f0c5c8ec0",
+                "transactionIndex": "0x0",
+                "type": "0x0"
+            }],
+            "firstPage": true,
+            "lastPage": false
+        }))
+        .unwrap();
+        assert_eq!(page.txs[0].nonce, 2.into());
+        assert_eq!(page.receipts[0].timestamp, 1_700_000_000);
+        assert_eq!(page.receipts[0].receipt.transaction_hash, page.txs[0].hash);
+        assert!(page.first_page && !page.last_page);
+    }
+}
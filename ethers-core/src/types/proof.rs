@@ -1,5 +1,42 @@
-use crate::types::{Address, Bytes, H256, U256, U64};
+use crate::{
+    types::{Address, Bytes, H256, U256, U64},
+    utils::keccak256,
+};
+use rlp::{Rlp, RlpStream};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The root of an empty trie, `keccak256(rlp(""))`
+const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// The code hash of accounts without code, `keccak256("")`
+const EMPTY_CODE_HASH: H256 = H256([
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+]);
+
+/// An error thrown when verifying a Merkle Patricia proof
+#[derive(Debug, Error)]
+pub enum ProofError {
+    /// A node of the proof is not valid RLP
+    #[error(transparent)]
+    Rlp(#[from] rlp::DecoderError),
+    /// A node of the proof does not match the hash referenced by its parent (or the root)
+    #[error("proof node {index} does not match the expected hash {expected:?}")]
+    HashMismatch { index: usize, expected: H256 },
+    /// The proof ended before reaching a value or its absence
+    #[error("the proof is incomplete")]
+    Incomplete,
+    /// A node is neither a branch, extension nor leaf node
+    #[error("proof node {0} is invalid")]
+    InvalidNode(usize),
+    /// The proof is valid, but proves a different value
+    #[error("the proven value does not match the expected value")]
+    ValueMismatch,
+}
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct StorageProof {
@@ -20,6 +57,154 @@ pub struct EIP1186ProofResponse {
     pub storage_proof: Vec<StorageProof>,
 }
 
+impl StorageProof {
+    /// Verifies that the storage slot `key` is set to `value` in the storage trie with the given
+    /// root, i.e. the `storage_hash` of the account.
+    pub fn verify(&self, storage_root: H256) -> Result<(), ProofError> {
+        let expected = if self.value.is_zero() {
+            // slots that are zero are removed from the trie
+            None
+        } else {
+            Some(rlp::encode(&self.value).to_vec())
+        };
+        let proven = verify_proof(storage_root, &keccak256(self.key), &self.proof)?;
+        if proven != expected {
+            return Err(ProofError::ValueMismatch)
+        }
+        Ok(())
+    }
+}
+
+impl EIP1186ProofResponse {
+    /// Verifies the account proof against the given state root, as well as all storage proofs
+    /// against the `storage_hash` of the account.
+    ///
+    /// The state root usually comes from a trusted block header, so the response of an untrusted
+    /// RPC can be checked.
+    pub fn verify(&self, state_root: H256) -> Result<(), ProofError> {
+        self.verify_account(state_root)?;
+        for proof in &self.storage_proof {
+            proof.verify(self.storage_hash)?;
+        }
+        Ok(())
+    }
+
+    /// Verifies the account proof against the given state root
+    pub fn verify_account(&self, state_root: H256) -> Result<(), ProofError> {
+        let is_empty = self.nonce.is_zero() &&
+            self.balance.is_zero() &&
+            self.storage_hash == EMPTY_ROOT &&
+            self.code_hash == EMPTY_CODE_HASH;
+        let expected = if is_empty {
+            // accounts that don't exist are proven by their absence
+            None
+        } else {
+            let mut stream = RlpStream::new_list(4);
+            stream.append(&self.nonce);
+            stream.append(&self.balance);
+            stream.append(&self.storage_hash);
+            stream.append(&self.code_hash);
+            Some(stream.out().to_vec())
+        };
+        let proven = verify_proof(state_root, &keccak256(self.address), &self.account_proof)?;
+        if proven != expected {
+            return Err(ProofError::ValueMismatch)
+        }
+        Ok(())
+    }
+}
+
+/// Verifies a Merkle Patricia proof for `key` against the `root` of the trie.
+///
+/// Returns the RLP encoded value stored at `key`, or `None` if the proof shows that the trie does
+/// not contain the `key`.
+pub fn verify_proof(
+    root: H256,
+    key: &[u8],
+    proof: &[Bytes],
+) -> Result<Option<Vec<u8>>, ProofError> {
+    let nibbles: Vec<u8> = key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    let mut path = &nibbles[..];
+
+    if proof.is_empty() {
+        return if root == EMPTY_ROOT { Ok(None) } else { Err(ProofError::Incomplete) }
+    }
+
+    let mut expected = root;
+    for (index, node) in proof.iter().enumerate() {
+        if H256(keccak256(node)) != expected {
+            return Err(ProofError::HashMismatch { index, expected })
+        }
+
+        // walk through the node and any nodes that are embedded into it
+        let mut node = Rlp::new(node);
+        loop {
+            let next = match node.item_count()? {
+                17 => match path.split_first() {
+                    Some((nibble, rest)) => {
+                        path = rest;
+                        node.at(*nibble as usize)?
+                    }
+                    None => return value(node.at(16)?),
+                },
+                2 => {
+                    let (is_leaf, node_path) = decode_path(node.at(0)?.data()?);
+                    if is_leaf {
+                        return if path == node_path.as_slice() {
+                            value(node.at(1)?)
+                        } else {
+                            Ok(None)
+                        }
+                    }
+                    if !path.starts_with(&node_path) {
+                        return Ok(None)
+                    }
+                    path = &path[node_path.len()..];
+                    node.at(1)?
+                }
+                _ => return Err(ProofError::InvalidNode(index)),
+            };
+
+            if next.is_list() {
+                // nodes shorter than 32 bytes are embedded into their parent
+                node = next;
+                continue
+            }
+            let reference = next.data()?;
+            if reference.is_empty() {
+                return Ok(None)
+            }
+            if reference.len() != 32 {
+                return Err(ProofError::InvalidNode(index))
+            }
+            expected = H256::from_slice(reference);
+            break
+        }
+    }
+
+    Err(ProofError::Incomplete)
+}
+
+/// Returns the value of a branch or leaf node, `None` if it is empty
+fn value(item: Rlp) -> Result<Option<Vec<u8>>, ProofError> {
+    let data = item.data()?;
+    Ok((!data.is_empty()).then(|| data.to_vec()))
+}
+
+/// Decodes the hex prefix encoded path of a leaf or extension node into its nibbles and returns
+/// whether the node is a leaf
+fn decode_path(encoded: &[u8]) -> (bool, Vec<u8>) {
+    let mut nibbles: Vec<u8> = encoded.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    if nibbles.is_empty() {
+        return (false, nibbles)
+    }
+    let flag = nibbles[0];
+    // odd paths include the first nibble in the flag byte
+    let skip = if flag & 1 == 1 { 1 } else { 2 };
+    nibbles.drain(..skip.min(nibbles.len()));
+    (flag & 2 == 2, nibbles)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -29,4 +214,41 @@ mod tests {
         serde_json::from_str::<EIP1186ProofResponse>(include_str!("../../testdata/proof.json"))
             .unwrap();
     }
+
+    #[test]
+    fn can_verify_proof() {
+        let proof: EIP1186ProofResponse =
+            serde_json::from_str(include_str!("../../testdata/proof.json")).unwrap();
+        let state_root = H256(keccak256(&proof.account_proof[0]));
+        proof.verify(state_root).unwrap();
+
+        // wrong root
+        assert!(matches!(
+            proof.verify(H256::zero()),
+            Err(ProofError::HashMismatch { index: 0, .. })
+        ));
+
+        // tampered account
+        let mut tampered = proof.clone();
+        tampered.balance = 1u64.into();
+        assert!(matches!(tampered.verify(state_root), Err(ProofError::ValueMismatch)));
+
+        // tampered storage, the slot is empty
+        let mut tampered = proof.clone();
+        tampered.storage_proof[0].value = 1u64.into();
+        assert!(matches!(tampered.verify(state_root), Err(ProofError::ValueMismatch)));
+
+        // incomplete proof
+        let mut tampered = proof;
+        tampered.account_proof.pop();
+        assert!(matches!(tampered.verify(state_root), Err(ProofError::Incomplete)));
+    }
+
+    #[test]
+    fn can_decode_hex_prefix_path() {
+        assert_eq!(decode_path(&[0x00, 0x12]), (false, vec![1, 2]));
+        assert_eq!(decode_path(&[0x11, 0x23]), (false, vec![1, 2, 3]));
+        assert_eq!(decode_path(&[0x20, 0x12]), (true, vec![1, 2]));
+        assert_eq!(decode_path(&[0x3f]), (true, vec![0xf]));
+    }
 }
@@ -0,0 +1,91 @@
+//! Types for constructing a
+//! [state override set](https://geth.ethereum.org/docs/rpc/ns-eth#3-object---state-override-set),
+//! as accepted by `eth_call`, `debug_traceCall` and `eth_simulateV1`
+
+use crate::types::{Address, Bytes, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The state elements to override for a particular account.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<Storage>,
+}
+
+impl Account {
+    /// Override the account nonce
+    pub fn nonce(&mut self, nonce: U64) -> &mut Self {
+        self.nonce = Some(nonce);
+        self
+    }
+    /// Override the account balance
+    pub fn balance(&mut self, bal: U256) -> &mut Self {
+        self.balance = Some(bal);
+        self
+    }
+    /// Override the code at the account
+    pub fn code(&mut self, code: Bytes) -> &mut Self {
+        self.code = Some(code);
+        self
+    }
+    /// Override the value of the account storage at the given storage `key`
+    pub fn store(&mut self, key: H256, val: H256) -> &mut Self {
+        self.storage.get_or_insert_with(Default::default).insert(key, val);
+        self
+    }
+}
+
+/// Wraps a map from storage slot to the overriden value.
+///
+/// Storage overrides can either replace the existing state of an account or they can be treated
+/// as a diff on the existing state.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Storage {
+    #[serde(rename = "stateDiff")]
+    Diff(HashMap<H256, H256>),
+    #[serde(rename = "state")]
+    Replace(HashMap<H256, H256>),
+}
+
+/// The default storage override is a diff on the existing state of the account.
+impl Default for Storage {
+    fn default() -> Self {
+        Self::Diff(Default::default())
+    }
+}
+impl std::ops::Deref for Storage {
+    type Target = HashMap<H256, H256>;
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Diff(map) => map,
+            Self::Replace(map) => map,
+        }
+    }
+}
+impl std::ops::DerefMut for Storage {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Diff(map) => map,
+            Self::Replace(map) => map,
+        }
+    }
+}
+
+/// A wrapper type that holds a complete state override set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct State(#[serde(skip_serializing_if = "HashMap::is_empty")] HashMap<Address, Account>);
+
+impl State {
+    /// Returns a mutable reference to the [`Account`] in the map.
+    pub fn account(&mut self, adr: Address) -> &mut Account {
+        self.0.entry(adr).or_default()
+    }
+}
@@ -7,9 +7,37 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum SyncingStatus {
     /// When client is synced to highest block, eth_syncing with return string "false"
-    IsFalse,
-    /// When client is still syncing past blocks we get IsSyncing information.
+    NotSyncing,
+    /// When client is still syncing past blocks we get IsSyncing information, including the
+    /// snap sync progress of geth.
     IsSyncing(Box<SyncProgress>),
+    /// The progress of the stages of the staged sync of Erigon
+    Staged(Box<StagedSyncProgress>),
+}
+
+impl SyncingStatus {
+    /// Returns true if the node is still syncing
+    pub fn is_syncing(&self) -> bool {
+        !matches!(self, SyncingStatus::NotSyncing)
+    }
+
+    /// Returns the block the node has synced to, `None` if it's not syncing
+    pub fn current_block(&self) -> Option<U64> {
+        match self {
+            SyncingStatus::NotSyncing => None,
+            SyncingStatus::IsSyncing(sync) => Some(sync.current_block),
+            SyncingStatus::Staged(sync) => Some(sync.current_block),
+        }
+    }
+
+    /// Returns the estimated highest block of the chain, `None` if the node is not syncing
+    pub fn highest_block(&self) -> Option<U64> {
+        match self {
+            SyncingStatus::NotSyncing => None,
+            SyncingStatus::IsSyncing(sync) => Some(sync.highest_block),
+            SyncingStatus::Staged(sync) => Some(sync.highest_block),
+        }
+    }
 }
 
 impl Serialize for SyncingStatus {
@@ -18,8 +46,9 @@ impl Serialize for SyncingStatus {
         S: Serializer,
     {
         match self {
-            SyncingStatus::IsFalse => serializer.serialize_bool(false),
+            SyncingStatus::NotSyncing => serializer.serialize_bool(false),
             SyncingStatus::IsSyncing(sync) => sync.serialize(serializer),
+            SyncingStatus::Staged(sync) => sync.serialize(serializer),
         }
     }
 }
@@ -34,16 +63,19 @@ impl<'de> Deserialize<'de> for SyncingStatus {
         pub enum SyncingStatusIntermediate {
             /// When client is synced to the highest block, eth_syncing with return string "false"
             IsFalse(bool),
+            /// Erigon's response has `stages`, which no other client returns
+            Staged(Box<StagedSyncProgress>),
             /// When client is still syncing past blocks we get IsSyncing information.
             IsSyncing(Box<SyncProgress>),
         }
 
         match SyncingStatusIntermediate::deserialize(deserializer)? {
-            SyncingStatusIntermediate::IsFalse(false) => Ok(SyncingStatus::IsFalse),
+            SyncingStatusIntermediate::IsFalse(false) => Ok(SyncingStatus::NotSyncing),
             SyncingStatusIntermediate::IsFalse(true) => Err(serde::de::Error::custom(
                 "eth_syncing returned `true` that is undefined value.",
             )),
             SyncingStatusIntermediate::IsSyncing(sync) => Ok(SyncingStatus::IsSyncing(sync)),
+            SyncingStatusIntermediate::Staged(sync) => Ok(SyncingStatus::Staged(sync)),
         }
     }
 }
@@ -96,6 +128,43 @@ pub struct SyncProgress {
     pub synced_storage: Option<U64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub synced_storage_bytes: Option<U64>,
+    /// The number of blocks whose transactions are indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_index_finished_blocks: Option<U64>,
+    /// The number of blocks whose transactions are yet to be indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_index_remaining_blocks: Option<U64>,
+    /// The number of blocks whose state history is yet to be indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_index_remaining: Option<U64>,
+}
+
+/// The sync status of Erigon, which syncs in [stages](https://github.com/erigontech/erigon/blob/main/eth/stagedsync/README.md)
+/// that each process all blocks up to the highest block before the next stage starts
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedSyncProgress {
+    pub current_block: U64,
+    pub highest_block: U64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starting_block: Option<U64>,
+    /// The stages, in the order they are executed
+    pub stages: Vec<SyncStage>,
+}
+
+impl StagedSyncProgress {
+    /// Returns the block the stage with the given name has processed
+    pub fn stage(&self, name: &str) -> Option<U64> {
+        self.stages.iter().find(|stage| stage.stage_name == name).map(|stage| stage.block_number)
+    }
+}
+
+/// A stage of the staged sync of Erigon, e.g. `Headers`, `Bodies` or `Execution`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SyncStage {
+    pub stage_name: String,
+    /// The block the stage has processed
+    pub block_number: U64,
 }
 
 #[cfg(test)]
@@ -120,12 +189,14 @@ mod tests {
         "syncedBytecodeBytes": "0xdec39008",
         "syncedBytecodes": "0x8d407",
         "syncedStorage": "0x2a517da1",
-        "syncedStorageBytes": "0x23634dbedf"
+        "syncedStorageBytes": "0x23634dbedf",
+        "txIndexFinishedBlocks": "0xea97ee",
+        "txIndexRemainingBlocks": "0x0"
     }"#;
 
         let sync: SyncingStatus = serde_json::from_str(s).unwrap();
         match sync {
-            SyncingStatus::IsFalse => {
+            SyncingStatus::NotSyncing | SyncingStatus::Staged(_) => {
                 panic!("unexpected variant")
             }
             SyncingStatus::IsSyncing(_) => {}
@@ -142,21 +213,50 @@ mod tests {
 
         let sync: SyncingStatus = serde_json::from_str(s).unwrap();
         match sync {
-            SyncingStatus::IsFalse => {
+            SyncingStatus::NotSyncing | SyncingStatus::Staged(_) => {
                 panic!("unexpected variant")
             }
             SyncingStatus::IsSyncing(_) => {}
         }
     }
 
+    #[test]
+    fn deserialize_sync_erigon() {
+        let s = r#"{
+        "currentBlock": "0x0",
+        "highestBlock": "0x12a2b71",
+        "stages": [
+            { "stage_name": "Snapshots", "block_number": "0x12a1f40" },
+            { "stage_name": "Headers", "block_number": "0x12a2b71" },
+            { "stage_name": "Execution", "block_number": "0x0" }
+        ]
+    }"#;
+
+        let sync: SyncingStatus = serde_json::from_str(s).unwrap();
+        assert!(sync.is_syncing());
+        assert_eq!(sync.highest_block(), Some(0x12a2b71.into()));
+        match &sync {
+            SyncingStatus::Staged(sync) => {
+                assert_eq!(sync.stages.len(), 3);
+                assert_eq!(sync.stage("Headers"), Some(0x12a2b71.into()));
+                assert_eq!(sync.stage("Bodies"), None);
+            }
+            _ => panic!("unexpected variant"),
+        }
+        assert_eq!(
+            serde_json::from_value::<SyncingStatus>(serde_json::to_value(&sync).unwrap()).unwrap(),
+            sync
+        );
+    }
+
     #[test]
     fn deserialize_sync_false() {
         let s = r#"false"#;
 
         let sync: SyncingStatus = serde_json::from_str(s).unwrap();
         match sync {
-            SyncingStatus::IsFalse => {}
-            SyncingStatus::IsSyncing(_) => {
+            SyncingStatus::NotSyncing => {}
+            SyncingStatus::IsSyncing(_) | SyncingStatus::Staged(_) => {
                 panic!("unexpected variant")
             }
         }
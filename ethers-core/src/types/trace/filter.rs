@@ -111,6 +111,38 @@ pub struct Trace {
     pub error: Option<String>,
 }
 
+impl Trace {
+    /// Returns the sender, recipient and amount of ether that was moved by this trace, if it moved
+    /// a non-zero amount and did not fail.
+    ///
+    /// Together with [`trace_block`] or [`trace_transaction`] this yields the internal transfers
+    /// made by contracts, which are not visible from the transactions alone.
+    ///
+    /// Delegate calls and call codes never move ether to another account and block rewards are
+    /// minted, so neither of them is a transfer.
+    ///
+    /// [`trace_block`]: https://openethereum.github.io/JSONRPC-trace-module#trace_block
+    /// [`trace_transaction`]: https://openethereum.github.io/JSONRPC-trace-module#trace_transaction
+    pub fn value_transfer(&self) -> Option<(Address, Address, U256)> {
+        if self.error.is_some() {
+            return None
+        }
+        let (from, to, value) = match &self.action {
+            Action::Call(call) => match call.call_type {
+                CallType::DelegateCall | CallType::CallCode => return None,
+                _ => (call.from, call.to, call.value),
+            },
+            Action::Create(create) => match &self.result {
+                Some(Res::Create(res)) => (create.from, res.address, create.value),
+                _ => return None,
+            },
+            Action::Suicide(suicide) => (suicide.address, suicide.refund_address, suicide.balance),
+            Action::Reward(_) => return None,
+        };
+        (!value.is_zero()).then_some((from, to, value))
+    }
+}
+
 /// Response
 #[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -476,4 +508,33 @@ mod tests {
         let _trace: Trace = serde_json::from_str(EXAMPLE_TRACE_SUICIDE).unwrap();
         let _trace: Trace = serde_json::from_str(EXAMPLE_TRACE_REWARD).unwrap();
     }
+
+    #[test]
+    fn test_trace_value_transfer() {
+        let mut trace: Trace = serde_json::from_str(EXAMPLE_TRACE_CALL).unwrap();
+        assert_eq!(trace.value_transfer(), None);
+
+        let call = match &mut trace.action {
+            Action::Call(call) => call,
+            _ => unreachable!(),
+        };
+        call.value = 100.into();
+        let transfer = (call.from, call.to, call.value);
+        assert_eq!(trace.value_transfer(), Some(transfer));
+
+        trace.error = Some("Reverted".to_string());
+        assert_eq!(trace.value_transfer(), None);
+
+        let mut trace: Trace = serde_json::from_str(EXAMPLE_TRACE_SUICIDE).unwrap();
+        let suicide = match &mut trace.action {
+            Action::Suicide(suicide) => suicide,
+            _ => unreachable!(),
+        };
+        suicide.balance = 1.into();
+        let transfer = (suicide.address, suicide.refund_address, suicide.balance);
+        assert_eq!(trace.value_transfer(), Some(transfer));
+
+        let trace: Trace = serde_json::from_str(EXAMPLE_TRACE_REWARD).unwrap();
+        assert_eq!(trace.value_transfer(), None);
+    }
 }
@@ -2,15 +2,17 @@ mod call;
 mod four_byte;
 mod noop;
 mod pre_state;
+mod storage_range;
 
 pub use self::{
     call::{CallConfig, CallFrame},
     four_byte::FourByteFrame,
     noop::NoopFrame,
     pre_state::{PreStateConfig, PreStateFrame},
+    storage_range::{StorageEntry, StorageRangeResult},
 };
 use crate::{
-    types::{Bytes, H256, U256},
+    types::{spoof, BlockOverrides, Bytes, H256, U256},
     utils::from_int_or_hex,
 };
 use serde::{Deserialize, Serialize};
@@ -179,6 +181,34 @@ pub struct GethDebugTracingOptions {
     pub timeout: Option<String>,
 }
 
+impl GethDebugTracingOptions {
+    /// Returns the options to trace with the built-in `callTracer` and the given `config`.
+    pub fn call_tracer(config: CallConfig) -> Self {
+        Self {
+            tracer: Some(GethDebugTracerType::BuiltInTracer(
+                GethDebugBuiltInTracerType::CallTracer,
+            )),
+            tracer_config: Some(GethDebugTracerConfig::BuiltInTracer(
+                GethDebugBuiltInTracerConfig::CallTracer(config),
+            )),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the options to trace with the built-in `prestateTracer` and the given `config`.
+    pub fn prestate_tracer(config: PreStateConfig) -> Self {
+        Self {
+            tracer: Some(GethDebugTracerType::BuiltInTracer(
+                GethDebugBuiltInTracerType::PreStateTracer,
+            )),
+            tracer_config: Some(GethDebugTracerConfig::BuiltInTracer(
+                GethDebugBuiltInTracerConfig::PreStateTracer(config),
+            )),
+            ..Default::default()
+        }
+    }
+}
+
 /// Bindings for additional `debug_traceCall` options
 ///
 /// See <https://geth.ethereum.org/docs/rpc/ns-debug#debug_tracecall>
@@ -187,5 +217,65 @@ pub struct GethDebugTracingOptions {
 pub struct GethDebugTracingCallOptions {
     #[serde(flatten)]
     pub tracing_options: GethDebugTracingOptions,
-    // TODO: Add stateoverrides and blockoverrides options
+    /// Account overrides applied before the call is executed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_overrides: Option<spoof::State>,
+    /// Overrides of the header fields of the block the call is executed in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_overrides: Option<BlockOverrides>,
+}
+
+impl GethDebugTracingCallOptions {
+    /// Sets the account overrides applied before the call is executed, e.g. to fund the sender
+    #[must_use]
+    pub fn state_overrides(mut self, state: spoof::State) -> Self {
+        self.state_overrides = Some(state);
+        self
+    }
+
+    /// Sets the overrides of the header fields of the block the call is executed in
+    #[must_use]
+    pub fn block_overrides(mut self, overrides: BlockOverrides) -> Self {
+        self.block_overrides = Some(overrides);
+        self
+    }
+}
+
+impl From<GethDebugTracingOptions> for GethDebugTracingCallOptions {
+    fn from(tracing_options: GethDebugTracingOptions) -> Self {
+        Self { tracing_options, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Address, BlockOverrides};
+
+    #[test]
+    fn test_serialize_call_options_with_overrides() {
+        let sender = Address::repeat_byte(1);
+        let mut state = spoof::State::default();
+        state.account(sender).balance(U256::exp10(18));
+        let opts =
+            GethDebugTracingCallOptions::from(GethDebugTracingOptions::call_tracer(CallConfig {
+                only_top_call: Some(true),
+                with_log: None,
+            }))
+            .state_overrides(state)
+            .block_overrides(BlockOverrides::default().number(100).base_fee_per_gas(0));
+
+        assert_eq!(
+            serde_json::to_value(&opts).unwrap(),
+            serde_json::json!({
+                "tracer": "callTracer",
+                "tracerConfig": { "onlyTopCall": true },
+                "stateOverrides": { format!("{sender:?}"): { "balance": "0xde0b6b3a7640000" } },
+                "blockOverrides": { "number": "0x64", "baseFeePerGas": "0x0" }
+            })
+        );
+        let de: GethDebugTracingCallOptions =
+            serde_json::from_value(serde_json::to_value(&opts).unwrap()).unwrap();
+        assert_eq!(de, opts);
+    }
 }
@@ -0,0 +1,53 @@
+use crate::types::H256;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// https://github.com/ethereum/go-ethereum/blob/a9ef135e2dd53682d106c6a2aede9187026cc1de/eth/api_debug.go#L178-L186
+/// A page of the storage of a contract, returned by `debug_storageRangeAt`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRangeResult {
+    /// The storage entries by the keccak256 hash of their slot, in ascending order
+    pub storage: BTreeMap<H256, StorageEntry>,
+    /// The hash of the slot the next page starts at, `None` if this is the last page
+    pub next_key: Option<H256>,
+}
+
+/// A slot of the storage of a contract and its value
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageEntry {
+    /// The slot, `None` if the node doesn't know the preimage of its hash
+    pub key: Option<H256>,
+    /// The value of the slot
+    pub value: H256,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_storage_range() {
+        let result: StorageRangeResult = serde_json::from_str(
+            r#"{
+                "storage": {
+                    "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563": {
+                        "key": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "value": "0x0000000000000000000000000000000000000000000000000000000000000010"
+                    },
+                    "0xb10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6": {
+                        "key": null,
+                        "value": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                    }
+                },
+                "nextKey": "0xc2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85b"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(result.storage.len(), 2);
+        let (_, first) = result.storage.iter().next().unwrap();
+        assert_eq!(first.key, Some(H256::zero()));
+        assert_eq!(first.value, H256::from_low_u64_be(16));
+        assert!(result.next_key.is_some());
+    }
+}
@@ -73,6 +73,29 @@ pub enum Diff<T> {
     Changed(ChangedType<T>),
 }
 
+impl<T> Diff<T> {
+    /// Returns whether the value didn't change
+    pub fn is_same(&self) -> bool {
+        matches!(self, Diff::Same)
+    }
+
+    /// Returns the value before the change, `None` if the value was set or didn't change
+    pub fn before(&self) -> Option<&T> {
+        match self {
+            Diff::Died(value) | Diff::Changed(ChangedType { from: value, .. }) => Some(value),
+            Diff::Same | Diff::Born(_) => None,
+        }
+    }
+
+    /// Returns the value after the change, `None` if the value was removed or didn't change
+    pub fn after(&self) -> Option<&T> {
+        match self {
+            Diff::Born(value) | Diff::Changed(ChangedType { to: value, .. }) => Some(value),
+            Diff::Same | Diff::Died(_) => None,
+        }
+    }
+}
+
 /// Serde-friendly `AccountDiff` shadow.
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub struct AccountDiff {
@@ -90,6 +113,19 @@ pub struct AccountDiff {
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
 pub struct StateDiff(pub BTreeMap<H160, AccountDiff>);
 
+impl StateDiff {
+    /// Returns the changes of the account, `None` if it wasn't changed
+    pub fn account(&self, address: &H160) -> Option<&AccountDiff> {
+        self.0.get(address)
+    }
+
+    /// Returns the value of the storage slot of the account after the change, `None` if the
+    /// slot wasn't changed
+    pub fn storage_after(&self, address: &H160, slot: &H256) -> Option<&H256> {
+        self.account(address)?.storage.get(slot)?.after()
+    }
+}
+
 // ------------------ Trace -------------
 /// Trace
 #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
@@ -226,6 +262,43 @@ mod tests {
         let _traces: Vec<BlockTrace> = serde_json::from_str(EXAMPLE_TRACES).unwrap();
     }
 
+    #[test]
+    fn test_deserialize_state_diff() {
+        let diff: StateDiff = serde_json::from_str(
+            r#"{
+                "0x0000000000000000000000000000000000000001": {
+                    "balance": {"*": {"from": "0x10", "to": "0x8"}},
+                    "nonce": "=",
+                    "code": "=",
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000002": {
+                            "+": "0x0000000000000000000000000000000000000000000000000000000000000003"
+                        }
+                    }
+                },
+                "0x0000000000000000000000000000000000000004": {
+                    "balance": {"+": "0x1"},
+                    "nonce": {"+": "0x0"},
+                    "code": {"+": "0x"},
+                    "storage": {}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let account = H160::from_low_u64_be(1);
+        let sender = diff.account(&account).unwrap();
+        assert_eq!(sender.balance.before(), Some(&U256::from(0x10)));
+        assert_eq!(sender.balance.after(), Some(&U256::from(8)));
+        assert!(sender.nonce.is_same());
+        let slot = H256::from_low_u64_be(2);
+        assert_eq!(diff.storage_after(&account, &slot), Some(&H256::from_low_u64_be(3)));
+
+        let created = diff.account(&H160::from_low_u64_be(4)).unwrap();
+        assert_eq!(created.balance.before(), None);
+        assert_eq!(created.balance.after(), Some(&U256::one()));
+    }
+
     #[test]
     fn test_deserialize_unknown_opcode() {
         let example_opcodes = r#"["GAS", "CREATE2", "CUSTOMOP"]"#;
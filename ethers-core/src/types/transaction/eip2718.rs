@@ -455,6 +455,23 @@ impl TypedTransaction {
             _ => None,
         }
     }
+
+    /// Converts an EIP-1559 transaction for chains without a base fee, which only accept legacy
+    /// and EIP-2930 transactions. The max fee per gas becomes the gas price, and the transaction
+    /// becomes an EIP-2930 transaction if it has an access list. Other transactions are left
+    /// unchanged.
+    pub fn downgrade_eip1559(&mut self) -> &mut Self {
+        if let Eip1559(inner) = self {
+            let access_list = std::mem::take(&mut inner.access_list);
+            let legacy: TransactionRequest = inner.clone().into();
+            *self = if access_list.0.is_empty() {
+                legacy.into()
+            } else {
+                Eip2930TransactionRequest::new(legacy, access_list).into()
+            };
+        }
+        self
+    }
 }
 
 impl TypedTransaction {
@@ -808,4 +825,23 @@ mod tests {
             assert_eq!(tx0, tx1);
         }
     }
+
+    #[test]
+    fn test_downgrade_eip1559() {
+        let tx = Eip1559TransactionRequest::new().max_fee_per_gas(5).max_priority_fee_per_gas(1);
+        let mut typed: TypedTransaction = tx.clone().into();
+        typed.downgrade_eip1559();
+        assert_eq!(typed, TypedTransaction::Legacy(TransactionRequest::new().gas_price(5)));
+
+        let access_list = AccessList(vec![Default::default()]);
+        let mut typed: TypedTransaction = tx.access_list(access_list.clone()).into();
+        typed.downgrade_eip1559();
+        let expected =
+            Eip2930TransactionRequest::new(TransactionRequest::new().gas_price(5), access_list);
+        assert_eq!(typed, TypedTransaction::Eip2930(expected));
+
+        let mut typed: TypedTransaction = TransactionRequest::new().gas_price(7).into();
+        typed.downgrade_eip1559();
+        assert_eq!(typed.gas_price(), Some(7.into()));
+    }
 }
@@ -118,6 +118,30 @@ pub struct TxpoolContent {
     pub queued: BTreeMap<Address, BTreeMap<String, Transaction>>,
 }
 
+impl TxpoolContent {
+    /// Returns the pending and queued transactions of the given sender
+    pub fn content_from(&self, sender: Address) -> TxpoolContentFrom {
+        TxpoolContentFrom {
+            pending: self.pending.get(&sender).cloned().unwrap_or_default(),
+            queued: self.queued.get(&sender).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// Transaction Pool Content From
+///
+/// Same as [TxpoolContent] but only includes the transactions of a single sender, keyed by their
+/// nonce.
+///
+/// See [here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_contentfrom) for more details
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxpoolContentFrom {
+    /// pending tx
+    pub pending: BTreeMap<String, Transaction>,
+    /// queued tx
+    pub queued: BTreeMap<String, Transaction>,
+}
+
 /// Transaction Pool Inspect
 ///
 /// The inspect inspection property can be queried to list a textual summary
@@ -331,6 +355,23 @@ mod tests {
         assert_eq!(deserialized, serde_json::from_str::<TxpoolContent>(&serialized).unwrap());
     }
 
+    #[test]
+    fn txpool_content_from() {
+        let sender = Address::repeat_byte(1);
+        let tx = Transaction { from: sender, nonce: 3u64.into(), ..Default::default() };
+        let mut content = TxpoolContent::default();
+        content.pending.insert(sender, BTreeMap::from([("3".to_string(), tx.clone())]));
+        content.queued.insert(Address::repeat_byte(2), BTreeMap::new());
+
+        let from = content.content_from(sender);
+        assert_eq!(from.pending.get("3"), Some(&tx));
+        assert!(from.queued.is_empty());
+        assert_eq!(content.content_from(Address::zero()), TxpoolContentFrom::default());
+
+        let json = serde_json::json!({ "pending": { "3": tx }, "queued": {} });
+        assert_eq!(serde_json::from_value::<TxpoolContentFrom>(json).unwrap(), from);
+    }
+
     #[test]
     fn serde_txpool_inspect() {
         let txpool_inspect_json = r#"
@@ -0,0 +1,165 @@
+//! Types of the [ERC-4337](https://eips.ethereum.org/EIPS/eip-4337) bundler RPC used by
+//! account abstraction clients
+
+use crate::{
+    abi::{self, Token},
+    types::{Address, Bytes, Log, TransactionReceipt, H256, U256, U64},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+
+/// A user operation of an account abstraction wallet, as sent to a bundler
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    /// The account making the operation
+    pub sender: Address,
+    /// Anti-replay nonce of the account
+    pub nonce: U256,
+    /// Factory address and calldata to deploy the account, empty if the account already exists
+    pub init_code: Bytes,
+    /// The calldata the account is called with
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    /// Gas paid to the bundler for the overhead not covered by the other limits
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    /// Paymaster address and data, empty if the account pays for itself
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// Returns the hash of the operation, which is what the account signs and what bundlers
+    /// identify the operation by.
+    ///
+    /// The hash commits to the entry point and chain id and does not include the signature.
+    pub fn hash(&self, entry_point: Address, chain_id: U256) -> H256 {
+        let packed = abi::encode(&[
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            Token::FixedBytes(keccak256(&self.init_code).to_vec()),
+            Token::FixedBytes(keccak256(&self.call_data).to_vec()),
+            Token::Uint(self.call_gas_limit),
+            Token::Uint(self.verification_gas_limit),
+            Token::Uint(self.pre_verification_gas),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            Token::FixedBytes(keccak256(&self.paymaster_and_data).to_vec()),
+        ]);
+        keccak256(abi::encode(&[
+            Token::FixedBytes(keccak256(packed).to_vec()),
+            Token::Address(entry_point),
+            Token::Uint(chain_id),
+        ]))
+        .into()
+    }
+}
+
+/// The gas limits estimated by `eth_estimateUserOperationGas`
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGasEstimate {
+    pub pre_verification_gas: U256,
+    pub verification_gas_limit: U256,
+    pub call_gas_limit: U256,
+}
+
+/// A user operation included on chain, as returned by `eth_getUserOperationByHash`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationByHash {
+    pub user_operation: UserOperation,
+    pub entry_point: Address,
+    pub block_number: U64,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+}
+
+/// The receipt of an included user operation, as returned by `eth_getUserOperationReceipt`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    pub user_op_hash: H256,
+    pub entry_point: Address,
+    pub sender: Address,
+    pub nonce: U256,
+    /// The paymaster that paid for the operation, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    /// The gas cost paid by the account or paymaster in wei
+    pub actual_gas_cost: U256,
+    pub actual_gas_used: U256,
+    /// Whether the execution of the operation succeeded
+    pub success: bool,
+    /// The revert reason if the execution failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<Bytes>,
+    /// The logs emitted by the operation
+    pub logs: Vec<Log>,
+    /// The receipt of the bundle transaction that included the operation
+    pub receipt: TransactionReceipt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_user_operation() {
+        let op = UserOperation {
+            sender: Address::repeat_byte(1),
+            nonce: 1u64.into(),
+            call_data: Bytes::from(vec![0xb6, 0x1d, 0x27, 0xf6]),
+            call_gas_limit: 100_000u64.into(),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&op).unwrap();
+        assert_eq!(value["callData"], "0xb61d27f6");
+        assert_eq!(value["callGasLimit"], "0x186a0");
+        assert_eq!(value["paymasterAndData"], "0x");
+        assert_eq!(serde_json::from_value::<UserOperation>(value).unwrap(), op);
+    }
+
+    #[test]
+    fn hash_matches_entry_point() {
+        // `EntryPoint.getUserOpHash` of v0.6: `keccak256(abi.encode(keccak256(pack(op)),
+        // entryPoint, chainid))`, computed independently of this implementation
+        let op = UserOperation {
+            sender: "0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap(),
+            nonce: 3u64.into(),
+            init_code: "0x9406cc6185a346906296840746125a0e449764545fbfb9cf000000000000000000000000ce0fefa6f7979c4c9b5373e0f5105b7259092c6d0000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_data: "0xb61d27f6000000000000000000000000690b9a9e9aa1c9db991c7721a92d351db4fac990000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_gas_limit: 200_000u64.into(),
+            verification_gas_limit: 100_000u64.into(),
+            pre_verification_gas: 21_000u64.into(),
+            max_fee_per_gas: 3_000_000_000u64.into(),
+            max_priority_fee_per_gas: 1_000_000_000u64.into(),
+            paymaster_and_data: "0xe93eca6595fe94091dc1af46aac2a8b5d79907700000000000000000000000000000000000000000000000000000000000000001".parse().unwrap(),
+            signature: Bytes::from(vec![1; 65]),
+        };
+        let entry_point: Address = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".parse().unwrap();
+        assert_eq!(
+            op.hash(entry_point, 1u64.into()),
+            "0xf2841a8736f3cab35cac354b48aa64f3096475522e7f245ffd7424c16f21f8d9".parse().unwrap()
+        );
+        assert_eq!(
+            op.hash(entry_point, 137u64.into()),
+            "0x6f9201dce9554f8ee04637ec93c99a21594bf27e1b61b1f1ef268f3ea9634526".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn hash_excludes_signature() {
+        let entry_point: Address = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".parse().unwrap();
+        let op = UserOperation { sender: Address::repeat_byte(1), ..Default::default() };
+        let hash = op.hash(entry_point, 1u64.into());
+
+        let signed = UserOperation { signature: Bytes::from(vec![1; 65]), ..op.clone() };
+        assert_eq!(signed.hash(entry_point, 1u64.into()), hash);
+        assert_ne!(op.hash(entry_point, 5u64.into()), hash);
+        assert_ne!(op.hash(Address::zero(), 1u64.into()), hash);
+    }
+}
@@ -30,6 +30,10 @@ mod units;
 use serde::{Deserialize, Deserializer};
 pub use units::Units;
 
+/// Utilities to compute the storage slots of state variables
+mod storage;
+pub use storage::{dynamic_array_slot, mapping_slot, PackedField};
+
 /// Re-export RLP
 pub use rlp;
 
@@ -382,6 +386,30 @@ pub fn get_create2_address_from_hash(
     Address::from(bytes)
 }
 
+/// The init code of the proxy that is deployed with CREATE2 by the CREATE3 pattern, which in turn
+/// deploys the actual contract with CREATE.
+///
+/// See <https://github.com/transmissions11/solmate/blob/main/src/utils/CREATE3.sol>
+pub const CREATE3_PROXY_INIT_CODE: [u8; 16] = [
+    0x67, 0x36, 0x3d, 0x3d, 0x37, 0x36, 0x3d, 0x34, 0xf0, 0x3d, 0x52, 0x60, 0x08, 0x60, 0x18, 0xf3,
+];
+
+/// Returns the CREATE3 address of a smart contract deployed by `deployer` with the given `salt`.
+///
+/// Unlike CREATE2, the address does not depend on the contract's init code: `deployer` first
+/// deploys a minimal proxy with CREATE2, which then deploys the contract with CREATE as its first
+/// transaction.
+///
+/// Note that `deployer` is the account executing the CREATE2, i.e. the CREATE3 factory contract,
+/// and factories commonly derive the actual salt from the caller and the user provided salt.
+///
+/// keccak256(rlp([keccak256(0xff ++ deployer ++ salt ++ keccak256(proxy_init_code))[12..],
+/// 1]))[12..]
+pub fn get_create3_address(deployer: impl Into<Address>, salt: impl AsRef<[u8]>) -> Address {
+    let proxy = get_create2_address_from_hash(deployer, salt, keccak256(CREATE3_PROXY_INIT_CODE));
+    get_contract_address(proxy, 1)
+}
+
 /// Converts a K256 SigningKey to an Ethereum Address
 pub fn secret_key_to_address(secret_key: &SigningKey) -> Address {
     let public_key = K256PublicKey::from(&secret_key.verifying_key());
@@ -459,13 +487,18 @@ pub fn eip1559_default_estimator(base_fee_per_gas: U256, rewards: Vec<Vec<U256>>
                 U256::from(EIP1559_FEE_ESTIMATION_DEFAULT_PRIORITY_FEE),
             )
         };
+    (eip1559_max_fee(base_fee_per_gas, max_priority_fee_per_gas), max_priority_fee_per_gas)
+}
+
+/// Returns the max fee per gas of the [default estimator](eip1559_default_estimator) for the
+/// given max priority fee per gas, which leaves room for the base fee to rise
+pub fn eip1559_max_fee(base_fee_per_gas: U256, max_priority_fee_per_gas: U256) -> U256 {
     let potential_max_fee = base_fee_surged(base_fee_per_gas);
-    let max_fee_per_gas = if max_priority_fee_per_gas > potential_max_fee {
+    if max_priority_fee_per_gas > potential_max_fee {
         max_priority_fee_per_gas + potential_max_fee
     } else {
         potential_max_fee
-    };
-    (max_fee_per_gas, max_priority_fee_per_gas)
+    }
 }
 
 /// Deserializes the input into a U256, accepting both 0x-prefixed hex and decimal strings with
@@ -981,6 +1014,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn create3_address() {
+        assert_eq!(
+            keccak256(CREATE3_PROXY_INIT_CODE),
+            hex!("21c35dbe1b344a2488cf3321d6ce542f8e9f305544ff09e4993a62319a497c1f")
+        );
+
+        let deployer = "0x9fBB3DF7C40Da2e5A0dE984fFE2CCB7C47cd0ABf".parse::<Address>().unwrap();
+        let salt = keccak256("salt");
+        let proxy = get_create2_address(deployer, salt, CREATE3_PROXY_INIT_CODE);
+        assert_eq!(get_create3_address(deployer, salt), get_contract_address(proxy, 1));
+        assert_ne!(get_create3_address(deployer, salt), get_create3_address(deployer, [1u8; 32]));
+    }
+
     #[test]
     fn bytes32_string_parsing() {
         let text_bytes_list = vec![
@@ -0,0 +1,162 @@
+//! Utilities to compute the storage slots of Solidity state variables, see
+//! <https://docs.soliditylang.org/en/latest/internals/layout_in_storage.html>
+
+use super::keccak256;
+use crate::{
+    abi::{self, Token, Tokenizable},
+    types::{H256, U256},
+};
+
+/// Returns the slot of the value at `key` in the mapping at `slot`.
+///
+/// This is `keccak256(key . slot)` where value type keys are padded to 32 bytes and `string` and
+/// `bytes` keys are used as they are.
+///
+/// # Example
+///
+/// ```
+/// use ethers_core::{types::{Address, U256}, utils::mapping_slot};
+///
+/// // `balances[holder]` of `mapping(address => uint256) balances` declared in slot 0
+/// let holder: Address = "0x6b175474e89094c44da98b954eedeac495271d0f".parse().unwrap();
+/// let slot = mapping_slot(holder, U256::zero());
+/// ```
+pub fn mapping_slot<K: Tokenizable>(key: K, slot: U256) -> H256 {
+    let mut preimage = match key.into_token() {
+        Token::String(key) => key.into_bytes(),
+        Token::Bytes(key) => key,
+        key => abi::encode(&[key]),
+    };
+    preimage.extend_from_slice(&slot_bytes(slot));
+    keccak256(preimage).into()
+}
+
+/// Returns the slot of the element at `index` of the dynamic array at `slot`, whose elements take
+/// up `element_slots` slots each.
+///
+/// The elements are stored from `keccak256(slot)` on, which is also where the data of long
+/// `string` and `bytes` values starts. Elements of at most 16 bytes share slots, for them pass the
+/// index of the slot, `index / (32 / size)` with an `element_slots` of 1, and read the element
+/// with a [`PackedField`].
+pub fn dynamic_array_slot(slot: U256, index: U256, element_slots: u64) -> H256 {
+    let start = U256::from_big_endian(&keccak256(slot_bytes(slot)));
+    let slot = start.overflowing_add(index.overflowing_mul(element_slots.into()).0).0;
+    H256(slot_bytes(slot))
+}
+
+/// The position of a member of a struct, or of a state variable, that may share its slot with
+/// other members.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PackedField {
+    /// The slot of the member, relative to the slot of the struct
+    pub slot: u64,
+    /// The offset of the member in the slot in bytes, counted from the lowest-order byte
+    pub offset: usize,
+    /// The size of the member in bytes
+    pub size: usize,
+}
+
+impl PackedField {
+    /// Returns the positions of the members of a struct with the given sizes in bytes, in order
+    /// of declaration, e.g. `[20, 12, 32]` for `struct { address a; uint96 b; uint256 c; }`.
+    ///
+    /// Members are packed into a slot as long as they fit. Nested structs and static arrays
+    /// always start a new slot and the next member starts a new slot as well, their size should
+    /// be rounded up to a multiple of 32.
+    pub fn layout(sizes: &[usize]) -> Vec<PackedField> {
+        let mut fields = Vec::with_capacity(sizes.len());
+        let (mut slot, mut offset) = (0u64, 0usize);
+        for &size in sizes {
+            if offset > 0 && (offset + size > 32 || size > 32) {
+                slot += 1;
+                offset = 0;
+            }
+            fields.push(PackedField { slot, offset, size });
+            if size >= 32 {
+                slot += ((size + 31) / 32) as u64;
+                offset = 0;
+            } else {
+                offset += size;
+            }
+        }
+        fields
+    }
+
+    /// Returns the absolute slot of the member of the struct at `base`
+    pub fn slot_at(&self, base: U256) -> H256 {
+        H256(slot_bytes(base.overflowing_add(self.slot.into()).0))
+    }
+
+    /// Returns the value of the member in the word read from its slot, right-aligned.
+    ///
+    /// Members of 32 bytes or more are read from their first word. Returns `None` if the member
+    /// does not fit into the word after its offset.
+    pub fn read(&self, word: H256) -> Option<U256> {
+        let size = self.size.min(32);
+        let end = 32usize.checked_sub(self.offset)?;
+        let start = end.checked_sub(size)?;
+        Some(U256::from_big_endian(&word.as_bytes()[start..end]))
+    }
+}
+
+/// Returns the slot as a 32 byte big-endian word
+fn slot_bytes(slot: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    slot.to_big_endian(&mut bytes);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Address;
+    use hex_literal::hex;
+
+    #[test]
+    fn computes_mapping_slots() {
+        // `balances[holder]` of a mapping in slot 0
+        let holder: Address = "0x0000000000000000000000000000000000000001".parse().unwrap();
+        let mut preimage = [0u8; 64];
+        preimage[31] = 1;
+        assert_eq!(mapping_slot(holder, U256::zero()), H256(keccak256(preimage)));
+
+        let mut preimage = b"key".to_vec();
+        preimage.extend_from_slice(&slot_bytes(U256::from(2)));
+        assert_eq!(mapping_slot("key".to_string(), U256::from(2)), H256(keccak256(preimage)));
+
+        // nested mappings hash the slot of the outer value
+        let inner = mapping_slot(U256::from(7), U256::one());
+        let outer = mapping_slot(holder, U256::from_big_endian(inner.as_bytes()));
+        assert_ne!(inner, outer);
+    }
+
+    #[test]
+    fn computes_dynamic_array_slots() {
+        // keccak256(uint256(0))
+        let start = H256(hex!("290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"));
+        assert_eq!(dynamic_array_slot(U256::zero(), U256::zero(), 1), start);
+        let third = dynamic_array_slot(U256::zero(), U256::from(2), 3);
+        assert_eq!(
+            U256::from_big_endian(third.as_bytes()),
+            U256::from_big_endian(start.as_bytes()) + 6
+        );
+    }
+
+    #[test]
+    fn packs_struct_members() {
+        // struct { address a; uint96 b; uint128 c; bool d; uint256 e; uint8[3] f; bool g; }
+        let fields = PackedField::layout(&[20, 12, 16, 1, 32, 32, 1]);
+        let positions: Vec<_> = fields.iter().map(|field| (field.slot, field.offset)).collect();
+        assert_eq!(positions, vec![(0, 0), (0, 20), (1, 0), (1, 16), (2, 0), (3, 0), (4, 0)]);
+
+        let word = H256(hex!("000000000000000000000000000000000000000000000000000000000000ff00"));
+        assert_eq!(PackedField { slot: 0, offset: 1, size: 1 }.read(word), Some(U256::from(0xff)));
+        assert_eq!(
+            PackedField { slot: 0, offset: 0, size: 64 }.read(word),
+            Some(U256::from(0xff00))
+        );
+        assert_eq!(PackedField { slot: 0, offset: 20, size: 20 }.read(word), None);
+        assert_eq!(PackedField { slot: 0, offset: 33, size: 1 }.read(word), None);
+        assert_eq!(fields[2].slot_at(U256::from(10)), H256::from_low_u64_be(11));
+    }
+}
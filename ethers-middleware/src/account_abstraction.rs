@@ -0,0 +1,656 @@
+use async_trait::async_trait;
+use auto_impl::auto_impl;
+use ethers_contract::{AbiError, BaseContract};
+use ethers_core::{
+    abi::{self, parse_abi, Token},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockId, Bytes, NameOrAddress,
+        TransactionRequest, UserOperation, UserOperationReceipt, H160, H256, U256,
+    },
+    utils::id,
+};
+use ethers_providers::{
+    interval, BundlerApi, FromErr, JsonRpcClient, Middleware, PendingTransaction, Provider,
+    ProviderError, StreamExt,
+};
+use ethers_signers::Signer;
+use futures_util::lock::Mutex;
+use instant::Instant;
+use std::{error::Error, fmt::Debug, ops::Deref, time::Duration};
+use thiserror::Error;
+
+/// The address of the v0.6 `EntryPoint` contract, which is deployed at the same address on all
+/// chains
+pub const ENTRY_POINT: Address = H160([
+    0x5f, 0xf1, 0x37, 0xd4, 0xb0, 0xfd, 0xcd, 0x49, 0xdc, 0xa3, 0x0c, 0x7c, 0xf5, 0x7e, 0x57, 0x8a,
+    0x02, 0x6d, 0x27, 0x89,
+]);
+
+/// The function of the `EntryPoint` that returns the nonce of an account.
+const GET_NONCE: &str =
+    "function getNonce(address sender, uint192 key) external view returns (uint256 nonce)";
+
+/// The signature user operations are estimated with, accounts must not revert when validating it
+const DUMMY_SIGNATURE: [u8; 65] = {
+    let mut signature = [0xff; 65];
+    signature[64] = 0x1c;
+    signature
+};
+
+/// Encodes a call of the account to `execute(address dest, uint256 value, bytes func)`, the
+/// function of the reference `SimpleAccount` and of most accounts derived from it
+pub fn simple_account_execute(to: Address, value: U256, data: Bytes) -> Bytes {
+    let mut call_data = id("execute(address,uint256,bytes)").to_vec();
+    call_data.extend(abi::encode(&[
+        Token::Address(to),
+        Token::Uint(value),
+        Token::Bytes(data.to_vec()),
+    ]));
+    call_data.into()
+}
+
+/// Provides the `paymaster_and_data` of user operations, for paymasters that sponsor their gas.
+///
+/// The hook is called twice for every operation: with the dummy signature before its gas is
+/// estimated, so the estimate covers the validation of the paymaster, and once more after the
+/// gas limits and fees are set, so a paymaster signing the operation commits to them.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[auto_impl(&, Box, Arc)]
+pub trait PaymasterHook: Send + Sync + Debug {
+    /// Returns the paymaster address and data of the operation, empty if the account pays for it
+    async fn paymaster_and_data(
+        &self,
+        op: &UserOperation,
+        entry_point: Address,
+        chain_id: U256,
+    ) -> Result<Bytes, Box<dyn Error + Send + Sync>>;
+}
+
+/// A user operation that was submitted to a bundler, awaiting [`PendingUserOperation::wait`]
+/// resolves to its receipt once it is included in a bundle.
+#[derive(Debug)]
+pub struct PendingUserOperation<'a, P> {
+    hash: H256,
+    bundler: &'a Provider<P>,
+    timeout: Duration,
+}
+
+impl<'a, P: JsonRpcClient> PendingUserOperation<'a, P> {
+    /// Creates a handle of the operation with the hash, which was submitted to the `bundler`
+    pub fn new(hash: H256, bundler: &'a Provider<P>) -> Self {
+        Self { hash, bundler, timeout: Duration::from_secs(300) }
+    }
+
+    /// Returns the hash of the operation
+    pub fn hash(&self) -> H256 {
+        self.hash
+    }
+
+    /// Sets how long [`PendingUserOperation::wait`] waits for the operation to be included
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Polls the bundler at the interval of its provider until the operation is included,
+    /// returning its receipt, or `None` if it wasn't included before the timeout
+    pub async fn wait(self) -> Result<Option<UserOperationReceipt>, ProviderError> {
+        let started = Instant::now();
+        let mut poll = interval(self.bundler.get_interval());
+        loop {
+            if let Some(receipt) = self.bundler.get_user_operation_receipt(self.hash).await? {
+                return Ok(Some(receipt))
+            }
+            if started.elapsed() >= self.timeout {
+                return Ok(None)
+            }
+            poll.next().await;
+        }
+    }
+}
+
+impl<'a, P> Deref for PendingUserOperation<'a, P> {
+    type Target = H256;
+
+    fn deref(&self) -> &H256 {
+        &self.hash
+    }
+}
+
+#[derive(Debug)]
+/// Middleware that sends transactions as ERC-4337 user operations of a smart account, so
+/// account abstraction wallets work with contract bindings and everything else built on
+/// [`Middleware::send_transaction`].
+///
+/// A transaction is converted into a user operation calling the account with the recipient,
+/// value and data of the transaction, encoded by [`simple_account_execute`] unless another
+/// encoder is set. The nonce of the operation is taken from the transaction, or fetched once from
+/// the `EntryPoint` and then counted locally like the
+/// [`NonceManagerMiddleware`](crate::NonceManagerMiddleware) does, so concurrent operations
+/// don't collide, nonces of operations that fail before they are submitted are given back. Its
+/// gas limits are estimated by the bundler, and its fees are taken from the transaction or
+/// estimated by the inner middleware. The owner of the account signs the hash of the operation
+/// as a message.
+///
+/// [`AccountAbstractionMiddleware::send_user_operation`] submits operations to the bundler and
+/// returns a [`PendingUserOperation`] without waiting for their inclusion. The bundle
+/// transaction of an operation is only known once it is included, so
+/// [`Middleware::send_transaction`] waits for the inclusion to return the pending bundle
+/// transaction.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::account_abstraction::AccountAbstractionMiddleware;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let bundler = Provider::<Http>::try_from("http://localhost:4337")?;
+/// let owner: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let account: Address = "0x0000000000000000000000000000000000004337".parse()?;
+/// let client = AccountAbstractionMiddleware::new(provider, bundler, owner, account);
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100);
+/// let receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AccountAbstractionMiddleware<M, P, S> {
+    inner: M,
+    bundler: Provider<P>,
+    signer: S,
+    account: Address,
+    entry_point: Address,
+    entry_point_contract: BaseContract,
+    init_code: Bytes,
+    paymaster: Option<Box<dyn PaymasterHook>>,
+    encode_call: fn(Address, U256, Bytes) -> Bytes,
+    receipt_timeout: Duration,
+    /// The next nonce of the account, fetched from the `EntryPoint` if `None`
+    next_nonce: Mutex<Option<U256>>,
+}
+
+impl<M, P, S> AccountAbstractionMiddleware<M, P, S>
+where
+    M: Middleware,
+    P: JsonRpcClient,
+    S: Signer,
+{
+    /// Creates a middleware sending user operations of the `account`, owned by the `signer`, to
+    /// the `bundler` for the [`ENTRY_POINT`]
+    pub fn new(inner: M, bundler: Provider<P>, signer: S, account: Address) -> Self {
+        let entry_point_contract = parse_abi(&[GET_NONCE]).expect("could not parse ABI").into();
+        Self {
+            inner,
+            bundler,
+            signer,
+            account,
+            entry_point: ENTRY_POINT,
+            entry_point_contract,
+            init_code: Bytes::default(),
+            paymaster: None,
+            encode_call: simple_account_execute,
+            receipt_timeout: Duration::from_secs(300),
+            next_nonce: Mutex::new(None),
+        }
+    }
+
+    /// Sets the `EntryPoint` the operations are sent for
+    #[must_use]
+    pub fn entry_point(mut self, entry_point: Address) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
+
+    /// Sets the factory address and calldata deploying the account, used until it is deployed
+    #[must_use]
+    pub fn init_code(mut self, init_code: impl Into<Bytes>) -> Self {
+        self.init_code = init_code.into();
+        self
+    }
+
+    /// Sets the hook providing the paymaster data of the operations
+    #[must_use]
+    pub fn paymaster(mut self, paymaster: impl PaymasterHook + 'static) -> Self {
+        self.paymaster = Some(Box::new(paymaster));
+        self
+    }
+
+    /// Sets how the recipient, value and data of transactions are encoded into the calldata of
+    /// the account
+    #[must_use]
+    pub fn encode_call(mut self, encode_call: fn(Address, U256, Bytes) -> Bytes) -> Self {
+        self.encode_call = encode_call;
+        self
+    }
+
+    /// Sets how long [`Middleware::send_transaction`] and the returned [`PendingUserOperation`]s
+    /// wait for an operation to be included
+    #[must_use]
+    pub fn receipt_timeout(mut self, receipt_timeout: Duration) -> Self {
+        self.receipt_timeout = receipt_timeout;
+        self
+    }
+
+    /// Returns the address of the smart account
+    pub fn account(&self) -> Address {
+        self.account
+    }
+
+    /// Returns the provider of the bundler
+    pub fn bundler(&self) -> &Provider<P> {
+        &self.bundler
+    }
+
+    /// Returns the nonce of the account from the `EntryPoint`
+    pub async fn nonce(&self) -> Result<U256, AccountAbstractionError<M, S>> {
+        let data = self.entry_point_contract.encode("getNonce", (self.account, U256::zero()))?;
+        let call: TypedTransaction =
+            TransactionRequest::new().to(self.entry_point).data(data).into();
+        let output =
+            self.inner.call(&call, None).await.map_err(AccountAbstractionError::MiddlewareError)?;
+        Ok(self.entry_point_contract.decode_output("getNonce", output)?)
+    }
+
+    /// Returns the next nonce of the account and counts it as used, fetching it from the
+    /// `EntryPoint` the first time
+    async fn allocate_nonce(&self) -> Result<U256, AccountAbstractionError<M, S>> {
+        // held while fetching, so concurrent operations wait for the first nonce
+        let mut next_nonce = self.next_nonce.lock().await;
+        let nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => self.nonce().await?,
+        };
+        *next_nonce = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Gives back an allocated nonce that won't be used, so the next operation doesn't leave a
+    /// gap. The nonce is fetched again if other nonces were allocated in the meantime.
+    async fn release_nonce(&self, nonce: U256) {
+        let mut next_nonce = self.next_nonce.lock().await;
+        *next_nonce = match *next_nonce {
+            Some(next) if next == nonce + 1 => Some(nonce),
+            _ => None,
+        };
+    }
+
+    /// Converts the transaction into a signed user operation of the account, with the nonce of
+    /// the transaction or the next nonce of the account
+    pub async fn user_operation(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<UserOperation, AccountAbstractionError<M, S>> {
+        let to = match tx.to() {
+            Some(NameOrAddress::Address(to)) => *to,
+            Some(NameOrAddress::Name(name)) => self
+                .inner
+                .resolve_name(name)
+                .await
+                .map_err(AccountAbstractionError::MiddlewareError)?,
+            None => return Err(AccountAbstractionError::MissingRecipient),
+        };
+        let value = tx.value().copied().unwrap_or_default();
+        let data = tx.data().cloned().unwrap_or_default();
+
+        let chain_id =
+            self.inner.get_chainid().await.map_err(AccountAbstractionError::MiddlewareError)?;
+        let code = self
+            .inner
+            .get_code(self.account, None)
+            .await
+            .map_err(AccountAbstractionError::MiddlewareError)?;
+
+        let (nonce, allocated) = match tx.nonce() {
+            Some(nonce) => (*nonce, false),
+            None => (self.allocate_nonce().await?, true),
+        };
+        let op = UserOperation {
+            sender: self.account,
+            nonce,
+            init_code: if code.is_empty() { self.init_code.clone() } else { Bytes::default() },
+            call_data: (self.encode_call)(to, value, data),
+            signature: DUMMY_SIGNATURE.to_vec().into(),
+            ..Default::default()
+        };
+        let res = self.complete_user_operation(op, tx, chain_id).await;
+        if res.is_err() && allocated {
+            self.release_nonce(nonce).await;
+        }
+        res
+    }
+
+    /// Sets the fees, paymaster data and gas limits of the operation and signs it
+    async fn complete_user_operation(
+        &self,
+        mut op: UserOperation,
+        tx: &TypedTransaction,
+        chain_id: U256,
+    ) -> Result<UserOperation, AccountAbstractionError<M, S>> {
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match tx {
+            TypedTransaction::Eip1559(inner) => {
+                match (inner.max_fee_per_gas, inner.max_priority_fee_per_gas) {
+                    (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) => {
+                        (max_fee_per_gas, max_priority_fee_per_gas)
+                    }
+                    _ => self.estimate_fees().await?,
+                }
+            }
+            _ => match tx.gas_price() {
+                Some(gas_price) => (gas_price, gas_price),
+                None => self.estimate_fees().await?,
+            },
+        };
+        op.max_fee_per_gas = max_fee_per_gas;
+        op.max_priority_fee_per_gas = max_priority_fee_per_gas;
+
+        op.paymaster_and_data = self.paymaster_and_data(&op, chain_id).await?;
+        let estimate = self
+            .bundler
+            .estimate_user_operation_gas(op.clone(), self.entry_point)
+            .await
+            .map_err(AccountAbstractionError::BundlerError)?;
+        op.call_gas_limit = estimate.call_gas_limit;
+        op.verification_gas_limit = estimate.verification_gas_limit;
+        op.pre_verification_gas = estimate.pre_verification_gas;
+        op.paymaster_and_data = self.paymaster_and_data(&op, chain_id).await?;
+
+        let hash = op.hash(self.entry_point, chain_id);
+        let signature = self
+            .signer
+            .sign_message(hash.as_bytes())
+            .await
+            .map_err(AccountAbstractionError::SignerError)?;
+        op.signature = signature.to_vec().into();
+        Ok(op)
+    }
+
+    /// Converts the transaction into a user operation and submits it to the bundler, returning
+    /// the pending operation without waiting for its inclusion.
+    ///
+    /// If the bundler rejects the operation, the nonce of the account is fetched from the
+    /// `EntryPoint` again for the next operation.
+    pub async fn send_user_operation(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<PendingUserOperation<'_, P>, AccountAbstractionError<M, S>> {
+        let op = self.user_operation(tx).await?;
+        match self.bundler.send_user_operation(op, self.entry_point).await {
+            Ok(hash) => {
+                Ok(PendingUserOperation::new(hash, &self.bundler).timeout(self.receipt_timeout))
+            }
+            Err(err) => {
+                // the nonce may be unused, or the local one may be out of sync
+                *self.next_nonce.lock().await = None;
+                Err(AccountAbstractionError::BundlerError(err))
+            }
+        }
+    }
+
+    async fn estimate_fees(&self) -> Result<(U256, U256), AccountAbstractionError<M, S>> {
+        self.inner
+            .estimate_eip1559_fees(None)
+            .await
+            .map_err(AccountAbstractionError::MiddlewareError)
+    }
+
+    async fn paymaster_and_data(
+        &self,
+        op: &UserOperation,
+        chain_id: U256,
+    ) -> Result<Bytes, AccountAbstractionError<M, S>> {
+        match &self.paymaster {
+            Some(paymaster) => paymaster
+                .paymaster_and_data(op, self.entry_point, chain_id)
+                .await
+                .map_err(AccountAbstractionError::PaymasterError),
+            None => Ok(Bytes::default()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the account abstraction middleware interacts with the blockchain
+pub enum AccountAbstractionError<M: Middleware, S: Signer> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error("bundler error: {0}")]
+    /// Thrown when the bundler rejects a request
+    BundlerError(ProviderError),
+
+    #[error("{0}")]
+    /// Thrown when the owner of the account fails to sign an operation
+    SignerError(S::Error),
+
+    #[error("paymaster error: {0}")]
+    /// Thrown when the paymaster hook fails
+    PaymasterError(Box<dyn Error + Send + Sync>),
+
+    #[error(transparent)]
+    /// Thrown when the call to the `EntryPoint` can't be encoded or decoded
+    AbiError(#[from] AbiError),
+
+    #[error("user operations can't deploy contracts")]
+    /// Thrown when the transaction has no recipient
+    MissingRecipient,
+
+    #[error("user operation {0:?} was not included in time")]
+    /// Thrown when an operation isn't included before the receipt timeout
+    Timeout(H256),
+}
+
+impl<M: Middleware, S: Signer> FromErr<M::Error> for AccountAbstractionError<M, S> {
+    fn from(src: M::Error) -> AccountAbstractionError<M, S> {
+        AccountAbstractionError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, P, S> Middleware for AccountAbstractionMiddleware<M, P, S>
+where
+    M: Middleware,
+    P: JsonRpcClient,
+    S: Signer,
+{
+    type Error = AccountAbstractionError<M, S>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    fn default_sender(&self) -> Option<Address> {
+        Some(self.account)
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        _: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let pending = self.send_user_operation(&tx.into()).await?;
+        let hash = pending.hash();
+        match pending.wait().await.map_err(AccountAbstractionError::BundlerError)? {
+            Some(receipt) => {
+                Ok(PendingTransaction::new(receipt.receipt.transaction_hash, self.provider()))
+            }
+            None => Err(AccountAbstractionError::Timeout(hash)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::types::{RecoveryMessage, Signature};
+    use ethers_providers::MockProvider;
+    use ethers_signers::LocalWallet;
+    use serde_json::json;
+
+    /// Mocks a node with an undeployed account whose nonce is 7, and a bundler
+    fn node() -> (Provider<MockProvider>, Provider<MockProvider>, MockProvider) {
+        let (provider, node) = Provider::mocked();
+        node.expect("eth_chainId").returns(U256::one()).unwrap();
+        node.expect("eth_getCode").returns(Bytes::default()).unwrap();
+        let nonce = Bytes::from(abi::encode(&[Token::Uint(7.into())]));
+        node.expect("eth_call").returns(nonce).unwrap();
+
+        let (bundler, mock) = Provider::mocked();
+        let estimate = json!({
+            "preVerificationGas": "0x1",
+            "verificationGasLimit": "0x2",
+            "callGasLimit": "0x3",
+        });
+        mock.expect("eth_estimateUserOperationGas").returns(estimate).unwrap();
+        (provider, bundler, mock)
+    }
+
+    #[derive(Debug)]
+    struct Sponsor;
+
+    #[async_trait]
+    impl PaymasterHook for Sponsor {
+        async fn paymaster_and_data(
+            &self,
+            op: &UserOperation,
+            _: Address,
+            _: U256,
+        ) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+            // commits to the gas limits, like a verifying paymaster
+            Ok(Bytes::from(vec![0x42, op.call_gas_limit.low_u32() as u8]))
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_signed_user_operations() {
+        let (provider, bundler, mock) = node();
+        let owner: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let account = Address::repeat_byte(0x43);
+        let client = AccountAbstractionMiddleware::new(provider, bundler, owner.clone(), account)
+            .init_code(vec![0xfa])
+            .paymaster(Sponsor);
+        assert_eq!(client.default_sender(), Some(account));
+
+        let to = Address::repeat_byte(1);
+        let estimated = UserOperation {
+            sender: account,
+            nonce: 7.into(),
+            // the account is not deployed yet
+            init_code: vec![0xfa].into(),
+            call_data: simple_account_execute(to, 100.into(), vec![0xab].into()),
+            max_fee_per_gas: 5.into(),
+            max_priority_fee_per_gas: 5.into(),
+            paymaster_and_data: vec![0x42, 0].into(),
+            signature: DUMMY_SIGNATURE.to_vec().into(),
+            ..Default::default()
+        };
+        let mut op = UserOperation {
+            call_gas_limit: 3.into(),
+            verification_gas_limit: 2.into(),
+            pre_verification_gas: 1.into(),
+            paymaster_and_data: vec![0x42, 3].into(),
+            ..estimated.clone()
+        };
+        let hash = op.hash(ENTRY_POINT, 1u64.into());
+        op.signature = owner.sign_message(hash.as_bytes()).await.unwrap().to_vec().into();
+        mock.expect("eth_sendUserOperation").returns(hash).unwrap();
+
+        let tx = TransactionRequest::pay(to, 100).data(vec![0xab]).gas_price(5).into();
+        assert_eq!(*client.send_user_operation(&tx).await.unwrap(), hash);
+        mock.assert_request("eth_estimateUserOperationGas", (&estimated, ENTRY_POINT)).unwrap();
+        mock.assert_request("eth_sendUserOperation", (&op, ENTRY_POINT)).unwrap();
+
+        let signature = Signature::try_from(op.signature.as_ref()).unwrap();
+        signature.verify(RecoveryMessage::Data(hash.as_bytes().to_vec()), owner.address()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn counts_nonces_locally() {
+        let (provider, bundler, _) = node();
+        let owner: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let client =
+            AccountAbstractionMiddleware::new(provider, bundler, owner, Address::repeat_byte(0x43));
+        let tx = TransactionRequest::pay(Address::repeat_byte(1), 100).gas_price(5).into();
+        let ops = futures_util::future::try_join_all((0..3).map(|_| client.user_operation(&tx)))
+            .await
+            .unwrap();
+        let mut nonces: Vec<_> = ops.iter().map(|op| op.nonce.as_u64()).collect();
+        nonces.sort_unstable();
+        assert_eq!(nonces, [7, 8, 9]);
+
+        // the nonce is fetched again after the bundler rejects an operation
+        let err = client.send_user_operation(&tx).await.unwrap_err();
+        assert!(matches!(err, AccountAbstractionError::BundlerError(_)));
+        assert_eq!(client.user_operation(&tx).await.unwrap().nonce, 7.into());
+    }
+
+    #[derive(Debug, Default)]
+    struct FailingOnce(std::sync::atomic::AtomicBool);
+
+    #[async_trait]
+    impl PaymasterHook for FailingOnce {
+        async fn paymaster_and_data(
+            &self,
+            _: &UserOperation,
+            _: Address,
+            _: U256,
+        ) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+            if self.0.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                Ok(Bytes::default())
+            } else {
+                Err("paymaster unavailable".into())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn releases_nonces_of_failed_operations() {
+        let (provider, bundler, _) = node();
+        let owner: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let client =
+            AccountAbstractionMiddleware::new(provider, bundler, owner, Address::repeat_byte(0x43))
+                .paymaster(FailingOnce::default());
+        let tx = TransactionRequest::pay(Address::repeat_byte(1), 100).gas_price(5).into();
+        let err = client.user_operation(&tx).await.unwrap_err();
+        assert!(matches!(err, AccountAbstractionError::PaymasterError(_)));
+        assert_eq!(client.user_operation(&tx).await.unwrap().nonce, 7.into());
+        assert_eq!(client.user_operation(&tx).await.unwrap().nonce, 8.into());
+    }
+
+    #[tokio::test]
+    async fn waits_for_pending_user_operations() {
+        let (bundler, mock) = Provider::mocked();
+        let bundler = bundler.interval(Duration::from_millis(1));
+        let pending = PendingUserOperation::new(H256::repeat_byte(1), &bundler)
+            .timeout(Duration::from_millis(1));
+        mock.expect("eth_getUserOperationReceipt").returns(serde_json::Value::Null).unwrap();
+        assert_eq!(pending.wait().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn rejects_deployments() {
+        let (provider, bundler, _) = node();
+        let owner: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let client =
+            AccountAbstractionMiddleware::new(provider, bundler, owner, Address::repeat_byte(0x43));
+        let tx = TransactionRequest::new().data(vec![0x60]).into();
+        let err = client.send_user_operation(&tx).await.unwrap_err();
+        assert!(matches!(err, AccountAbstractionError::MissingRecipient));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::nonce_recovery::NonceErrorKind;
+use async_trait::async_trait;
+use ethers_core::{
+    types::{transaction::eip2718::TypedTransaction, BlockId, Bytes, TxHash},
+    utils::keccak256,
+};
+use ethers_providers::{
+    FromErr, JsonRpcClient, Middleware, PendingTransaction, Provider, ProviderError,
+};
+use futures_util::future::{join, join_all};
+use std::fmt::Display;
+use thiserror::Error;
+
+/// The outcome of submitting a signed transaction to the primary and the backup endpoints of a
+/// [`BroadcastMiddleware`]
+#[derive(Debug)]
+pub struct BroadcastReport<M: Middleware> {
+    /// The hash of the transaction
+    pub hash: TxHash,
+    /// The outcome of the inner middleware
+    pub primary: Result<(), M::Error>,
+    /// The outcomes of the backups, in the order they were added
+    pub backups: Vec<Result<(), ProviderError>>,
+}
+
+impl<M: Middleware> BroadcastReport<M> {
+    /// Returns whether an endpoint accepted the transaction, endpoints that already knew it
+    /// count as accepting it
+    pub fn is_accepted(&self) -> bool {
+        accepted(&self.primary) || self.backups.iter().any(accepted)
+    }
+
+    /// Returns how many endpoints accepted the transaction
+    pub fn accepted(&self) -> usize {
+        accepted(&self.primary) as usize + self.backups.iter().filter(|res| accepted(res)).count()
+    }
+}
+
+fn accepted<E: Display>(res: &Result<(), E>) -> bool {
+    match res {
+        Ok(()) => true,
+        Err(err) => NonceErrorKind::of(err) == Some(NonceErrorKind::AlreadyKnown),
+    }
+}
+
+#[derive(Debug)]
+/// Middleware that submits signed transactions to backup endpoints along with the inner
+/// middleware, so they propagate faster and a single flaky RPC doesn't fail them.
+///
+/// Transactions are signed by the inner middleware, e.g. a
+/// [`SignerMiddleware`](crate::SignerMiddleware), and the raw transaction is submitted to the
+/// inner middleware and all backups concurrently. The backups are only used for broadcasting,
+/// everything else, including watching the pending transaction, goes through the inner
+/// middleware. Sending fails only if no endpoint accepts the transaction, in which case the error
+/// of the inner middleware is returned. Use [`BroadcastMiddleware::broadcast`] to get the outcome
+/// of every endpoint.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::{broadcast::BroadcastMiddleware, SignerMiddleware};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let wallet: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let client = SignerMiddleware::new_with_provider_chain(provider, wallet).await?;
+///
+/// let client = BroadcastMiddleware::new(client)
+///     .backup(Provider::<Http>::try_from("https://rpc.ankr.com/eth")?)
+///     .backup(Provider::<Http>::try_from("https://cloudflare-eth.com")?);
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100);
+/// let receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct BroadcastMiddleware<M, P> {
+    inner: M,
+    backups: Vec<Provider<P>>,
+}
+
+impl<M, P> BroadcastMiddleware<M, P>
+where
+    M: Middleware,
+    P: JsonRpcClient,
+{
+    /// Creates a middleware without backups, add them with [`BroadcastMiddleware::backup`]
+    pub fn new(inner: M) -> Self {
+        Self { inner, backups: Vec::new() }
+    }
+
+    /// Adds a backup endpoint that signed transactions are submitted to
+    #[must_use]
+    pub fn backup(mut self, backup: Provider<P>) -> Self {
+        self.backups.push(backup);
+        self
+    }
+
+    /// Returns the backup endpoints
+    pub fn backups(&self) -> &[Provider<P>] {
+        &self.backups
+    }
+
+    /// Submits the signed transaction to the inner middleware and all backups concurrently,
+    /// returning the outcome of every endpoint
+    pub async fn broadcast(&self, raw: Bytes) -> BroadcastReport<M> {
+        let hash = TxHash::from(keccak256(&raw));
+        let primary = async { self.inner.send_raw_transaction(raw.clone()).await.map(drop) };
+        let backups = join_all(self.backups.iter().map(|backup| {
+            let raw = raw.clone();
+            async move { backup.send_raw_transaction(raw).await.map(drop) }
+        }));
+        let (primary, backups) = join(primary, backups).await;
+
+        for (i, res) in backups.iter().enumerate() {
+            if let Err(err) = res {
+                tracing::debug!(?hash, backup = i, %err, "backup did not accept the transaction");
+            }
+        }
+        BroadcastReport { hash, primary, backups }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the broadcast middleware interacts with the blockchain
+pub enum BroadcastError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error("Broadcasting transactions requires a sender")]
+    /// Thrown when the transaction has no sender and there is no default sender
+    MissingSender,
+}
+
+impl<M: Middleware> FromErr<M::Error> for BroadcastError<M> {
+    fn from(src: M::Error) -> BroadcastError<M> {
+        BroadcastError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, P> Middleware for BroadcastMiddleware<M, P>
+where
+    M: Middleware,
+    P: JsonRpcClient,
+{
+    type Error = BroadcastError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn send_raw_transaction<'a>(
+        &'a self,
+        tx: Bytes,
+    ) -> Result<PendingTransaction<'a, Self::Provider>, Self::Error> {
+        let report = self.broadcast(tx).await;
+        match report.primary {
+            Err(err) if !report.backups.iter().any(accepted) => {
+                Err(BroadcastError::MiddlewareError(err))
+            }
+            _ => Ok(PendingTransaction::new(report.hash, self.provider())),
+        }
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        self.inner
+            .fill_transaction(&mut tx, block)
+            .await
+            .map_err(BroadcastError::MiddlewareError)?;
+        let from = *tx.from().ok_or(BroadcastError::MissingSender)?;
+        let signature = self
+            .inner
+            .sign_transaction(&tx, from)
+            .await
+            .map_err(BroadcastError::MiddlewareError)?;
+        self.send_raw_transaction(tx.rlp_signed(&signature)).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::SignerMiddleware;
+    use ethers_core::types::{Address, TransactionRequest};
+    use ethers_providers::{JsonRpcError, MockProvider};
+    use ethers_signers::{LocalWallet, Signer};
+
+    /// Mocks an endpoint answering a raw transaction with the hash, or rejecting it with the error
+    fn endpoint(error: Option<&str>) -> (Provider<MockProvider>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        match error {
+            Some(message) => mock.push_error(JsonRpcError {
+                code: -32000,
+                message: message.to_string(),
+                data: None,
+            }),
+            None => mock.push(TxHash::zero()).unwrap(),
+        }
+        (provider, mock)
+    }
+
+    #[tokio::test]
+    async fn reports_outcomes_of_endpoints() {
+        let (backup, mock) = endpoint(None);
+        let client = BroadcastMiddleware::new(endpoint(Some("connection reset")).0)
+            .backup(endpoint(Some("already known")).0)
+            .backup(endpoint(Some("insufficient funds")).0)
+            .backup(backup);
+
+        let raw = Bytes::from(vec![1, 2, 3]);
+        let report = client.broadcast(raw.clone()).await;
+        assert_eq!(report.hash, TxHash::from(keccak256(&raw)));
+        assert!(report.primary.is_err());
+        assert!(report.is_accepted());
+        // the backup that already knew the transaction accepted it
+        assert_eq!(report.accepted(), 2);
+        mock.assert_request("eth_sendRawTransaction", [raw]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn sends_signed_transactions_to_backups() {
+        let wallet: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let wallet = wallet.with_chain_id(1u64);
+        let tx: TypedTransaction = TransactionRequest::pay(Address::zero(), 1)
+            .from(wallet.address())
+            .nonce(0)
+            .gas(21_000)
+            .gas_price(1)
+            .chain_id(1u64)
+            .into();
+        let raw = tx.rlp_signed(&wallet.sign_transaction_sync(&tx));
+
+        let (backup, mock) = endpoint(None);
+        let signer = SignerMiddleware::new(endpoint(Some("connection reset")).0, wallet);
+        let client = BroadcastMiddleware::new(signer).backup(backup);
+        let pending = client.send_transaction(tx, None).await.unwrap();
+        assert_eq!(pending.tx_hash(), TxHash::from(keccak256(&raw)));
+        mock.assert_request("eth_sendRawTransaction", [&raw]).unwrap();
+
+        // fails if no endpoint accepts the transaction
+        let client = BroadcastMiddleware::new(endpoint(Some("connection reset")).0)
+            .backup(endpoint(Some("connection reset")).0);
+        let err = client.send_raw_transaction(raw).await.unwrap_err();
+        assert!(err.to_string().contains("connection reset"));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     gas_oracle::{GasOracle, GasOracleMiddleware},
+    tx_manager::{TxManagerMiddleware, TxPolicy},
     NonceManagerMiddleware, SignerMiddleware,
 };
 use ethers_core::types::Address;
@@ -86,6 +87,13 @@ pub trait MiddlewareBuilder: Middleware + Sized + 'static {
     {
         GasOracleMiddleware::new(self, gas_oracle)
     }
+
+    /// Wraps `self` inside a [`TxManagerMiddleware`](crate::tx_manager::TxManagerMiddleware).
+    ///
+    /// [`TxPolicy`](crate::tx_manager::TxPolicy)
+    fn tx_manager(self, policy: TxPolicy) -> TxManagerMiddleware<Self> {
+        TxManagerMiddleware::new(self, policy)
+    }
 }
 
 impl<M> MiddlewareBuilder for M where M: Middleware + Sized + 'static {}
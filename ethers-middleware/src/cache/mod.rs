@@ -0,0 +1,379 @@
+mod store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::FileCacheStore;
+#[cfg(all(feature = "redis", not(target_arch = "wasm32")))]
+pub use store::RedisCacheStore;
+pub use store::{CacheStore, MemoryCacheStore};
+
+use async_trait::async_trait;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, *};
+use ethers_providers::{FromErr, Middleware};
+use instant::Instant;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{sync::Mutex, time::Duration};
+use thiserror::Error;
+
+/// How long the number of the finalized block is reused before it's fetched again
+const FINALIZED_REFRESH: Duration = Duration::from_secs(12);
+
+#[derive(Debug)]
+/// Middleware that caches the responses of idempotent reads: calls, logs, blocks, transactions
+/// and receipts.
+///
+/// Responses that can no longer change are cached forever: those of blocks requested by hash,
+/// and of calls, logs, blocks, transactions and receipts at or below the finalized block. Other
+/// responses, e.g. of calls at the latest block, are only cached if a [`ttl`](Self::ttl) is set,
+/// until it expires. Pending transactions, and blocks and transactions that are not known yet,
+/// are never cached.
+///
+/// The responses are kept in memory by default, a persistent [`CacheStore`] keeps them across
+/// restarts, see [`CacheMiddleware::with_store`]. Responses are keyed by the chain id along with
+/// the request, so clients of different chains can share a store. Requests are sent to the inner
+/// middleware if the store fails.
+///
+/// Unlike the response cache of the
+/// [`Provider`](ethers_providers::Provider::response_cache), which keeps a bounded number of
+/// responses in memory and only caches requests that can never change by their params, e.g.
+/// blocks by hash, the middleware learns the finalized block from the node to also cache reads
+/// of finalized blocks by number, caches recent reads for a ttl, and can persist the responses or
+/// share them between processes. It only caches the reads listed above, and only requests that go
+/// through the middleware, not those that other middlewares send to the provider directly.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, Filter};
+/// use ethers_middleware::cache::{CacheMiddleware, FileCacheStore};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::{convert::TryFrom, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let client = CacheMiddleware::with_store(provider, FileCacheStore::new("./cache/mainnet"))
+///     .ttl(Some(Duration::from_secs(2)));
+///
+/// // served from disk after the first run
+/// let filter = Filter::new().address(Address::random()).from_block(0).to_block(1_000_000);
+/// let logs = client.get_logs(&filter).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CacheMiddleware<M, S = MemoryCacheStore> {
+    inner: M,
+    store: S,
+    ttl: Option<Duration>,
+    /// The chain id of the node, fetched on first use
+    chain_id: Mutex<Option<U256>>,
+    /// The last fetched number of the finalized block, `None` if the node doesn't know it
+    finalized: Mutex<Option<(Option<U64>, Instant)>>,
+}
+
+impl<M> CacheMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Instantiates the cache, keeping the responses in memory
+    pub fn new(inner: M) -> Self {
+        Self::with_store(inner, MemoryCacheStore::new())
+    }
+}
+
+impl<M, S> CacheMiddleware<M, S>
+where
+    M: Middleware,
+    S: CacheStore,
+{
+    /// Instantiates the cache, keeping the responses in the `store`
+    pub fn with_store(inner: M, store: S) -> Self {
+        Self { inner, store, ttl: None, chain_id: Mutex::new(None), finalized: Mutex::new(None) }
+    }
+
+    /// Sets how long responses that may still change are cached, `None` to not cache them
+    /// (default: `None`)
+    #[must_use]
+    pub fn ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Returns the store of the responses
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns the number of the finalized block, or `None` if the node doesn't support the
+    /// `finalized` block tag
+    pub async fn finalized_block(&self) -> Result<Option<U64>, CacheError<M>> {
+        if let Some((number, fetched)) = *self.finalized.lock().unwrap() {
+            if fetched.elapsed() < FINALIZED_REFRESH {
+                return Ok(number)
+            }
+        }
+        let number = match self.inner.get_block(BlockNumber::Finalized).await {
+            Ok(block) => block.and_then(|block| block.number),
+            Err(err) => {
+                tracing::debug!(%err, "could not fetch the finalized block");
+                None
+            }
+        };
+        *self.finalized.lock().unwrap() = Some((number, Instant::now()));
+        Ok(number)
+    }
+
+    /// Returns whether the block is at or below the finalized block
+    async fn is_finalized(&self, block: Option<U64>) -> Result<bool, CacheError<M>> {
+        let block = match block {
+            Some(block) => block,
+            None => return Ok(false),
+        };
+        Ok(self.finalized_block().await?.map_or(false, |finalized| block <= finalized))
+    }
+
+    /// Returns whether the response at the block can no longer change
+    async fn is_final_at(&self, block: Option<BlockId>) -> Result<bool, CacheError<M>> {
+        match block {
+            Some(BlockId::Hash(_)) => Ok(true),
+            Some(BlockId::Number(BlockNumber::Number(number))) => {
+                self.is_finalized(Some(number)).await
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Returns the key of the request, the chain id, method and params
+    async fn key<T: Serialize>(&self, method: &str, params: T) -> Result<String, CacheError<M>> {
+        let cached = *self.chain_id.lock().unwrap();
+        let chain_id = match cached {
+            Some(chain_id) => chain_id,
+            None => {
+                let chain_id =
+                    self.inner.get_chainid().await.map_err(CacheError::MiddlewareError)?;
+                *self.chain_id.lock().unwrap() = Some(chain_id);
+                chain_id
+            }
+        };
+        Ok(format!("{chain_id}:{method}:{}", serde_json::to_string(&params).unwrap_or_default()))
+    }
+
+    async fn lookup<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        match self.store.get(key).await {
+            Ok(value) => value.and_then(|value| serde_json::from_value(value).ok()),
+            Err(err) => {
+                tracing::warn!(%err, key, "could not read the cache");
+                None
+            }
+        }
+    }
+
+    /// Stores the response forever if it's final, or else until the ttl expires
+    async fn save<T: Serialize>(&self, key: &str, response: &T, is_final: bool) {
+        let ttl = match (is_final, self.ttl) {
+            (true, _) => None,
+            (false, Some(ttl)) => Some(ttl),
+            (false, None) => return,
+        };
+        let value = match serde_json::to_value(response) {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+        if let Err(err) = self.store.insert(key, value, ttl).await {
+            tracing::warn!(%err, key, "could not write the cache");
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the cache middleware interacts with the blockchain
+pub enum CacheError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> FromErr<M::Error> for CacheError<M> {
+    fn from(src: M::Error) -> CacheError<M> {
+        CacheError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, S> Middleware for CacheMiddleware<M, S>
+where
+    M: Middleware,
+    S: CacheStore,
+{
+    type Error = CacheError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let key = self.key("eth_call", (tx, block)).await?;
+        if let Some(output) = self.lookup(&key).await {
+            return Ok(output)
+        }
+        let output = self.inner.call(tx, block).await.map_err(CacheError::MiddlewareError)?;
+        self.save(&key, &output, self.is_final_at(block).await?).await;
+        Ok(output)
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, Self::Error> {
+        let key = self.key("eth_getLogs", filter).await?;
+        if let Some(logs) = self.lookup(&key).await {
+            return Ok(logs)
+        }
+        let logs = self.inner.get_logs(filter).await.map_err(CacheError::MiddlewareError)?;
+        let is_final = match filter.block_option {
+            FilterBlockOption::AtBlockHash(_) => true,
+            FilterBlockOption::Range { to_block: Some(BlockNumber::Number(to)), .. } => {
+                self.is_finalized(Some(to)).await?
+            }
+            _ => false,
+        };
+        self.save(&key, &logs, is_final).await;
+        Ok(logs)
+    }
+
+    async fn get_block<T: Into<BlockId> + Send + Sync>(
+        &self,
+        block_hash_or_number: T,
+    ) -> Result<Option<Block<TxHash>>, Self::Error> {
+        let id = block_hash_or_number.into();
+        let key = self.key("eth_getBlock", id).await?;
+        if let Some(block) = self.lookup(&key).await {
+            return Ok(Some(block))
+        }
+        let block = self.inner.get_block(id).await.map_err(CacheError::MiddlewareError)?;
+        if let Some(block) = &block {
+            self.save(&key, block, self.is_final_at(Some(id)).await?).await;
+        }
+        Ok(block)
+    }
+
+    async fn get_transaction<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<Transaction>, Self::Error> {
+        let hash = transaction_hash.into();
+        let key = self.key("eth_getTransactionByHash", hash).await?;
+        if let Some(tx) = self.lookup(&key).await {
+            return Ok(Some(tx))
+        }
+        let tx = self.inner.get_transaction(hash).await.map_err(CacheError::MiddlewareError)?;
+        if let Some(tx) = tx.as_ref().filter(|tx| tx.block_number.is_some()) {
+            self.save(&key, tx, self.is_finalized(tx.block_number).await?).await;
+        }
+        Ok(tx)
+    }
+
+    async fn get_transaction_receipt<T: Send + Sync + Into<TxHash>>(
+        &self,
+        transaction_hash: T,
+    ) -> Result<Option<TransactionReceipt>, Self::Error> {
+        let hash = transaction_hash.into();
+        let key = self.key("eth_getTransactionReceipt", hash).await?;
+        if let Some(receipt) = self.lookup(&key).await {
+            return Ok(Some(receipt))
+        }
+        let receipt =
+            self.inner.get_transaction_receipt(hash).await.map_err(CacheError::MiddlewareError)?;
+        if let Some(receipt) = &receipt {
+            self.save(&key, receipt, self.is_finalized(receipt.block_number).await?).await;
+        }
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_providers::Provider;
+
+    fn block(number: u64) -> Block<TxHash> {
+        Block {
+            number: Some(number.into()),
+            hash: Some(H256::repeat_byte(1)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn caches_finalized_responses_forever() {
+        let (provider, mock) = Provider::mocked();
+        let client = CacheMiddleware::new(provider.with_chain_id(1u64));
+
+        // the finalized block is fetched after the logs
+        let filter = Filter::new().from_block(1).to_block(10);
+        mock.push(block(20)).unwrap();
+        mock.push::<Vec<Log>, _>(vec![Log::default()]).unwrap();
+        assert_eq!(client.get_logs(&filter).await.unwrap().len(), 1);
+        assert_eq!(client.get_logs(&filter).await.unwrap().len(), 1);
+
+        // blocks above the finalized block are not cached
+        mock.push(block(30)).unwrap();
+        mock.push(block(30)).unwrap();
+        client.get_block(30u64).await.unwrap();
+        client.get_block(30u64).await.unwrap();
+
+        // blocks requested by hash are
+        mock.push(block(30)).unwrap();
+        client.get_block(H256::repeat_byte(1)).await.unwrap();
+        assert_eq!(client.get_block(H256::repeat_byte(1)).await.unwrap(), Some(block(30)));
+
+        mock.assert_request("eth_getLogs", [&filter]).unwrap();
+        mock.assert_request("eth_getBlockByNumber", ("finalized", false)).unwrap();
+        mock.assert_request("eth_getBlockByNumber", ("0x1e", false)).unwrap();
+        mock.assert_request("eth_getBlockByNumber", ("0x1e", false)).unwrap();
+        mock.assert_request("eth_getBlockByHash", (H256::repeat_byte(1), false)).unwrap();
+        assert!(mock.assert_request("eth_getBlockByHash", ()).is_err());
+    }
+
+    #[tokio::test]
+    async fn caches_recent_responses_until_they_expire() {
+        let (provider, mock) = Provider::mocked();
+        let client =
+            CacheMiddleware::new(provider.with_chain_id(1u64)).ttl(Some(Duration::from_millis(50)));
+
+        let tx: TypedTransaction = TransactionRequest::new().to(Address::zero()).into();
+        mock.push::<Bytes, Bytes>(vec![1].into()).unwrap();
+        assert_eq!(client.call(&tx, None).await.unwrap(), Bytes::from(vec![1]));
+        assert_eq!(client.call(&tx, None).await.unwrap(), Bytes::from(vec![1]));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        mock.push::<Bytes, Bytes>(vec![2].into()).unwrap();
+        assert_eq!(client.call(&tx, None).await.unwrap(), Bytes::from(vec![2]));
+        assert_eq!(client.store().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn keys_responses_by_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let hash = H256::repeat_byte(1);
+        // returns whether the client of the chain fetched the block instead of reading it from
+        // the shared store
+        let fetches_block = |chain_id: u64| {
+            let store = FileCacheStore::new(dir.path().to_owned());
+            async move {
+                let (provider, mock) = Provider::mocked();
+                let client = CacheMiddleware::with_store(provider, store);
+                mock.push(block(30)).unwrap();
+                mock.push(U256::from(chain_id)).unwrap();
+                assert_eq!(client.get_block(hash).await.unwrap(), Some(block(30)));
+                mock.assert_request("eth_chainId", ()).unwrap();
+                mock.assert_request("eth_getBlockByHash", (hash, false)).is_ok()
+            }
+        };
+        assert!(fetches_block(1).await);
+        assert!(!fetches_block(1).await);
+        assert!(fetches_block(5).await);
+    }
+}
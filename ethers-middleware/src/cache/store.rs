@@ -0,0 +1,297 @@
+use async_trait::async_trait;
+use instant::Instant;
+use serde_json::Value;
+use std::{collections::HashMap, error::Error, fmt::Debug, sync::Mutex, time::Duration};
+
+/// Storage for the cached responses, see
+/// [`CacheMiddleware::with_store`](super::CacheMiddleware::with_store).
+///
+/// Responses are keyed by the chain id, method and params of the request, so a store can be
+/// shared by clients of different chains.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait CacheStore: Send + Sync + Debug {
+    type Error: Error + Send + Sync + 'static;
+
+    /// Returns the response stored under the key, or `None` if none is stored or it expired
+    async fn get(&self, key: &str) -> Result<Option<Value>, Self::Error>;
+
+    /// Stores the response under the key, until it expires after the `ttl` if one is set
+    async fn insert(
+        &self,
+        key: &str,
+        value: Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Keeps the responses in memory, they are lost when the process exits
+#[derive(Debug, Default)]
+pub struct MemoryCacheStore {
+    /// key -> (response, expiry)
+    entries: Mutex<HashMap<String, (Value, Option<Instant>)>>,
+}
+
+impl MemoryCacheStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of stored responses, including expired ones that were not accessed
+    /// since they expired
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no responses are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl CacheStore for MemoryCacheStore {
+    type Error = std::convert::Infallible;
+
+    async fn get(&self, key: &str) -> Result<Option<Value>, Self::Error> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some((_, Some(expiry))) if *expiry <= Instant::now() => {
+                entries.remove(key);
+                Ok(None)
+            }
+            Some((value, _)) => Ok(Some(value.clone())),
+            None => Ok(None),
+        }
+    }
+
+    async fn insert(
+        &self,
+        key: &str,
+        value: Value,
+        ttl: Option<Duration>,
+    ) -> Result<(), Self::Error> {
+        let expiry = ttl.map(|ttl| Instant::now() + ttl);
+        self.entries.lock().unwrap().insert(key.to_string(), (value, expiry));
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileCacheStore;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod file {
+    use super::*;
+    use ethers_core::utils::{hex, keccak256};
+    use serde::{Deserialize, Serialize};
+    use std::{
+        fs, io,
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    /// A stored response and when it expires, in seconds since the unix epoch
+    #[derive(Serialize, Deserialize)]
+    struct Entry {
+        expires: Option<u64>,
+        value: Value,
+    }
+
+    /// Keeps the responses in a directory, one JSON file per response, so they survive restarts.
+    ///
+    /// Files are written to a temporary file first and then renamed, so concurrent processes
+    /// never read partially written responses. The file I/O blocks, so it runs on the blocking
+    /// threads of the Tokio runtime, which the store must be used within.
+    #[derive(Debug, Clone)]
+    pub struct FileCacheStore {
+        dir: PathBuf,
+    }
+
+    impl FileCacheStore {
+        /// Stores the responses in the directory at `dir`, which is created on first use
+        pub fn new(dir: impl Into<PathBuf>) -> Self {
+            Self { dir: dir.into() }
+        }
+
+        /// Returns the path of the file of the response stored under the key
+        fn path(&self, key: &str) -> PathBuf {
+            self.dir.join(format!("{}.json", hex::encode(keccak256(key))))
+        }
+
+        fn now() -> u64 {
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+        }
+
+        /// Runs the file I/O on the blocking threads
+        async fn blocking<T: Send + 'static>(
+            f: impl FnOnce() -> Result<T, io::Error> + Send + 'static,
+        ) -> Result<T, io::Error> {
+            tokio::task::spawn_blocking(f)
+                .await
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+        }
+
+        fn get_blocking(path: &Path) -> Result<Option<Value>, io::Error> {
+            let contents = match fs::read(path) {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err),
+            };
+            let entry: Entry = serde_json::from_slice(&contents)?;
+            if entry.expires.map_or(false, |expires| expires <= Self::now()) {
+                let _ = fs::remove_file(path);
+                return Ok(None)
+            }
+            Ok(Some(entry.value))
+        }
+
+        fn insert_blocking(dir: &Path, path: &Path, entry: &Entry) -> Result<(), io::Error> {
+            fs::create_dir_all(dir)?;
+            let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+            fs::write(&tmp, serde_json::to_vec(entry)?)?;
+            fs::rename(tmp, path)
+        }
+    }
+
+    #[async_trait]
+    impl CacheStore for FileCacheStore {
+        type Error = io::Error;
+
+        async fn get(&self, key: &str) -> Result<Option<Value>, Self::Error> {
+            let path = self.path(key);
+            Self::blocking(move || Self::get_blocking(&path)).await
+        }
+
+        async fn insert(
+            &self,
+            key: &str,
+            value: Value,
+            ttl: Option<Duration>,
+        ) -> Result<(), Self::Error> {
+            let entry = Entry { expires: ttl.map(|ttl| Self::now() + ttl.as_secs().max(1)), value };
+            let (dir, path) = (self.dir.clone(), self.path(key));
+            Self::blocking(move || Self::insert_blocking(&dir, &path, &entry)).await
+        }
+    }
+}
+
+#[cfg(all(feature = "redis", not(target_arch = "wasm32")))]
+pub use self::redis::RedisCacheStore;
+
+#[cfg(all(feature = "redis", not(target_arch = "wasm32")))]
+mod redis {
+    use super::*;
+    use crate::redis::{RedisClient, RedisError};
+
+    /// The default prefix of the keys of the responses
+    const DEFAULT_PREFIX: &str = "ethers:cache:";
+
+    /// Keeps the responses in [Redis](https://redis.io), shared by all processes that use the
+    /// same server and key prefix.
+    ///
+    /// Responses are stored as JSON at the key `<prefix><key>`, responses with a ttl expire on
+    /// the server.
+    #[derive(Debug)]
+    pub struct RedisCacheStore {
+        client: RedisClient,
+        prefix: String,
+    }
+
+    impl RedisCacheStore {
+        /// Stores the responses on the server at the
+        /// `redis://[[username]:password@]host[:port][/db]` url, which is connected to on first
+        /// use, under the `ethers:cache:` prefix
+        pub fn new(url: &str) -> Result<Self, RedisError> {
+            Ok(Self { client: RedisClient::open(url)?, prefix: DEFAULT_PREFIX.to_string() })
+        }
+
+        /// Sets the prefix of the keys
+        #[must_use]
+        pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+            self.prefix = prefix.into();
+            self
+        }
+    }
+
+    #[async_trait]
+    impl CacheStore for RedisCacheStore {
+        type Error = RedisError;
+
+        async fn get(&self, key: &str) -> Result<Option<Value>, Self::Error> {
+            let key = format!("{}{key}", self.prefix);
+            match self.client.query(&[b"GET", key.as_bytes()]).await?.into_bulk()? {
+                Some(value) => serde_json::from_slice(&value)
+                    .map(Some)
+                    .map_err(|err| RedisError::UnexpectedReply(err.to_string())),
+                None => Ok(None),
+            }
+        }
+
+        async fn insert(
+            &self,
+            key: &str,
+            value: Value,
+            ttl: Option<Duration>,
+        ) -> Result<(), Self::Error> {
+            let key = format!("{}{key}", self.prefix);
+            let value = value.to_string();
+            match ttl {
+                Some(ttl) => {
+                    // expiry times must be positive
+                    let ttl = ttl.as_millis().max(1).to_string();
+                    self.client
+                        .query(&[b"SET", key.as_bytes(), value.as_bytes(), b"PX", ttl.as_bytes()])
+                        .await?
+                }
+                None => self.client.query(&[b"SET", key.as_bytes(), value.as_bytes()]).await?,
+            };
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn expires_responses() {
+        let store = MemoryCacheStore::new();
+        store.insert("a", json!(1), None).await.unwrap();
+        store.insert("b", json!(2), Some(Duration::ZERO)).await.unwrap();
+        assert_eq!(store.get("a").await.unwrap(), Some(json!(1)));
+        assert_eq!(store.get("b").await.unwrap(), None);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn persists_responses_in_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileCacheStore::new(dir.path().join("cache"));
+        assert_eq!(store.get("a").await.unwrap(), None);
+        store.insert("a", json!({"number": "0x1"}), None).await.unwrap();
+
+        // another store of the same directory reads the response
+        let store = FileCacheStore::new(dir.path().join("cache"));
+        assert_eq!(store.get("a").await.unwrap(), Some(json!({"number": "0x1"})));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "redis")]
+    #[ignore = "needs a Redis server at redis://127.0.0.1"]
+    async fn persists_responses_in_redis() {
+        let store = RedisCacheStore::new(crate::redis::TEST_URL)
+            .unwrap()
+            .prefix(crate::redis::test_prefix());
+        assert_eq!(store.get("a").await.unwrap(), None);
+        store.insert("a", json!({"number": "0x1"}), None).await.unwrap();
+        store.insert("b", json!(2), Some(Duration::from_millis(1))).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(store.get("a").await.unwrap(), Some(json!({"number": "0x1"})));
+        assert_eq!(store.get("b").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use ethers_core::types::{Address, Signature, U64};
+use ethers_providers::{FlashbotsSigner, FromErr, Http, Middleware, Provider};
+use ethers_signers::Signer;
+use thiserror::Error;
+use url::Url;
+
+/// Signs the requests to the relay with a [`Signer`]
+#[derive(Debug)]
+struct RelaySigner<S>(S);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S: Signer> FlashbotsSigner for RelaySigner<S> {
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    async fn sign_message(
+        &self,
+        message: &str,
+    ) -> Result<Signature, Box<dyn std::error::Error + Send + Sync>> {
+        self.0.sign_message(message).await.map_err(|err| err.to_string().into())
+    }
+}
+
+#[derive(Debug)]
+/// Middleware that sends the bundle requests of a searcher to a Flashbots relay, signed with a
+/// reputation key, and all other requests to the inner middleware.
+///
+/// The relay is a [`Provider`] over an [`Http`] client created with
+/// [`Http::new_with_flashbots_signer`], so the [`FlashbotsApi`](ethers_providers::FlashbotsApi)
+/// bundle methods can be called on [`FlashbotsMiddleware::relay`]. The reputation key can be held
+/// by any [`Signer`], e.g. a remote signer.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::BundleRequest;
+/// use ethers_middleware::flashbots::FlashbotsMiddleware;
+/// use ethers_providers::{FlashbotsApi, Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::convert::TryFrom;
+/// use url::Url;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// // the reputation key, which should not hold funds
+/// let key: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let client =
+///     FlashbotsMiddleware::new(provider, Url::parse("https://relay.flashbots.net")?, key);
+///
+/// let block = client.next_block().await?;
+/// let bundle = BundleRequest::new(vec![/* signed transactions */], block);
+/// let sent = client.relay().send_bundle(bundle).await?;
+/// let stats = client.relay().get_bundle_stats(sent.bundle_hash, block).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FlashbotsMiddleware<M> {
+    inner: M,
+    relay: Provider<Http>,
+    reputation_address: Address,
+}
+
+impl<M: Middleware> FlashbotsMiddleware<M> {
+    /// Creates a middleware that sends bundle requests to the relay at the url, signed with the
+    /// `signer`
+    pub fn new<S: Signer + 'static>(inner: M, relay_url: impl Into<Url>, signer: S) -> Self {
+        let reputation_address = signer.address();
+        let relay = Http::new_with_flashbots_signer(relay_url, RelaySigner(signer));
+        Self { inner, relay: Provider::new(relay), reputation_address }
+    }
+
+    /// Returns the provider of the relay, whose requests are signed
+    pub fn relay(&self) -> &Provider<Http> {
+        &self.relay
+    }
+
+    /// Returns the address of the reputation key
+    pub fn reputation_address(&self) -> Address {
+        self.reputation_address
+    }
+
+    /// Returns the number of the next block, the earliest block a bundle can target
+    pub async fn next_block(&self) -> Result<U64, FlashbotsMiddlewareError<M>> {
+        let block = self
+            .inner
+            .get_block_number()
+            .await
+            .map_err(FlashbotsMiddlewareError::MiddlewareError)?;
+        Ok(block + 1)
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the Flashbots middleware interacts with the blockchain
+pub enum FlashbotsMiddlewareError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> FromErr<M::Error> for FlashbotsMiddlewareError<M> {
+    fn from(src: M::Error) -> FlashbotsMiddlewareError<M> {
+        FlashbotsMiddlewareError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for FlashbotsMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = FlashbotsMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::utils::{hex, keccak256};
+    use ethers_signers::LocalWallet;
+
+    #[tokio::test]
+    async fn signs_messages_with_the_signer() {
+        let key = LocalWallet::new(&mut rand::thread_rng());
+        let message = format!("0x{}", hex::encode(keccak256(b"{}")));
+
+        let signer = RelaySigner(key.clone());
+        assert_eq!(FlashbotsSigner::address(&signer), key.address());
+        let signature = FlashbotsSigner::sign_message(&signer, &message).await.unwrap();
+        signature.verify(message, key.address()).unwrap();
+
+        let client = FlashbotsMiddleware::new(
+            Provider::<Http>::try_from("http://localhost:8545").unwrap(),
+            Url::parse("https://relay.flashbots.net").unwrap(),
+            key.clone(),
+        );
+        assert_eq!(client.reputation_address(), key.address());
+        assert_eq!(client.relay().as_ref().flashbots_address(), Some(key.address()));
+    }
+}
@@ -6,7 +6,7 @@ mod linear;
 pub use linear::LinearGasPrice;
 
 use async_trait::async_trait;
-use ethers_core::types::{BlockId, TransactionRequest, TxHash, U256};
+use ethers_core::types::{BlockId, BlockNumber, TxHash, U256};
 use ethers_providers::{interval, FromErr, Middleware, PendingTransaction, StreamExt};
 use futures_util::lock::Mutex;
 use instant::Instant;
@@ -29,6 +29,167 @@ pub trait GasEscalator: Send + Sync + std::fmt::Debug {
     fn get_gas_price(&self, initial_price: U256, time_elapsed: u64) -> U256;
 }
 
+/// The minimum increase of the fees of a replacement transaction in percent, nodes reject
+/// replacements with smaller increases as underpriced
+pub const MIN_REPLACEMENT_BUMP: u64 = 10;
+
+/// The fees that are escalated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fees {
+    /// The gas price of a legacy or EIP-2930 transaction
+    Legacy { gas_price: U256 },
+    /// The fees of an EIP-1559 transaction
+    Eip1559 { max_fee_per_gas: U256, max_priority_fee_per_gas: U256 },
+}
+
+impl Fees {
+    /// Returns the fees of the transaction, `None` if they aren't set
+    pub fn of(tx: &TypedTransaction) -> Option<Self> {
+        match tx {
+            TypedTransaction::Eip1559(inner) => Some(Fees::Eip1559 {
+                max_fee_per_gas: inner.max_fee_per_gas?,
+                max_priority_fee_per_gas: inner.max_priority_fee_per_gas?,
+            }),
+            _ => Some(Fees::Legacy { gas_price: tx.gas_price()? }),
+        }
+    }
+
+    /// Sets the fees of the transaction
+    pub fn apply(self, tx: &mut TypedTransaction) {
+        match (self, tx) {
+            (
+                Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas },
+                TypedTransaction::Eip1559(inner),
+            ) => {
+                inner.max_fee_per_gas = Some(max_fee_per_gas);
+                inner.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+            }
+            (Fees::Legacy { gas_price }, tx) => {
+                tx.set_gas_price(gas_price);
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the fees raised by `percent`, at least by [`MIN_REPLACEMENT_BUMP`] percent. The max
+    /// fee of EIP-1559 fees is raised to the priority fee if it's lower.
+    pub fn bump(self, percent: u64) -> Fees {
+        let bump = |fee: U256| fee + fee * percent.max(MIN_REPLACEMENT_BUMP) / 100;
+        match self {
+            Fees::Legacy { gas_price } => Fees::Legacy { gas_price: bump(gas_price) },
+            Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => Fees::Eip1559 {
+                max_fee_per_gas: bump(max_fee_per_gas),
+                max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+            }
+            .normalized(),
+        }
+    }
+
+    /// Returns the fees with the gas price or the max fee and the priority fee capped at `cap`
+    pub fn cap(self, cap: U256) -> Fees {
+        match self {
+            Fees::Legacy { gas_price } => Fees::Legacy { gas_price: gas_price.min(cap) },
+            Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => Fees::Eip1559 {
+                max_fee_per_gas: max_fee_per_gas.min(cap),
+                max_priority_fee_per_gas: max_priority_fee_per_gas.min(cap),
+            },
+        }
+    }
+
+    /// Returns whether a transaction with these fees may replace one with the `current` fees,
+    /// i.e. whether every fee rose by at least [`MIN_REPLACEMENT_BUMP`] percent
+    pub fn replaces(&self, current: &Fees) -> bool {
+        let rose = |fee: U256, current: U256| fee >= current + current * MIN_REPLACEMENT_BUMP / 100;
+        match (*self, *current) {
+            (Fees::Legacy { gas_price }, Fees::Legacy { gas_price: current }) => {
+                rose(gas_price, current)
+            }
+            (
+                Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas },
+                Fees::Eip1559 {
+                    max_fee_per_gas: current_max,
+                    max_priority_fee_per_gas: current_tip,
+                },
+            ) => rose(max_fee_per_gas, current_max) && rose(max_priority_fee_per_gas, current_tip),
+            _ => false,
+        }
+    }
+
+    /// Raises the max fee to the priority fee, which it may not be lower than
+    fn normalized(self) -> Fees {
+        match self {
+            Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => Fees::Eip1559 {
+                max_fee_per_gas: max_fee_per_gas.max(max_priority_fee_per_gas),
+                max_priority_fee_per_gas,
+            },
+            fees => fees,
+        }
+    }
+
+    /// Returns the fees escalated from the `initial` fees, or `None` if they don't increase
+    /// enough over the `current` fees to replace the transaction
+    fn escalate<E: GasEscalator>(
+        escalator: &E,
+        initial: Fees,
+        current: Fees,
+        time_elapsed: u64,
+    ) -> Option<Fees> {
+        let escalate =
+            |initial, current: U256| escalator.get_gas_price(initial, time_elapsed).max(current);
+        let fees = match (initial, current) {
+            (Fees::Legacy { gas_price: initial }, Fees::Legacy { gas_price }) => {
+                Fees::Legacy { gas_price: escalate(initial, gas_price) }
+            }
+            (
+                Fees::Eip1559 {
+                    max_fee_per_gas: initial_max,
+                    max_priority_fee_per_gas: initial_tip,
+                },
+                Fees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas },
+            ) => Fees::Eip1559 {
+                max_fee_per_gas: escalate(initial_max, max_fee_per_gas),
+                max_priority_fee_per_gas: escalate(initial_tip, max_priority_fee_per_gas),
+            }
+            .normalized(),
+            _ => return None,
+        };
+        (fees != current && fees.replaces(&current)).then_some(fees)
+    }
+}
+
+/// Fills the transaction with the inner middleware and sets its nonce to the pending transaction
+/// count of its sender if it has none, so replacements can reuse the nonce. Returns whether the
+/// nonce was set, it isn't if the transaction already has one or if it has no sender.
+pub(crate) async fn fill_with_nonce<M: Middleware>(
+    inner: &M,
+    tx: &mut TypedTransaction,
+    block: Option<BlockId>,
+) -> Result<bool, M::Error> {
+    inner.fill_transaction(tx, block).await?;
+    let from = match (tx.nonce(), tx.from()) {
+        (None, Some(from)) => *from,
+        _ => return Ok(false),
+    };
+    let nonce = inner.get_transaction_count(from, Some(BlockNumber::Pending.into())).await?;
+    tx.set_nonce(nonce);
+    Ok(true)
+}
+
+/// A transaction monitored for escalation, along with its replacements
+#[derive(Debug, Clone)]
+pub struct MonitoredTransaction {
+    /// The hashes of the transaction and of its replacements, in order of broadcast
+    pub hashes: Vec<TxHash>,
+    /// The last broadcast replacement
+    pub tx: TypedTransaction,
+    /// The fees of the first broadcast, which are escalated
+    pub initial_fees: Fees,
+    /// When the transaction was first broadcast
+    pub sent_at: Instant,
+    /// The block the transaction was sent with, which replacements are sent with as well
+    pub block: Option<BlockId>,
+}
+
 #[derive(Debug, Clone)]
 /// The frequency at which transactions will be bumped
 pub enum Frequency {
@@ -42,6 +203,11 @@ pub enum Frequency {
 /// A Gas escalator allows bumping transactions' gas price to avoid getting them
 /// stuck in the memory pool.
 ///
+/// The gas price of legacy and EIP-2930 transactions is escalated, of EIP-1559 transactions
+/// both the max fee and the priority fee are. Replacements are only sent once every fee rose by
+/// [`MIN_REPLACEMENT_BUMP`] percent. The transaction and its replacements share a nonce, once
+/// any of them is mined the escalation stops.
+///
 /// ```no_run
 /// use ethers_providers::{Provider, Http};
 /// use ethers_middleware::{
@@ -67,8 +233,7 @@ pub struct GasEscalatorMiddleware<M, E> {
     pub(crate) inner: Arc<M>,
     pub(crate) escalator: E,
     /// The transactions which are currently being monitored for escalation
-    #[allow(clippy::type_complexity)]
-    pub txs: Arc<Mutex<Vec<(TxHash, TransactionRequest, Instant, Option<BlockId>)>>>,
+    pub txs: Arc<Mutex<Vec<MonitoredTransaction>>>,
     frequency: Frequency,
 }
 
@@ -103,7 +268,11 @@ where
         tx: T,
         block: Option<BlockId>,
     ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
-        let tx = tx.into();
+        let mut tx = tx.into();
+        fill_with_nonce(self.inner(), &mut tx, block)
+            .await
+            .map_err(GasEscalatorError::MiddlewareError)?;
+        let fees = Fees::of(&tx).ok_or(GasEscalatorError::UnsupportedTxType)?;
 
         let pending_tx = self
             .inner()
@@ -111,15 +280,28 @@ where
             .await
             .map_err(GasEscalatorError::MiddlewareError)?;
 
-        let tx = match tx {
-            TypedTransaction::Legacy(inner) => inner,
-            TypedTransaction::Eip2930(inner) => inner.tx,
-            _ => return Err(GasEscalatorError::UnsupportedTxType),
-        };
-
-        // insert the tx in the pending txs
-        let mut lock = self.txs.lock().await;
-        lock.push((*pending_tx, tx, Instant::now(), block));
+        let mut txs = self.txs.lock().await;
+        // a transaction with the nonce of a monitored one replaces it
+        let replaced = txs.iter_mut().find(|monitored| {
+            tx.nonce().is_some() &&
+                monitored.tx.nonce() == tx.nonce() &&
+                monitored.tx.from() == tx.from()
+        });
+        match replaced {
+            Some(monitored) => {
+                monitored.hashes.push(*pending_tx);
+                monitored.tx = tx;
+                monitored.initial_fees = fees;
+                monitored.sent_at = Instant::now();
+            }
+            None => txs.push(MonitoredTransaction {
+                hashes: vec![*pending_tx],
+                tx,
+                initial_fees: fees,
+                sent_at: Instant::now(),
+                block,
+            }),
+        }
 
         Ok(pending_tx)
     }
@@ -180,56 +362,69 @@ where
             // Pop all transactions and re-insert those that have not been included yet
             for _ in 0..len {
                 // this must never panic as we're explicitly within bounds
-                let (tx_hash, mut replacement_tx, time, priority) =
-                    txs.pop().expect("should have element in vector");
-
-                let receipt = self.get_transaction_receipt(tx_hash).await?;
-                tracing::trace!(tx_hash = ?tx_hash, "checking if exists");
-                if receipt.is_none() {
-                    let old_gas_price = replacement_tx.gas_price.expect("gas price must be set");
-                    // Get the new gas price based on how much time passed since the
-                    // tx was last broadcast
-                    let new_gas_price = self
-                        .escalator
-                        .get_gas_price(old_gas_price, now.duration_since(time).as_secs());
-
-                    let new_txhash = if new_gas_price != old_gas_price {
-                        // bump the gas price
-                        replacement_tx.gas_price = Some(new_gas_price);
-
-                        // the tx hash will be different so we need to update it
-                        match self.inner().send_transaction(replacement_tx.clone(), priority).await
-                        {
-                            Ok(new_tx_hash) => {
-                                let new_tx_hash = *new_tx_hash;
-                                tracing::trace!(
-                                    old_tx_hash = ?tx_hash,
-                                    new_tx_hash = ?new_tx_hash,
-                                    old_gas_price = ?old_gas_price,
-                                    new_gas_price = ?new_gas_price,
-                                    "escalated"
-                                );
-                                new_tx_hash
-                            }
-                            Err(err) => {
-                                if err.to_string().contains("nonce too low") {
-                                    // ignore "nonce too low" errors because they
-                                    // may happen if we try to broadcast a higher
-                                    // gas price tx when one of the previous ones
-                                    // was already mined (meaning we also do not
-                                    // push it back to the pending txs vector)
-                                    continue
-                                } else {
-                                    return Err(GasEscalatorError::MiddlewareError(err))
-                                }
+                let mut monitored = txs.pop().expect("should have element in vector");
+
+                tracing::trace!(tx_hashes = ?monitored.hashes, "checking if exists");
+                let mut mined = false;
+                for tx_hash in &monitored.hashes {
+                    if self.get_transaction_receipt(*tx_hash).await?.is_some() {
+                        mined = true;
+                        break
+                    }
+                }
+                if mined {
+                    continue
+                }
+
+                // Get the new fees based on how much time passed since the tx was first
+                // broadcast
+                let current = Fees::of(&monitored.tx).expect("fees must be set");
+                let escalated = Fees::escalate(
+                    &self.escalator,
+                    monitored.initial_fees,
+                    current,
+                    now.duration_since(monitored.sent_at).as_secs(),
+                );
+                if let Some(fees) = escalated {
+                    let mut replacement_tx = monitored.tx.clone();
+                    fees.apply(&mut replacement_tx);
+
+                    // the tx hash will be different so we need to track it
+                    match self
+                        .inner()
+                        .send_transaction(replacement_tx.clone(), monitored.block)
+                        .await
+                    {
+                        Ok(new_tx_hash) => {
+                            let new_tx_hash = *new_tx_hash;
+                            tracing::trace!(
+                                old_tx_hash = ?monitored.hashes.last(),
+                                new_tx_hash = ?new_tx_hash,
+                                old_fees = ?current,
+                                new_fees = ?fees,
+                                "escalated"
+                            );
+                            monitored.hashes.push(new_tx_hash);
+                            monitored.tx = replacement_tx;
+                        }
+                        Err(err) => {
+                            let message = err.to_string();
+                            if message.contains("nonce too low") {
+                                // ignore "nonce too low" errors because they
+                                // may happen if we try to broadcast a higher
+                                // gas price tx when one of the previous ones
+                                // was already mined (meaning we also do not
+                                // push it back to the pending txs vector)
+                                continue
+                            } else if !message.contains("underpriced") {
+                                return Err(GasEscalatorError::MiddlewareError(err))
                             }
+                            // keep the last replacement if the node requires a larger bump
                         }
-                    } else {
-                        tx_hash
-                    };
-
-                    txs.push((new_txhash, replacement_tx, time, priority));
+                    }
                 }
+
+                txs.push(monitored);
             }
         }
 
@@ -251,6 +446,93 @@ pub enum GasEscalatorError<M: Middleware> {
     /// Thrown when an internal middleware errors
     MiddlewareError(M::Error),
 
-    #[error("Gas escalation is only supported for EIP2930 or Legacy transactions")]
+    #[error("Gas escalation requires the gas price or the EIP-1559 fees to be set")]
     UnsupportedTxType,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn escalates_eip1559_fees() {
+        let escalator = LinearGasPrice::new(100u64, 1u64, None::<u64>);
+        let initial =
+            Fees::Eip1559 { max_fee_per_gas: 1_000.into(), max_priority_fee_per_gas: 100.into() };
+
+        // the priority fee must rise by 10% as well
+        assert_eq!(Fees::escalate(&escalator, initial, initial, 0), None);
+        assert_eq!(
+            Fees::escalate(&escalator, initial, initial, 1),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 1_100.into(),
+                max_priority_fee_per_gas: 200.into()
+            })
+        );
+        let current =
+            Fees::Eip1559 { max_fee_per_gas: 1_100.into(), max_priority_fee_per_gas: 200.into() };
+        assert_eq!(Fees::escalate(&escalator, initial, current, 2), None);
+        assert_eq!(
+            Fees::escalate(&escalator, initial, current, 3),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 1_300.into(),
+                max_priority_fee_per_gas: 400.into()
+            })
+        );
+
+        // the max fee is raised to the priority fee rather than capping the priority fee
+        let escalator = LinearGasPrice::new(10u64, 1u64, None::<u64>);
+        let initial =
+            Fees::Eip1559 { max_fee_per_gas: 80.into(), max_priority_fee_per_gas: 100.into() };
+        let current =
+            Fees::Eip1559 { max_fee_per_gas: 100.into(), max_priority_fee_per_gas: 100.into() };
+        assert_eq!(
+            Fees::escalate(&escalator, initial, current, 3),
+            Some(Fees::Eip1559 {
+                max_fee_per_gas: 130.into(),
+                max_priority_fee_per_gas: 130.into()
+            })
+        );
+
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new().into();
+        assert_eq!(Fees::of(&tx), None);
+        current.apply(&mut tx);
+        assert_eq!(Fees::of(&tx), Some(current));
+    }
+
+    #[test]
+    fn bumps_fees() {
+        let fees =
+            Fees::Eip1559 { max_fee_per_gas: 100.into(), max_priority_fee_per_gas: 100.into() };
+        // bumps are raised to the minimum
+        let bumped = fees.bump(5);
+        assert_eq!(
+            bumped,
+            Fees::Eip1559 { max_fee_per_gas: 110.into(), max_priority_fee_per_gas: 110.into() }
+        );
+        assert!(bumped.replaces(&fees));
+        assert!(!bumped.cap(105.into()).replaces(&fees));
+
+        let fees = Fees::Legacy { gas_price: 100.into() };
+        assert_eq!(fees.bump(20), Fees::Legacy { gas_price: 120.into() });
+        assert!(!fees.replaces(&fees));
+        assert!(!fees.bump(20).replaces(&bumped));
+    }
+
+    #[test]
+    fn escalates_gas_price() {
+        let escalator = LinearGasPrice::new(5u64, 1u64, Some(1_000u64));
+        let fees = |gas_price: u64| Fees::Legacy { gas_price: gas_price.into() };
+
+        // the gas price is escalated from the initial one, once it rose by 10%
+        assert_eq!(Fees::escalate(&escalator, fees(100), fees(100), 1), None);
+        assert_eq!(Fees::escalate(&escalator, fees(100), fees(100), 2), Some(fees(110)));
+        assert_eq!(Fees::escalate(&escalator, fees(100), fees(110), 3), None);
+        assert_eq!(Fees::escalate(&escalator, fees(980), fees(1_000), 10), None);
+
+        let mut tx: TypedTransaction = TransactionRequest::new().into();
+        fees(7).apply(&mut tx);
+        assert_eq!(tx.gas_price(), Some(7.into()));
+    }
+}
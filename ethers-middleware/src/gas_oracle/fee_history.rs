@@ -0,0 +1,116 @@
+use super::{GasCategory, GasOracle, GasOracleError, Result};
+use async_trait::async_trait;
+use ethers_core::types::{FeeHistoryStrategy, U256};
+use ethers_providers::Middleware;
+use std::fmt::Debug;
+
+/// Gas oracle that estimates fees from the `eth_feeHistory` of a [`Middleware`], without relying
+/// on a third-party API.
+///
+/// The priority fee is the median of the configured reward percentile of the priority fees paid
+/// in recent blocks, the max fee adds the headroom of the base fee multiplier to the next base
+/// fee, see [`FeeHistoryStrategy`]. Legacy gas prices are the next base fee plus the priority
+/// fee.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::gas_oracle::{FeeHistoryOracle, GasCategory, GasOracle};
+/// use ethers_providers::{Http, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let oracle = FeeHistoryOracle::new(provider).category(GasCategory::Fast).base_fee_multiplier(150);
+/// let (max_fee, priority_fee) = oracle.estimate_eip1559_fees().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct FeeHistoryOracle<M: Middleware> {
+    provider: M,
+    strategy: FeeHistoryStrategy,
+}
+
+impl<M: Middleware> FeeHistoryOracle<M> {
+    /// Creates an oracle that uses the [standard](FeeHistoryStrategy::standard) strategy
+    pub fn new(provider: M) -> Self {
+        Self::with_strategy(provider, FeeHistoryStrategy::standard())
+    }
+
+    /// Creates an oracle that uses the strategy, e.g. with more blocks, a minimum priority fee or
+    /// another base fee multiplier
+    pub fn with_strategy(provider: M, strategy: FeeHistoryStrategy) -> Self {
+        Self { provider, strategy }
+    }
+
+    /// Sets the reward percentile of the category: the 10th for `SafeLow`, the 50th for
+    /// `Standard`, the 90th for `Fast` and the 99th for `Fastest`
+    pub fn category(self, gas_category: GasCategory) -> Self {
+        let percentile = match gas_category {
+            GasCategory::SafeLow => 10.0,
+            GasCategory::Standard => 50.0,
+            GasCategory::Fast => 90.0,
+            GasCategory::Fastest => 99.0,
+        };
+        self.reward_percentile(percentile)
+    }
+
+    /// Sets the percentile of the priority fees paid in each block, e.g. `50.0` for the median
+    pub fn reward_percentile(mut self, percentile: f64) -> Self {
+        self.strategy.reward_percentile = percentile;
+        self
+    }
+
+    /// Sets the percentage of the next base fee the max fee allows for, e.g. `200` to stay
+    /// valid even if the base fee doubles
+    pub fn base_fee_multiplier(mut self, percent: u64) -> Self {
+        self.strategy.base_fee_multiplier = percent;
+        self
+    }
+
+    /// Sets the number of past blocks to fetch the fee history of
+    pub fn block_count(mut self, block_count: u64) -> Self {
+        self.strategy.block_count = block_count;
+        self
+    }
+
+    /// Sets the lower bound of the estimated priority fee
+    pub fn min_priority_fee(mut self, fee: impl Into<U256>) -> Self {
+        self.strategy.min_priority_fee = fee.into();
+        self
+    }
+
+    /// Returns the strategy of the estimates
+    pub fn strategy(&self) -> &FeeHistoryStrategy {
+        &self.strategy
+    }
+
+    async fn estimate(&self, strategy: FeeHistoryStrategy) -> Result<(U256, U256)>
+    where
+        M::Error: 'static,
+    {
+        self.provider
+            .estimate_eip1559_fees_from_history(strategy)
+            .await
+            .map_err(|err| GasOracleError::ProviderError(Box::new(err)))
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> GasOracle for FeeHistoryOracle<M>
+where
+    M::Error: 'static,
+{
+    async fn fetch(&self) -> Result<U256> {
+        // without headroom the max fee is the next base fee plus the priority fee
+        let (gas_price, _) = self.estimate(self.strategy.clone().base_fee_multiplier(100)).await?;
+        Ok(gas_price)
+    }
+
+    async fn estimate_eip1559_fees(&self) -> Result<(U256, U256)> {
+        self.estimate(self.strategy.clone()).await
+    }
+}
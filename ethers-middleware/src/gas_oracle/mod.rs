@@ -29,6 +29,12 @@ pub use gas_now::GasNow;
 pub mod provider_oracle;
 pub use provider_oracle::ProviderOracle;
 
+pub mod fee_history;
+pub use fee_history::FeeHistoryOracle;
+
+pub mod registry;
+pub use registry::{GasRegistry, GasStrategy, RegistryGasMiddleware, RegistryGasMiddlewareError};
+
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 use ethers_core::types::U256;
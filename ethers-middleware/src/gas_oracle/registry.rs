@@ -0,0 +1,367 @@
+use super::GWEI_TO_WEI_U256;
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, BlockId, Chain, PriorityFeeFallback, U256,
+};
+use ethers_providers::{FromErr, Middleware, PendingTransaction};
+use std::{collections::HashMap, convert::TryFrom, sync::Mutex};
+use thiserror::Error;
+
+/// How the fees of transactions are estimated on a chain, see [`GasRegistry`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GasStrategy {
+    /// Whether the chain only accepts legacy and EIP-2930 transactions, EIP-1559 transactions are
+    /// converted to legacy ones when they are filled
+    pub legacy: bool,
+    /// The lowest priority fee of EIP-1559 transactions, estimates below it are raised to it
+    pub min_priority_fee: Option<U256>,
+    /// The lowest gas price of legacy transactions, estimates below it are raised to it
+    pub min_gas_price: Option<U256>,
+}
+
+impl GasStrategy {
+    /// Strategy of a chain that implements EIP-1559
+    pub const fn eip1559() -> Self {
+        Self { legacy: false, min_priority_fee: None, min_gas_price: None }
+    }
+
+    /// Strategy of a chain that only accepts legacy transactions
+    pub const fn legacy() -> Self {
+        Self { legacy: true, min_priority_fee: None, min_gas_price: None }
+    }
+
+    /// Sets the lowest priority fee of EIP-1559 transactions
+    #[must_use]
+    pub fn min_priority_fee(mut self, fee: impl Into<U256>) -> Self {
+        self.min_priority_fee = Some(fee.into());
+        self
+    }
+
+    /// Sets the lowest gas price of legacy transactions
+    #[must_use]
+    pub fn min_gas_price(mut self, price: impl Into<U256>) -> Self {
+        self.min_gas_price = Some(price.into());
+        self
+    }
+
+    /// Returns the default strategy of the known chain.
+    ///
+    /// The minimum priority fee is the fixed [`PriorityFeeFallback::for_chain`] of the chain,
+    /// which legacy transactions have to pay as gas price as well.
+    pub fn for_chain(chain: Chain) -> Self {
+        let mut strategy = match chain {
+            // Bedrock accepts EIP-1559 transactions, and its sequencer orders them by their tip
+            // while nodes may estimate a zero tip
+            Chain::Optimism | Chain::OptimismGoerli => {
+                return Self::eip1559().min_priority_fee(GWEI_TO_WEI_U256 / 1000)
+            }
+            chain if chain.is_legacy() => Self::legacy(),
+            _ => Self::eip1559(),
+        };
+        // e.g. the validators of Polygon reject transactions paying less than 30 gwei
+        if let PriorityFeeFallback::Fixed(fee) = PriorityFeeFallback::for_chain(chain as u64) {
+            if !fee.is_zero() {
+                strategy = strategy.min_priority_fee(fee).min_gas_price(fee);
+            }
+        }
+        strategy
+    }
+}
+
+/// Maps chain ids to the [`GasStrategy`] used to fill the fees of their transactions, so clients
+/// of many chains don't have to configure each of them.
+///
+/// Chains of [`Chain`] use their [`GasStrategy::for_chain`] unless another strategy is set for
+/// them: EIP-1559 or legacy transactions, Polygon's 30 gwei minimum priority fee and the minimum
+/// tips of L2s. Other chains use the fallback strategy of the registry, which defaults to
+/// EIP-1559.
+#[derive(Clone, Debug, Default)]
+pub struct GasRegistry {
+    strategies: HashMap<u64, GasStrategy>,
+    fallback: GasStrategy,
+}
+
+impl GasRegistry {
+    /// Creates a registry of the [`GasStrategy::for_chain`] of known chains, with the EIP-1559
+    /// strategy as fallback
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the strategy of the chain
+    #[must_use]
+    pub fn with(mut self, chain_id: impl Into<u64>, strategy: GasStrategy) -> Self {
+        self.insert(chain_id, strategy);
+        self
+    }
+
+    /// Sets the strategy of chains that are not in the registry
+    #[must_use]
+    pub fn fallback(mut self, strategy: GasStrategy) -> Self {
+        self.fallback = strategy;
+        self
+    }
+
+    /// Sets the strategy of the chain, returning its previous one
+    pub fn insert(
+        &mut self,
+        chain_id: impl Into<u64>,
+        strategy: GasStrategy,
+    ) -> Option<GasStrategy> {
+        self.strategies.insert(chain_id.into(), strategy)
+    }
+
+    /// Returns the strategy of the chain
+    pub fn get(&self, chain_id: u64) -> GasStrategy {
+        if let Some(strategy) = self.strategies.get(&chain_id) {
+            return *strategy
+        }
+        Chain::try_from(chain_id).map(GasStrategy::for_chain).unwrap_or(self.fallback)
+    }
+}
+
+/// Middleware filling the fees of transactions with the [`GasStrategy`] of the chain it is
+/// connected to, looked up in a [`GasRegistry`].
+///
+/// Before the inner middleware fills the transaction, EIP-1559 transactions are converted to
+/// legacy ones on legacy chains, and the fees that are not set are estimated by the inner
+/// middleware and raised to the minimums of the chain. Fees set by the caller are never changed.
+///
+/// The chain id of the transaction is used if it is set, otherwise the one of the inner
+/// middleware, which is fetched once.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, Eip1559TransactionRequest};
+/// use ethers_middleware::gas_oracle::{GasRegistry, GasStrategy, RegistryGasMiddleware};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// // a private chain tipping at least 2 wei
+/// let registry = GasRegistry::default().with(1337u64, GasStrategy::eip1559().min_priority_fee(2));
+/// let client = RegistryGasMiddleware::new(provider, registry);
+///
+/// let tx = Eip1559TransactionRequest::new().to(Address::random()).value(100);
+/// client.send_transaction(tx, None).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RegistryGasMiddleware<M> {
+    inner: M,
+    registry: GasRegistry,
+    chain_id: Mutex<Option<u64>>,
+}
+
+impl<M> RegistryGasMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Wraps the middleware, filling the fees of transactions with the strategies of the
+    /// `registry`
+    pub fn new(inner: M, registry: GasRegistry) -> Self {
+        Self { inner, registry, chain_id: Mutex::new(None) }
+    }
+
+    /// Returns the registry of the strategies
+    pub fn registry(&self) -> &GasRegistry {
+        &self.registry
+    }
+
+    /// Returns the strategy used for the transaction
+    pub async fn strategy(
+        &self,
+        tx: &TypedTransaction,
+    ) -> Result<GasStrategy, RegistryGasMiddlewareError<M>> {
+        let chain_id = match tx.chain_id() {
+            Some(chain_id) => chain_id.as_u64(),
+            None => self.chain_id().await?,
+        };
+        Ok(self.registry.get(chain_id))
+    }
+
+    async fn chain_id(&self) -> Result<u64, RegistryGasMiddlewareError<M>> {
+        if let Some(chain_id) = *self.chain_id.lock().unwrap() {
+            return Ok(chain_id)
+        }
+        let chain_id =
+            self.inner.get_chainid().await.map_err(RegistryGasMiddlewareError::MiddlewareError)?;
+        let chain_id =
+            u64::try_from(chain_id).map_err(|_| RegistryGasMiddlewareError::InvalidChainId)?;
+        *self.chain_id.lock().unwrap() = Some(chain_id);
+        Ok(chain_id)
+    }
+}
+
+#[derive(Debug, Error)]
+/// Error thrown when the registry gas middleware interacts with the blockchain
+pub enum RegistryGasMiddlewareError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error("chain id does not fit in a u64")]
+    /// Thrown when the chain id of the inner middleware is too large
+    InvalidChainId,
+}
+
+impl<M: Middleware> FromErr<M::Error> for RegistryGasMiddlewareError<M> {
+    fn from(src: M::Error) -> RegistryGasMiddlewareError<M> {
+        RegistryGasMiddlewareError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for RegistryGasMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = RegistryGasMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        let strategy = self.strategy(tx).await?;
+
+        if strategy.legacy {
+            tx.downgrade_eip1559();
+        }
+
+        match tx {
+            TypedTransaction::Legacy(_) | TypedTransaction::Eip2930(_) => {
+                if tx.gas_price().is_none() {
+                    let mut gas_price = self
+                        .inner
+                        .get_gas_price()
+                        .await
+                        .map_err(RegistryGasMiddlewareError::MiddlewareError)?;
+                    if let Some(min_gas_price) = strategy.min_gas_price {
+                        gas_price = gas_price.max(min_gas_price);
+                    }
+                    tx.set_gas_price(gas_price);
+                }
+            }
+            TypedTransaction::Eip1559(ref mut inner) => {
+                if inner.max_priority_fee_per_gas.is_none() || inner.max_fee_per_gas.is_none() {
+                    let (mut max_fee_per_gas, mut max_priority_fee_per_gas) = self
+                        .inner
+                        .estimate_eip1559_fees(None)
+                        .await
+                        .map_err(RegistryGasMiddlewareError::MiddlewareError)?;
+                    if let Some(min_priority_fee) = strategy.min_priority_fee {
+                        if max_priority_fee_per_gas < min_priority_fee {
+                            // keep the headroom of the estimate for the base fee
+                            max_fee_per_gas += min_priority_fee - max_priority_fee_per_gas;
+                            max_priority_fee_per_gas = min_priority_fee;
+                        }
+                    }
+                    let max_fee_per_gas = *inner.max_fee_per_gas.get_or_insert(max_fee_per_gas);
+                    if inner.max_priority_fee_per_gas.is_none() {
+                        inner.max_priority_fee_per_gas =
+                            Some(max_priority_fee_per_gas.min(max_fee_per_gas));
+                    }
+                }
+            }
+        }
+
+        self.inner.fill_transaction(tx, block).await.map_err(FromErr::from)
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        self.fill_transaction(&mut tx, block).await?;
+        self.inner
+            .send_transaction(tx, block)
+            .await
+            .map_err(RegistryGasMiddlewareError::MiddlewareError)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, Block, Eip1559TransactionRequest, H256, U64};
+    use ethers_providers::Provider;
+
+    #[test]
+    fn knows_chains() {
+        let registry = GasRegistry::default();
+        assert_eq!(registry.get(Chain::Mainnet as u64), GasStrategy::eip1559());
+        assert!(registry.get(Chain::Arbitrum as u64).legacy);
+        assert_eq!(
+            registry.get(Chain::Polygon as u64).min_priority_fee,
+            Some(GWEI_TO_WEI_U256 * 30)
+        );
+        // unknown chains use the fallback
+        assert_eq!(registry.get(424242), GasStrategy::eip1559());
+        let registry = registry.fallback(GasStrategy::legacy());
+        assert_eq!(registry.get(424242), GasStrategy::legacy());
+    }
+
+    #[tokio::test]
+    async fn raises_priority_fee_to_minimum() {
+        let (provider, mock) = Provider::mocked();
+        let client = RegistryGasMiddleware::new(provider, GasRegistry::default());
+
+        // a node suggesting a tip of 1 gwei with a 10 gwei base fee
+        mock.push(GWEI_TO_WEI_U256).unwrap();
+        let base_fee_per_gas = Some(GWEI_TO_WEI_U256 * 10);
+        mock.push(Block::<H256> { base_fee_per_gas, ..Default::default() }).unwrap();
+
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(Address::zero())
+            .to(Address::zero())
+            .gas(21_000)
+            .nonce(0)
+            .chain_id(Chain::Polygon as u64)
+            .into();
+        client.fill_transaction(&mut tx, None).await.unwrap();
+
+        let tx = match tx {
+            TypedTransaction::Eip1559(tx) => tx,
+            tx => panic!("unexpected transaction type {tx:?}"),
+        };
+        let min_priority_fee = GWEI_TO_WEI_U256 * 30;
+        assert_eq!(tx.max_priority_fee_per_gas, Some(min_priority_fee));
+        assert!(tx.max_fee_per_gas.unwrap() > min_priority_fee);
+    }
+
+    #[tokio::test]
+    async fn converts_to_legacy_on_legacy_chains() {
+        let (provider, mock) = Provider::mocked();
+        let client = RegistryGasMiddleware::new(provider, GasRegistry::default());
+
+        mock.push(U256::from(100)).unwrap();
+        mock.push(U64::from(Chain::Arbitrum as u64)).unwrap();
+
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(Address::zero())
+            .to(Address::zero())
+            .gas(21_000)
+            .nonce(0)
+            .into();
+        client.fill_transaction(&mut tx, None).await.unwrap();
+
+        assert!(matches!(tx, TypedTransaction::Legacy(_)));
+        assert_eq!(tx.gas_price(), Some(100.into()));
+        mock.assert_request("eth_chainId", ()).unwrap();
+        mock.assert_request("eth_gasPrice", ()).unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use ethers_contract::{AbiError, BaseContract};
+use ethers_core::{
+    abi::parse_abi,
+    types::{transaction::eip2718::TypedTransaction, *},
+};
+use ethers_providers::{FromErr, Middleware};
+use thiserror::Error;
+
+/// The address of the `GasPriceOracle` predeploy of OP-stack chains, like Optimism and Base
+pub const GAS_PRICE_ORACLE: Address = H160([
+    0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x0f,
+]);
+
+/// The function of the `GasPriceOracle` that is called.
+const GET_L1_FEE: &str = "function getL1Fee(bytes memory _data) external view returns (uint256)";
+
+/// The cost of a transaction on an OP-stack chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TotalCost {
+    /// The fee of the execution on the L2, the gas limit times the gas price or max fee
+    pub l2_fee: U256,
+    /// The fee of publishing the transaction data on the L1
+    pub l1_fee: U256,
+    /// The value sent with the transaction
+    pub value: U256,
+}
+
+impl TotalCost {
+    /// Returns the most the sender pays for the transaction, including its value
+    pub fn total(&self) -> U256 {
+        self.l2_fee + self.l1_fee + self.value
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Middleware that accounts for the L1 data fee of transactions on OP-stack chains, like
+/// Optimism and Base.
+///
+/// Besides the fee of the execution on the L2, the sender of a transaction on these chains pays
+/// for publishing its data on the L1, which the `GasPriceOracle` predeploy computes. Balance checks
+/// and fee reports that only consider `gas * gas_price` underestimate the cost.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::l1_fee::L1FeeMiddleware;
+/// use ethers_providers::{Http, Provider};
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("https://mainnet.optimism.io")?;
+/// let client = L1FeeMiddleware::new(provider);
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100).from(Address::random()).into();
+/// let cost = client.estimate_total_cost(&tx, None).await?;
+/// println!("l1 fee: {}, total: {}", cost.l1_fee, cost.total());
+/// # Ok(())
+/// # }
+/// ```
+pub struct L1FeeMiddleware<M> {
+    inner: M,
+    oracle: Address,
+    contract: BaseContract,
+}
+
+impl<M> L1FeeMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Creates a new client that queries the [`GAS_PRICE_ORACLE`] predeploy
+    pub fn new(inner: M) -> Self {
+        Self::with_oracle(inner, GAS_PRICE_ORACLE)
+    }
+
+    /// Creates a new client that queries the `GasPriceOracle` at the address
+    pub fn with_oracle(inner: M, oracle: Address) -> Self {
+        let contract = parse_abi(&[GET_L1_FEE]).expect("could not parse ABI").into();
+        Self { inner, oracle, contract }
+    }
+
+    /// Returns the L1 data fee of the transaction, as computed by the `GasPriceOracle` from its
+    /// RLP encoding
+    pub async fn l1_fee(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<U256, L1FeeError<M>> {
+        let data = self.contract.encode("getL1Fee", tx.rlp())?;
+        let call: TypedTransaction = TransactionRequest::new().to(self.oracle).data(data).into();
+        let output = self.inner.call(&call, block).await.map_err(L1FeeError::MiddlewareError)?;
+        Ok(self.contract.decode_output("getL1Fee", output)?)
+    }
+
+    /// Returns the cost of the transaction, filling its missing fields first. The L2 fee of
+    /// EIP-1559 transactions uses the max fee, so it's an upper bound.
+    pub async fn estimate_total_cost(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<TotalCost, L1FeeError<M>> {
+        let mut tx = tx.clone();
+        self.inner.fill_transaction(&mut tx, block).await.map_err(L1FeeError::MiddlewareError)?;
+
+        let l2_fee = tx.gas().copied().unwrap_or_default() * tx.gas_price().unwrap_or_default();
+        let l1_fee = self.l1_fee(&tx, block).await?;
+        Ok(TotalCost { l2_fee, l1_fee, value: tx.value().copied().unwrap_or_default() })
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the L1 fee middleware interacts with the blockchain
+pub enum L1FeeError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error(transparent)]
+    /// Thrown when the call to the `GasPriceOracle` can't be encoded or decoded
+    AbiError(#[from] AbiError),
+}
+
+impl<M: Middleware> FromErr<M::Error> for L1FeeError<M> {
+    fn from(src: M::Error) -> L1FeeError<M> {
+        L1FeeError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for L1FeeMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = L1FeeError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::abi::{encode, Token};
+    use ethers_providers::Provider;
+
+    #[test]
+    fn oracle_address() {
+        assert_eq!(GAS_PRICE_ORACLE, "0x420000000000000000000000000000000000000F".parse().unwrap());
+    }
+
+    #[tokio::test]
+    async fn estimates_total_cost() {
+        let (provider, mock) = Provider::mocked();
+        let client = L1FeeMiddleware::new(provider);
+
+        let tx: TypedTransaction =
+            TransactionRequest::pay(Address::repeat_byte(1), 100).gas(21_000).gas_price(2).into();
+        mock.push::<Bytes, Bytes>(encode(&[Token::Uint(1_000.into())]).into()).unwrap();
+        let cost = client.estimate_total_cost(&tx, None).await.unwrap();
+        assert_eq!(
+            cost,
+            TotalCost { l2_fee: 42_000.into(), l1_fee: 1_000.into(), value: 100.into() }
+        );
+        assert_eq!(cost.total(), 43_100.into());
+
+        let data = client.contract.encode("getL1Fee", tx.rlp()).unwrap();
+        let call: TypedTransaction =
+            TransactionRequest::new().to(GAS_PRICE_ORACLE).data(data).into();
+        mock.assert_request("eth_call", (call, BlockNumber::Latest)).unwrap();
+    }
+}
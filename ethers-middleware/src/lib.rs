@@ -7,6 +7,10 @@
 /// their timely inclusion.
 pub mod gas_escalator;
 
+/// The [Transaction Manager](crate::tx_manager::TxManagerMiddleware) tracks sent transactions
+/// until they are mined, rebroadcasting dropped and replacing stuck transactions
+pub mod tx_manager;
+
 /// The gas oracle middleware is used to get the gas price from a list of gas oracles
 /// instead of using eth_gasPrice. For usage examples, refer to the
 /// [`GasOracle`](crate::gas_oracle::GasOracle) trait.
@@ -17,6 +21,10 @@ pub mod gas_oracle;
 pub mod nonce_manager;
 pub use nonce_manager::NonceManagerMiddleware;
 
+/// The [nonce recovery middleware](crate::nonce_recovery::NonceRecoveryMiddleware) resyncs the
+/// nonce and resends transactions rejected with nonce errors
+pub mod nonce_recovery;
+
 /// The [Transformer](crate::transformer::TransformerMiddleware) is used to intercept transactions
 /// and transform them to be sent via various supported transformers, e.g.,
 /// [DSProxy](crate::transformer::DsProxy)
@@ -27,6 +35,11 @@ pub mod transformer;
 pub mod signer;
 pub use signer::SignerMiddleware;
 
+/// The [NodeSigner](crate::NodeSigner) is a [`Signer`](ethers_signers::Signer) for accounts that
+/// are managed by the node, for use with the [`SignerMiddleware`]
+pub mod node_signer;
+pub use node_signer::{NodeSigner, NodeSignerError};
+
 /// The [Policy](crate::PolicyMiddleware) is used to ensure transactions comply with the rules
 /// configured in the `PolicyMiddleware` before sending them.
 pub mod policy;
@@ -37,6 +50,49 @@ pub use policy::PolicyMiddleware;
 pub mod timelag;
 pub use timelag::TimeLag;
 
+/// The [L1 fee middleware](crate::l1_fee::L1FeeMiddleware) estimates the L1 data fee of
+/// transactions on OP-stack chains
+pub mod l1_fee;
+
+/// The [Flashbots middleware](crate::flashbots::FlashbotsMiddleware) sends bundle requests to a
+/// Flashbots relay, signed with a reputation key
+pub mod flashbots;
+
+/// The [private transaction middleware](crate::private_tx::PrivateTxMiddleware) sends
+/// transactions to a private relay, falling back to the public mempool after a deadline
+pub mod private_tx;
+
+/// The [broadcast middleware](crate::broadcast::BroadcastMiddleware) submits signed transactions
+/// to backup endpoints along with the inner middleware
+pub mod broadcast;
+
+/// The [account abstraction middleware](crate::account_abstraction::AccountAbstractionMiddleware)
+/// sends transactions as ERC-4337 user operations of a smart account
+pub mod account_abstraction;
+
+/// The [simulation middleware](crate::simulation::SimulationMiddleware) simulates transactions
+/// before sending them, returning their decoded revert reason if they would fail
+pub mod simulation;
+
+/// The [cache middleware](crate::cache::CacheMiddleware) caches the responses of reads that can
+/// no longer change, and of recent reads for a configured time
+pub mod cache;
+
+/// The [tracing middleware](crate::tracing_spans::TracingMiddleware) opens a `tracing` span for
+/// every step of sending a transaction
+pub mod tracing_spans;
+
+/// The [metrics middleware](crate::metrics::MetricsMiddleware) records metrics of sent
+/// transactions with the [`metrics`](::metrics) facade
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+/// A minimal [Redis](https://redis.io) client for the stores that are shared between processes,
+/// the [`RedisNonceStore`](crate::nonce_manager::RedisNonceStore) and the
+/// [`RedisCacheStore`](crate::cache::RedisCacheStore)
+#[cfg(all(feature = "redis", not(target_arch = "wasm32")))]
+pub mod redis;
+
 /// The [MiddlewareBuilder](crate::MiddlewareBuilder) provides a way to compose many
 /// [`Middleware`](ethers_providers::Middleware) in a concise way
 pub mod builder;
@@ -0,0 +1,346 @@
+use async_trait::async_trait;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockId, BlockNumber};
+use ethers_providers::{FromErr, Middleware, PendingTransaction};
+use std::{sync::Arc, time::Duration};
+use thiserror::Error;
+
+/// Counter of the transactions sent
+pub const TRANSACTIONS_SENT: &str = "ethers_transactions_sent_total";
+
+/// Counter of the transactions that failed to be sent
+pub const TRANSACTION_SEND_ERRORS: &str = "ethers_transaction_send_errors_total";
+
+/// Counter of the transactions that were mined, by status, `success` or `reverted`
+pub const TRANSACTIONS_CONFIRMED: &str = "ethers_transactions_confirmed_total";
+
+/// Counter of the transactions that were not mined before the confirmation timeout
+pub const TRANSACTIONS_UNCONFIRMED: &str = "ethers_transactions_unconfirmed_total";
+
+/// Histogram of the time from sending transactions until they were mined, in seconds
+pub const CONFIRMATION_DURATION: &str = "ethers_transaction_confirmation_seconds";
+
+/// Histogram of the gas used by mined transactions
+pub const GAS_USED: &str = "ethers_transaction_gas_used";
+
+/// Histogram of the fees paid for mined transactions in ether
+pub const FEES: &str = "ethers_transaction_fees_ether";
+
+/// Gauge of how far the nonce of the last sent transaction was ahead of the pending transaction
+/// count of its sender, non-zero values mean a gap that blocks the transaction
+pub const NONCE_GAP: &str = "ethers_nonce_gap";
+
+/// Describes the metrics to the installed recorder.
+///
+/// This is optional, exporters use the descriptions for e.g. the `# HELP` lines of Prometheus.
+/// The requests themselves are recorded by the [`Provider`](ethers_providers::Provider), see
+/// [`ethers_providers::metrics`].
+pub fn describe() {
+    ::metrics::describe_counter!(TRANSACTIONS_SENT, "Number of transactions sent");
+    ::metrics::describe_counter!(TRANSACTION_SEND_ERRORS, "Number of transactions that failed");
+    ::metrics::describe_counter!(TRANSACTIONS_CONFIRMED, "Number of transactions mined");
+    ::metrics::describe_counter!(
+        TRANSACTIONS_UNCONFIRMED,
+        "Number of transactions not mined before the confirmation timeout"
+    );
+    ::metrics::describe_histogram!(
+        CONFIRMATION_DURATION,
+        ::metrics::Unit::Seconds,
+        "Time until sent transactions were mined"
+    );
+    ::metrics::describe_histogram!(GAS_USED, "Gas used by mined transactions");
+    ::metrics::describe_histogram!(FEES, "Fees paid for mined transactions in ether");
+    ::metrics::describe_gauge!(NONCE_GAP, "Nonce gap of the last sent transaction");
+    ethers_providers::metrics::describe();
+}
+
+#[derive(Debug)]
+/// Middleware that records metrics of the transactions it sends with the
+/// [`metrics`](::metrics) facade, exported by the recorder installed by the application, e.g.
+/// `metrics-exporter-prometheus`.
+///
+/// Every sent transaction is counted and watched in the background until it's mined, to record
+/// its status, gas used, fees and the time it took to be mined. Before a transaction is sent,
+/// its nonce is compared to the pending transaction count of the sender to record nonce gaps,
+/// the transaction is sent even if the count can't be fetched. Use
+/// [`MetricsMiddleware::nonce_gaps`] to skip the extra request.
+///
+/// The metrics are not labeled by sender, so their number stays bounded however many accounts
+/// send transactions.
+/// Requests and their errors are recorded per method by the
+/// [`Provider`](ethers_providers::Provider) itself, see [`ethers_providers::metrics`].
+///
+/// On wasm, transactions are not watched, so only sent transactions and nonce gaps are recorded.
+///
+/// # Example
+///
+/// ```ignore
+/// use ethers_middleware::metrics::{self, MetricsMiddleware};
+///
+/// metrics_exporter_prometheus::PrometheusBuilder::new().install()?;
+/// metrics::describe();
+/// let client = MetricsMiddleware::new(client);
+/// ```
+pub struct MetricsMiddleware<M> {
+    inner: Arc<M>,
+    confirmation_timeout: Duration,
+    nonce_gaps: bool,
+}
+
+impl<M> MetricsMiddleware<M>
+where
+    M: Middleware + 'static,
+{
+    /// Creates a middleware that watches transactions for up to an hour
+    pub fn new(inner: M) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            confirmation_timeout: Duration::from_secs(3600),
+            nonce_gaps: true,
+        }
+    }
+
+    /// Sets whether nonce gaps are recorded, which fetches the pending transaction count of the
+    /// sender before each transaction is sent
+    #[must_use]
+    pub fn nonce_gaps(mut self, nonce_gaps: bool) -> Self {
+        self.nonce_gaps = nonce_gaps;
+        self
+    }
+
+    /// Sets how long sent transactions are watched before they are recorded as unconfirmed
+    #[must_use]
+    pub fn confirmation_timeout(mut self, timeout: Duration) -> Self {
+        self.confirmation_timeout = timeout;
+        self
+    }
+
+    /// Watches the transaction in the background until it's mined or the timeout elapsed
+    #[cfg(not(target_arch = "wasm32"))]
+    fn watch(&self, tx_hash: ethers_core::types::TxHash) {
+        use ethers_providers::{interval, StreamExt};
+        use instant::Instant;
+
+        let inner = self.inner.clone();
+        let timeout = self.confirmation_timeout;
+        tokio::spawn(async move {
+            let sent = Instant::now();
+            let mut ticks = interval(inner.provider().get_interval());
+            while sent.elapsed() < timeout {
+                ticks.next().await;
+                if let Ok(Some(receipt)) = inner.get_transaction_receipt(tx_hash).await {
+                    if receipt.block_number.is_some() {
+                        record_receipt(&receipt, sent.elapsed());
+                        return
+                    }
+                }
+            }
+            ::metrics::increment_counter!(TRANSACTIONS_UNCONFIRMED);
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn watch(&self, _: ethers_core::types::TxHash) {}
+}
+
+/// Records the status, gas used and fees of a mined transaction
+#[cfg(not(target_arch = "wasm32"))]
+fn record_receipt(receipt: &ethers_core::types::TransactionReceipt, elapsed: Duration) {
+    use ethers_core::utils::format_units;
+
+    let status = if receipt.status == Some(1u64.into()) { "success" } else { "reverted" };
+    ::metrics::increment_counter!(TRANSACTIONS_CONFIRMED, "status" => status);
+    ::metrics::histogram!(CONFIRMATION_DURATION, elapsed.as_secs_f64());
+    if let Some(gas_used) = receipt.gas_used {
+        ::metrics::histogram!(GAS_USED, gas_used.as_u64() as f64);
+        if let Some(price) = receipt.effective_gas_price {
+            let fee = format_units(gas_used * price, "ether").unwrap_or_default();
+            ::metrics::histogram!(FEES, fee.parse::<f64>().unwrap_or_default());
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the metrics middleware interacts with the blockchain
+pub enum MetricsError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> FromErr<M::Error> for MetricsError<M> {
+    fn from(src: M::Error) -> MetricsError<M> {
+        MetricsError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for MetricsMiddleware<M>
+where
+    M: Middleware + 'static,
+{
+    type Error = MetricsError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Sends the transaction, recording its nonce gap, and watches it until it's mined
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        self.inner.fill_transaction(&mut tx, block).await.map_err(MetricsError::MiddlewareError)?;
+        if let (true, Some(from), Some(nonce)) = (self.nonce_gaps, tx.from(), tx.nonce()) {
+            // the probe is best-effort, the transaction is sent anyway
+            match self.inner.get_transaction_count(*from, Some(BlockNumber::Pending.into())).await {
+                Ok(pending) => {
+                    let gap = nonce.saturating_sub(pending).low_u64();
+                    ::metrics::gauge!(NONCE_GAP, gap as f64);
+                }
+                Err(err) => tracing::debug!(%err, "failed to fetch the pending transaction count"),
+            }
+        }
+
+        match self.inner.send_transaction(tx, block).await {
+            Ok(pending) => {
+                ::metrics::increment_counter!(TRANSACTIONS_SENT);
+                self.watch(pending.tx_hash());
+                Ok(pending)
+            }
+            Err(err) => {
+                ::metrics::increment_counter!(TRANSACTION_SEND_ERRORS);
+                Err(MetricsError::MiddlewareError(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ::metrics::{
+        Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Recorder, SharedString, Unit,
+    };
+    use ethers_core::types::{Address, TransactionReceipt, TransactionRequest, H256, U256};
+    use ethers_providers::{JsonRpcError, Provider};
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        },
+    };
+
+    #[derive(Default)]
+    struct Samples(Mutex<Vec<f64>>);
+
+    impl HistogramFn for Samples {
+        fn record(&self, value: f64) {
+            self.0.lock().unwrap().push(value);
+        }
+    }
+
+    /// Keeps the counters and histograms by their name and labels
+    #[derive(Default, Clone)]
+    struct TestRecorder {
+        counters: Arc<Mutex<HashMap<String, Arc<AtomicU64>>>>,
+        histograms: Arc<Mutex<HashMap<String, Arc<Samples>>>>,
+        gauges: Arc<Mutex<HashMap<String, Arc<AtomicU64>>>>,
+    }
+
+    impl TestRecorder {
+        fn key(key: &Key) -> String {
+            let labels = key.labels().map(|label| format!("{}={}", label.key(), label.value()));
+            format!("{}{{{}}}", key.name(), labels.collect::<Vec<_>>().join(","))
+        }
+
+        fn counter(&self, key: &str) -> u64 {
+            let counters = self.counters.lock().unwrap();
+            counters.get(key).map_or(0, |counter| counter.load(Ordering::SeqCst))
+        }
+
+        fn gauge(&self, key: &str) -> f64 {
+            let gauges = self.gauges.lock().unwrap();
+            gauges.get(key).map_or(0.0, |gauge| f64::from_bits(gauge.load(Ordering::SeqCst)))
+        }
+
+        fn samples(&self, key: &str) -> Vec<f64> {
+            let histograms = self.histograms.lock().unwrap();
+            histograms.get(key).map_or(vec![], |samples| samples.0.lock().unwrap().clone())
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key) -> Counter {
+            let mut counters = self.counters.lock().unwrap();
+            Counter::from_arc(counters.entry(Self::key(key)).or_default().clone())
+        }
+
+        fn register_gauge(&self, key: &Key) -> Gauge {
+            let mut gauges = self.gauges.lock().unwrap();
+            Gauge::from_arc(gauges.entry(Self::key(key)).or_default().clone())
+        }
+
+        fn register_histogram(&self, key: &Key) -> Histogram {
+            let mut histograms = self.histograms.lock().unwrap();
+            Histogram::from_arc(histograms.entry(Self::key(key)).or_default().clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn records_transactions() {
+        let recorder = TestRecorder::default();
+        ::metrics::set_boxed_recorder(Box::new(recorder.clone())).unwrap();
+        describe();
+
+        let (provider, mock) = Provider::mocked();
+        let client = MetricsMiddleware::new(provider.interval(Duration::from_millis(10)));
+
+        let from = Address::repeat_byte(1);
+        let hash = H256::repeat_byte(2);
+        let receipt = TransactionReceipt {
+            transaction_hash: hash,
+            block_number: Some(1u64.into()),
+            status: Some(1u64.into()),
+            gas_used: Some(21_000.into()),
+            effective_gas_price: Some(U256::exp10(9)),
+            ..Default::default()
+        };
+        mock.push(receipt.clone()).unwrap();
+        mock.push(hash).unwrap();
+        mock.push(U256::from(2)).unwrap();
+
+        let tx = TransactionRequest::pay(Address::zero(), 1)
+            .from(from)
+            .nonce(3)
+            .gas(21_000)
+            .gas_price(1);
+        client.send_transaction(tx.clone(), None).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(recorder.counter(&format!("{TRANSACTIONS_SENT}{{}}")), 1);
+        assert_eq!(recorder.gauge(&format!("{NONCE_GAP}{{}}")), 1.0);
+        assert_eq!(recorder.counter(&format!("{TRANSACTIONS_CONFIRMED}{{status=success}}")), 1);
+        assert_eq!(recorder.samples(&format!("{GAS_USED}{{}}")), [21_000.0]);
+        assert_eq!(recorder.samples(&format!("{FEES}{{}}")), [0.000021]);
+        assert_eq!(recorder.samples(&format!("{CONFIRMATION_DURATION}{{}}")).len(), 1);
+
+        // failing to fetch the pending transaction count doesn't fail the transaction
+        mock.push(receipt).unwrap();
+        mock.push(hash).unwrap();
+        mock.push_error(JsonRpcError { code: -32000, message: "down".to_string(), data: None });
+        client.send_transaction(tx, None).await.unwrap();
+        assert_eq!(recorder.counter(&format!("{TRANSACTIONS_SENT}{{}}")), 2);
+        assert_eq!(recorder.gauge(&format!("{NONCE_GAP}{{}}")), 1.0);
+    }
+}
@@ -0,0 +1,301 @@
+use async_trait::async_trait;
+use ethers_core::{
+    types::{
+        transaction::{
+            eip2718::{TypedTransaction, TypedTransactionError},
+            eip712::{Eip712, TypedData},
+        },
+        Address, Bytes, Signature, SignatureError, TxHash, H256,
+    },
+    utils,
+};
+use ethers_providers::{Middleware, ProviderError};
+use ethers_signers::Signer;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// A [`Signer`] for an account that is managed by the node, e.g. an account unlocked in geth's
+/// keystore or an account of a [Clef](https://geth.ethereum.org/docs/tools/clef/introduction)
+/// instance the node forwards signing requests to with `--signer`.
+///
+/// Transactions are signed with `eth_signTransaction`, or `personal_signTransaction` if a
+/// passphrase is set, and messages with `eth_sign` or `personal_sign`. Combined with the
+/// [`SignerMiddleware`](crate::SignerMiddleware) this lets dev environments and custodial nodes
+/// sign transactions without a local key, which are then broadcast with
+/// `eth_sendRawTransaction`.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{Middleware, Provider, Http};
+/// use ethers_middleware::{NodeSigner, SignerMiddleware};
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use std::{convert::TryFrom, sync::Arc};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Arc::new(Provider::<Http>::try_from("http://localhost:8545")?);
+/// let account = provider.get_accounts().await?[0];
+///
+/// let signer = NodeSigner::new(provider.clone(), account);
+/// let client = SignerMiddleware::new_with_provider_chain(provider, signer).await?;
+///
+/// let tx = TransactionRequest::pay("vitalik.eth", 100);
+/// let receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NodeSigner<M> {
+    provider: M,
+    address: Address,
+    chain_id: u64,
+    passphrase: Option<String>,
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the node fails to sign a transaction or message
+pub enum NodeSignerError {
+    #[error(transparent)]
+    /// Thrown when the request to the node fails
+    ProviderError(#[from] ProviderError),
+
+    #[error(transparent)]
+    /// Thrown when the transaction returned by the node can't be decoded
+    DecodingError(#[from] TypedTransactionError),
+
+    #[error(transparent)]
+    /// Thrown when the signature returned by the node is invalid
+    SignatureError(#[from] SignatureError),
+
+    /// Thrown when the node signed the message or transaction with a different account or
+    /// changed the transaction before signing it
+    #[error("the node's signature does not match the signer's account")]
+    WrongSignature,
+
+    /// Thrown when typed data that is not a [`TypedData`] is signed, the node needs the full
+    /// typed data rather than its hash
+    #[error("only `TypedData` can be signed by the node, use `NodeSigner::sign_typed_data_v4`")]
+    UnsupportedTypedData,
+}
+
+/// The response of `eth_signTransaction`
+#[derive(Debug, Serialize, Deserialize)]
+struct SignTransactionResponse {
+    raw: Bytes,
+}
+
+impl<M: Middleware> NodeSigner<M> {
+    /// Creates a signer for the `address` account of the node.
+    ///
+    /// The chain id defaults to 1, use [`with_chain_id`](Signer::with_chain_id) or
+    /// [`SignerMiddleware::new_with_provider_chain`](crate::SignerMiddleware::new_with_provider_chain)
+    /// to set it.
+    pub fn new(provider: M, address: Address) -> Self {
+        Self { provider, address, chain_id: 1, passphrase: None }
+    }
+
+    /// Sets the passphrase the account is decrypted with for every request, this switches to the
+    /// `personal_` methods so the account doesn't have to be unlocked.
+    #[must_use]
+    pub fn with_passphrase(mut self, passphrase: impl Into<String>) -> Self {
+        self.passphrase = Some(passphrase.into());
+        self
+    }
+
+    /// Returns a reference to the provider the signing requests are sent to
+    pub fn provider(&self) -> &M {
+        &self.provider
+    }
+
+    /// Sends the transaction to be signed and broadcast by the node.
+    ///
+    /// Unlike [`sign_transaction`](Signer::sign_transaction) this uses `personal_sendTransaction`
+    /// if a passphrase is set, which also works with nodes that don't expose signed transactions.
+    pub async fn send_transaction(&self, tx: &TypedTransaction) -> Result<TxHash, NodeSignerError> {
+        let tx = utils::serialize(&self.with_from(tx));
+        let provider = self.provider.provider();
+        let hash = match self.passphrase {
+            Some(ref passphrase) => {
+                let params = [tx, utils::serialize(passphrase)];
+                provider.request("personal_sendTransaction", params).await?
+            }
+            None => provider.request("eth_sendTransaction", [tx]).await?,
+        };
+        Ok(hash)
+    }
+
+    /// Signs the typed data with `eth_signTypedData_v4`
+    pub async fn sign_typed_data_v4(
+        &self,
+        payload: &TypedData,
+    ) -> Result<Signature, NodeSignerError> {
+        let params = [utils::serialize(&self.address), utils::serialize(payload)];
+        let sig: Bytes = self.provider.provider().request("eth_signTypedData_v4", params).await?;
+        let hash = payload.encode_eip712().map_err(|_| NodeSignerError::UnsupportedTypedData)?;
+        self.verify(&sig, hash.into())
+    }
+
+    /// Decodes the signature returned by the node and checks that it was made by the signer's
+    /// account
+    fn verify(&self, sig: &[u8], hash: H256) -> Result<Signature, NodeSignerError> {
+        let sig = Signature::try_from(sig)?;
+        sig.verify(hash, self.address).map_err(|_| NodeSignerError::WrongSignature)?;
+        Ok(sig)
+    }
+
+    fn with_from(&self, tx: &TypedTransaction) -> TypedTransaction {
+        let mut tx = tx.clone();
+        tx.set_from(self.address);
+        tx
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> Signer for NodeSigner<M> {
+    type Error = NodeSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        let message = message.as_ref();
+        let data = utils::serialize(&Bytes::from(message.to_vec()));
+        let address = utils::serialize(&self.address);
+        let provider = self.provider.provider();
+        let sig: Bytes = match self.passphrase {
+            Some(ref passphrase) => {
+                let params = [data, address, utils::serialize(passphrase)];
+                provider.request("personal_sign", params).await?
+            }
+            None => provider.request("eth_sign", [address, data]).await?,
+        };
+        self.verify(&sig, utils::hash_message(message))
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = self.with_from(tx);
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        let provider = self.provider.provider();
+        let params = utils::serialize(&tx);
+        let response: SignTransactionResponse = match self.passphrase {
+            Some(ref passphrase) => {
+                let params = [params, utils::serialize(passphrase)];
+                provider.request("personal_signTransaction", params).await?
+            }
+            None => provider.request("eth_signTransaction", [params]).await?,
+        };
+
+        // the signature is only returned as part of the signed transaction, it has to match the
+        // transaction that was requested since the caller encodes it with the signature
+        let (_, sig) = TypedTransaction::decode_signed(&utils::rlp::Rlp::new(&response.raw))?;
+        sig.verify(tx.sighash(), self.address).map_err(|_| NodeSignerError::WrongSignature)?;
+        Ok(sig)
+    }
+
+    /// Only [`TypedData`] can be signed, see [`NodeSigner::sign_typed_data_v4`]
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        _payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        Err(NodeSignerError::UnsupportedTypedData)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(all(test, not(feature = "celo"), not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::SignerMiddleware;
+    use ethers_core::types::TransactionRequest;
+    use ethers_providers::Provider;
+    use ethers_signers::LocalWallet;
+    use serde_json::json;
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(1337u64)
+    }
+
+    fn tx() -> TypedTransaction {
+        TransactionRequest::pay(Address::repeat_byte(1), 100)
+            .nonce(3)
+            .gas(21_000)
+            .gas_price(1_000_000_000)
+            .chain_id(1337)
+            .into()
+    }
+
+    #[tokio::test]
+    async fn signs_transaction_on_node() {
+        let (provider, mock) = Provider::mocked();
+        let wallet = wallet();
+        let signer = NodeSigner::new(provider.clone(), wallet.address()).with_chain_id(1337u64);
+        let client = SignerMiddleware::new(provider, signer);
+
+        let mut tx = tx();
+        tx.set_from(wallet.address());
+        let sig = wallet.sign_transaction(&tx).await.unwrap();
+        let raw = tx.rlp_signed(&sig);
+        mock.push(tx.hash(&sig)).unwrap();
+        mock.push(json!({ "raw": raw, "tx": {} })).unwrap();
+
+        let pending = client.send_transaction(tx.clone(), None).await.unwrap();
+        assert_eq!(*pending, tx.hash(&sig));
+        mock.assert_request("eth_signTransaction", [&tx]).unwrap();
+        mock.assert_request("eth_sendRawTransaction", [raw]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_foreign_signature() {
+        let (provider, mock) = Provider::mocked();
+        let other = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(1337u64);
+        let signer = NodeSigner::new(provider, wallet().address());
+
+        let mut tx = tx();
+        tx.set_from(other.address());
+        let raw = tx.rlp_signed(&other.sign_transaction(&tx).await.unwrap());
+        mock.push(json!({ "raw": raw, "tx": {} })).unwrap();
+
+        let err = signer.sign_transaction(&tx).await.unwrap_err();
+        assert!(matches!(err, NodeSignerError::WrongSignature));
+    }
+
+    #[tokio::test]
+    async fn signs_message_with_passphrase() {
+        let (provider, mock) = Provider::mocked();
+        let wallet = wallet();
+        let signer = NodeSigner::new(provider, wallet.address()).with_passphrase("hunter2");
+
+        let sig = wallet.sign_message("hello").await.unwrap();
+        mock.push::<Bytes, Bytes>(sig.to_vec().into()).unwrap();
+
+        assert_eq!(signer.sign_message("hello").await.unwrap(), sig);
+        mock.assert_request(
+            "personal_sign",
+            [
+                utils::serialize(&Bytes::from(b"hello".to_vec())),
+                utils::serialize(&wallet.address()),
+                utils::serialize(&"hunter2"),
+            ],
+        )
+        .unwrap();
+    }
+}
@@ -1,16 +1,26 @@
+mod store;
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::FileNonceStore;
+#[cfg(all(feature = "redis", not(target_arch = "wasm32")))]
+pub use store::RedisNonceStore;
+pub use store::{MemoryNonceStore, NonceStore};
+
 use async_trait::async_trait;
 use ethers_core::types::{transaction::eip2718::TypedTransaction, *};
 use ethers_providers::{FromErr, Middleware, PendingTransaction};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use thiserror::Error;
 
 #[derive(Debug)]
 /// Middleware used for calculating nonces locally, useful for signing multiple
 /// consecutive transactions without waiting for them to hit the mempool
-pub struct NonceManagerMiddleware<M> {
+///
+/// The nonces are kept in memory by default, a persistent [`NonceStore`] keeps nonce allocation
+/// safe across restarts and processes, see [`NonceManagerMiddleware::with_store`]. If sending a
+/// transaction fails and the node's nonce differs from the stored one, e.g. because another
+/// sender used the nonce, the store is resynced with the node and the transaction is sent again.
+pub struct NonceManagerMiddleware<M, S = MemoryNonceStore> {
     inner: M,
-    initialized: AtomicBool,
-    nonce: AtomicU64,
+    store: S,
     address: Address,
 }
 
@@ -21,13 +31,29 @@ where
     /// Instantiates the nonce manager with a 0 nonce. The `address` should be the
     /// address which you'll be sending transactions from
     pub fn new(inner: M, address: Address) -> Self {
-        Self { initialized: false.into(), nonce: 0.into(), inner, address }
+        Self::with_store(inner, address, MemoryNonceStore::new())
     }
 
     /// Returns the next nonce to be used
     pub fn next(&self) -> U256 {
-        let nonce = self.nonce.fetch_add(1, Ordering::SeqCst);
-        nonce.into()
+        self.store.next(self.address)
+    }
+}
+
+impl<M, S> NonceManagerMiddleware<M, S>
+where
+    M: Middleware,
+    S: NonceStore,
+{
+    /// Instantiates the nonce manager with the nonces of the `store`, the nonce of the `address`
+    /// is fetched from the node if the store has none
+    pub fn with_store(inner: M, address: Address, store: S) -> Self {
+        Self { inner, store, address }
+    }
+
+    /// Returns the store of the nonces
+    pub fn store(&self) -> &S {
+        &self.store
     }
 
     pub async fn initialize_nonce(
@@ -35,46 +61,51 @@ where
         block: Option<BlockId>,
     ) -> Result<U256, NonceManagerError<M>> {
         // initialize the nonce the first time the manager is called
-        if !self.initialized.load(Ordering::SeqCst) {
-            let nonce = self
-                .inner
-                .get_transaction_count(self.address, block)
-                .await
-                .map_err(FromErr::from)?;
-            self.nonce.store(nonce.as_u64(), Ordering::SeqCst);
-            self.initialized.store(true, Ordering::SeqCst);
-            Ok(nonce)
-        } else {
+        if let Some(nonce) = self.store.get(self.address).await.map_err(store_error)? {
             // return current nonce
-            Ok(self.nonce.load(Ordering::SeqCst).into())
+            return Ok(nonce)
         }
+        let nonce =
+            self.inner.get_transaction_count(self.address, block).await.map_err(FromErr::from)?;
+        self.store.initialize(self.address, nonce).await.map_err(store_error)?;
+        Ok(self.store.get(self.address).await.map_err(store_error)?.unwrap_or(nonce))
     }
 
     async fn get_transaction_count_with_manager(
         &self,
         block: Option<BlockId>,
     ) -> Result<U256, NonceManagerError<M>> {
-        // initialize the nonce the first time the manager is called
-        if !self.initialized.load(Ordering::SeqCst) {
-            let nonce = self
-                .inner
-                .get_transaction_count(self.address, block)
-                .await
-                .map_err(FromErr::from)?;
-            self.nonce.store(nonce.as_u64(), Ordering::SeqCst);
-            self.initialized.store(true, Ordering::SeqCst);
+        if let Some(nonce) = self.store.allocate(self.address).await.map_err(store_error)? {
+            return Ok(nonce)
         }
 
-        Ok(self.next())
+        // initialize the nonce the first time the manager is called, unless another manager
+        // sharing the store did so in the meantime
+        self.initialize_nonce(block).await?;
+        self.store
+            .allocate(self.address)
+            .await
+            .map_err(store_error)?
+            .ok_or_else(|| NonceManagerError::StoreError("the store did not keep the nonce".into()))
     }
 }
 
+fn store_error<M: Middleware>(
+    err: impl std::error::Error + Send + Sync + 'static,
+) -> NonceManagerError<M> {
+    NonceManagerError::StoreError(Box::new(err))
+}
+
 #[derive(Error, Debug)]
 /// Thrown when an error happens at the Nonce Manager
 pub enum NonceManagerError<M: Middleware> {
     /// Thrown when the internal middleware errors
     #[error("{0}")]
     MiddlewareError(M::Error),
+
+    /// Thrown when the nonce store fails
+    #[error("nonce store error: {0}")]
+    StoreError(Box<dyn std::error::Error + Send + Sync>),
 }
 
 impl<M: Middleware> FromErr<M::Error> for NonceManagerError<M> {
@@ -85,9 +116,10 @@ impl<M: Middleware> FromErr<M::Error> for NonceManagerError<M> {
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-impl<M> Middleware for NonceManagerMiddleware<M>
+impl<M, S> Middleware for NonceManagerMiddleware<M, S>
 where
     M: Middleware,
+    S: NonceStore,
 {
     type Error = NonceManagerError<M>;
     type Provider = M::Provider;
@@ -127,10 +159,10 @@ where
             Ok(tx_hash) => Ok(tx_hash),
             Err(err) => {
                 let nonce = self.get_transaction_count(self.address, block).await?;
-                if nonce != self.nonce.load(Ordering::SeqCst).into() {
+                if Some(nonce) != self.store.get(self.address).await.map_err(store_error)? {
                     // try re-submitting the transaction with the correct nonce if there
                     // was a nonce mismatch
-                    self.nonce.store(nonce.as_u64(), Ordering::SeqCst);
+                    self.store.set(self.address, nonce + 1).await.map_err(store_error)?;
                     tx.set_nonce(nonce);
                     self.inner.send_transaction(tx, block).await.map_err(FromErr::from)
                 } else {
@@ -0,0 +1,332 @@
+use async_trait::async_trait;
+use ethers_core::types::{Address, U256};
+use std::{collections::HashMap, error::Error, fmt::Debug, sync::Mutex};
+
+/// Storage for the next nonce of each address, see
+/// [`NonceManagerMiddleware::with_store`](super::NonceManagerMiddleware::with_store).
+///
+/// Stores that persist the nonces or share them between processes keep nonce allocation safe
+/// across restarts and multi-process deployments, as long as [`NonceStore::allocate`] is atomic.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait NonceStore: Send + Sync + Debug {
+    type Error: Error + Send + Sync + 'static;
+
+    /// Returns the next nonce of the address, or `None` if none is stored
+    async fn get(&self, address: Address) -> Result<Option<U256>, Self::Error>;
+
+    /// Stores the next nonce of the address
+    async fn set(&self, address: Address, nonce: U256) -> Result<(), Self::Error>;
+
+    /// Stores the next nonce of the address unless one is already stored
+    async fn initialize(&self, address: Address, nonce: U256) -> Result<(), Self::Error>;
+
+    /// Returns the next nonce of the address and stores the nonce after it, or returns `None` if
+    /// none is stored
+    async fn allocate(&self, address: Address) -> Result<Option<U256>, Self::Error>;
+}
+
+/// Keeps the nonces in memory, they are lost when the process exits
+#[derive(Debug, Default)]
+pub struct MemoryNonceStore {
+    nonces: Mutex<HashMap<Address, U256>>,
+}
+
+impl MemoryNonceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next nonce of the address and stores the nonce after it, starting at `0`
+    pub(crate) fn next(&self, address: Address) -> U256 {
+        let mut nonces = self.nonces.lock().unwrap();
+        let nonce = nonces.entry(address).or_default();
+        let next = *nonce;
+        *nonce = next + 1;
+        next
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NonceStore for MemoryNonceStore {
+    type Error = std::convert::Infallible;
+
+    async fn get(&self, address: Address) -> Result<Option<U256>, Self::Error> {
+        Ok(self.nonces.lock().unwrap().get(&address).copied())
+    }
+
+    async fn set(&self, address: Address, nonce: U256) -> Result<(), Self::Error> {
+        self.nonces.lock().unwrap().insert(address, nonce);
+        Ok(())
+    }
+
+    async fn initialize(&self, address: Address, nonce: U256) -> Result<(), Self::Error> {
+        self.nonces.lock().unwrap().entry(address).or_insert(nonce);
+        Ok(())
+    }
+
+    async fn allocate(&self, address: Address) -> Result<Option<U256>, Self::Error> {
+        let mut nonces = self.nonces.lock().unwrap();
+        Ok(nonces.get_mut(&address).map(|nonce| {
+            let next = *nonce;
+            *nonce = next + 1;
+            next
+        }))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileNonceStore;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod file {
+    use super::*;
+    use fs2::FileExt;
+    use std::{
+        fs::{File, OpenOptions},
+        io::{self, Read, Seek, SeekFrom, Write},
+        path::{Path, PathBuf},
+    };
+
+    /// Keeps the nonces in a JSON file, shared by all processes that use the same file.
+    ///
+    /// Every access holds an exclusive lock of the file, so concurrent processes never allocate
+    /// the same nonce. Waiting for the lock and the file I/O block, so they run on the blocking
+    /// threads of the Tokio runtime, which the store must be used within.
+    #[derive(Debug, Clone)]
+    pub struct FileNonceStore {
+        path: PathBuf,
+    }
+
+    impl FileNonceStore {
+        /// Stores the nonces in the file at `path`, which is created on first use
+        pub fn new(path: impl Into<PathBuf>) -> Self {
+            Self { path: path.into() }
+        }
+
+        /// Reads the nonces with the file locked, then writes them if `f` changed them
+        async fn update<T: Send + 'static>(
+            &self,
+            f: impl FnOnce(&mut HashMap<Address, U256>) -> T + Send + 'static,
+        ) -> Result<T, io::Error> {
+            let path = self.path.clone();
+            tokio::task::spawn_blocking(move || Self::update_blocking(&path, f))
+                .await
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+        }
+
+        fn update_blocking<T>(
+            path: &Path,
+            f: impl FnOnce(&mut HashMap<Address, U256>) -> T,
+        ) -> Result<T, io::Error> {
+            let mut file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)?;
+            FileExt::lock_exclusive(&file)?;
+            let res = Self::update_locked(&mut file, f);
+            FileExt::unlock(&file)?;
+            res
+        }
+
+        fn update_locked<T>(
+            file: &mut File,
+            f: impl FnOnce(&mut HashMap<Address, U256>) -> T,
+        ) -> Result<T, io::Error> {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+            let mut nonces: HashMap<Address, U256> = if contents.trim().is_empty() {
+                HashMap::new()
+            } else {
+                serde_json::from_str(&contents)?
+            };
+            let before = nonces.clone();
+            let res = f(&mut nonces);
+            if nonces != before {
+                file.set_len(0)?;
+                file.seek(SeekFrom::Start(0))?;
+                file.write_all(serde_json::to_string(&nonces)?.as_bytes())?;
+                file.sync_data()?;
+            }
+            Ok(res)
+        }
+    }
+
+    #[async_trait]
+    impl NonceStore for FileNonceStore {
+        type Error = io::Error;
+
+        async fn get(&self, address: Address) -> Result<Option<U256>, Self::Error> {
+            self.update(move |nonces| nonces.get(&address).copied()).await
+        }
+
+        async fn set(&self, address: Address, nonce: U256) -> Result<(), Self::Error> {
+            self.update(move |nonces| {
+                nonces.insert(address, nonce);
+            })
+            .await
+        }
+
+        async fn initialize(&self, address: Address, nonce: U256) -> Result<(), Self::Error> {
+            self.update(move |nonces| {
+                nonces.entry(address).or_insert(nonce);
+            })
+            .await
+        }
+
+        async fn allocate(&self, address: Address) -> Result<Option<U256>, Self::Error> {
+            self.update(move |nonces| {
+                nonces.get_mut(&address).map(|nonce| {
+                    let next = *nonce;
+                    *nonce = next + 1;
+                    next
+                })
+            })
+            .await
+        }
+    }
+}
+
+#[cfg(all(feature = "redis", not(target_arch = "wasm32")))]
+pub use self::redis::RedisNonceStore;
+
+#[cfg(all(feature = "redis", not(target_arch = "wasm32")))]
+mod redis {
+    use super::*;
+    use crate::redis::{RedisClient, RedisError};
+
+    /// Returns the nonce stored at the key and increments it, or returns nil if none is stored.
+    /// Redis runs scripts atomically.
+    const ALLOCATE_SCRIPT: &str = "local nonce = redis.call('GET', KEYS[1])
+if nonce then redis.call('INCR', KEYS[1]) end
+return nonce";
+
+    /// The default prefix of the keys of the nonces
+    const DEFAULT_PREFIX: &str = "ethers:nonce:";
+
+    /// Keeps the nonces in [Redis](https://redis.io), shared by all processes that use the same
+    /// server and key prefix.
+    ///
+    /// The nonce of an address is stored as a decimal string at the key `<prefix><address>`,
+    /// nonces are allocated with a script that Redis runs atomically, so concurrent processes
+    /// never allocate the same nonce.
+    #[derive(Debug)]
+    pub struct RedisNonceStore {
+        client: RedisClient,
+        prefix: String,
+    }
+
+    impl RedisNonceStore {
+        /// Stores the nonces on the server at the
+        /// `redis://[[username]:password@]host[:port][/db]` url, which is connected to on first
+        /// use, under the `ethers:nonce:` prefix
+        pub fn new(url: &str) -> Result<Self, RedisError> {
+            Ok(Self { client: RedisClient::open(url)?, prefix: DEFAULT_PREFIX.to_string() })
+        }
+
+        /// Sets the prefix of the keys, e.g. to keep the nonces of several chains apart
+        #[must_use]
+        pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+            self.prefix = prefix.into();
+            self
+        }
+
+        fn key(&self, address: Address) -> String {
+            format!("{}{address:?}", self.prefix)
+        }
+    }
+
+    fn parse_nonce(value: Option<Vec<u8>>) -> Result<Option<U256>, RedisError> {
+        value
+            .map(|value| {
+                std::str::from_utf8(&value)
+                    .ok()
+                    .and_then(|value| U256::from_dec_str(value).ok())
+                    .ok_or_else(|| {
+                        RedisError::UnexpectedReply(String::from_utf8_lossy(&value).into_owned())
+                    })
+            })
+            .transpose()
+    }
+
+    #[async_trait]
+    impl NonceStore for RedisNonceStore {
+        type Error = RedisError;
+
+        async fn get(&self, address: Address) -> Result<Option<U256>, Self::Error> {
+            let reply = self.client.query(&[b"GET", self.key(address).as_bytes()]).await?;
+            parse_nonce(reply.into_bulk()?)
+        }
+
+        async fn set(&self, address: Address, nonce: U256) -> Result<(), Self::Error> {
+            let (key, nonce) = (self.key(address), nonce.to_string());
+            self.client.query(&[b"SET", key.as_bytes(), nonce.as_bytes()]).await?;
+            Ok(())
+        }
+
+        async fn initialize(&self, address: Address, nonce: U256) -> Result<(), Self::Error> {
+            let (key, nonce) = (self.key(address), nonce.to_string());
+            self.client.query(&[b"SET", key.as_bytes(), nonce.as_bytes(), b"NX"]).await?;
+            Ok(())
+        }
+
+        async fn allocate(&self, address: Address) -> Result<Option<U256>, Self::Error> {
+            let key = self.key(address);
+            let reply = self
+                .client
+                .query(&[b"EVAL", ALLOCATE_SCRIPT.as_bytes(), b"1", key.as_bytes()])
+                .await?;
+            parse_nonce(reply.into_bulk()?)
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    async fn allocates_nonces(store: impl NonceStore) {
+        let address = Address::repeat_byte(1);
+        assert_eq!(store.allocate(address).await.unwrap(), None);
+
+        store.initialize(address, 5.into()).await.unwrap();
+        store.initialize(address, 7.into()).await.unwrap();
+        assert_eq!(store.allocate(address).await.unwrap(), Some(5.into()));
+        assert_eq!(store.allocate(address).await.unwrap(), Some(6.into()));
+        assert_eq!(store.get(address).await.unwrap(), Some(7.into()));
+
+        store.set(address, 3.into()).await.unwrap();
+        assert_eq!(store.allocate(address).await.unwrap(), Some(3.into()));
+        assert_eq!(store.get(Address::zero()).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn memory_store() {
+        allocates_nonces(MemoryNonceStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn file_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nonces.json");
+        allocates_nonces(FileNonceStore::new(&path)).await;
+
+        // the nonces survive restarts
+        let store = FileNonceStore::new(&path);
+        assert_eq!(store.get(Address::repeat_byte(1)).await.unwrap(), Some(4.into()));
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "redis")]
+    #[ignore = "needs a Redis server at redis://127.0.0.1"]
+    async fn redis_store() {
+        let store = RedisNonceStore::new(crate::redis::TEST_URL)
+            .unwrap()
+            .prefix(crate::redis::test_prefix());
+        allocates_nonces(store).await;
+    }
+}
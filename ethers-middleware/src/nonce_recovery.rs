@@ -0,0 +1,304 @@
+use crate::gas_escalator::{fill_with_nonce, Fees, MIN_REPLACEMENT_BUMP};
+use async_trait::async_trait;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockId, BlockNumber};
+use ethers_providers::{FromErr, Middleware, PendingTransaction};
+use std::fmt::Display;
+use thiserror::Error;
+
+/// The default number of times a transaction is resent after a nonce error
+pub const DEFAULT_MAX_RETRIES: usize = 3;
+
+/// A nonce related error returned by a node when sending a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceErrorKind {
+    /// The nonce was already used by a mined transaction
+    NonceTooLow,
+    /// The same transaction is already in the mempool of the node
+    AlreadyKnown,
+    /// Another transaction with the same nonce is in the mempool, and the fees of the
+    /// transaction are not high enough to replace it
+    ReplacementUnderpriced,
+}
+
+impl NonceErrorKind {
+    /// Recognizes the nonce error in the message of the error, `None` if it is another error
+    pub fn of(err: &impl Display) -> Option<Self> {
+        let message = err.to_string().to_lowercase();
+        let contains = |needles: &[&str]| needles.iter().any(|needle| message.contains(needle));
+
+        if contains(&["nonce too low", "nonce is too low", "invalid transaction nonce", "oldnonce"])
+        {
+            Some(NonceErrorKind::NonceTooLow)
+        } else if contains(&["already known", "known transaction", "already imported"]) {
+            Some(NonceErrorKind::AlreadyKnown)
+        } else if contains(&["replacement transaction underpriced", "replacement underpriced"]) {
+            Some(NonceErrorKind::ReplacementUnderpriced)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Middleware that recovers from nonce errors when sending transactions, by resyncing the nonce
+/// with the node and sending the transaction again a bounded number of times.
+///
+/// - "nonce too low": the nonce is replaced by the pending transaction count of the sender. Only
+///   nonces that the middleware assigned itself are resynced, the error is returned if the
+///   transaction came with a nonce.
+/// - "replacement underpriced": the fees of the transaction are raised by [`MIN_REPLACEMENT_BUMP`]
+///   percent to replace the pending transaction with the same nonce, the nonce is never changed.
+/// - "already known": the transaction is already pending, sending it with another nonce would send
+///   it twice, so [`NonceRecoveryError::AlreadyKnown`] is returned instead of the error of the
+///   node.
+///
+/// Transactions are filled by the inner middleware before they are sent and get the pending
+/// transaction count of their sender as nonce if they have none, so the recovery needs a sender,
+/// from the transaction or the default sender. Place the middleware above the
+/// [`SignerMiddleware`](crate::SignerMiddleware) so each retry is signed again.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::nonce_recovery::NonceRecoveryMiddleware;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let client = NonceRecoveryMiddleware::new(provider).max_retries(5);
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100).from(Address::random());
+/// client.send_transaction(tx, None).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct NonceRecoveryMiddleware<M> {
+    inner: M,
+    max_retries: usize,
+}
+
+impl<M> NonceRecoveryMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Wraps the middleware, retrying [`DEFAULT_MAX_RETRIES`] times
+    pub fn new(inner: M) -> Self {
+        Self { inner, max_retries: DEFAULT_MAX_RETRIES }
+    }
+
+    /// Sets how often a transaction is resent after a nonce error
+    #[must_use]
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Corrects the transaction after the nonce error, `assigned` says whether the middleware
+    /// assigned its nonce. Returns whether the transaction can be resent.
+    async fn recover(
+        &self,
+        tx: &mut TypedTransaction,
+        kind: NonceErrorKind,
+        assigned: bool,
+    ) -> Result<bool, NonceRecoveryError<M>> {
+        match kind {
+            NonceErrorKind::NonceTooLow if assigned => {
+                let from = *tx.from().ok_or(NonceRecoveryError::MissingSender)?;
+                let nonce = self
+                    .inner
+                    .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                    .await
+                    .map_err(NonceRecoveryError::MiddlewareError)?;
+                tx.set_nonce(nonce);
+                Ok(true)
+            }
+            NonceErrorKind::ReplacementUnderpriced => match Fees::of(tx) {
+                Some(fees) => {
+                    fees.bump(MIN_REPLACEMENT_BUMP).apply(tx);
+                    Ok(true)
+                }
+                None => Ok(false),
+            },
+            _ => Ok(false),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the nonce recovery middleware interacts with the blockchain
+pub enum NonceRecoveryError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error("the transaction is already pending")]
+    /// Thrown when the node already knows the transaction
+    AlreadyKnown,
+
+    #[error("nonce recovery requires a sender")]
+    /// Thrown when the transaction has no sender and there is no default sender
+    MissingSender,
+}
+
+impl<M: Middleware> FromErr<M::Error> for NonceRecoveryError<M> {
+    fn from(src: M::Error) -> NonceRecoveryError<M> {
+        NonceRecoveryError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for NonceRecoveryMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = NonceRecoveryError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        if tx.from().is_none() {
+            if let Some(default_sender) = self.default_sender() {
+                tx.set_from(default_sender);
+            }
+        }
+        let assigned = fill_with_nonce(&self.inner, &mut tx, block)
+            .await
+            .map_err(NonceRecoveryError::MiddlewareError)?;
+
+        let mut retries = 0;
+        loop {
+            let err = match self.inner.send_transaction(tx.clone(), block).await {
+                Ok(pending) => return Ok(pending),
+                Err(err) => err,
+            };
+            let kind = match NonceErrorKind::of(&err) {
+                Some(NonceErrorKind::AlreadyKnown) => return Err(NonceRecoveryError::AlreadyKnown),
+                Some(kind) if retries < self.max_retries => kind,
+                _ => return Err(NonceRecoveryError::MiddlewareError(err)),
+            };
+            if !self.recover(&mut tx, kind, assigned).await? {
+                return Err(NonceRecoveryError::MiddlewareError(err))
+            }
+            retries += 1;
+            tracing::debug!(?kind, nonce = ?tx.nonce(), retries, "resending after nonce error");
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, TransactionRequest, H256, U256};
+    use ethers_providers::{JsonRpcError, MockProvider, Provider};
+
+    /// Mocks a node answering the pending nonce requests with the nonces and rejecting the sent
+    /// transactions with the errors, in order
+    fn node(nonces: &[u64], errors: &[&str]) -> (Provider<MockProvider>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        for nonce in nonces {
+            mock.expect("eth_getTransactionCount").times(1).returns(U256::from(*nonce)).unwrap();
+        }
+        mock.push(H256::repeat_byte(1)).unwrap();
+        for message in errors.iter().rev() {
+            mock.push_error(JsonRpcError {
+                code: -32000,
+                message: message.to_string(),
+                data: None,
+            });
+        }
+        (provider, mock)
+    }
+
+    fn tx() -> TransactionRequest {
+        TransactionRequest::pay(Address::zero(), 1)
+            .from(Address::repeat_byte(1))
+            .gas(21_000)
+            .gas_price(100)
+    }
+
+    fn assert_sent(mock: &MockProvider, tx: TransactionRequest) {
+        mock.assert_request("eth_sendTransaction", [TypedTransaction::Legacy(tx)]).unwrap();
+    }
+
+    fn assert_nonce_request(mock: &MockProvider) {
+        let from = Address::repeat_byte(1);
+        mock.assert_request("eth_getTransactionCount", (from, "pending")).unwrap();
+    }
+
+    #[test]
+    fn recognizes_nonce_errors() {
+        assert_eq!(NonceErrorKind::of(&"nonce too low"), Some(NonceErrorKind::NonceTooLow));
+        assert_eq!(
+            NonceErrorKind::of(&"Transaction with the same hash was already imported."),
+            Some(NonceErrorKind::AlreadyKnown)
+        );
+        assert_eq!(
+            NonceErrorKind::of(&"replacement transaction underpriced"),
+            Some(NonceErrorKind::ReplacementUnderpriced)
+        );
+        assert_eq!(NonceErrorKind::of(&"transaction underpriced"), None);
+    }
+
+    #[tokio::test]
+    async fn resyncs_nonce_too_low() {
+        let (provider, mock) = node(&[3, 5], &["nonce too low"]);
+        let client = NonceRecoveryMiddleware::new(provider);
+        client.send_transaction(tx(), None).await.unwrap();
+
+        assert_nonce_request(&mock);
+        assert_sent(&mock, tx().nonce(3));
+        assert_nonce_request(&mock);
+        assert_sent(&mock, tx().nonce(5));
+    }
+
+    #[tokio::test]
+    async fn keeps_nonces_of_the_caller() {
+        let (provider, mock) = node(&[], &["nonce too low"]);
+        let client = NonceRecoveryMiddleware::new(provider);
+        let err = client.send_transaction(tx().nonce(3), None).await.unwrap_err();
+        assert!(matches!(err, NonceRecoveryError::MiddlewareError(_)));
+        assert_sent(&mock, tx().nonce(3));
+        assert!(mock.assert_request("eth_getTransactionCount", ()).is_err());
+    }
+
+    #[tokio::test]
+    async fn bumps_fees_of_underpriced_replacements() {
+        let (provider, mock) = node(&[], &["replacement transaction underpriced"]);
+        let client = NonceRecoveryMiddleware::new(provider);
+        client.send_transaction(tx().nonce(3), None).await.unwrap();
+
+        assert_sent(&mock, tx().nonce(3));
+        assert_sent(&mock, tx().nonce(3).gas_price(110));
+    }
+
+    #[tokio::test]
+    async fn bounds_retries() {
+        let (provider, mock) = node(&[3, 5], &["nonce too low"; 3]);
+        let client = NonceRecoveryMiddleware::new(provider).max_retries(1);
+        let err = client.send_transaction(tx(), None).await.unwrap_err();
+        assert!(matches!(err, NonceRecoveryError::MiddlewareError(_)));
+        assert_nonce_request(&mock);
+        assert_sent(&mock, tx().nonce(3));
+        assert_nonce_request(&mock);
+        assert_sent(&mock, tx().nonce(5));
+        assert!(mock.assert_request("eth_sendTransaction", ()).is_err());
+
+        let (provider, _) = node(&[3], &["already known"]);
+        let client = NonceRecoveryMiddleware::new(provider);
+        let err = client.send_transaction(tx(), None).await.unwrap_err();
+        assert!(matches!(err, NonceRecoveryError::AlreadyKnown));
+    }
+}
@@ -1,11 +1,16 @@
-use ethers_core::types::{transaction::eip2718::TypedTransaction, BlockId};
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Address, BlockId, NameOrAddress, Selector, U256,
+};
 use ethers_providers::{FromErr, Middleware, PendingTransaction};
 
 use async_trait::async_trait;
-use std::fmt::Debug;
+use std::{collections::HashSet, fmt, fmt::Debug, future::Future};
 use thiserror::Error;
 
 /// Basic trait to ensure that transactions about to be sent follow certain rules.
+///
+/// Policies are async, so they can consult a database or an approval service before a
+/// transaction is sent, see [`policy_fn`] for policies from async closures.
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait Policy: Sync + Send + Debug {
@@ -45,7 +50,239 @@ impl Policy for RejectEverything {
     }
 }
 
+/// Why a transaction was rejected by one of the built-in policies
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// The recipient isn't allowed, `None` for contract deployments
+    #[error("recipient {0:?} is not allowed")]
+    RecipientNotAllowed(Option<NameOrAddress>),
+    /// The value exceeds the maximum
+    #[error("value {value} exceeds the maximum of {max}")]
+    ValueTooHigh { value: U256, max: U256 },
+    /// The gas price, or the max fee of EIP-1559 transactions, exceeds the maximum
+    #[error("gas price {gas_price} exceeds the maximum of {max}")]
+    GasPriceTooHigh { gas_price: U256, max: U256 },
+    /// The calldata calls a denied function
+    #[error("selector 0x{} is denied", ethers_core::utils::hex::encode(.0))]
+    SelectorDenied(Selector),
+    /// Rejected by a custom policy
+    #[error("{0}")]
+    Rejected(String),
+}
+
+/// A policy that only allows transactions to the listed addresses, e.g. the contracts a service
+/// interacts with. Contract deployments are rejected unless they are
+/// [allowed](AddressAllowlist::allow_deployments).
+#[derive(Debug, Clone, Default)]
+pub struct AddressAllowlist {
+    addresses: HashSet<Address>,
+    deployments: bool,
+}
+
+impl AddressAllowlist {
+    /// Creates a policy allowing transactions to the addresses, without contract deployments
+    pub fn new(addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self { addresses: addresses.into_iter().collect(), deployments: false }
+    }
+
+    /// Allows transactions to the address
+    pub fn allow(&mut self, address: Address) {
+        self.addresses.insert(address);
+    }
+
+    /// Sets whether contract deployments are allowed
+    #[must_use]
+    pub fn allow_deployments(mut self, deployments: bool) -> Self {
+        self.deployments = deployments;
+        self
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for AddressAllowlist {
+    type Error = PolicyViolation;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        let allowed = match tx.to() {
+            Some(NameOrAddress::Address(to)) => self.addresses.contains(to),
+            // names are resolved before the policy is applied
+            Some(NameOrAddress::Name(_)) => false,
+            None => self.deployments,
+        };
+        if !allowed {
+            return Err(PolicyViolation::RecipientNotAllowed(tx.to().cloned()))
+        }
+        Ok(tx)
+    }
+}
+
+/// A policy that rejects transactions whose value exceeds the maximum
+#[derive(Debug, Clone, Copy)]
+pub struct MaxValue(pub U256);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for MaxValue {
+    type Error = PolicyViolation;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        match tx.value() {
+            Some(value) if *value > self.0 => {
+                Err(PolicyViolation::ValueTooHigh { value: *value, max: self.0 })
+            }
+            _ => Ok(tx),
+        }
+    }
+}
+
+/// A policy that rejects transactions whose gas price, or max fee for EIP-1559 transactions,
+/// exceeds the maximum
+#[derive(Debug, Clone, Copy)]
+pub struct MaxGasPrice(pub U256);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for MaxGasPrice {
+    type Error = PolicyViolation;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        match tx.gas_price() {
+            Some(gas_price) if gas_price > self.0 => {
+                Err(PolicyViolation::GasPriceTooHigh { gas_price, max: self.0 })
+            }
+            _ => Ok(tx),
+        }
+    }
+}
+
+/// A policy that rejects transactions calling the listed functions, by the selector of the
+/// calldata
+#[derive(Debug, Clone, Default)]
+pub struct SelectorDenylist {
+    selectors: HashSet<Selector>,
+}
+
+impl SelectorDenylist {
+    /// Creates a policy denying calls of the functions with the selectors
+    pub fn new(selectors: impl IntoIterator<Item = Selector>) -> Self {
+        Self { selectors: selectors.into_iter().collect() }
+    }
+
+    /// Denies calls of the function with the selector
+    pub fn deny(&mut self, selector: Selector) {
+        self.selectors.insert(selector);
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for SelectorDenylist {
+    type Error = PolicyViolation;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        if let Some(data) = tx.data().filter(|data| data.len() >= 4) {
+            let selector: Selector = data[..4].try_into().expect("4 bytes");
+            if self.selectors.contains(&selector) {
+                return Err(PolicyViolation::SelectorDenied(selector))
+            }
+        }
+        Ok(tx)
+    }
+}
+
+/// A policy that applies all of its policies in order, each to the transaction returned by the
+/// previous one
+#[derive(Debug, Default)]
+pub struct AllOf {
+    policies: Vec<Box<dyn Policy<Error = PolicyViolation>>>,
+}
+
+impl AllOf {
+    /// Creates a policy without policies, which allows every transaction
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a policy, applied after the previously added policies
+    #[must_use]
+    pub fn with(mut self, policy: impl Policy<Error = PolicyViolation> + 'static) -> Self {
+        self.policies.push(Box::new(policy));
+        self
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl Policy for AllOf {
+    type Error = PolicyViolation;
+
+    async fn ensure_can_send(
+        &self,
+        mut tx: TypedTransaction,
+    ) -> Result<TypedTransaction, Self::Error> {
+        for policy in &self.policies {
+            tx = policy.ensure_can_send(tx).await?;
+        }
+        Ok(tx)
+    }
+}
+
+/// A policy from an async closure, see [`policy_fn`]
+pub struct PolicyFn<F> {
+    f: F,
+}
+
+impl<F> Debug for PolicyFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PolicyFn").finish_non_exhaustive()
+    }
+}
+
+/// Returns a policy that applies the async closure, e.g. to ask an approval service whether the
+/// transaction may be sent
+///
+/// ```
+/// use ethers_middleware::policy::{policy_fn, PolicyViolation};
+///
+/// let policy = policy_fn(|tx| async move {
+///     // consult a database or an approval service
+///     let approved = tx.nonce().is_some();
+///     if approved {
+///         Ok(tx)
+///     } else {
+///         Err(PolicyViolation::Rejected("not approved".to_string()))
+///     }
+/// });
+/// ```
+pub fn policy_fn<F, Fut, E>(f: F) -> PolicyFn<F>
+where
+    F: Fn(TypedTransaction) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<TypedTransaction, E>> + Send,
+    E: Sync + Send + Debug,
+{
+    PolicyFn { f }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<F, Fut, E> Policy for PolicyFn<F>
+where
+    F: Fn(TypedTransaction) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<TypedTransaction, E>> + Send,
+    E: Sync + Send + Debug,
+{
+    type Error = E;
+
+    async fn ensure_can_send(&self, tx: TypedTransaction) -> Result<TypedTransaction, Self::Error> {
+        (self.f)(tx).await
+    }
+}
+
 /// Middleware used to enforce certain policies for transactions.
+///
+/// Transactions are filled by the inner middleware before the policy is applied, so that
+/// policies see the resolved recipient and the fees that will be paid.
 #[derive(Clone, Debug)]
 pub struct PolicyMiddleware<M, P> {
     pub(crate) inner: M,
@@ -95,18 +332,90 @@ where
         &self.inner
     }
 
-    /// This ensures the tx complies with the registered policy.
+    /// This fills the tx and ensures it complies with the registered policy.
     /// If so then this simply delegates the transaction to the inner middleware
     async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
         &self,
         tx: T,
         block: Option<BlockId>,
     ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
-        let tx = self
-            .policy
-            .ensure_can_send(tx.into())
+        let mut tx = tx.into();
+        self.inner
+            .fill_transaction(&mut tx, block)
             .await
-            .map_err(PolicyMiddlewareError::PolicyError)?;
+            .map_err(PolicyMiddlewareError::MiddlewareError)?;
+        let tx =
+            self.policy.ensure_can_send(tx).await.map_err(PolicyMiddlewareError::PolicyError)?;
         self.inner.send_transaction(tx, block).await.map_err(PolicyMiddlewareError::MiddlewareError)
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Bytes, TransactionRequest, H256};
+    use ethers_providers::Provider;
+
+    fn tx(to: Address) -> TypedTransaction {
+        TransactionRequest::pay(to, 100).gas(21_000).gas_price(10).into()
+    }
+
+    #[tokio::test]
+    async fn applies_builtin_policies() {
+        let allowed = Address::repeat_byte(1);
+        let policy = AllOf::new()
+            .with(AddressAllowlist::new([allowed]))
+            .with(MaxValue(100.into()))
+            .with(MaxGasPrice(10.into()))
+            .with(SelectorDenylist::new([[9, 5, 234, 179]]));
+
+        assert!(policy.ensure_can_send(tx(allowed)).await.is_ok());
+        assert_eq!(
+            policy.ensure_can_send(tx(Address::zero())).await.unwrap_err(),
+            PolicyViolation::RecipientNotAllowed(Some(Address::zero().into()))
+        );
+
+        let mut too_much = tx(allowed);
+        too_much.set_value(101);
+        assert_eq!(
+            policy.ensure_can_send(too_much).await.unwrap_err(),
+            PolicyViolation::ValueTooHigh { value: 101.into(), max: 100.into() }
+        );
+
+        let mut too_expensive = tx(allowed);
+        too_expensive.set_gas_price(11);
+        assert_eq!(
+            policy.ensure_can_send(too_expensive).await.unwrap_err(),
+            PolicyViolation::GasPriceTooHigh { gas_price: 11.into(), max: 10.into() }
+        );
+
+        let mut denied = tx(allowed);
+        denied.set_data(Bytes::from(vec![9, 5, 234, 179, 0]));
+        assert_eq!(
+            policy.ensure_can_send(denied).await.unwrap_err(),
+            PolicyViolation::SelectorDenied([9, 5, 234, 179])
+        );
+    }
+
+    #[tokio::test]
+    async fn applies_async_policies() {
+        let (provider, mock) = Provider::mocked();
+        let policy = policy_fn(|tx: TypedTransaction| async move {
+            tokio::task::yield_now().await;
+            match tx.to() {
+                Some(NameOrAddress::Address(to)) if to.is_zero() => {
+                    Err(PolicyViolation::Rejected("burn".to_string()))
+                }
+                _ => Ok(tx),
+            }
+        });
+        let client = PolicyMiddleware::new(provider, policy);
+
+        let err = client.send_transaction(tx(Address::zero()), None).await.unwrap_err();
+        assert!(matches!(err, PolicyMiddlewareError::PolicyError(PolicyViolation::Rejected(_))));
+
+        mock.push(H256::zero()).unwrap();
+        client.send_transaction(tx(Address::repeat_byte(1)), None).await.unwrap();
+    }
+}
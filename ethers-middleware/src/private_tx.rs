@@ -0,0 +1,300 @@
+use async_trait::async_trait;
+use ethers_core::{
+    types::{transaction::eip2718::TypedTransaction, BlockId, Bytes, TxHash},
+    utils::{hex, keccak256},
+};
+use ethers_providers::{
+    interval, FromErr, JsonRpcClient, Middleware, PendingTransaction, Provider, ProviderError,
+    StreamExt,
+};
+use serde_json::{json, Value};
+use std::{sync::Arc, time::Duration};
+use thiserror::Error;
+
+/// The URL of [Flashbots Protect](https://docs.flashbots.net/flashbots-protect/overview)
+pub const FLASHBOTS_PROTECT: &str = "https://rpc.flashbots.net";
+
+/// The URL of [MEV Blocker](https://mevblocker.io)
+pub const MEV_BLOCKER: &str = "https://rpc.mevblocker.io";
+
+/// The URL of the [bloXroute](https://docs.bloxroute.com) cloud API, which requires an
+/// `Authorization` header
+pub const BLOXROUTE: &str = "https://api.blxrbdn.com";
+
+/// How signed transactions are submitted to a private relay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateRelayMethod {
+    /// `eth_sendRawTransaction`, for RPC endpoints like Flashbots Protect and MEV Blocker
+    SendRawTransaction,
+    /// `eth_sendPrivateTransaction`, for the Flashbots relay
+    SendPrivateTransaction,
+    /// `blxr_private_tx`, for bloXroute
+    Bloxroute,
+}
+
+impl PrivateRelayMethod {
+    /// Returns the method and params of a request submitting the raw transaction
+    fn request(&self, raw: &Bytes) -> (&'static str, Value) {
+        match self {
+            PrivateRelayMethod::SendRawTransaction => ("eth_sendRawTransaction", json!([raw])),
+            PrivateRelayMethod::SendPrivateTransaction => {
+                ("eth_sendPrivateTransaction", json!([{ "tx": raw }]))
+            }
+            PrivateRelayMethod::Bloxroute => {
+                ("blxr_private_tx", json!({ "transaction": hex::encode(raw) }))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+/// Middleware that sends transactions to a private relay instead of the public mempool, to
+/// protect them from sandwiching and other front-running.
+///
+/// Transactions are signed by the inner middleware, e.g. a
+/// [`SignerMiddleware`](crate::SignerMiddleware), and submitted to the relay with the configured
+/// [`PrivateRelayMethod`]. If a transaction isn't mined before the fallback deadline, or the
+/// relay rejects it, it's sent to the public mempool by the inner middleware. Without a deadline
+/// transactions are only ever sent privately. On wasm the deadline is not waited for, only
+/// rejected transactions fall back to the public mempool.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::{private_tx::{PrivateTxMiddleware, MEV_BLOCKER}, SignerMiddleware};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_signers::LocalWallet;
+/// use std::{convert::TryFrom, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let wallet: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let client = SignerMiddleware::new_with_provider_chain(provider, wallet).await?;
+///
+/// let relay = Provider::<Http>::try_from(MEV_BLOCKER)?;
+/// let client = PrivateTxMiddleware::new(client, relay)
+///     .fallback_after(Some(Duration::from_secs(300)));
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100);
+/// let receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PrivateTxMiddleware<M, P> {
+    inner: Arc<M>,
+    relay: Arc<Provider<P>>,
+    method: PrivateRelayMethod,
+    fallback_after: Option<Duration>,
+}
+
+impl<M, P> PrivateTxMiddleware<M, P>
+where
+    M: Middleware + 'static,
+    P: JsonRpcClient + 'static,
+{
+    /// Creates a middleware that sends transactions to the relay with `eth_sendRawTransaction`,
+    /// falling back to the public mempool if they aren't mined within 3 minutes
+    pub fn new(inner: M, relay: Provider<P>) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            relay: Arc::new(relay),
+            method: PrivateRelayMethod::SendRawTransaction,
+            fallback_after: Some(Duration::from_secs(180)),
+        }
+    }
+
+    /// Sets how transactions are submitted to the relay
+    #[must_use]
+    pub fn method(mut self, method: PrivateRelayMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Sets how long to wait for transactions to be mined before sending them to the public
+    /// mempool, `None` to never send them publicly
+    #[must_use]
+    pub fn fallback_after(mut self, fallback_after: Option<Duration>) -> Self {
+        self.fallback_after = fallback_after;
+        self
+    }
+
+    /// Returns the provider of the private relay
+    pub fn relay(&self) -> &Provider<P> {
+        &self.relay
+    }
+
+    /// Submits the signed transaction to the relay
+    pub async fn send_private_raw_transaction(&self, raw: &Bytes) -> Result<(), ProviderError> {
+        let (method, params) = self.method.request(raw);
+        let _: Value = self.relay.request(method, params).await?;
+        Ok(())
+    }
+
+    /// Sends the transaction to the public mempool after the deadline, unless it was mined
+    #[cfg(not(target_arch = "wasm32"))]
+    fn schedule_fallback(&self, deadline: Duration, hash: TxHash, raw: Bytes) {
+        let inner = self.inner.clone();
+        tokio::spawn(async move {
+            interval(deadline).next().await;
+            match inner.get_transaction_receipt(hash).await {
+                Ok(Some(_)) => {}
+                _ => {
+                    tracing::debug!(?hash, "sending private transaction to the public mempool");
+                    if let Err(err) = inner.send_raw_transaction(raw).await {
+                        tracing::debug!(?hash, %err, "failed to send the transaction publicly");
+                    }
+                }
+            }
+        });
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn schedule_fallback(&self, _: Duration, _: TxHash, _: Bytes) {}
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the private transaction middleware sends a transaction
+pub enum PrivateTxError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error("the private relay rejected the transaction: {0}")]
+    /// Thrown when the relay rejects a transaction that may not be sent publicly
+    RelayError(ProviderError),
+
+    #[error("Private transactions require a sender")]
+    /// Thrown when the transaction has no sender after it was filled
+    MissingSender,
+}
+
+impl<M: Middleware> FromErr<M::Error> for PrivateTxError<M> {
+    fn from(src: M::Error) -> PrivateTxError<M> {
+        PrivateTxError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M, P> Middleware for PrivateTxMiddleware<M, P>
+where
+    M: Middleware + 'static,
+    P: JsonRpcClient + 'static,
+{
+    type Error = PrivateTxError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Signs the transaction with the inner middleware and sends it to the private relay
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        self.inner
+            .fill_transaction(&mut tx, block)
+            .await
+            .map_err(PrivateTxError::MiddlewareError)?;
+        let from = *tx.from().ok_or(PrivateTxError::MissingSender)?;
+        let signature = self
+            .inner
+            .sign_transaction(&tx, from)
+            .await
+            .map_err(PrivateTxError::MiddlewareError)?;
+        let raw = tx.rlp_signed(&signature);
+        let hash = TxHash::from(keccak256(&raw));
+
+        match (self.send_private_raw_transaction(&raw).await, self.fallback_after) {
+            (Ok(()), Some(deadline)) => self.schedule_fallback(deadline, hash, raw),
+            (Ok(()), None) => {}
+            (Err(err), Some(_)) => {
+                tracing::debug!(?hash, %err, "the private relay rejected the transaction");
+                return self
+                    .inner
+                    .send_raw_transaction(raw)
+                    .await
+                    .map_err(PrivateTxError::MiddlewareError)
+            }
+            (Err(err), None) => return Err(PrivateTxError::RelayError(err)),
+        }
+
+        Ok(PendingTransaction::new(hash, self.provider()))
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::SignerMiddleware;
+    use ethers_core::types::{Address, TransactionReceipt, TransactionRequest, H256};
+    use ethers_signers::{LocalWallet, Signer};
+
+    fn tx() -> TransactionRequest {
+        TransactionRequest::pay(Address::repeat_byte(1), 100)
+            .nonce(0)
+            .gas(21_000)
+            .gas_price(1)
+            .chain_id(1)
+    }
+
+    #[tokio::test]
+    async fn sends_privately_then_publicly() {
+        let (provider, mock) = Provider::mocked();
+        let (relay, relay_mock) = Provider::mocked();
+        let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(1u64);
+        let client =
+            PrivateTxMiddleware::new(SignerMiddleware::new(provider, wallet.clone()), relay)
+                .fallback_after(Some(Duration::from_millis(10)));
+
+        let tx: TypedTransaction = tx().from(wallet.address()).into();
+        let raw = tx.rlp_signed(&wallet.sign_transaction_sync(&tx));
+        let hash = H256::from(keccak256(&raw));
+
+        relay_mock.push(hash).unwrap();
+        mock.push(hash).unwrap();
+        mock.push(None::<TransactionReceipt>).unwrap();
+        assert_eq!(*client.send_transaction(tx, None).await.unwrap(), hash);
+        relay_mock.assert_request("eth_sendRawTransaction", [raw.clone()]).unwrap();
+
+        // not mined before the deadline
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        mock.assert_request("eth_getTransactionReceipt", [hash]).unwrap();
+        mock.assert_request("eth_sendRawTransaction", [raw]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn falls_back_on_rejections() {
+        let (provider, mock) = Provider::mocked();
+        let (relay, _) = Provider::mocked();
+        let wallet = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(1u64);
+        let client =
+            PrivateTxMiddleware::new(SignerMiddleware::new(provider, wallet.clone()), relay)
+                .method(PrivateRelayMethod::SendPrivateTransaction);
+
+        let tx: TypedTransaction = tx().from(wallet.address()).into();
+        let raw = tx.rlp_signed(&wallet.sign_transaction_sync(&tx));
+        mock.push(H256::from(keccak256(&raw))).unwrap();
+        client.send_transaction(tx.clone(), None).await.unwrap();
+        mock.assert_request("eth_sendRawTransaction", [raw]).unwrap();
+
+        // without a deadline, transactions are never sent publicly
+        let client = client.fallback_after(None);
+        let err = client.send_transaction(tx, None).await.unwrap_err();
+        assert!(matches!(err, PrivateTxError::RelayError(_)));
+    }
+
+    #[test]
+    fn bloxroute_params() {
+        let (method, params) = PrivateRelayMethod::Bloxroute.request(&Bytes::from(vec![2, 0xf8]));
+        assert_eq!(method, "blxr_private_tx");
+        assert_eq!(params, json!({ "transaction": "02f8" }));
+    }
+}
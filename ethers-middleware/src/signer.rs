@@ -4,7 +4,7 @@ use ethers_core::types::{
 };
 use ethers_providers::{maybe, FromErr, Middleware, PendingTransaction};
 use ethers_signers::Signer;
-use std::convert::TryFrom;
+use std::{collections::HashMap, convert::TryFrom};
 
 use async_trait::async_trait;
 use thiserror::Error;
@@ -17,7 +17,7 @@ use thiserror::Error;
 ///
 /// ```no_run
 /// use ethers_providers::{Middleware, Provider, Http};
-/// use ethers_signers::LocalWallet;
+/// use ethers_signers::{LocalWallet, Signer};
 /// use ethers_middleware::SignerMiddleware;
 /// use ethers_core::types::{Address, TransactionRequest};
 /// use std::convert::TryFrom;
@@ -44,6 +44,15 @@ use thiserror::Error;
 /// // number of confirmations
 /// let receipt = pending_tx.confirmations(6).await?;
 ///
+/// // More signers can be registered, transactions are signed by the signer of their `from`
+/// // address
+/// let wallet3: LocalWallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+///     .parse()?;
+/// let address3 = wallet3.address();
+/// client.add_signer(wallet3);
+/// let tx3 = TransactionRequest::pay("vitalik.eth", 300).from(address3);
+/// let receipt3 = client.send_transaction(tx3, None).await?.await?;
+///
 /// // You can connect with other wallets at runtime via the `with_signer` function
 /// let wallet2: LocalWallet = "cd8c407233c0560f6de24bb2dc60a8b02335c959a1a17f749ce6c1ccf63d74a7"
 ///     .parse()?;
@@ -65,6 +74,8 @@ pub struct SignerMiddleware<M, S> {
     pub(crate) inner: M,
     pub(crate) signer: S,
     pub(crate) address: Address,
+    /// The additional signers by address, see [`SignerMiddleware::add_signer`]
+    pub(crate) signers: HashMap<Address, S>,
 }
 
 impl<M: Middleware, S: Signer> FromErr<M::Error> for SignerMiddlewareError<M, S> {
@@ -118,7 +129,7 @@ where
     /// [`Signer`] ethers_signers::Signer
     pub fn new(inner: M, signer: S) -> Self {
         let address = signer.address();
-        SignerMiddleware { inner, signer, address }
+        SignerMiddleware { inner, signer, address, signers: HashMap::new() }
     }
 
     /// Signs and returns the RLP encoding of the signed transaction.
@@ -129,9 +140,11 @@ where
         &self,
         mut tx: TypedTransaction,
     ) -> Result<Bytes, SignerMiddlewareError<M, S>> {
+        let signer = tx.from().and_then(|from| self.signer_of(from)).unwrap_or(&self.signer);
+
         // compare chain_id and use signer's chain_id if the tranasaction's chain_id is None,
         // return an error if they are not consistent
-        let chain_id = self.signer.chain_id();
+        let chain_id = signer.chain_id();
         match tx.chain_id() {
             Some(id) if id.as_u64() != chain_id => {
                 return Err(SignerMiddlewareError::DifferentChainID)
@@ -143,7 +156,7 @@ where
         }
 
         let signature =
-            self.signer.sign_transaction(&tx).await.map_err(SignerMiddlewareError::SignerError)?;
+            signer.sign_transaction(&tx).await.map_err(SignerMiddlewareError::SignerError)?;
 
         // Return the raw rlp-encoded signed transaction
         Ok(tx.rlp_signed(&signature))
@@ -159,6 +172,30 @@ where
         &self.signer
     }
 
+    /// Registers an additional signer, which signs the transactions and messages from its
+    /// address. Returns the signer previously registered for the address, if any.
+    ///
+    /// The chain id of the signer is not changed, it needs to match the chain id of the other
+    /// signers.
+    pub fn add_signer(&mut self, signer: S) -> Option<S> {
+        self.signers.insert(signer.address(), signer)
+    }
+
+    /// Returns the signers, starting with the signer of the client's address
+    pub fn signers(&self) -> impl Iterator<Item = &S> {
+        std::iter::once(&self.signer)
+            .chain(self.signers.values().filter(|signer| signer.address() != self.address))
+    }
+
+    /// Returns the signer of the address, if it's registered
+    pub fn signer_of(&self, address: &Address) -> Option<&S> {
+        if *address == self.address {
+            Some(&self.signer)
+        } else {
+            self.signers.get(address)
+        }
+    }
+
     /// Builds a SignerMiddleware with the given Signer.
     #[must_use]
     pub fn with_signer(&self, signer: S) -> Self
@@ -187,7 +224,7 @@ where
         let chain_id =
             inner.get_chainid().await.map_err(|e| SignerMiddlewareError::MiddlewareError(e))?;
         let signer = signer.with_chain_id(chain_id.as_u64());
-        Ok(SignerMiddleware { inner, signer, address })
+        Ok(SignerMiddleware { inner, signer, address, signers: HashMap::new() })
     }
 
     fn set_tx_from_if_none(&self, tx: &TypedTransaction) -> TypedTransaction {
@@ -224,12 +261,15 @@ where
         true
     }
 
+    /// Signs the transaction with the signer of `from`, or with the client's signer if `from`
+    /// isn't registered
     async fn sign_transaction(
         &self,
         tx: &TypedTransaction,
-        _: Address,
+        from: Address,
     ) -> Result<Signature, Self::Error> {
-        Ok(self.signer.sign_transaction(tx).await.map_err(SignerMiddlewareError::SignerError)?)
+        let signer = self.signer_of(&from).unwrap_or(&self.signer);
+        Ok(signer.sign_transaction(tx).await.map_err(SignerMiddlewareError::SignerError)?)
     }
 
     /// Helper for filling a transaction's nonce using the wallet
@@ -247,7 +287,7 @@ where
         tx.set_from(from);
 
         // get the signer's chain_id if the transaction does not set it
-        let chain_id = self.signer.chain_id();
+        let chain_id = self.signer_of(&from).unwrap_or(&self.signer).chain_id();
         if tx.chain_id().is_none() {
             tx.set_chain_id(chain_id);
         }
@@ -286,8 +326,8 @@ where
         // fill any missing fields
         self.fill_transaction(&mut tx, block).await?;
 
-        // If the from address is set and is not our signer, delegate to inner
-        if tx.from().is_some() && tx.from() != Some(&self.address()) {
+        // If the from address is set and is not one of our signers, delegate to inner
+        if tx.from().map_or(false, |from| self.signer_of(from).is_none()) {
             return self
                 .inner
                 .send_transaction(tx, block)
@@ -306,14 +346,15 @@ where
             .map_err(SignerMiddlewareError::MiddlewareError)
     }
 
-    /// Signs a message with the internal signer, or if none is present it will make a call to
-    /// the connected node's `eth_call` API.
+    /// Signs a message with the signer of `from`, or with the client's signer if `from` isn't
+    /// registered
     async fn sign<T: Into<Bytes> + Send + Sync>(
         &self,
         data: T,
-        _: &Address,
+        from: &Address,
     ) -> Result<Signature, Self::Error> {
-        self.signer.sign_message(data.into()).await.map_err(SignerMiddlewareError::SignerError)
+        let signer = self.signer_of(from).unwrap_or(&self.signer);
+        signer.sign_message(data.into()).await.map_err(SignerMiddlewareError::SignerError)
     }
 
     async fn estimate_gas(
@@ -351,7 +392,7 @@ where
 mod tests {
     use super::*;
     use ethers_core::{
-        types::{Eip1559TransactionRequest, TransactionRequest},
+        types::{Eip1559TransactionRequest, TransactionRequest, H256},
         utils::{self, keccak256, Anvil},
     };
     use ethers_providers::Provider;
@@ -517,6 +558,33 @@ mod tests {
         assert_eq!(tx.from, acc);
     }
 
+    #[tokio::test]
+    async fn routes_tx_to_signer_of_from() {
+        let (provider, mock) = Provider::mocked();
+        let first = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(1u64);
+        let second = LocalWallet::new(&mut rand::thread_rng()).with_chain_id(1u64);
+        let mut client = SignerMiddleware::new(provider, first.clone());
+        assert!(client.add_signer(second.clone()).is_none());
+        let addresses: Vec<_> = client.signers().map(|signer| signer.address()).collect();
+        assert_eq!(addresses, vec![first.address(), second.address()]);
+
+        let tx: TypedTransaction = TransactionRequest::pay(Address::zero(), 100)
+            .from(second.address())
+            .nonce(0)
+            .gas(21_000)
+            .gas_price(1)
+            .chain_id(1)
+            .into();
+        mock.push(H256::zero()).unwrap();
+        client.send_transaction(tx.clone(), None).await.unwrap();
+
+        let signature = second.sign_transaction_sync(&tx);
+        mock.assert_request("eth_sendRawTransaction", [tx.rlp_signed(&signature)]).unwrap();
+
+        let signature = client.sign(b"hello".to_vec(), &second.address()).await.unwrap();
+        signature.verify("hello", second.address()).unwrap();
+    }
+
     #[tokio::test]
     async fn converts_tx_to_legacy_to_match_chain() {
         let eip1559 = Eip1559TransactionRequest {
@@ -0,0 +1,369 @@
+use async_trait::async_trait;
+use ethers_contract::{BaseContract, StringOrPanic};
+use ethers_core::{
+    abi::AbiDecode,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockId, BlockNumber, Bytes, CallFrame,
+        GethDebugBuiltInTracerType, GethDebugTracerType, GethDebugTracingCallOptions, GethTrace,
+        GethTraceFrame,
+    },
+};
+use ethers_providers::{FromErr, JsonRpcError, Middleware, PendingTransaction, ProviderError};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// How transactions are simulated before they are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SimulationMethod {
+    /// `eth_call`, which only reports the revert data of the top level call
+    #[default]
+    Call,
+    /// `debug_traceCall` with the `callTracer`, which reports the revert data of the innermost
+    /// failed call, even if a contract above it swallowed or replaced the revert reason
+    TraceCall,
+}
+
+#[derive(Debug)]
+/// Middleware that simulates every transaction at the pending block before it's signed and
+/// sent, so transactions that would revert never cost any gas.
+///
+/// Transactions are filled by the inner middleware first, so the simulation uses the sender, gas
+/// limit and fees they will be sent with. If the gas estimate of a transaction without a gas
+/// limit reverts, the transaction is not simulated but rejected the same way.
+///
+/// If the simulation reverts, [`SimulationError::Reverted`] is returned with the revert data and
+/// its decoded reason: `Error(string)` and `Panic(uint256)` are always decoded, custom errors if
+/// the reverting contract was registered with [`SimulationMiddleware::with_abi`].
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::{abi::parse_abi, types::{Address, TransactionRequest}};
+/// use ethers_middleware::simulation::{SimulationError, SimulationMiddleware};
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// # let pool = Address::random();
+/// let abi = parse_abi(&["error InsufficientLiquidity(uint256 available)"])?;
+/// let client = SimulationMiddleware::new(provider).with_abi(pool, abi);
+///
+/// let tx = TransactionRequest::new().to(pool).data(vec![0xde, 0xad, 0xbe, 0xef]);
+/// match client.send_transaction(tx, None).await {
+///     Err(SimulationError::Reverted { reason, .. }) => println!("not sent: {reason:?}"),
+///     res => {
+///         res?.await?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SimulationMiddleware<M> {
+    inner: M,
+    method: SimulationMethod,
+    abis: HashMap<Address, BaseContract>,
+}
+
+impl<M> SimulationMiddleware<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    /// Creates a middleware that simulates transactions with `eth_call`
+    pub fn new(inner: M) -> Self {
+        Self { inner, method: SimulationMethod::default(), abis: HashMap::new() }
+    }
+
+    /// Sets how transactions are simulated
+    #[must_use]
+    pub fn method(mut self, method: SimulationMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Decodes the custom errors of the contract at `address` with its ABI
+    #[must_use]
+    pub fn with_abi(mut self, address: Address, abi: impl Into<BaseContract>) -> Self {
+        self.abis.insert(address, abi.into());
+        self
+    }
+
+    /// Simulates the transaction at the pending block, returning the output of the call if it
+    /// succeeds
+    pub async fn simulate(&self, tx: &TypedTransaction) -> Result<Bytes, SimulationError<M>> {
+        let block = Some(BlockNumber::Pending.into());
+        match self.method {
+            SimulationMethod::Call => {
+                self.inner.call(tx, block).await.map_err(|err| self.revert_error(tx, err))
+            }
+            SimulationMethod::TraceCall => {
+                let mut opts = GethDebugTracingCallOptions::default();
+                opts.tracing_options.tracer = Some(GethDebugTracerType::BuiltInTracer(
+                    GethDebugBuiltInTracerType::CallTracer,
+                ));
+                let trace = self
+                    .inner
+                    .debug_trace_call(tx.clone(), block, opts)
+                    .await
+                    .map_err(SimulationError::MiddlewareError)?;
+                let frame = match trace {
+                    GethTrace::Known(GethTraceFrame::CallTracer(frame)) => frame,
+                    other => serde_json::to_value(other)
+                        .and_then(serde_json::from_value)
+                        .map_err(|err| SimulationError::ProviderError(err.into()))?,
+                };
+
+                match failed_call(&frame) {
+                    Some(failed) => {
+                        let to = failed.to.as_ref().and_then(|to| to.as_address()).copied();
+                        let reason = failed
+                            .output
+                            .as_ref()
+                            .and_then(|data| self.decode_revert(to, data))
+                            .or_else(|| failed.error.clone());
+                        Err(SimulationError::Reverted { data: failed.output.clone(), reason })
+                    }
+                    None => Ok(frame.output.unwrap_or_default()),
+                }
+            }
+        }
+    }
+
+    /// Returns [`SimulationError::Reverted`] if the error of the inner middleware is a revert of
+    /// the transaction
+    fn revert_error(&self, tx: &TypedTransaction, err: M::Error) -> SimulationError<M> {
+        match revert_response(&err) {
+            Some(response) => {
+                let data = response.as_revert_data();
+                let reason =
+                    data.as_ref().and_then(|data| self.decode_revert(tx.to_addr().copied(), data));
+                SimulationError::Reverted { data, reason }
+            }
+            None => SimulationError::MiddlewareError(err),
+        }
+    }
+
+    /// Decodes the revert data as `Error(string)`, `Panic(uint256)` or a custom error of the
+    /// contract at `to`
+    fn decode_revert(&self, to: Option<Address>, data: &[u8]) -> Option<String> {
+        if let Ok(revert) = StringOrPanic::decode(data) {
+            return Some(revert.to_string())
+        }
+        let selector = data.get(..4)?;
+        let error = self
+            .abis
+            .get(&to?)?
+            .abi()
+            .errors()
+            .find(|error| error.signature().as_bytes().starts_with(selector))?;
+        let tokens = error.decode(&data[4..]).ok()?;
+        let args = tokens.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        Some(format!("{}({args})", error.name))
+    }
+}
+
+/// Returns the innermost failed call of the call tree, which is usually the origin of a revert
+/// that bubbled up through the calls above it
+fn failed_call(frame: &CallFrame) -> Option<&CallFrame> {
+    frame.error.as_ref()?;
+    Some(frame.calls.iter().flatten().rev().find_map(failed_call).unwrap_or(frame))
+}
+
+/// Returns the error response of the node if the call reverted, searching the error and its
+/// [`source`](std::error::Error::source) chain for a [`ProviderError`]
+fn revert_response<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a JsonRpcError> {
+    let mut next = Some(err);
+    while let Some(err) = next {
+        if let Some(response) =
+            err.downcast_ref::<ProviderError>().and_then(ProviderError::as_error_response)
+        {
+            let reverted = response.as_revert_data().is_some() ||
+                response.message.to_lowercase().contains("revert");
+            return reverted.then_some(response)
+        }
+        next = err.source();
+    }
+    None
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the simulation middleware sends a transaction
+pub enum SimulationError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error(transparent)]
+    /// Thrown when the trace of the simulation can't be decoded
+    ProviderError(ProviderError),
+
+    #[error("the transaction reverted in simulation: {}", reason.as_deref().unwrap_or("unknown reason"))]
+    /// Thrown when the simulation reverts, so the transaction was not sent
+    Reverted {
+        /// The revert data, if the node returned it
+        data: Option<Bytes>,
+        /// The decoded revert reason
+        reason: Option<String>,
+    },
+}
+
+impl<M: Middleware> FromErr<M::Error> for SimulationError<M> {
+    fn from(src: M::Error) -> SimulationError<M> {
+        SimulationError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for SimulationMiddleware<M>
+where
+    M: Middleware,
+    M::Error: 'static,
+{
+    type Error = SimulationError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Fills and simulates the transaction and only sends it if it succeeds
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        if let Err(err) = self.inner.fill_transaction(&mut tx, block).await {
+            return Err(self.revert_error(&tx, err))
+        }
+
+        self.simulate(&tx).await?;
+        self.inner.send_transaction(tx, block).await.map_err(SimulationError::MiddlewareError)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::{
+        abi::{parse_abi, AbiEncode},
+        types::{TransactionRequest, H256, U256},
+    };
+    use ethers_providers::Provider;
+    use serde_json::json;
+
+    fn revert(data: impl Into<Bytes>) -> JsonRpcError {
+        let data = Some(json!(data.into()));
+        JsonRpcError { code: 3, message: "execution reverted".to_string(), data }
+    }
+
+    fn tx(to: Address) -> TransactionRequest {
+        TransactionRequest::new().from(Address::repeat_byte(2)).to(to).gas(21_000).gas_price(1)
+    }
+
+    #[tokio::test]
+    async fn does_not_send_reverting_transactions() {
+        let pool = Address::repeat_byte(1);
+        let abi = parse_abi(&["error InsufficientLiquidity(uint256 available)"]).unwrap();
+        let mut data = ethers_core::utils::id("InsufficientLiquidity(uint256)").to_vec();
+        data.extend(U256::from(7).encode());
+
+        let (provider, mock) = Provider::mocked();
+        let call = mock.expect("eth_call").returns_error(revert(data.clone())).unwrap();
+        let client = SimulationMiddleware::new(provider).with_abi(pool, abi);
+        match client.send_transaction(tx(pool), None).await.unwrap_err() {
+            SimulationError::Reverted { data: revert, reason } => {
+                assert_eq!(revert, Some(data.into()));
+                assert_eq!(reason.as_deref(), Some("InsufficientLiquidity(7)"));
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+        assert_eq!(call.calls(), 1);
+
+        // revert strings are decoded for any contract
+        let data = StringOrPanic::RevertString("too late".to_string()).encode();
+        let (provider, mock) = Provider::mocked();
+        mock.push_error(revert(data));
+        let client = SimulationMiddleware::new(provider);
+        let err = client.send_transaction(tx(pool), None).await.unwrap_err();
+        assert!(
+            matches!(err, SimulationError::Reverted { reason: Some(reason), .. } if reason == "too late")
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_successful_transactions() {
+        let (provider, mock) = Provider::mocked();
+        let call = mock.expect("eth_call").returns(Bytes::default()).unwrap();
+        let send = mock.expect("eth_sendTransaction").returns(H256::repeat_byte(1)).unwrap();
+        let client = SimulationMiddleware::new(provider);
+        let pending = client.send_transaction(tx(Address::repeat_byte(1)), None).await.unwrap();
+        assert_eq!(*pending, H256::repeat_byte(1));
+        assert_eq!((call.calls(), send.calls()), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn simulates_filled_transactions() {
+        let to = Address::repeat_byte(1);
+        let (provider, mock) = Provider::mocked();
+        mock.push(H256::repeat_byte(1)).unwrap();
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        mock.push(U256::from(50_000)).unwrap();
+        let client = SimulationMiddleware::new(provider);
+        let tx = TransactionRequest::new().from(Address::repeat_byte(2)).to(to).gas_price(1);
+        client.send_transaction(tx.clone(), None).await.unwrap();
+
+        mock.assert_request("eth_estimateGas", [TypedTransaction::Legacy(tx.clone())]).unwrap();
+        let filled = TypedTransaction::Legacy(tx.gas(50_000));
+        mock.assert_request("eth_call", (&filled, "pending")).unwrap();
+        mock.assert_request("eth_sendTransaction", [&filled]).unwrap();
+
+        // reverting gas estimates are rejected like reverting simulations
+        let data = StringOrPanic::RevertString("too late".to_string()).encode();
+        let (provider, mock) = Provider::mocked();
+        mock.push_error(revert(data));
+        let client = SimulationMiddleware::new(provider);
+        let tx = TransactionRequest::new().from(Address::repeat_byte(2)).to(to).gas_price(1);
+        let err = client.send_transaction(tx, None).await.unwrap_err();
+        assert!(
+            matches!(err, SimulationError::Reverted { reason: Some(reason), .. } if reason == "too late")
+        );
+    }
+
+    #[tokio::test]
+    async fn decodes_innermost_revert_of_trace() {
+        let (router, pool) = (Address::repeat_byte(1), Address::repeat_byte(3));
+        let data = Bytes::from(StringOrPanic::Panic(0x11.into()).encode());
+        let trace = json!({
+            "type": "CALL",
+            "from": Address::repeat_byte(2),
+            "to": router,
+            "input": "0x",
+            "output": "0x",
+            "error": "execution reverted",
+            "calls": [{
+                "type": "CALL",
+                "from": router,
+                "to": pool,
+                "input": "0x",
+                "output": data,
+                "error": "execution reverted"
+            }]
+        });
+        let (provider, mock) = Provider::mocked();
+        mock.push(trace).unwrap();
+        let client = SimulationMiddleware::new(provider).method(SimulationMethod::TraceCall);
+
+        match client.send_transaction(tx(router), None).await.unwrap_err() {
+            SimulationError::Reverted { data: revert, reason } => {
+                assert_eq!(revert, Some(data));
+                assert_eq!(reason.as_deref(), Some("panic code 0x11"));
+            }
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, Address, BlockId, Signature, TransactionReceipt,
+};
+use ethers_providers::{FromErr, Middleware, PendingTransaction, ProviderError};
+use thiserror::Error;
+use tracing::{field, Instrument, Span};
+
+#[derive(Debug, Clone)]
+/// Middleware that opens a `tracing` span for every step of sending a transaction: filling,
+/// signing, sending and awaiting its receipt.
+///
+/// The spans are opened at the `DEBUG` level and record the sender, nonce, gas, gas price, chain
+/// id and hash of the transaction as fields once they are known, so traces show how long each
+/// step took and which middleware below it the time was spent in. Spans of the steps nest under
+/// the span of [`send_transaction`](Middleware::send_transaction).
+///
+/// Pending transactions are polled by the provider, outside of the middleware stack, await them
+/// with [`TracingMiddleware::confirm`] to trace the wait for the receipt.
+///
+/// Signing is only traced when [`sign_transaction`](Middleware::sign_transaction) is called on
+/// this middleware. A [`SignerMiddleware`](crate::SignerMiddleware) below it signs the
+/// transactions it sends itself, so in `TracingMiddleware<SignerMiddleware<_>>` the signing is
+/// part of the `send_transaction` span. Middlewares that sign through their inner middleware,
+/// like [`BroadcastMiddleware`](crate::broadcast::BroadcastMiddleware), get a `sign_transaction`
+/// span when placed above it, e.g. in
+/// `BroadcastMiddleware<TracingMiddleware<SignerMiddleware<_>>, _>`.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use ethers_middleware::tracing_spans::TracingMiddleware;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let client = TracingMiddleware::new(provider);
+///
+/// let tx = TransactionRequest::pay(Address::random(), 100).from(Address::random());
+/// let pending = client.send_transaction(tx, None).await?;
+/// let receipt = client.confirm(pending, 1).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TracingMiddleware<M> {
+    inner: M,
+}
+
+impl<M> TracingMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Wraps the middleware
+    pub fn new(inner: M) -> Self {
+        Self { inner }
+    }
+
+    /// Awaits the receipt of the pending transaction in an `await_receipt` span, which records
+    /// the block number, gas used and status of the receipt
+    pub async fn confirm<'a>(
+        &self,
+        pending: PendingTransaction<'a, M::Provider>,
+        confirmations: usize,
+    ) -> Result<Option<TransactionReceipt>, ProviderError> {
+        let span = tracing::debug_span!(
+            "await_receipt",
+            tx_hash = ?pending.tx_hash(),
+            confirmations,
+            block_number = field::Empty,
+            gas_used = field::Empty,
+            status = field::Empty,
+        );
+        let receipt = pending.confirmations(confirmations).instrument(span.clone()).await?;
+        if let Some(receipt) = &receipt {
+            if let Some(block_number) = receipt.block_number {
+                span.record("block_number", block_number.as_u64());
+            }
+            if let Some(gas_used) = receipt.gas_used {
+                span.record("gas_used", field::display(gas_used));
+            }
+            if let Some(status) = receipt.status {
+                span.record("status", status.as_u64());
+            }
+        }
+        Ok(receipt)
+    }
+}
+
+/// Records the fields of the transaction that are set on the span
+fn record_tx(span: &Span, tx: &TypedTransaction) {
+    if let Some(from) = tx.from() {
+        span.record("from", field::debug(from));
+    }
+    if let Some(nonce) = tx.nonce() {
+        span.record("nonce", field::display(nonce));
+    }
+    if let Some(gas) = tx.gas() {
+        span.record("gas", field::display(gas));
+    }
+    if let Some(gas_price) = tx.gas_price() {
+        span.record("gas_price", field::display(gas_price));
+    }
+    if let Some(chain_id) = tx.chain_id() {
+        span.record("chain_id", chain_id.as_u64());
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the tracing middleware interacts with the blockchain
+pub enum TracingMiddlewareError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+}
+
+impl<M: Middleware> FromErr<M::Error> for TracingMiddlewareError<M> {
+    fn from(src: M::Error) -> TracingMiddlewareError<M> {
+        TracingMiddlewareError::MiddlewareError(src)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for TracingMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = TracingMiddlewareError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        let span = tracing::debug_span!(
+            "fill_transaction",
+            from = field::Empty,
+            nonce = field::Empty,
+            gas = field::Empty,
+            gas_price = field::Empty,
+            chain_id = field::Empty,
+        );
+        let res = self.inner.fill_transaction(tx, block).instrument(span.clone()).await;
+        record_tx(&span, tx);
+        res.map_err(TracingMiddlewareError::MiddlewareError)
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &TypedTransaction,
+        from: Address,
+    ) -> Result<Signature, Self::Error> {
+        let span = tracing::debug_span!(
+            "sign_transaction",
+            from = ?from,
+            nonce = field::Empty,
+            gas = field::Empty,
+            gas_price = field::Empty,
+            chain_id = field::Empty,
+        );
+        record_tx(&span, tx);
+        self.inner
+            .sign_transaction(tx, from)
+            .instrument(span)
+            .await
+            .map_err(TracingMiddlewareError::MiddlewareError)
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let span = tracing::debug_span!(
+            "send_transaction",
+            from = field::Empty,
+            nonce = field::Empty,
+            gas = field::Empty,
+            gas_price = field::Empty,
+            chain_id = field::Empty,
+            tx_hash = field::Empty,
+        );
+        let mut tx = tx.into();
+        self.fill_transaction(&mut tx, block).instrument(span.clone()).await?;
+        record_tx(&span, &tx);
+
+        let pending = self
+            .inner
+            .send_transaction(tx, block)
+            .instrument(span.clone())
+            .await
+            .map_err(TracingMiddlewareError::MiddlewareError)?;
+        span.record("tx_hash", field::debug(pending.tx_hash()));
+        Ok(pending)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{broadcast::BroadcastMiddleware, SignerMiddleware};
+    use ethers_core::types::{TransactionRequest, H256, U64};
+    use ethers_providers::{MockProvider, Provider};
+    use ethers_signers::{LocalWallet, Signer};
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    /// Collects the recorded fields of the spans by span name
+    #[derive(Clone, Default)]
+    struct Spans(Arc<Mutex<BTreeMap<String, BTreeMap<String, String>>>>);
+
+    struct Fields<'a>(&'a mut BTreeMap<String, String>);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    impl<S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>> Layer<S> for Spans {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            let mut spans = self.0.lock().unwrap();
+            attrs
+                .record(&mut Fields(spans.entry(attrs.metadata().name().to_string()).or_default()));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let name = ctx.span(id).unwrap().name().to_string();
+            values.record(&mut Fields(self.0.lock().unwrap().entry(name).or_default()));
+        }
+    }
+
+    #[tokio::test]
+    async fn records_transaction_fields() {
+        let spans = Spans::default();
+        let _guard = tracing_subscriber::registry().with(spans.clone()).set_default();
+
+        let (provider, mock) = Provider::mocked();
+        let client = TracingMiddleware::new(provider);
+        let hash = H256::repeat_byte(1);
+        mock.push(hash).unwrap();
+
+        let tx = TransactionRequest::pay(Address::zero(), 1)
+            .from(Address::repeat_byte(2))
+            .nonce(7)
+            .gas(21_000)
+            .gas_price(3)
+            .chain_id(U64::from(5));
+        let pending = client.send_transaction(tx, None).await.unwrap();
+        assert_eq!(pending.tx_hash(), hash);
+
+        let spans = spans.0.lock().unwrap();
+        let send = &spans["send_transaction"];
+        assert_eq!(send["nonce"], "7");
+        assert_eq!(send["gas"], "21000");
+        assert_eq!(send["gas_price"], "3");
+        assert_eq!(send["chain_id"], "5");
+        assert_eq!(send["tx_hash"], format!("{hash:?}"));
+        assert_eq!(spans["fill_transaction"]["from"], format!("{:?}", Address::repeat_byte(2)));
+    }
+
+    #[tokio::test]
+    async fn records_signing_below_broadcasts() {
+        let spans = Spans::default();
+        let _guard = tracing_subscriber::registry().with(spans.clone()).set_default();
+
+        let (provider, mock) = Provider::mocked();
+        mock.push(H256::repeat_byte(1)).unwrap();
+        let wallet: LocalWallet =
+            "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc".parse().unwrap();
+        let wallet = wallet.with_chain_id(5u64);
+        let from = wallet.address();
+        let client = BroadcastMiddleware::<_, MockProvider>::new(TracingMiddleware::new(
+            SignerMiddleware::new(provider, wallet),
+        ));
+
+        let tx = TransactionRequest::pay(Address::zero(), 1)
+            .nonce(7)
+            .gas(21_000)
+            .gas_price(3)
+            .chain_id(U64::from(5));
+        client.send_transaction(tx, None).await.unwrap();
+
+        let spans = spans.0.lock().unwrap();
+        let sign = &spans["sign_transaction"];
+        assert_eq!(sign["from"], format!("{from:?}"));
+        assert_eq!(sign["nonce"], "7");
+    }
+}
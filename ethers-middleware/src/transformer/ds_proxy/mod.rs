@@ -2,6 +2,7 @@ mod factory;
 use factory::{CreatedFilter, DsProxyFactory, ADDRESS_BOOK};
 
 use super::{Transformer, TransformerError};
+use async_trait::async_trait;
 use ethers_contract::{builders::ContractCall, BaseContract, ContractError};
 use ethers_core::{
     abi::parse_abi,
@@ -174,8 +175,10 @@ impl DsProxy {
     }
 }
 
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 impl Transformer for DsProxy {
-    fn transform(&self, tx: &mut TypedTransaction) -> Result<(), TransformerError> {
+    async fn transform(&self, tx: &mut TypedTransaction) -> Result<(), TransformerError> {
         // the target address cannot be None.
         let target =
             *tx.to_addr().ok_or_else(|| TransformerError::MissingField("to".to_string()))?;
@@ -0,0 +1,355 @@
+use super::{
+    meta_tx::{self, LocalNonce},
+    Transformer, TransformerError,
+};
+use async_trait::async_trait;
+use ethers_contract::{BaseContract, ContractError};
+use ethers_core::{
+    abi::parse_abi,
+    types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip712::{EIP712Domain, Eip712, TypedData},
+        },
+        *,
+    },
+};
+use ethers_providers::Middleware;
+use ethers_signers::{LocalWallet, Signer};
+use serde_json::json;
+
+/// The functions of the forwarder contract that are called.
+const FORWARDER_ABI: &[&str] = &[
+    "function getNonce(address from) public view returns (uint256)",
+    "struct ForwardRequest { address from; address to; uint256 value; uint256 gas; uint256 nonce; bytes data; }",
+    "function execute(ForwardRequest calldata req, bytes calldata signature) public payable returns (bool, bytes memory)",
+];
+
+/// The fields of the EIP-712 `ForwardRequest` type.
+const FORWARD_REQUEST_FIELDS: &[(&str, &str)] = &[
+    ("from", "address"),
+    ("to", "address"),
+    ("value", "uint256"),
+    ("gas", "uint256"),
+    ("nonce", "uint256"),
+    ("data", "bytes"),
+];
+
+/// The gas forwarded to the recipient if the transaction doesn't set a gas limit
+pub const DEFAULT_REQUEST_GAS: u64 = 1_000_000;
+
+/// A call forwarded to its recipient by a trusted forwarder, signed by its sender.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardRequest {
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    /// The gas the forwarder calls the recipient with
+    pub gas: U256,
+    pub nonce: U256,
+    pub data: Bytes,
+}
+
+impl ForwardRequest {
+    /// Returns the EIP-712 typed data of the request for the forwarder at `forwarder`, whose
+    /// domain has the `name` and `version`
+    pub fn typed_data(
+        &self,
+        name: &str,
+        version: &str,
+        chain_id: u64,
+        forwarder: Address,
+    ) -> Result<TypedData, TransformerError> {
+        let domain = EIP712Domain {
+            name: Some(name.to_string()),
+            version: Some(version.to_string()),
+            chain_id: Some(chain_id.into()),
+            verifying_contract: Some(forwarder),
+            salt: None,
+        };
+        meta_tx::typed_data(
+            domain,
+            "ForwardRequest",
+            FORWARD_REQUEST_FIELDS,
+            json!({
+                "from": self.from,
+                "to": self.to,
+                "value": self.value,
+                "gas": self.gas,
+                "nonce": self.nonce,
+                "data": self.data,
+            }),
+        )
+    }
+
+    /// Returns the EIP-712 hash of the request for the forwarder at `forwarder`, whose domain has
+    /// the `name` and `version`, which its sender signs
+    pub fn hash(
+        &self,
+        name: &str,
+        version: &str,
+        chain_id: u64,
+        forwarder: Address,
+    ) -> Result<H256, TransformerError> {
+        Ok(self.typed_data(name, version, chain_id, forwarder)?.encode_eip712()?.into())
+    }
+
+    /// Returns the calldata the forwarder calls the recipient with, the data of the request with
+    /// the sender appended, which EIP-2771 recipients read as the `_msgSender()`
+    pub fn forwarded_data(&self) -> Bytes {
+        let mut data = self.data.to_vec();
+        data.extend_from_slice(self.from.as_bytes());
+        data.into()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Represents an [EIP-2771](https://eips.ethereum.org/EIPS/eip-2771) trusted forwarder, like
+/// OpenZeppelin's `MinimalForwarder`, that implements the [Transformer](super::Transformer)
+/// trait, enabling gasless transactions.
+///
+/// Transactions are turned into forward requests of the `signer`, which it signs with EIP-712,
+/// and wrapped into `execute` calls of the forwarder. The forwarder verifies the signature and
+/// calls the recipient with the data of the request and the address of the signer appended, which
+/// recipients that trust the forwarder use as the sender. The wrapping transaction is sent, and
+/// paid for, by the client of the [`TransformerMiddleware`](super::TransformerMiddleware), the
+/// relayer. The gas limit of a transaction, if it is set, becomes the gas of the request, and the
+/// wrapping transaction is estimated again.
+///
+/// Requests use the nonces the forwarder keeps for the signer, see [`Transformer::resync`] for how
+/// they are kept in sync.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::{
+///     transformer::{Forwarder, TransformerMiddleware},
+///     SignerMiddleware,
+/// };
+/// use ethers_signers::LocalWallet;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let relayer: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let client = SignerMiddleware::new_with_provider_chain(provider, relayer).await?;
+///
+/// # let forwarder_address = Address::random();
+/// # let recipient = Address::random();
+/// let user = LocalWallet::new(&mut rand::thread_rng());
+/// let forwarder = Forwarder::connect(&client, forwarder_address, user).await?;
+/// let client = TransformerMiddleware::new(client, forwarder);
+///
+/// // calls the recipient on behalf of the user, the relayer pays for the gas
+/// let tx = TransactionRequest::new().to(recipient).data(vec![0x12, 0x34]);
+/// let _tx_receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Forwarder<S = LocalWallet> {
+    address: Address,
+    chain_id: u64,
+    name: String,
+    version: String,
+    nonce: LocalNonce,
+    signer: S,
+    contract: BaseContract,
+}
+
+impl<S: Signer> Forwarder<S> {
+    /// Creates a new instance of the forwarder at `address` on the chain, whose next request is
+    /// signed by the `signer` with the `nonce`, for the domain of OpenZeppelin's
+    /// `MinimalForwarder`
+    pub fn new(address: Address, chain_id: u64, nonce: u64, signer: S) -> Self {
+        let contract = parse_abi(FORWARDER_ABI).expect("could not parse ABI").into();
+        Self {
+            address,
+            chain_id,
+            name: "MinimalForwarder".to_string(),
+            version: "0.0.1".to_string(),
+            nonce: LocalNonce::new(nonce),
+            signer,
+            contract,
+        }
+    }
+
+    /// Creates a new instance of the forwarder at `address`, fetching the chain id and the nonce
+    /// of the signer with the client
+    pub async fn connect<M: Middleware>(
+        client: &M,
+        address: Address,
+        signer: S,
+    ) -> Result<Self, ContractError<M>> {
+        let chain_id = client.get_chainid().await.map_err(ContractError::MiddlewareError)?;
+        let forwarder = Self::new(address, chain_id.as_u64(), 0, signer);
+        forwarder.resync(client).await?;
+        Ok(forwarder)
+    }
+
+    /// Sets the name and version of the EIP-712 domain of the forwarder.
+    #[must_use]
+    pub fn domain(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.name = name.into();
+        self.version = version.into();
+        self
+    }
+
+    /// The address of the forwarder.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// The nonce of the next request of the signer.
+    pub fn nonce(&self) -> u64 {
+        self.nonce.get()
+    }
+
+    /// Sets the nonce of the next request of the signer.
+    pub fn set_nonce(&self, nonce: u64) {
+        self.nonce.set(nonce);
+    }
+
+    /// Returns the EIP-712 signature of the request by the signer
+    pub async fn sign(&self, req: &ForwardRequest) -> Result<Bytes, TransformerError> {
+        let typed_data = req.typed_data(&self.name, &self.version, self.chain_id, self.address)?;
+        meta_tx::sign(&self.signer, &typed_data).await
+    }
+
+    /// Returns the calldata of the `execute` call that forwards the signed request
+    pub async fn execute(&self, req: &ForwardRequest) -> Result<Bytes, TransformerError> {
+        let args = (
+            (req.from, req.to, req.value, req.gas, req.nonce, req.data.clone()),
+            self.sign(req).await?,
+        );
+        Ok(self.contract.encode("execute", args)?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S: Signer> Transformer for Forwarder<S> {
+    async fn transform(&self, tx: &mut TypedTransaction) -> Result<(), TransformerError> {
+        // the target address cannot be None.
+        let to = *tx.to_addr().ok_or_else(|| TransformerError::MissingField("to".to_string()))?;
+
+        let req = ForwardRequest {
+            from: self.signer.address(),
+            to,
+            value: tx.value().copied().unwrap_or_default(),
+            gas: tx.gas().copied().unwrap_or_else(|| DEFAULT_REQUEST_GAS.into()),
+            nonce: self.nonce.next().into(),
+            data: tx.data().cloned().unwrap_or_default(),
+        };
+
+        // the relayer sends the value with the request and pays for the forwarding
+        tx.set_data(self.execute(&req).await?);
+        tx.set_to(self.address);
+        *tx.gas_mut() = None;
+
+        Ok(())
+    }
+
+    async fn resync<M: Middleware>(&self, client: &M) -> Result<(), ContractError<M>> {
+        let from = self.signer.address();
+        self.nonce.fetch(client, &self.contract, self.address, "getNonce", from).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_forward_requests() {
+        let forwarder = Address::repeat_byte(0x11);
+        let req = ForwardRequest {
+            from: Address::repeat_byte(0x33),
+            to: Address::repeat_byte(0x22),
+            value: 100.into(),
+            gas: 50_000.into(),
+            nonce: 7.into(),
+            data: vec![1, 2, 3].into(),
+        };
+
+        let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "ForwardRequest": [
+                    { "name": "from", "type": "address" },
+                    { "name": "to", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "gas", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "data", "type": "bytes" }
+                ]
+            },
+            "primaryType": "ForwardRequest",
+            "domain": {
+                "name": "MinimalForwarder",
+                "version": "0.0.1",
+                "chainId": 5,
+                "verifyingContract": forwarder
+            },
+            "message": {
+                "from": req.from,
+                "to": req.to,
+                "value": "100",
+                "gas": "50000",
+                "nonce": "7",
+                "data": "0x010203"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            req.hash("MinimalForwarder", "0.0.1", 5, forwarder).unwrap(),
+            H256::from(typed_data.encode_eip712().unwrap())
+        );
+
+        let mut forwarded = vec![1, 2, 3];
+        forwarded.extend_from_slice(req.from.as_bytes());
+        assert_eq!(req.forwarded_data(), Bytes::from(forwarded));
+    }
+
+    #[tokio::test]
+    async fn wraps_transactions() {
+        let signer = LocalWallet::new(&mut rand::thread_rng());
+        let forwarder = Forwarder::new(Address::repeat_byte(0x11), 1, 7, signer.clone());
+
+        let to = Address::repeat_byte(0x22);
+        let mut tx: TypedTransaction =
+            TransactionRequest::new().to(to).data(vec![1, 2, 3]).gas(50_000).into();
+        forwarder.transform(&mut tx).await.unwrap();
+        assert_eq!(tx.to_addr(), Some(&forwarder.address()));
+        assert_eq!(tx.gas(), None);
+        assert_eq!(forwarder.nonce(), 8);
+
+        let req = ForwardRequest {
+            from: signer.address(),
+            to,
+            gas: 50_000.into(),
+            nonce: 7.into(),
+            data: vec![1, 2, 3].into(),
+            ..Default::default()
+        };
+        assert_eq!(tx.data(), Some(&forwarder.execute(&req).await.unwrap()));
+        assert_eq!(
+            tx.data().unwrap()[..4],
+            ethers_core::utils::id(
+                "execute((address,address,uint256,uint256,uint256,bytes),bytes)"
+            )
+        );
+
+        let hash = req.hash("MinimalForwarder", "0.0.1", 1, forwarder.address()).unwrap();
+        let signature = Signature::try_from(forwarder.sign(&req).await.unwrap().as_ref()).unwrap();
+        assert_eq!(signature.recover(hash).unwrap(), signer.address());
+    }
+}
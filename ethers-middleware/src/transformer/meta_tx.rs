@@ -0,0 +1,103 @@
+//! Helpers of the transformers that wrap transactions into EIP-712 signed messages, which a
+//! contract verifies and executes
+
+use super::TransformerError;
+use ethers_contract::{BaseContract, ContractError};
+use ethers_core::{
+    abi::{InvalidOutputType, Tokenize},
+    types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip712::{EIP712Domain, Eip712DomainType, Eip712Error, TypedData},
+        },
+        Address, Bytes, TransactionRequest, U256,
+    },
+};
+use ethers_providers::Middleware;
+use ethers_signers::Signer;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// A nonce of the contract, counted locally and shared by the clones of a transformer
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LocalNonce(Arc<AtomicU64>);
+
+impl LocalNonce {
+    pub fn new(nonce: u64) -> Self {
+        Self(Arc::new(AtomicU64::new(nonce)))
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set(&self, nonce: u64) {
+        self.0.store(nonce, Ordering::SeqCst);
+    }
+
+    /// Returns the nonce and counts it as used
+    pub fn next(&self) -> u64 {
+        self.0.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Sets the nonce to the one returned by the view `function` of the contract at `address`
+    pub async fn fetch<M: Middleware, T: Tokenize>(
+        &self,
+        client: &M,
+        contract: &BaseContract,
+        address: Address,
+        function: &str,
+        args: T,
+    ) -> Result<(), ContractError<M>> {
+        let tx: TypedTransaction =
+            TransactionRequest::new().to(address).data(contract.encode(function, args)?).into();
+        let output = client.call(&tx, None).await.map_err(ContractError::MiddlewareError)?;
+        let nonce: U256 = contract.decode_output(function, output)?;
+        if nonce > U256::from(u64::MAX) {
+            return Err(InvalidOutputType(format!("nonce {nonce} does not fit into a u64")).into())
+        }
+        self.set(nonce.as_u64());
+        Ok(())
+    }
+}
+
+/// Returns the typed data of a message of the `primary_type`, whose `fields` are `(name, type)`
+/// pairs in the order of the type
+pub(crate) fn typed_data(
+    domain: EIP712Domain,
+    primary_type: &str,
+    fields: &[(&str, &str)],
+    message: serde_json::Value,
+) -> Result<TypedData, TransformerError> {
+    let fields = fields
+        .iter()
+        .map(|(name, r#type)| Eip712DomainType {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+        })
+        .collect();
+    let message = match message {
+        serde_json::Value::Object(message) => message.into_iter().collect(),
+        message => return Err(Eip712Error::Message(format!("{message} is not an object")).into()),
+    };
+    Ok(TypedData {
+        domain,
+        types: [(primary_type.to_string(), fields)].into_iter().collect(),
+        primary_type: primary_type.to_string(),
+        message,
+    })
+}
+
+/// Signs the typed data, returning the signature as `r ‖ s ‖ v`
+pub(crate) async fn sign<S: Signer>(
+    signer: &S,
+    typed_data: &TypedData,
+) -> Result<Bytes, TransformerError> {
+    let signature = signer
+        .sign_typed_data(typed_data)
+        .await
+        .map_err(|err| TransformerError::SignerError(err.to_string()))?;
+    Ok(signature.to_vec().into())
+}
@@ -61,14 +61,23 @@ where
     ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
         let mut tx = tx.into();
 
-        // construct the appropriate proxy tx.
-        self.transformer.transform(&mut tx)?;
+        let res = async {
+            // construct the appropriate proxy tx.
+            self.transformer.transform(&mut tx).await?;
 
-        self.fill_transaction(&mut tx, block).await?;
-        // send the proxy tx.
-        self.inner
-            .send_transaction(tx, block)
-            .await
-            .map_err(TransformerMiddlewareError::MiddlewareError)
+            self.fill_transaction(&mut tx, block).await?;
+            // send the proxy tx.
+            self.inner
+                .send_transaction(tx, block)
+                .await
+                .map_err(TransformerMiddlewareError::MiddlewareError)
+        }
+        .await;
+        if res.is_err() {
+            if let Err(err) = self.transformer.resync(&self.inner).await {
+                tracing::warn!(%err, "failed to resync the transformer");
+            }
+        }
+        res
     }
 }
@@ -1,11 +1,24 @@
 mod ds_proxy;
 pub use ds_proxy::DsProxy;
 
+mod safe;
+pub use safe::{GnosisSafe, SafeTransaction};
+
+mod meta_tx;
+
+mod forwarder;
+pub use forwarder::{ForwardRequest, Forwarder, DEFAULT_REQUEST_GAS};
+
 mod middleware;
 pub use middleware::TransformerMiddleware;
 
-use ethers_contract::AbiError;
-use ethers_core::{abi::ParseError, types::transaction::eip2718::TypedTransaction};
+use async_trait::async_trait;
+use ethers_contract::{AbiError, ContractError};
+use ethers_core::{
+    abi::ParseError,
+    types::transaction::{eip2718::TypedTransaction, eip712::Eip712Error},
+};
+use ethers_providers::Middleware;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,15 +32,34 @@ pub enum TransformerError {
 
     #[error(transparent)]
     AbiError(#[from] AbiError),
+
+    #[error("signer error: {0}")]
+    SignerError(String),
+
+    #[error(transparent)]
+    Eip712Error(#[from] Eip712Error),
 }
 
 /// `Transformer` is a trait to be implemented by a proxy wallet, eg. [`DsProxy`], that intends to
 /// intercept a transaction request and transform it into one that is instead sent via the proxy
 /// contract.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait Transformer: Send + Sync + std::fmt::Debug {
     /// Transforms a [`transaction request`] into one that can be broadcasted and execute via the
     /// proxy contract.
     ///
     /// [`transaction request`]: struct@ethers_core::types::TransactionRequest
-    fn transform(&self, tx: &mut TypedTransaction) -> Result<(), TransformerError>;
+    async fn transform(&self, tx: &mut TypedTransaction) -> Result<(), TransformerError>;
+
+    /// Called by the [`TransformerMiddleware`] when a transformed transaction could not be sent.
+    ///
+    /// Transformers of signed meta-transactions, like [`GnosisSafe`] and [`Forwarder`], count the
+    /// nonces of their signers locally, starting at the nonce fetched from the contract when they
+    /// connect, so transactions are transformed without a request. The nonce of a transaction
+    /// that wasn't sent is unused, so they fetch the nonce from the contract again. Does nothing
+    /// by default.
+    async fn resync<M: Middleware>(&self, _client: &M) -> Result<(), ContractError<M>> {
+        Ok(())
+    }
 }
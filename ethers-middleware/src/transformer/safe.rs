@@ -0,0 +1,358 @@
+use super::{
+    meta_tx::{self, LocalNonce},
+    Transformer, TransformerError,
+};
+use async_trait::async_trait;
+use ethers_contract::{BaseContract, ContractError};
+use ethers_core::{
+    abi::parse_abi,
+    types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip712::{EIP712Domain, Eip712, TypedData},
+        },
+        *,
+    },
+};
+use ethers_providers::Middleware;
+use ethers_signers::{LocalWallet, Signer};
+use futures_util::future::try_join_all;
+use serde_json::json;
+
+/// The functions of the Safe contract that are called.
+const SAFE_ABI: &[&str] = &[
+    "function nonce() public view returns (uint256)",
+    "function execTransaction(address to, uint256 value, bytes calldata data, uint8 operation, uint256 safeTxGas, uint256 baseGas, uint256 gasPrice, address gasToken, address refundReceiver, bytes memory signatures) public payable returns (bool success)",
+];
+
+/// The fields of the EIP-712 `SafeTx` type.
+const SAFE_TX_FIELDS: &[(&str, &str)] = &[
+    ("to", "address"),
+    ("value", "uint256"),
+    ("data", "bytes"),
+    ("operation", "uint8"),
+    ("safeTxGas", "uint256"),
+    ("baseGas", "uint256"),
+    ("gasPrice", "uint256"),
+    ("gasToken", "address"),
+    ("refundReceiver", "address"),
+    ("nonce", "uint256"),
+];
+
+/// A transaction executed by a Safe, signed by its owners.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SafeTransaction {
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    /// `0` for calls, `1` for delegate calls
+    pub operation: u8,
+    pub safe_tx_gas: U256,
+    pub base_gas: U256,
+    pub gas_price: U256,
+    pub gas_token: Address,
+    pub refund_receiver: Address,
+    pub nonce: U256,
+}
+
+impl SafeTransaction {
+    /// Returns the EIP-712 typed data of the transaction for the Safe at `safe`, whose domain has
+    /// no name and version as of Safe 1.3.0
+    pub fn typed_data(&self, chain_id: u64, safe: Address) -> Result<TypedData, TransformerError> {
+        let domain = EIP712Domain {
+            chain_id: Some(chain_id.into()),
+            verifying_contract: Some(safe),
+            ..Default::default()
+        };
+        meta_tx::typed_data(
+            domain,
+            "SafeTx",
+            SAFE_TX_FIELDS,
+            json!({
+                "to": self.to,
+                "value": self.value,
+                "data": self.data,
+                "operation": self.operation,
+                "safeTxGas": self.safe_tx_gas,
+                "baseGas": self.base_gas,
+                "gasPrice": self.gas_price,
+                "gasToken": self.gas_token,
+                "refundReceiver": self.refund_receiver,
+                "nonce": self.nonce,
+            }),
+        )
+    }
+
+    /// Returns the EIP-712 hash of the transaction for the Safe at `safe`, which its owners sign
+    pub fn hash(&self, chain_id: u64, safe: Address) -> Result<H256, TransformerError> {
+        Ok(self.typed_data(chain_id, safe)?.encode_eip712()?.into())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Represents a [Safe](https://github.com/safe-global/safe-contracts) (formerly Gnosis Safe) that
+/// implements the [Transformer](super::Transformer) trait, for Safe versions 1.3.0 and above.
+///
+/// Transactions are wrapped into `execTransaction` calls of the Safe, which sends the value and
+/// calldata of the original transaction from its own balance. The owners sign the Safe
+/// transaction with EIP-712, so they must reach the threshold of the Safe. The transaction can be
+/// sent by any account that pays for the gas.
+///
+/// Each transaction uses the next nonce of the Safe, see [`Transformer::resync`] for how it is
+/// kept in sync.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_middleware::{
+///     transformer::{GnosisSafe, TransformerMiddleware},
+///     SignerMiddleware,
+/// };
+/// use ethers_signers::LocalWallet;
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_core::types::{Address, TransactionRequest};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let owner: LocalWallet = "380eb0f3d505f087e438eca80bc4df9a7faa24f868e69fc0440261a0fc0567dc"
+///     .parse()?;
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let client = SignerMiddleware::new_with_provider_chain(provider, owner.clone()).await?;
+///
+/// # let safe_address = Address::random();
+/// let safe = GnosisSafe::connect(&client, safe_address, vec![owner]).await?;
+/// let client = TransformerMiddleware::new(client, safe);
+///
+/// // pays 100 wei from the balance of the Safe
+/// let tx = TransactionRequest::pay(Address::random(), 100);
+/// let _tx_receipt = client.send_transaction(tx, None).await?.await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct GnosisSafe<S = LocalWallet> {
+    address: Address,
+    chain_id: u64,
+    nonce: LocalNonce,
+    /// The owners that sign, ordered by address as the Safe expects their signatures
+    owners: Vec<S>,
+    contract: BaseContract,
+}
+
+impl<S: Signer> GnosisSafe<S> {
+    /// Creates a new instance of the Safe at `address` on the chain, whose next transaction is
+    /// signed by the `owners` with the `nonce`
+    pub fn new(
+        address: Address,
+        chain_id: u64,
+        nonce: u64,
+        owners: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let mut owners: Vec<_> = owners.into_iter().collect();
+        owners.sort_by_key(|owner| owner.address());
+        let contract = parse_abi(SAFE_ABI).expect("could not parse ABI").into();
+        Self { address, chain_id, nonce: LocalNonce::new(nonce), owners, contract }
+    }
+
+    /// Creates a new instance of the Safe at `address`, fetching the chain id and the nonce of
+    /// the Safe with the client
+    pub async fn connect<M: Middleware>(
+        client: &M,
+        address: Address,
+        owners: impl IntoIterator<Item = S>,
+    ) -> Result<Self, ContractError<M>> {
+        let chain_id = client.get_chainid().await.map_err(ContractError::MiddlewareError)?;
+        let safe = Self::new(address, chain_id.as_u64(), 0, owners);
+        safe.resync(client).await?;
+        Ok(safe)
+    }
+
+    /// The address of the Safe.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// The nonce of the next transaction of the Safe.
+    pub fn nonce(&self) -> u64 {
+        self.nonce.get()
+    }
+
+    /// Sets the nonce of the next transaction of the Safe.
+    pub fn set_nonce(&self, nonce: u64) {
+        self.nonce.set(nonce);
+    }
+
+    /// Returns the signatures of the transaction by the owners, in the encoding of the Safe
+    pub async fn sign(&self, tx: &SafeTransaction) -> Result<Bytes, TransformerError> {
+        let typed_data = tx.typed_data(self.chain_id, self.address)?;
+        let signatures =
+            try_join_all(self.owners.iter().map(|owner| meta_tx::sign(owner, &typed_data))).await?;
+        Ok(signatures.concat().into())
+    }
+
+    /// Returns the calldata of the `execTransaction` call that executes the signed transaction
+    pub async fn exec_transaction(&self, tx: &SafeTransaction) -> Result<Bytes, TransformerError> {
+        let args = (
+            tx.to,
+            tx.value,
+            tx.data.clone(),
+            tx.operation,
+            tx.safe_tx_gas,
+            tx.base_gas,
+            tx.gas_price,
+            tx.gas_token,
+            tx.refund_receiver,
+            self.sign(tx).await?,
+        );
+        Ok(self.contract.encode("execTransaction", args)?)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S: Signer> Transformer for GnosisSafe<S> {
+    async fn transform(&self, tx: &mut TypedTransaction) -> Result<(), TransformerError> {
+        // the target address cannot be None.
+        let to = *tx.to_addr().ok_or_else(|| TransformerError::MissingField("to".to_string()))?;
+
+        let safe_tx = SafeTransaction {
+            to,
+            value: tx.value().copied().unwrap_or_default(),
+            data: tx.data().cloned().unwrap_or_default(),
+            nonce: self.nonce.next().into(),
+            ..Default::default()
+        };
+
+        // update appropriate fields of the Safe tx, the value is paid by the Safe.
+        tx.set_data(self.exec_transaction(&safe_tx).await?);
+        tx.set_to(self.address);
+        tx.set_value(0);
+
+        Ok(())
+    }
+
+    async fn resync<M: Middleware>(&self, client: &M) -> Result<(), ContractError<M>> {
+        self.nonce.fetch(client, &self.contract, self.address, "nonce", ()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transformer::TransformerMiddleware;
+    use ethers_core::abi::{encode, Token};
+    use ethers_providers::{JsonRpcError, Provider};
+
+    #[test]
+    fn hashes_safe_transactions() {
+        let safe = Address::repeat_byte(0x11);
+        let tx = SafeTransaction {
+            to: Address::repeat_byte(0x22),
+            value: 100.into(),
+            data: vec![1, 2, 3].into(),
+            nonce: 7.into(),
+            ..Default::default()
+        };
+
+        let typed_data: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "SafeTx": [
+                    { "name": "to", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "data", "type": "bytes" },
+                    { "name": "operation", "type": "uint8" },
+                    { "name": "safeTxGas", "type": "uint256" },
+                    { "name": "baseGas", "type": "uint256" },
+                    { "name": "gasPrice", "type": "uint256" },
+                    { "name": "gasToken", "type": "address" },
+                    { "name": "refundReceiver", "type": "address" },
+                    { "name": "nonce", "type": "uint256" }
+                ]
+            },
+            "primaryType": "SafeTx",
+            "domain": { "chainId": 5, "verifyingContract": safe },
+            "message": {
+                "to": tx.to,
+                "value": "100",
+                "data": "0x010203",
+                "operation": 0,
+                "safeTxGas": "0",
+                "baseGas": "0",
+                "gasPrice": "0",
+                "gasToken": Address::zero(),
+                "refundReceiver": Address::zero(),
+                "nonce": "7"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(tx.hash(5, safe).unwrap(), H256::from(typed_data.encode_eip712().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn wraps_transactions() {
+        let owners: Vec<LocalWallet> =
+            (0..3).map(|_| LocalWallet::new(&mut rand::thread_rng())).collect();
+        let safe = GnosisSafe::new(Address::repeat_byte(0x11), 1, 7, owners.clone());
+
+        let to = Address::repeat_byte(0x22);
+        let mut tx: TypedTransaction = TransactionRequest::pay(to, 100).data(vec![1, 2, 3]).into();
+        safe.transform(&mut tx).await.unwrap();
+        assert_eq!(tx.to_addr(), Some(&safe.address()));
+        assert_eq!(tx.value(), Some(&U256::zero()));
+        assert_eq!(safe.nonce(), 8);
+
+        let safe_tx = SafeTransaction {
+            to,
+            value: 100.into(),
+            data: vec![1, 2, 3].into(),
+            nonce: 7.into(),
+            ..Default::default()
+        };
+        assert_eq!(tx.data(), Some(&safe.exec_transaction(&safe_tx).await.unwrap()));
+
+        // the signatures are ordered by owner
+        let hash = safe_tx.hash(1, safe.address()).unwrap();
+        let signatures = safe.sign(&safe_tx).await.unwrap();
+        let signers: Vec<_> = signatures
+            .chunks(65)
+            .map(|signature| Signature::try_from(signature).unwrap().recover(hash).unwrap())
+            .collect();
+        let mut expected: Vec<_> = owners.iter().map(|owner| owner.address()).collect();
+        expected.sort();
+        assert_eq!(signers, expected);
+    }
+
+    #[tokio::test]
+    async fn resyncs_nonce_of_unsent_transactions() {
+        let (provider, mock) = Provider::mocked();
+        let owner = LocalWallet::new(&mut rand::thread_rng());
+        let safe = GnosisSafe::new(Address::repeat_byte(0x11), 1, 7, vec![owner]);
+        let client = TransformerMiddleware::new(provider, safe.clone());
+
+        // sending fails, and the nonce is fetched from the Safe again
+        mock.push::<Bytes, Bytes>(encode(&[Token::Uint(7.into())]).into()).unwrap();
+        mock.push_error(JsonRpcError { code: -32000, message: "underpriced".into(), data: None });
+        let tx = TransactionRequest::pay(Address::repeat_byte(0x22), 100)
+            .from(Address::zero())
+            .gas(100_000)
+            .gas_price(1);
+        client.send_transaction(tx, None).await.unwrap_err();
+        assert_eq!(safe.nonce(), 7);
+    }
+
+    #[tokio::test]
+    async fn rejects_nonces_above_u64() {
+        let (provider, mock) = Provider::mocked();
+        let owner = LocalWallet::new(&mut rand::thread_rng());
+        let safe = GnosisSafe::new(Address::repeat_byte(0x11), 1, 7, vec![owner]);
+
+        mock.push::<Bytes, Bytes>(encode(&[Token::Uint(U256::MAX)]).into()).unwrap();
+        let err = safe.resync(&provider).await.unwrap_err();
+        assert!(matches!(err, ContractError::DetokenizationError(_)), "{err}");
+        assert_eq!(safe.nonce(), 7);
+    }
+}
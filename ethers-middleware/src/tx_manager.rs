@@ -0,0 +1,596 @@
+use crate::gas_escalator::{fill_with_nonce, Fees, Frequency, MIN_REPLACEMENT_BUMP};
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::eip2718::TypedTransaction, BlockId, BlockNumber, TxHash, U256, U64,
+};
+use ethers_providers::{interval, FromErr, Middleware, PendingTransaction, StreamExt};
+use instant::Instant;
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+
+#[cfg(target_arch = "wasm32")]
+type WatcherFuture<'a> = Pin<Box<dyn futures_util::stream::Stream<Item = ()> + 'a>>;
+#[cfg(not(target_arch = "wasm32"))]
+type WatcherFuture<'a> = Pin<Box<dyn futures_util::stream::Stream<Item = ()> + Send + 'a>>;
+
+/// The state of a transaction tracked by the [`TxManagerMiddleware`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxState {
+    /// Broadcast and waiting to be mined
+    Pending,
+    /// Pending for longer than the policy allows and it can't be replaced anymore, either
+    /// because it was replaced as often as allowed or the fees reached their cap
+    Stuck,
+    /// Unknown to the node and it couldn't be rebroadcast
+    Dropped,
+    /// Mined, with the hash of the mined broadcast and whether it succeeded
+    Mined { hash: TxHash, block_number: Option<U64>, success: bool },
+    /// Another transaction with the same nonce was mined
+    Superseded,
+}
+
+impl TxState {
+    /// Returns whether the state can't change anymore, i.e. the nonce of the transaction was used
+    pub fn is_final(&self) -> bool {
+        matches!(self, TxState::Mined { .. } | TxState::Superseded)
+    }
+}
+
+/// A transaction tracked by the [`TxManagerMiddleware`], along with its rebroadcasts and
+/// replacements
+#[derive(Debug, Clone)]
+pub struct TrackedTransaction {
+    /// The last broadcast version of the transaction
+    pub tx: TypedTransaction,
+    /// The hashes of the transaction and of its replacements, in order of broadcast
+    pub hashes: Vec<TxHash>,
+    /// The state of the transaction as of the last check
+    pub state: TxState,
+    /// The block the transaction was sent with, which rebroadcasts and replacements are sent
+    /// with as well
+    pub block: Option<BlockId>,
+    /// When the transaction was first broadcast
+    pub sent_at: Instant,
+    /// When the transaction was last broadcast
+    pub last_broadcast: Instant,
+    /// How often the transaction was rebroadcast after it was dropped
+    pub rebroadcasts: usize,
+    /// How often the transaction was replaced with higher fees
+    pub replacements: usize,
+    /// The error of the last failed rebroadcast or replacement
+    pub last_error: Option<String>,
+}
+
+impl TrackedTransaction {
+    /// Returns the hash of the last broadcast
+    pub fn hash(&self) -> TxHash {
+        *self.hashes.last().expect("tracked transactions were broadcast")
+    }
+}
+
+/// How the [`TxManagerMiddleware`] handles stuck and dropped transactions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxPolicy {
+    /// How long a transaction may be pending since its last broadcast before it's replaced
+    pub stuck_after: Duration,
+    /// The increase of the fees of replacements in percent, at least [`MIN_REPLACEMENT_BUMP`]
+    pub fee_bump: u64,
+    /// How often a transaction is replaced before it's considered stuck
+    pub max_replacements: usize,
+    /// The cap of the gas price or max fee of replacements
+    pub max_fee: Option<U256>,
+    /// Whether transactions that the node dropped are rebroadcast
+    pub rebroadcast: bool,
+}
+
+impl Default for TxPolicy {
+    fn default() -> Self {
+        Self {
+            stuck_after: Duration::from_secs(60),
+            fee_bump: 15,
+            max_replacements: 5,
+            max_fee: None,
+            rebroadcast: true,
+        }
+    }
+}
+
+impl TxPolicy {
+    /// Sets how long a transaction may be pending since its last broadcast before it's replaced
+    /// (default: 60s)
+    #[must_use]
+    pub fn stuck_after(mut self, stuck_after: Duration) -> Self {
+        self.stuck_after = stuck_after;
+        self
+    }
+
+    /// Sets the increase of the fees of replacements in percent, raised to
+    /// [`MIN_REPLACEMENT_BUMP`] (default: 15)
+    #[must_use]
+    pub fn fee_bump(mut self, percent: u64) -> Self {
+        self.fee_bump = percent.max(MIN_REPLACEMENT_BUMP);
+        self
+    }
+
+    /// Sets how often a transaction is replaced before it's considered stuck, `0` to never
+    /// replace transactions (default: 5)
+    #[must_use]
+    pub fn max_replacements(mut self, max_replacements: usize) -> Self {
+        self.max_replacements = max_replacements;
+        self
+    }
+
+    /// Sets the cap of the gas price or max fee of replacements (default: none)
+    #[must_use]
+    pub fn max_fee(mut self, max_fee: impl Into<U256>) -> Self {
+        self.max_fee = Some(max_fee.into());
+        self
+    }
+
+    /// Sets whether transactions that the node dropped are rebroadcast (default: `true`)
+    #[must_use]
+    pub fn rebroadcast(mut self, rebroadcast: bool) -> Self {
+        self.rebroadcast = rebroadcast;
+        self
+    }
+
+    /// Returns the fees of a replacement, or `None` if the cap doesn't allow raising them by
+    /// [`MIN_REPLACEMENT_BUMP`]
+    fn bump(&self, fees: Fees) -> Option<Fees> {
+        let mut bumped = fees.bump(self.fee_bump);
+        if let Some(max_fee) = self.max_fee {
+            bumped = bumped.cap(max_fee);
+        }
+        bumped.replaces(&fees).then_some(bumped)
+    }
+}
+
+/// Middleware that tracks every sent transaction until its nonce is used, rebroadcasting it if
+/// the node dropped it and replacing it with higher fees if it's stuck, as configured by the
+/// [`TxPolicy`].
+///
+/// The transactions are checked by [`TxManagerMiddleware::check`], which
+/// [`TxManagerMiddleware::monitor`] calls at the chosen frequency. Their states can be queried
+/// by the hash of any of their broadcasts with [`TxManagerMiddleware::transaction`].
+///
+/// ```no_run
+/// use ethers_middleware::{
+///     gas_escalator::Frequency,
+///     tx_manager::{TxManagerMiddleware, TxPolicy},
+/// };
+/// use ethers_providers::{Http, Middleware, Provider};
+/// use ethers_core::types::TransactionRequest;
+/// use std::{convert::TryFrom, sync::Arc, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let policy = TxPolicy::default().stuck_after(Duration::from_secs(30)).max_replacements(3);
+/// let manager = Arc::new(TxManagerMiddleware::new(provider, policy));
+///
+/// let monitor = manager.clone();
+/// tokio::spawn(async move { monitor.monitor(Frequency::PerBlock).await });
+///
+/// let tx = TransactionRequest::new().to("vitalik.eth").value(100);
+/// let hash = *manager.send_transaction(tx, None).await?;
+/// let state = manager.transaction(hash).map(|tracked| tracked.state);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TxManagerMiddleware<M> {
+    inner: M,
+    policy: TxPolicy,
+    txs: Arc<Mutex<Vec<TrackedTransaction>>>,
+}
+
+impl<M: Clone> Clone for TxManagerMiddleware<M> {
+    fn clone(&self) -> Self {
+        Self { inner: self.inner.clone(), policy: self.policy.clone(), txs: self.txs.clone() }
+    }
+}
+
+impl<M> TxManagerMiddleware<M>
+where
+    M: Middleware,
+{
+    /// Instantiates the middleware, which handles stuck and dropped transactions as the `policy`
+    /// says
+    pub fn new(inner: M, policy: TxPolicy) -> Self {
+        Self { inner, policy, txs: Default::default() }
+    }
+
+    /// Returns the policy of stuck and dropped transactions
+    pub fn policy(&self) -> &TxPolicy {
+        &self.policy
+    }
+
+    /// Returns all tracked transactions, in order of their first broadcast
+    pub fn transactions(&self) -> Vec<TrackedTransaction> {
+        self.txs.lock().unwrap().clone()
+    }
+
+    /// Returns the tracked transactions whose nonce wasn't used yet
+    pub fn pending(&self) -> Vec<TrackedTransaction> {
+        self.txs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|tracked| !tracked.state.is_final())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the tracked transaction with a broadcast of the hash
+    pub fn transaction(&self, hash: TxHash) -> Option<TrackedTransaction> {
+        self.txs.lock().unwrap().iter().find(|tracked| tracked.hashes.contains(&hash)).cloned()
+    }
+
+    /// Stops tracking the transactions whose nonce was used and returns them
+    pub fn remove_final(&self) -> Vec<TrackedTransaction> {
+        let mut txs = self.txs.lock().unwrap();
+        let (done, pending) = txs.drain(..).partition(|tracked| tracked.state.is_final());
+        *txs = pending;
+        done
+    }
+
+    /// Checks the tracked transactions at the frequency, logging failed checks
+    pub async fn monitor(&self, frequency: Frequency) -> Result<(), TxManagerError<M>> {
+        let mut watcher: WatcherFuture = match frequency {
+            Frequency::PerBlock => {
+                Box::pin(self.inner.watch_blocks().await.map_err(FromErr::from)?.map(|_| ()))
+            }
+            Frequency::Duration(ms) => Box::pin(interval(Duration::from_millis(ms))),
+        };
+
+        while watcher.next().await.is_some() {
+            if let Err(err) = self.check().await {
+                tracing::warn!(%err, "failed to check the tracked transactions");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Updates the states of the tracked transactions whose nonce wasn't used yet, rebroadcasting
+    /// dropped and replacing stuck transactions as the policy says
+    pub async fn check(&self) -> Result<(), TxManagerError<M>> {
+        let pending: Vec<_> = self
+            .txs
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|tracked| !tracked.state.is_final())
+            .map(|tracked| tracked.hashes[0])
+            .collect();
+
+        for id in pending {
+            let mut tracked = match self.transaction(id) {
+                Some(tracked) => tracked,
+                None => continue,
+            };
+            let res = self.check_transaction(&mut tracked).await;
+            // write back the progress, even if a request failed
+            if let Some(entry) =
+                self.txs.lock().unwrap().iter_mut().find(|tracked| tracked.hashes[0] == id)
+            {
+                *entry = tracked;
+            }
+            res?;
+        }
+
+        Ok(())
+    }
+
+    async fn check_transaction(
+        &self,
+        tracked: &mut TrackedTransaction,
+    ) -> Result<(), TxManagerError<M>> {
+        if self.check_mined(tracked).await? {
+            return Ok(())
+        }
+
+        let (from, nonce) = match (tracked.tx.from(), tracked.tx.nonce()) {
+            (Some(from), Some(nonce)) => (*from, *nonce),
+            _ => return Err(TxManagerError::MissingSender),
+        };
+        let mined_nonce = self
+            .inner
+            .get_transaction_count(from, Some(BlockNumber::Latest.into()))
+            .await
+            .map_err(FromErr::from)?;
+        if mined_nonce > nonce {
+            // one of the broadcasts may have been mined after its receipt was requested
+            if !self.check_mined(tracked).await? {
+                tracked.state = TxState::Superseded;
+            }
+            return Ok(())
+        }
+
+        if self.inner.get_transaction(tracked.hash()).await.map_err(FromErr::from)?.is_none() {
+            tracked.state = TxState::Dropped;
+            if self.policy.rebroadcast {
+                let tx = tracked.tx.clone();
+                if self.broadcast(tracked, tx).await {
+                    tracked.rebroadcasts += 1;
+                }
+            }
+            return Ok(())
+        }
+
+        if tracked.last_broadcast.elapsed() < self.policy.stuck_after {
+            return Ok(())
+        }
+        tracked.state = TxState::Stuck;
+        if tracked.replacements >= self.policy.max_replacements {
+            return Ok(())
+        }
+        if let Some(fees) = Fees::of(&tracked.tx).and_then(|fees| self.policy.bump(fees)) {
+            let mut replacement = tracked.tx.clone();
+            fees.apply(&mut replacement);
+            if self.broadcast(tracked, replacement).await {
+                tracked.replacements += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Marks the transaction as mined if any of its broadcasts has a receipt, returns whether one
+    /// has
+    async fn check_mined(
+        &self,
+        tracked: &mut TrackedTransaction,
+    ) -> Result<bool, TxManagerError<M>> {
+        for hash in tracked.hashes.iter().rev() {
+            if let Some(receipt) =
+                self.inner.get_transaction_receipt(*hash).await.map_err(FromErr::from)?
+            {
+                tracked.state = TxState::Mined {
+                    hash: *hash,
+                    block_number: receipt.block_number,
+                    success: receipt.status != Some(0.into()),
+                };
+                return Ok(true)
+            }
+        }
+        Ok(false)
+    }
+
+    /// Broadcasts the transaction in place of the tracked one, returns whether it succeeded
+    async fn broadcast(&self, tracked: &mut TrackedTransaction, tx: TypedTransaction) -> bool {
+        match self.inner.send_transaction(tx.clone(), tracked.block).await {
+            Ok(pending_tx) => {
+                let hash = *pending_tx;
+                tracing::trace!(old_tx_hash = ?tracked.hash(), new_tx_hash = ?hash, "rebroadcast");
+                if hash != tracked.hash() {
+                    tracked.hashes.push(hash);
+                }
+                tracked.tx = tx;
+                tracked.state = TxState::Pending;
+                tracked.last_broadcast = Instant::now();
+                tracked.last_error = None;
+                true
+            }
+            Err(err) => {
+                // e.g. "nonce too low" once the nonce was used, which the next check detects
+                tracked.last_error = Some(err.to_string());
+                false
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M> Middleware for TxManagerMiddleware<M>
+where
+    M: Middleware,
+{
+    type Error = TxManagerError<M>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        if tx.from().is_none() {
+            let from = self.inner.default_sender().ok_or(TxManagerError::MissingSender)?;
+            tx.set_from(from);
+        }
+        // rebroadcasts and replacements must reuse the nonce
+        fill_with_nonce(&self.inner, &mut tx, block).await.map_err(FromErr::from)?;
+
+        let pending_tx =
+            self.inner.send_transaction(tx.clone(), block).await.map_err(FromErr::from)?;
+
+        let now = Instant::now();
+        self.txs.lock().unwrap().push(TrackedTransaction {
+            tx,
+            hashes: vec![*pending_tx],
+            state: TxState::Pending,
+            block,
+            sent_at: now,
+            last_broadcast: now,
+            rebroadcasts: 0,
+            replacements: 0,
+            last_error: None,
+        });
+
+        Ok(pending_tx)
+    }
+}
+
+impl<M: Middleware> FromErr<M::Error> for TxManagerError<M> {
+    fn from(src: M::Error) -> TxManagerError<M> {
+        TxManagerError::MiddlewareError(src)
+    }
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when the TxManager interacts with the blockchain
+pub enum TxManagerError<M: Middleware> {
+    #[error("{0}")]
+    /// Thrown when an internal middleware errors
+    MiddlewareError(M::Error),
+
+    #[error("Tracking transactions requires a sender")]
+    /// Thrown when the transaction has no sender and there is no default sender
+    MissingSender,
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, Transaction, TransactionReceipt, TransactionRequest, H256};
+    use ethers_providers::{MockProvider, Provider};
+    use serde_json::{json, Value};
+
+    fn manager(policy: TxPolicy) -> (TxManagerMiddleware<Provider<MockProvider>>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        (TxManagerMiddleware::new(provider, policy), mock)
+    }
+
+    fn tx() -> TransactionRequest {
+        TransactionRequest::new()
+            .from(Address::repeat_byte(1))
+            .to(Address::repeat_byte(2))
+            .gas(21_000)
+            .gas_price(100)
+    }
+
+    fn hash(n: u64) -> H256 {
+        H256::from_low_u64_be(n)
+    }
+
+    fn pending(hash: H256) -> Value {
+        json!(Transaction { hash, ..Default::default() })
+    }
+
+    fn mined(hash: H256) -> Value {
+        json!(TransactionReceipt {
+            transaction_hash: hash,
+            status: Some(1u64.into()),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn replaces_stuck_transactions() {
+        let (manager, mock) = manager(
+            TxPolicy::default().stuck_after(Duration::ZERO).max_replacements(2).max_fee(125),
+        );
+        // the hash and the pending nonce
+        mock.push(hash(1)).unwrap();
+        mock.push(U256::zero()).unwrap();
+        let hash = *manager.send_transaction(tx(), None).await.unwrap();
+        assert_eq!(manager.transaction(hash).unwrap().tx.nonce(), Some(&0.into()));
+
+        // the hash of the replacement of the transaction, which the node knows, whose nonce is
+        // unused and which is not mined
+        mock.push(self::hash(2)).unwrap();
+        mock.push(pending(hash)).unwrap();
+        mock.push(U256::zero()).unwrap();
+        mock.push(Value::Null).unwrap();
+        manager.check().await.unwrap();
+        let tracked = manager.transaction(hash).unwrap();
+        assert_eq!(tracked.state, TxState::Pending);
+        assert_eq!(tracked.replacements, 1);
+        assert_eq!(tracked.tx.gas_price(), Some(115.into()));
+
+        // the max fee caps the second bump below the minimum increase
+        mock.push(pending(self::hash(2))).unwrap();
+        mock.push(U256::zero()).unwrap();
+        mock.push(Value::Null).unwrap();
+        mock.push(Value::Null).unwrap();
+        manager.check().await.unwrap();
+        let tracked = manager.transaction(hash).unwrap();
+        assert_eq!(tracked.state, TxState::Stuck);
+        assert_eq!(tracked.hashes.len(), 2);
+
+        mock.push(mined(tracked.hash())).unwrap();
+        manager.check().await.unwrap();
+        let tracked = manager.transaction(hash).unwrap();
+        assert_eq!(
+            tracked.state,
+            TxState::Mined { hash: tracked.hashes[1], block_number: None, success: true }
+        );
+        assert!(manager.pending().is_empty());
+        assert_eq!(manager.remove_final().len(), 1);
+        assert!(manager.transactions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rebroadcasts_dropped_transactions() {
+        let (manager, mock) = manager(TxPolicy::default());
+        mock.push(hash(1)).unwrap();
+        mock.push(U256::zero()).unwrap();
+        let hash = *manager.send_transaction(tx(), None).await.unwrap();
+
+        // the node dropped the transaction
+        mock.push(hash).unwrap();
+        mock.push(Value::Null).unwrap();
+        mock.push(U256::zero()).unwrap();
+        mock.push(Value::Null).unwrap();
+        manager.check().await.unwrap();
+        let tracked = manager.transaction(hash).unwrap();
+        assert_eq!(tracked.state, TxState::Pending);
+        assert_eq!(tracked.rebroadcasts, 1);
+        let typed: TypedTransaction = tx().nonce(0).into();
+        mock.assert_request("eth_getTransactionCount", (Address::repeat_byte(1), "pending"))
+            .unwrap();
+        mock.assert_request("eth_sendTransaction", [&typed]).unwrap();
+        mock.assert_request("eth_getTransactionReceipt", [hash]).unwrap();
+        mock.assert_request("eth_getTransactionCount", (Address::repeat_byte(1), "latest"))
+            .unwrap();
+        mock.assert_request("eth_getTransactionByHash", [hash]).unwrap();
+        mock.assert_request("eth_sendTransaction", [&typed]).unwrap();
+
+        // dropped transactions stay dropped without rebroadcasts
+        let (manager, mock) = self::manager(TxPolicy::default().rebroadcast(false));
+        mock.push(hash).unwrap();
+        mock.push(U256::zero()).unwrap();
+        let hash = *manager.send_transaction(tx(), None).await.unwrap();
+        mock.push(Value::Null).unwrap();
+        mock.push(U256::zero()).unwrap();
+        mock.push(Value::Null).unwrap();
+        manager.check().await.unwrap();
+        assert_eq!(manager.transaction(hash).unwrap().state, TxState::Dropped);
+
+        // until another transaction uses the nonce
+        mock.push(Value::Null).unwrap();
+        mock.push(U256::one()).unwrap();
+        mock.push(Value::Null).unwrap();
+        manager.check().await.unwrap();
+        assert_eq!(manager.transaction(hash).unwrap().state, TxState::Superseded);
+    }
+
+    #[tokio::test]
+    async fn rechecks_receipts_before_superseding() {
+        let (manager, mock) = manager(TxPolicy::default());
+        mock.push(hash(1)).unwrap();
+        mock.push(U256::zero()).unwrap();
+        let hash = *manager.send_transaction(tx(), None).await.unwrap();
+
+        // the transaction is mined between the receipt and the nonce requests
+        mock.push(mined(hash)).unwrap();
+        mock.push(U256::one()).unwrap();
+        mock.push(Value::Null).unwrap();
+        manager.check().await.unwrap();
+        assert_eq!(
+            manager.transaction(hash).unwrap().state,
+            TxState::Mined { hash, block_number: None, success: true }
+        );
+    }
+}
@@ -3,8 +3,8 @@
 use async_trait::async_trait;
 use ethers_core::{types::*, utils::Anvil};
 use ethers_middleware::gas_oracle::{
-    BlockNative, Etherchain, Etherscan, GasCategory, GasNow, GasOracle, GasOracleError,
-    GasOracleMiddleware, Polygon, ProviderOracle, Result,
+    BlockNative, Etherchain, Etherscan, FeeHistoryOracle, GasCategory, GasNow, GasOracle,
+    GasOracleError, GasOracleMiddleware, Polygon, ProviderOracle, Result,
 };
 use ethers_providers::{Http, Middleware, Provider};
 use serial_test::serial;
@@ -67,6 +67,27 @@ async fn provider_oracle() {
     assert_eq!(gas, expected_gas_price);
 }
 
+#[tokio::test]
+async fn fee_history_oracle() {
+    let (provider, mock) = Provider::mocked();
+    let history = FeeHistory {
+        base_fee_per_gas: vec![90.into(), 100.into()],
+        gas_used_ratio: vec![0.5],
+        oldest_block: 1.into(),
+        reward: vec![vec![3.into()], vec![1.into()], vec![2.into()]],
+    };
+    let oracle =
+        FeeHistoryOracle::new(provider).category(GasCategory::Fast).base_fee_multiplier(150);
+
+    mock.push(history.clone()).unwrap();
+    assert_eq!(oracle.estimate_eip1559_fees().await.unwrap(), (152.into(), 2.into()));
+    mock.assert_request("eth_feeHistory", (U256::from(10), "latest", [90.0])).unwrap();
+
+    // legacy gas prices have no headroom
+    mock.push(history).unwrap();
+    assert_eq!(oracle.fetch().await.unwrap(), 102.into());
+}
+
 #[tokio::test]
 async fn blocknative() {
     let gas_now_oracle = BlockNative::default();
@@ -66,8 +66,9 @@ pub struct EthProtocolInfo {
     /// The eth network version.
     pub network: u64,
 
-    /// The total difficulty of the host's blockchain.
-    #[serde(deserialize_with = "from_int_or_hex")]
+    /// The total difficulty of the host's blockchain, zero if the node doesn't report it (geth
+    /// stopped reporting it after the merge).
+    #[serde(default, deserialize_with = "from_int_or_hex")]
     pub difficulty: U256,
 
     /// The Keccak hash of the host's genesis block.
@@ -124,11 +125,13 @@ pub struct EthInfo {
     /// The negotiated eth version.
     pub version: u64,
 
-    /// The total difficulty of the peer's blockchain.
-    #[serde(deserialize_with = "from_int_or_hex")]
+    /// The total difficulty of the peer's blockchain, zero if the node doesn't report it (geth
+    /// stopped reporting it after the merge).
+    #[serde(default, deserialize_with = "from_int_or_hex")]
     pub difficulty: U256,
 
-    /// The hash of the peer's best known block.
+    /// The hash of the peer's best known block, zero if the node doesn't report it.
+    #[serde(default)]
     pub head: H256,
 }
 
@@ -259,6 +262,37 @@ mod tests {
         assert_eq!(peer_info.enode, "enode://bb37b7302f79e47c1226d6e3ccf0ef6d51146019efdcc1f6e861fd1c1a78d5e84e486225a6a8a503b93d5c50125ee980835c92bde7f7d12f074c16f4e439a578@127.0.0.1:60872");
     }
 
+    #[test]
+    fn deserialize_peer_info_post_merge() {
+        // geth no longer reports the difficulty and head of peers
+        let response = r#"{
+            "enode":"enode://bb37b7302f79e47c1226d6e3ccf0ef6d51146019efdcc1f6e861fd1c1a78d5e84e486225a6a8a503b93d5c50125ee980835c92bde7f7d12f074c16f4e439a578@127.0.0.1:60872",
+            "id":"ca23c04b7e796da5d6a5f04a62b81c88d41b1341537db85a2b6443e838d8339b",
+            "name":"Geth/v1.14.8-stable/linux-amd64/go1.22.6",
+            "caps":["eth/68","snap/1"],
+            "network":{
+                "localAddress":"127.0.0.1:30304",
+                "remoteAddress":"127.0.0.1:60872",
+                "inbound":false,
+                "trusted":false,
+                "static":true
+            },
+            "protocols":{
+                "eth":{"version":68},
+                "snap":{"version":1}
+            }
+        }"#;
+        let peer_info: PeerInfo = serde_json::from_str(response).unwrap();
+        match peer_info.protocols.eth.unwrap() {
+            EthPeerInfo::Info(info) => {
+                assert_eq!(info.version, 68);
+                assert_eq!(info.difficulty, U256::zero());
+            }
+            EthPeerInfo::Handshake => panic!("unexpected handshake"),
+        }
+        assert!(peer_info.network.static_node);
+    }
+
     #[test]
     fn deserialize_node_info() {
         // this response also has an enr
@@ -0,0 +1,463 @@
+//! Client for the [enhanced APIs](https://docs.alchemy.com/reference/enhanced-apis-overview) of
+//! Alchemy, the `alchemy_` RPC methods that serve indexed token and transfer data
+
+use crate::{Middleware, ProviderError};
+use async_trait::async_trait;
+use ethers_core::types::{Address, BlockId, BlockNumber, TransactionReceipt, H256, U256, U64};
+use serde::{Deserialize, Serialize};
+
+/// The `alchemy_` RPC methods served by Alchemy endpoints.
+///
+/// Implemented for every [`Middleware`], the requests are sent to the underlying
+/// [`Provider`](crate::Provider) of the stack.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::Address;
+/// use ethers_providers::{
+///     alchemy::{AssetTransfersRequest, TransferCategory},
+///     AlchemyApi, Http, Provider,
+/// };
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("https://eth-mainnet.g.alchemy.com/v2/KEY")?;
+/// let address: Address = "0x6887246668a3b87f54deb3b94ba47a6f63f32985".parse()?;
+///
+/// let request = AssetTransfersRequest::default()
+///     .from_address(address)
+///     .category(vec![TransferCategory::External, TransferCategory::Erc20]);
+/// let page = provider.get_asset_transfers(&request).await?;
+/// for transfer in page.transfers {
+///     println!("{:?} {:?}", transfer.asset, transfer.value);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait AlchemyApi {
+    /// Returns a page of the transfers matching the request, `alchemy_getAssetTransfers`
+    ///
+    /// The next page is requested with the [`page_key`](AssetTransfers::page_key) of the
+    /// response.
+    async fn get_asset_transfers(
+        &self,
+        request: &AssetTransfersRequest,
+    ) -> Result<AssetTransfers, ProviderError>;
+
+    /// Returns the balances of `address` of the given ERC20 tokens, `alchemy_getTokenBalances`
+    async fn get_token_balances(
+        &self,
+        address: Address,
+        tokens: Vec<Address>,
+    ) -> Result<TokenBalances, ProviderError>;
+
+    /// Returns the name, symbol, decimals and logo of the ERC20 token, `alchemy_getTokenMetadata`
+    async fn get_token_metadata(&self, token: Address) -> Result<TokenMetadata, ProviderError>;
+
+    /// Returns the receipts of all transactions of the block, `alchemy_getTransactionReceipts`
+    async fn get_transaction_receipts(
+        &self,
+        block: BlockId,
+    ) -> Result<Vec<TransactionReceipt>, ProviderError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> AlchemyApi for M {
+    async fn get_asset_transfers(
+        &self,
+        request: &AssetTransfersRequest,
+    ) -> Result<AssetTransfers, ProviderError> {
+        self.provider().request("alchemy_getAssetTransfers", [request]).await
+    }
+
+    async fn get_token_balances(
+        &self,
+        address: Address,
+        tokens: Vec<Address>,
+    ) -> Result<TokenBalances, ProviderError> {
+        self.provider().request("alchemy_getTokenBalances", (address, tokens)).await
+    }
+
+    async fn get_token_metadata(&self, token: Address) -> Result<TokenMetadata, ProviderError> {
+        self.provider().request("alchemy_getTokenMetadata", [token]).await
+    }
+
+    async fn get_transaction_receipts(
+        &self,
+        block: BlockId,
+    ) -> Result<Vec<TransactionReceipt>, ProviderError> {
+        let block = match block {
+            BlockId::Hash(hash) => ReceiptsBlock { block_hash: Some(hash), block_number: None },
+            BlockId::Number(number) => {
+                ReceiptsBlock { block_hash: None, block_number: Some(number) }
+            }
+        };
+        let res: TransactionReceipts =
+            self.provider().request("alchemy_getTransactionReceipts", [block]).await?;
+        Ok(res.receipts)
+    }
+}
+
+/// The params of `alchemy_getTransactionReceipts`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptsBlock {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_hash: Option<H256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_number: Option<BlockNumber>,
+}
+
+/// The response of `alchemy_getTransactionReceipts`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct TransactionReceipts {
+    receipts: Vec<TransactionReceipt>,
+}
+
+/// The kinds of transfers returned by `alchemy_getAssetTransfers`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferCategory {
+    /// Transfers of ether by transactions
+    External,
+    /// Transfers of ether by internal calls
+    Internal,
+    /// Transfers of ERC20 tokens
+    Erc20,
+    /// Transfers of ERC721 tokens
+    Erc721,
+    /// Transfers of ERC1155 tokens
+    Erc1155,
+    /// Transfers of NFTs that don't follow a standard, like CryptoPunks
+    #[serde(rename = "specialnft")]
+    SpecialNft,
+}
+
+/// The order of the transfers returned by `alchemy_getAssetTransfers`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferOrder {
+    /// From the oldest to the most recent transfer
+    Asc,
+    /// From the most recent to the oldest transfer
+    Desc,
+}
+
+/// The filter of `alchemy_getAssetTransfers`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfersRequest {
+    /// The first block to include, the genesis block if `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<BlockNumber>,
+    /// The last block to include, the latest block if `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<BlockNumber>,
+    /// Only include transfers from this address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_address: Option<Address>,
+    /// Only include transfers to this address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_address: Option<Address>,
+    /// Only include transfers of these token contracts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contract_addresses: Vec<Address>,
+    /// The kinds of transfers to include
+    #[serde(default)]
+    pub category: Vec<TransferCategory>,
+    /// The order of the transfers, ascending if `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<TransferOrder>,
+    /// Whether to include the timestamp of the block of each transfer
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub with_metadata: bool,
+    /// Whether to exclude transfers of zero value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_zero_value: Option<bool>,
+    /// The maximum number of transfers per page, 1000 if `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<U64>,
+    /// The key of the page to return, from a previous response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_key: Option<String>,
+}
+
+impl AssetTransfersRequest {
+    /// Sets the first block to include
+    #[must_use]
+    pub fn from_block<T: Into<BlockNumber>>(mut self, block: T) -> Self {
+        self.from_block = Some(block.into());
+        self
+    }
+
+    /// Sets the last block to include
+    #[must_use]
+    pub fn to_block<T: Into<BlockNumber>>(mut self, block: T) -> Self {
+        self.to_block = Some(block.into());
+        self
+    }
+
+    /// Only includes transfers from this address
+    #[must_use]
+    pub fn from_address(mut self, address: Address) -> Self {
+        self.from_address = Some(address);
+        self
+    }
+
+    /// Only includes transfers to this address
+    #[must_use]
+    pub fn to_address(mut self, address: Address) -> Self {
+        self.to_address = Some(address);
+        self
+    }
+
+    /// Only includes transfers of these token contracts
+    #[must_use]
+    pub fn contract_addresses(mut self, contracts: Vec<Address>) -> Self {
+        self.contract_addresses = contracts;
+        self
+    }
+
+    /// Sets the kinds of transfers to include
+    #[must_use]
+    pub fn category(mut self, category: Vec<TransferCategory>) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Sets the order of the transfers
+    #[must_use]
+    pub fn order(mut self, order: TransferOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    /// Includes the timestamp of the block of each transfer
+    #[must_use]
+    pub fn with_metadata(mut self) -> Self {
+        self.with_metadata = true;
+        self
+    }
+
+    /// Sets the maximum number of transfers per page
+    #[must_use]
+    pub fn max_count(mut self, max_count: u64) -> Self {
+        self.max_count = Some(max_count.into());
+        self
+    }
+
+    /// Sets the key of the page to return, from a previous response
+    #[must_use]
+    pub fn page_key(mut self, page_key: impl Into<String>) -> Self {
+        self.page_key = Some(page_key.into());
+        self
+    }
+}
+
+/// A page of transfers, returned by `alchemy_getAssetTransfers`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfers {
+    /// The transfers of this page
+    pub transfers: Vec<AssetTransfer>,
+    /// The key of the next page, `None` if this is the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_key: Option<String>,
+}
+
+/// A transfer of ether or tokens
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfer {
+    /// The kind of the transfer
+    pub category: Option<TransferCategory>,
+    /// The number of the block that included the transfer
+    pub block_num: U64,
+    /// The sender
+    pub from: Address,
+    /// The recipient, `None` for contract creations
+    pub to: Option<Address>,
+    /// The transferred amount in units of the asset, `None` for NFTs and tokens without
+    /// decimals
+    pub value: Option<f64>,
+    /// The ids and amounts of the transferred ERC1155 tokens
+    #[serde(default, rename = "erc1155Metadata")]
+    pub erc1155_metadata: Option<Vec<Erc1155Metadata>>,
+    /// The id of the transferred NFT
+    pub token_id: Option<String>,
+    /// The symbol of the asset, `ETH` for ether
+    pub asset: Option<String>,
+    /// The unique id of the transfer
+    pub unique_id: String,
+    /// The hash of the transaction
+    pub hash: H256,
+    /// The raw value and contract of the transfer
+    pub raw_contract: RawContract,
+    /// The timestamp of the block, if requested with
+    /// [`with_metadata`](AssetTransfersRequest::with_metadata)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<TransferMetadata>,
+}
+
+/// The id and amount of a transferred ERC1155 token
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc1155Metadata {
+    /// The id of the token
+    pub token_id: String,
+    /// The transferred amount
+    pub value: String,
+}
+
+/// The raw value of a transfer and the contract of the token
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawContract {
+    /// The transferred amount in the smallest unit of the asset
+    pub value: Option<U256>,
+    /// The token contract, `None` for ether
+    pub address: Option<Address>,
+    /// The decimals of the token
+    pub decimal: Option<U256>,
+}
+
+/// The metadata of a transfer
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferMetadata {
+    /// The timestamp of the block in ISO 8601 format
+    pub block_timestamp: String,
+}
+
+/// The token balances of an address, returned by `alchemy_getTokenBalances`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalances {
+    /// The address holding the tokens
+    pub address: Address,
+    /// The balances, in the order of the requested tokens
+    pub token_balances: Vec<TokenBalance>,
+}
+
+/// The balance of a token
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBalance {
+    /// The token contract
+    pub contract_address: Address,
+    /// The balance in the smallest unit of the token, `None` if it couldn't be read
+    pub token_balance: Option<U256>,
+    /// Why the balance couldn't be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The metadata of an ERC20 token, returned by `alchemy_getTokenMetadata`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    /// The name of the token
+    pub name: Option<String>,
+    /// The symbol of the token
+    pub symbol: Option<String>,
+    /// The decimals of the token
+    pub decimals: Option<u8>,
+    /// The URL of the logo of the token
+    pub logo: Option<String>,
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::Provider;
+    use serde_json::json;
+
+    #[test]
+    fn deserializes_asset_transfers() {
+        let page: AssetTransfers = serde_json::from_value(json!({
+            "transfers": [{
+                "blockNum": "0xe96c95",
+                "uniqueId": "0x2b9e11f5f3c12ba1575fb0a1d7e0bb7d4bcc3e0bbf0e93d5a0b5c5a2b9a6f3c7:log:72",
+                "hash": "0x2b9e11f5f3c12ba1575fb0a1d7e0bb7d4bcc3e0bbf0e93d5a0b5c5a2b9a6f3c7",
+                "from": "0x6887246668a3b87f54deb3b94ba47a6f63f32985",
+                "to": "0x5c43b1ed97e52d009611d89b74fa829fe4ac56b1",
+                "value": 1.5,
+                "erc721TokenId": null,
+                "erc1155Metadata": null,
+                "tokenId": null,
+                "asset": "USDC",
+                "category": "erc20",
+                "rawContract": {
+                    "value": "0x16e360",
+                    "address": "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+                    "decimal": "0x6"
+                },
+                "metadata": {"blockTimestamp": "2022-06-09T20:05:52.000Z"}
+            }],
+            "pageKey": "5e4f2f3a-2b11-4c2d-b3b6-1a2b3c4d5e6f"
+        }))
+        .unwrap();
+
+        let transfer = &page.transfers[0];
+        assert_eq!(transfer.category, Some(TransferCategory::Erc20));
+        assert_eq!(transfer.block_num, U64::from(0xe96c95));
+        assert_eq!(transfer.value, Some(1.5));
+        assert_eq!(transfer.raw_contract.value, Some(U256::from(1_500_000)));
+        assert_eq!(transfer.raw_contract.decimal, Some(U256::from(6)));
+        assert_eq!(transfer.metadata.as_ref().unwrap().block_timestamp, "2022-06-09T20:05:52.000Z");
+        assert!(page.page_key.is_some());
+    }
+
+    #[tokio::test]
+    async fn queries_enhanced_apis() {
+        let (provider, mock) = Provider::mocked();
+        let address = Address::repeat_byte(1);
+        let token = Address::repeat_byte(2);
+
+        let request = AssetTransfersRequest::default()
+            .from_block(100u64)
+            .to_address(address)
+            .category(vec![TransferCategory::Erc721, TransferCategory::SpecialNft])
+            .order(TransferOrder::Desc)
+            .max_count(10);
+        mock.push(AssetTransfers::default()).unwrap();
+        assert_eq!(
+            provider.get_asset_transfers(&request).await.unwrap(),
+            AssetTransfers::default()
+        );
+        let params = json!([{
+            "fromBlock": "0x64",
+            "toAddress": address,
+            "category": ["erc721", "specialnft"],
+            "order": "desc",
+            "maxCount": "0xa"
+        }]);
+        mock.assert_request("alchemy_getAssetTransfers", params).unwrap();
+
+        let balances = TokenBalances {
+            address,
+            token_balances: vec![TokenBalance {
+                contract_address: token,
+                token_balance: Some(U256::from(5)),
+                error: None,
+            }],
+        };
+        mock.push(balances.clone()).unwrap();
+        assert_eq!(provider.get_token_balances(address, vec![token]).await.unwrap(), balances);
+        mock.assert_request("alchemy_getTokenBalances", (address, [token])).unwrap();
+
+        let metadata = TokenMetadata { symbol: Some("DAI".to_string()), ..Default::default() };
+        mock.push(metadata.clone()).unwrap();
+        assert_eq!(provider.get_token_metadata(token).await.unwrap(), metadata);
+        mock.assert_request("alchemy_getTokenMetadata", [token]).unwrap();
+
+        mock.push(TransactionReceipts { receipts: vec![TransactionReceipt::default()] }).unwrap();
+        let receipts = provider.get_transaction_receipts(BlockNumber::from(7u64).into()).await;
+        assert_eq!(receipts.unwrap().len(), 1);
+        mock.assert_request("alchemy_getTransactionReceipts", json!([{"blockNumber": "0x7"}]))
+            .unwrap();
+    }
+}
@@ -0,0 +1,545 @@
+//! Client for the standard [beacon node REST API](https://ethereum.github.io/beacon-APIs/) of
+//! consensus clients
+
+use ethers_core::types::{Address, Bytes, OtherFields, H256};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+use url::Url;
+
+/// A client for the REST API of a beacon node.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::beacon::{BeaconClient, BlockId, StateId};
+/// use url::Url;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let beacon = BeaconClient::new(Url::parse("http://localhost:5052")?);
+/// let head = beacon.header(BlockId::Head).await?;
+/// println!("head slot: {}", head.data.header.message.slot);
+///
+/// let validators = beacon.validators(StateId::Finalized, &["0", "1"]).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BeaconClient {
+    client: Client,
+    url: Url,
+}
+
+impl BeaconClient {
+    /// Creates a client for the beacon node at the given url
+    ///
+    /// The endpoints are resolved relative to the url, so a url with a path like
+    /// `http://localhost:3500/beacon` serves `http://localhost:3500/beacon/eth/v1/..`
+    pub fn new(url: impl Into<Url>) -> Self {
+        Self::new_with_client(url, Client::new())
+    }
+
+    /// Creates a client for the beacon node at the given url that uses the given http client
+    pub fn new_with_client(url: impl Into<Url>, client: Client) -> Self {
+        let mut url = url.into();
+        // the endpoints are joined to the url, which would replace its last path segment
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        Self { client, url }
+    }
+
+    /// The url of the beacon node
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the genesis of the chain, `/eth/v1/beacon/genesis`
+    pub async fn genesis(&self) -> Result<BeaconResponse<Genesis>, BeaconError> {
+        self.get("eth/v1/beacon/genesis", &[]).await
+    }
+
+    /// Returns the header of the given block, `/eth/v1/beacon/headers/{block_id}`
+    pub async fn header(
+        &self,
+        block: BlockId,
+    ) -> Result<BeaconResponse<BlockHeaderResponse>, BeaconError> {
+        self.get(&format!("eth/v1/beacon/headers/{block}"), &[]).await
+    }
+
+    /// Returns the given block, `/eth/v2/beacon/blocks/{block_id}`
+    pub async fn block(
+        &self,
+        block: BlockId,
+    ) -> Result<BeaconResponse<SignedBeaconBlock>, BeaconError> {
+        self.get(&format!("eth/v2/beacon/blocks/{block}"), &[]).await
+    }
+
+    /// Returns the root of the given block, `/eth/v1/beacon/blocks/{block_id}/root`
+    pub async fn block_root(&self, block: BlockId) -> Result<BeaconResponse<Root>, BeaconError> {
+        self.get(&format!("eth/v1/beacon/blocks/{block}/root"), &[]).await
+    }
+
+    /// Returns the validators with the given indices or public keys at the given state, or all
+    /// validators if `ids` is empty, `/eth/v1/beacon/states/{state_id}/validators`
+    pub async fn validators<S: AsRef<str>>(
+        &self,
+        state: StateId,
+        ids: &[S],
+    ) -> Result<BeaconResponse<Vec<ValidatorData>>, BeaconError> {
+        let ids = ids.iter().map(AsRef::as_ref).collect::<Vec<_>>().join(",");
+        let query = if ids.is_empty() { vec![] } else { vec![("id", ids)] };
+        self.get(&format!("eth/v1/beacon/states/{state}/validators"), &query).await
+    }
+
+    /// Returns the validator with the given index or public key at the given state,
+    /// `/eth/v1/beacon/states/{state_id}/validators/{validator_id}`
+    pub async fn validator(
+        &self,
+        state: StateId,
+        id: impl fmt::Display,
+    ) -> Result<BeaconResponse<ValidatorData>, BeaconError> {
+        self.get(&format!("eth/v1/beacon/states/{state}/validators/{id}"), &[]).await
+    }
+
+    /// Returns the blob sidecars of the given block, optionally only the ones with the given
+    /// indices, `/eth/v1/beacon/blob_sidecars/{block_id}`
+    pub async fn blob_sidecars(
+        &self,
+        block: BlockId,
+        indices: &[u64],
+    ) -> Result<BeaconResponse<Vec<BlobSidecar>>, BeaconError> {
+        let indices = indices.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+        let query = if indices.is_empty() { vec![] } else { vec![("indices", indices)] };
+        self.get(&format!("eth/v1/beacon/blob_sidecars/{block}"), &query).await
+    }
+
+    /// Sends a GET request to the given path of the API
+    async fn get<R: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<R, BeaconError> {
+        let url = self.url.join(path)?;
+        let res = self.client.get(url).query(query).send().await?;
+        let status = res.status();
+        let body = res.bytes().await?;
+        if status != StatusCode::OK {
+            return Err(match serde_json::from_slice::<ApiError>(&body) {
+                Ok(err) => BeaconError::Api(err),
+                Err(_) => BeaconError::Status(status, String::from_utf8_lossy(&body).to_string()),
+            })
+        }
+        serde_json::from_slice(&body).map_err(|err| BeaconError::SerdeJson {
+            err,
+            text: String::from_utf8_lossy(&body).to_string(),
+        })
+    }
+}
+
+/// Error thrown by the [`BeaconClient`]
+#[derive(Debug, Error)]
+pub enum BeaconError {
+    /// Thrown if the request failed
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    /// Thrown if the url of an endpoint could not be created
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    /// Thrown if the beacon node returned an error
+    #[error(transparent)]
+    Api(ApiError),
+    /// Thrown if the beacon node returned an unexpected status code without an error message
+    #[error("unexpected status {0}: {1}")]
+    Status(StatusCode, String),
+    /// Thrown if the response could not be parsed
+    #[error("Deserialization Error: {err}. Response: {text}")]
+    SerdeJson {
+        /// The error of the deserialization
+        err: serde_json::Error,
+        /// The body of the response
+        text: String,
+    },
+}
+
+/// An error returned by the beacon node
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+#[error("{message} (code: {code})")]
+pub struct ApiError {
+    /// The HTTP status code
+    pub code: u16,
+    /// The description of the error
+    pub message: String,
+}
+
+/// Identifies a block, e.g. in `/eth/v1/beacon/headers/{block_id}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockId {
+    /// The canonical head of the node
+    Head,
+    /// The genesis block
+    Genesis,
+    /// The latest finalized block
+    Finalized,
+    /// The block at the given slot
+    Slot(u64),
+    /// The block with the given root
+    Root(H256),
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockId::Head => f.write_str("head"),
+            BlockId::Genesis => f.write_str("genesis"),
+            BlockId::Finalized => f.write_str("finalized"),
+            BlockId::Slot(slot) => write!(f, "{slot}"),
+            BlockId::Root(root) => write!(f, "{root:?}"),
+        }
+    }
+}
+
+impl From<u64> for BlockId {
+    fn from(slot: u64) -> Self {
+        BlockId::Slot(slot)
+    }
+}
+
+impl From<H256> for BlockId {
+    fn from(root: H256) -> Self {
+        BlockId::Root(root)
+    }
+}
+
+/// Identifies a state, e.g. in `/eth/v1/beacon/states/{state_id}/validators`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateId {
+    /// The state of the canonical head of the node
+    Head,
+    /// The genesis state
+    Genesis,
+    /// The state of the latest finalized checkpoint
+    Finalized,
+    /// The state of the latest justified checkpoint
+    Justified,
+    /// The state at the given slot
+    Slot(u64),
+    /// The state with the given root
+    Root(H256),
+}
+
+impl fmt::Display for StateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateId::Head => f.write_str("head"),
+            StateId::Genesis => f.write_str("genesis"),
+            StateId::Finalized => f.write_str("finalized"),
+            StateId::Justified => f.write_str("justified"),
+            StateId::Slot(slot) => write!(f, "{slot}"),
+            StateId::Root(root) => write!(f, "{root:?}"),
+        }
+    }
+}
+
+impl From<u64> for StateId {
+    fn from(slot: u64) -> Self {
+        StateId::Slot(slot)
+    }
+}
+
+impl From<H256> for StateId {
+    fn from(root: H256) -> Self {
+        StateId::Root(root)
+    }
+}
+
+/// The envelope of all responses of the beacon API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconResponse<T> {
+    /// The requested data
+    pub data: T,
+    /// The fork the data belongs to, only included in versioned responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Whether the response references an unverified execution payload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_optimistic: Option<bool>,
+    /// Whether the response references finalized history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finalized: Option<bool>,
+}
+
+/// Details about the genesis of the chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Genesis {
+    /// The unix timestamp of the genesis
+    #[serde(with = "quoted_u64")]
+    pub genesis_time: u64,
+    /// The root of the validators at genesis
+    pub genesis_validators_root: H256,
+    /// The fork version of the genesis
+    pub genesis_fork_version: Bytes,
+}
+
+/// A block root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Root {
+    /// The hash tree root
+    pub root: H256,
+}
+
+/// The response of `/eth/v1/beacon/headers/{block_id}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeaderResponse {
+    /// The root of the block
+    pub root: H256,
+    /// Whether the block is part of the canonical chain
+    pub canonical: bool,
+    /// The signed header of the block
+    pub header: SignedBeaconBlockHeader,
+}
+
+/// A block header signed by its proposer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBeaconBlockHeader {
+    /// The header
+    pub message: BeaconBlockHeader,
+    /// The BLS signature of the proposer
+    pub signature: Bytes,
+}
+
+/// The header of a beacon block, which commits to its body by its root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconBlockHeader {
+    /// The slot of the block
+    #[serde(with = "quoted_u64")]
+    pub slot: u64,
+    /// The index of the validator that proposed the block
+    #[serde(with = "quoted_u64")]
+    pub proposer_index: u64,
+    /// The root of the parent block
+    pub parent_root: H256,
+    /// The root of the state after the block
+    pub state_root: H256,
+    /// The root of the body of the block
+    pub body_root: H256,
+}
+
+/// A beacon block signed by its proposer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBeaconBlock {
+    /// The block
+    pub message: BeaconBlock,
+    /// The BLS signature of the proposer
+    pub signature: Bytes,
+}
+
+/// A beacon block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconBlock {
+    /// The slot of the block
+    #[serde(with = "quoted_u64")]
+    pub slot: u64,
+    /// The index of the validator that proposed the block
+    #[serde(with = "quoted_u64")]
+    pub proposer_index: u64,
+    /// The root of the parent block
+    pub parent_root: H256,
+    /// The root of the state after the block
+    pub state_root: H256,
+    /// The body of the block
+    pub body: BeaconBlockBody,
+}
+
+/// The body of a beacon block.
+///
+/// The contents of the body change with every fork, fields that are not present in all forks are
+/// collected in `other`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeaconBlockBody {
+    /// The BLS signature of the epoch by the proposer, mixed into the RANDAO
+    pub randao_reveal: Bytes,
+    /// Arbitrary data set by the proposer
+    pub graffiti: H256,
+    /// The fields of the body that depend on the fork
+    #[serde(flatten)]
+    pub other: OtherFields,
+}
+
+/// A validator and its status at a given state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorData {
+    /// The index of the validator in the registry
+    #[serde(with = "quoted_u64")]
+    pub index: u64,
+    /// Balance in Gwei
+    #[serde(with = "quoted_u64")]
+    pub balance: u64,
+    /// The status of the validator at the state
+    pub status: ValidatorStatus,
+    /// The validator
+    pub validator: Validator,
+}
+
+/// A validator of the registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validator {
+    /// The BLS public key of the validator
+    pub pubkey: Bytes,
+    /// The credentials that withdrawals are sent to
+    pub withdrawal_credentials: H256,
+    /// Effective balance in Gwei
+    #[serde(with = "quoted_u64")]
+    pub effective_balance: u64,
+    /// Whether the validator was slashed
+    pub slashed: bool,
+    /// The epoch the validator became eligible for activation
+    #[serde(with = "quoted_u64")]
+    pub activation_eligibility_epoch: u64,
+    /// The epoch the validator was activated, `u64::MAX` if it wasn't
+    #[serde(with = "quoted_u64")]
+    pub activation_epoch: u64,
+    /// The epoch the validator exited, `u64::MAX` if it didn't
+    #[serde(with = "quoted_u64")]
+    pub exit_epoch: u64,
+    /// The epoch the balance of the validator can be withdrawn, `u64::MAX` if it can't
+    #[serde(with = "quoted_u64")]
+    pub withdrawable_epoch: u64,
+}
+
+impl Validator {
+    /// Returns the execution layer address withdrawals are sent to, if the validator has
+    /// `0x01` withdrawal credentials
+    pub fn withdrawal_address(&self) -> Option<Address> {
+        let credentials = self.withdrawal_credentials.as_bytes();
+        (credentials[0] == 0x01).then(|| Address::from_slice(&credentials[12..]))
+    }
+}
+
+/// The status of a validator, see
+/// <https://hackmd.io/ofFJ5gOmQpu1jjHilHbdQQ>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    /// The deposit was processed, the validator is not eligible for activation yet
+    PendingInitialized,
+    /// The validator is waiting in the activation queue
+    PendingQueued,
+    /// The validator is active
+    ActiveOngoing,
+    /// The validator is active and exiting
+    ActiveExiting,
+    /// The validator is active and was slashed
+    ActiveSlashed,
+    /// The validator exited without being slashed
+    ExitedUnslashed,
+    /// The validator exited after being slashed
+    ExitedSlashed,
+    /// The balance of the validator can be withdrawn
+    WithdrawalPossible,
+    /// The balance of the validator was withdrawn
+    WithdrawalDone,
+}
+
+/// A blob of a block along with its KZG commitment and proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobSidecar {
+    /// The index of the blob in the block
+    #[serde(with = "quoted_u64")]
+    pub index: u64,
+    /// The blob
+    pub blob: Bytes,
+    /// The KZG commitment to the blob
+    pub kzg_commitment: Bytes,
+    /// The KZG proof of the blob against the commitment
+    pub kzg_proof: Bytes,
+    /// The header of the block of the blob
+    pub signed_block_header: SignedBeaconBlockHeader,
+    /// The Merkle proof of the commitment against the body root of the block
+    pub kzg_commitment_inclusion_proof: Vec<H256>,
+}
+
+/// The beacon API encodes integers as decimal strings
+mod quoted_u64 {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        ethers_core::types::serde_helpers::deserialize_stringified_u64(deserializer)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Request};
+    use tokio::task::JoinHandle;
+
+    /// Serves a single HTTP request with the given status and body
+    async fn serve_once(status: &'static str, body: &'static str) -> (Url, JoinHandle<Request>) {
+        let (url, handle) = test_server::serve_once(status, "", |_| body.to_string()).await;
+        (Url::parse(&url).unwrap(), handle)
+    }
+
+    #[tokio::test]
+    async fn can_get_header() {
+        let (url, request) = serve_once(
+            "200 OK",
+            r#"{"execution_optimistic":false,"finalized":true,"data":{"root":"0xcf8e0d4e9587369b2301d0790347320302cc0943d5a1884560367e8208d920f2","canonical":true,"header":{"message":{"slot":"1","proposer_index":"1","parent_root":"0xcf8e0d4e9587369b2301d0790347320302cc0943d5a1884560367e8208d920f2","state_root":"0xcf8e0d4e9587369b2301d0790347320302cc0943d5a1884560367e8208d920f2","body_root":"0xcf8e0d4e9587369b2301d0790347320302cc0943d5a1884560367e8208d920f2"},"signature":"0x1b66ac1fb663c9bc59509846d6ec05345bd908eda73e670af888da41af171505cc411d61252fb6cb3fa0017b679f8bb2305b26a285fa2737f175668d0dff91cc1b66ac1fb663c9bc59509846d6ec05345bd908eda73e670af888da41af171505"}}}"#,
+        )
+        .await;
+
+        // the last segment of the path is kept without a trailing slash
+        let url = url.join("beacon").unwrap();
+        let header = BeaconClient::new(url).header(BlockId::Slot(1)).await.unwrap();
+        assert_eq!(
+            request.await.unwrap().request_line(),
+            "GET /beacon/eth/v1/beacon/headers/1 HTTP/1.1"
+        );
+        assert_eq!(header.finalized, Some(true));
+        assert!(header.data.canonical);
+        assert_eq!(header.data.header.message.slot, 1);
+    }
+
+    #[tokio::test]
+    async fn can_get_validators() {
+        let (url, request) = serve_once(
+            "200 OK",
+            r#"{"execution_optimistic":false,"data":[{"index":"1","balance":"32000000000","status":"active_ongoing","validator":{"pubkey":"0x93247f2209abcacf57b75a51dafae777f9dd38bc7053d1af526f220a7489a6d3a2753e5f3e8b1cfe39b56f43611df74a","withdrawal_credentials":"0x010000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045","effective_balance":"32000000000","slashed":false,"activation_eligibility_epoch":"0","activation_epoch":"0","exit_epoch":"18446744073709551615","withdrawable_epoch":"18446744073709551615"}}]}"#,
+        )
+        .await;
+
+        let validators =
+            BeaconClient::new(url).validators(StateId::Head, &["1", "0x9324"]).await.unwrap();
+        assert_eq!(
+            request.await.unwrap().request_line(),
+            "GET /eth/v1/beacon/states/head/validators?id=1%2C0x9324 HTTP/1.1"
+        );
+        let validator = &validators.data[0];
+        assert_eq!(validator.status, ValidatorStatus::ActiveOngoing);
+        assert_eq!(validator.validator.exit_epoch, u64::MAX);
+        assert_eq!(
+            validator.validator.withdrawal_address(),
+            Some("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_api_errors() {
+        let (url, _) =
+            serve_once("404 Not Found", r#"{"code":404,"message":"Block not found"}"#).await;
+
+        let err = BeaconClient::new(url).block(BlockId::Head).await.unwrap_err();
+        match err {
+            BeaconError::Api(err) => {
+                assert_eq!(err, ApiError { code: 404, message: "Block not found".to_string() })
+            }
+            err => panic!("unexpected error {err:?}"),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Client for the [ERC-4337](https://eips.ethereum.org/EIPS/eip-4337#rpc-methods-eth-namespace)
+//! bundler RPC methods
+
+use crate::{Middleware, ProviderError};
+use async_trait::async_trait;
+use ethers_core::types::{
+    Address, UserOperation, UserOperationByHash, UserOperationGasEstimate, UserOperationReceipt,
+    H256,
+};
+
+/// The RPC methods bundlers expose to account abstraction clients.
+///
+/// Implemented for every [`Middleware`], the requests are sent to the underlying
+/// [`Provider`](crate::Provider) of the stack.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, UserOperation};
+/// use ethers_providers::{BundlerApi, Http, Provider};
+/// use std::convert::TryFrom;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let bundler = Provider::<Http>::try_from("http://localhost:4337")?;
+/// let entry_point: Address = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".parse()?;
+///
+/// let mut op = UserOperation::default();
+/// let estimate = bundler.estimate_user_operation_gas(op.clone(), entry_point).await?;
+/// op.call_gas_limit = estimate.call_gas_limit;
+/// op.verification_gas_limit = estimate.verification_gas_limit;
+/// op.pre_verification_gas = estimate.pre_verification_gas;
+///
+/// let hash = bundler.send_user_operation(op, entry_point).await?;
+/// let receipt = bundler.get_user_operation_receipt(hash).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait BundlerApi {
+    /// Submits a user operation to the bundler's mempool and returns its hash,
+    /// `eth_sendUserOperation`
+    async fn send_user_operation(
+        &self,
+        op: UserOperation,
+        entry_point: Address,
+    ) -> Result<H256, ProviderError>;
+
+    /// Estimates the gas limits of a user operation, `eth_estimateUserOperationGas`
+    ///
+    /// The gas limits and fees of `op` are ignored and its signature need not be valid.
+    async fn estimate_user_operation_gas(
+        &self,
+        op: UserOperation,
+        entry_point: Address,
+    ) -> Result<UserOperationGasEstimate, ProviderError>;
+
+    /// Returns the user operation with the given hash if it was included on chain,
+    /// `eth_getUserOperationByHash`
+    async fn get_user_operation_by_hash(
+        &self,
+        hash: H256,
+    ) -> Result<Option<UserOperationByHash>, ProviderError>;
+
+    /// Returns the receipt of the user operation with the given hash if it was included on chain,
+    /// `eth_getUserOperationReceipt`
+    async fn get_user_operation_receipt(
+        &self,
+        hash: H256,
+    ) -> Result<Option<UserOperationReceipt>, ProviderError>;
+
+    /// Returns the entry points the bundler supports, `eth_supportedEntryPoints`
+    async fn supported_entry_points(&self) -> Result<Vec<Address>, ProviderError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> BundlerApi for M {
+    async fn send_user_operation(
+        &self,
+        op: UserOperation,
+        entry_point: Address,
+    ) -> Result<H256, ProviderError> {
+        self.provider().request("eth_sendUserOperation", (op, entry_point)).await
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        op: UserOperation,
+        entry_point: Address,
+    ) -> Result<UserOperationGasEstimate, ProviderError> {
+        self.provider().request("eth_estimateUserOperationGas", (op, entry_point)).await
+    }
+
+    async fn get_user_operation_by_hash(
+        &self,
+        hash: H256,
+    ) -> Result<Option<UserOperationByHash>, ProviderError> {
+        self.provider().request("eth_getUserOperationByHash", [hash]).await
+    }
+
+    async fn get_user_operation_receipt(
+        &self,
+        hash: H256,
+    ) -> Result<Option<UserOperationReceipt>, ProviderError> {
+        self.provider().request("eth_getUserOperationReceipt", [hash]).await
+    }
+
+    async fn supported_entry_points(&self) -> Result<Vec<Address>, ProviderError> {
+        self.provider().request("eth_supportedEntryPoints", ()).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::Provider;
+    use ethers_core::types::U256;
+
+    #[tokio::test]
+    async fn talks_to_bundler() {
+        let (provider, mock) = Provider::mocked();
+        let entry_point = Address::repeat_byte(0x55);
+        let op = UserOperation { sender: Address::repeat_byte(1), ..Default::default() };
+
+        let estimate =
+            UserOperationGasEstimate { call_gas_limit: U256::from(21_000), ..Default::default() };
+        mock.push(estimate.clone()).unwrap();
+        let res = provider.estimate_user_operation_gas(op.clone(), entry_point).await.unwrap();
+        assert_eq!(res, estimate);
+        mock.assert_request("eth_estimateUserOperationGas", (op.clone(), entry_point)).unwrap();
+
+        let hash = op.hash(entry_point, 1u64.into());
+        mock.push(hash).unwrap();
+        assert_eq!(provider.send_user_operation(op.clone(), entry_point).await.unwrap(), hash);
+        mock.assert_request("eth_sendUserOperation", (op, entry_point)).unwrap();
+
+        mock.push(serde_json::Value::Null).unwrap();
+        assert_eq!(provider.get_user_operation_receipt(hash).await.unwrap(), None);
+        mock.assert_request("eth_getUserOperationReceipt", [hash]).unwrap();
+
+        mock.push::<Vec<Address>, _>(vec![entry_point]).unwrap();
+        assert_eq!(provider.supported_entry_points().await.unwrap(), vec![entry_point]);
+        mock.assert_request("eth_supportedEntryPoints", ()).unwrap();
+    }
+}
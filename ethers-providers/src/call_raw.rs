@@ -9,7 +9,7 @@ use ethers_core::{
     utils,
 };
 use pin_project::pin_project;
-use serde::{ser::SerializeTuple, Deserialize, Serialize};
+use serde::{ser::SerializeTuple, Serialize};
 use std::{
     fmt,
     future::Future,
@@ -231,91 +231,11 @@ where
 /// [state override set](https://geth.ethereum.org/docs/rpc/ns-eth#3-object---state-override-set)
 pub mod spoof {
     use super::*;
-    use std::collections::HashMap;
-
-    /// The state elements to override for a particular account.
-    #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
-    pub struct Account {
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub nonce: Option<U64>,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub balance: Option<U256>,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        pub code: Option<Bytes>,
-        #[serde(flatten, skip_serializing_if = "Option::is_none")]
-        pub storage: Option<Storage>,
-    }
-
-    impl Account {
-        /// Override the account nonce
-        pub fn nonce(&mut self, nonce: U64) -> &mut Self {
-            self.nonce = Some(nonce);
-            self
-        }
-        /// Override the account balance
-        pub fn balance(&mut self, bal: U256) -> &mut Self {
-            self.balance = Some(bal);
-            self
-        }
-        /// Override the code at the account
-        pub fn code(&mut self, code: Bytes) -> &mut Self {
-            self.code = Some(code);
-            self
-        }
-        /// Override the value of the account storage at the given storage `key`
-        pub fn store(&mut self, key: H256, val: H256) -> &mut Self {
-            self.storage.get_or_insert_with(Default::default).insert(key, val);
-            self
-        }
-    }
-
-    /// Wraps a map from storage slot to the overriden value.
-    ///
-    /// Storage overrides can either replace the existing state of an account or they can be treated
-    /// as a diff on the existing state.
-    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-    pub enum Storage {
-        #[serde(rename = "stateDiff")]
-        Diff(HashMap<H256, H256>),
-        #[serde(rename = "state")]
-        Replace(HashMap<H256, H256>),
-    }
-
-    /// The default storage override is a diff on the existing state of the account.
-    impl Default for Storage {
-        fn default() -> Self {
-            Self::Diff(Default::default())
-        }
-    }
-    impl std::ops::Deref for Storage {
-        type Target = HashMap<H256, H256>;
-        fn deref(&self) -> &Self::Target {
-            match self {
-                Self::Diff(map) => map,
-                Self::Replace(map) => map,
-            }
-        }
-    }
-    impl std::ops::DerefMut for Storage {
-        fn deref_mut(&mut self) -> &mut Self::Target {
-            match self {
-                Self::Diff(map) => map,
-                Self::Replace(map) => map,
-            }
-        }
-    }
-
-    /// A wrapper type that holds a complete state override set.
-    #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
-    #[serde(transparent)]
-    pub struct State(#[serde(skip_serializing_if = "HashMap::is_empty")] HashMap<Address, Account>);
 
-    impl State {
-        /// Returns a mutable reference to the [`Account`] in the map.
-        pub fn account(&mut self, adr: Address) -> &mut Account {
-            self.0.entry(adr).or_default()
-        }
-    }
+    pub use ethers_core::{
+        types::spoof::{Account, State, Storage},
+        utils::{dynamic_array_slot, mapping_slot},
+    };
 
     /// Returns an empty state override set.
     ///
@@ -490,6 +410,27 @@ pub mod spoof {
         state.account(adr).store(key, val);
         state
     }
+
+    /// Returns the storage slot of the `balanceOf` entry for `holder` in an ERC20 token that
+    /// follows the OpenZeppelin storage layout, where the balances mapping is at slot `0`.
+    ///
+    /// Tokens with a different layout can use [`mapping_slot`] with their balances slot instead.
+    ///
+    /// # Example
+    /// ```
+    /// # use ethers_core::{types::{Address, H256}, utils::parse_ether};
+    /// # use ethers_providers::call_raw::spoof;
+    /// let token: Address = "0x6B175474E89094C44Da98b954EedeAC495271d0F".parse().unwrap();
+    /// let holder: Address = "0x6fC21092DA55B392b045eD78F4732bff3C580e2c".parse().unwrap();
+    ///
+    /// // give `holder` 100 tokens for the call
+    /// let mut amount = H256::zero();
+    /// parse_ether(100u64).unwrap().to_big_endian(amount.as_bytes_mut());
+    /// let state = spoof::storage(token, spoof::erc20_balance_slot(holder), amount);
+    /// ```
+    pub fn erc20_balance_slot(holder: Address) -> H256 {
+        mapping_slot(holder, U256::zero())
+    }
 }
 
 #[cfg(test)]
@@ -500,6 +441,7 @@ mod tests {
         types::TransactionRequest,
         utils::{get_contract_address, keccak256, parse_ether, Geth},
     };
+    use serde::Deserialize;
     use std::convert::TryFrom;
 
     // Deserializes eth_call parameters as owned data for testing serialization
@@ -617,4 +559,26 @@ mod tests {
         let bytes = provider.call_raw(&tx).state(&state).await.unwrap();
         assert_eq!(H256::from_slice(bytes.as_ref()), val);
     }
+
+    #[test]
+    fn test_storage_slots() {
+        // keccak256(uint256(0))
+        let start: H256 =
+            "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563".parse().unwrap();
+        assert_eq!(spoof::dynamic_array_slot(0.into(), 0.into(), 1), start);
+        assert_eq!(
+            spoof::dynamic_array_slot(0.into(), 1.into(), 1),
+            "0x290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e564".parse().unwrap()
+        );
+
+        let holder: Address = "0x6fC21092DA55B392b045eD78F4732bff3C580e2c".parse().unwrap();
+        let mut buf = [0u8; 64];
+        buf[12..32].copy_from_slice(holder.as_bytes());
+        buf[63] = 5;
+        assert_eq!(spoof::mapping_slot(holder, 5.into()), H256::from(keccak256(buf)));
+
+        buf[63] = 0;
+        assert_eq!(spoof::erc20_balance_slot(holder), H256::from(keccak256(buf)));
+        assert_eq!(spoof::mapping_slot(H256::zero(), 0.into()), H256::from(keccak256([0u8; 64])));
+    }
 }
@@ -0,0 +1,42 @@
+//! The features of a node, see [`Provider::node_capabilities`](crate::Provider::node_capabilities)
+
+use crate::provider::NodeClient;
+
+/// The features of a node, probed by
+/// [`Provider::node_capabilities`](crate::Provider::node_capabilities).
+///
+/// Once known, the provider consults them to pick code paths: requests of namespaces the node
+/// doesn't serve fail with
+/// [`ProviderError::UnsupportedMethod`](crate::ProviderError::UnsupportedMethod) without being
+/// sent, EIP-1559 transactions are filled as legacy transactions on chains without a base fee and
+/// logs are polled instead of subscribed to if subscriptions aren't supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeCapabilities {
+    /// The `web3_clientVersion` of the node
+    pub client_version: String,
+    /// The client, if it's one of the known clients
+    pub client: Option<NodeClient>,
+    /// Whether the latest block has a base fee, i.e. the chain supports EIP-1559 transactions
+    pub eip1559: bool,
+    /// Whether the node serves the `debug` namespace
+    pub debug: bool,
+    /// Whether the node serves the `trace` namespace
+    pub trace: bool,
+    /// Whether the transport supports subscriptions
+    pub pubsub: bool,
+}
+
+impl NodeCapabilities {
+    /// Returns whether the node serves the method, as far as its namespace was probed
+    pub fn supports(&self, method: &str) -> bool {
+        if method.starts_with("debug_") {
+            self.debug
+        } else if method.starts_with("trace_") {
+            self.trace
+        } else if method == "eth_subscribe" || method == "eth_unsubscribe" {
+            self.pubsub
+        } else {
+            true
+        }
+    }
+}
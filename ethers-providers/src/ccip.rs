@@ -0,0 +1,222 @@
+//! [CCIP-Read](https://eips.ethereum.org/EIPS/eip-3668): offchain data retrieval for reverting
+//! `eth_call`s, see [`Provider::ccip_read`](crate::Provider::ccip_read)
+
+use crate::ProviderError;
+use ethers_core::{
+    abi::{self, ParamType, Token},
+    types::{Address, Bytes},
+    utils::id,
+};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+/// The signature of the error contracts revert with to request offchain data
+const OFFCHAIN_LOOKUP: &str = "OffchainLookup(address,string[],bytes,bytes4,bytes)";
+
+/// Settings for following `OffchainLookup` reverts
+#[derive(Debug, Clone)]
+pub(crate) struct CcipRead {
+    /// How many lookups are followed for a single call
+    pub(crate) max_redirects: usize,
+    client: Client,
+}
+
+impl CcipRead {
+    pub(crate) fn new(max_redirects: usize) -> Self {
+        Self { max_redirects, client: Client::new() }
+    }
+
+    /// Fetches the data requested by the lookup from its gateways, in order.
+    ///
+    /// A gateway that fails with a server error is skipped, any other failure is final.
+    pub(crate) async fn fetch(&self, lookup: &OffchainLookup) -> Result<Bytes, ProviderError> {
+        #[derive(Deserialize)]
+        struct GatewayResponse {
+            data: Bytes,
+        }
+
+        let sender = format!("{:?}", lookup.sender);
+        let data = format!("0x{}", hex::encode(&lookup.call_data));
+        let mut last_error = None;
+        for template in &lookup.urls {
+            let url = template.replace("{sender}", &sender).replace("{data}", &data);
+            // gateways that expect the data in the body don't include it in the url template
+            let request = if template.contains("{data}") {
+                self.client.get(&url)
+            } else {
+                self.client.post(&url).json(&serde_json::json!({ "data": data, "sender": sender }))
+            };
+            let res = request.send().await?;
+            let status = res.status();
+            if status.is_server_error() {
+                last_error = Some(format!("gateway {url} failed with {status}"));
+                continue
+            }
+            if status != StatusCode::OK {
+                let text = res.text().await.unwrap_or_default();
+                return Err(ProviderError::CcipReadError(format!(
+                    "gateway {url} failed with {status}: {text}"
+                )))
+            }
+            return Ok(res.json::<GatewayResponse>().await?.data)
+        }
+        Err(ProviderError::CcipReadError(
+            last_error.unwrap_or_else(|| "no gateway urls".to_string()),
+        ))
+    }
+}
+
+/// A request for offchain data, decoded from the revert data of a call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OffchainLookup {
+    /// The contract that reverted, which is also called with the response
+    pub(crate) sender: Address,
+    /// Gateway url templates
+    pub(crate) urls: Vec<String>,
+    pub(crate) call_data: Bytes,
+    /// The selector of the function the response is passed to
+    pub(crate) callback_function: [u8; 4],
+    /// Data passed to the callback along with the response
+    pub(crate) extra_data: Bytes,
+}
+
+impl OffchainLookup {
+    /// Decodes the revert data of a call, returns `None` if it's not an `OffchainLookup` error
+    pub(crate) fn decode(revert: &[u8]) -> Option<Self> {
+        if revert.len() < 4 || revert[..4] != id(OFFCHAIN_LOOKUP) {
+            return None
+        }
+        let params = [
+            ParamType::Address,
+            ParamType::Array(Box::new(ParamType::String)),
+            ParamType::Bytes,
+            ParamType::FixedBytes(4),
+            ParamType::Bytes,
+        ];
+        let mut tokens = abi::decode(&params, &revert[4..]).ok()?.into_iter();
+        let sender = tokens.next()?.into_address()?;
+        let urls = tokens
+            .next()?
+            .into_array()?
+            .into_iter()
+            .map(Token::into_string)
+            .collect::<Option<Vec<_>>>()?;
+        let call_data = tokens.next()?.into_bytes()?.into();
+        let callback_function = tokens.next()?.into_fixed_bytes()?.try_into().ok()?;
+        let extra_data = tokens.next()?.into_bytes()?.into();
+        Some(Self { sender, urls, call_data, callback_function, extra_data })
+    }
+
+    /// Returns the calldata of the callback with the response of the gateway,
+    /// `callback(response, extraData)`
+    pub(crate) fn callback(&self, response: Bytes) -> Bytes {
+        let args =
+            abi::encode(&[Token::Bytes(response.to_vec()), Token::Bytes(self.extra_data.to_vec())]);
+        [&self.callback_function[..], &args].concat().into()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn encode(&self) -> Bytes {
+        let urls = self.urls.iter().cloned().map(Token::String).collect();
+        let args = abi::encode(&[
+            Token::Address(self.sender),
+            Token::Array(urls),
+            Token::Bytes(self.call_data.to_vec()),
+            Token::FixedBytes(self.callback_function.to_vec()),
+            Token::Bytes(self.extra_data.to_vec()),
+        ]);
+        [&id(OFFCHAIN_LOOKUP)[..], &args].concat().into()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{test_server, JsonRpcError, Middleware, Provider};
+    use ethers_core::{
+        types::{transaction::eip2718::TypedTransaction, BlockNumber, TransactionRequest},
+        utils,
+    };
+    use serde_json::json;
+
+    fn revert(data: Bytes) -> JsonRpcError {
+        let data = Some(json!(data));
+        JsonRpcError { code: 3, message: "execution reverted".to_string(), data }
+    }
+
+    /// Serves a single gateway request, the handle resolves to the request line
+    async fn gateway(data: Bytes) -> (String, tokio::task::JoinHandle<String>) {
+        let body = json!({ "data": data }).to_string();
+        let (url, handle) = test_server::serve_once("200 OK", "", move |_| body).await;
+        let handle = tokio::spawn(async move { handle.await.unwrap().request_line().to_string() });
+        (format!("{url}/{{sender}}/{{data}}.json"), handle)
+    }
+
+    fn lookup(url: String) -> OffchainLookup {
+        OffchainLookup {
+            sender: Address::repeat_byte(1),
+            urls: vec![url],
+            call_data: Bytes::from(vec![0xaa, 0xbb]),
+            callback_function: [1, 2, 3, 4],
+            extra_data: Bytes::from(vec![0xcc]),
+        }
+    }
+
+    #[test]
+    fn decodes_offchain_lookup() {
+        let lookup = lookup("https://example.com/{sender}/{data}.json".to_string());
+        assert_eq!(&lookup.encode()[..4], &[0x55, 0x6f, 0x18, 0x30]);
+        assert_eq!(OffchainLookup::decode(&lookup.encode()), Some(lookup));
+        assert_eq!(OffchainLookup::decode(&[0x08, 0xc3, 0x79, 0xa0]), None);
+    }
+
+    #[tokio::test]
+    async fn follows_offchain_lookup() {
+        let (url, request) = gateway(Bytes::from(vec![0xdd])).await;
+        let lookup = lookup(url);
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.ccip_read(4);
+        mock.push::<Bytes, Bytes>(vec![0xee].into()).unwrap();
+        mock.push_error(revert(lookup.encode()));
+
+        let mut tx: TypedTransaction = TransactionRequest::new().to(lookup.sender).into();
+        let res = provider.call(&tx, None).await.unwrap();
+        assert_eq!(res, Bytes::from(vec![0xee]));
+
+        let request = request.await.unwrap();
+        let sender = format!("{:?}", lookup.sender);
+        assert_eq!(request, format!("GET /{sender}/0xaabb.json HTTP/1.1"));
+
+        let latest = utils::serialize(&BlockNumber::Latest);
+        mock.assert_request("eth_call", [utils::serialize(&tx), latest.clone()]).unwrap();
+        tx.set_data(lookup.callback(Bytes::from(vec![0xdd])));
+        mock.assert_request("eth_call", [utils::serialize(&tx), latest]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn limits_redirects() {
+        let (url, _) = gateway(Bytes::from(vec![0xdd])).await;
+        let lookup = lookup(url);
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.ccip_read(1);
+        mock.push_error(revert(lookup.encode()));
+        mock.push_error(revert(lookup.encode()));
+
+        let tx: TypedTransaction = TransactionRequest::new().to(lookup.sender).into();
+        let err = provider.call(&tx, None).await.unwrap_err();
+        assert!(matches!(err, ProviderError::CcipReadError(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn ignores_lookups_of_other_contracts() {
+        let lookup = lookup("http://localhost:1/{data}".to_string());
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.ccip_read(4);
+        mock.push_error(revert(lookup.encode()));
+
+        let tx: TypedTransaction = TransactionRequest::new().to(Address::repeat_byte(2)).into();
+        let err = provider.call(&tx, None).await.unwrap_err();
+        assert_eq!(err.as_revert_data(), Some(lookup.encode()));
+    }
+}
@@ -0,0 +1,128 @@
+//! Client for the [Engine API](https://github.com/ethereum/execution-apis/tree/main/src/engine)
+//! of execution clients
+
+use crate::{JsonRpcClient, Provider, ProviderError};
+use async_trait::async_trait;
+use ethers_core::types::{
+    ExecutionPayloadV3, ForkchoiceState, ForkchoiceUpdated, GetPayloadV3Response,
+    PayloadAttributesV3, PayloadId, PayloadStatus, H256,
+};
+
+/// The methods of the Engine API that consensus clients use to drive an execution client.
+///
+/// The Engine API is served on a separate, authenticated port (usually `8551`), so the provider
+/// needs to sign its requests with the execution client's JWT secret, see
+/// [`Http::new_with_jwt`](crate::Http::new_with_jwt).
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::ForkchoiceState;
+/// use ethers_providers::{EngineApi, Http, JwtSecret, Provider};
+/// use url::Url;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let secret = JwtSecret::from_file("/path/to/jwt.hex")?;
+/// let provider = Provider::new(Http::new_with_jwt(Url::parse("http://localhost:8551")?, secret));
+///
+/// let state = ForkchoiceState::default();
+/// let updated = provider.forkchoice_updated_v3(state, None).await?;
+/// println!("{:?}", updated.payload_status.status);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait EngineApi {
+    /// Sends a new payload to the execution client for validation, `engine_newPayloadV3`
+    ///
+    /// `versioned_hashes` are the versioned hashes of the blobs of the payload's transactions.
+    async fn new_payload_v3(
+        &self,
+        payload: ExecutionPayloadV3,
+        versioned_hashes: Vec<H256>,
+        parent_beacon_block_root: H256,
+    ) -> Result<PayloadStatus, ProviderError>;
+
+    /// Updates the fork choice of the execution client and, if `attributes` are provided, starts
+    /// building a payload on top of the new head, `engine_forkchoiceUpdatedV3`
+    async fn forkchoice_updated_v3(
+        &self,
+        state: ForkchoiceState,
+        attributes: Option<PayloadAttributesV3>,
+    ) -> Result<ForkchoiceUpdated, ProviderError>;
+
+    /// Returns the payload built by the given build process, `engine_getPayloadV3`
+    async fn get_payload_v3(
+        &self,
+        payload_id: PayloadId,
+    ) -> Result<GetPayloadV3Response, ProviderError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<P: JsonRpcClient> EngineApi for Provider<P> {
+    async fn new_payload_v3(
+        &self,
+        payload: ExecutionPayloadV3,
+        versioned_hashes: Vec<H256>,
+        parent_beacon_block_root: H256,
+    ) -> Result<PayloadStatus, ProviderError> {
+        self.request("engine_newPayloadV3", (payload, versioned_hashes, parent_beacon_block_root))
+            .await
+    }
+
+    async fn forkchoice_updated_v3(
+        &self,
+        state: ForkchoiceState,
+        attributes: Option<PayloadAttributesV3>,
+    ) -> Result<ForkchoiceUpdated, ProviderError> {
+        self.request("engine_forkchoiceUpdatedV3", (state, attributes)).await
+    }
+
+    async fn get_payload_v3(
+        &self,
+        payload_id: PayloadId,
+    ) -> Result<GetPayloadV3Response, ProviderError> {
+        self.request("engine_getPayloadV3", [payload_id]).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::types::{PayloadValidity, U64};
+
+    #[tokio::test]
+    async fn drives_execution_client() {
+        let (provider, mock) = Provider::mocked();
+        let valid = PayloadStatus {
+            status: PayloadValidity::Valid,
+            latest_valid_hash: Some(H256::repeat_byte(1)),
+            validation_error: None,
+        };
+
+        let payload = ExecutionPayloadV3 { block_number: U64::from(1), ..Default::default() };
+        let hashes = vec![H256::repeat_byte(2)];
+        let root = H256::repeat_byte(3);
+        mock.push(valid.clone()).unwrap();
+        let status = provider.new_payload_v3(payload.clone(), hashes.clone(), root).await;
+        assert_eq!(status.unwrap(), valid);
+        mock.assert_request("engine_newPayloadV3", (payload, hashes, root)).unwrap();
+
+        let state = ForkchoiceState { head_block_hash: H256::repeat_byte(1), ..Default::default() };
+        let attributes = PayloadAttributesV3 { timestamp: U64::from(12), ..Default::default() };
+        let payload_id = PayloadId::repeat_byte(4);
+        let updated = ForkchoiceUpdated { payload_status: valid, payload_id: Some(payload_id) };
+        mock.push(updated.clone()).unwrap();
+        let res = provider.forkchoice_updated_v3(state.clone(), Some(attributes.clone())).await;
+        assert_eq!(res.unwrap(), updated);
+        mock.assert_request("engine_forkchoiceUpdatedV3", (state, Some(attributes))).unwrap();
+
+        let built = GetPayloadV3Response::default();
+        mock.push(built.clone()).unwrap();
+        assert_eq!(provider.get_payload_v3(payload_id).await.unwrap(), built);
+        mock.assert_request("engine_getPayloadV3", [payload_id]).unwrap();
+    }
+}
@@ -1,11 +1,14 @@
 //! [Ethereum Name Service](https://docs.ens.domains/) support
 //! Adapted from <https://github.com/hhatto/rust-ens/blob/master/src/lib.rs>
+use bech32::{ToBase32, Variant};
 use ethers_core::{
-    types::{Address, NameOrAddress, Selector, TransactionRequest, H160, H256},
+    abi::{self, ParamType, Token},
+    types::{Address, Bytes, Chain, NameOrAddress, Selector, TransactionRequest, H160, H256},
     utils::keccak256,
 };
+use sha2::{Digest, Sha256};
 
-use std::convert::TryInto;
+use std::{convert::TryInto, fmt};
 
 /// ENS registry address (`0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e`)
 pub const ENS_ADDRESS: Address = H160([
@@ -13,6 +16,80 @@ pub const ENS_ADDRESS: Address = H160([
     0, 0, 0, 0, 0, 12, 46, 7, 78, 198, 154, 13, 251, 41, 151, 186, 108, 125, 46, 30,
 ]);
 
+/// ENS UniversalResolver address of the default registry on mainnet
+/// (`0xce01f8eee7E479C928F8919abD53E553a36CeF67`), see [`universal_resolver`] for other chains
+pub const UNIVERSAL_RESOLVER_ADDRESS: Address = H160([
+    206, 1, 248, 238, 231, 228, 121, 201, 40, 248, 145, 154, 189, 83, 229, 83, 163, 108, 239, 103,
+]);
+
+/// ENS UniversalResolver address of the default registry on Goerli
+/// (`0x56522D00C410a43BFfDF00a9A569489297385790`)
+const GOERLI_UNIVERSAL_RESOLVER_ADDRESS: Address =
+    H160([86, 82, 45, 0, 196, 16, 164, 59, 255, 223, 0, 169, 165, 105, 72, 146, 151, 56, 87, 144]);
+
+/// ENS UniversalResolver address of the default registry on Sepolia
+/// (`0xc8Af999e38273D658BE1b921b88A9Ddf005769cC`)
+const SEPOLIA_UNIVERSAL_RESOLVER_ADDRESS: Address = H160([
+    200, 175, 153, 158, 56, 39, 61, 101, 139, 225, 185, 33, 184, 138, 157, 223, 0, 87, 105, 204,
+]);
+
+/// [Multicall3](https://github.com/mds1/multicall) address
+/// (`0xcA11bde05977b3631167028862bE2a173976CA11`), see [`multicall3`] for the chains it is
+/// deployed on
+pub const MULTICALL3_ADDRESS: Address =
+    H160([202, 17, 189, 224, 89, 119, 179, 99, 17, 103, 2, 136, 98, 190, 42, 23, 57, 118, 202, 17]);
+
+/// Returns the UniversalResolver of the default registry on the chain, `None` if ENS isn't
+/// deployed on it
+pub fn universal_resolver(chain: Chain) -> Option<Address> {
+    match chain {
+        Chain::Mainnet => Some(UNIVERSAL_RESOLVER_ADDRESS),
+        Chain::Goerli => Some(GOERLI_UNIVERSAL_RESOLVER_ADDRESS),
+        Chain::Sepolia => Some(SEPOLIA_UNIVERSAL_RESOLVER_ADDRESS),
+        _ => None,
+    }
+}
+
+/// Returns the Multicall3 deployment on the chain, `None` if it isn't known, e.g. on dev chains
+pub fn multicall3(chain: Chain) -> Option<Address> {
+    use Chain::*;
+
+    match chain {
+        Mainnet |
+        Ropsten |
+        Rinkeby |
+        Goerli |
+        Kovan |
+        Sepolia |
+        Optimism |
+        OptimismKovan |
+        OptimismGoerli |
+        Arbitrum |
+        ArbitrumTestnet |
+        ArbitrumGoerli |
+        ArbitrumNova |
+        Cronos |
+        BinanceSmartChain |
+        BinanceSmartChainTestnet |
+        XDai |
+        Chiado |
+        Polygon |
+        PolygonMumbai |
+        Fantom |
+        FantomTestnet |
+        Moonbeam |
+        Moonriver |
+        Moonbase |
+        Evmos |
+        Avalanche |
+        AvalancheFuji |
+        Celo |
+        CeloAlfajores |
+        Aurora => Some(MULTICALL3_ADDRESS),
+        _ => None,
+    }
+}
+
 // Selectors
 const ENS_REVERSE_REGISTRAR_DOMAIN: &str = "addr.reverse";
 
@@ -25,12 +102,26 @@ pub const ADDR_SELECTOR: Selector = [59, 59, 87, 222];
 /// name(bytes32)
 pub const NAME_SELECTOR: Selector = [105, 31, 52, 49];
 
+/// addr(bytes32, uint256), [ENSIP-9](https://docs.ens.domains/ens-improvement-proposals/ensip-9-multichain-address-resolution)
+pub const MULTICOIN_ADDR_SELECTOR: Selector = [241, 203, 126, 6];
+
 /// text(bytes32, string)
 pub const FIELD_SELECTOR: Selector = [89, 209, 212, 60];
 
 /// supportsInterface(bytes4 interfaceID)
 pub const INTERFACE_SELECTOR: Selector = [1, 255, 201, 167];
 
+/// reverse(bytes reverseName) of the UniversalResolver
+pub const REVERSE_SELECTOR: Selector = [236, 17, 200, 35];
+
+/// aggregate3((address target, bool allowFailure, bytes callData)[]) of Multicall3
+const AGGREGATE3_SELECTOR: Selector = [130, 173, 86, 203];
+
+/// resolve(bytes name, bytes data), also the interface id of
+/// [ENSIP-10](https://docs.ens.domains/ens-improvement-proposals/ensip-10-wildcard-resolution)
+/// extended resolvers
+pub const RESOLVE_SELECTOR: Selector = [144, 97, 185, 35];
+
 /// Returns a transaction request for calling the `resolver` method on the ENS server
 pub fn get_resolver<T: Into<NameOrAddress>>(ens_address: T, name: &str) -> TransactionRequest {
     // keccak256('resolver(bytes32)')
@@ -70,6 +161,89 @@ pub fn resolve<T: Into<NameOrAddress>>(
     }
 }
 
+/// How many [CCIP-Read](https://eips.ethereum.org/EIPS/eip-3668) lookups of an extended resolver
+/// are followed, if the provider doesn't follow lookups already
+pub const MAX_CCIP_REDIRECTS: usize = 4;
+
+/// Returns a transaction request for calling `resolve(bytes,bytes)` on an
+/// [ENSIP-10](https://docs.ens.domains/ens-improvement-proposals/ensip-10-wildcard-resolution)
+/// extended resolver, `data` is the calldata of the record that is resolved, e.g. `addr(bytes32)`
+pub fn resolve_extended<T: Into<NameOrAddress>>(
+    resolver_address: T,
+    name: &str,
+    data: &[u8],
+) -> Result<TransactionRequest, String> {
+    let args = abi::encode(&[Token::Bytes(dns_encode(name)?), Token::Bytes(data.to_vec())]);
+    Ok(TransactionRequest {
+        data: Some([&RESOLVE_SELECTOR[..], &args].concat().into()),
+        to: Some(resolver_address.into()),
+        ..Default::default()
+    })
+}
+
+/// Returns the calldata of the UniversalResolver's `reverse(bytes)` for the address
+pub fn reverse_extended(addr: Address) -> Result<Vec<u8>, String> {
+    let args = abi::encode(&[Token::Bytes(dns_encode(&reverse_address(addr))?)]);
+    Ok([&REVERSE_SELECTOR[..], &args].concat())
+}
+
+/// Returns a transaction request for calling `aggregate3` on the [Multicall3](MULTICALL3_ADDRESS)
+/// deployment at `multicall`, which performs all calls and returns whether each succeeded along
+/// with its return or revert data, see [`decode_aggregate3`]
+pub fn aggregate3(
+    multicall: Address,
+    calls: impl IntoIterator<Item = (Address, Vec<u8>)>,
+) -> TransactionRequest {
+    let calls = calls
+        .into_iter()
+        .map(|(target, data)| {
+            Token::Tuple(vec![Token::Address(target), Token::Bool(true), Token::Bytes(data)])
+        })
+        .collect();
+    let args = abi::encode(&[Token::Array(calls)]);
+    TransactionRequest {
+        data: Some([&AGGREGATE3_SELECTOR[..], &args].concat().into()),
+        to: Some(multicall.into()),
+        ..Default::default()
+    }
+}
+
+/// Decodes the `(bool success, bytes returnData)[]` returned by `aggregate3`
+pub fn decode_aggregate3(data: &[u8]) -> Option<Vec<(bool, Vec<u8>)>> {
+    let result = ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes]);
+    abi::decode(&[ParamType::Array(Box::new(result))], data)
+        .ok()?
+        .pop()?
+        .into_array()?
+        .into_iter()
+        .map(|result| {
+            let mut result = result.into_tuple()?.into_iter();
+            Some((result.next()?.into_bool()?, result.next()?.into_bytes()?))
+        })
+        .collect()
+}
+
+/// Returns the parent of a name, `None` for the root
+pub fn parent(name: &str) -> Option<&str> {
+    if name.is_empty() {
+        return None
+    }
+    Some(name.split_once('.').map(|(_, parent)| parent).unwrap_or_default())
+}
+
+/// Returns the DNS wire format of a name, the length prefixed labels terminated by the empty root
+/// label
+pub fn dns_encode(name: &str) -> Result<Vec<u8>, String> {
+    let mut encoded = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        let len: u8 = label.len().try_into().map_err(|_| format!("label `{label}` is too long"))?;
+        encoded.push(len);
+        encoded.extend_from_slice(label.as_bytes());
+    }
+    encoded.push(0);
+    Ok(encoded)
+}
+
 /// Returns the reverse-registrar name of an address.
 pub fn reverse_address(addr: Address) -> String {
     format!("{addr:?}.{ENS_REVERSE_REGISTRAR_DOMAIN}")[2..].to_string()
@@ -104,6 +278,109 @@ pub fn parameterhash(name: &str) -> Vec<u8> {
     }
 }
 
+/// [SLIP-44](https://github.com/satoshilabs/slips/blob/master/slip-0044.md) coin types of the
+/// commonly resolved chains
+pub mod coin_type {
+    /// Bitcoin
+    pub const BTC: u64 = 0;
+    /// Litecoin
+    pub const LTC: u64 = 2;
+    /// Dogecoin
+    pub const DOGE: u64 = 3;
+    /// Ethereum, the record of [`addr(bytes32)`](super::ADDR_SELECTOR)
+    pub const ETH: u64 = 60;
+    /// Ethereum Classic
+    pub const ETC: u64 = 61;
+    /// Solana
+    pub const SOL: u64 = 501;
+
+    /// Returns the coin type of an EVM chain,
+    /// [ENSIP-11](https://docs.ens.domains/ens-improvement-proposals/ensip-11-evmchain-address-resolution)
+    pub const fn evm(chain_id: u64) -> u64 {
+        0x8000_0000 | chain_id
+    }
+}
+
+/// An address of any chain as stored by an ENS resolver, in the binary representation of its chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MulticoinAddress {
+    /// The SLIP-44 coin type of the chain, see [`coin_type`]
+    pub coin_type: u64,
+    /// The address, e.g. the `scriptPubkey` of Bitcoin addresses
+    pub bytes: Bytes,
+}
+
+impl MulticoinAddress {
+    pub fn new(coin_type: u64, bytes: impl Into<Bytes>) -> Self {
+        Self { coin_type, bytes: bytes.into() }
+    }
+
+    /// Returns the address of Ethereum and other EVM chains
+    pub fn to_evm_address(&self) -> Option<Address> {
+        let evm = matches!(self.coin_type, coin_type::ETH | coin_type::ETC) ||
+            self.coin_type & 0x8000_0000 != 0;
+        (evm && self.bytes.len() == 20).then(|| Address::from_slice(&self.bytes))
+    }
+
+    /// Returns the address of a Bitcoin-like chain encoded from its `scriptPubkey`: P2PKH and
+    /// P2SH scripts are base58check encoded, segwit programs are bech32 (version 0) or bech32m
+    /// encoded
+    pub fn to_bitcoin_address(&self) -> Option<String> {
+        let (p2pkh, p2sh, hrp) = match self.coin_type {
+            coin_type::BTC => (0x00, 0x05, Some("bc")),
+            coin_type::LTC => (0x30, 0x32, Some("ltc")),
+            coin_type::DOGE => (0x1e, 0x16, None),
+            _ => return None,
+        };
+        match &self.bytes[..] {
+            // OP_DUP OP_HASH160 <hash> OP_EQUALVERIFY OP_CHECKSIG
+            [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+                Some(base58check(p2pkh, hash))
+            }
+            // OP_HASH160 <hash> OP_EQUAL
+            [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => Some(base58check(p2sh, hash)),
+            // OP_n <program>
+            [version @ (0x00 | 0x51..=0x60), len @ 0x02..=0x28, program @ ..]
+                if program.len() == *len as usize =>
+            {
+                let (version, variant) = match version {
+                    0x00 => (0, Variant::Bech32),
+                    n => (n - 0x50, Variant::Bech32m),
+                };
+                let mut data = vec![bech32::u5::try_from_u8(version).ok()?];
+                data.extend(program.to_base32());
+                bech32::encode(hrp?, data, variant).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the base58 encoded address of Solana
+    pub fn to_solana_address(&self) -> Option<String> {
+        (self.coin_type == coin_type::SOL && self.bytes.len() == 32)
+            .then(|| bs58::encode(&self.bytes).into_string())
+    }
+}
+
+/// Formats the address the way its chain does, or as hex if the chain is unknown
+impl fmt::Display for MulticoinAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(addr) = self.to_evm_address() {
+            return write!(f, "{}", ethers_core::utils::to_checksum(&addr, None))
+        }
+        match self.to_bitcoin_address().or_else(|| self.to_solana_address()) {
+            Some(addr) => f.write_str(&addr),
+            None => write!(f, "{}", self.bytes),
+        }
+    }
+}
+
+fn base58check(version: u8, payload: &[u8]) -> String {
+    let data = [&[version][..], payload].concat();
+    let checksum = Sha256::digest(Sha256::digest(&data));
+    bs58::encode([&data[..], &checksum[..4]].concat()).into_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,6 +403,92 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dns_encode() {
+        assert_eq!(dns_encode("").unwrap(), vec![0]);
+        assert_eq!(dns_encode("foo.eth").unwrap(), b"\x03foo\x03eth\x00".to_vec());
+        assert!(dns_encode(&"a".repeat(256)).is_err());
+
+        assert_eq!(parent("sub.foo.eth"), Some("foo.eth"));
+        assert_eq!(parent("eth"), Some(""));
+        assert_eq!(parent(""), None);
+        assert_eq!(RESOLVE_SELECTOR, ethers_core::utils::id("resolve(bytes,bytes)"));
+    }
+
+    #[test]
+    fn test_aggregate3() {
+        assert_eq!(REVERSE_SELECTOR, ethers_core::utils::id("reverse(bytes)"));
+        assert_eq!(
+            AGGREGATE3_SELECTOR,
+            ethers_core::utils::id("aggregate3((address,bool,bytes)[])")
+        );
+        assert_eq!(
+            UNIVERSAL_RESOLVER_ADDRESS,
+            "0xce01f8eee7E479C928F8919abD53E553a36CeF67".parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            MULTICALL3_ADDRESS,
+            "0xcA11bde05977b3631167028862bE2a173976CA11".parse::<Address>().unwrap()
+        );
+        assert_eq!(
+            universal_resolver(Chain::Goerli),
+            Some("0x56522D00C410a43BFfDF00a9A569489297385790".parse().unwrap())
+        );
+        assert_eq!(
+            universal_resolver(Chain::Sepolia),
+            Some("0xc8Af999e38273D658BE1b921b88A9Ddf005769cC".parse().unwrap())
+        );
+        assert_eq!(universal_resolver(Chain::Polygon), None);
+        assert_eq!(multicall3(Chain::Polygon), Some(MULTICALL3_ADDRESS));
+        assert_eq!(multicall3(Chain::AnvilHardhat), None);
+
+        let results = abi::encode(&[Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(vec![1, 2])]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![])]),
+        ])]);
+        assert_eq!(decode_aggregate3(&results).unwrap(), vec![(true, vec![1, 2]), (false, vec![])]);
+        assert!(decode_aggregate3(&[1, 2, 3]).is_none());
+    }
+
+    #[test]
+    fn test_multicoin_address() {
+        assert_eq!(MULTICOIN_ADDR_SELECTOR, ethers_core::utils::id("addr(bytes32,uint256)"));
+
+        for (coin_type, script, expected) in [
+            (
+                coin_type::BTC,
+                "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac",
+                "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa",
+            ),
+            (
+                coin_type::BTC,
+                "a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1887",
+                "3Ai1JZ8pdJb2ksieUV8FsxSNVJCpoPi8W6",
+            ),
+            (
+                coin_type::BTC,
+                "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+                "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4",
+            ),
+            (
+                coin_type::LTC,
+                "76a914a5f4d12ce3685781b227c1f39548ddef429e978388ac",
+                "LaMT348PWRnrqeeWArpwQPbuanpXDZGEUz",
+            ),
+        ] {
+            let addr = MulticoinAddress::new(coin_type, hex::decode(script).unwrap());
+            assert_eq!(addr.to_bitcoin_address().unwrap(), expected);
+            assert_eq!(addr.to_string(), expected);
+        }
+
+        let addr = MulticoinAddress::new(coin_type::SOL, vec![0; 32]);
+        assert_eq!(addr.to_string(), "11111111111111111111111111111111");
+
+        let addr = MulticoinAddress::new(coin_type::evm(10), Address::repeat_byte(0xab).0.to_vec());
+        assert_eq!(addr.to_evm_address(), Some(Address::repeat_byte(0xab)));
+        assert_eq!(MulticoinAddress::new(coin_type::BTC, vec![1, 2]).to_string(), "0x0102");
+    }
+
     #[test]
     fn test_parametershash() {
         assert_eq!(
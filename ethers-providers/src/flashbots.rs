@@ -0,0 +1,197 @@
+//! Client for the [Flashbots](https://docs.flashbots.net/flashbots-auction/advanced/rpc-endpoint)
+//! bundle RPC methods
+
+use crate::{Middleware, ProviderError};
+use async_trait::async_trait;
+use ethers_core::types::{
+    BundleRequest, BundleStats, BundleStatsRequest, CallBundleRequest, CallBundleResponse,
+    MevBundle, SendBundleResponse, SimBundleOverrides, SimBundleResponse, H256, U64,
+};
+
+/// The RPC methods of the Flashbots relay and the MEV-Share matchmaker.
+///
+/// Implemented for every [`Middleware`], the requests are sent to the underlying
+/// [`Provider`](crate::Provider) of the stack. The relay only accepts requests signed by the
+/// searcher, see [`Http::new_with_flashbots_signer`](crate::Http::new_with_flashbots_signer).
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::{k256::ecdsa::SigningKey, types::BundleRequest};
+/// use ethers_providers::{FlashbotsApi, Http, Provider};
+/// use url::Url;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let key = SigningKey::from_bytes(&[0x11; 32])?;
+/// let url = Url::parse("https://relay.flashbots.net")?;
+/// let relay = Provider::new(Http::new_with_flashbots_signer(url, key));
+///
+/// let bundle = BundleRequest::new(vec![/* signed transactions */], 17_000_000u64);
+/// let sent = relay.send_bundle(bundle).await?;
+/// let stats = relay.get_bundle_stats(sent.bundle_hash, 17_000_000u64).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait FlashbotsApi {
+    /// Sends a bundle to the relay, `eth_sendBundle`
+    async fn send_bundle(&self, bundle: BundleRequest)
+        -> Result<SendBundleResponse, ProviderError>;
+
+    /// Sends a bundle to the MEV-Share matchmaker, `mev_sendBundle`
+    async fn mev_send_bundle(&self, bundle: MevBundle)
+        -> Result<SendBundleResponse, ProviderError>;
+
+    /// Returns the status of a bundle that targeted the given block,
+    /// `flashbots_getBundleStats`
+    async fn get_bundle_stats<B: Into<U64> + Send + Sync>(
+        &self,
+        bundle_hash: H256,
+        block_number: B,
+    ) -> Result<BundleStats, ProviderError>;
+
+    /// Simulates a bundle on top of the given state without submitting it, `eth_callBundle`
+    ///
+    /// The response contains the gas used, revert reason and coinbase payment of every
+    /// transaction of the bundle.
+    async fn call_bundle(
+        &self,
+        bundle: CallBundleRequest,
+    ) -> Result<CallBundleResponse, ProviderError>;
+
+    /// Simulates a MEV-Share bundle without submitting it, `mev_simBundle`
+    async fn mev_sim_bundle(
+        &self,
+        bundle: MevBundle,
+        overrides: SimBundleOverrides,
+    ) -> Result<SimBundleResponse, ProviderError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> FlashbotsApi for M {
+    async fn send_bundle(
+        &self,
+        bundle: BundleRequest,
+    ) -> Result<SendBundleResponse, ProviderError> {
+        self.provider().request("eth_sendBundle", [bundle]).await
+    }
+
+    async fn mev_send_bundle(
+        &self,
+        bundle: MevBundle,
+    ) -> Result<SendBundleResponse, ProviderError> {
+        self.provider().request("mev_sendBundle", [bundle]).await
+    }
+
+    async fn get_bundle_stats<B: Into<U64> + Send + Sync>(
+        &self,
+        bundle_hash: H256,
+        block_number: B,
+    ) -> Result<BundleStats, ProviderError> {
+        let request = BundleStatsRequest { bundle_hash, block_number: block_number.into() };
+        self.provider().request("flashbots_getBundleStats", [request]).await
+    }
+
+    async fn call_bundle(
+        &self,
+        bundle: CallBundleRequest,
+    ) -> Result<CallBundleResponse, ProviderError> {
+        self.provider().request("eth_callBundle", [bundle]).await
+    }
+
+    async fn mev_sim_bundle(
+        &self,
+        bundle: MevBundle,
+        overrides: SimBundleOverrides,
+    ) -> Result<SimBundleResponse, ProviderError> {
+        self.provider().request("mev_simBundle", (bundle, overrides)).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::Provider;
+    use ethers_core::types::{BundleItem, Bytes, Inclusion, U256};
+
+    #[tokio::test]
+    async fn sends_bundles() {
+        let (provider, mock) = Provider::mocked();
+        let sent = SendBundleResponse { bundle_hash: H256::repeat_byte(1) };
+        let tx = Bytes::from(vec![0x02, 0xf8]);
+
+        let bundle = BundleRequest::new(vec![tx.clone()], 10u64);
+        mock.push(sent.clone()).unwrap();
+        assert_eq!(provider.send_bundle(bundle.clone()).await.unwrap(), sent);
+        mock.assert_request("eth_sendBundle", [bundle]).unwrap();
+
+        let bundle = MevBundle::new(
+            Inclusion::range(10u64, 12u64),
+            vec![BundleItem::Tx { tx, can_revert: false }],
+        );
+        mock.push(sent.clone()).unwrap();
+        assert_eq!(provider.mev_send_bundle(bundle.clone()).await.unwrap(), sent);
+        mock.assert_request("mev_sendBundle", [bundle]).unwrap();
+
+        let stats = BundleStats { is_simulated: true, ..Default::default() };
+        mock.push(stats.clone()).unwrap();
+        assert_eq!(provider.get_bundle_stats(sent.bundle_hash, 10u64).await.unwrap(), stats);
+        let request = BundleStatsRequest { bundle_hash: sent.bundle_hash, block_number: 10.into() };
+        mock.assert_request("flashbots_getBundleStats", [request]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn simulates_bundles() {
+        let (provider, mock) = Provider::mocked();
+        let tx = Bytes::from(vec![0x02, 0xf8]);
+
+        let bundle = CallBundleRequest::new(vec![tx.clone()], 10u64);
+        mock.push(serde_json::json!({
+            "bundleGasPrice": "1",
+            "bundleHash": H256::repeat_byte(1),
+            "coinbaseDiff": "21000",
+            "ethSentToCoinbase": "0",
+            "gasFees": "21000",
+            "results": [{
+                "coinbaseDiff": "21000",
+                "ethSentToCoinbase": "0",
+                "fromAddress": "0x02A727155aeF8609c9f7F2179b2a1f560B39F5A0",
+                "gasFees": "21000",
+                "gasPrice": "1",
+                "gasUsed": 21000,
+                "toAddress": null,
+                "txHash": H256::repeat_byte(2),
+                "error": "execution reverted"
+            }],
+            "stateBlockNumber": 9,
+            "totalGasUsed": 21000
+        }))
+        .unwrap();
+        let res = provider.call_bundle(bundle.clone()).await.unwrap();
+        assert_eq!(res.coinbase_diff, U256::from(21000));
+        assert!(!res.results[0].is_success());
+        mock.assert_request("eth_callBundle", [bundle]).unwrap();
+
+        let bundle = MevBundle::new(
+            Inclusion::at_block(10u64),
+            vec![BundleItem::Tx { tx, can_revert: false }],
+        );
+        let overrides = SimBundleOverrides { timestamp: Some(100u64.into()), ..Default::default() };
+        let sim = SimBundleResponse {
+            success: true,
+            error: None,
+            state_block: 9u64.into(),
+            mev_gas_price: 1u64.into(),
+            profit: 21000u64.into(),
+            refundable_value: 0u64.into(),
+            gas_used: 21000u64.into(),
+            logs: None,
+        };
+        mock.push(sim.clone()).unwrap();
+        assert_eq!(provider.mev_sim_bundle(bundle.clone(), overrides.clone()).await.unwrap(), sim);
+        mock.assert_request("mev_simBundle", (bundle, overrides)).unwrap();
+    }
+}
@@ -8,7 +8,12 @@ mod transports;
 pub use transports::*;
 
 mod provider;
-pub use provider::{is_local_endpoint, FilterKind, Provider, ProviderError, ProviderExt};
+pub use provider::{
+    is_local_endpoint, FilterKind, NodeClient, Provider, ProviderError, ProviderExt,
+};
+
+mod capabilities;
+pub use capabilities::NodeCapabilities;
 
 // types for the admin api
 pub mod admin;
@@ -18,7 +23,7 @@ pub use admin::{NodeInfo, PeerInfo};
 pub mod ens;
 
 mod pending_transaction;
-pub use pending_transaction::PendingTransaction;
+pub use pending_transaction::{PendingTransaction, PendingTxStatus, PendingTxStatusStream};
 
 mod pending_escalator;
 pub use pending_escalator::EscalatingPending;
@@ -26,17 +31,59 @@ pub use pending_escalator::EscalatingPending;
 mod log_query;
 pub use log_query::{LogQuery, LogQueryError};
 
+mod response_cache;
+
+mod ccip;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 mod stream;
 pub use futures_util::StreamExt;
 pub use stream::{
-    interval, FilterWatcher, TransactionStream, DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL,
+    interval, BlockStream, FilterWatcher, GetBlockError, GetTransactionError, LogFilterManager,
+    TransactionStream, DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL,
 };
 
 mod pubsub;
-pub use pubsub::{PubsubClient, SubscriptionStream};
+pub use pubsub::{
+    BackfilledLogStream, BlockHashStream, DynSubscriptionStream, FullPendingTxStream, PubsubClient,
+    Reorg, ReorgStream, SubscribeOrPoll, SubscriptionStream, DEFAULT_PENDING_TX_CONCURRENCY,
+    DEFAULT_REORG_DEPTH,
+};
 
 pub mod call_raw;
 pub mod erc;
+pub mod simulate;
+
+mod engine;
+pub use engine::EngineApi;
+
+mod bundler;
+pub use bundler::BundlerApi;
+
+mod flashbots;
+pub use flashbots::FlashbotsApi;
+
+mod otterscan;
+pub use otterscan::OtterscanApi;
+
+#[cfg(feature = "alchemy")]
+pub mod alchemy;
+#[cfg(feature = "alchemy")]
+pub use alchemy::AlchemyApi;
+
+mod namespace;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use async_trait::async_trait;
+}
+
+pub mod beacon;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod test_server;
 
 use async_trait::async_trait;
 use auto_impl::auto_impl;
@@ -51,7 +98,7 @@ use url::Url;
 
 // feature-enabled support for dev-rpc methods
 #[cfg(feature = "dev-rpc")]
-pub use provider::dev_rpc::DevRpcMiddleware;
+pub use provider::dev_rpc::{DevNode, DevRpcMiddleware};
 
 /// A simple gas escalation policy
 pub type EscalationPolicy = Box<dyn Fn(U256, usize) -> U256 + Send + Sync>;
@@ -77,6 +124,16 @@ pub trait JsonRpcClient: Debug + Send + Sync {
     where
         T: Debug + Serialize + Send + Sync,
         R: DeserializeOwned + Send;
+
+    /// Returns this client as a type-erased [`PubsubClientWrapper`] if the transport supports
+    /// `eth_subscribe` subscriptions.
+    ///
+    /// This allows code that is generic over the transport to detect subscription support at
+    /// runtime, see [`Middleware::subscribe_or_watch_logs`]. Transports which implement
+    /// [`PubsubClient`] should override this.
+    fn as_pubsub(&self) -> Option<&dyn PubsubClientWrapper> {
+        None
+    }
 }
 
 pub trait FromErr<T> {
@@ -273,6 +330,37 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().lookup_address(address).await.map_err(FromErr::from)
     }
 
+    /// Returns the addresses the `ens_names` resolve to, `None` for names that can't be resolved.
+    ///
+    /// The names are resolved with the ENS UniversalResolver in a single Multicall3 `eth_call`,
+    /// only names served offchain are resolved one by one. On chains without a known
+    /// UniversalResolver, see [`ens::universal_resolver`], or with a custom ENS registry, every
+    /// name is resolved one by one.
+    async fn resolve_names(&self, ens_names: &[&str]) -> Result<Vec<Option<Address>>, Self::Error> {
+        self.inner().resolve_names(ens_names).await.map_err(FromErr::from)
+    }
+
+    /// Returns the ENS names of the `addresses`, `None` for addresses without a reverse record or
+    /// whose name doesn't resolve back to them.
+    ///
+    /// Like [`Middleware::resolve_names`] the names are looked up in a single `eth_call`.
+    async fn lookup_addresses(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<String>>, Self::Error> {
+        self.inner().lookup_addresses(addresses).await.map_err(FromErr::from)
+    }
+
+    /// Returns the address of another chain that the `ens_name` resolves to, see
+    /// [`ens::coin_type`] for the coin types
+    async fn resolve_multicoin(
+        &self,
+        ens_name: &str,
+        coin_type: u64,
+    ) -> Result<ens::MulticoinAddress, Self::Error> {
+        self.inner().resolve_multicoin(ens_name, coin_type).await.map_err(FromErr::from)
+    }
+
     async fn resolve_avatar(&self, ens_name: &str) -> Result<Url, Self::Error> {
         self.inner().resolve_avatar(ens_name).await.map_err(FromErr::from)
     }
@@ -350,6 +438,16 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().get_net_version().await.map_err(FromErr::from)
     }
 
+    /// Returns the number of peers currently connected to the node, `net_peerCount`
+    async fn get_net_peer_count(&self) -> Result<U64, Self::Error> {
+        self.inner().get_net_peer_count().await.map_err(FromErr::from)
+    }
+
+    /// Returns true if the node is listening for network connections, `net_listening`
+    async fn get_net_listening(&self) -> Result<bool, Self::Error> {
+        self.inner().get_net_listening().await.map_err(FromErr::from)
+    }
+
     async fn get_balance<T: Into<NameOrAddress> + Send + Sync>(
         &self,
         from: T,
@@ -390,6 +488,23 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().estimate_eip1559_fees(estimator).await.map_err(FromErr::from)
     }
 
+    /// Estimates the max fee per gas and max priority fee per gas of an EIP-1559 transaction from
+    /// the fee history of recent blocks, according to the given `strategy`.
+    async fn estimate_eip1559_fees_from_history(
+        &self,
+        strategy: FeeHistoryStrategy,
+    ) -> Result<(U256, U256), Self::Error> {
+        self.inner().estimate_eip1559_fees_from_history(strategy).await.map_err(FromErr::from)
+    }
+
+    /// Returns the node's suggestion for the max priority fee per gas of an EIP-1559 transaction.
+    ///
+    /// If the node doesn't support `eth_maxPriorityFeePerGas`, the fee is estimated with the
+    /// provider's [`PriorityFeeFallback`](ethers_core::types::PriorityFeeFallback).
+    async fn max_priority_fee_per_gas(&self) -> Result<U256, Self::Error> {
+        self.inner().max_priority_fee_per_gas().await.map_err(FromErr::from)
+    }
+
     async fn get_accounts(&self) -> Result<Vec<Address>, Self::Error> {
         self.inner().get_accounts().await.map_err(FromErr::from)
     }
@@ -431,7 +546,10 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().get_logs(filter).await.map_err(FromErr::from)
     }
 
-    /// Returns a stream of logs are loaded in pages of given page size
+    /// Returns a stream of the logs matching the filter, loaded in pages of `page_size` blocks
+    /// from its `from_block` to its `to_block` or the latest block.
+    ///
+    /// Pages that exceed the limits of the node are split, see [`LogQuery`].
     fn get_logs_paginated<'a>(
         &'a self,
         filter: &Filter,
@@ -574,6 +692,10 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().txpool_content().await.map_err(FromErr::from)
     }
 
+    async fn txpool_content_from(&self, sender: Address) -> Result<TxpoolContentFrom, Self::Error> {
+        self.inner().txpool_content_from(sender).await.map_err(FromErr::from)
+    }
+
     async fn txpool_inspect(&self) -> Result<TxpoolInspect, Self::Error> {
         self.inner().txpool_inspect().await.map_err(FromErr::from)
     }
@@ -594,6 +716,31 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().debug_trace_transaction(tx_hash, trace_options).await.map_err(FromErr::from)
     }
 
+    /// Traces the given transaction with the built-in `callTracer`, returning its call tree
+    async fn debug_trace_transaction_call_tracer(
+        &self,
+        tx_hash: TxHash,
+        config: CallConfig,
+    ) -> Result<CallFrame, Self::Error> {
+        self.inner()
+            .debug_trace_transaction_call_tracer(tx_hash, config)
+            .await
+            .map_err(FromErr::from)
+    }
+
+    /// Traces the given transaction with the built-in `prestateTracer`, returning the state of the
+    /// accounts it touched before it was executed, or the state diff if `diff_mode` is set
+    async fn debug_trace_transaction_prestate_tracer(
+        &self,
+        tx_hash: TxHash,
+        config: PreStateConfig,
+    ) -> Result<PreStateFrame, Self::Error> {
+        self.inner()
+            .debug_trace_transaction_prestate_tracer(tx_hash, config)
+            .await
+            .map_err(FromErr::from)
+    }
+
     /// Executes the given call and returns a number of possible traces for it
     async fn debug_trace_call<T: Into<TypedTransaction> + Send + Sync>(
         &self,
@@ -604,9 +751,32 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().debug_trace_call(req, block, trace_options).await.map_err(FromErr::from)
     }
 
+    /// Returns up to `limit` storage slots of the contract at `address`, starting at the slot
+    /// whose keccak256 hash is `start_key`, as they were before the transaction at `tx_index` of
+    /// the block was executed
+    ///
+    /// The slots are ordered by their hash, the next page starts at the
+    /// [`next_key`](StorageRangeResult::next_key) of the result.
+    async fn debug_storage_range_at(
+        &self,
+        block_hash: H256,
+        tx_index: u64,
+        address: Address,
+        start_key: H256,
+        limit: u64,
+    ) -> Result<StorageRangeResult, Self::Error> {
+        self.inner()
+            .debug_storage_range_at(block_hash, tx_index, address, start_key, limit)
+            .await
+            .map_err(FromErr::from)
+    }
+
     // Parity `trace` support
 
     /// Executes the given call and returns a number of possible traces for it
+    ///
+    /// The changes of balances, nonces, code and storage are returned in the
+    /// [`state_diff`](BlockTrace::state_diff) if [`TraceType::StateDiff`] is requested.
     async fn trace_call<T: Into<TypedTransaction> + Send + Sync>(
         &self,
         req: T,
@@ -616,6 +786,8 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().trace_call(req, trace_type, block).await.map_err(FromErr::from)
     }
 
+    /// Executes the given calls one after another on top of the state of `block`, each call seeing
+    /// the changes of the previous ones, and returns the requested traces of each call
     async fn trace_call_many<T: Into<TypedTransaction> + Send + Sync>(
         &self,
         req: Vec<(T, Vec<TraceType>)>,
@@ -733,6 +905,18 @@ pub trait Middleware: Sync + Send + Debug {
         self.inner().subscribe_logs(filter).await.map_err(FromErr::from)
     }
 
+    /// Streams logs matching the filter via `eth_subscribe` if the transport supports
+    /// subscriptions, otherwise falls back to polling an installed filter like [`Self::watch`].
+    ///
+    /// Unlike [`Self::subscribe_logs`], this does not require the transport to implement
+    /// [`PubsubClient`], see [`JsonRpcClient::as_pubsub`].
+    async fn subscribe_or_watch_logs<'a>(
+        &'a self,
+        filter: &Filter,
+    ) -> Result<SubscribeOrPoll<'a, Self::Provider, Log>, Self::Error> {
+        self.inner().subscribe_or_watch_logs(filter).await.map_err(FromErr::from)
+    }
+
     async fn fee_history<T: Into<U256> + serde::Serialize + Send + Sync>(
         &self,
         block_count: T,
@@ -745,6 +929,11 @@ pub trait Middleware: Sync + Send + Debug {
             .map_err(FromErr::from)
     }
 
+    /// Returns the access list of the transaction along with the gas it uses with the access
+    /// list, via `eth_createAccessList`.
+    ///
+    /// Nodes that don't serve the method fail with [`ProviderError::UnsupportedMethod`], so
+    /// callers can send the transaction without an access list instead.
     async fn create_access_list(
         &self,
         tx: &TypedTransaction,
@@ -752,6 +941,18 @@ pub trait Middleware: Sync + Send + Debug {
     ) -> Result<AccessListWithGasUsed, Self::Error> {
         self.inner().create_access_list(tx, block).await.map_err(FromErr::from)
     }
+
+    /// Simulates a sequence of blocks of calls on top of `block` via `eth_simulateV1`, each block
+    /// may override header fields and account state before its calls are executed.
+    ///
+    /// Not all clients support this method.
+    async fn simulate_v1(
+        &self,
+        payload: &simulate::SimulatePayload,
+        block: Option<BlockId>,
+    ) -> Result<Vec<simulate::SimulatedBlock>, Self::Error> {
+        self.inner().simulate_v1(payload, block).await.map_err(FromErr::from)
+    }
 }
 
 #[cfg(feature = "celo")]
@@ -8,6 +8,12 @@ use std::{
 };
 use thiserror::Error;
 
+/// A stream of the logs matching a filter, loaded with `eth_getLogs` in pages of block ranges,
+/// see [`Middleware::get_logs_paginated`](crate::Middleware::get_logs_paginated).
+///
+/// The pages span from the `from_block` of the filter to its `to_block`, or the latest block if it
+/// has none. If the node rejects a page because the range or the result is too large, the page is
+/// split until the node accepts it and the smaller page size is kept for the remaining pages.
 pub struct LogQuery<'a, P> {
     provider: &'a Provider<P>,
     filter: Filter,
@@ -15,6 +21,8 @@ pub struct LogQuery<'a, P> {
     page_size: u64,
     current_logs: VecDeque<Log>,
     last_block: Option<U64>,
+    /// The block range of the page that is loaded
+    page: Option<(U64, U64)>,
     state: LogQueryState<'a>,
 }
 
@@ -37,15 +45,59 @@ where
             page_size: 10000,
             current_logs: VecDeque::new(),
             last_block: None,
+            page: None,
             state: LogQueryState::Initial,
         }
     }
 
-    /// set page size for pagination
+    /// set page size for pagination, i.e. the number of blocks queried at once
     pub fn with_page_size(mut self, page_size: u64) -> Self {
-        self.page_size = page_size;
+        self.page_size = page_size.max(1);
         self
     }
+
+    /// Returns the request for the next page, or `None` if all pages were loaded
+    fn load_next_page(&mut self) -> Option<PinBoxFut<'a, Vec<Log>>> {
+        // can safely assume these are set while paginating
+        let from_block = self.from_block.unwrap();
+        let last_block = self.last_block.unwrap();
+        if from_block > last_block {
+            return None
+        }
+        let to_block = last_block.min(from_block + self.page_size - 1);
+        self.page = Some((from_block, to_block));
+        self.from_block = Some(to_block + 1);
+
+        let filter = self.filter.clone().from_block(from_block).to_block(to_block);
+        let provider = self.provider;
+        Some(Box::pin(async move { provider.get_logs(&filter).await }))
+    }
+}
+
+/// Returns true if the node rejected an `eth_getLogs` request because the block range or the
+/// number of results exceeded its limits.
+///
+/// There is no standard error for this, nodes reply with e.g. `query returned more than 10000
+/// results`, `block range is too wide` or `exceed maximum block range: 5000`. Rate limits, which
+/// some nodes also report with code `-32005`, are not matched since a smaller page would not help.
+fn is_limit_exceeded(err: &ProviderError) -> bool {
+    let err = match err.as_error_response() {
+        Some(err) => err,
+        None => return false,
+    };
+    let message = err.message.to_lowercase();
+    [
+        "query returned more than",
+        "block range",
+        "range is too large",
+        "range too large",
+        "too many blocks",
+        "response size",
+        "max results",
+        "logs matched by query exceeds",
+    ]
+    .iter()
+    .any(|s| message.contains(s))
 }
 
 macro_rules! rewake_with_new_state {
@@ -87,20 +139,17 @@ where
             }
             LogQueryState::LoadLastBlock(fut) => {
                 match futures_util::ready!(fut.as_mut().poll(ctx)) {
-                    Ok(last_block) => {
-                        self.last_block = Some(last_block);
-
-                        // this is okay because we will only enter this state when the filter is
-                        // paginatable i.e. from block is set
-                        let from_block = self.filter.get_from_block().unwrap();
-                        let to_block = from_block + self.page_size;
-                        self.from_block = Some(to_block + 1);
-
-                        let filter = self.filter.clone().from_block(from_block).to_block(to_block);
-                        let provider = self.provider;
-                        // load first page of logs
-                        let fut = Box::pin(async move { provider.get_logs(&filter).await });
-                        rewake_with_new_state!(ctx, self, LogQueryState::LoadLogs(fut));
+                    Ok(latest_block) => {
+                        self.last_block = Some(match self.filter.get_to_block() {
+                            Some(to_block) => to_block.min(latest_block),
+                            None => latest_block,
+                        });
+                        match self.load_next_page() {
+                            Some(fut) => {
+                                rewake_with_new_state!(ctx, self, LogQueryState::LoadLogs(fut));
+                            }
+                            None => Poll::Ready(None),
+                        }
                     }
                     Err(err) => Poll::Ready(Some(Err(LogQueryError::LoadLastBlockError(err)))),
                 }
@@ -110,7 +159,19 @@ where
                     self.current_logs = VecDeque::from(logs);
                     rewake_with_new_state!(ctx, self, LogQueryState::Consume);
                 }
-                Err(err) => Poll::Ready(Some(Err(LogQueryError::LoadLogsError(err)))),
+                Err(err) => match self.page {
+                    // split the page and retry if the node refused to serve it
+                    Some((from_block, to_block))
+                        if to_block > from_block && is_limit_exceeded(&err) =>
+                    {
+                        self.page_size = ((to_block - from_block).as_u64() + 1) / 2;
+                        self.from_block = Some(from_block);
+                        // the page isn't empty, so there is a next page
+                        let fut = self.load_next_page().unwrap();
+                        rewake_with_new_state!(ctx, self, LogQueryState::LoadLogs(fut));
+                    }
+                    _ => Poll::Ready(Some(Err(LogQueryError::LoadLogsError(err)))),
+                },
             },
             LogQueryState::Consume => {
                 let log = self.current_logs.pop_front();
@@ -120,22 +181,13 @@ where
                         Poll::Ready(None)
                     } else {
                         // load new logs if there are still more pages to go through
-                        // can safely assume this will always be set in this state
-                        let from_block = self.from_block.unwrap();
-                        let to_block = from_block + self.page_size;
-
-                        // no more pages to load, and everything is consumed
-                        // can safely assume this will always be set in this state
-                        if from_block > self.last_block.unwrap() {
-                            return Poll::Ready(None)
+                        match self.load_next_page() {
+                            Some(fut) => {
+                                rewake_with_new_state!(ctx, self, LogQueryState::LoadLogs(fut));
+                            }
+                            // no more pages to load, and everything is consumed
+                            None => Poll::Ready(None),
                         }
-                        // load next page
-                        self.from_block = Some(to_block + 1);
-
-                        let filter = self.filter.clone().from_block(from_block).to_block(to_block);
-                        let provider = self.provider;
-                        let fut = Box::pin(async move { provider.get_logs(&filter).await });
-                        rewake_with_new_state!(ctx, self, LogQueryState::LoadLogs(fut));
                     }
                 } else {
                     Poll::Ready(log.map(Ok))
@@ -144,3 +196,100 @@ where
         }
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{JsonRpcError, MockProvider};
+    use futures_util::StreamExt;
+
+    /// Mocks a node at block 20 that serves at most 4 blocks of logs per request and has one log
+    /// in every block, stubbing the pages the tests expect to be requested
+    fn limited_node(pages: &[(u64, u64)]) -> (Provider<MockProvider>, MockProvider) {
+        let (provider, mock) = Provider::mocked();
+        mock.expect("eth_blockNumber").returns(U64::from(20)).unwrap();
+        for &(from, to) in pages {
+            let filter = Filter::new().from_block(from).to_block(to);
+            let page = mock.expect("eth_getLogs").with_params([filter]).times(1);
+            if to - from >= 4 {
+                let message = "query returned more than 10000 results".to_string();
+                page.returns_error(JsonRpcError { code: -32005, message, data: None }).unwrap();
+            } else {
+                let logs = (from..=to)
+                    .map(|number| Log { block_number: Some(number.into()), ..Default::default() })
+                    .collect::<Vec<_>>();
+                page.returns(logs).unwrap();
+            }
+        }
+        (provider, mock)
+    }
+
+    fn assert_pages(mock: &MockProvider, pages: &[(u64, u64)]) {
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        for &(from, to) in pages {
+            let filter = Filter::new().from_block(from).to_block(to);
+            mock.assert_request("eth_getLogs", [filter]).unwrap();
+        }
+        mock.verify().unwrap();
+    }
+
+    #[tokio::test]
+    async fn splits_pages_over_limit() {
+        let pages = [(3, 12), (3, 7), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12)];
+        let (provider, mock) = limited_node(&pages);
+        let filter = Filter::new().from_block(3).to_block(12);
+        let logs = provider
+            .get_logs_paginated(&filter, 10)
+            .map(|log| log.unwrap().block_number.unwrap().as_u64())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(logs, (3..=12).collect::<Vec<_>>());
+        assert_pages(&mock, &pages);
+    }
+
+    #[test]
+    fn recognizes_limit_errors() {
+        let error = |code, message: &str| {
+            ProviderError::JsonRpcClientError(Box::new(JsonRpcError {
+                code,
+                message: message.to_string(),
+                data: None,
+            }))
+        };
+        assert!(is_limit_exceeded(&error(-32005, "query returned more than 10000 results")));
+        assert!(is_limit_exceeded(&error(-32602, "exceed maximum block range: 5000")));
+        assert!(is_limit_exceeded(&error(-32000, "Log response size exceeded.")));
+        assert!(!is_limit_exceeded(&error(
+            -32005,
+            "daily request count exceeded, request rate limited"
+        )));
+        assert!(!is_limit_exceeded(&error(-32005, "limit exceeded")));
+        assert!(!is_limit_exceeded(&error(429, "Too many requests, rate limit exceeded")));
+    }
+
+    #[tokio::test]
+    async fn returns_rate_limits_without_splitting() {
+        let (provider, mock) = Provider::mocked();
+        mock.expect("eth_blockNumber").returns(U64::from(20)).unwrap();
+        let message = "daily request count exceeded, request rate limited".to_string();
+        mock.expect("eth_getLogs")
+            .returns_error(JsonRpcError { code: -32005, message, data: None })
+            .unwrap();
+
+        let filter = Filter::new().from_block(3).to_block(12);
+        let mut logs = provider.get_logs_paginated(&filter, 10);
+        assert!(matches!(logs.next().await, Some(Err(LogQueryError::LoadLogsError(_)))));
+        assert_pages(&mock, &[(3, 12)]);
+    }
+
+    #[tokio::test]
+    async fn pages_end_at_latest_block() {
+        let pages = [(15, 17), (18, 20)];
+        let (provider, mock) = limited_node(&pages);
+        let filter = Filter::new().from_block(15);
+        let logs = provider.get_logs_paginated(&filter, 3).collect::<Vec<_>>().await;
+        assert_eq!(logs.len(), 6);
+        assert_pages(&mock, &pages);
+    }
+}
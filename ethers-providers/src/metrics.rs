@@ -0,0 +1,155 @@
+//! Metrics of the requests sent by a [`Provider`](crate::Provider), recorded with the
+//! [`metrics`](::metrics) facade.
+//!
+//! Every request that reaches the transport is recorded, labeled with its `method`. The metrics
+//! are exported by the recorder installed by the application, e.g. `metrics-exporter-prometheus`:
+//!
+//! ```ignore
+//! metrics_exporter_prometheus::PrometheusBuilder::new().install()?;
+//! ethers_providers::metrics::describe();
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::Instant;
+
+/// Counter of the requests sent
+pub const REQUESTS: &str = "ethers_provider_requests_total";
+
+/// Counter of the requests that failed
+pub const REQUEST_ERRORS: &str = "ethers_provider_request_errors_total";
+
+/// Histogram of the latency of requests in seconds
+pub const REQUEST_DURATION: &str = "ethers_provider_request_duration_seconds";
+
+/// Describes the metrics to the installed recorder.
+///
+/// This is optional, exporters use the descriptions for e.g. the `# HELP` lines of Prometheus.
+pub fn describe() {
+    ::metrics::describe_counter!(REQUESTS, "Number of JSON-RPC requests sent");
+    ::metrics::describe_counter!(REQUEST_ERRORS, "Number of JSON-RPC requests that failed");
+    ::metrics::describe_histogram!(
+        REQUEST_DURATION,
+        ::metrics::Unit::Seconds,
+        "Latency of JSON-RPC requests"
+    );
+}
+
+/// Measures a single request
+pub(crate) struct RequestTimer<'a> {
+    method: &'a str,
+    started: Instant,
+}
+
+impl<'a> RequestTimer<'a> {
+    pub(crate) fn start(method: &'a str) -> Self {
+        Self { method, started: Instant::now() }
+    }
+
+    /// Records the request and whether it succeeded
+    pub(crate) fn finish(self, success: bool) {
+        let method = self.method.to_string();
+        ::metrics::increment_counter!(REQUESTS, "method" => method.clone());
+        if !success {
+            ::metrics::increment_counter!(REQUEST_ERRORS, "method" => method.clone());
+        }
+        ::metrics::histogram!(
+            REQUEST_DURATION,
+            self.started.elapsed().as_secs_f64(),
+            "method" => method
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, Provider};
+    use ::metrics::{
+        Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Recorder, SharedString, Unit,
+    };
+    use ethers_core::types::U64;
+    use std::{
+        collections::HashMap,
+        sync::{atomic::AtomicU64, Arc, Mutex},
+    };
+
+    #[derive(Default)]
+    struct Samples(Mutex<Vec<f64>>);
+
+    impl HistogramFn for Samples {
+        fn record(&self, value: f64) {
+            self.0.lock().unwrap().push(value);
+        }
+    }
+
+    /// Keeps all metrics by their name and labels
+    #[derive(Default, Clone)]
+    struct TestRecorder {
+        counters: Arc<Mutex<HashMap<String, Arc<AtomicU64>>>>,
+        histograms: Arc<Mutex<HashMap<String, Arc<Samples>>>>,
+    }
+
+    impl TestRecorder {
+        fn key(key: &Key) -> String {
+            let labels = key.labels().map(|label| format!("{}={}", label.key(), label.value()));
+            format!("{}{{{}}}", key.name(), labels.collect::<Vec<_>>().join(","))
+        }
+
+        fn counter(&self, key: &str) -> u64 {
+            let counters = self.counters.lock().unwrap();
+            counters.get(key).map_or(0, |counter| counter.load(std::sync::atomic::Ordering::SeqCst))
+        }
+
+        fn samples(&self, key: &str) -> usize {
+            let histograms = self.histograms.lock().unwrap();
+            histograms.get(key).map_or(0, |samples| samples.0.lock().unwrap().len())
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key) -> Counter {
+            let mut counters = self.counters.lock().unwrap();
+            let counter = counters.entry(Self::key(key)).or_default().clone();
+            Counter::from_arc(counter)
+        }
+
+        fn register_gauge(&self, _: &Key) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &Key) -> Histogram {
+            let mut histograms = self.histograms.lock().unwrap();
+            let samples = histograms.entry(Self::key(key)).or_default().clone();
+            Histogram::from_arc(samples)
+        }
+    }
+
+    #[tokio::test]
+    async fn records_requests() {
+        let recorder = TestRecorder::default();
+        ::metrics::set_boxed_recorder(Box::new(recorder.clone())).unwrap();
+        describe();
+
+        let (provider, mock) = Provider::mocked();
+        mock.push(U64::from(1)).unwrap();
+        provider.get_block_number().await.unwrap();
+        provider.get_block_number().await.unwrap_err();
+
+        assert_eq!(recorder.counter("ethers_provider_requests_total{method=eth_blockNumber}"), 2);
+        assert_eq!(
+            recorder.counter("ethers_provider_request_errors_total{method=eth_blockNumber}"),
+            1
+        );
+        assert_eq!(
+            recorder.samples("ethers_provider_request_duration_seconds{method=eth_blockNumber}"),
+            2
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Typed extension traits for custom RPC namespaces
+
+/// Generates an extension trait with typed wrappers of custom RPC methods, like
+/// [`OtterscanApi`](crate::OtterscanApi) for the `ots_` methods.
+///
+/// Every method is declared with its arguments, the type of its result and the name of the RPC
+/// method. The arguments are sent as the positional params of the request, so they have to
+/// implement `Debug + Serialize + Send + Sync`, and the result has to implement
+/// `Debug + Serialize + DeserializeOwned + Send`.
+///
+/// The trait is implemented for every [`Middleware`](crate::Middleware), the requests are sent
+/// to the underlying [`Provider`](crate::Provider) of the stack and fail with a
+/// [`ProviderError`](crate::ProviderError).
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Bytes, H256, U64};
+/// use ethers_providers::{rpc_namespace, Http, Provider};
+///
+/// rpc_namespace! {
+///     /// The `acme_` methods of Acme nodes
+///     pub trait AcmeApi {
+///         /// Returns the number of transactions in the private mempool
+///         fn private_mempool_size() -> U64 = "acme_privateMempoolSize";
+///
+///         /// Submits a raw transaction to the private mempool
+///         fn send_private_transaction(tx: Bytes, max_block: U64) -> H256 =
+///             "acme_sendPrivateTransaction";
+///     }
+/// }
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let size = provider.private_mempool_size().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! rpc_namespace {
+    (
+        $(#[$attr:meta])*
+        $vis:vis trait $name:ident {
+            $(
+                $(#[$fn_attr:meta])*
+                fn $fn:ident($($arg:ident: $arg_ty:ty),* $(,)?) -> $ret:ty = $method:literal;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[cfg_attr(target_arch = "wasm32", $crate::__private::async_trait(?Send))]
+        #[cfg_attr(not(target_arch = "wasm32"), $crate::__private::async_trait)]
+        $vis trait $name {
+            $(
+                $(#[$fn_attr])*
+                async fn $fn(
+                    &self,
+                    $($arg: $arg_ty),*
+                ) -> ::core::result::Result<$ret, $crate::ProviderError>;
+            )*
+        }
+
+        #[cfg_attr(target_arch = "wasm32", $crate::__private::async_trait(?Send))]
+        #[cfg_attr(not(target_arch = "wasm32"), $crate::__private::async_trait)]
+        impl<M: $crate::Middleware> $name for M {
+            $(
+                async fn $fn(
+                    &self,
+                    $($arg: $arg_ty),*
+                ) -> ::core::result::Result<$ret, $crate::ProviderError> {
+                    $crate::Middleware::provider(self).request($method, ($($arg,)*)).await
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use crate::Provider;
+    use ethers_core::types::{Address, H256, U64};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct BundleStatus {
+        included_in: Option<U64>,
+    }
+
+    crate::rpc_namespace! {
+        /// A vendor namespace
+        trait VendorApi {
+            /// Without params
+            fn mempool_size() -> U64 = "vendor_mempoolSize";
+            fn bundle_status(hash: H256) -> Option<BundleStatus> = "vendor_bundleStatus";
+            fn balance_at(address: Address, block: u64,) -> U64 = "vendor_balanceAt";
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_typed_requests() {
+        let (provider, mock) = Provider::mocked();
+
+        mock.push(U64::from(7)).unwrap();
+        assert_eq!(provider.mempool_size().await.unwrap(), U64::from(7));
+        mock.assert_request("vendor_mempoolSize", ()).unwrap();
+
+        let status = BundleStatus { included_in: Some(U64::from(10)) };
+        mock.push(Some(status.clone())).unwrap();
+        assert_eq!(provider.bundle_status(H256::repeat_byte(1)).await.unwrap(), Some(status));
+        mock.assert_request("vendor_bundleStatus", [H256::repeat_byte(1)]).unwrap();
+
+        mock.push(U64::from(3)).unwrap();
+        let balance = provider.balance_at(Address::repeat_byte(2), 5).await.unwrap();
+        assert_eq!(balance, U64::from(3));
+        mock.assert_request("vendor_balanceAt", (Address::repeat_byte(2), 5)).unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+//! Client for the [Otterscan](https://github.com/otterscan/otterscan/blob/develop/docs/custom-jsonrpc.md)
+//! `ots_` RPC methods
+
+use crate::{Middleware, ProviderError};
+use async_trait::async_trait;
+use ethers_core::types::{Address, ContractCreator, TransactionsWithReceipts, TxHash};
+
+/// The `ots_` RPC methods of Erigon and other nodes that serve Otterscan, which index the
+/// history of addresses so it can be queried without an external indexer.
+///
+/// Implemented for every [`Middleware`], the requests are sent to the underlying
+/// [`Provider`](crate::Provider) of the stack.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::Address;
+/// use ethers_providers::{Http, OtterscanApi, Provider};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+/// let address: Address = "0x6887246668a3b87f54deb3b94ba47a6f63f32985".parse()?;
+///
+/// // the 25 most recent transactions of the address
+/// let page = provider.search_transactions_before(address, 0, 25).await?;
+/// for tx in page.txs {
+///     println!("{:?}", tx.hash);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait OtterscanApi {
+    /// Returns the version of the `ots_` methods served by the node, `ots_getApiLevel`
+    async fn get_api_level(&self) -> Result<u64, ProviderError>;
+
+    /// Returns the hash of the transaction that `sender` sent with the given nonce, or `None` if
+    /// it hasn't sent a transaction with that nonce yet, `ots_getTransactionBySenderAndNonce`
+    async fn get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> Result<Option<TxHash>, ProviderError>;
+
+    /// Returns a page of up to `page_size` transactions of `address` that were included before
+    /// `block`, from the most recent to the oldest, `ots_searchTransactionsBefore`
+    ///
+    /// The transactions of an address are the ones it sent, received or that emitted logs or
+    /// made internal calls involving it. A `block` of `0` starts at the latest block.
+    async fn search_transactions_before(
+        &self,
+        address: Address,
+        block: u64,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts, ProviderError>;
+
+    /// Returns a page of up to `page_size` transactions of `address` that were included after
+    /// `block`, from the most recent to the oldest, `ots_searchTransactionsAfter`
+    ///
+    /// A `block` of `0` starts at the genesis block.
+    async fn search_transactions_after(
+        &self,
+        address: Address,
+        block: u64,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts, ProviderError>;
+
+    /// Returns the creator of the contract at `address`, or `None` if it isn't a contract,
+    /// `ots_getContractCreator`
+    async fn get_contract_creator(
+        &self,
+        address: Address,
+    ) -> Result<Option<ContractCreator>, ProviderError>;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<M: Middleware> OtterscanApi for M {
+    async fn get_api_level(&self) -> Result<u64, ProviderError> {
+        self.provider().request("ots_getApiLevel", ()).await
+    }
+
+    async fn get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: u64,
+    ) -> Result<Option<TxHash>, ProviderError> {
+        self.provider().request("ots_getTransactionBySenderAndNonce", (sender, nonce)).await
+    }
+
+    async fn search_transactions_before(
+        &self,
+        address: Address,
+        block: u64,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts, ProviderError> {
+        self.provider().request("ots_searchTransactionsBefore", (address, block, page_size)).await
+    }
+
+    async fn search_transactions_after(
+        &self,
+        address: Address,
+        block: u64,
+        page_size: usize,
+    ) -> Result<TransactionsWithReceipts, ProviderError> {
+        self.provider().request("ots_searchTransactionsAfter", (address, block, page_size)).await
+    }
+
+    async fn get_contract_creator(
+        &self,
+        address: Address,
+    ) -> Result<Option<ContractCreator>, ProviderError> {
+        self.provider().request("ots_getContractCreator", [address]).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::Provider;
+    use ethers_core::types::H256;
+
+    #[tokio::test]
+    async fn queries_address_history() {
+        let (provider, mock) = Provider::mocked();
+        let address = Address::repeat_byte(1);
+
+        mock.push(Some(H256::repeat_byte(2))).unwrap();
+        let hash = provider.get_transaction_by_sender_and_nonce(address, 7).await.unwrap();
+        assert_eq!(hash, Some(H256::repeat_byte(2)));
+        mock.assert_request("ots_getTransactionBySenderAndNonce", (address, 7)).unwrap();
+
+        let page = TransactionsWithReceipts { first_page: true, ..Default::default() };
+        mock.push(page.clone()).unwrap();
+        assert_eq!(provider.search_transactions_before(address, 0, 25).await.unwrap(), page);
+        mock.assert_request("ots_searchTransactionsBefore", (address, 0, 25)).unwrap();
+
+        mock.push(page.clone()).unwrap();
+        assert_eq!(provider.search_transactions_after(address, 100, 10).await.unwrap(), page);
+        mock.assert_request("ots_searchTransactionsAfter", (address, 100, 10)).unwrap();
+
+        mock.push::<Option<ContractCreator>, _>(None).unwrap();
+        assert_eq!(provider.get_contract_creator(address).await.unwrap(), None);
+        mock.assert_request("ots_getContractCreator", [address]).unwrap();
+    }
+}
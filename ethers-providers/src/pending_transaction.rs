@@ -1,9 +1,10 @@
 use crate::{stream::interval, JsonRpcClient, Middleware, PinBoxFut, Provider, ProviderError};
-use ethers_core::types::{Transaction, TransactionReceipt, TxHash, U64};
+use ethers_core::types::{Transaction, TransactionReceipt, TxHash, H256, U64};
 use futures_core::stream::Stream;
 use futures_util::stream::StreamExt;
 use pin_project::pin_project;
 use std::{
+    collections::VecDeque,
     fmt,
     future::Future,
     ops::Deref,
@@ -52,6 +53,10 @@ use wasm_timer::Delay;
 /// # Ok(())
 /// # }
 /// ```
+///
+/// If the block of the receipt is reorged out while waiting for more than 1 confirmation, the
+/// pending transaction waits for the transaction to be included again. Use
+/// [`PendingTransaction::status_stream`] to observe these changes.
 #[pin_project]
 pub struct PendingTransaction<'a, P> {
     tx_hash: TxHash,
@@ -60,6 +65,25 @@ pub struct PendingTransaction<'a, P> {
     state: PendingTxState<'a>,
     interval: Box<dyn Stream<Item = ()> + Send + Unpin>,
     retries_remaining: usize,
+    /// The changes of the inclusion status that were not yielded yet, only recorded for
+    /// [`PendingTxStatusStream`]
+    status: Option<VecDeque<PendingTxStatus>>,
+    /// The confirmations of the current receipt that were recorded
+    recorded_confirmations: usize,
+}
+
+/// The inclusion status of a pending transaction, see [`PendingTransaction::status_stream`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PendingTxStatus {
+    /// The transaction was included in a block and has the given number of confirmations
+    Included { block_hash: H256, block_number: U64, confirmations: usize },
+    /// The block the transaction was included in is no longer part of the chain, the transaction
+    /// is waited on again
+    Reorged { block_hash: H256 },
+    /// The transaction has the requested number of confirmations
+    Confirmed(Box<TransactionReceipt>),
+    /// The transaction was dropped from the mempool
+    Dropped,
 }
 
 const DEFAULT_RETRIES: usize = 3;
@@ -76,6 +100,8 @@ impl<'a, P: JsonRpcClient> PendingTransaction<'a, P> {
             state: PendingTxState::InitialDelay(delay),
             interval: Box::new(interval(provider.get_interval())),
             retries_remaining: DEFAULT_RETRIES,
+            status: None,
+            recorded_confirmations: 0,
         }
     }
 
@@ -120,6 +146,14 @@ impl<'a, P: JsonRpcClient> PendingTransaction<'a, P> {
         self.retries_remaining = retries;
         self
     }
+
+    /// Returns a stream of the changes of the inclusion status of the transaction, which ends
+    /// once it is [`Confirmed`](PendingTxStatus::Confirmed) or
+    /// [`Dropped`](PendingTxStatus::Dropped)
+    pub fn status_stream(mut self) -> PendingTxStatusStream<'a, P> {
+        self.status = Some(VecDeque::new());
+        PendingTxStatusStream { pending: self, done: false }
+    }
 }
 
 impl<'a, P> PendingTransaction<'a, P> {
@@ -146,6 +180,13 @@ impl<'a, P> PendingTransaction<'a, P> {
     }
 }
 
+/// Records a change of the inclusion status if it's streamed
+fn record(status: &mut Option<VecDeque<PendingTxStatus>>, change: PendingTxStatus) {
+    if let Some(status) = status {
+        status.push_back(change);
+    }
+}
+
 macro_rules! rewake_with_new_state {
     ($ctx:ident, $this:ident, $new_state:expr) => {
         *$this.state = $new_state;
@@ -247,6 +288,18 @@ impl<'a, P: JsonRpcClient> Future for PendingTransaction<'a, P> {
                     PendingTxState::PausedGettingReceipt
                 );
 
+                if let Some(receipt) = receipt.as_ref() {
+                    *this.recorded_confirmations = 1;
+                    record(
+                        this.status,
+                        PendingTxStatus::Included {
+                            block_hash: receipt.block_hash.unwrap_or_default(),
+                            block_number: receipt.block_number.unwrap_or_default(),
+                            confirmations: 1,
+                        },
+                    );
+                }
+
                 // If we requested more than 1 confirmation, we need to compare the receipt's
                 // block number and the current block
                 if *this.confirmations > 1 {
@@ -286,18 +339,62 @@ impl<'a, P: JsonRpcClient> Future for PendingTransaction<'a, P> {
                 let inclusion_block = receipt
                     .block_number
                     .expect("Receipt did not have a block number. This should never happen");
-                // if the transaction has at least K confirmations, return the receipt
+                let confirmations = (current_block + 1).saturating_sub(inclusion_block).as_usize();
+                if confirmations > *this.recorded_confirmations {
+                    *this.recorded_confirmations = confirmations;
+                    record(
+                        this.status,
+                        PendingTxStatus::Included {
+                            block_hash: receipt.block_hash.unwrap_or_default(),
+                            block_number: inclusion_block,
+                            confirmations,
+                        },
+                    );
+                }
+                // if the transaction has at least K confirmations, check that the block of the
+                // receipt is still part of the chain before returning it
                 // (subtract 1 since the tx already has 1 conf when it's mined)
                 if current_block > inclusion_block + *this.confirmations - 1 {
-                    let receipt = Some(receipt);
-                    *this.state = PendingTxState::Completed;
-                    return Poll::Ready(Ok(receipt))
+                    let fut = Box::pin(this.provider.get_transaction_receipt(*this.tx_hash));
+                    *this.state = PendingTxState::VerifyingReceipt(fut, Some(receipt));
+                    ctx.waker().wake_by_ref();
                 } else {
                     tracing::trace!(tx_hash = ?this.tx_hash, "confirmations {}/{}", current_block - inclusion_block + 1, this.confirmations);
                     *this.state = PendingTxState::PausedGettingBlockNumber(Some(receipt));
                     ctx.waker().wake_by_ref();
                 }
             }
+            PendingTxState::VerifyingReceipt(fut, receipt) => {
+                let current = match futures_util::ready!(fut.as_mut().poll(ctx)) {
+                    Ok(current) => current,
+                    Err(_) => {
+                        // If the provider errors, check the confirmations again after the
+                        // interval
+                        rewake_with_new_state!(
+                            ctx,
+                            this,
+                            PendingTxState::PausedGettingBlockNumber(receipt.take())
+                        );
+                    }
+                };
+                let receipt = receipt.take().expect("VerifyingReceipt without receipt");
+                if current.as_ref().map(|current| current.block_hash) == Some(receipt.block_hash) {
+                    *this.state = PendingTxState::Completed;
+                    return Poll::Ready(Ok(Some(receipt)))
+                }
+
+                let block_hash = receipt.block_hash.unwrap_or_default();
+                tracing::debug!(tx_hash = ?this.tx_hash, ?block_hash, "Pending tx was reorged");
+                record(this.status, PendingTxStatus::Reorged { block_hash });
+                if current.is_some() {
+                    // included in another block, count its confirmations
+                    *this.state = PendingTxState::CheckingReceipt(current);
+                } else {
+                    // back in the mempool, or dropped
+                    *this.state = PendingTxState::PausedGettingTx;
+                }
+                ctx.waker().wake_by_ref();
+            }
             PendingTxState::Completed => {
                 panic!("polled pending transaction future after completion")
             }
@@ -307,6 +404,59 @@ impl<'a, P: JsonRpcClient> Future for PendingTransaction<'a, P> {
     }
 }
 
+/// The changes of the inclusion status of a pending transaction, created by
+/// [`PendingTransaction::status_stream`]
+#[must_use = "streams do nothing unless polled"]
+pub struct PendingTxStatusStream<'a, P> {
+    pending: PendingTransaction<'a, P>,
+    done: bool,
+}
+
+impl<'a, P> PendingTxStatusStream<'a, P> {
+    /// Returns the transaction hash of the pending transaction
+    pub fn tx_hash(&self) -> TxHash {
+        self.pending.tx_hash
+    }
+
+    fn next_status(&mut self) -> Option<PendingTxStatus> {
+        self.pending.status.as_mut().and_then(VecDeque::pop_front)
+    }
+}
+
+impl<'a, P: JsonRpcClient> Stream for PendingTxStatusStream<'a, P> {
+    type Item = Result<PendingTxStatus, ProviderError>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(status) = this.next_status() {
+                return Poll::Ready(Some(Ok(status)))
+            }
+            if this.done {
+                return Poll::Ready(None)
+            }
+
+            let res = match Pin::new(&mut this.pending).poll(ctx) {
+                Poll::Ready(res) => res,
+                Poll::Pending => {
+                    return match this.next_status() {
+                        Some(status) => Poll::Ready(Some(Ok(status))),
+                        None => Poll::Pending,
+                    }
+                }
+            };
+            this.done = true;
+            match res {
+                Ok(Some(receipt)) => {
+                    record(&mut this.pending.status, PendingTxStatus::Confirmed(Box::new(receipt)))
+                }
+                Ok(None) => record(&mut this.pending.status, PendingTxStatus::Dropped),
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+    }
+}
+
 impl<'a, P> fmt::Debug for PendingTransaction<'a, P> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("PendingTransaction")
@@ -367,6 +517,10 @@ enum PendingTxState<'a> {
     /// Polling the blockchain for the current block number
     GettingBlockNumber(PinBoxFut<'a, U64>, Option<TransactionReceipt>),
 
+    /// Polling the blockchain for the receipt again to detect whether the block of the receipt
+    /// was reorged out
+    VerifyingReceipt(PinBoxFut<'a, Option<TransactionReceipt>>, Option<TransactionReceipt>),
+
     /// Future has completed and should panic if polled again
     Completed,
 }
@@ -382,9 +536,91 @@ impl<'a> fmt::Debug for PendingTxState<'a> {
             PendingTxState::GettingBlockNumber(_, _) => "GettingBlockNumber",
             PendingTxState::PausedGettingBlockNumber(_) => "PausedGettingBlockNumber",
             PendingTxState::CheckingReceipt(_) => "CheckingReceipt",
+            PendingTxState::VerifyingReceipt(_, _) => "VerifyingReceipt",
             PendingTxState::Completed => "Completed",
         };
 
         f.debug_struct("PendingTxState").field("state", &state).finish()
     }
 }
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+
+    fn receipt(block: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            block_hash: Some(H256::from_low_u64_be(block)),
+            block_number: Some(block.into()),
+            ..Default::default()
+        }
+    }
+
+    fn included(block: u64, confirmations: usize) -> PendingTxStatus {
+        PendingTxStatus::Included {
+            block_hash: H256::from_low_u64_be(block),
+            block_number: block.into(),
+            confirmations,
+        }
+    }
+
+    #[tokio::test]
+    async fn waits_again_after_reorg() {
+        let (provider, mock) = Provider::mocked();
+        let tx = Transaction { block_number: Some(10u64.into()), ..Default::default() };
+
+        mock.push(receipt(11)).unwrap();
+        mock.push(U64::from(13)).unwrap();
+        // the receipt moved to another block while waiting for the confirmations
+        mock.push(receipt(11)).unwrap();
+        mock.push(U64::from(12)).unwrap();
+        mock.push(U64::from(11)).unwrap();
+        mock.push(receipt(10)).unwrap();
+        mock.push(tx).unwrap();
+
+        let pending = PendingTransaction::new(H256::zero(), &provider)
+            .interval(Duration::from_millis(1))
+            .confirmations(2);
+        let status = pending.status_stream().map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(
+            status,
+            vec![
+                included(10, 1),
+                included(10, 2),
+                included(10, 3),
+                PendingTxStatus::Reorged { block_hash: H256::from_low_u64_be(10) },
+                included(11, 1),
+                included(11, 3),
+                PendingTxStatus::Confirmed(Box::new(receipt(11))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn waits_again_after_tx_was_reorged_out() {
+        let (provider, mock) = Provider::mocked();
+        let tx = Transaction { block_number: Some(10u64.into()), ..Default::default() };
+
+        mock.push(None::<Transaction>).unwrap();
+        mock.push(None::<TransactionReceipt>).unwrap();
+        mock.push(U64::from(12)).unwrap();
+        mock.push(receipt(10)).unwrap();
+        mock.push(tx).unwrap();
+
+        let pending = PendingTransaction::new(H256::zero(), &provider)
+            .interval(Duration::from_millis(1))
+            .confirmations(2)
+            .retries(0);
+        let status = pending.status_stream().map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(
+            status,
+            vec![
+                included(10, 1),
+                included(10, 3),
+                PendingTxStatus::Reorged { block_hash: H256::from_low_u64_be(10) },
+                PendingTxStatus::Dropped,
+            ]
+        );
+    }
+}
@@ -1,10 +1,16 @@
 use crate::{
     call_raw::CallBuilder,
+    ccip::{CcipRead, OffchainLookup},
     ens, erc, maybe,
-    pubsub::{PubsubClient, SubscriptionStream},
+    pubsub::{
+        BackfilledLogStream, DynSubscriptionStream, FullPendingTxStream, PubsubClient, ReorgStream,
+        SubscribeOrPoll, SubscriptionStream,
+    },
+    response_cache::ResponseCache,
     stream::{FilterWatcher, DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL},
-    FromErr, Http as HttpProvider, JsonRpcClient, JsonRpcClientWrapper, LogQuery, MockProvider,
-    NodeInfo, PeerInfo, PendingTransaction, QuorumProvider, RwClient, SyncingStatus,
+    FromErr, Http as HttpProvider, HttpClientError, JsonRpcClient, JsonRpcClientWrapper,
+    JsonRpcError, LogQuery, MockProvider, NodeCapabilities, NodeInfo, PeerInfo, PendingTransaction,
+    QuorumProvider, RetryClientError, RwClient, SyncingStatus,
 };
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "ws"))]
@@ -21,26 +27,42 @@ use ethers_core::{
     abi::{self, Detokenize, ParamType},
     types::{
         transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed},
-        Address, Block, BlockId, BlockNumber, BlockTrace, Bytes, Chain, EIP1186ProofResponse,
-        FeeHistory, Filter, FilterBlockOption, GethDebugTracingCallOptions,
-        GethDebugTracingOptions, GethTrace, Log, NameOrAddress, Selector, Signature, Trace,
-        TraceFilter, TraceType, Transaction, TransactionReceipt, TransactionRequest, TxHash,
-        TxpoolContent, TxpoolInspect, TxpoolStatus, H256, U256, U64,
+        Address, Block, BlockId, BlockNumber, BlockTrace, Bytes, CallConfig, CallFrame, Chain,
+        EIP1186ProofResponse, FeeHistory, FeeHistoryStrategy, Filter, FilterBlockOption,
+        GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, Log, NameOrAddress,
+        PreStateConfig, PreStateFrame, PriorityFeeFallback, Selector, Signature,
+        StorageRangeResult, Trace, TraceFilter, TraceType, Transaction, TransactionReceipt,
+        TransactionRequest, TxHash, TxpoolContent, TxpoolContentFrom, TxpoolInspect, TxpoolStatus,
+        H256, U256, U64,
     },
     utils,
 };
-use futures_util::{lock::Mutex, try_join};
+#[cfg(not(target_arch = "wasm32"))]
+use futures_timer::Delay;
+use futures_util::{
+    future::{self, try_join_all, Either},
+    lock::Mutex,
+    try_join,
+};
 use hex::FromHex;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
-    collections::VecDeque, convert::TryFrom, fmt::Debug, str::FromStr, sync::Arc, time::Duration,
+    collections::VecDeque,
+    convert::TryFrom,
+    fmt::Debug,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
 };
 use thiserror::Error;
 use tracing::trace;
 use tracing_futures::Instrument;
 use url::{ParseError, Url};
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::Delay;
 
-#[derive(Copy, Clone)]
+/// The known node clients, see [`Provider::node_client`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum NodeClient {
     Geth,
     Erigon,
@@ -95,6 +117,34 @@ pub struct Provider<P> {
     /// Unsupported node client = `Some(None)`
     /// Supported node client = `Some(Some(NodeClient))`
     _node_client: Arc<Mutex<Option<NodeClient>>>,
+    /// Cache for responses that can never change, disabled if `None`
+    response_cache: Option<Arc<ResponseCache>>,
+    /// Whether and how far `OffchainLookup` reverts of calls are followed
+    ccip_read: Option<CcipRead>,
+    /// How the priority fee is estimated if `eth_maxPriorityFeePerGas` isn't supported, chosen
+    /// by chain id if `None`
+    priority_fee_fallback: Option<PriorityFeeFallback>,
+    /// How EIP-1559 fees are estimated from the fee history, the default estimator is used if
+    /// `None`
+    fee_strategy: Option<FeeHistoryStrategy>,
+    /// How long requests may take before they are cancelled, unlimited if `None`
+    timeout: Option<Duration>,
+    /// The chain id pinned by the first `eth_chainId` request or by
+    /// [`with_chain_id`](Provider::with_chain_id), shared by clones
+    chain_id: Arc<RwLock<Option<U256>>>,
+    /// The features of the node probed by [`node_capabilities`](Provider::node_capabilities) or
+    /// pinned by [`with_capabilities`](Provider::with_capabilities), shared by clones
+    capabilities: Arc<RwLock<Option<NodeCapabilities>>>,
+    /// The polling interval learned from the chain's block time, used unless `interval` is set
+    learned_interval: Arc<RwLock<Option<LearnedInterval>>>,
+}
+
+/// A polling interval learned from the chain's block time
+#[derive(Debug, Clone, Copy)]
+struct LearnedInterval {
+    interval: Duration,
+    /// Whether it was measured by [`Provider::tune_interval`], block time hints don't replace it
+    tuned: bool,
 }
 
 impl<P> AsRef<P> for Provider<P> {
@@ -144,8 +194,98 @@ pub enum ProviderError {
 
     #[error("Attempted to sign a transaction with no available signer. Hint: did you mean to use a SignerMiddleware?")]
     SignerUnavailable,
+
+    /// An error while following an EIP-3668 `OffchainLookup`
+    #[error("CCIP-Read failed: {0}")]
+    CcipReadError(String),
+
+    /// The request was cancelled because the node didn't respond in time
+    #[error("request timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// The node doesn't serve the method, e.g. `eth_createAccessList` on nodes without EIP-2930
+    /// support
+    #[error("the node does not support `{0}`")]
+    UnsupportedMethod(String),
+}
+
+impl ProviderError {
+    /// Returns the JSON-RPC error response of the node, if this error was caused by one.
+    pub fn as_error_response(&self) -> Option<&JsonRpcError> {
+        let err = match self {
+            ProviderError::JsonRpcClientError(err) => err,
+            _ => return None,
+        };
+        if let Some(err) = err.downcast_ref::<JsonRpcError>() {
+            return Some(err)
+        }
+        if let Some(HttpClientError::JsonRpcError(err)) = err.downcast_ref() {
+            return Some(err)
+        }
+        if let Some(crate::MockError::JsonRpcError(err)) = err.downcast_ref() {
+            return Some(err)
+        }
+        #[cfg(feature = "ws")]
+        if let Some(crate::WsClientError::JsonRpcError(err)) = err.downcast_ref() {
+            return Some(err)
+        }
+        #[cfg(all(feature = "ipc", any(unix, windows)))]
+        if let Some(crate::IpcError::JsonRpcError(err)) = err.downcast_ref() {
+            return Some(err)
+        }
+        #[cfg(all(feature = "uds", unix))]
+        if let Some(crate::HttpUdsError::ClientError(HttpClientError::JsonRpcError(err))) =
+            err.downcast_ref()
+        {
+            return Some(err)
+        }
+        if let Some(RetryClientError::ProviderError(err)) = err.downcast_ref() {
+            return err.as_error_response()
+        }
+        None
+    }
+
+    /// Returns the revert data if this error was caused by a reverted call and the node included
+    /// the revert data in its error response.
+    pub fn as_revert_data(&self) -> Option<Bytes> {
+        self.as_error_response()?.as_revert_data()
+    }
+}
+
+/// Returns true if the node rejected the request because it doesn't serve the method.
+///
+/// Besides the standard `-32601` code, some nodes reply with a generic error code and one of the
+/// known messages about the method, e.g. "the method eth_getBlockReceipts does not
+/// exist/is not available". Other messages saying that something does not exist or is not
+/// available, like those about pruned state, are real errors.
+fn is_method_not_found(err: &ProviderError) -> bool {
+    let err = match err.as_error_response() {
+        Some(err) => err,
+        None => return false,
+    };
+    if err.code == -32601 {
+        return true
+    }
+    let message = err.message.trim_end_matches('.').to_lowercase();
+    if message == "method not found" || message.starts_with("unsupported method") {
+        return true
+    }
+    match message.strip_prefix("the method ").and_then(|rest| rest.split_once(' ')) {
+        Some((_, reason)) => [
+            "does not exist/is not available",
+            "does not exist",
+            "is not available",
+            "is not supported",
+            "not found",
+        ]
+        .contains(&reason),
+        None => false,
+    }
 }
 
+/// How many recent blocks [`Provider::tune_interval`] averages the block time of
+const BLOCK_TIME_SAMPLE: u64 = 10;
+
 /// Types of filters supported by the JSON-RPC.
 #[derive(Clone, Debug)]
 pub enum FilterKind<'a> {
@@ -169,6 +309,14 @@ impl<P: JsonRpcClient> Provider<P> {
             interval: None,
             from: None,
             _node_client: Arc::new(Mutex::new(None)),
+            response_cache: None,
+            ccip_read: None,
+            priority_fee_fallback: None,
+            fee_strategy: None,
+            timeout: None,
+            chain_id: Default::default(),
+            capabilities: Default::default(),
+            learned_interval: Default::default(),
         }
     }
 
@@ -190,6 +338,97 @@ impl<P: JsonRpcClient> Provider<P> {
         }
     }
 
+    /// Returns the features of the node, probing them on the first call.
+    ///
+    /// Besides `web3_clientVersion`, the latest block is requested to check for a base fee and
+    /// the genesis block is traced to check whether the `debug` and `trace` namespaces are
+    /// served. Once known, the features are shared by clones and consulted by other methods, see
+    /// [`NodeCapabilities`].
+    pub async fn node_capabilities(&self) -> Result<NodeCapabilities, ProviderError> {
+        if let Some(capabilities) = self.capabilities() {
+            return Ok(capabilities)
+        }
+
+        // the namespace is served if the node doesn't reject the method itself
+        let probe = |method: &'static str, params: Vec<serde_json::Value>| async move {
+            match self.request::<_, serde_json::Value>(method, params).await {
+                Ok(_) => Ok(true),
+                Err(err) if is_method_not_found(&err) => Ok(false),
+                Err(err) if err.as_error_response().is_some() => Ok(true),
+                Err(err) => Err(err),
+            }
+        };
+        let genesis = utils::serialize(&BlockNumber::Number(0.into()));
+        let (client_version, latest, debug, trace) = try_join!(
+            self.client_version(),
+            self.get_block(BlockNumber::Latest),
+            probe("debug_traceBlockByNumber", vec![genesis.clone(), serde_json::json!({})]),
+            probe("trace_block", vec![genesis]),
+        )?;
+        let capabilities = NodeCapabilities {
+            client: client_version.parse().ok(),
+            client_version,
+            eip1559: latest.and_then(|block| block.base_fee_per_gas).is_some(),
+            debug,
+            trace,
+            pubsub: self.inner.as_pubsub().is_some(),
+        };
+        *self.capabilities.write().unwrap_or_else(|err| err.into_inner()) =
+            Some(capabilities.clone());
+        Ok(capabilities)
+    }
+
+    /// Returns the features of the node, or `None` if they haven't been probed by
+    /// [`node_capabilities`](Provider::node_capabilities) yet
+    pub fn capabilities(&self) -> Option<NodeCapabilities> {
+        self.capabilities.read().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    /// Pins the features of the node without probing them
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: NodeCapabilities) -> Self {
+        self.capabilities = Arc::new(RwLock::new(Some(capabilities)));
+        self
+    }
+
+    /// Returns whether the node serves the method, `true` if its features aren't known
+    fn supports(&self, method: &str) -> bool {
+        self.capabilities
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .as_ref()
+            .map_or(true, |capabilities| capabilities.supports(method))
+    }
+
+    /// Returns the pinned chain id, or `None` if it hasn't been resolved yet.
+    ///
+    /// The chain id is resolved by the first call to [`get_chainid`](Middleware::get_chainid),
+    /// which is then answered without a request, or pinned with
+    /// [`with_chain_id`](Provider::with_chain_id).
+    pub fn chain_id(&self) -> Option<U256> {
+        *self.chain_id.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Pins the chain id without requesting it from the node
+    #[must_use]
+    pub fn with_chain_id(mut self, chain_id: impl Into<U256>) -> Self {
+        let chain_id = chain_id.into();
+        self.chain_id = Arc::new(RwLock::new(Some(chain_id)));
+        let tuned = self.learned_interval().filter(|learned| learned.tuned);
+        self.learned_interval = Arc::new(RwLock::new(tuned));
+        self.learn_interval_from_chain(chain_id);
+        self
+    }
+
+    /// Requests the chain id from the node and pins it, for nodes that may be reconfigured to
+    /// another chain
+    pub async fn refresh_chain_id(&self) -> Result<U256, ProviderError> {
+        let chain_id: U256 = self.request("eth_chainId", ()).await?;
+        *self.chain_id.write().unwrap_or_else(|err| err.into_inner()) = Some(chain_id);
+        self.learn_interval_from_chain(chain_id);
+        Ok(chain_id)
+    }
+
     #[must_use]
     pub fn with_sender(mut self, address: impl Into<Address>) -> Self {
         self.from = Some(address.into());
@@ -201,13 +440,81 @@ impl<P: JsonRpcClient> Provider<P> {
         T: Debug + Serialize + Send + Sync,
         R: Serialize + DeserializeOwned + Debug + Send,
     {
+        self.request_with(method, params, self.timeout).await
+    }
+
+    /// Sends the request like [`Provider::request`], but cancels it and returns
+    /// [`ProviderError::Timeout`] if the node doesn't respond within `timeout`, overriding the
+    /// provider's [`request_timeout`](Provider::set_request_timeout).
+    pub async fn request_with_timeout<T, R>(
+        &self,
+        method: &str,
+        params: T,
+        timeout: Duration,
+    ) -> Result<R, ProviderError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: Serialize + DeserializeOwned + Debug + Send,
+    {
+        self.request_with(method, params, Some(timeout)).await
+    }
+
+    async fn request_with<T, R>(
+        &self,
+        method: &str,
+        params: T,
+        timeout: Option<Duration>,
+    ) -> Result<R, ProviderError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: Serialize + DeserializeOwned + Debug + Send,
+    {
+        if !self.supports(method) {
+            return Err(ProviderError::UnsupportedMethod(method.to_string()))
+        }
+
         let span =
             tracing::trace_span!("rpc", method = method, params = ?serde_json::to_string(&params)?);
         // https://docs.rs/tracing/0.1.22/tracing/span/struct.Span.html#in-asynchronous-code
         let res = async move {
+            let cached = match self.response_cache.as_ref() {
+                Some(cache) => ResponseCache::key(method, &serde_json::to_value(&params)?)
+                    .map(|key| (cache, key)),
+                None => None,
+            };
+            if let Some((cache, key)) = cached.as_ref() {
+                if let Some(res) = cache.get(key) {
+                    trace!(rx = ?res, "cached");
+                    return Ok(serde_json::from_value(res)?)
+                }
+            }
+
             trace!("tx");
-            let res: R = self.inner.request(method, params).await.map_err(Into::into)?;
+            #[cfg(feature = "metrics")]
+            let timer = crate::metrics::RequestTimer::start(method);
+            let request = self.inner.request(method, params);
+            let res = match timeout {
+                // dropping the request future cancels it in the transport
+                Some(timeout) => {
+                    futures_util::pin_mut!(request);
+                    match future::select(request, Delay::new(timeout)).await {
+                        Either::Left((res, _)) => res.map_err(Into::into),
+                        Either::Right(_) => Err(ProviderError::Timeout(timeout)),
+                    }
+                }
+                None => request.await.map_err(Into::into),
+            };
+            #[cfg(feature = "metrics")]
+            timer.finish(res.is_ok());
+            let res: R = res?;
             trace!(rx = ?serde_json::to_string(&res)?);
+
+            if let Some((cache, key)) = cached {
+                let value = serde_json::to_value(&res)?;
+                if ResponseCache::is_final(method, &value) {
+                    cache.insert(key, value);
+                }
+            }
             Ok::<_, ProviderError>(res)
         }
         .instrument(span)
@@ -340,6 +647,11 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
             tx.set_to(addr);
         }
 
+        // chains without a base fee only accept legacy and EIP-2930 transactions
+        if self.capabilities().map_or(false, |capabilities| !capabilities.eip1559) {
+            tx.downgrade_eip1559();
+        }
+
         // fill gas price
         match tx {
             TypedTransaction::Eip2930(_) | TypedTransaction::Legacy(_) => {
@@ -456,13 +768,29 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
 
     /// Returns all receipts for a block.
     ///
-    /// Note that this uses the `eth_getBlockReceipts` RPC, which is
-    /// non-standard and currently supported by Erigon.
+    /// This uses the `eth_getBlockReceipts` RPC, if the node doesn't serve it the receipts of the
+    /// block's transactions are fetched concurrently instead.
     async fn get_block_receipts<T: Into<BlockNumber> + Send + Sync>(
         &self,
         block: T,
     ) -> Result<Vec<TransactionReceipt>, Self::Error> {
-        self.request("eth_getBlockReceipts", [block.into()]).await
+        let block = block.into();
+        match self.request("eth_getBlockReceipts", [block]).await {
+            Err(err) if is_method_not_found(&err) => {}
+            res => return res,
+        }
+
+        let hashes = self
+            .get_block(block)
+            .await?
+            .ok_or_else(|| ProviderError::CustomError(format!("Block {block} not found")))?
+            .transactions;
+        try_join_all(hashes.into_iter().map(|hash| async move {
+            self.get_transaction_receipt(hash).await?.ok_or_else(|| {
+                ProviderError::CustomError(format!("Receipt of transaction {hash:?} not found"))
+            })
+        }))
+        .await
     }
 
     /// Returns all receipts for that block. Must be done on a parity node.
@@ -480,10 +808,20 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
 
     /// Gets a heuristic recommendation of max fee per gas and max priority fee per gas for
     /// EIP-1559 compatible transactions.
+    ///
+    /// Without an `estimator` the fees are estimated with the strategy set with
+    /// [`Provider::set_fee_strategy`], if any. Otherwise the priority fee is the one of
+    /// [`Middleware::max_priority_fee_per_gas`], which falls back to the
+    /// [`PriorityFeeFallback`] of the provider if the node doesn't suggest one, and the max fee
+    /// leaves room for the base fee to rise, see [`utils::eip1559_max_fee`].
     async fn estimate_eip1559_fees(
         &self,
         estimator: Option<fn(U256, Vec<Vec<U256>>) -> (U256, U256)>,
     ) -> Result<(U256, U256), Self::Error> {
+        if let (None, Some(strategy)) = (estimator, self.fee_strategy.clone()) {
+            return self.estimate_eip1559_fees_from_history(strategy).await
+        }
+
         let base_fee_per_gas = self
             .get_block(BlockNumber::Latest)
             .await?
@@ -491,6 +829,15 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
             .base_fee_per_gas
             .ok_or_else(|| ProviderError::CustomError("EIP-1559 not activated".into()))?;
 
+        let estimator = match estimator {
+            Some(estimator) => estimator,
+            None => {
+                let max_priority_fee_per_gas = self.max_priority_fee_per_gas().await?;
+                let max_fee_per_gas =
+                    utils::eip1559_max_fee(base_fee_per_gas, max_priority_fee_per_gas);
+                return Ok((max_fee_per_gas, max_priority_fee_per_gas))
+            }
+        };
         let fee_history = self
             .fee_history(
                 utils::EIP1559_FEE_ESTIMATION_PAST_BLOCKS,
@@ -498,15 +845,39 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
                 &[utils::EIP1559_FEE_ESTIMATION_REWARD_PERCENTILE],
             )
             .await?;
+        Ok(estimator(base_fee_per_gas, fee_history.reward))
+    }
 
-        // use the provided fee estimator function, or fallback to the default implementation.
-        let (max_fee_per_gas, max_priority_fee_per_gas) = if let Some(es) = estimator {
-            es(base_fee_per_gas, fee_history.reward)
-        } else {
-            utils::eip1559_default_estimator(base_fee_per_gas, fee_history.reward)
+    /// Estimates the max fee per gas and max priority fee per gas of an EIP-1559 transaction from
+    /// the fee history of recent blocks, according to the given `strategy`.
+    async fn estimate_eip1559_fees_from_history(
+        &self,
+        strategy: FeeHistoryStrategy,
+    ) -> Result<(U256, U256), Self::Error> {
+        let fee_history = self
+            .fee_history(strategy.block_count, BlockNumber::Latest, &[strategy.reward_percentile])
+            .await?;
+        Ok(fee_history.estimate_eip1559_fees(&strategy))
+    }
+
+    async fn max_priority_fee_per_gas(&self) -> Result<U256, ProviderError> {
+        let err = match self.request("eth_maxPriorityFeePerGas", ()).await {
+            Err(err) if is_method_not_found(&err) => err,
+            res => return res,
         };
+        trace!(?err, "eth_maxPriorityFeePerGas unsupported, falling back");
 
-        Ok((max_fee_per_gas, max_priority_fee_per_gas))
+        let fallback = match self.priority_fee_fallback.clone() {
+            Some(fallback) => fallback,
+            None => PriorityFeeFallback::for_chain(self.get_chainid().await?.low_u64()),
+        };
+        match fallback {
+            PriorityFeeFallback::Fixed(fee) => Ok(fee),
+            PriorityFeeFallback::FeeHistory(strategy) => {
+                Ok(self.estimate_eip1559_fees_from_history(strategy).await?.1)
+            }
+            PriorityFeeFallback::BlocksMedian(blocks) => self.median_priority_fee(blocks).await,
+        }
     }
 
     /// Gets the accounts on the node
@@ -548,11 +919,16 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
 
     /// Returns the currently configured chain id, a value used in replay-protected
     /// transaction signing as introduced by EIP-155.
+    ///
+    /// The chain id is requested once and pinned, see [`Provider::chain_id`].
     async fn get_chainid(&self) -> Result<U256, ProviderError> {
-        self.request("eth_chainId", ()).await
+        match self.chain_id() {
+            Some(chain_id) => Ok(chain_id),
+            None => self.refresh_chain_id().await,
+        }
     }
 
-    /// Return current client syncing status. If IsFalse sync is over.
+    /// Return current client syncing status. If NotSyncing sync is over.
     async fn syncing(&self) -> Result<SyncingStatus, Self::Error> {
         self.request("eth_syncing", ()).await
     }
@@ -562,6 +938,14 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("net_version", ()).await
     }
 
+    async fn get_net_peer_count(&self) -> Result<U64, ProviderError> {
+        self.request("net_peerCount", ()).await
+    }
+
+    async fn get_net_listening(&self) -> Result<bool, ProviderError> {
+        self.request("net_listening", ()).await
+    }
+
     ////// Contract Execution
     //
     // These are relatively low-level calls. The Contracts API should usually be used instead.
@@ -574,9 +958,13 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         tx: &TypedTransaction,
         block: Option<BlockId>,
     ) -> Result<Bytes, ProviderError> {
-        let tx = utils::serialize(tx);
-        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
-        self.request("eth_call", [tx, block]).await
+        match self.ccip_read.as_ref() {
+            Some(ccip) => self.call_with_ccip_read(tx, block, ccip).await,
+            None => {
+                let block = block.unwrap_or_else(|| BlockNumber::Latest.into());
+                self.request("eth_call", [utils::serialize(tx), utils::serialize(&block)]).await
+            }
+        }
     }
 
     /// Sends a transaction to a single Ethereum node and return the estimated amount of gas
@@ -606,7 +994,22 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
     ) -> Result<AccessListWithGasUsed, ProviderError> {
         let tx = utils::serialize(tx);
         let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
-        self.request("eth_createAccessList", [tx, block]).await
+        match self.request("eth_createAccessList", [tx, block]).await {
+            Err(err) if is_method_not_found(&err) => {
+                Err(ProviderError::UnsupportedMethod("eth_createAccessList".to_string()))
+            }
+            res => res,
+        }
+    }
+
+    async fn simulate_v1(
+        &self,
+        payload: &crate::simulate::SimulatePayload,
+        block: Option<BlockId>,
+    ) -> Result<Vec<crate::simulate::SimulatedBlock>, ProviderError> {
+        let payload = utils::serialize(payload);
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        self.request("eth_simulateV1", [payload, block]).await
     }
 
     /// Sends the transaction to the entire Ethereum network and returns the transaction's hash
@@ -795,6 +1198,9 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
 
     /// Returns the EIP-1186 proof response
     /// <https://github.com/ethereum/EIPs/issues/1186>
+    ///
+    /// The response can be checked against a trusted state root with
+    /// [`EIP1186ProofResponse::verify`].
     async fn get_proof<T: Into<NameOrAddress> + Send + Sync>(
         &self,
         from: T,
@@ -927,6 +1333,10 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
 
     /// Returns the address that the `ens_name` resolves to (or None if not configured).
     ///
+    /// Names without a resolver of their own are resolved by the resolver of their closest
+    /// ancestor if it supports ENSIP-10 wildcards. The `OffchainLookup`s of such resolvers are
+    /// followed even if [CCIP-Read](Provider::set_ccip_read) is disabled.
+    ///
     /// # Panics
     ///
     /// If the bytes returned from the ENS registrar/resolver cannot be interpreted as
@@ -952,6 +1362,125 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         }
     }
 
+    /// Resolves the names in one call if the default ENS registry is used on a chain with a
+    /// UniversalResolver and Multicall3, otherwise one by one
+    async fn resolve_names(
+        &self,
+        ens_names: &[&str],
+    ) -> Result<Vec<Option<Address>>, ProviderError> {
+        let (resolver, multicall) = match self.ens_batch_contracts().await? {
+            Some(contracts) => contracts,
+            None => {
+                let names =
+                    ens_names.iter().map(|name| async move { self.resolve_name(name).await.ok() });
+                return Ok(future::join_all(names).await)
+            }
+        };
+        let calls = ens_names.iter().map(|name| {
+            let data = [&ens::ADDR_SELECTOR[..], &ens::namehash(name).0].concat();
+            ens::resolve_extended(resolver, name, &data)
+                .ok()
+                .and_then(|tx| tx.data)
+                .unwrap_or_default()
+                .to_vec()
+        });
+        let results = self.universal_resolver_batch(resolver, multicall, calls).await?;
+        let names = ens_names.iter().zip(results).map(|(name, result)| async move {
+            match result {
+                // `resolve(bytes,bytes)` returns the record and the resolver
+                Ok(data) => abi::decode(&[ParamType::Bytes, ParamType::Address], &data)
+                    .ok()
+                    .and_then(|tokens| tokens.into_iter().next()?.into_bytes())
+                    .and_then(|record| abi::decode(&[ParamType::Address], &record).ok())
+                    .and_then(|tokens| tokens.into_iter().next()?.into_address())
+                    .filter(|addr| !addr.is_zero()),
+                Err(true) => self.resolve_name(name).await.ok(),
+                Err(false) => None,
+            }
+        });
+        Ok(future::join_all(names).await)
+    }
+
+    /// Looks up the names in one call if the default ENS registry is used on a chain with a
+    /// UniversalResolver and Multicall3, otherwise one by one
+    async fn lookup_addresses(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<String>>, ProviderError> {
+        let (resolver, multicall) = match self.ens_batch_contracts().await? {
+            Some(contracts) => contracts,
+            None => {
+                let names = addresses
+                    .iter()
+                    .map(|addr| async move { self.lookup_address(*addr).await.ok() });
+                return Ok(future::join_all(names).await)
+            }
+        };
+        let calls = addresses.iter().map(|addr| ens::reverse_extended(*addr).unwrap_or_default());
+        let results = self.universal_resolver_batch(resolver, multicall, calls).await?;
+        let names = addresses.iter().zip(results).map(|(addr, result)| async move {
+            match result {
+                // `reverse(bytes)` returns the name, the address it resolves to and the resolvers,
+                // the UniversalResolver already checked that the name resolves
+                Ok(data) => {
+                    let params = [
+                        ParamType::String,
+                        ParamType::Address,
+                        ParamType::Address,
+                        ParamType::Address,
+                    ];
+                    let mut tokens = abi::decode(&params, &data).ok()?.into_iter();
+                    let name = tokens.next()?.into_string()?;
+                    let resolved = tokens.next()?.into_address()?;
+                    (!name.is_empty() && resolved == *addr).then_some(name)
+                }
+                Err(true) => self.lookup_address(*addr).await.ok(),
+                Err(false) => None,
+            }
+        });
+        Ok(future::join_all(names).await)
+    }
+
+    /// Returns the address of another chain that the `ens_name` resolves to,
+    /// [ENSIP-9](https://docs.ens.domains/ens-improvement-proposals/ensip-9-multichain-address-resolution)
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use ethers_providers::{ens::coin_type, Provider, Http as HttpProvider, Middleware};
+    /// # use std::convert::TryFrom;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// # let provider = Provider::<HttpProvider>::try_from("https://mainnet.infura.io/v3/c60b0bb42f8a4c6481ecd229eddaca27").unwrap();
+    /// let btc = provider.resolve_multicoin("vitalik.eth", coin_type::BTC).await.unwrap();
+    /// println!("{btc}");
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the bytes returned from the ENS registrar/resolver cannot be interpreted as
+    /// bytes. This should theoretically never happen.
+    async fn resolve_multicoin(
+        &self,
+        ens_name: &str,
+        coin_type: u64,
+    ) -> Result<ens::MulticoinAddress, ProviderError> {
+        let bytes: Bytes = self
+            .query_resolver_parameters(
+                ParamType::Bytes,
+                ens_name,
+                ens::MULTICOIN_ADDR_SELECTOR,
+                Some(&ens::bytes_32ify(coin_type)),
+            )
+            .await?;
+        if bytes.is_empty() {
+            return Err(ProviderError::EnsError(format!(
+                "`{ens_name}` has no address for coin type {coin_type}"
+            )))
+        }
+        Ok(ens::MulticoinAddress::new(coin_type, bytes))
+    }
+
     /// Returns the avatar HTTP link of the avatar that the `ens_name` resolves to (or None
     /// if not configured)
     ///
@@ -1011,7 +1540,7 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
                             ..Default::default()
                         };
                         let data = self.call(&tx.into(), None).await?;
-                        if decode_bytes::<u64>(ParamType::Uint(64), data) == 0 {
+                        if decode_bytes::<U256>(ParamType::Uint(256), data).is_zero() {
                             return Err(ProviderError::CustomError("Incorrect balance.".to_string()))
                         }
                     }
@@ -1095,6 +1624,17 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("txpool_content", ()).await
     }
 
+    /// Returns the details of all transactions of the given sender that are currently pending
+    /// for inclusion in the next block(s), as well as the ones that are being scheduled for future
+    /// execution only.
+    /// Ref: [Here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_contentfrom)
+    async fn txpool_content_from(
+        &self,
+        sender: Address,
+    ) -> Result<TxpoolContentFrom, ProviderError> {
+        self.request("txpool_contentFrom", [sender]).await
+    }
+
     /// Returns a summary of all the transactions currently pending for inclusion in the next
     /// block(s), as well as the ones that are being scheduled for future execution only.
     /// Ref: [Here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_inspect)
@@ -1120,6 +1660,29 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("debug_traceTransaction", [tx_hash, trace_options]).await
     }
 
+    /// Traces the given transaction with the built-in `callTracer`, returning its call tree
+    async fn debug_trace_transaction_call_tracer(
+        &self,
+        tx_hash: TxHash,
+        config: CallConfig,
+    ) -> Result<CallFrame, ProviderError> {
+        let tx_hash = utils::serialize(&tx_hash);
+        let trace_options = utils::serialize(&GethDebugTracingOptions::call_tracer(config));
+        self.request("debug_traceTransaction", [tx_hash, trace_options]).await
+    }
+
+    /// Traces the given transaction with the built-in `prestateTracer`, returning the state of the
+    /// accounts it touched before it was executed, or the state diff if `diff_mode` is set
+    async fn debug_trace_transaction_prestate_tracer(
+        &self,
+        tx_hash: TxHash,
+        config: PreStateConfig,
+    ) -> Result<PreStateFrame, ProviderError> {
+        let tx_hash = utils::serialize(&tx_hash);
+        let trace_options = utils::serialize(&GethDebugTracingOptions::prestate_tracer(config));
+        self.request("debug_traceTransaction", [tx_hash, trace_options]).await
+    }
+
     /// Executes the given call and returns a number of possible traces for it
     async fn debug_trace_call<T: Into<TypedTransaction> + Send + Sync>(
         &self,
@@ -1134,6 +1697,18 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
         self.request("debug_traceCall", [req, block, trace_options]).await
     }
 
+    async fn debug_storage_range_at(
+        &self,
+        block_hash: H256,
+        tx_index: u64,
+        address: Address,
+        start_key: H256,
+        limit: u64,
+    ) -> Result<StorageRangeResult, ProviderError> {
+        self.request("debug_storageRangeAt", (block_hash, tx_index, address, start_key, limit))
+            .await
+    }
+
     /// Executes the given call and returns a number of possible traces for it
     async fn trace_call<T: Into<TypedTransaction> + Send + Sync>(
         &self,
@@ -1270,26 +1845,30 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
     where
         P: PubsubClient,
     {
-        let loaded_logs = match filter.block_option {
-            FilterBlockOption::Range { from_block, to_block: _ } => {
-                if from_block.is_none() {
-                    vec![]
-                } else {
-                    self.get_logs(filter).await?
-                }
-            }
-            FilterBlockOption::AtBlockHash(_block_hash) => self.get_logs(filter).await?,
-        };
-        let loaded_logs = VecDeque::from(loaded_logs);
+        let loaded_logs = self.subscription_backfill_logs(filter).await?;
 
-        let logs = utils::serialize(&"logs"); // TODO: Make this a static
-        let filter = utils::serialize(filter);
-        self.subscribe([logs, filter]).await.map(|mut stream| {
+        self.subscribe(logs_subscription_params(filter)).await.map(|mut stream| {
             stream.set_loaded_elements(loaded_logs);
             stream
         })
     }
 
+    async fn subscribe_or_watch_logs<'a>(
+        &'a self,
+        filter: &Filter,
+    ) -> Result<SubscribeOrPoll<'a, P, Log>, ProviderError> {
+        if self.inner.as_pubsub().is_none() || !self.supports("eth_subscribe") {
+            return self.watch(filter).await.map(SubscribeOrPoll::Poll)
+        }
+
+        let loaded_logs = self.subscription_backfill_logs(filter).await?;
+
+        let id: U256 = self.request("eth_subscribe", logs_subscription_params(filter)).await?;
+        let mut stream = DynSubscriptionStream::new(id, self)?;
+        stream.set_loaded_elements(loaded_logs);
+        Ok(SubscribeOrPoll::Subscription(stream))
+    }
+
     async fn fee_history<T: Into<U256> + Send + Sync>(
         &self,
         block_count: T,
@@ -1331,6 +1910,176 @@ impl<P: JsonRpcClient> Middleware for Provider<P> {
 }
 
 impl<P: JsonRpcClient> Provider<P> {
+    /// Returns the values of the storage slots of `address`, in the order of the slots
+    ///
+    /// The `eth_getStorageAt` requests are sent concurrently. Over HTTP, [`Provider::batch`] sends
+    /// them in a single request instead.
+    ///
+    /// The slots of mappings, dynamic arrays and packed structs can be computed with
+    /// [`mapping_slot`](utils::mapping_slot), [`dynamic_array_slot`](utils::dynamic_array_slot)
+    /// and [`PackedField`](utils::PackedField).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_core::{types::{Address, U256}, utils::mapping_slot};
+    /// use ethers_providers::{Http, Provider};
+    ///
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let token: Address = "0x6b175474e89094c44da98b954eedeac495271d0f".parse()?;
+    /// let holders = [Address::repeat_byte(1), Address::repeat_byte(2)];
+    /// // `balanceOf` of DAI is declared in slot 2
+    /// let slots: Vec<_> = holders.iter().map(|holder| mapping_slot(*holder, U256::from(2))).collect();
+    /// let balances = provider.get_storage_batch(token, &slots, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_storage_batch(
+        &self,
+        address: Address,
+        slots: &[H256],
+        block: Option<BlockId>,
+    ) -> Result<Vec<H256>, ProviderError> {
+        let from = utils::serialize(&address);
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        let values = try_join_all(slots.iter().map(|slot| {
+            // the position is a QUANTITY, like in `get_storage_at`
+            let position = utils::serialize(&U256::from_big_endian(slot.as_bytes()));
+            self.request::<_, String>("eth_getStorageAt", [from.clone(), position, block.clone()])
+        }))
+        .await?;
+        values
+            .into_iter()
+            .map(|value| {
+                let value = format!("{:0>64}", value.replace("0x", ""));
+                Ok(H256::from_slice(&Vec::from_hex(value)?))
+            })
+            .collect()
+    }
+
+    /// Returns the median of the priority fees paid by the transactions of the last `blocks`
+    /// blocks, zero if they contain no transactions
+    async fn median_priority_fee(&self, blocks: u64) -> Result<U256, ProviderError> {
+        let latest = self.get_block_number().await?.as_u64();
+        let blocks = try_join_all(
+            (latest.saturating_sub(blocks.saturating_sub(1))..=latest)
+                .map(|number| self.get_block_with_txs(number)),
+        )
+        .await?;
+
+        let mut fees = blocks
+            .into_iter()
+            .flatten()
+            .flat_map(|block| {
+                let base_fee = block.base_fee_per_gas.unwrap_or_default();
+                block.transactions.into_iter().filter_map(move |tx| {
+                    match (tx.max_fee_per_gas, tx.max_priority_fee_per_gas) {
+                        (Some(max_fee), Some(priority_fee)) => {
+                            Some(priority_fee.min(max_fee.saturating_sub(base_fee)))
+                        }
+                        _ => tx.gas_price.map(|price| price.saturating_sub(base_fee)),
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        fees.sort_unstable();
+        Ok(fees.get(fees.len() / 2).copied().unwrap_or_default())
+    }
+
+    /// Returns the historical logs a new logs subscription for `filter` should yield before any
+    /// pushed logs, i.e. all matching logs if the filter starts at a specific block.
+    async fn subscription_backfill_logs(
+        &self,
+        filter: &Filter,
+    ) -> Result<VecDeque<Log>, ProviderError> {
+        let loaded_logs = match filter.block_option {
+            FilterBlockOption::Range { from_block, to_block: _ } => {
+                if from_block.is_none() {
+                    vec![]
+                } else {
+                    self.get_logs(filter).await?
+                }
+            }
+            FilterBlockOption::AtBlockHash(_block_hash) => self.get_logs(filter).await?,
+        };
+        Ok(VecDeque::from(loaded_logs))
+    }
+
+    /// Calls the contract and follows the `OffchainLookup` reverts of the called contract, see
+    /// [`Provider::set_ccip_read`]
+    async fn call_with_ccip_read(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+        ccip: &CcipRead,
+    ) -> Result<Bytes, ProviderError> {
+        let block = utils::serialize(&block.unwrap_or_else(|| BlockNumber::Latest.into()));
+        let mut tx = tx.clone();
+        let mut redirects = 0;
+        loop {
+            let err = match self.request("eth_call", [utils::serialize(&tx), block.clone()]).await {
+                Ok(res) => return Ok(res),
+                Err(err) => err,
+            };
+            // only the called contract may request offchain data
+            let lookup = match err.as_revert_data().and_then(|data| OffchainLookup::decode(&data)) {
+                Some(lookup) if tx.to() == Some(&NameOrAddress::Address(lookup.sender)) => lookup,
+                _ => return Err(err),
+            };
+            if redirects == ccip.max_redirects {
+                return Err(ProviderError::CcipReadError(format!(
+                    "exceeded the limit of {} redirects",
+                    ccip.max_redirects
+                )))
+            }
+            redirects += 1;
+
+            let response = ccip.fetch(&lookup).await?;
+            tx.set_data(lookup.callback(response));
+        }
+    }
+
+    /// Returns the UniversalResolver and the Multicall3 deployment that resolve names in one
+    /// call, `None` if the ENS registry is overridden or either isn't deployed on the chain
+    async fn ens_batch_contracts(&self) -> Result<Option<(Address, Address)>, ProviderError> {
+        if self.ens.is_some() {
+            return Ok(None)
+        }
+        let chain = match Chain::try_from(self.get_chainid().await?) {
+            Ok(chain) => chain,
+            Err(_) => return Ok(None),
+        };
+        Ok(ens::universal_resolver(chain).zip(ens::multicall3(chain)))
+    }
+
+    /// Calls the UniversalResolver with each calldata in a single call of the Multicall3
+    /// deployment, returns the return data of the successful calls and whether failed calls
+    /// requested offchain data
+    async fn universal_resolver_batch(
+        &self,
+        resolver: Address,
+        multicall: Address,
+        calls: impl Iterator<Item = Vec<u8>>,
+    ) -> Result<Vec<Result<Vec<u8>, bool>>, ProviderError> {
+        let calls: Vec<_> = calls.map(|data| (resolver, data)).collect();
+        if calls.is_empty() {
+            return Ok(Vec::new())
+        }
+        let len = calls.len();
+        let data = self.call(&ens::aggregate3(multicall, calls).into(), None).await?;
+        let results = ens::decode_aggregate3(&data)
+            .filter(|results| results.len() == len)
+            .ok_or_else(|| ProviderError::EnsError("invalid multicall response".to_string()))?;
+        Ok(results
+            .into_iter()
+            .map(|(success, data)| match success {
+                true => Ok(data),
+                false => Err(OffchainLookup::decode(&data).is_some()),
+            })
+            .collect())
+    }
+
     async fn query_resolver<T: Detokenize>(
         &self,
         param: ParamType,
@@ -1350,18 +2099,47 @@ impl<P: JsonRpcClient> Provider<P> {
         // Get the ENS address, prioritize the local override variable
         let ens_addr = self.ens.unwrap_or(ens::ENS_ADDRESS);
 
-        // first get the resolver responsible for this name
-        // the call will return a Bytes array which we convert to an address
-        let data = self.call(&ens::get_resolver(ens_addr, ens_name).into(), None).await?;
+        // ENSIP-10: the resolver of the closest ancestor with a resolver is responsible for the
+        // name, which it must support via `resolve(bytes,bytes)` if it's not set for the name
+        let mut name = ens_name;
+        let resolver_address = loop {
+            // the call will return a Bytes array which we convert to an address
+            let data = self.call(&ens::get_resolver(ens_addr, name).into(), None).await?;
+            // otherwise, decode_bytes panics
+            if !data.0.is_empty() {
+                let resolver_address: Address = decode_bytes(ParamType::Address, data);
+                if resolver_address != Address::zero() {
+                    break resolver_address
+                }
+            }
+            name = match ens::parent(name) {
+                Some(parent) if !parent.is_empty() => parent,
+                _ => return Err(ProviderError::EnsError(ens_name.to_string())),
+            };
+        };
 
-        // otherwise, decode_bytes panics
-        if data.0.is_empty() {
-            return Err(ProviderError::EnsError(ens_name.to_string()))
-        }
-
-        let resolver_address: Address = decode_bytes(ParamType::Address, data);
-        if resolver_address == Address::zero() {
-            return Err(ProviderError::EnsError(ens_name.to_string()))
+        let tx = ens::resolve(resolver_address, selector, ens_name, parameters);
+        // only wildcard resolution needs `resolve(bytes,bytes)`, names with a resolver of their
+        // own are resolved without asking for it
+        if name != ens_name {
+            if !self.supports_extended_resolver(resolver_address).await {
+                return Err(ProviderError::EnsError(format!(
+                    "`{ens_name}` resolver ({resolver_address:?}) does not support wildcards."
+                )))
+            }
+            let data = tx.data.unwrap_or_default();
+            let tx = ens::resolve_extended(resolver_address, ens_name, &data)
+                .map_err(ProviderError::EnsError)?;
+            // extended resolvers usually serve their records offchain
+            let ccip =
+                self.ccip_read.clone().unwrap_or_else(|| CcipRead::new(ens::MAX_CCIP_REDIRECTS));
+            let data = self.call_with_ccip_read(&tx.into(), None, &ccip).await?;
+            let record = abi::decode(&[ParamType::Bytes], data.as_ref())
+                .ok()
+                .and_then(|tokens| tokens.into_iter().next()?.into_bytes())
+                .filter(|record| !record.is_empty())
+                .ok_or_else(|| ProviderError::EnsError(ens_name.to_string()))?;
+            return Ok(decode_bytes(param, record.into()))
         }
 
         if let ParamType::Address = param {
@@ -1370,13 +2148,24 @@ impl<P: JsonRpcClient> Provider<P> {
         }
 
         // resolve
-        let data = self
-            .call(&ens::resolve(resolver_address, selector, ens_name, parameters).into(), None)
-            .await?;
+        let data = self.call(&tx.into(), None).await?;
 
         Ok(decode_bytes(param, data))
     }
 
+    /// Returns whether the resolver implements the ENSIP-10 `resolve(bytes,bytes)`.
+    ///
+    /// Resolvers that revert when calling `supportsInterface(bytes4)` don't support it.
+    async fn supports_extended_resolver(&self, resolver_address: Address) -> bool {
+        let tx = ens::supports_interface(resolver_address, ens::RESOLVE_SELECTOR);
+        match self.call(&tx.into(), None).await {
+            Ok(data) => abi::decode(&[ParamType::Bool], data.as_ref())
+                .map(|token| token[0].clone().into_bool().unwrap_or_default())
+                .unwrap_or_default(),
+            Err(_) => false,
+        }
+    }
+
     /// Validates that the resolver supports `selector`.
     async fn validate_resolver(
         &self,
@@ -1440,10 +2229,289 @@ impl<P: JsonRpcClient> Provider<P> {
         self
     }
 
+    /// Caches up to `capacity` responses to requests that can never change, like `net_version`,
+    /// `eth_getBlockByHash`, `eth_getTransactionByHash` for mined transactions or `eth_getCode` at
+    /// a fixed block. The least recently used response is evicted once the cache is full.
+    ///
+    /// A `capacity` of `0` disables the cache (default). Clones of the provider share the cache.
+    pub fn set_response_cache(&mut self, capacity: usize) -> &mut Self {
+        self.response_cache = (capacity > 0).then(|| Arc::new(ResponseCache::new(capacity)));
+        self
+    }
+
+    /// Caches up to `capacity` responses to requests that can never change, see
+    /// [`Provider::set_response_cache`]
+    #[must_use]
+    pub fn response_cache(mut self, capacity: usize) -> Self {
+        self.set_response_cache(capacity);
+        self
+    }
+
+    /// Follows [EIP-3668](https://eips.ethereum.org/EIPS/eip-3668) `OffchainLookup` reverts of
+    /// [`call`](Middleware::call)s: the data is fetched from the gateways of the lookup and the
+    /// call is re-issued with the contract's callback, up to `max_redirects` times per call. This
+    /// is required to resolve offchain-backed ENS names and to read data served by L2 gateways.
+    ///
+    /// A `max_redirects` of `0` disables CCIP-Read (default), the EIP recommends a limit of `4`.
+    pub fn set_ccip_read(&mut self, max_redirects: usize) -> &mut Self {
+        self.ccip_read = (max_redirects > 0).then(|| CcipRead::new(max_redirects));
+        self
+    }
+
+    /// Follows `OffchainLookup` reverts of calls up to `max_redirects` times, see
+    /// [`Provider::set_ccip_read`]
+    #[must_use]
+    pub fn ccip_read(mut self, max_redirects: usize) -> Self {
+        self.set_ccip_read(max_redirects);
+        self
+    }
+
+    /// Sets how [`Middleware::max_priority_fee_per_gas`] estimates the priority fee if the node
+    /// doesn't support `eth_maxPriorityFeePerGas`.
+    ///
+    /// By default the fallback is chosen with [`PriorityFeeFallback::for_chain`].
+    pub fn set_priority_fee_fallback(&mut self, fallback: PriorityFeeFallback) -> &mut Self {
+        self.priority_fee_fallback = Some(fallback);
+        self
+    }
+
+    /// Sets how the priority fee is estimated if `eth_maxPriorityFeePerGas` isn't supported, see
+    /// [`Provider::set_priority_fee_fallback`]
+    #[must_use]
+    pub fn priority_fee_fallback(mut self, fallback: PriorityFeeFallback) -> Self {
+        self.set_priority_fee_fallback(fallback);
+        self
+    }
+
+    /// Sets the strategy that [`Middleware::estimate_eip1559_fees`] estimates fees with if no
+    /// estimator is given, e.g. when the fees of transactions are filled in
+    /// [`Middleware::fill_transaction`], instead of the default estimator.
+    pub fn set_fee_strategy(&mut self, strategy: FeeHistoryStrategy) -> &mut Self {
+        self.fee_strategy = Some(strategy);
+        self
+    }
+
+    /// Sets the strategy that fees are estimated with, see [`Provider::set_fee_strategy`]
+    #[must_use]
+    pub fn fee_strategy(mut self, strategy: FeeHistoryStrategy) -> Self {
+        self.set_fee_strategy(strategy);
+        self
+    }
+
+    /// Sets how long requests may take before they are cancelled with
+    /// [`ProviderError::Timeout`] (default: unlimited).
+    ///
+    /// Unlike the timeout of the HTTP client this also applies to other transports, and can be
+    /// overridden for single requests with [`Provider::request_with_timeout`] or by calling
+    /// [`Middleware`] methods on a clone of the provider with a different timeout, e.g. for slow
+    /// trace calls.
+    pub fn set_request_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long requests may take before they are cancelled, see
+    /// [`Provider::set_request_timeout`]
+    #[must_use]
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.set_request_timeout(timeout);
+        self
+    }
+
     /// Gets the polling interval which the provider currently uses for event filters
-    /// and pending transactions (default: 7 seconds)
+    /// and pending transactions.
+    ///
+    /// This is the interval set with [`Provider::set_interval`], or else half of the chain's
+    /// block time once it was learned, see [`Provider::tune_interval`], or else 7 seconds.
     pub fn get_interval(&self) -> Duration {
-        self.interval.unwrap_or(DEFAULT_POLL_INTERVAL)
+        self.interval
+            .or_else(|| self.learned_interval().map(|learned| learned.interval))
+            .unwrap_or(DEFAULT_POLL_INTERVAL)
+    }
+
+    fn learned_interval(&self) -> Option<LearnedInterval> {
+        *self.learned_interval.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Learns the polling interval from the timestamps of the last 10 blocks and returns it, half
+    /// of their average block time clamped to 100ms..=7s.
+    ///
+    /// Without tuning, the interval is learned from the chain's
+    /// [block time hint](Chain::average_blocktime_hint) once the chain id is known, e.g. after
+    /// the first [`get_chainid`](Middleware::get_chainid). A tuned interval is not replaced by the
+    /// hint, also when the chain id is pinned or refreshed later. The learned interval is shared
+    /// by clones, an interval set with [`Provider::set_interval`] takes precedence.
+    pub async fn tune_interval(&self) -> Result<Duration, ProviderError> {
+        let latest = self.get_block_number().await?;
+        let sample = latest.min(BLOCK_TIME_SAMPLE.into());
+        if sample.is_zero() {
+            return Err(ProviderError::CustomError("no blocks to learn the block time".to_string()))
+        }
+        let (latest, oldest) = try_join!(self.get_block(latest), self.get_block(latest - sample))?;
+        let (latest, oldest) = latest.zip(oldest).ok_or_else(|| {
+            ProviderError::CustomError("blocks to learn the block time not found".to_string())
+        })?;
+        let elapsed = latest.timestamp.saturating_sub(oldest.timestamp).low_u64() * 1000;
+        let block_time = Duration::from_millis(elapsed / sample.as_u64());
+        Ok(self.set_learned_interval(block_time, true))
+    }
+
+    /// Learns the polling interval from the chain's block time hint, if it has one and the
+    /// interval wasn't tuned
+    fn learn_interval_from_chain(&self, chain_id: U256) {
+        if self.learned_interval().map_or(false, |learned| learned.tuned) {
+            return
+        }
+        if let Some(block_time) =
+            Chain::try_from(chain_id).ok().and_then(|chain| chain.average_blocktime_hint())
+        {
+            self.set_learned_interval(block_time, false);
+        }
+    }
+
+    fn set_learned_interval(&self, block_time: Duration, tuned: bool) -> Duration {
+        // poll twice per block
+        let interval = (block_time / 2).clamp(DEFAULT_LOCAL_POLL_INTERVAL, DEFAULT_POLL_INTERVAL);
+        *self.learned_interval.write().unwrap_or_else(|err| err.into_inner()) =
+            Some(LearnedInterval { interval, tuned });
+        interval
+    }
+
+    /// Streams the logs matching the filter like [`Middleware::watch`], but installs the filter
+    /// again if the node expires it, see [`LogFilterManager`](crate::LogFilterManager).
+    ///
+    /// If the filter has no `from_block`, logs are streamed from the current block on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_core::types::{Address, Filter};
+    /// use ethers_providers::{Http, Provider, StreamExt};
+    /// use std::convert::TryFrom;
+    ///
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let usdt: Address = "0xdAC17F958D2ee523a2206206994597C13D831ec7".parse()?;
+    /// let filter = Filter::new().address(usdt);
+    /// let mut logs = provider.watch_logs_managed(&filter).await?;
+    /// while let Some(log) = logs.next().await {
+    ///     println!("{:?}", log?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn watch_logs_managed(
+        &self,
+        filter: &Filter,
+    ) -> Result<crate::LogFilterManager<'_, P>, ProviderError> {
+        let from = match filter.get_from_block() {
+            Some(from) => from,
+            None => self.get_block_number().await?,
+        };
+        let id = self.new_filter(FilterKind::Logs(filter)).await?;
+        Ok(crate::LogFilterManager::new(id, self, filter.clone(), from))
+    }
+}
+
+impl<P: PubsubClient> Provider<P> {
+    /// Streams the logs matching the filter from `from_block` on: the past logs are queried in
+    /// pages, then the logs pushed by a subscription are yielded.
+    ///
+    /// The subscription is installed first so no logs are missed in between, logs that are both
+    /// queried and pushed are yielded once. The block range of `filter` is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use ethers_core::types::{Address, Filter};
+    /// # use ethers_providers::{Provider, StreamExt, Ws};
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = Provider::<Ws>::connect("ws://localhost:8546").await?;
+    /// let filter = Filter::new().address("0xdAC17F958D2ee523a2206206994597C13D831ec7".parse::<Address>()?);
+    /// let mut logs = provider.subscribe_logs_from(&filter, 17_000_000u64).await?.page_size(1000);
+    /// while let Some(log) = logs.next().await {
+    ///     println!("{:?}", log?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_logs_from(
+        &self,
+        filter: &Filter,
+        from_block: impl Into<BlockNumber>,
+    ) -> Result<BackfilledLogStream<'_, P>, ProviderError> {
+        let subscribed_at = self.get_block_number().await?;
+        let filter = Filter { block_option: FilterBlockOption::default(), ..filter.clone() };
+        let live = self.subscribe([utils::serialize(&"logs"), utils::serialize(&filter)]).await?;
+        let history = LogQuery::new(self, &filter.from_block(from_block));
+        Ok(BackfilledLogStream::new(history, live, subscribed_at))
+    }
+
+    /// Subscribes to new heads and yields a [`Reorg`](crate::Reorg) whenever the chain is
+    /// reorganized, i.e. a new head doesn't descend from the previous one.
+    ///
+    /// The hashes of the latest [`DEFAULT_REORG_DEPTH`](crate::DEFAULT_REORG_DEPTH) blocks are
+    /// tracked, see [`ReorgStream::depth`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_providers::{Provider, StreamExt, Ws};
+    ///
+    /// let provider = Provider::<Ws>::connect("ws://localhost:8546").await?;
+    /// let mut reorgs = provider.subscribe_reorgs().await?;
+    /// while let Some(reorg) = reorgs.next().await {
+    ///     let reorg = reorg?;
+    ///     println!("{} blocks replaced after {:?}", reorg.depth(), reorg.common_ancestor.hash);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_reorgs(&self) -> Result<ReorgStream<'_, P>, ProviderError> {
+        let heads = self.subscribe_blocks().await?;
+        Ok(ReorgStream::new(self, heads))
+    }
+
+    /// Subscribes to the full transactions entering the mempool.
+    ///
+    /// Uses `eth_subscribe("newPendingTransactions", true)` if the node supports it. Otherwise
+    /// the node only pushes the hashes of pending transactions, and at most
+    /// [`DEFAULT_PENDING_TX_CONCURRENCY`](crate::DEFAULT_PENDING_TX_CONCURRENCY) of them are
+    /// fetched with `eth_getTransactionByHash` at once, see
+    /// [`FullPendingTxStream::is_hydrated`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_providers::{Provider, StreamExt, Ws};
+    ///
+    /// let provider = Provider::<Ws>::connect("ws://localhost:8546").await?;
+    /// let mut txs = provider.subscribe_full_pending_txs().await?;
+    /// while let Some(tx) = txs.next().await {
+    ///     let tx = tx?;
+    ///     println!("{:?} from {:?}", tx.hash, tx.from);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn subscribe_full_pending_txs(
+        &self,
+    ) -> Result<FullPendingTxStream<'_, P>, ProviderError> {
+        match self.subscribe(("newPendingTransactions", true)).await {
+            Ok(txs) => Ok(FullPendingTxStream::full(self, txs)),
+            // nodes that don't support full transactions reject the additional param
+            Err(err) if err.as_error_response().is_some() => {
+                let hashes = self.subscribe_pending_txs().await?;
+                Ok(FullPendingTxStream::hydrated(
+                    self,
+                    hashes,
+                    crate::DEFAULT_PENDING_TX_CONCURRENCY,
+                ))
+            }
+            Err(err) => Err(err),
+        }
     }
 }
 
@@ -1499,6 +2567,12 @@ impl Provider<HttpProvider> {
     pub fn url_mut(&mut self) -> &mut Url {
         self.inner.url_mut()
     }
+
+    /// Returns a new batch to send multiple requests in a single HTTP request, see
+    /// [`Http::batch`](HttpProvider::batch)
+    pub fn batch(&self) -> crate::BatchRequest<'_> {
+        self.inner.batch()
+    }
 }
 
 impl<Read, Write> Provider<RwClient<Read, Write>>
@@ -1550,6 +2624,11 @@ impl Provider<MockProvider> {
     }
 }
 
+/// Returns the params of the `eth_subscribe` request of the logs matching the filter
+fn logs_subscription_params(filter: &Filter) -> [serde_json::Value; 2] {
+    [utils::serialize(&"logs"), utils::serialize(filter)]
+}
+
 /// infallible conversion of Bytes to Address/String
 ///
 /// # Panics
@@ -1752,13 +2831,45 @@ pub fn is_local_endpoint(url: &str) -> bool {
 pub mod dev_rpc {
     use crate::{FromErr, Middleware, ProviderError};
     use async_trait::async_trait;
-    use ethers_core::types::U256;
+    use ethers_core::types::{Address, Bytes, H256, U256};
+    use serde::Serialize;
     use thiserror::Error;
 
-    use std::fmt::Debug;
+    use std::{fmt::Debug, str::FromStr};
+
+    /// The development node a [`DevRpcMiddleware`] talks to.
+    ///
+    /// Snapshots, mining and time travel are served under the same `evm_*` names by all of them,
+    /// the account manipulation methods are prefixed with the name of the node.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum DevNode {
+        /// Foundry's Anvil
+        Anvil,
+        /// The Hardhat Network
+        Hardhat,
+        /// Ganache 7, whose account methods are prefixed with `evm_`
+        Ganache,
+    }
+
+    impl FromStr for DevNode {
+        type Err = ProviderError;
+
+        /// Parses the `web3_clientVersion` of a development node
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.split('/').next().unwrap().to_lowercase().as_str() {
+                "anvil" => Ok(DevNode::Anvil),
+                "hardhatnetwork" => Ok(DevNode::Hardhat),
+                "ganache" | "ethereumjs testrpc" => Ok(DevNode::Ganache),
+                _ => Err(ProviderError::UnsupportedNodeClient),
+            }
+        }
+    }
 
     #[derive(Clone, Debug)]
-    pub struct DevRpcMiddleware<M>(M);
+    pub struct DevRpcMiddleware<M> {
+        inner: M,
+        node: DevNode,
+    }
 
     #[derive(Error, Debug)]
     pub enum DevRpcMiddlewareError<M: Middleware> {
@@ -1770,6 +2881,9 @@ pub mod dev_rpc {
 
         #[error("Could not revert to snapshot")]
         NoSnapshot,
+
+        #[error("{node:?} does not support {method}")]
+        Unsupported { node: DevNode, method: &'static str },
     }
 
     #[async_trait]
@@ -1779,7 +2893,7 @@ pub mod dev_rpc {
         type Inner = M;
 
         fn inner(&self) -> &M {
-            &self.0
+            &self.inner
         }
     }
 
@@ -1799,15 +2913,61 @@ pub mod dev_rpc {
     }
 
     impl<M: Middleware> DevRpcMiddleware<M> {
+        /// Wraps `inner`, which is expected to be connected to Anvil
         pub fn new(inner: M) -> Self {
-            Self(inner)
+            Self::with_node(inner, DevNode::Anvil)
+        }
+
+        /// Wraps `inner`, which is connected to the given development node
+        pub fn with_node(inner: M, node: DevNode) -> Self {
+            Self { inner, node }
+        }
+
+        /// Wraps `inner` after detecting the development node it's connected to from its
+        /// `web3_clientVersion`
+        pub async fn detect(inner: M) -> Result<Self, DevRpcMiddlewareError<M>> {
+            let version =
+                inner.client_version().await.map_err(DevRpcMiddlewareError::MiddlewareError)?;
+            Ok(Self::with_node(inner, version.parse()?))
+        }
+
+        /// Returns the development node the requests are tailored to
+        pub fn node(&self) -> DevNode {
+            self.node
+        }
+
+        /// Sends a request whose result is irrelevant, the nodes disagree on it anyway
+        async fn send<T: Debug + Serialize + Send + Sync>(
+            &self,
+            method: &str,
+            params: T,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            self.provider().request::<T, serde_json::Value>(method, params).await?;
+            Ok(())
+        }
+
+        /// Picks the name of a method that's served under a different name by each node
+        fn method(
+            &self,
+            name: &'static str,
+            [anvil, hardhat, ganache]: [Option<&'static str>; 3],
+        ) -> Result<&'static str, DevRpcMiddlewareError<M>> {
+            match self.node {
+                DevNode::Anvil => anvil,
+                DevNode::Hardhat => hardhat,
+                DevNode::Ganache => ganache,
+            }
+            .ok_or(DevRpcMiddlewareError::Unsupported { node: self.node, method: name })
         }
 
+        /// Takes a snapshot of the state of the node and returns its id, see
+        /// [`revert_to_snapshot`](Self::revert_to_snapshot)
         // Ganache, Hardhat and Anvil increment snapshot ID even if no state has changed
         pub async fn snapshot(&self) -> Result<U256, DevRpcMiddlewareError<M>> {
             self.provider().request::<(), U256>("evm_snapshot", ()).await.map_err(From::from)
         }
 
+        /// Reverts the state of the node to the snapshot, which can't be reverted to again
         pub async fn revert_to_snapshot(&self, id: U256) -> Result<(), DevRpcMiddlewareError<M>> {
             let ok = self
                 .provider()
@@ -1820,6 +2980,130 @@ pub mod dev_rpc {
                 Err(DevRpcMiddlewareError::NoSnapshot)
             }
         }
+
+        /// Mines `blocks` blocks, regardless of the pending transactions
+        pub async fn mine(&self, blocks: usize) -> Result<(), DevRpcMiddlewareError<M>> {
+            for _ in 0..blocks {
+                self.send("evm_mine", ()).await?;
+            }
+            Ok(())
+        }
+
+        /// Toggles whether a block is mined for every transaction that's sent
+        pub async fn set_automine(&self, enabled: bool) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self
+                .method("set_automine", [Some("evm_setAutomine"), Some("evm_setAutomine"), None])?;
+            self.send(method, [enabled]).await
+        }
+
+        /// Sets the timestamp of the next block
+        pub async fn set_next_block_timestamp(
+            &self,
+            timestamp: u64,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self.method(
+                "set_next_block_timestamp",
+                [Some("evm_setNextBlockTimestamp"), Some("evm_setNextBlockTimestamp"), None],
+            )?;
+            self.send(method, [timestamp]).await
+        }
+
+        /// Moves the clock of the node `seconds` into the future
+        pub async fn increase_time(&self, seconds: u64) -> Result<(), DevRpcMiddlewareError<M>> {
+            self.send("evm_increaseTime", [seconds]).await
+        }
+
+        /// Sets the balance of `address` in wei
+        pub async fn set_balance(
+            &self,
+            address: Address,
+            balance: U256,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self.method(
+                "set_balance",
+                [
+                    Some("anvil_setBalance"),
+                    Some("hardhat_setBalance"),
+                    Some("evm_setAccountBalance"),
+                ],
+            )?;
+            self.send(method, (address, balance)).await
+        }
+
+        /// Sets the nonce of `address`, the next transaction it sends must use it
+        pub async fn set_nonce(
+            &self,
+            address: Address,
+            nonce: U256,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self.method(
+                "set_nonce",
+                [Some("anvil_setNonce"), Some("hardhat_setNonce"), Some("evm_setAccountNonce")],
+            )?;
+            self.send(method, (address, nonce)).await
+        }
+
+        /// Replaces the code of `address`, e.g. to mock a contract
+        pub async fn set_code(
+            &self,
+            address: Address,
+            code: Bytes,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self.method(
+                "set_code",
+                [Some("anvil_setCode"), Some("hardhat_setCode"), Some("evm_setAccountCode")],
+            )?;
+            self.send(method, (address, code)).await
+        }
+
+        /// Sets the value of the storage `slot` of `address`
+        pub async fn set_storage_at(
+            &self,
+            address: Address,
+            slot: H256,
+            value: H256,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self.method(
+                "set_storage_at",
+                [
+                    Some("anvil_setStorageAt"),
+                    Some("hardhat_setStorageAt"),
+                    Some("evm_setAccountStorageAt"),
+                ],
+            )?;
+            self.send(method, (address, slot, value)).await
+        }
+
+        /// Allows sending transactions from `address` without its private key
+        ///
+        /// Ganache can only unlock accounts on startup, see its `--wallet.unlockedAccounts` flag.
+        pub async fn impersonate_account(
+            &self,
+            address: Address,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self.method(
+                "impersonate_account",
+                [Some("anvil_impersonateAccount"), Some("hardhat_impersonateAccount"), None],
+            )?;
+            self.send(method, [address]).await
+        }
+
+        /// Stops sending the transactions of `address` without its private key, see
+        /// [`impersonate_account`](Self::impersonate_account)
+        pub async fn stop_impersonating_account(
+            &self,
+            address: Address,
+        ) -> Result<(), DevRpcMiddlewareError<M>> {
+            let method = self.method(
+                "stop_impersonating_account",
+                [
+                    Some("anvil_stopImpersonatingAccount"),
+                    Some("hardhat_stopImpersonatingAccount"),
+                    None,
+                ],
+            )?;
+            self.send(method, [address]).await
+        }
     }
     #[cfg(test)]
     // Celo blocks can not get parsed when used with Ganache
@@ -1830,6 +3114,59 @@ pub mod dev_rpc {
         use ethers_core::utils::Anvil;
         use std::convert::TryFrom;
 
+        #[test]
+        fn parses_dev_node() {
+            assert_eq!("anvil/v0.1.0".parse::<DevNode>().unwrap(), DevNode::Anvil);
+            assert_eq!(
+                "HardhatNetwork/2.12.2/@ethereumjs/vm/5.9.3".parse::<DevNode>().unwrap(),
+                DevNode::Hardhat
+            );
+            assert_eq!(
+                "Ganache/v7.7.3/EthereumJS TestRPC/v7.7.3/ethereum-js".parse::<DevNode>().unwrap(),
+                DevNode::Ganache
+            );
+            assert!("Geth/v1.11.0".parse::<DevNode>().is_err());
+        }
+
+        #[tokio::test]
+        async fn maps_methods_per_node() {
+            let (provider, mock) = Provider::mocked();
+            mock.push::<String, _>("HardhatNetwork/2.12.2/@ethereumjs/vm/5.9.3".to_string())
+                .unwrap();
+            let client = DevRpcMiddleware::detect(provider.clone()).await.unwrap();
+            assert_eq!(client.node(), DevNode::Hardhat);
+            mock.assert_request("web3_clientVersion", ()).unwrap();
+
+            let address = Address::repeat_byte(1);
+            mock.push(true).unwrap();
+            client.set_balance(address, 100.into()).await.unwrap();
+            mock.assert_request("hardhat_setBalance", (address, U256::from(100))).unwrap();
+
+            mock.push(true).unwrap();
+            client.impersonate_account(address).await.unwrap();
+            mock.assert_request("hardhat_impersonateAccount", [address]).unwrap();
+
+            let client = DevRpcMiddleware::with_node(provider, DevNode::Ganache);
+            mock.push(true).unwrap();
+            client.set_storage_at(address, H256::zero(), H256::repeat_byte(2)).await.unwrap();
+            mock.assert_request(
+                "evm_setAccountStorageAt",
+                (address, H256::zero(), H256::repeat_byte(2)),
+            )
+            .unwrap();
+
+            mock.push::<serde_json::Value, _>(serde_json::Value::Null).unwrap();
+            client.increase_time(60).await.unwrap();
+            mock.assert_request("evm_increaseTime", [60u64]).unwrap();
+
+            let err = client.impersonate_account(address).await.unwrap_err();
+            assert!(matches!(
+                err,
+                DevRpcMiddlewareError::Unsupported { node: DevNode::Ganache, .. }
+            ));
+            mock.assert_request("evm_setAutomine", [true]).unwrap_err();
+        }
+
         #[tokio::test]
         async fn test_snapshot() {
             let anvil = Anvil::new().spawn();
@@ -1895,8 +3232,10 @@ mod tests {
     use super::*;
     use crate::Http;
     use ethers_core::{
+        abi::Token,
         types::{
-            transaction::eip2930::AccessList, Eip1559TransactionRequest, TransactionRequest, H256,
+            transaction::eip2930::AccessList, Eip1559TransactionRequest, StorageEntry,
+            TransactionRequest, H256,
         },
         utils::{Anvil, Genesis, Geth, GethInstance},
     };
@@ -2074,6 +3413,169 @@ mod tests {
         assert!(!receipts.is_empty());
     }
 
+    #[test]
+    fn recognizes_method_not_found_errors() {
+        let err = |code, message: &str| {
+            let err = JsonRpcError { code, message: message.to_string(), data: None };
+            ProviderError::JsonRpcClientError(Box::new(err))
+        };
+        assert!(is_method_not_found(&err(-32601, "Method not found")));
+        for message in [
+            "the method eth_getBlockReceipts does not exist/is not available",
+            "The method 'eth_getBlockReceipts' is not supported.",
+            "Unsupported method: eth_getBlockReceipts",
+            "Method not found",
+        ] {
+            assert!(is_method_not_found(&err(-32000, message)), "{message}");
+        }
+        for message in [
+            "historical state not available",
+            "block does not exist",
+            "missing trie node 42 (path ) state 0x42 is not available",
+            "the method eth_call failed: header not found",
+        ] {
+            assert!(!is_method_not_found(&err(-32000, message)), "{message}");
+        }
+    }
+
+    #[tokio::test]
+    async fn block_receipts_fallback() {
+        let (provider, mock) = Provider::mocked();
+        // the node doesn't serve `eth_getBlockReceipts`
+        let message = "the method eth_getBlockReceipts does not exist".to_string();
+        let err = JsonRpcError { code: -32000, message, data: None };
+        mock.expect("eth_getBlockReceipts").returns_error(err).unwrap();
+        let hashes = [H256::repeat_byte(1), H256::repeat_byte(2)];
+        let receipts = hashes
+            .map(|transaction_hash| TransactionReceipt { transaction_hash, ..Default::default() });
+        mock.push(receipts[1].clone()).unwrap();
+        mock.push(receipts[0].clone()).unwrap();
+        mock.push(Block::<TxHash> { transactions: hashes.to_vec(), ..Default::default() }).unwrap();
+
+        let res = provider.get_block_receipts(7u64).await.unwrap();
+        assert_eq!(res, receipts);
+        let block = utils::serialize(&BlockNumber::from(7u64));
+        mock.assert_request("eth_getBlockReceipts", [&block]).unwrap();
+        mock.assert_request("eth_getBlockByNumber", [block, utils::serialize(&false)]).unwrap();
+        mock.assert_request("eth_getTransactionReceipt", [hashes[0]]).unwrap();
+        mock.assert_request("eth_getTransactionReceipt", [hashes[1]]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn request_timeout() {
+        let (provider, mock) = Provider::mocked();
+        // the node takes too long to answer `debug_traceTransaction`
+        let slow = mock.expect("debug_traceTransaction").delay(Duration::from_secs(60));
+        slow.returns(serde_json::Value::Null).unwrap();
+        mock.push(U64::from(7)).unwrap();
+        let provider = provider.request_timeout(Duration::from_millis(50));
+
+        assert_eq!(provider.get_block_number().await.unwrap(), 7.into());
+        let err = provider.debug_trace_transaction(H256::zero(), Default::default()).await;
+        assert!(matches!(err, Err(ProviderError::Timeout(t)) if t == Duration::from_millis(50)));
+
+        let timeout = Duration::from_millis(10);
+        let err = provider
+            .request_with_timeout::<_, serde_json::Value>("debug_traceTransaction", (), timeout)
+            .await;
+        assert!(matches!(err, Err(ProviderError::Timeout(t)) if t == timeout));
+    }
+
+    #[tokio::test]
+    async fn net_status() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(true).unwrap();
+        mock.push(U64::from(25)).unwrap();
+        assert_eq!(provider.get_net_peer_count().await.unwrap(), 25.into());
+        assert!(provider.get_net_listening().await.unwrap());
+        mock.assert_request("net_peerCount", ()).unwrap();
+        mock.assert_request("net_listening", ()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn fills_fees_with_the_fee_strategy() {
+        let (provider, mock) = Provider::mocked();
+        let strategy = FeeHistoryStrategy::fast().block_count(2).min_priority_fee(5);
+        let provider = provider.fee_strategy(strategy);
+        mock.push(FeeHistory {
+            base_fee_per_gas: vec![90.into(), 100.into()],
+            gas_used_ratio: vec![0.5, 0.5],
+            oldest_block: 1.into(),
+            reward: vec![vec![3.into()], vec![7.into()]],
+        })
+        .unwrap();
+
+        let mut tx: TypedTransaction =
+            Eip1559TransactionRequest::new().to(Address::zero()).gas(21_000).into();
+        provider.fill_transaction(&mut tx, None).await.unwrap();
+        let tx = tx.as_eip1559_ref().unwrap();
+        assert_eq!(tx.max_priority_fee_per_gas, Some(7.into()));
+        assert_eq!(tx.max_fee_per_gas, Some(207.into()));
+        mock.assert_request("eth_feeHistory", (U256::from(2), "latest", [90.0])).unwrap();
+    }
+
+    #[tokio::test]
+    async fn fills_priority_fees_with_the_fallback() {
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.priority_fee_fallback(PriorityFeeFallback::Fixed(7.into()));
+        // the node doesn't serve `eth_maxPriorityFeePerGas`
+        let err =
+            JsonRpcError { code: -32601, message: "Method not found".to_string(), data: None };
+        mock.expect("eth_maxPriorityFeePerGas").returns_error(err).unwrap();
+        mock.push(Block::<TxHash> { base_fee_per_gas: Some(100.into()), ..Default::default() })
+            .unwrap();
+
+        let mut tx: TypedTransaction =
+            Eip1559TransactionRequest::new().to(Address::zero()).gas(21_000).into();
+        provider.fill_transaction(&mut tx, None).await.unwrap();
+        let tx = tx.as_eip1559_ref().unwrap();
+        assert_eq!(tx.max_priority_fee_per_gas, Some(7.into()));
+        assert_eq!(tx.max_fee_per_gas, Some(200.into()));
+        mock.assert_request("eth_getBlockByNumber", ("latest", false)).unwrap();
+        mock.assert_request("eth_maxPriorityFeePerGas", ()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn max_priority_fee_fallback() {
+        let (provider, mock) = Provider::mocked();
+        // the node doesn't serve `eth_maxPriorityFeePerGas`
+        let err =
+            JsonRpcError { code: -32601, message: "Method not found".to_string(), data: None };
+        mock.expect("eth_maxPriorityFeePerGas").returns_error(err).unwrap();
+
+        // without a configured fallback, the fallback of the chain is used
+        mock.push(U256::from(137)).unwrap();
+        assert_eq!(provider.max_priority_fee_per_gas().await.unwrap(), 30_000_000_000u64.into());
+        mock.assert_request("eth_maxPriorityFeePerGas", ()).unwrap();
+        mock.assert_request("eth_chainId", ()).unwrap();
+
+        let provider = provider.priority_fee_fallback(PriorityFeeFallback::Fixed(7.into()));
+        assert_eq!(provider.max_priority_fee_per_gas().await.unwrap(), 7.into());
+        mock.assert_request("eth_maxPriorityFeePerGas", ()).unwrap();
+
+        let provider = provider.priority_fee_fallback(PriorityFeeFallback::BlocksMedian(2));
+        let tx = |gas_price: u64, fees: Option<(u64, u64)>| Transaction {
+            gas_price: Some(gas_price.into()),
+            max_fee_per_gas: fees.map(|fees| fees.0.into()),
+            max_priority_fee_per_gas: fees.map(|fees| fees.1.into()),
+            ..Default::default()
+        };
+        let block = |transactions| Block::<Transaction> {
+            base_fee_per_gas: Some(10.into()),
+            transactions,
+            ..Default::default()
+        };
+        mock.push(block(vec![tx(15, None), tx(14, Some((14, 5)))])).unwrap();
+        mock.push(block(vec![tx(13, Some((30, 3)))])).unwrap();
+        mock.push(U64::from(9)).unwrap();
+        // the tips are 3, 4 and 5
+        assert_eq!(provider.max_priority_fee_per_gas().await.unwrap(), 4.into());
+        mock.assert_request("eth_maxPriorityFeePerGas", ()).unwrap();
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        let number = utils::serialize(&BlockNumber::from(8u64));
+        mock.assert_request("eth_getBlockByNumber", [number, utils::serialize(&true)]).unwrap();
+    }
+
     #[tokio::test]
     // Celo blocks can not get parsed when used with Ganache
     #[cfg(not(feature = "celo"))]
@@ -2142,6 +3644,165 @@ mod tests {
         dbg!(traces);
     }
 
+    #[tokio::test]
+    async fn learns_polling_interval() {
+        let (provider, mock) = Provider::mocked();
+        assert_eq!(provider.get_interval(), DEFAULT_POLL_INTERVAL);
+
+        let block = |number: u64, timestamp: u64| Block::<TxHash> {
+            number: Some(number.into()),
+            timestamp: timestamp.into(),
+            ..Default::default()
+        };
+        mock.push(block(90, 1_000)).unwrap();
+        mock.push(block(100, 1_040)).unwrap();
+        mock.push(U64::from(100)).unwrap();
+        assert_eq!(provider.tune_interval().await.unwrap(), Duration::from_secs(2));
+        assert_eq!(provider.clone().get_interval(), Duration::from_secs(2));
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_getBlockByNumber", ("0x64", false)).unwrap();
+        mock.assert_request("eth_getBlockByNumber", ("0x5a", false)).unwrap();
+
+        // the hint of the chain doesn't replace the tuned interval
+        let provider = provider.with_chain_id(Chain::Polygon as u64);
+        assert_eq!(provider.get_interval(), Duration::from_secs(2));
+        mock.push(U256::from(Chain::Mainnet as u64)).unwrap();
+        provider.refresh_chain_id().await.unwrap();
+        assert_eq!(provider.get_interval(), Duration::from_secs(2));
+
+        // from the block time hint of the chain
+        let provider = Provider::mocked().0.with_chain_id(Chain::Polygon as u64);
+        assert_eq!(provider.get_interval(), Duration::from_millis(1_050));
+        let provider = provider.with_chain_id(Chain::Mainnet as u64);
+        assert_eq!(provider.get_interval(), Duration::from_millis(6_500));
+
+        // a set interval takes precedence
+        let provider = provider.interval(Duration::from_secs(1));
+        assert_eq!(provider.get_interval(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn probes_node_capabilities() {
+        // a pre-London geth node without the `trace` namespace
+        let (provider, mock) = Provider::mocked();
+        let err = |code, message: &str| JsonRpcError { code, message: message.into(), data: None };
+        let stubs = [
+            mock.expect("web3_clientVersion").returns("Geth/v1.10.8-stable/linux-amd64").unwrap(),
+            mock.expect("eth_getBlockByNumber").returns(Block::<TxHash>::default()).unwrap(),
+            mock.expect("debug_traceBlockByNumber")
+                .returns_error(err(-32000, "genesis is not traceable"))
+                .unwrap(),
+            mock.expect("trace_block")
+                .returns_error(err(
+                    -32601,
+                    "the method trace_block does not exist/is not available",
+                ))
+                .unwrap(),
+        ];
+
+        assert_eq!(provider.capabilities(), None);
+        let capabilities = provider.node_capabilities().await.unwrap();
+        assert_eq!(
+            capabilities,
+            NodeCapabilities {
+                client_version: "Geth/v1.10.8-stable/linux-amd64".to_string(),
+                client: Some(NodeClient::Geth),
+                eip1559: false,
+                debug: true,
+                trace: false,
+                pubsub: false,
+            }
+        );
+        assert_eq!(provider.clone().capabilities(), Some(capabilities));
+        let calls = || stubs.iter().map(|stub| stub.calls()).sum::<usize>();
+        assert_eq!(calls(), 4);
+
+        // unsupported namespaces aren't requested
+        let err = provider.trace_block(BlockNumber::Latest).await.unwrap_err();
+        assert!(
+            matches!(err, ProviderError::UnsupportedMethod(ref method) if method == "trace_block")
+        );
+        provider.node_capabilities().await.unwrap();
+        assert_eq!(calls(), 4);
+
+        // EIP-1559 transactions are sent as legacy transactions
+        let mut tx: TypedTransaction =
+            Eip1559TransactionRequest::new().max_fee_per_gas(5u64).gas(21_000u64).into();
+        provider.fill_transaction(&mut tx, None).await.unwrap();
+        assert!(matches!(tx, TypedTransaction::Legacy(_)));
+        assert_eq!(tx.gas_price(), Some(5u64.into()));
+        assert_eq!(calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn create_access_list_unsupported() {
+        let (provider, mock) = Provider::mocked();
+        let message = "the method eth_createAccessList does not exist/is not available";
+        mock.push_error(JsonRpcError { code: -32000, message: message.to_string(), data: None });
+        let tx = TransactionRequest::new().to(Address::repeat_byte(1)).into();
+        let err = provider.create_access_list(&tx, None).await.unwrap_err();
+        assert!(
+            matches!(err, ProviderError::UnsupportedMethod(ref method) if method == "eth_createAccessList")
+        );
+
+        let access_list = AccessListWithGasUsed {
+            access_list: AccessList::default(),
+            gas_used: 21_000u64.into(),
+        };
+        mock.push(access_list).unwrap();
+        let res = provider.create_access_list(&tx, None).await.unwrap();
+        assert_eq!(res.gas_used, 21_000u64.into());
+    }
+
+    #[tokio::test]
+    async fn trace_call_state_diff() {
+        let (provider, mock) = Provider::mocked();
+        let from = Address::repeat_byte(1);
+        let to = Address::repeat_byte(2);
+        let tx = TransactionRequest::new().from(from).to(to).value(10u64);
+
+        let trace: BlockTrace = serde_json::from_value(serde_json::json!({
+            "output": "0x",
+            "trace": null,
+            "vmTrace": null,
+            "stateDiff": {
+                "0x0202020202020202020202020202020202020202": {
+                    "balance": {"*": {"from": "0x0", "to": "0xa"}},
+                    "nonce": "=",
+                    "code": "=",
+                    "storage": {}
+                }
+            }
+        }))
+        .unwrap();
+        mock.push(trace.clone()).unwrap();
+        let res = provider.trace_call(tx.clone(), vec![TraceType::StateDiff], Some(5u64.into()));
+        let res = res.await.unwrap();
+        let diff = res.state_diff.unwrap();
+        assert_eq!(diff.account(&to).unwrap().balance.after(), Some(&U256::from(10)));
+        let typed: TypedTransaction = tx.clone().into();
+        mock.assert_request(
+            "trace_call",
+            [
+                utils::serialize(&typed),
+                utils::serialize(&[TraceType::StateDiff]),
+                utils::serialize(&BlockNumber::from(5u64)),
+            ],
+        )
+        .unwrap();
+
+        mock.push::<Vec<BlockTrace>, _>(vec![trace.clone(), trace]).unwrap();
+        let calls = vec![(tx.clone(), vec![TraceType::StateDiff]), (tx, vec![TraceType::Trace])];
+        assert_eq!(provider.trace_call_many(calls, None).await.unwrap().len(), 2);
+        let calls =
+            vec![(typed.clone(), vec![TraceType::StateDiff]), (typed, vec![TraceType::Trace])];
+        mock.assert_request(
+            "trace_callMany",
+            [utils::serialize(&calls), utils::serialize(&BlockNumber::Latest)],
+        )
+        .unwrap();
+    }
+
     #[tokio::test]
     async fn test_fill_transaction_1559() {
         let (mut provider, mock) = Provider::mocked();
@@ -2249,6 +3910,304 @@ mod tests {
         assert!(tx.access_list().is_none());
     }
 
+    #[tokio::test]
+    async fn subscribe_or_watch_logs_falls_back_to_polling() {
+        let (provider, mock) = Provider::mocked();
+        let filter = Filter::new().event("Transfer(address,address,uint256)");
+
+        mock.push(U256::from(7u64)).unwrap();
+        let stream = provider.subscribe_or_watch_logs(&filter).await.unwrap();
+
+        assert!(!stream.is_subscription());
+        assert_eq!(stream.id(), U256::from(7u64));
+        mock.assert_request("eth_newFilter", [&filter]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn debug_trace_transaction_with_builtin_tracers() {
+        let (provider, mock) = Provider::mocked();
+        let tx_hash = H256::repeat_byte(1);
+
+        let frame = CallFrame {
+            typ: "CALL".to_string(),
+            from: Address::repeat_byte(2),
+            to: Some(Address::repeat_byte(3).into()),
+            gas_used: 21000u64.into(),
+            ..Default::default()
+        };
+        mock.push(frame.clone()).unwrap();
+        let config = CallConfig { only_top_call: Some(true), with_log: None };
+        let trace = provider.debug_trace_transaction_call_tracer(tx_hash, config.clone()).await;
+        assert_eq!(trace.unwrap(), frame);
+        mock.assert_request(
+            "debug_traceTransaction",
+            (tx_hash, GethDebugTracingOptions::call_tracer(config)),
+        )
+        .unwrap();
+
+        let diff: PreStateFrame = serde_json::from_str(
+            r#"{"pre":{"0x0202020202020202020202020202020202020202":{"balance":"0x2"}},"post":{"0x0202020202020202020202020202020202020202":{"balance":"0x1"}}}"#,
+        )
+        .unwrap();
+        mock.push(diff.clone()).unwrap();
+        let config = PreStateConfig { diff_mode: Some(true) };
+        let trace = provider.debug_trace_transaction_prestate_tracer(tx_hash, config.clone()).await;
+        assert_eq!(trace.unwrap(), diff);
+        mock.assert_request(
+            "debug_traceTransaction",
+            (tx_hash, GethDebugTracingOptions::prestate_tracer(config)),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn debug_storage_range_at() {
+        let (provider, mock) = Provider::mocked();
+        let block_hash = H256::repeat_byte(1);
+        let address = Address::repeat_byte(2);
+
+        let mut range =
+            StorageRangeResult { next_key: Some(H256::repeat_byte(4)), ..Default::default() };
+        range.storage.insert(
+            H256::repeat_byte(3),
+            StorageEntry { key: Some(H256::zero()), value: H256::from_low_u64_be(1) },
+        );
+        mock.push(range.clone()).unwrap();
+        let res = provider.debug_storage_range_at(block_hash, 0, address, H256::zero(), 10).await;
+        assert_eq!(res.unwrap(), range);
+        mock.assert_request("debug_storageRangeAt", (block_hash, 0, address, H256::zero(), 10))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn txpool_content_from() {
+        let (provider, mock) = Provider::mocked();
+        let sender = Address::repeat_byte(1);
+        let tx = Transaction { from: sender, nonce: 7u64.into(), ..Default::default() };
+        let content = TxpoolContentFrom {
+            pending: [("7".to_string(), tx)].into_iter().collect(),
+            queued: Default::default(),
+        };
+
+        mock.push(content.clone()).unwrap();
+        assert_eq!(provider.txpool_content_from(sender).await.unwrap(), content);
+        mock.assert_request("txpool_contentFrom", [sender]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn caches_immutable_responses() {
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.response_cache(16);
+
+        mock.expect("net_version").times(1).returns("1").unwrap();
+        assert_eq!(provider.get_net_version().await.unwrap(), "1");
+        assert_eq!(provider.clone().get_net_version().await.unwrap(), "1");
+
+        // pending transactions are not cached
+        let hash = H256::repeat_byte(1);
+        let mut tx = Transaction { hash, ..Default::default() };
+        mock.push(tx.clone()).unwrap();
+        assert_eq!(provider.get_transaction(hash).await.unwrap().unwrap().block_hash, None);
+
+        tx.block_hash = Some(H256::repeat_byte(2));
+        tx.block_number = Some(1u64.into());
+        mock.expect("eth_getTransactionByHash").times(1).returns(tx.clone()).unwrap();
+        assert_eq!(provider.get_transaction(hash).await.unwrap(), Some(tx.clone()));
+        assert_eq!(provider.get_transaction(hash).await.unwrap(), Some(tx));
+
+        // mutable state is always requested
+        mock.push(U64::from(1)).unwrap();
+        mock.push(U64::from(2)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(2));
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(1));
+        mock.verify().unwrap();
+
+        // disabled cache
+        let provider = provider.response_cache(0);
+        assert!(provider.get_net_version().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn pins_chain_id() {
+        let (provider, mock) = Provider::mocked();
+        assert_eq!(provider.chain_id(), None);
+
+        mock.expect("eth_chainId").times(1).returns(U256::one()).unwrap();
+        assert_eq!(provider.get_chainid().await.unwrap(), U256::one());
+        assert_eq!(provider.clone().get_chainid().await.unwrap(), U256::one());
+        assert_eq!(provider.chain_id(), Some(U256::one()));
+        mock.verify().unwrap();
+
+        // refreshing updates the clones
+        let clone = provider.clone();
+        mock.push(U256::from(5)).unwrap();
+        assert_eq!(provider.refresh_chain_id().await.unwrap(), U256::from(5));
+        assert_eq!(clone.get_chainid().await.unwrap(), U256::from(5));
+
+        let provider = provider.with_chain_id(10u64);
+        assert_eq!(provider.get_chainid().await.unwrap(), U256::from(10));
+        assert_eq!(clone.chain_id(), Some(U256::from(5)));
+    }
+
+    #[tokio::test]
+    async fn reads_storage_slots() {
+        let (provider, mock) = Provider::mocked();
+        // nodes trim the leading zeroes of the values
+        for value in ["0x2", "0x1234"] {
+            mock.expect("eth_getStorageAt")
+                .with_params((Address::zero(), value, "latest"))
+                .times(1)
+                .returns(value)
+                .unwrap();
+        }
+
+        let slots = [H256::from_low_u64_be(2), H256::from_low_u64_be(0x1234)];
+        let values = provider.get_storage_batch(Address::zero(), &slots, None).await.unwrap();
+        assert_eq!(values, slots);
+        mock.verify().unwrap();
+    }
+
+    #[tokio::test]
+    async fn resolves_wildcard_names() {
+        let (provider, mock) = Provider::mocked();
+        let resolver = Address::repeat_byte(1);
+        let owner = Address::repeat_byte(2);
+        let word = |token| Bytes::from(abi::encode(&[token]));
+
+        let record = abi::encode(&[Token::Address(owner)]);
+        mock.push::<Bytes, _>(word(Token::Bytes(record))).unwrap();
+        mock.push::<Bytes, _>(word(Token::Bool(true))).unwrap();
+        mock.push::<Bytes, _>(word(Token::Address(resolver))).unwrap();
+        mock.push::<Bytes, _>(word(Token::Address(Address::zero()))).unwrap();
+        assert_eq!(provider.resolve_name("sub.foo.eth").await.unwrap(), owner);
+
+        let record = ens::resolve(resolver, ens::ADDR_SELECTOR, "sub.foo.eth", None);
+        for tx in [
+            ens::get_resolver(ens::ENS_ADDRESS, "sub.foo.eth"),
+            ens::get_resolver(ens::ENS_ADDRESS, "foo.eth"),
+            ens::supports_interface(resolver, ens::RESOLVE_SELECTOR),
+            ens::resolve_extended(resolver, "sub.foo.eth", &record.data.unwrap()).unwrap(),
+        ] {
+            let tx: TypedTransaction = tx.into();
+            mock.assert_request("eth_call", [utils::serialize(&tx), utils::serialize(&"latest")])
+                .unwrap();
+        }
+
+        // the resolvers of ancestors must support wildcards
+        mock.push::<Bytes, _>(word(Token::Bool(false))).unwrap();
+        mock.push::<Bytes, _>(word(Token::Address(resolver))).unwrap();
+        mock.push::<Bytes, _>(word(Token::Address(Address::zero()))).unwrap();
+        let err = provider.resolve_name("sub.foo.eth").await.unwrap_err();
+        assert!(err.to_string().contains("does not support wildcards"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn resolves_multicoin_addresses() {
+        let (provider, mock) = Provider::mocked();
+        let resolver = Address::repeat_byte(1);
+        let word = |token| Bytes::from(abi::encode(&[token]));
+        let script = hex::decode("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+
+        mock.push::<Bytes, _>(word(Token::Bytes(script.clone()))).unwrap();
+        mock.push::<Bytes, _>(word(Token::Address(resolver))).unwrap();
+        let addr = provider.resolve_multicoin("foo.eth", ens::coin_type::BTC).await.unwrap();
+        assert_eq!(addr, ens::MulticoinAddress::new(ens::coin_type::BTC, script));
+        assert_eq!(addr.to_string(), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+
+        let record = ens::resolve(
+            resolver,
+            ens::MULTICOIN_ADDR_SELECTOR,
+            "foo.eth",
+            Some(&ens::bytes_32ify(ens::coin_type::BTC)),
+        );
+        // names with a resolver of their own don't need wildcard support
+        for tx in [ens::get_resolver(ens::ENS_ADDRESS, "foo.eth"), record] {
+            let tx: TypedTransaction = tx.into();
+            mock.assert_request("eth_call", [utils::serialize(&tx), utils::serialize(&"latest")])
+                .unwrap();
+        }
+        assert!(mock.assert_request("eth_call", ()).is_err());
+
+        // unset records are empty
+        mock.push::<Bytes, _>(word(Token::Bytes(vec![]))).unwrap();
+        mock.push::<Bytes, _>(word(Token::Address(resolver))).unwrap();
+        provider.resolve_multicoin("foo.eth", ens::coin_type::SOL).await.unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn resolves_names_in_one_call() {
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.with_chain_id(Chain::Sepolia as u64);
+        let universal_resolver = ens::universal_resolver(Chain::Sepolia).unwrap();
+        let owner = Address::repeat_byte(2);
+        let resolver = Address::repeat_byte(1);
+        let results = |results: Vec<(bool, Vec<u8>)>| {
+            let results = results
+                .into_iter()
+                .map(|(success, data)| Token::Tuple(vec![Token::Bool(success), Token::Bytes(data)]))
+                .collect();
+            Bytes::from(abi::encode(&[Token::Array(results)]))
+        };
+
+        let record = abi::encode(&[Token::Address(owner)]);
+        let resolved = abi::encode(&[Token::Bytes(record), Token::Address(resolver)]);
+        mock.push::<Bytes, _>(results(vec![(true, resolved), (false, vec![])])).unwrap();
+        let addrs = provider.resolve_names(&["foo.eth", "unknown.eth"]).await.unwrap();
+        assert_eq!(addrs, vec![Some(owner), None]);
+
+        let calls = ["foo.eth", "unknown.eth"].map(|name| {
+            let data = [&ens::ADDR_SELECTOR[..], &ens::namehash(name).0].concat();
+            let tx = ens::resolve_extended(universal_resolver, name, &data).unwrap();
+            (universal_resolver, tx.data.unwrap().to_vec())
+        });
+        let tx: TypedTransaction = ens::aggregate3(ens::MULTICALL3_ADDRESS, calls).into();
+        mock.assert_request("eth_call", [utils::serialize(&tx), utils::serialize(&"latest")])
+            .unwrap();
+
+        // names must resolve back to the address
+        let other = Address::repeat_byte(3);
+        let reverse = |name: &str, addr| {
+            abi::encode(&[
+                Token::String(name.to_string()),
+                Token::Address(addr),
+                Token::Address(resolver),
+                Token::Address(resolver),
+            ])
+        };
+        mock.push::<Bytes, _>(results(vec![
+            (true, reverse("foo.eth", owner)),
+            (true, reverse("foo.eth", owner)),
+        ]))
+        .unwrap();
+        let names = provider.lookup_addresses(&[owner, other]).await.unwrap();
+        assert_eq!(names, vec![Some("foo.eth".to_string()), None]);
+
+        let calls =
+            [owner, other].map(|addr| (universal_resolver, ens::reverse_extended(addr).unwrap()));
+        let tx: TypedTransaction = ens::aggregate3(ens::MULTICALL3_ADDRESS, calls).into();
+        mock.assert_request("eth_call", [utils::serialize(&tx), utils::serialize(&"latest")])
+            .unwrap();
+
+        assert_eq!(provider.resolve_names(&[]).await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn resolves_names_one_by_one_without_universal_resolver() {
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.with_chain_id(Chain::Polygon as u64);
+        // the registry has no resolver for the name
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        let addrs = provider.resolve_names(&["foo.eth"]).await.unwrap();
+        assert_eq!(addrs, vec![None]);
+
+        for name in ["foo.eth", "eth"] {
+            let tx: TypedTransaction = ens::get_resolver(ens::ENS_ADDRESS, name).into();
+            mock.assert_request("eth_call", [utils::serialize(&tx), utils::serialize(&"latest")])
+                .unwrap();
+        }
+    }
+
     #[tokio::test]
     async fn mainnet_lookup_address_invalid_resolver() {
         let provider = crate::MAINNET.provider();
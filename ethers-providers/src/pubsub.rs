@@ -1,13 +1,17 @@
-use crate::{JsonRpcClient, Middleware, Provider, TransactionStream};
+use crate::{
+    stream::{BlockStream, GetTransactionError},
+    FilterWatcher, JsonRpcClient, LogQuery, LogQueryError, Middleware, PinBoxFut, Provider,
+    ProviderError, PubsubClientWrapper, TransactionStream,
+};
 
-use ethers_core::types::{TxHash, U256};
+use ethers_core::types::{Block, Log, Transaction, TxHash, H256, U256, U64};
 
-use futures_util::stream::Stream;
+use futures_util::stream::{Stream, StreamExt};
 use pin_project::{pin_project, pinned_drop};
 use serde::de::DeserializeOwned;
 use serde_json::value::RawValue;
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
     marker::PhantomData,
     pin::Pin,
     task::{Context, Poll},
@@ -71,6 +75,26 @@ where
     }
 }
 
+#[cfg(feature = "ws")]
+impl<'a, R: DeserializeOwned> SubscriptionStream<'a, crate::Ws, R> {
+    /// Returns the number of notifications the [`Ws`](crate::Ws) client dropped since the last
+    /// call because the buffer of this subscription was full, see
+    /// [`Ws::with_subscription_buffer`](crate::Ws::with_subscription_buffer).
+    pub fn take_lagged(&self) -> u64 {
+        self.rx.take_lagged()
+    }
+
+    /// Returns a stream of the items that yields [`Lagged`](crate::Lagged) in place of the
+    /// notifications the [`Ws`](crate::Ws) client dropped because the buffer of this subscription
+    /// was full
+    pub fn report_lag(self) -> impl Stream<Item = Result<R, crate::Lagged>> + Unpin + 'a
+    where
+        R: 'a,
+    {
+        crate::transports::report_lag(Box::pin(self), |stream| stream.take_lagged())
+    }
+}
+
 // Each subscription item is a serde_json::Value which must be decoded to the
 // subscription's return type.
 // TODO: Can this be replaced with an `rx.map` in the constructor?
@@ -129,3 +153,760 @@ where
         TransactionStream::new(self.provider, self, n)
     }
 }
+
+/// The hashes of the block headers of a [`Middleware::subscribe_blocks`] subscription
+pub type BlockHashStream<'a, P> = futures_util::stream::FilterMap<
+    SubscriptionStream<'a, P, Block<TxHash>>,
+    futures_util::future::Ready<Option<H256>>,
+    fn(Block<TxHash>) -> futures_util::future::Ready<Option<H256>>,
+>;
+
+impl<'a, P> SubscriptionStream<'a, P, Block<TxHash>>
+where
+    P: PubsubClient,
+{
+    /// Returns a stream that yields the full blocks, with their transactions, for the block
+    /// headers this stream yields.
+    ///
+    /// This internally calls `Provider::get_block_with_txs` with every new block. No more than n
+    /// futures will be buffered at any point in time, the blocks are yielded in order.
+    pub fn full_blocks(self, n: usize) -> BlockStream<'a, P, BlockHashStream<'a, P>> {
+        let provider = self.provider;
+        let hashes: BlockHashStream<'a, P> =
+            self.filter_map(|header| futures_util::future::ready(header.hash));
+        BlockStream::new(provider, hashes, n)
+    }
+}
+
+/// Notification stream returned by a type-erased [`PubsubClientWrapper`]
+type DynNotificationStream =
+    Box<dyn futures_core::Stream<Item = Box<RawValue>> + Send + Unpin + 'static>;
+
+#[must_use = "subscriptions do nothing unless you stream them"]
+/// Streams data from an installed filter via `eth_subscribe`, like [`SubscriptionStream`], but
+/// without requiring the provider's transport to implement [`PubsubClient`] at compile time.
+///
+/// The transport's subscription support is resolved at runtime via
+/// [`JsonRpcClient::as_pubsub`].
+pub struct DynSubscriptionStream<'a, P, R> {
+    /// The subscription's installed id on the ethereum node
+    pub id: U256,
+
+    loaded_elements: VecDeque<R>,
+
+    provider: &'a Provider<P>,
+
+    pubsub: &'a dyn PubsubClientWrapper,
+
+    rx: DynNotificationStream,
+}
+
+impl<'a, P, R> DynSubscriptionStream<'a, P, R>
+where
+    P: JsonRpcClient,
+    R: DeserializeOwned,
+{
+    /// Creates a new subscription stream for the provided subscription id.
+    ///
+    /// Returns an error if the provider's transport does not support subscriptions.
+    ///
+    /// ### Note
+    /// See [`SubscriptionStream::new`] for why this should not be instantiated with a known ID.
+    pub fn new(id: U256, provider: &'a Provider<P>) -> Result<Self, ProviderError> {
+        let pubsub = provider.as_ref().as_pubsub().ok_or_else(|| {
+            ProviderError::CustomError("transport does not support subscriptions".to_string())
+        })?;
+        let rx = pubsub.subscribe(id)?;
+        Ok(Self { id, provider, pubsub, rx, loaded_elements: VecDeque::new() })
+    }
+
+    /// Unsubscribes from the subscription.
+    pub async fn unsubscribe(&self) -> Result<bool, ProviderError> {
+        self.provider.request("eth_unsubscribe", [self.id]).await
+    }
+
+    pub fn set_loaded_elements(&mut self, loaded_elements: VecDeque<R>) {
+        self.loaded_elements = loaded_elements;
+    }
+}
+
+impl<'a, P, R> Stream for DynSubscriptionStream<'a, P, R>
+where
+    R: DeserializeOwned + Unpin,
+{
+    type Item = R;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Some(next_element) = this.loaded_elements.pop_front() {
+            return Poll::Ready(Some(next_element))
+        }
+
+        match futures_util::ready!(this.rx.poll_next_unpin(ctx)) {
+            Some(item) => match serde_json::from_str(item.get()) {
+                Ok(res) => Poll::Ready(Some(res)),
+                Err(err) => {
+                    error!("failed to deserialize item {:?}", err);
+                    Poll::Pending
+                }
+            },
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+impl<P, R> Drop for DynSubscriptionStream<'_, P, R> {
+    fn drop(&mut self) {
+        // on drop it removes the handler from the transport so that it stops
+        // getting populated. We need to call `unsubscribe` explicitly to cancel
+        // the subscription
+        let _ = self.pubsub.unsubscribe(self.id);
+    }
+}
+
+/// A stream that is either backed by an `eth_subscribe` subscription or, if the transport does
+/// not support subscriptions, by polling an installed filter.
+///
+/// Created by [`Middleware::subscribe_or_watch_logs`].
+#[must_use = "streams do nothing unless you stream them"]
+pub enum SubscribeOrPoll<'a, P, R> {
+    /// Items are pushed by the node via `eth_subscribe`
+    Subscription(DynSubscriptionStream<'a, P, R>),
+    /// Items are fetched by polling an installed filter
+    Poll(FilterWatcher<'a, P, R>),
+}
+
+impl<'a, P, R> SubscribeOrPoll<'a, P, R> {
+    /// The id of the underlying subscription or installed filter
+    pub fn id(&self) -> U256 {
+        match self {
+            SubscribeOrPoll::Subscription(stream) => stream.id,
+            SubscribeOrPoll::Poll(watcher) => watcher.id,
+        }
+    }
+
+    /// Returns `true` if this is backed by an `eth_subscribe` subscription
+    pub fn is_subscription(&self) -> bool {
+        matches!(self, SubscribeOrPoll::Subscription(_))
+    }
+}
+
+impl<'a, P, R> Stream for SubscribeOrPoll<'a, P, R>
+where
+    P: JsonRpcClient,
+    R: serde::Serialize + Send + Sync + DeserializeOwned + std::fmt::Debug + Unpin + 'a,
+{
+    type Item = R;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
+        match self.get_mut() {
+            SubscribeOrPoll::Subscription(stream) => stream.poll_next_unpin(ctx),
+            SubscribeOrPoll::Poll(watcher) => watcher.poll_next_unpin(ctx),
+        }
+    }
+}
+
+/// A logs subscription that first yields the matching logs of past blocks, see
+/// [`Provider::subscribe_logs_from`].
+///
+/// The subscription is installed before the past logs are queried, logs pushed for blocks that
+/// were already queried are skipped unless they were `removed` by a reorg.
+#[must_use = "subscriptions do nothing unless you stream them"]
+pub struct BackfilledLogStream<'a, P: PubsubClient> {
+    /// The past logs, `None` once all of them were yielded
+    history: Option<LogQuery<'a, P>>,
+    live: SubscriptionStream<'a, P, Log>,
+    /// The latest block when subscribing, logs of later blocks may be both queried and pushed
+    subscribed_at: U64,
+    /// The queried logs of blocks after `subscribed_at`
+    seen: HashSet<(Option<H256>, Option<U256>)>,
+    failed: bool,
+}
+
+impl<'a, P: PubsubClient> BackfilledLogStream<'a, P> {
+    pub(crate) fn new(
+        history: LogQuery<'a, P>,
+        live: SubscriptionStream<'a, P, Log>,
+        subscribed_at: U64,
+    ) -> Self {
+        Self { history: Some(history), live, subscribed_at, seen: HashSet::new(), failed: false }
+    }
+
+    /// The id of the underlying subscription
+    pub fn id(&self) -> U256 {
+        self.live.id
+    }
+
+    /// Sets how many blocks are queried at once for the past logs (default: 10000)
+    pub fn page_size(mut self, page_size: u64) -> Self {
+        self.history = self.history.map(|history| history.with_page_size(page_size));
+        self
+    }
+
+    /// Unsubscribes from the subscription.
+    pub async fn unsubscribe(&self) -> Result<bool, ProviderError> {
+        self.live.unsubscribe().await
+    }
+}
+
+impl<'a, P: PubsubClient> Stream for BackfilledLogStream<'a, P> {
+    type Item = Result<Log, LogQueryError<ProviderError>>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.failed {
+            return Poll::Ready(None)
+        }
+
+        if let Some(history) = this.history.as_mut() {
+            match futures_util::ready!(history.poll_next_unpin(ctx)) {
+                Some(Ok(log)) => {
+                    if log.block_number.map_or(false, |block| block > this.subscribed_at) {
+                        this.seen.insert((log.block_hash, log.log_index));
+                    }
+                    return Poll::Ready(Some(Ok(log)))
+                }
+                Some(Err(err)) => {
+                    // resuming with the pushed logs would leave a gap
+                    this.failed = true;
+                    return Poll::Ready(Some(Err(err)))
+                }
+                None => this.history = None,
+            }
+        }
+
+        loop {
+            match futures_util::ready!(this.live.poll_next_unpin(ctx)) {
+                Some(log) => {
+                    let removed = log.removed.unwrap_or_default();
+                    if !removed && this.seen.remove(&(log.block_hash, log.log_index)) {
+                        continue
+                    }
+                    return Poll::Ready(Some(Ok(log)))
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// How many of the latest blocks a [`ReorgStream`] remembers by default
+pub const DEFAULT_REORG_DEPTH: usize = 64;
+
+/// A reorganization of the chain, yielded by [`Provider::subscribe_reorgs`]
+///
+/// If the node went back to an earlier block without new blocks on top of it, e.g. after a
+/// snapshot of a development node was reverted, `new_blocks` is empty and the common ancestor is
+/// the new head, see [`Reorg::new_head`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    /// The latest block that's part of both the old and the new chain
+    pub common_ancestor: Block<TxHash>,
+    /// The blocks of the old chain after the common ancestor, in ascending order
+    pub dropped_blocks: Vec<Block<TxHash>>,
+    /// The blocks of the new chain after the common ancestor, in ascending order, the last one is
+    /// the new head. Empty if the common ancestor is the new head.
+    pub new_blocks: Vec<Block<TxHash>>,
+}
+
+impl Reorg {
+    /// The number of blocks that were dropped
+    pub fn depth(&self) -> usize {
+        self.dropped_blocks.len()
+    }
+
+    /// The head of the new chain, the last of the new blocks or the common ancestor if there are
+    /// none
+    pub fn new_head(&self) -> &Block<TxHash> {
+        self.new_blocks.last().unwrap_or(&self.common_ancestor)
+    }
+}
+
+/// Tracks the hashes of the latest blocks pushed by a `newHeads` subscription and yields a
+/// [`Reorg`] whenever a new head doesn't extend the chain seen so far, see
+/// [`Provider::subscribe_reorgs`].
+///
+/// Ancestors of a new head that weren't pushed are fetched, so gaps in the subscription don't
+/// register as reorgs. A reorg deeper than the remembered blocks is yielded as an error, after
+/// which tracking restarts from the new head.
+#[must_use = "subscriptions do nothing unless you stream them"]
+pub struct ReorgStream<'a, P: PubsubClient> {
+    provider: &'a Provider<P>,
+    heads: SubscriptionStream<'a, P, Block<TxHash>>,
+    /// The latest blocks of the canonical chain, in ascending order
+    chain: VecDeque<Block<TxHash>>,
+    depth: usize,
+    /// The blocks of the new head's chain that couldn't be connected to `chain` yet
+    pending: Vec<Block<TxHash>>,
+    parent: Option<PinBoxFut<'a, Option<Block<TxHash>>>>,
+}
+
+impl<'a, P: PubsubClient> ReorgStream<'a, P> {
+    pub(crate) fn new(
+        provider: &'a Provider<P>,
+        heads: SubscriptionStream<'a, P, Block<TxHash>>,
+    ) -> Self {
+        Self {
+            provider,
+            heads,
+            chain: VecDeque::new(),
+            depth: DEFAULT_REORG_DEPTH,
+            pending: Vec::new(),
+            parent: None,
+        }
+    }
+
+    /// Sets how many of the latest blocks are remembered, reorgs deeper than that can't be
+    /// resolved
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// The id of the underlying subscription
+    pub fn id(&self) -> U256 {
+        self.heads.id
+    }
+
+    /// Unsubscribes from the new heads
+    pub async fn unsubscribe(&self) -> Result<bool, ProviderError> {
+        self.heads.unsubscribe().await
+    }
+
+    fn extend(&mut self, blocks: Vec<Block<TxHash>>) {
+        self.chain.extend(blocks);
+        while self.chain.len() > self.depth {
+            self.chain.pop_front();
+        }
+    }
+
+    /// Replaces the blocks after the `ancestor`th block with `new_blocks`, returns the reorg if
+    /// any blocks were dropped
+    fn replace(
+        &mut self,
+        ancestor: usize,
+        new_blocks: Vec<Block<TxHash>>,
+    ) -> Option<Result<Reorg, ProviderError>> {
+        let dropped_blocks: Vec<_> = self.chain.split_off(ancestor + 1).into();
+        let common_ancestor = self.chain[ancestor].clone();
+        self.extend(new_blocks.clone());
+        if dropped_blocks.is_empty() {
+            return None
+        }
+        Some(Ok(Reorg { common_ancestor, dropped_blocks, new_blocks }))
+    }
+
+    /// Tries to connect the pending blocks to the chain, returns `None` if the parent of the
+    /// oldest pending block has to be fetched first or if there was no reorg
+    fn connect(&mut self) -> Option<Result<Reorg, ProviderError>> {
+        let first = &self.pending[0];
+        let (hash, parent_hash) = (first.hash.unwrap_or_default(), first.parent_hash);
+
+        if let Some(idx) = self.chain.iter().position(|block| block.hash == Some(hash)) {
+            // a known block was pushed again, the blocks after it are no longer canonical
+            let new_blocks: Vec<_> =
+                std::mem::take(&mut self.pending).into_iter().skip(1).collect();
+            return self.replace(idx, new_blocks)
+        }
+
+        if self.chain.is_empty() {
+            let blocks = std::mem::take(&mut self.pending);
+            self.extend(blocks);
+            return None
+        }
+
+        if let Some(idx) = self.chain.iter().position(|block| block.hash == Some(parent_hash)) {
+            let new_blocks = std::mem::take(&mut self.pending);
+            return self.replace(idx, new_blocks)
+        }
+
+        let oldest = self.chain.front().and_then(|block| block.number).unwrap_or_default();
+        if first.number.map_or(true, |number| number <= oldest) {
+            let blocks = std::mem::take(&mut self.pending);
+            self.chain.clear();
+            self.extend(blocks);
+            return Some(Err(ProviderError::CustomError(format!(
+                "reorg deeper than {} blocks",
+                self.depth
+            ))))
+        }
+
+        let provider = self.provider;
+        self.parent = Some(Box::pin(async move { provider.get_block(parent_hash).await }));
+        None
+    }
+}
+
+impl<'a, P: PubsubClient> Stream for ReorgStream<'a, P> {
+    type Item = Result<Reorg, ProviderError>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(parent) = this.parent.as_mut() {
+                let res = futures_util::ready!(parent.as_mut().poll(ctx));
+                this.parent = None;
+                match res {
+                    Ok(Some(block)) => this.pending.insert(0, block),
+                    Ok(None) => {
+                        let hash = this.pending[0].parent_hash;
+                        this.pending.clear();
+                        let err = format!("ancestor {hash:?} of the new head not found");
+                        return Poll::Ready(Some(Err(ProviderError::CustomError(err))))
+                    }
+                    Err(err) => {
+                        this.pending.clear();
+                        return Poll::Ready(Some(Err(err)))
+                    }
+                }
+            } else {
+                match futures_util::ready!(this.heads.poll_next_unpin(ctx)) {
+                    Some(head) => this.pending = vec![head],
+                    None => return Poll::Ready(None),
+                }
+            }
+
+            if let Some(reorg) = this.connect() {
+                return Poll::Ready(Some(reorg))
+            }
+        }
+    }
+}
+
+/// How many transactions a [`FullPendingTxStream`] fetches at once if the node only pushes their
+/// hashes
+pub const DEFAULT_PENDING_TX_CONCURRENCY: usize = 16;
+
+/// The full transactions entering the mempool, see [`Provider::subscribe_full_pending_txs`].
+#[must_use = "subscriptions do nothing unless you stream them"]
+pub struct FullPendingTxStream<'a, P: PubsubClient> {
+    id: U256,
+    provider: &'a Provider<P>,
+    txs: PendingTxs<'a, P>,
+}
+
+enum PendingTxs<'a, P: PubsubClient> {
+    /// The node pushes the full transactions
+    Full(SubscriptionStream<'a, P, Transaction>),
+    /// The node pushes the hashes, the transactions are fetched with `eth_getTransactionByHash`
+    Hydrated(TransactionStream<'a, P, SubscriptionStream<'a, P, TxHash>>),
+}
+
+impl<'a, P: PubsubClient> FullPendingTxStream<'a, P> {
+    pub(crate) fn full(
+        provider: &'a Provider<P>,
+        txs: SubscriptionStream<'a, P, Transaction>,
+    ) -> Self {
+        Self { id: txs.id, provider, txs: PendingTxs::Full(txs) }
+    }
+
+    pub(crate) fn hydrated(
+        provider: &'a Provider<P>,
+        hashes: SubscriptionStream<'a, P, TxHash>,
+        max_concurrent: usize,
+    ) -> Self {
+        let id = hashes.id;
+        Self {
+            id,
+            provider,
+            txs: PendingTxs::Hydrated(hashes.transactions_unordered(max_concurrent)),
+        }
+    }
+
+    /// The id of the underlying subscription
+    pub fn id(&self) -> U256 {
+        self.id
+    }
+
+    /// Returns `true` if the node only pushes the hashes of the transactions and they are fetched
+    /// with `eth_getTransactionByHash`
+    pub fn is_hydrated(&self) -> bool {
+        matches!(self.txs, PendingTxs::Hydrated(_))
+    }
+
+    /// Unsubscribes from the subscription.
+    pub async fn unsubscribe(&self) -> Result<bool, ProviderError> {
+        self.provider.unsubscribe(self.id).await
+    }
+}
+
+impl<'a, P: PubsubClient> Stream for FullPendingTxStream<'a, P> {
+    type Item = Result<Transaction, GetTransactionError>;
+
+    fn poll_next(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Option<Self::Item>> {
+        match &mut self.get_mut().txs {
+            PendingTxs::Full(txs) => txs.poll_next_unpin(ctx).map(|tx| tx.map(Ok)),
+            PendingTxs::Hydrated(txs) => txs.poll_next_unpin(ctx),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{JsonRpcError, MockError, MockProvider, MockPubsub};
+    use ethers_core::types::{Filter, Log};
+
+    fn log(block: u64) -> Log {
+        Log {
+            block_number: Some(block.into()),
+            block_hash: Some(H256::from_low_u64_be(block)),
+            log_index: Some(U256::zero()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn backfills_logs_before_pushed_logs() {
+        let client = MockPubsub::default();
+        let removed = Log { removed: Some(true), ..log(6) };
+        for log in [log(6), removed.clone(), log(8)] {
+            client.notify(1u64, &log);
+        }
+        client.mock.push::<Vec<Log>, _>(vec![]).unwrap();
+        client.mock.push::<Vec<Log>, _>(vec![log(6)]).unwrap();
+        client.mock.push::<Vec<Log>, _>(vec![log(4)]).unwrap();
+        client.mock.push(U64::from(7)).unwrap();
+        client.mock.push(U256::one()).unwrap();
+        client.mock.push(U64::from(5)).unwrap();
+        let provider = Provider::new(client.clone());
+
+        let filter = Filter::new().from_block(1u64).address(ethers_core::types::Address::zero());
+        let stream = provider.subscribe_logs_from(&filter, 3u64).await.unwrap().page_size(2);
+        assert_eq!(stream.id(), U256::one());
+        let logs = stream.map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(logs, vec![log(4), log(6), removed, log(8)]);
+
+        client.mock.assert_request("eth_blockNumber", ()).unwrap();
+        let live = Filter::new().address(ethers_core::types::Address::zero());
+        client.mock.assert_request("eth_subscribe", ("logs", &live)).unwrap();
+        client.mock.assert_request("eth_blockNumber", ()).unwrap();
+        client
+            .mock
+            .assert_request("eth_getLogs", [live.clone().from_block(3).to_block(4)])
+            .unwrap();
+        client
+            .mock
+            .assert_request("eth_getLogs", [live.clone().from_block(5).to_block(6)])
+            .unwrap();
+        client.mock.assert_request("eth_getLogs", [live.from_block(7).to_block(7)]).unwrap();
+    }
+
+    /// A block whose hash and parent hash are made of the given bytes
+    fn block(number: u64, hash: u8, parent: u8) -> Block<TxHash> {
+        Block {
+            number: Some(number.into()),
+            hash: Some(H256::repeat_byte(hash)),
+            parent_hash: H256::repeat_byte(parent),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn detects_reorgs() {
+        let client = MockPubsub::default();
+        // 1 <- 2 <- 3 is replaced by 1 <- 12 <- 13 <- 14, 15 is not pushed
+        let heads = [
+            block(1, 1, 0),
+            block(2, 2, 1),
+            block(3, 3, 2),
+            block(3, 13, 12),
+            block(4, 14, 13),
+            block(6, 16, 15),
+        ];
+        for head in &heads {
+            client.notify(1u64, head);
+        }
+        client.mock.push(block(5, 15, 14)).unwrap();
+        client.mock.push(block(2, 12, 1)).unwrap();
+        client.mock.push(U256::one()).unwrap();
+        let provider = Provider::new(client.clone());
+
+        let reorgs = provider.subscribe_reorgs().await.unwrap();
+        assert_eq!(reorgs.id(), U256::one());
+        let reorgs = reorgs.map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(
+            reorgs,
+            vec![Reorg {
+                common_ancestor: block(1, 1, 0),
+                dropped_blocks: vec![block(2, 2, 1), block(3, 3, 2)],
+                new_blocks: vec![block(2, 12, 1), block(3, 13, 12)],
+            }]
+        );
+        assert_eq!(reorgs[0].depth(), 2);
+        assert_eq!(reorgs[0].new_head(), &block(3, 13, 12));
+
+        client.mock.assert_request("eth_subscribe", ["newHeads"]).unwrap();
+        let hash = |byte| (H256::repeat_byte(byte), false);
+        client.mock.assert_request("eth_getBlockByHash", hash(12)).unwrap();
+        client.mock.assert_request("eth_getBlockByHash", hash(15)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn detects_rewinds_to_known_blocks() {
+        let client = MockPubsub::default();
+        for head in [block(1, 1, 0), block(2, 2, 1), block(3, 3, 2), block(2, 2, 1)] {
+            client.notify(1u64, &head);
+        }
+        client.mock.push(U256::one()).unwrap();
+        let provider = Provider::new(client.clone());
+
+        let reorgs = provider.subscribe_reorgs().await.unwrap();
+        let reorgs = reorgs.map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(
+            reorgs,
+            vec![Reorg {
+                common_ancestor: block(2, 2, 1),
+                dropped_blocks: vec![block(3, 3, 2)],
+                new_blocks: vec![],
+            }]
+        );
+        assert_eq!(reorgs[0].new_head(), &block(2, 2, 1));
+    }
+
+    #[tokio::test]
+    async fn reports_reorgs_deeper_than_tracked() {
+        let client = MockPubsub::default();
+        for head in [block(1, 1, 0), block(2, 2, 1), block(3, 3, 2), block(3, 13, 12)] {
+            client.notify(1u64, &head);
+        }
+        client.mock.push(block(2, 12, 11)).unwrap();
+        client.mock.push(U256::one()).unwrap();
+        let provider = Provider::new(client.clone());
+
+        let reorgs = provider.subscribe_reorgs().await.unwrap().depth(2);
+        let reorgs = reorgs.collect::<Vec<_>>().await;
+        assert_eq!(reorgs.len(), 1);
+        assert!(matches!(&reorgs[0], Err(ProviderError::CustomError(_))), "{reorgs:?}");
+    }
+
+    fn tx(nonce: u64) -> Transaction {
+        Transaction {
+            hash: H256::from_low_u64_be(nonce),
+            nonce: nonce.into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribes_to_full_pending_txs() {
+        let client = MockPubsub::default();
+        for tx in [tx(1), tx(2)] {
+            client.notify(1u64, &tx);
+        }
+        client.mock.push(U256::one()).unwrap();
+        let provider = Provider::new(client.clone());
+
+        let txs = provider.subscribe_full_pending_txs().await.unwrap();
+        assert_eq!(txs.id(), U256::one());
+        assert!(!txs.is_hydrated());
+        let txs = txs.map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(txs, vec![tx(1), tx(2)]);
+
+        client.mock.assert_request("eth_subscribe", ("newPendingTransactions", true)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn hydrates_pending_tx_hashes() {
+        let client = MockPubsub::default();
+        client.notify(2u64, tx(1).hash);
+        client.mock.push(tx(1)).unwrap();
+        client.mock.push(U256::from(2)).unwrap();
+        client.mock.push_error(JsonRpcError {
+            code: -32602,
+            message: "too many arguments, want at most 1".to_string(),
+            data: None,
+        });
+        let provider = Provider::new(client.clone());
+
+        let txs = provider.subscribe_full_pending_txs().await.unwrap();
+        assert_eq!(txs.id(), U256::from(2));
+        assert!(txs.is_hydrated());
+        let txs = txs.map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(txs, vec![tx(1)]);
+
+        client.mock.assert_request("eth_subscribe", ("newPendingTransactions", true)).unwrap();
+        client.mock.assert_request("eth_subscribe", ["newPendingTransactions"]).unwrap();
+        client.mock.assert_request("eth_getTransactionByHash", [tx(1).hash]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn wrappers_forward_subscriptions() {
+        use crate::{
+            FallbackProvider, JsonRpcClientWrapper, QuorumProvider, RateLimitedClient, RetryClient,
+            RetryPolicy, RoutingClient, RwClient, SingleflightClient, TracingClient,
+            WeightedProvider,
+        };
+
+        fn supported(client: impl JsonRpcClient) -> bool {
+            client.as_pubsub().is_some()
+        }
+
+        #[derive(Debug)]
+        struct NeverRetry;
+
+        impl RetryPolicy<MockError> for NeverRetry {
+            fn should_retry(&self, _: &MockError) -> bool {
+                false
+            }
+
+            fn backoff_hint(&self, _: &MockError) -> Option<std::time::Duration> {
+                None
+            }
+        }
+
+        let client = MockPubsub::default();
+        let boxed = || Box::new(client.clone()) as Box<dyn JsonRpcClientWrapper>;
+        let http = || Box::new(MockProvider::new()) as Box<dyn JsonRpcClientWrapper>;
+
+        let retry = RetryClient::new(client.clone(), Box::new(NeverRetry), 0, 0);
+        assert!(supported(retry));
+        assert!(supported(RwClient::new(client.clone(), MockProvider::new())));
+        assert!(!supported(RwClient::new(MockProvider::new(), client.clone())));
+        assert!(supported(TracingClient::new(client.clone())));
+        assert!(supported(SingleflightClient::new(client.clone())));
+        assert!(supported(RateLimitedClient::new(client.clone(), 10)));
+        #[cfg(all(feature = "light-client", not(feature = "celo")))]
+        assert!(supported(crate::LightClient::new(client.clone(), H256::zero())));
+
+        let quorum = QuorumProvider::dyn_rpc().add_provider(WeightedProvider::new(boxed()));
+        assert!(supported(quorum.build()));
+        let quorum = QuorumProvider::dyn_rpc()
+            .add_provider(WeightedProvider::new(boxed()))
+            .add_provider(WeightedProvider::new(http()));
+        assert!(!supported(quorum.build()));
+
+        let routing = RoutingClient::dyn_rpc(http()).route(["eth_subscribe"], boxed()).build();
+        assert!(supported(routing));
+        assert!(supported(RoutingClient::dyn_rpc(http()).route(["eth_"], boxed()).build()));
+        assert!(!supported(RoutingClient::dyn_rpc(http()).build()));
+
+        assert!(supported(FallbackProvider::dyn_rpc().add_provider(boxed()).build()));
+        let fallback = FallbackProvider::dyn_rpc().add_provider(boxed()).add_provider(http());
+        assert!(!supported(fallback.build()));
+    }
+
+    #[tokio::test]
+    async fn subscribes_on_the_fallback_client_of_the_subscription() {
+        use crate::FallbackProvider;
+
+        let (primary, backup) = (MockPubsub::default(), MockPubsub::default());
+        let head = block(1, 1, 0);
+        backup.notify(1u64, &head);
+        backup.mock.push(U256::one()).unwrap();
+        // failed clients are tried first again right away
+        let client = FallbackProvider::builder()
+            .add_providers([primary, backup.clone()])
+            .health_check_interval(std::time::Duration::ZERO)
+            .build();
+        let provider = Provider::new(client);
+
+        // the primary has no response and fails
+        let id: U256 = provider.request("eth_subscribe", ["newHeads"]).await.unwrap();
+        assert_eq!(provider.as_ref().active(), 0);
+        let heads = DynSubscriptionStream::<_, Block<TxHash>>::new(id, &provider).unwrap();
+        assert_eq!(heads.collect::<Vec<_>>().await, vec![head]);
+        backup.mock.assert_request("eth_subscribe", ["newHeads"]).unwrap();
+    }
+}
@@ -0,0 +1,138 @@
+//! A bounded LRU cache for responses that can never change, see
+//! [`Provider::response_cache`](crate::Provider::response_cache)
+
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+/// Block tags that don't refer to a fixed block
+const BLOCK_TAGS: [&str; 5] = ["latest", "pending", "earliest", "safe", "finalized"];
+
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    capacity: usize,
+    inner: Mutex<Lru>,
+}
+
+#[derive(Debug, Default)]
+struct Lru {
+    /// Incremented on every access
+    tick: u64,
+    /// cache key -> (response, last access)
+    entries: HashMap<String, (Value, u64)>,
+    /// last access -> cache key
+    order: BTreeMap<u64, String>,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { capacity, inner: Default::default() }
+    }
+
+    /// Returns the key under which the response to this request is cached, if the response can
+    /// never change.
+    pub(crate) fn key(method: &str, params: &Value) -> Option<String> {
+        match method {
+            "net_version" | "eth_getBlockByHash" | "eth_getTransactionByHash" => {}
+            // only code at a fixed block can't change
+            "eth_getCode" => {
+                let block = params.as_array().and_then(|params| params.get(1))?;
+                if block.as_str().map_or(false, |tag| BLOCK_TAGS.contains(&tag)) {
+                    return None
+                }
+            }
+            _ => return None,
+        }
+        Some(format!("{method}:{params}"))
+    }
+
+    /// Returns whether the response can be cached.
+    ///
+    /// Blocks and transactions which are not known yet may show up later and transactions may
+    /// still be pending, so only final responses are cached.
+    pub(crate) fn is_final(method: &str, response: &Value) -> bool {
+        match method {
+            "eth_getBlockByHash" => !response.is_null(),
+            "eth_getTransactionByHash" => !response.is_null() && !response["blockHash"].is_null(),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn get(&self, key: &str) -> Option<Value> {
+        let mut guard = self.inner.lock().unwrap();
+        let lru = &mut *guard;
+        lru.tick += 1;
+        let tick = lru.tick;
+        let (value, last_access) = lru.entries.get_mut(key)?;
+        let value = value.clone();
+        let previous = std::mem::replace(last_access, tick);
+        lru.order.remove(&previous);
+        lru.order.insert(tick, key.to_string());
+        Some(value)
+    }
+
+    pub(crate) fn insert(&self, key: String, value: Value) {
+        if self.capacity == 0 {
+            return
+        }
+        let mut guard = self.inner.lock().unwrap();
+        let lru = &mut *guard;
+        lru.tick += 1;
+        let tick = lru.tick;
+        if let Some((_, previous)) = lru.entries.insert(key.clone(), (value, tick)) {
+            lru.order.remove(&previous);
+        }
+        lru.order.insert(tick, key);
+        while lru.entries.len() > self.capacity {
+            // evict the least recently used entry
+            let oldest = match lru.order.keys().next() {
+                Some(oldest) => *oldest,
+                None => break,
+            };
+            if let Some(key) = lru.order.remove(&oldest) {
+                lru.entries.remove(&key);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn only_caches_immutable_requests() {
+        assert!(ResponseCache::key("net_version", &Value::Null).is_some());
+        assert!(ResponseCache::key("eth_blockNumber", &Value::Null).is_none());
+        assert!(ResponseCache::key("eth_getCode", &json!(["0x00", "0x10"])).is_some());
+        assert!(ResponseCache::key("eth_getCode", &json!(["0x00", "latest"])).is_none());
+
+        assert!(!ResponseCache::is_final("eth_getBlockByHash", &Value::Null));
+        let pending = json!({"hash": "0x01", "blockHash": null});
+        assert!(!ResponseCache::is_final("eth_getTransactionByHash", &pending));
+        let mined = json!({"hash": "0x01", "blockHash": "0x02"});
+        assert!(ResponseCache::is_final("eth_getTransactionByHash", &mined));
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = ResponseCache::new(2);
+        cache.insert("a".to_string(), json!(1));
+        cache.insert("b".to_string(), json!(2));
+        assert_eq!(cache.get("a"), Some(json!(1)));
+
+        cache.insert("c".to_string(), json!(3));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(json!(1)));
+        assert_eq!(cache.get("c"), Some(json!(3)));
+    }
+}
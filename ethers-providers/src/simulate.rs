@@ -0,0 +1,247 @@
+//! Types for the [`eth_simulateV1`](https://github.com/ethereum/execution-apis/pull/484) rpc
+//! method, see [`Middleware::simulate_v1`](crate::Middleware::simulate_v1)
+
+use crate::call_raw::spoof;
+use ethers_core::types::{transaction::eip2718::TypedTransaction, Block, Bytes, Log, TxHash, U64};
+use serde::{Deserialize, Serialize};
+
+pub use ethers_core::types::BlockOverrides;
+
+/// The input of `eth_simulateV1`: a sequence of blocks that are simulated on top of each other
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatePayload {
+    /// The blocks to simulate, in order
+    pub block_state_calls: Vec<SimBlock>,
+    /// Whether ETH transfers are reported as logs of the `0xeeee…eeee` address
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trace_transfers: bool,
+    /// Whether the calls are validated like real transactions, i.e. nonces, balances and fees are
+    /// checked
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub validation: bool,
+}
+
+impl SimulatePayload {
+    /// Creates a new payload that simulates the given blocks
+    pub fn new(blocks: impl IntoIterator<Item = SimBlock>) -> Self {
+        Self { block_state_calls: blocks.into_iter().collect(), ..Default::default() }
+    }
+
+    /// Appends a block to the simulation
+    #[must_use]
+    pub fn block(mut self, block: SimBlock) -> Self {
+        self.block_state_calls.push(block);
+        self
+    }
+
+    /// Sets whether ETH transfers are reported as logs
+    #[must_use]
+    pub fn trace_transfers(mut self, trace_transfers: bool) -> Self {
+        self.trace_transfers = trace_transfers;
+        self
+    }
+
+    /// Sets whether the calls are validated like real transactions
+    #[must_use]
+    pub fn validation(mut self, validation: bool) -> Self {
+        self.validation = validation;
+        self
+    }
+}
+
+/// A simulated block: the calls it contains and the overrides applied before executing them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimBlock {
+    /// Overrides of the block header fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_overrides: Option<BlockOverrides>,
+    /// Account overrides applied before the first call of the block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_overrides: Option<spoof::State>,
+    /// The calls to execute, in order
+    #[serde(default)]
+    pub calls: Vec<TypedTransaction>,
+}
+
+impl SimBlock {
+    /// Creates a new block that executes the given calls
+    pub fn new<T: Into<TypedTransaction>>(calls: impl IntoIterator<Item = T>) -> Self {
+        Self { calls: calls.into_iter().map(Into::into).collect(), ..Default::default() }
+    }
+
+    /// Appends a call to the block
+    #[must_use]
+    pub fn call<T: Into<TypedTransaction>>(mut self, call: T) -> Self {
+        self.calls.push(call.into());
+        self
+    }
+
+    /// Sets the block header overrides
+    #[must_use]
+    pub fn block_overrides(mut self, overrides: BlockOverrides) -> Self {
+        self.block_overrides = Some(overrides);
+        self
+    }
+
+    /// Sets the account overrides applied before the first call of the block
+    #[must_use]
+    pub fn state_overrides(mut self, state: spoof::State) -> Self {
+        self.state_overrides = Some(state);
+        self
+    }
+}
+
+/// A block returned by `eth_simulateV1`, along with the results of its calls
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimulatedBlock {
+    /// The header and transaction hashes of the block
+    #[serde(flatten)]
+    pub block: Block<TxHash>,
+    /// The results of the calls, in the order of [`SimBlock::calls`]
+    pub calls: Vec<SimCallResult>,
+}
+
+/// The outcome of a single simulated call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimCallResult {
+    /// The output of the call, or the revert data if it failed
+    pub return_data: Bytes,
+    /// The logs emitted by the call, empty if it failed
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    pub gas_used: U64,
+    /// `1` if the call succeeded, `0` otherwise
+    pub status: U64,
+    /// Why the call failed, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<SimCallError>,
+}
+
+impl SimCallResult {
+    /// Returns true if the call succeeded
+    pub fn is_success(&self) -> bool {
+        self.status == U64::one()
+    }
+}
+
+/// The error of a failed simulated call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SimCallError {
+    pub code: i64,
+    pub message: String,
+    /// The revert data, if the call reverted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use ethers_core::types::Address;
+    use serde_json::json;
+    #[cfg(not(feature = "celo"))]
+    use {
+        crate::{Middleware, Provider},
+        ethers_core::{
+            types::{BlockNumber, TransactionRequest, H256, U256},
+            utils,
+        },
+    };
+
+    #[tokio::test]
+    #[cfg(not(feature = "celo"))]
+    async fn simulates_blocks() {
+        let (provider, mock) = Provider::mocked();
+        let from = Address::repeat_byte(1);
+        let to = Address::repeat_byte(2);
+        let mut state = spoof::state();
+        state.account(from).balance(U256::exp10(18));
+        let payload =
+            SimulatePayload::new([SimBlock::new([TransactionRequest::pay(to, 100).from(from)])
+                .state_overrides(state)
+                .block_overrides(BlockOverrides::default().number(20).base_fee_per_gas(0))])
+            .block(SimBlock::new([TransactionRequest::new().from(from).to(to).data(vec![1])]))
+            .validation(true);
+
+        let topic = H256::repeat_byte(3);
+        let response = json!([
+            {
+                "number": "0x14",
+                "hash": H256::repeat_byte(4),
+                "parentHash": H256::repeat_byte(5),
+                "timestamp": "0x1",
+                "gasLimit": "0x1c9c380",
+                "gasUsed": "0x5208",
+                "baseFeePerGas": "0x0",
+                "transactions": [H256::repeat_byte(6)],
+                "calls": [{
+                    "returnData": "0x",
+                    "logs": [{
+                        "address": to,
+                        "topics": [topic],
+                        "data": "0x",
+                        "blockNumber": "0x14",
+                        "transactionIndex": "0x0",
+                        "logIndex": "0x0"
+                    }],
+                    "gasUsed": "0x5208",
+                    "status": "0x1"
+                }]
+            },
+            {
+                "number": "0x15",
+                "hash": H256::repeat_byte(7),
+                "parentHash": H256::repeat_byte(4),
+                "timestamp": "0xd",
+                "gasLimit": "0x1c9c380",
+                "gasUsed": "0x5300",
+                "transactions": [H256::repeat_byte(8)],
+                "calls": [{
+                    "returnData": "0x08c379a0",
+                    "logs": [],
+                    "gasUsed": "0x5300",
+                    "status": "0x0",
+                    "error": { "code": 3, "message": "execution reverted", "data": "0x08c379a0" }
+                }]
+            }
+        ]);
+        mock.push::<serde_json::Value, _>(response).unwrap();
+
+        let blocks = provider.simulate_v1(&payload, None).await.unwrap();
+        mock.assert_request(
+            "eth_simulateV1",
+            [utils::serialize(&payload), utils::serialize(&BlockNumber::Latest)],
+        )
+        .unwrap();
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].block.number, Some(20.into()));
+        assert_eq!(blocks[0].block.transactions, vec![H256::repeat_byte(6)]);
+        assert!(blocks[0].calls[0].is_success());
+        assert_eq!(blocks[0].calls[0].logs[0].topics, vec![topic]);
+        let failed = &blocks[1].calls[0];
+        assert!(!failed.is_success());
+        assert_eq!(failed.error.as_ref().unwrap().data, Some(Bytes::from(vec![8, 195, 121, 160])));
+    }
+
+    #[test]
+    fn serializes_payload() {
+        let payload = SimulatePayload::new([SimBlock::default()
+            .block_overrides(BlockOverrides::default().time(12).fee_recipient(Address::zero()))])
+        .trace_transfers(true);
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            json!({
+                "blockStateCalls": [{
+                    "blockOverrides": { "time": "0xc", "feeRecipient": Address::zero() },
+                    "calls": []
+                }],
+                "traceTransfers": true
+            })
+        );
+    }
+}
@@ -1,13 +1,17 @@
 #![allow(clippy::return_self_not_must_use)]
 
-use crate::{JsonRpcClient, Middleware, PinBoxFut, Provider, ProviderError};
-use ethers_core::types::{Transaction, TxHash, U256};
+use crate::{FilterKind, JsonRpcClient, Middleware, PinBoxFut, Provider, ProviderError};
+use ethers_core::types::{Block, Filter, Log, Transaction, TxHash, H256, U256, U64};
 use futures_core::{stream::Stream, Future};
-use futures_util::{stream, stream::FuturesUnordered, FutureExt, StreamExt};
+use futures_util::{
+    stream,
+    stream::{FuturesOrdered, FuturesUnordered},
+    FutureExt, StreamExt,
+};
 use pin_project::pin_project;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
-    collections::VecDeque,
+    collections::{HashSet, VecDeque},
     fmt::Debug,
     pin::Pin,
     task::{Context, Poll},
@@ -139,6 +143,20 @@ where
     }
 }
 
+impl<'a, P> FilterWatcher<'a, P, H256>
+where
+    P: JsonRpcClient,
+{
+    /// Returns a stream that yields the full blocks, with their transactions, for the block hashes
+    /// of [`Middleware::watch_blocks`].
+    ///
+    /// This internally calls `Provider::get_block_with_txs` with every new block. No more than n
+    /// futures will be buffered at any point in time, the blocks are yielded in order.
+    pub fn full_blocks(self, n: usize) -> BlockStream<'a, P, Self> {
+        BlockStream::new(self.provider, self, n)
+    }
+}
+
 /// Errors `TransactionStream` can throw
 #[derive(Debug, thiserror::Error)]
 pub enum GetTransactionError {
@@ -254,6 +272,263 @@ where
     }
 }
 
+/// Errors `BlockStream` can throw
+#[derive(Debug, thiserror::Error)]
+pub enum GetBlockError {
+    #[error("Failed to get block `{0:?}`: {1}")]
+    ProviderError(H256, ProviderError),
+    /// `get_block_with_txs` resulted in a `None`
+    #[error("Block `{0:?}` not found")]
+    NotFound(H256),
+}
+
+impl From<GetBlockError> for ProviderError {
+    fn from(err: GetBlockError) -> Self {
+        match err {
+            GetBlockError::ProviderError(_, err) => err,
+            err @ GetBlockError::NotFound(_) => ProviderError::CustomError(err.to_string()),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type BlockFut<'a> = Pin<Box<dyn Future<Output = BlockResult> + Send + 'a>>;
+#[cfg(target_arch = "wasm32")]
+type BlockFut<'a> = Pin<Box<dyn Future<Output = BlockResult> + 'a>>;
+
+type BlockResult = Result<Block<Transaction>, GetBlockError>;
+
+/// Drains a stream of block hashes and yields the entire `Block`s with their transactions, in the
+/// order of the hashes.
+#[must_use = "streams do nothing unless polled"]
+pub struct BlockStream<'a, P, St> {
+    /// Currently running futures pending completion.
+    pending: FuturesOrdered<BlockFut<'a>>,
+    /// Block hashes that get requested as soon as another future finishes.
+    buffered: VecDeque<H256>,
+    /// The provider that gets the blocks
+    provider: &'a Provider<P>,
+    /// A stream of block hashes.
+    stream: St,
+    /// max allowed futures to execute at once.
+    max_concurrent: usize,
+}
+
+impl<'a, P: JsonRpcClient, St> BlockStream<'a, P, St> {
+    /// Create a new `BlockStream` instance
+    pub fn new(provider: &'a Provider<P>, stream: St, max_concurrent: usize) -> Self {
+        Self {
+            pending: FuturesOrdered::new(),
+            buffered: Default::default(),
+            provider,
+            stream,
+            max_concurrent,
+        }
+    }
+
+    /// Push a future into the queue
+    fn push_block(&mut self, hash: H256) {
+        let fut = self.provider.get_block_with_txs(hash).then(move |res| match res {
+            Ok(Some(block)) => futures_util::future::ok(block),
+            Ok(None) => futures_util::future::err(GetBlockError::NotFound(hash)),
+            Err(err) => futures_util::future::err(GetBlockError::ProviderError(hash, err)),
+        });
+        self.pending.push_back(Box::pin(fut));
+    }
+}
+
+impl<'a, P, St> Stream for BlockStream<'a, P, St>
+where
+    P: JsonRpcClient,
+    St: Stream<Item = H256> + Unpin + 'a,
+{
+    type Item = BlockResult;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        // drain buffered blocks first
+        while this.pending.len() < this.max_concurrent {
+            if let Some(hash) = this.buffered.pop_front() {
+                this.push_block(hash);
+            } else {
+                break
+            }
+        }
+
+        let mut stream_done = false;
+        loop {
+            match Stream::poll_next(Pin::new(&mut this.stream), cx) {
+                Poll::Ready(Some(hash)) => {
+                    if this.pending.len() < this.max_concurrent && this.buffered.is_empty() {
+                        this.push_block(hash);
+                    } else {
+                        this.buffered.push_back(hash);
+                    }
+                }
+                Poll::Ready(None) => {
+                    stream_done = true;
+                    break
+                }
+                _ => break,
+            }
+        }
+
+        // poll running futures
+        if let block @ Poll::Ready(Some(_)) = this.pending.poll_next_unpin(cx) {
+            return block
+        }
+
+        if stream_done && this.pending.is_empty() && this.buffered.is_empty() {
+            // all done
+            return Poll::Ready(None)
+        }
+
+        Poll::Pending
+    }
+}
+
+enum LogFilterState<'a> {
+    WaitForInterval,
+    GetFilterChanges(PinBoxFut<'a, Vec<Log>>),
+    /// Installs the filter again and queries the logs that were missed in the meantime
+    Reinstall(PinBoxFut<'a, (U256, Vec<Log>)>),
+    NextItem(IntoIter<Log>),
+}
+
+/// Streams logs from an installed filter like [`FilterWatcher`], but installs the filter again
+/// when the node expired it, see [`Provider::watch_logs_managed`].
+///
+/// Nodes drop filters that weren't polled for a while, e.g. after a network outage, and then
+/// reply `filter not found`. The filter is then reinstalled from the block of the last yielded log
+/// and the logs since then are queried, logs that were already yielded are skipped.
+///
+/// Other errors are yielded and polling continues.
+#[must_use = "filters do nothing unless you stream them"]
+pub struct LogFilterManager<'a, P> {
+    /// The id of the currently installed filter
+    pub id: U256,
+    provider: &'a Provider<P>,
+    filter: Filter,
+    interval: Box<dyn Stream<Item = ()> + Send + Unpin>,
+    state: LogFilterState<'a>,
+    seen: SeenLogs,
+}
+
+/// The latest block logs were yielded for and the logs of it that were yielded
+struct SeenLogs {
+    /// The block to resume from when the filter is reinstalled
+    block: U64,
+    logs: HashSet<(Option<H256>, Option<U256>)>,
+}
+
+impl SeenLogs {
+    /// Returns true if the log wasn't yielded yet and records it
+    fn insert(&mut self, log: &Log) -> bool {
+        if log.removed.unwrap_or_default() {
+            return true
+        }
+        let block = match log.block_number {
+            Some(block) => block,
+            None => return true,
+        };
+        if block > self.block {
+            self.block = block;
+            self.logs.clear();
+        } else if block < self.block {
+            // reinstalling the filter only repeats logs of `block` and later
+            return true
+        }
+        self.logs.insert((log.block_hash, log.log_index))
+    }
+}
+
+impl<'a, P: JsonRpcClient> LogFilterManager<'a, P> {
+    pub(crate) fn new(id: U256, provider: &'a Provider<P>, filter: Filter, from: U64) -> Self {
+        Self {
+            id,
+            provider,
+            filter,
+            interval: Box::new(interval(provider.get_interval())),
+            state: LogFilterState::WaitForInterval,
+            seen: SeenLogs { block: from, logs: HashSet::new() },
+        }
+    }
+
+    /// Sets the stream's polling interval
+    pub fn interval(mut self, duration: Duration) -> Self {
+        self.interval = Box::new(interval(duration));
+        self
+    }
+
+    fn reinstall(&self) -> PinBoxFut<'a, (U256, Vec<Log>)> {
+        let provider = self.provider;
+        let filter = self.filter.clone().from_block(self.seen.block);
+        Box::pin(async move {
+            let id = provider.new_filter(FilterKind::Logs(&filter)).await?;
+            let logs = provider.get_logs(&filter).await?;
+            Ok((id, logs))
+        })
+    }
+}
+
+impl<'a, P: JsonRpcClient> Stream for LogFilterManager<'a, P> {
+    type Item = Result<Log, ProviderError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            this.state = match &mut this.state {
+                LogFilterState::WaitForInterval => {
+                    let _ready = futures_util::ready!(this.interval.poll_next_unpin(cx));
+                    LogFilterState::GetFilterChanges(Box::pin(
+                        this.provider.get_filter_changes(this.id),
+                    ))
+                }
+                LogFilterState::GetFilterChanges(fut) => {
+                    match futures_util::ready!(fut.as_mut().poll(cx)) {
+                        Ok(logs) => LogFilterState::NextItem(logs.into_iter()),
+                        Err(err) if is_filter_not_found(&err) => {
+                            LogFilterState::Reinstall(this.reinstall())
+                        }
+                        Err(err) => {
+                            this.state = LogFilterState::WaitForInterval;
+                            return Poll::Ready(Some(Err(err)))
+                        }
+                    }
+                }
+                LogFilterState::Reinstall(fut) => match futures_util::ready!(fut.as_mut().poll(cx))
+                {
+                    Ok((id, logs)) => {
+                        this.id = id;
+                        LogFilterState::NextItem(logs.into_iter())
+                    }
+                    Err(err) => {
+                        // the old id is still unknown to the node, so the next poll retries
+                        this.state = LogFilterState::WaitForInterval;
+                        return Poll::Ready(Some(Err(err)))
+                    }
+                },
+                LogFilterState::NextItem(iter) => {
+                    if let Some(log) = iter.find(|log| this.seen.insert(log)) {
+                        return Poll::Ready(Some(Ok(log)))
+                    }
+                    LogFilterState::WaitForInterval
+                }
+            };
+        }
+    }
+}
+
+/// Returns true if the node replied that the polled filter isn't installed (anymore)
+fn is_filter_not_found(err: &ProviderError) -> bool {
+    err.as_error_response().map_or(false, |err| {
+        let message = err.message.to_lowercase();
+        message.contains("filter") &&
+            (message.contains("not found") || message.contains("does not exist"))
+    })
+}
+
 #[cfg(test)]
 #[cfg(not(target_arch = "wasm32"))]
 mod tests {
@@ -356,4 +631,62 @@ mod tests {
             txs.into_iter().map(|tx| tx.unwrap().transaction_hash).collect()
         );
     }
+
+    #[tokio::test]
+    async fn streams_full_blocks_in_order() {
+        let (provider, mock) = Provider::mocked();
+        let hashes = [H256::repeat_byte(1), H256::repeat_byte(2)];
+        let blocks =
+            hashes.map(|hash| Block::<Transaction> { hash: Some(hash), ..Default::default() });
+
+        mock.push(blocks[1].clone()).unwrap();
+        mock.push(blocks[0].clone()).unwrap();
+        mock.push::<Vec<H256>, _>(hashes.to_vec()).unwrap();
+
+        let watcher = FilterWatcher::new(1, &provider).interval(Duration::from_millis(1));
+        let received = watcher.full_blocks(2).take(2).map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(received, blocks.to_vec());
+
+        mock.assert_request("eth_getFilterChanges", [U256::one()]).unwrap();
+        for hash in hashes {
+            mock.assert_request("eth_getBlockByHash", (hash, true)).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn reinstalls_expired_log_filters() {
+        use crate::JsonRpcError;
+
+        let log = |block: u64, index: u64| Log {
+            block_number: Some(block.into()),
+            block_hash: Some(H256::from_low_u64_be(block)),
+            log_index: Some(index.into()),
+            ..Default::default()
+        };
+        let (provider, mock) = Provider::mocked();
+        let provider = provider.interval(Duration::from_millis(1));
+        mock.push::<Vec<Log>, _>(vec![log(7, 0), log(8, 0)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(6, 0), log(6, 1), log(7, 0)]).unwrap();
+        mock.push(U256::from(2)).unwrap();
+        // the second poll fails
+        let message = "filter not found".to_string();
+        mock.push_error(JsonRpcError { code: -32000, message, data: None });
+        mock.push::<Vec<Log>, _>(vec![log(6, 0)]).unwrap();
+        mock.push(U256::one()).unwrap();
+        mock.push(U64::from(5)).unwrap();
+
+        let filter = Filter::new().address(ethers_core::types::Address::zero());
+        let watcher = provider.watch_logs_managed(&filter).await.unwrap();
+        let logs = watcher.take(4).map(Result::unwrap).collect::<Vec<_>>().await;
+        assert_eq!(logs, vec![log(6, 0), log(6, 1), log(7, 0), log(8, 0)]);
+
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+        mock.assert_request("eth_newFilter", [&filter]).unwrap();
+        mock.assert_request("eth_getFilterChanges", [U256::one()]).unwrap();
+        mock.assert_request("eth_getFilterChanges", [U256::one()]).unwrap();
+        let resumed = filter.from_block(6);
+        mock.assert_request("eth_newFilter", [&resumed]).unwrap();
+        mock.assert_request("eth_getLogs", [&resumed]).unwrap();
+        mock.assert_request("eth_getFilterChanges", [U256::from(2)]).unwrap();
+    }
 }
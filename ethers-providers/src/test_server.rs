@@ -0,0 +1,96 @@
+//! A minimal HTTP server for testing the HTTP based clients, serving a single request
+
+use serde_json::Value;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+    task::JoinHandle,
+};
+
+/// A request received by the test server
+#[derive(Debug)]
+pub(crate) struct Request {
+    /// The request line and headers
+    pub head: String,
+    pub body: String,
+}
+
+impl Request {
+    /// Returns the request line, e.g. `GET / HTTP/1.1`
+    pub fn request_line(&self) -> &str {
+        self.head.lines().next().unwrap()
+    }
+
+    /// Returns the body parsed as JSON
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
+
+/// Serves a single request on a local port with the given status, additional headers, each
+/// terminated by `\r\n`, and the body returned by `respond`.
+///
+/// Returns the base url of the server, e.g. `http://127.0.0.1:1234`, and a handle resolving to
+/// the request.
+pub(crate) async fn serve_once(
+    status: &'static str,
+    headers: &'static str,
+    respond: impl FnOnce(&Request) -> String + Send + 'static,
+) -> (String, JoinHandle<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        respond_once(stream, status, headers, respond).await
+    });
+    (url, handle)
+}
+
+/// Like [`serve_once`], but serves the request on the unix socket at `path`
+#[cfg(all(feature = "uds", unix))]
+pub(crate) fn serve_once_uds(
+    path: &std::path::Path,
+    status: &'static str,
+    respond: impl FnOnce(&Request) -> String + Send + 'static,
+) -> JoinHandle<Request> {
+    let listener = tokio::net::UnixListener::bind(path).unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        respond_once(stream, status, "", respond).await
+    })
+}
+
+async fn respond_once<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    status: &'static str,
+    headers: &'static str,
+    respond: impl FnOnce(&Request) -> String,
+) -> Request {
+    let mut buf = Vec::new();
+    let request = loop {
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await.unwrap();
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf).to_string();
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let len = head
+                .lines()
+                .find_map(|line| {
+                    line.to_lowercase()
+                        .strip_prefix("content-length: ")
+                        .map(|len| len.parse::<usize>().unwrap())
+                })
+                .unwrap_or_default();
+            if body.len() >= len {
+                break Request { head: head.to_string(), body: body.to_string() }
+            }
+        }
+    };
+    let body = respond(&request);
+    let response = format!(
+        "HTTP/1.1 {status}\r\n{headers}content-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+    request
+}
@@ -0,0 +1,495 @@
+//! A [JsonRpcClient] implementation that sends requests to a primary client and fails over to
+//! backup clients
+
+use super::quorum::{pubsub, JsonRpcClientWrapper, NotificationStream, QuorumParams};
+use crate::{provider::ProviderError, JsonRpcClient, PubsubClient};
+use async_trait::async_trait;
+use ethers_core::types::{U256, U64};
+use futures_util::future::{self, join_all, Either};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures_timer::Delay;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::{Delay, Instant};
+
+/// How long a failed client is skipped before requests are sent to it again, by default
+pub const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A client that sends every request to the first healthy client in order of priority.
+///
+/// A client becomes unhealthy if it can't be reached, fails with a transport error or doesn't
+/// respond within the [`request_timeout`](FallbackProviderBuilder::request_timeout); the request
+/// is then sent to the next client. Errors returned by a node, like a reverted call, are returned
+/// as they are since the next node would return the same error.
+///
+/// Unhealthy clients are skipped for the
+/// [`health_check_interval`](FallbackProviderBuilder::health_check_interval), afterwards the next
+/// request is sent to them again and they become healthy once they respond, so requests fail back
+/// to the primary client once it recovers. To check the clients without delaying requests, the
+/// provider can [check them in the background](FallbackProviderBuilder::background_health_checks)
+/// or [`health_check`](FallbackProvider::health_check) can be called when needed.
+/// If all clients are unhealthy, the request is still sent to every client in order.
+///
+/// If all clients support subscriptions, the `FallbackProvider` is a [`PubsubClient`] as well.
+/// A subscription stays on the client that created it, so its stream ends if that client fails,
+/// new subscriptions are created on the next healthy client.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{FallbackProvider, Http, Middleware, Provider};
+/// use std::{str::FromStr, time::Duration};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = FallbackProvider::builder()
+///     .add_provider(Http::from_str("http://primary:8545")?)
+///     .add_provider(Http::from_str("http://backup:8545")?)
+///     .request_timeout(Duration::from_secs(5))
+///     .build();
+/// let provider = Provider::new(client);
+/// let block_number = provider.get_block_number().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FallbackProvider<T = Box<dyn JsonRpcClientWrapper>> {
+    /// The clients, in order of priority, shared with the background health checks
+    providers: Arc<Vec<T>>,
+    /// When each client last failed, `None` if it is healthy
+    failures: Arc<Failures>,
+    /// How long requests may take before the next client is tried, unlimited if `None`
+    timeout: Option<Duration>,
+    /// How long failed clients are skipped
+    health_check_interval: Duration,
+    /// The client that created each subscription
+    subscriptions: Mutex<HashMap<U256, usize>>,
+}
+
+impl FallbackProvider<Box<dyn JsonRpcClientWrapper>> {
+    /// Create a `FallbackProvider` for different `JsonRpcClient` types
+    pub fn dyn_rpc() -> FallbackProviderBuilder<Box<dyn JsonRpcClientWrapper>> {
+        Self::builder()
+    }
+}
+
+impl<T> FallbackProvider<T> {
+    /// Returns a `FallbackProviderBuilder` without any clients
+    pub fn builder() -> FallbackProviderBuilder<T> {
+        FallbackProviderBuilder::default()
+    }
+
+    /// Returns the clients, in order of priority
+    pub fn providers(&self) -> &[T] {
+        &self.providers
+    }
+
+    /// Returns whether the client at `idx` is healthy
+    pub fn is_healthy(&self, idx: usize) -> bool {
+        self.failures.lock().unwrap().get(idx).map_or(false, Option::is_none)
+    }
+
+    /// Returns the index of the client the next request is sent to first
+    pub fn active(&self) -> usize {
+        self.order().first().copied().unwrap_or_default()
+    }
+
+    /// Returns the indices of the clients in the order they are tried: the healthy clients and
+    /// the ones that are due to be checked again, then the other unhealthy clients
+    fn order(&self) -> Vec<usize> {
+        let failures = self.failures.lock().unwrap();
+        let (mut order, unhealthy): (Vec<_>, Vec<_>) = (0..failures.len()).partition(|idx| {
+            failures[*idx].map_or(true, |failed| failed.elapsed() >= self.health_check_interval)
+        });
+        order.extend(unhealthy);
+        order
+    }
+
+    fn set_healthy(&self, idx: usize, healthy: bool) {
+        set_healthy(&self.failures, idx, healthy)
+    }
+}
+
+/// When each client last failed, `None` if it is healthy
+type Failures = Mutex<Vec<Option<Instant>>>;
+
+fn set_healthy(failures: &Failures, idx: usize, healthy: bool) {
+    failures.lock().unwrap()[idx] = (!healthy).then(Instant::now);
+}
+
+/// Sends the request to the client, failing with [`ProviderError::Timeout`] if it doesn't respond
+/// in time
+async fn request_client<T: JsonRpcClientWrapper>(
+    provider: &T,
+    timeout: Option<Duration>,
+    method: &str,
+    params: QuorumParams,
+) -> Result<serde_json::Value, ProviderError> {
+    let request = provider.request(method, params);
+    match timeout {
+        // dropping the request future cancels it in the transport
+        Some(timeout) => {
+            futures_util::pin_mut!(request);
+            match future::select(request, Delay::new(timeout)).await {
+                Either::Left((res, _)) => res,
+                Either::Right(_) => Err(ProviderError::Timeout(timeout)),
+            }
+        }
+        None => request.await,
+    }
+}
+
+/// Checks every client with an `eth_blockNumber` request and updates its health
+async fn check_health<T: JsonRpcClientWrapper>(
+    providers: &[T],
+    failures: &Failures,
+    timeout: Option<Duration>,
+) -> Vec<bool> {
+    let checks = providers.iter().enumerate().map(|(idx, provider)| async move {
+        let res = request_client(provider, timeout, "eth_blockNumber", QuorumParams::Zst).await;
+        let healthy = res.map_or(false, |block| serde_json::from_value::<U64>(block).is_ok());
+        set_healthy(failures, idx, healthy);
+        healthy
+    });
+    join_all(checks).await
+}
+
+/// Checks the clients every `period` until the provider is dropped
+fn spawn_health_checks<T: JsonRpcClientWrapper + 'static>(
+    providers: Weak<Vec<T>>,
+    failures: Weak<Failures>,
+    timeout: Option<Duration>,
+    period: Duration,
+) {
+    let checks = async move {
+        loop {
+            Delay::new(period).await;
+            let (providers, failures) = match (providers.upgrade(), failures.upgrade()) {
+                (Some(providers), Some(failures)) => (providers, failures),
+                _ => break,
+            };
+            let healthy = check_health(&providers, &failures, timeout).await;
+            tracing::trace!(?healthy, "checked the health of the clients");
+        }
+    };
+
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(checks);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(checks);
+}
+
+impl<T: JsonRpcClientWrapper> FallbackProvider<T> {
+    /// Sends the request to the client at `idx`, failing with [`ProviderError::Timeout`] if it
+    /// doesn't respond in time
+    async fn request_client(
+        &self,
+        idx: usize,
+        method: &str,
+        params: QuorumParams,
+    ) -> Result<serde_json::Value, ProviderError> {
+        request_client(&self.providers[idx], self.timeout, method, params).await
+    }
+
+    /// Checks every client with an `eth_blockNumber` request and updates its health, returning
+    /// whether each client is healthy
+    pub async fn health_check(&self) -> Vec<bool> {
+        check_health(&self.providers, &self.failures, self.timeout).await
+    }
+}
+
+/// Builder for a [`FallbackProvider`]
+#[derive(Debug)]
+pub struct FallbackProviderBuilder<T> {
+    providers: Vec<T>,
+    timeout: Option<Duration>,
+    health_check_interval: Duration,
+    background_health_checks: Option<Duration>,
+}
+
+impl<T> Default for FallbackProviderBuilder<T> {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            timeout: None,
+            health_check_interval: DEFAULT_HEALTH_CHECK_INTERVAL,
+            background_health_checks: None,
+        }
+    }
+}
+
+impl<T> FallbackProviderBuilder<T> {
+    /// Adds a client with a lower priority than the clients added so far
+    pub fn add_provider(mut self, provider: T) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Adds clients with a lower priority than the clients added so far, in order of priority
+    pub fn add_providers(mut self, providers: impl IntoIterator<Item = T>) -> Self {
+        self.providers.extend(providers);
+        self
+    }
+
+    /// Sets how long requests may take before they are sent to the next client (default:
+    /// unlimited)
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long failed clients are skipped before requests are sent to them again (default:
+    /// 30 seconds)
+    pub fn health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    /// Checks the health of all clients every `period` in a background task, which stops once the
+    /// `FallbackProvider` is dropped (default: only failed requests and
+    /// [`health_check`](FallbackProvider::health_check) update the health)
+    pub fn background_health_checks(mut self, period: Duration) -> Self {
+        self.background_health_checks = Some(period);
+        self
+    }
+
+    /// Creates the `FallbackProvider` with the configured clients, all of them healthy, and
+    /// starts the background health checks if configured
+    pub fn build(self) -> FallbackProvider<T>
+    where
+        T: JsonRpcClientWrapper + 'static,
+    {
+        let FallbackProviderBuilder {
+            providers,
+            timeout,
+            health_check_interval,
+            background_health_checks,
+        } = self;
+        let failures = Arc::new(Mutex::new(vec![None; providers.len()]));
+        let providers = Arc::new(providers);
+        if let Some(period) = background_health_checks {
+            spawn_health_checks(
+                Arc::downgrade(&providers),
+                Arc::downgrade(&failures),
+                timeout,
+                period,
+            );
+        }
+        FallbackProvider {
+            providers,
+            failures,
+            timeout,
+            health_check_interval,
+            subscriptions: Mutex::default(),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for FallbackProvider<C>
+where
+    C: JsonRpcClientWrapper,
+{
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = if std::mem::size_of::<T>() == 0 {
+            // we don't want `()` to become `"null"`.
+            QuorumParams::Zst
+        } else {
+            QuorumParams::Value(serde_json::to_value(params)?)
+        };
+
+        if method == "eth_unsubscribe" {
+            if let Some(idx) = self.subscription_client(&params) {
+                let value = self.request_client(idx, method, params).await?;
+                return Ok(serde_json::from_value(value)?)
+            }
+        }
+
+        let mut last_err = None;
+        for idx in self.order() {
+            match self.request_client(idx, method, params.clone()).await {
+                Ok(value) => {
+                    self.set_healthy(idx, true);
+                    if method == "eth_subscribe" {
+                        if let Ok(id) = serde_json::from_value(value.clone()) {
+                            self.subscriptions.lock().unwrap().insert(id, idx);
+                        }
+                    }
+                    return Ok(serde_json::from_value(value)?)
+                }
+                // the node responded, the next one would return the same error
+                Err(err) if err.as_error_response().is_some() => {
+                    self.set_healthy(idx, true);
+                    return Err(err)
+                }
+                Err(err) => {
+                    tracing::warn!(?err, idx, method, "client failed, trying the next one");
+                    self.set_healthy(idx, false);
+                    last_err = Some(err);
+                }
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| ProviderError::CustomError("no clients to send to".to_string())))
+    }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        let supported =
+            self.providers.iter().all(|provider| provider.as_pubsub_wrapper().is_some());
+        (supported && !self.providers.is_empty()).then_some(self as _)
+    }
+}
+
+impl<C> FallbackProvider<C> {
+    /// Returns the client that created the subscription of the `eth_unsubscribe` params
+    fn subscription_client(&self, params: &QuorumParams) -> Option<usize> {
+        let id = match params {
+            QuorumParams::Value(serde_json::Value::Array(params)) => params.first()?,
+            _ => return None,
+        };
+        let id = serde_json::from_value::<U256>(id.clone()).ok()?;
+        self.subscriptions.lock().unwrap().get(&id).copied()
+    }
+}
+
+impl<C> PubsubClient for FallbackProvider<C>
+where
+    C: JsonRpcClientWrapper,
+{
+    type NotificationStream = NotificationStream;
+
+    fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
+        let id = id.into();
+        let idx = self.subscriptions.lock().unwrap().get(&id).copied();
+        pubsub(&self.providers[idx.unwrap_or_else(|| self.active())])?.subscribe(id)
+    }
+
+    fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
+        let id = id.into();
+        let idx = self.subscriptions.lock().unwrap().remove(&id);
+        pubsub(&self.providers[idx.unwrap_or_else(|| self.active())])?.unsubscribe(id)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{JsonRpcError, Middleware, MockProvider, Provider};
+
+    #[tokio::test]
+    async fn fails_over_and_back() {
+        let primary = MockProvider::new();
+        let backup = MockProvider::new();
+        let client = FallbackProvider::builder()
+            .add_providers([primary.clone(), backup.clone()])
+            .health_check_interval(Duration::from_millis(50))
+            .build();
+        let provider = Provider::new(client);
+
+        // the primary has no response and fails
+        backup.push(U64::from(1)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(1));
+        primary.assert_request("eth_blockNumber", ()).unwrap();
+        backup.assert_request("eth_blockNumber", ()).unwrap();
+        assert!(!provider.as_ref().is_healthy(0));
+        assert_eq!(provider.as_ref().active(), 1);
+
+        // the primary is skipped until it's due to be checked again
+        backup.push(U64::from(2)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(2));
+        backup.assert_request("eth_blockNumber", ()).unwrap();
+        assert!(primary.assert_request("eth_blockNumber", ()).is_err());
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(provider.as_ref().active(), 0);
+        primary.push(U64::from(3)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(3));
+        assert!(provider.as_ref().is_healthy(0));
+        assert!(backup.assert_request("eth_blockNumber", ()).is_err());
+    }
+
+    #[tokio::test]
+    async fn fails_over_on_timeouts() {
+        let (primary, backup) = (MockProvider::new(), MockProvider::new());
+        let hanging = primary.expect("eth_blockNumber").delay(Duration::from_secs(60));
+        hanging.returns(U64::from(0)).unwrap();
+        let client = FallbackProvider::builder()
+            .add_providers([primary, backup.clone()])
+            .request_timeout(Duration::from_millis(10))
+            .build();
+        let provider = Provider::new(client);
+
+        backup.push(U64::from(1)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(1));
+        assert!(!provider.as_ref().is_healthy(0));
+    }
+
+    #[tokio::test]
+    async fn returns_node_errors() {
+        let (primary, backup) = (MockProvider::new(), MockProvider::new());
+        primary.push_error(JsonRpcError { code: 3, message: "rejected".to_string(), data: None });
+        let client = FallbackProvider::builder().add_providers([primary, backup.clone()]).build();
+        let provider = Provider::new(client);
+
+        let err = provider.get_block_number().await.unwrap_err();
+        assert_eq!(err.as_error_response().unwrap().code, 3);
+        assert!(backup.assert_request("eth_blockNumber", ()).is_err());
+        assert!(provider.as_ref().is_healthy(0));
+    }
+
+    #[tokio::test]
+    async fn checks_health() {
+        let primary = MockProvider::new();
+        let backup = MockProvider::new();
+        let client =
+            FallbackProvider::builder().add_providers([primary.clone(), backup.clone()]).build();
+
+        backup.push(U64::from(1)).unwrap();
+        assert_eq!(client.health_check().await, vec![false, true]);
+        assert_eq!(client.active(), 1);
+
+        primary.push(U64::from(1)).unwrap();
+        backup.push(U64::from(1)).unwrap();
+        assert_eq!(client.health_check().await, vec![true, true]);
+        assert_eq!(client.active(), 0);
+    }
+
+    #[tokio::test]
+    async fn checks_health_in_the_background() {
+        let primary = MockProvider::new();
+        let checks = primary.expect("eth_blockNumber").returns(U64::from(1)).unwrap();
+        let client = FallbackProvider::builder()
+            .add_provider(primary)
+            .background_health_checks(Duration::from_millis(10))
+            .build();
+        client.set_healthy(0, false);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(client.is_healthy(0));
+        assert!(checks.calls() > 1);
+
+        // the checks stop with the provider
+        drop(client);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let calls = checks.calls();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(checks.calls(), calls);
+    }
+}
@@ -0,0 +1,628 @@
+//! A transport that serves common JSON-RPC methods from the GraphQL endpoint of execution clients,
+//! see [EIP-1767](https://eips.ethereum.org/EIPS/eip-1767)
+
+use crate::{provider::ProviderError, JsonRpcClient};
+use async_trait::async_trait;
+use ethers_core::{
+    abi::ethereum_types::BloomInput,
+    types::{Block, BlockId, Bloom, Log, Transaction, TransactionReceipt},
+};
+use reqwest::Client;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+use url::Url;
+
+const LOG_FIELDS: &str =
+    "index account { address } topics data transaction { hash index block { number hash } }";
+
+const TX_FIELDS: &str = "hash nonce index from { address } to { address } value gasPrice \
+    maxFeePerGas maxPriorityFeePerGas gas inputData r s v type \
+    accessList { address storageKeys } block { number hash }";
+
+const RECEIPT_FIELDS: &str = "hash index from { address } to { address } status gasUsed \
+    cumulativeGasUsed effectiveGasPrice createdContract { address } type block { number hash }";
+
+const BLOCK_FIELDS: &str = "number hash parent { hash } nonce transactionsRoot stateRoot \
+    receiptsRoot miner { address } extraData gasLimit gasUsed baseFeePerGas timestamp logsBloom \
+    mixHash difficulty totalDifficulty ommerHash ommers { hash }";
+
+/// A client for the GraphQL endpoint of execution clients, e.g. geth's `/graphql`.
+///
+/// It implements [`JsonRpcClient`] for the block, transaction, receipt and log queries of the
+/// [`Middleware`](crate::Middleware) trait, so it can be used as the transport of a
+/// [`Provider`](crate::Provider) for indexing. Other methods fail with
+/// [`GraphqlError::UnsupportedMethod`].
+///
+/// Unlike JSON-RPC, GraphQL can fetch a block along with the receipts of all its transactions in
+/// a single request, see [`Graphql::block_with_receipts`] and [`Graphql::blocks_with_receipts`]
+/// for ranges of blocks. `eth_getBlockReceipts` is served the same way.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::BlockNumber;
+/// use ethers_providers::{Graphql, Middleware, Provider};
+/// use url::Url;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let graphql = Graphql::new(Url::parse("http://localhost:8545/graphql")?);
+///
+/// let (block, receipts) =
+///     graphql.block_with_receipts(BlockNumber::Latest.into()).await?.expect("no block");
+///
+/// let provider = Provider::new(graphql);
+/// let block = provider.get_block_with_txs(1u64).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Graphql {
+    client: Client,
+    url: Url,
+}
+
+/// Error thrown by the [`Graphql`] transport
+#[derive(Debug, Error)]
+pub enum GraphqlError {
+    /// Thrown if the request failed
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    /// Thrown if the endpoint rejected the query
+    #[error("GraphQL query failed: {}", .0.join(", "))]
+    Query(Vec<String>),
+    /// Thrown if the JSON-RPC method has no GraphQL equivalent
+    #[error("unsupported method {0}")]
+    UnsupportedMethod(String),
+    /// Thrown if the params of a JSON-RPC method have no GraphQL equivalent, e.g. the `pending`
+    /// block tag
+    #[error("unsupported params {0}")]
+    UnsupportedParams(String),
+    /// Thrown if a request or response could not be (de)serialized
+    #[error("Deserialization Error: {err}. Response: {text}")]
+    SerdeJson { err: serde_json::Error, text: String },
+}
+
+impl From<GraphqlError> for ProviderError {
+    fn from(src: GraphqlError) -> Self {
+        match src {
+            GraphqlError::Reqwest(err) => ProviderError::HTTPError(err),
+            _ => ProviderError::JsonRpcClientError(Box::new(src)),
+        }
+    }
+}
+
+impl Graphql {
+    /// Creates a client for the GraphQL endpoint at the given url
+    pub fn new(url: impl Into<Url>) -> Self {
+        Self::new_with_client(url, Client::new())
+    }
+
+    /// Creates a client for the GraphQL endpoint at the given url that uses the given http client
+    pub fn new_with_client(url: impl Into<Url>, client: Client) -> Self {
+        Self { client, url: url.into() }
+    }
+
+    /// The url of the GraphQL endpoint
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Sends a GraphQL query and returns its `data`
+    pub async fn query(&self, query: &str, variables: Value) -> Result<Value, GraphqlError> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            query: &'a str,
+            variables: Value,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(default)]
+            data: Value,
+            #[serde(default)]
+            errors: Vec<QueryError>,
+        }
+
+        #[derive(Deserialize)]
+        struct QueryError {
+            message: String,
+        }
+
+        let res = self.client.post(self.url.as_ref()).json(&Request { query, variables });
+        let body = res.send().await?.bytes().await?;
+        let res: Response = serde_json::from_slice(&body).map_err(|err| {
+            GraphqlError::SerdeJson { err, text: String::from_utf8_lossy(&body).to_string() }
+        })?;
+        if !res.errors.is_empty() {
+            return Err(GraphqlError::Query(res.errors.into_iter().map(|e| e.message).collect()))
+        }
+        Ok(res.data)
+    }
+
+    /// Returns the block with all its transactions and their receipts in a single query
+    pub async fn block_with_receipts(
+        &self,
+        block: BlockId,
+    ) -> Result<Option<(Block<Transaction>, Vec<TransactionReceipt>)>, GraphqlError> {
+        let id = to_value(block)?;
+        let block = self.block(&id, &with_receipts()).await?;
+        if block.is_null() {
+            return Ok(None)
+        }
+        block_with_receipts(&block).map(Some)
+    }
+
+    /// Returns the blocks from `from` to `to`, inclusive, with all their transactions and their
+    /// receipts in a single query
+    pub async fn blocks_with_receipts(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<(Block<Transaction>, Vec<TransactionReceipt>)>, GraphqlError> {
+        let query = format!(
+            "query($from: Long!, $to: Long) {{ blocks(from: $from, to: $to) {{ \
+             {BLOCK_FIELDS} transactions {{ {} }} }} }}",
+            with_receipts()
+        );
+        let variables = json!({ "from": format!("{from:#x}"), "to": format!("{to:#x}") });
+        let data = self.query(&query, variables).await?;
+        let blocks = data["blocks"].as_array().map(Vec::as_slice).unwrap_or_default();
+        blocks.iter().map(block_with_receipts).collect()
+    }
+
+    /// Returns the block with the given hash or number and the given selection of its
+    /// transactions, `Null` if there is no such block
+    async fn block(&self, id: &Value, transactions: &str) -> Result<Value, GraphqlError> {
+        // EIP-1898 block ids are objects
+        let (hash, number) = match id.as_str() {
+            Some(hash) if hash.len() == 66 => (id.clone(), Value::Null),
+            _ if !id["blockHash"].is_null() => (id["blockHash"].clone(), Value::Null),
+            _ if !id["blockNumber"].is_null() => (Value::Null, block_number(&id["blockNumber"])?),
+            _ => (Value::Null, block_number(id)?),
+        };
+        let query = format!(
+            "query($hash: Bytes32, $number: Long) {{ block(hash: $hash, number: $number) {{ \
+             {BLOCK_FIELDS} transactions {{ {transactions} }} }} }}"
+        );
+        let mut data = self.query(&query, json!({ "hash": hash, "number": number })).await?;
+        Ok(data["block"].take())
+    }
+
+    /// Returns the transaction with the given hash and selection, `Null` if there is no such
+    /// transaction
+    async fn transaction(&self, hash: &Value, fields: &str) -> Result<Value, GraphqlError> {
+        let query = format!("query($hash: Bytes32!) {{ transaction(hash: $hash) {{ {fields} }} }}");
+        let mut data = self.query(&query, json!({ "hash": hash })).await?;
+        Ok(data["transaction"].take())
+    }
+
+    /// Returns the logs matching the JSON-RPC filter
+    async fn logs(&self, filter: &Value) -> Result<Value, GraphqlError> {
+        let mut criteria = Map::new();
+        match &filter["address"] {
+            Value::Null => {}
+            Value::Array(addresses) => {
+                criteria.insert("addresses".to_string(), Value::Array(addresses.clone()));
+            }
+            address => {
+                criteria.insert("addresses".to_string(), json!([address]));
+            }
+        }
+        if let Some(topics) = filter["topics"].as_array() {
+            // an empty list matches any topic
+            let topics = topics
+                .iter()
+                .map(|topic| match topic {
+                    Value::Null => json!([]),
+                    Value::Array(_) => topic.clone(),
+                    topic => json!([topic]),
+                })
+                .collect();
+            criteria.insert("topics".to_string(), Value::Array(topics));
+        }
+
+        let data = if filter["blockHash"].is_null() {
+            for (rpc, graphql) in [("fromBlock", "fromBlock"), ("toBlock", "toBlock")] {
+                let number = block_number(&filter[rpc])?;
+                if !number.is_null() {
+                    criteria.insert(graphql.to_string(), number);
+                }
+            }
+            let query = format!(
+                "query($filter: FilterCriteria!) {{ logs(filter: $filter) {{ {LOG_FIELDS} }} }}"
+            );
+            self.query(&query, json!({ "filter": criteria })).await?
+        } else {
+            let query = format!(
+                "query($hash: Bytes32!, $filter: BlockFilterCriteria!) {{ block(hash: $hash) {{ \
+                 logs(filter: $filter) {{ {LOG_FIELDS} }} }} }}"
+            );
+            let variables = json!({ "hash": filter["blockHash"], "filter": criteria });
+            let mut data = self.query(&query, variables).await?;
+            data["block"].take()
+        };
+        let logs = data["logs"].as_array().map(Vec::as_slice).unwrap_or_default();
+        Ok(Value::Array(logs.iter().map(log_to_rpc).collect()))
+    }
+
+    /// Serves the JSON-RPC method and returns the result in the shape of the JSON-RPC response
+    async fn dispatch(&self, method: &str, params: &Value) -> Result<Value, GraphqlError> {
+        let param = |index: usize| params.get(index).cloned().unwrap_or_default();
+        match method {
+            "eth_blockNumber" => {
+                let data = self.query("{ block { number } }", json!({})).await?;
+                Ok(quantity(&data["block"]["number"]))
+            }
+            "eth_chainId" => Ok(self.query("{ chainID }", json!({})).await?["chainID"].take()),
+            "eth_gasPrice" => Ok(self.query("{ gasPrice }", json!({})).await?["gasPrice"].take()),
+            "eth_getBlockByNumber" | "eth_getBlockByHash" => {
+                let full = param(1).as_bool().unwrap_or_default();
+                let block = self.block(&param(0), if full { TX_FIELDS } else { "hash" }).await?;
+                Ok(if block.is_null() { block } else { block_to_rpc(&block, full) })
+            }
+            "eth_getBlockReceipts" => {
+                let block = self.block(&param(0), &with_receipts()).await?;
+                if block.is_null() {
+                    return Ok(block)
+                }
+                self::transactions(&block).iter().map(receipt_to_rpc).collect()
+            }
+            "eth_getTransactionByHash" => {
+                let tx = self.transaction(&param(0), TX_FIELDS).await?;
+                Ok(if tx.is_null() { tx } else { tx_to_rpc(&tx) })
+            }
+            "eth_getTransactionReceipt" => {
+                let tx = self.transaction(&param(0), &with_receipts()).await?;
+                // pending transactions have no receipt
+                if tx["block"].is_null() {
+                    return Ok(Value::Null)
+                }
+                receipt_to_rpc(&tx)
+            }
+            "eth_getLogs" => self.logs(&param(0)).await,
+            _ => Err(GraphqlError::UnsupportedMethod(method.to_string())),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl JsonRpcClient for Graphql {
+    type Error = GraphqlError;
+
+    async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, GraphqlError> {
+        let params = to_value(params)?;
+        from_value(self.dispatch(method, &params).await?)
+    }
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, GraphqlError> {
+    serde_json::to_value(value)
+        .map_err(|err| GraphqlError::SerdeJson { err, text: "request".to_string() })
+}
+
+fn from_value<R: DeserializeOwned>(value: Value) -> Result<R, GraphqlError> {
+    let text = value.to_string();
+    serde_json::from_value(value).map_err(|err| GraphqlError::SerdeJson { err, text })
+}
+
+/// Converts a JSON-RPC block number into a GraphQL `Long`, `Null` for the latest block
+fn block_number(number: &Value) -> Result<Value, GraphqlError> {
+    match number.as_str() {
+        None if number.is_null() => Ok(Value::Null),
+        Some("latest") => Ok(Value::Null),
+        Some("earliest") => Ok(json!("0x0")),
+        Some(number) if number.starts_with("0x") => Ok(json!(number)),
+        _ => Err(GraphqlError::UnsupportedParams(number.to_string())),
+    }
+}
+
+/// Converts a GraphQL `Long`, which older clients return as a number, into a JSON-RPC quantity
+fn quantity(value: &Value) -> Value {
+    match value.as_u64() {
+        Some(value) => json!(format!("{value:#x}")),
+        None => value.clone(),
+    }
+}
+
+/// Removes `null` fields, so that serde treats them as missing
+fn object(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            Value::Object(map.into_iter().filter(|(_, v)| !v.is_null()).collect())
+        }
+        value => value,
+    }
+}
+
+fn transactions(block: &Value) -> &[Value] {
+    block["transactions"].as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// The selection of transactions that includes their receipts
+fn with_receipts() -> String {
+    format!("{TX_FIELDS} {RECEIPT_FIELDS} logs {{ {LOG_FIELDS} }}")
+}
+
+fn block_with_receipts(
+    block: &Value,
+) -> Result<(Block<Transaction>, Vec<TransactionReceipt>), GraphqlError> {
+    let receipts = transactions(block).iter().map(receipt_to_rpc).collect::<Result<_, _>>()?;
+    Ok((from_value(block_to_rpc(block, true))?, from_value(Value::Array(receipts))?))
+}
+
+fn block_to_rpc(block: &Value, full: bool) -> Value {
+    let transactions = transactions(block)
+        .iter()
+        .map(|tx| if full { tx_to_rpc(tx) } else { tx["hash"].clone() })
+        .collect::<Vec<_>>();
+    let uncles = block["ommers"].as_array().map(Vec::as_slice).unwrap_or_default();
+    object(json!({
+        "hash": block["hash"],
+        "parentHash": block["parent"]["hash"],
+        "sha3Uncles": block["ommerHash"],
+        "miner": block["miner"]["address"],
+        "stateRoot": block["stateRoot"],
+        "transactionsRoot": block["transactionsRoot"],
+        "receiptsRoot": block["receiptsRoot"],
+        "number": quantity(&block["number"]),
+        "gasUsed": quantity(&block["gasUsed"]),
+        "gasLimit": quantity(&block["gasLimit"]),
+        "extraData": block["extraData"],
+        "logsBloom": block["logsBloom"],
+        "timestamp": quantity(&block["timestamp"]),
+        "difficulty": block["difficulty"],
+        "totalDifficulty": block["totalDifficulty"],
+        "uncles": uncles.iter().map(|uncle| uncle["hash"].clone()).collect::<Vec<_>>(),
+        "transactions": transactions,
+        "mixHash": block["mixHash"],
+        "nonce": block["nonce"],
+        "baseFeePerGas": block["baseFeePerGas"],
+    }))
+}
+
+fn tx_to_rpc(tx: &Value) -> Value {
+    object(json!({
+        "hash": tx["hash"],
+        "nonce": quantity(&tx["nonce"]),
+        "blockHash": tx["block"]["hash"],
+        "blockNumber": quantity(&tx["block"]["number"]),
+        "transactionIndex": quantity(&tx["index"]),
+        "from": tx["from"]["address"],
+        "to": tx["to"]["address"],
+        "value": tx["value"],
+        "gasPrice": tx["gasPrice"],
+        "maxFeePerGas": tx["maxFeePerGas"],
+        "maxPriorityFeePerGas": tx["maxPriorityFeePerGas"],
+        "gas": quantity(&tx["gas"]),
+        "input": tx["inputData"],
+        "v": tx["v"],
+        "r": tx["r"],
+        "s": tx["s"],
+        "type": quantity(&tx["type"]),
+        "accessList": tx["accessList"],
+    }))
+}
+
+fn receipt_to_rpc(tx: &Value) -> Result<Value, GraphqlError> {
+    let logs = tx["logs"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let logs = logs.iter().map(log_to_rpc).collect::<Vec<_>>();
+    // GraphQL doesn't expose the bloom of a receipt, but it only depends on the logs
+    let mut bloom = Bloom::default();
+    for log in &logs {
+        let log: Log = from_value(log.clone())?;
+        bloom.accrue(BloomInput::Raw(log.address.as_bytes()));
+        for topic in &log.topics {
+            bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+        }
+    }
+    Ok(object(json!({
+        "transactionHash": tx["hash"],
+        "transactionIndex": quantity(&tx["index"]),
+        "blockHash": tx["block"]["hash"],
+        "blockNumber": quantity(&tx["block"]["number"]),
+        "from": tx["from"]["address"],
+        "to": tx["to"]["address"],
+        "cumulativeGasUsed": quantity(&tx["cumulativeGasUsed"]),
+        "gasUsed": quantity(&tx["gasUsed"]),
+        "contractAddress": tx["createdContract"]["address"],
+        "logs": logs,
+        "status": quantity(&tx["status"]),
+        "logsBloom": bloom,
+        "type": quantity(&tx["type"]),
+        "effectiveGasPrice": tx["effectiveGasPrice"],
+    })))
+}
+
+fn log_to_rpc(log: &Value) -> Value {
+    let tx = &log["transaction"];
+    object(json!({
+        "address": log["account"]["address"],
+        "topics": log["topics"],
+        "data": log["data"],
+        "blockHash": tx["block"]["hash"],
+        "blockNumber": quantity(&tx["block"]["number"]),
+        "transactionHash": tx["hash"],
+        "transactionIndex": quantity(&tx["index"]),
+        "logIndex": quantity(&log["index"]),
+        "removed": false,
+    }))
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{test_server, Middleware, Provider};
+    #[cfg(not(feature = "celo"))]
+    use ethers_core::types::BlockNumber;
+    use ethers_core::types::{Address, Filter, H256, U64};
+    use tokio::task::JoinHandle;
+
+    /// Serves a single GraphQL request with the given response, the handle resolves to the
+    /// request
+    async fn serve_once(response: Value) -> (Url, JoinHandle<Value>) {
+        let (url, handle) =
+            test_server::serve_once("200 OK", "", move |_| response.to_string()).await;
+        let handle = tokio::spawn(async move { handle.await.unwrap().json() });
+        (Url::parse(&format!("{url}/graphql")).unwrap(), handle)
+    }
+
+    #[cfg(not(feature = "celo"))]
+    fn block() -> Value {
+        json!({
+            // older clients return a number instead of a hex string
+            "number": 1,
+            "hash": H256::repeat_byte(1),
+            "parent": { "hash": H256::repeat_byte(2) },
+            "nonce": "0x0000000000000000",
+            "transactionsRoot": H256::zero(),
+            "stateRoot": H256::zero(),
+            "receiptsRoot": H256::zero(),
+            "miner": { "address": Address::repeat_byte(3) },
+            "extraData": "0x",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x5208",
+            "baseFeePerGas": "0x7",
+            "timestamp": "0x64",
+            "logsBloom": Bloom::default(),
+            "mixHash": H256::zero(),
+            "difficulty": "0x0",
+            "totalDifficulty": "0x0",
+            "ommerHash": H256::zero(),
+            "ommers": [],
+        })
+    }
+
+    fn transaction() -> Value {
+        json!({
+            "hash": H256::repeat_byte(4),
+            "nonce": "0x0",
+            "index": "0x0",
+            "from": { "address": Address::repeat_byte(5) },
+            "to": null,
+            "value": "0x0",
+            "gasPrice": "0x7",
+            "maxFeePerGas": "0x8",
+            "maxPriorityFeePerGas": "0x0",
+            "gas": "0x5208",
+            "inputData": "0x",
+            "r": "0x1",
+            "s": "0x1",
+            "v": "0x0",
+            "type": "0x2",
+            "accessList": [],
+            "block": { "number": "0x1", "hash": H256::repeat_byte(1) },
+            "status": "0x1",
+            "gasUsed": "0x5208",
+            "cumulativeGasUsed": "0x5208",
+            "effectiveGasPrice": "0x7",
+            "createdContract": { "address": Address::repeat_byte(6) },
+            "logs": [{
+                "index": "0x0",
+                "account": { "address": Address::repeat_byte(6) },
+                "topics": [H256::repeat_byte(7)],
+                "data": "0x",
+                "transaction": {
+                    "hash": H256::repeat_byte(4),
+                    "index": "0x0",
+                    "block": { "number": "0x1", "hash": H256::repeat_byte(1) }
+                }
+            }],
+        })
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "celo"))]
+    async fn serves_blocks_through_provider() {
+        let mut block = block();
+        block["transactions"] = json!([transaction()]);
+        let (url, request) = serve_once(json!({ "data": { "block": block } })).await;
+
+        let provider = Provider::new(Graphql::new(url));
+        let block = provider.get_block_with_txs(1u64).await.unwrap().unwrap();
+        let request = request.await.unwrap();
+        assert_eq!(request["variables"], json!({ "hash": null, "number": "0x1" }));
+
+        assert_eq!(block.number, Some(U64::from(1)));
+        assert_eq!(block.author, Some(Address::repeat_byte(3)));
+        assert_eq!(block.gas_used, 21000u64.into());
+        let tx = &block.transactions[0];
+        assert_eq!(tx.from, Address::repeat_byte(5));
+        assert_eq!(tx.to, None);
+        assert_eq!(tx.block_number, Some(U64::from(1)));
+
+        let err = provider.get_block(BlockNumber::Pending).await.unwrap_err();
+        assert!(err.to_string().contains("unsupported params"));
+        let err = provider.get_balance(Address::zero(), None).await.unwrap_err();
+        assert!(err.to_string().contains("unsupported method eth_getBalance"));
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "celo"))]
+    async fn fetches_block_with_receipts_at_once() {
+        let mut block = block();
+        block["transactions"] = json!([transaction()]);
+        let (url, request) = serve_once(json!({ "data": { "block": block } })).await;
+
+        let graphql = Graphql::new(url);
+        let (block, receipts) =
+            graphql.block_with_receipts(H256::repeat_byte(1).into()).await.unwrap().unwrap();
+        let request = request.await.unwrap();
+        assert_eq!(request["variables"]["hash"], json!(H256::repeat_byte(1)));
+
+        assert_eq!(block.transactions.len(), 1);
+        let receipt = &receipts[0];
+        assert_eq!(receipt.transaction_hash, H256::repeat_byte(4));
+        assert_eq!(receipt.status, Some(U64::from(1)));
+        assert_eq!(receipt.contract_address, Some(Address::repeat_byte(6)));
+        assert_eq!(receipt.logs[0].log_index, Some(0u64.into()));
+        assert!(receipt.logs_bloom.contains_input(BloomInput::Raw(&[6; 20])));
+
+        let mut block = self::block();
+        block["transactions"] = json!([]);
+        let (url, request) =
+            serve_once(json!({ "data": { "blocks": [block.clone(), block] } })).await;
+        let blocks = Graphql::new(url).blocks_with_receipts(1, 2).await.unwrap();
+        let request = request.await.unwrap();
+        assert_eq!(request["variables"], json!({ "from": "0x1", "to": "0x2" }));
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks[0].1.is_empty());
+    }
+
+    #[tokio::test]
+    async fn maps_log_filters() {
+        let logs = transaction()["logs"].clone();
+        let (url, request) = serve_once(json!({ "data": { "logs": logs } })).await;
+
+        let provider = Provider::new(Graphql::new(url));
+        let filter = Filter::new()
+            .from_block(1u64)
+            .address(Address::repeat_byte(6))
+            .topic1(vec![H256::repeat_byte(8), H256::repeat_byte(9)]);
+        let logs = provider.get_logs(&filter).await.unwrap();
+        let request = request.await.unwrap();
+        assert_eq!(
+            request["variables"]["filter"],
+            json!({
+                "fromBlock": "0x1",
+                "addresses": [Address::repeat_byte(6)],
+                "topics": [[], [H256::repeat_byte(8), H256::repeat_byte(9)]]
+            })
+        );
+        assert_eq!(logs[0].transaction_hash, Some(H256::repeat_byte(4)));
+        assert_eq!(logs[0].block_number, Some(U64::from(1)));
+    }
+
+    #[tokio::test]
+    async fn surfaces_query_errors() {
+        let (url, _) = serve_once(json!({ "errors": [{ "message": "syntax error" }] })).await;
+        let err = Graphql::new(url).query("{ block {", json!({})).await.unwrap_err();
+        assert!(matches!(err, GraphqlError::Query(errors) if errors == ["syntax error"]));
+    }
+}
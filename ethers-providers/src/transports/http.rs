@@ -1,13 +1,29 @@
 // Code adapted from: https://github.com/althea-net/guac_rs/tree/master/web3/src/jsonrpc
 
-use super::common::{Authorization, JsonRpcError, Request, Response};
+use super::common::{Authorization, JsonRpcError, JwtSecret, Request, Response};
 use crate::{provider::ProviderError, JsonRpcClient};
 use async_trait::async_trait;
-use reqwest::{header::HeaderValue, Client, Error as ReqwestError};
-use serde::{de::DeserializeOwned, Serialize};
+use ethers_core::{
+    k256::ecdsa::{recoverable, signature::hazmat::PrehashSigner, SigningKey},
+    types::{Address, Signature},
+    utils::{hash_message, keccak256, secret_key_to_address},
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+    Client, Error as ReqwestError, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::value::RawValue;
 use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
     str::FromStr,
-    sync::atomic::{AtomicU64, Ordering},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 use thiserror::Error;
 use url::Url;
@@ -32,6 +48,24 @@ pub struct Provider {
     id: AtomicU64,
     client: Client,
     url: Url,
+    /// Secret used to sign a token for every request, if set
+    jwt: Option<JwtSecret>,
+    /// Key used to sign the body of every request for the Flashbots relay, if set
+    flashbots_signer: Option<Arc<dyn FlashbotsSigner>>,
+    /// Headers added to every request
+    headers: HeaderMap,
+    /// Called for the headers of every request, if set
+    header_provider: Option<HeaderProvider>,
+}
+
+/// A callback returning headers that are added to every request, e.g. a rotating access token
+#[derive(Clone)]
+struct HeaderProvider(Arc<dyn Fn() -> HeaderMap + Send + Sync>);
+
+impl fmt::Debug for HeaderProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HeaderProvider")
+    }
 }
 
 #[derive(Error, Debug)]
@@ -47,6 +81,23 @@ pub enum ClientError {
     #[error("Deserialization Error: {err}. Response: {text}")]
     /// Serde JSON Error
     SerdeJson { err: serde_json::Error, text: String },
+
+    /// Thrown if the response to a batch did not include a response to one of its requests
+    #[error("Missing response to request {0} of the batch")]
+    MissingBatchResponse(usize),
+
+    /// Thrown if the Flashbots signer failed to sign the request
+    #[error("Failed to sign the request: {0}")]
+    SignerError(String),
+
+    /// Thrown if the endpoint responded with `429 Too Many Requests`
+    #[error("Rate limited (retry after {retry_after:?}). Response: {text}")]
+    RateLimited {
+        /// The delay requested by the `Retry-After` header, if any
+        retry_after: Option<Duration>,
+        /// The response body
+        text: String,
+    },
 }
 
 impl From<ClientError> for ProviderError {
@@ -73,32 +124,121 @@ impl JsonRpcClient for Provider {
         let next_id = self.id.fetch_add(1, Ordering::SeqCst);
         let payload = Request::new(next_id, method, params);
 
-        let res = self.client.post(self.url.as_ref()).json(&payload).send().await?;
-        let body = res.bytes().await?;
-
-        let raw = match serde_json::from_slice(&body) {
-            Ok(Response::Success { result, .. }) => result.to_owned(),
-            Ok(Response::Error { error, .. }) => return Err(error.into()),
-            Ok(_) => {
-                let err = ClientError::SerdeJson {
-                    err: serde::de::Error::custom("unexpected notification over HTTP transport"),
-                    text: String::from_utf8_lossy(&body).to_string(),
-                };
-                return Err(err)
-            }
-            Err(err) => {
-                return Err(ClientError::SerdeJson {
-                    err,
-                    text: String::from_utf8_lossy(&body).to_string(),
-                })
-            }
-        };
+        let res = self.post(&payload).await?.send().await?;
+        let body = check_rate_limit(res).await?.bytes().await?;
+
+        decode_response(&body)
+    }
+}
+
+/// Deserializes the result of the JSON-RPC response in the body of an HTTP response
+pub(crate) fn decode_response<R: DeserializeOwned>(body: &[u8]) -> Result<R, ClientError> {
+    let raw = match serde_json::from_slice(body) {
+        Ok(Response::Success { result, .. }) => result.to_owned(),
+        Ok(Response::Error { error, .. }) => return Err(error.into()),
+        Ok(_) => {
+            let err = ClientError::SerdeJson {
+                err: serde::de::Error::custom("unexpected notification over HTTP transport"),
+                text: String::from_utf8_lossy(body).to_string(),
+            };
+            return Err(err)
+        }
+        Err(err) => {
+            return Err(ClientError::SerdeJson {
+                err,
+                text: String::from_utf8_lossy(body).to_string(),
+            })
+        }
+    };
+
+    serde_json::from_str(raw.get())
+        .map_err(|err| ClientError::SerdeJson { err, text: raw.to_string() })
+}
+
+/// The header the Flashbots relay expects the signature of the request body in
+const FLASHBOTS_SIGNATURE: &str = "X-Flashbots-Signature";
+
+/// A key signing the bodies of requests to the Flashbots relay, see
+/// [`Http::new_with_flashbots_signer`](Provider::new_with_flashbots_signer).
+///
+/// Implemented for [`SigningKey`]. Implement it to keep the reputation key in another signer,
+/// e.g. a remote signer, `ethers-middleware` implements it for any `Signer` in its
+/// `FlashbotsMiddleware`.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait FlashbotsSigner: fmt::Debug + Send + Sync {
+    /// Returns the address of the key
+    fn address(&self) -> Address;
+
+    /// Returns the EIP-191 signature of the message
+    async fn sign_message(
+        &self,
+        message: &str,
+    ) -> Result<Signature, Box<dyn std::error::Error + Send + Sync>>;
+}
 
-        let res = serde_json::from_str(raw.get())
-            .map_err(|err| ClientError::SerdeJson { err, text: raw.to_string() })?;
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl FlashbotsSigner for SigningKey {
+    fn address(&self) -> Address {
+        secret_key_to_address(self)
+    }
+
+    async fn sign_message(
+        &self,
+        message: &str,
+    ) -> Result<Signature, Box<dyn std::error::Error + Send + Sync>> {
+        let signature: recoverable::Signature =
+            self.sign_prehash(hash_message(message).as_bytes())?;
+        let mut bytes = signature.as_ref()[..64].to_vec();
+        bytes.push(u8::from(signature.recovery_id()) + 27);
+        Ok(Signature::try_from(bytes.as_slice())?)
+    }
+}
 
-        Ok(res)
+/// Returns the `X-Flashbots-Signature` header value for the body, `<address>:<signature>` where
+/// the signature is an EIP-191 signature of the hex encoded hash of the body
+pub(crate) async fn flashbots_signature(
+    signer: &dyn FlashbotsSigner,
+    body: &[u8],
+) -> Result<String, ClientError> {
+    let message = format!("0x{}", hex::encode(keccak256(body)));
+    let signature = signer
+        .sign_message(&message)
+        .await
+        .map_err(|err| ClientError::SignerError(err.to_string()))?;
+    Ok(format!("{:?}:0x{}", signer.address(), hex::encode(<[u8; 65]>::from(signature))))
+}
+
+/// Returns [`ClientError::RateLimited`] if the endpoint rejected the request with
+/// `429 Too Many Requests`
+async fn check_rate_limit(res: reqwest::Response) -> Result<reqwest::Response, ClientError> {
+    if res.status() != StatusCode::TOO_MANY_REQUESTS {
+        return Ok(res)
+    }
+    let retry_after = retry_after(res.headers());
+    let body = res.bytes().await?;
+    Err(ClientError::RateLimited { retry_after, text: String::from_utf8_lossy(&body).to_string() })
+}
+
+/// Returns the delay requested by the `Retry-After` header of a response, if any
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers.get(RETRY_AFTER).and_then(|val| val.to_str().ok()).and_then(parse_retry_after)
+}
+
+/// Parses the value of a `Retry-After` header, which is either a number of seconds or an HTTP
+/// date.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds))
+    }
+    // `SystemTime::now` is not available on wasm
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Ok(date) = httpdate::parse_http_date(value) {
+        return Some(date.duration_since(std::time::SystemTime::now()).unwrap_or_default())
     }
+    None
 }
 
 impl Provider {
@@ -142,15 +282,33 @@ impl Provider {
         url: impl Into<Url>,
         auth: Authorization,
     ) -> Result<Self, HttpClientError> {
-        let mut auth_value = HeaderValue::from_str(&auth.to_string())?;
-        auth_value.set_sensitive(true);
-
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(reqwest::header::AUTHORIZATION, auth_value);
-
-        let client = Client::builder().default_headers(headers).build()?;
+        Self::builder(url).auth(auth).build()
+    }
 
-        Ok(Self::new_with_client(url, client))
+    /// Returns a [`Builder`] to configure the authentication and headers of the client
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_providers::Http;
+    /// use reqwest::header::{HeaderMap, HeaderValue};
+    /// use url::Url;
+    ///
+    /// # fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = Http::builder(Url::parse("http://localhost:8545")?)
+    ///     .bearer_auth("my-token")
+    ///     .header("x-api-key", "my-key")
+    ///     .header_provider(|| {
+    ///         let mut headers = HeaderMap::new();
+    ///         headers.insert("x-session", HeaderValue::from_static("rotated-token"));
+    ///         headers
+    ///     })
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder(url: impl Into<Url>) -> Builder {
+        Builder::new(url)
     }
 
     /// Allows to customize the provider by providing your own http client
@@ -166,7 +324,238 @@ impl Provider {
     /// let provider = Http::new_with_client(url, client);
     /// ```
     pub fn new_with_client(url: impl Into<Url>, client: reqwest::Client) -> Self {
-        Self { id: AtomicU64::new(1), client, url: url.into() }
+        Self {
+            id: AtomicU64::new(1),
+            client,
+            url: url.into(),
+            jwt: None,
+            flashbots_signer: None,
+            headers: HeaderMap::new(),
+            header_provider: None,
+        }
+    }
+
+    /// Initializes a new HTTP Client that authenticates every request with a JSON Web Token
+    /// signed with the given secret, as required by the Engine API of execution clients
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_providers::{Http, JwtSecret};
+    /// use url::Url;
+    ///
+    /// # fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let secret = JwtSecret::from_file("/path/to/jwt.hex")?;
+    /// let provider = Http::new_with_jwt(Url::parse("http://localhost:8551")?, secret);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_with_jwt(url: impl Into<Url>, secret: JwtSecret) -> Self {
+        Self { jwt: Some(secret), ..Self::new(url) }
+    }
+
+    /// Initializes a new HTTP Client that signs the body of every request with the given key, as
+    /// required by the Flashbots relay to identify searchers
+    ///
+    /// The key should not be the key of an account holding funds, it is only used to build a
+    /// reputation with the relay. It can be a [`SigningKey`] or any other [`FlashbotsSigner`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_core::k256::ecdsa::SigningKey;
+    /// use ethers_providers::Http;
+    /// use url::Url;
+    ///
+    /// # fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let key = SigningKey::from_bytes(&[0x11; 32])?;
+    /// let relay = Http::new_with_flashbots_signer(Url::parse("https://relay.flashbots.net")?, key);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_with_flashbots_signer(
+        url: impl Into<Url>,
+        signer: impl FlashbotsSigner + 'static,
+    ) -> Self {
+        Self { flashbots_signer: Some(Arc::new(signer)), ..Self::new(url) }
+    }
+
+    /// Returns the address of the Flashbots signer, if set
+    pub fn flashbots_address(&self) -> Option<Address> {
+        self.flashbots_signer.as_ref().map(|signer| signer.address())
+    }
+
+    /// Returns a POST request of the payload to the url with the configured headers, authenticated
+    /// with a fresh token if a JWT secret is set and signed if a Flashbots signer is set
+    async fn post<T: Serialize + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<reqwest::RequestBuilder, ClientError> {
+        let mut request = self.client.post(self.url.as_ref());
+        if !self.headers.is_empty() {
+            request = request.headers(self.headers.clone());
+        }
+        if let Some(provider) = self.header_provider.as_ref() {
+            request = request.headers((provider.0)());
+        }
+        let request = match self.jwt.as_ref() {
+            #[cfg(not(target_arch = "wasm32"))]
+            Some(secret) => request.bearer_auth(secret.token()),
+            _ => request,
+        };
+        let signer = match self.flashbots_signer.as_ref() {
+            Some(signer) => signer,
+            None => return Ok(request.json(payload)),
+        };
+        let body = serde_json::to_vec(payload)
+            .map_err(|err| ClientError::SerdeJson { err, text: "request".to_string() })?;
+        Ok(request
+            .header(FLASHBOTS_SIGNATURE, flashbots_signature(signer.as_ref(), &body).await?)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body))
+    }
+}
+
+impl Provider {
+    /// Returns a new [`BatchRequest`] to send multiple requests to this client at once.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use ethers_core::types::{Address, U256, U64};
+    /// use ethers_providers::Http;
+    /// use std::str::FromStr;
+    ///
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let provider = Http::from_str("http://localhost:8545")?;
+    ///
+    /// let mut batch = provider.batch();
+    /// let block_number = batch.add_request("eth_blockNumber", ())?;
+    /// let balance = batch.add_request("eth_getBalance", (Address::zero(), "latest"))?;
+    ///
+    /// let responses = batch.send().await?;
+    /// let block_number: U64 = responses.get(block_number)?;
+    /// let balance: U256 = responses.get(balance)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn batch(&self) -> BatchRequest<'_> {
+        BatchRequest { provider: self, requests: Vec::new() }
+    }
+}
+
+/// Multiple JSON-RPC requests that are sent as a single JSON-RPC batch, created with
+/// [`Http::batch`](Provider::batch).
+///
+/// This takes a single HTTP request for any number of JSON-RPC requests, which is useful for
+/// rate-limited endpoints.
+#[derive(Debug)]
+#[must_use = "batches do nothing unless sent"]
+pub struct BatchRequest<'a> {
+    provider: &'a Provider,
+    requests: Vec<(u64, Box<RawValue>)>,
+}
+
+impl<'a> BatchRequest<'a> {
+    /// Adds a request to the batch, returning its index in the [`BatchResponse`].
+    pub fn add_request<T: Serialize>(
+        &mut self,
+        method: &str,
+        params: T,
+    ) -> Result<usize, ClientError> {
+        let id = self.provider.id.fetch_add(1, Ordering::SeqCst);
+        let request = serde_json::value::to_raw_value(&Request::new(id, method, params))
+            .map_err(|err| ClientError::SerdeJson { err, text: method.to_string() })?;
+        self.requests.push((id, request));
+        Ok(self.requests.len() - 1)
+    }
+
+    /// Returns the number of requests in the batch.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns whether the batch contains no requests.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Sends all requests in a single POST request and correlates the responses with the requests
+    /// by their id.
+    ///
+    /// Fails if the HTTP request fails or the node rejects the batch as a whole, errors of single
+    /// requests are returned by [`BatchResponse::get`].
+    pub async fn send(self) -> Result<BatchResponse, ClientError> {
+        if self.requests.is_empty() {
+            return Ok(BatchResponse { responses: Vec::new() })
+        }
+
+        let payload: Vec<_> = self.requests.iter().map(|(_, request)| request).collect();
+        let res = self.provider.post(&payload).await?.send().await?;
+        let body = check_rate_limit(res).await?.bytes().await?;
+
+        let mut responses: HashMap<u64, Result<Box<RawValue>, JsonRpcError>> =
+            match serde_json::from_slice::<Vec<Response<'_>>>(&body) {
+                Ok(responses) => responses
+                    .into_iter()
+                    .filter_map(|response| match response {
+                        Response::Success { id, result } => Some((id, Ok(result.to_owned()))),
+                        Response::Error { id, error } => Some((id, Err(error))),
+                        Response::Notification { .. } => None,
+                    })
+                    .collect(),
+                Err(err) => {
+                    // nodes reply with a single error if they reject the whole batch
+                    #[derive(Deserialize)]
+                    struct BatchError {
+                        error: JsonRpcError,
+                    }
+                    if let Ok(BatchError { error }) = serde_json::from_slice(&body) {
+                        return Err(error.into())
+                    }
+                    return Err(ClientError::SerdeJson {
+                        err,
+                        text: String::from_utf8_lossy(&body).to_string(),
+                    })
+                }
+            };
+
+        let responses = self.requests.iter().map(|(id, _)| responses.remove(id)).collect();
+        Ok(BatchResponse { responses })
+    }
+}
+
+/// The responses to a [`BatchRequest`], in the order of its requests.
+#[derive(Debug)]
+pub struct BatchResponse {
+    responses: Vec<Option<Result<Box<RawValue>, JsonRpcError>>>,
+}
+
+impl BatchResponse {
+    /// Returns the deserialized response to the request at `index`.
+    pub fn get<R: DeserializeOwned>(&self, index: usize) -> Result<R, ClientError> {
+        match self.responses.get(index) {
+            Some(Some(Ok(raw))) => serde_json::from_str(raw.get())
+                .map_err(|err| ClientError::SerdeJson { err, text: raw.to_string() }),
+            Some(Some(Err(err))) => Err(err.clone().into()),
+            _ => Err(ClientError::MissingBatchResponse(index)),
+        }
+    }
+
+    /// Returns the number of responses, which is the number of requests in the batch.
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    /// Returns whether the batch contained no requests.
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    /// Returns the raw responses, `None` for requests the node did not respond to.
+    pub fn into_raw(self) -> Vec<Option<Result<Box<RawValue>, JsonRpcError>>> {
+        self.responses
     }
 }
 
@@ -181,7 +570,209 @@ impl FromStr for Provider {
 
 impl Clone for Provider {
     fn clone(&self) -> Self {
-        Self { id: AtomicU64::new(1), client: self.client.clone(), url: self.url.clone() }
+        Self {
+            id: AtomicU64::new(1),
+            client: self.client.clone(),
+            url: self.url.clone(),
+            jwt: self.jwt.clone(),
+            flashbots_signer: self.flashbots_signer.clone(),
+            headers: self.headers.clone(),
+            header_provider: self.header_provider.clone(),
+        }
+    }
+}
+
+/// Configures the authentication and headers of an [`Http`](Provider) client, created with
+/// [`Http::builder`](Provider::builder).
+///
+/// Headers are sent with every request, including requests of [`BatchRequest`]s. Errors of
+/// invalid headers are returned by [`Builder::build`].
+///
+/// The connection pool, HTTP/2, TCP and proxy options tune the underlying [`Client`] for
+/// high-throughput services, they are ignored if a client is passed with [`Builder::client`].
+#[derive(Debug)]
+#[must_use = "builders do nothing unless built"]
+pub struct Builder {
+    url: Url,
+    client: Option<Client>,
+    client_builder: reqwest::ClientBuilder,
+    headers: HeaderMap,
+    header_provider: Option<HeaderProvider>,
+    error: Option<HttpClientError>,
+}
+
+impl Builder {
+    fn new(url: impl Into<Url>) -> Self {
+        Self {
+            url: url.into(),
+            client: None,
+            client_builder: Client::builder(),
+            headers: HeaderMap::new(),
+            header_provider: None,
+            error: None,
+        }
+    }
+
+    /// Sends the requests with the given client instead of building one with the transport
+    /// options of this builder
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Sets the maximum number of idle connections per host kept in the pool (default:
+    /// unlimited)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_max_idle_per_host(self, max: usize) -> Self {
+        self.map_client(|builder| builder.pool_max_idle_per_host(max))
+    }
+
+    /// Sets how long idle connections are kept in the pool, `None` keeps them forever (default:
+    /// 90 seconds)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_idle_timeout(self, timeout: Option<Duration>) -> Self {
+        self.map_client(|builder| builder.pool_idle_timeout(timeout))
+    }
+
+    /// Sets the timeout for establishing connections (default: none)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn connect_timeout(self, timeout: Duration) -> Self {
+        self.map_client(|builder| builder.connect_timeout(timeout))
+    }
+
+    /// Only uses HTTP/1
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http1_only(self) -> Self {
+        self.map_client(|builder| builder.http1_only())
+    }
+
+    /// Uses HTTP/2 without negotiating it first, for endpoints that are known to support it
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http2_prior_knowledge(self) -> Self {
+        self.map_client(|builder| builder.http2_prior_knowledge())
+    }
+
+    /// Sends HTTP/2 pings at the interval to keep connections alive, `None` disables them
+    /// (default)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http2_keep_alive_interval(self, interval: Option<Duration>) -> Self {
+        self.map_client(|builder| builder.http2_keep_alive_interval(interval))
+    }
+
+    /// Sets whether the HTTP/2 flow control windows are sized adaptively (default: false)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn http2_adaptive_window(self, enabled: bool) -> Self {
+        self.map_client(|builder| builder.http2_adaptive_window(enabled))
+    }
+
+    /// Sets the interval of TCP keepalive probes, `None` disables them (default)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tcp_keepalive(self, interval: Option<Duration>) -> Self {
+        self.map_client(|builder| builder.tcp_keepalive(interval))
+    }
+
+    /// Sets `TCP_NODELAY` on connections (default: true)
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tcp_nodelay(self, enabled: bool) -> Self {
+        self.map_client(|builder| builder.tcp_nodelay(enabled))
+    }
+
+    /// Sends the requests through the proxy, by default the proxies of the `HTTP_PROXY` and
+    /// `HTTPS_PROXY` environment variables are used
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn proxy(self, proxy: reqwest::Proxy) -> Self {
+        self.map_client(|builder| builder.proxy(proxy))
+    }
+
+    /// Doesn't use any proxy, including the proxies of the environment
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn no_proxy(self) -> Self {
+        self.map_client(|builder| builder.no_proxy())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn map_client(
+        mut self,
+        f: impl FnOnce(reqwest::ClientBuilder) -> reqwest::ClientBuilder,
+    ) -> Self {
+        self.client_builder = f(std::mem::take(&mut self.client_builder));
+        self
+    }
+
+    /// Authenticates every request with the given `Authorization` header
+    pub fn auth(mut self, auth: Authorization) -> Self {
+        match HeaderValue::from_str(&auth.to_string()) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                self.headers.insert(AUTHORIZATION, value);
+            }
+            Err(err) => self.error = Some(err.into()),
+        }
+        self
+    }
+
+    /// Authenticates every request with the given bearer token
+    pub fn bearer_auth(self, token: impl Into<String>) -> Self {
+        self.auth(Authorization::bearer(token))
+    }
+
+    /// Authenticates every request with the given username and password
+    pub fn basic_auth(self, username: impl AsRef<str>, password: impl AsRef<str>) -> Self {
+        self.auth(Authorization::basic(username, password))
+    }
+
+    /// Adds a header to every request, replacing previous values of the header
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<HttpClientError>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<HttpClientError>,
+    {
+        let header = HeaderName::try_from(name)
+            .map_err(Into::into)
+            .and_then(|name| Ok((name, HeaderValue::try_from(value).map_err(Into::into)?)));
+        match header {
+            Ok((name, value)) => {
+                self.headers.insert(name, value);
+            }
+            Err(err) => self.error = Some(err),
+        }
+        self
+    }
+
+    /// Adds the headers to every request, replacing previous values of the headers
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Calls `provider` for headers that are added to every request, replacing static headers of
+    /// the same name.
+    ///
+    /// This is meant for credentials that expire, e.g. an access token that a background task
+    /// refreshes. The callback is called for every request, so it should be cheap.
+    pub fn header_provider(
+        mut self,
+        provider: impl Fn() -> HeaderMap + Send + Sync + 'static,
+    ) -> Self {
+        self.header_provider = Some(HeaderProvider(Arc::new(provider)));
+        self
+    }
+
+    /// Builds the client, fails if one of the headers is invalid or the client can't be built
+    pub fn build(self) -> Result<Provider, HttpClientError> {
+        if let Some(err) = self.error {
+            return Err(err)
+        }
+        let client = match self.client {
+            Some(client) => client,
+            None => self.client_builder.build()?,
+        };
+        let mut provider = Provider::new_with_client(self.url, client);
+        provider.headers = self.headers;
+        provider.header_provider = self.header_provider;
+        Ok(provider)
     }
 }
 
@@ -192,7 +783,256 @@ pub enum HttpClientError {
     #[error(transparent)]
     InvalidHeader(#[from] http::header::InvalidHeaderValue),
 
+    /// Thrown if the name of a header is invalid
+    #[error(transparent)]
+    InvalidHeaderName(#[from] http::header::InvalidHeaderName),
+
     /// Thrown if unable to build client
     #[error(transparent)]
     ClientBuild(#[from] reqwest::Error),
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::test_server;
+    use base64::{engine::general_purpose, Engine};
+    use ethers_core::types::U64;
+    use serde_json::{json, Value};
+
+    /// Serves a single HTTP request, responding with the result of `respond` for its body.
+    async fn serve_once(respond: impl FnOnce(Value) -> Value + Send + 'static) -> Url {
+        serve_once_with("200 OK", "", move |_, body| respond(body)).await
+    }
+
+    /// Like [`serve_once`], but responds with the given status and additional headers, each
+    /// terminated by `\r\n`. `respond` also receives the head of the request.
+    async fn serve_once_with(
+        status: &'static str,
+        headers: &'static str,
+        respond: impl FnOnce(&str, Value) -> Value + Send + 'static,
+    ) -> Url {
+        let (url, _) = test_server::serve_once(status, headers, move |request| {
+            respond(&request.head, request.json()).to_string()
+        })
+        .await;
+        Url::parse(&url).unwrap()
+    }
+
+    #[tokio::test]
+    async fn batch_correlates_responses() {
+        // responds in reverse order, failing unknown methods and omitting `eth_gasPrice`
+        let url = serve_once(|requests| {
+            let responses: Vec<Value> = requests
+                .as_array()
+                .unwrap()
+                .iter()
+                .rev()
+                .filter(|request| request["method"] != "eth_gasPrice")
+                .map(|request| {
+                    let id = &request["id"];
+                    match request["method"].as_str().unwrap() {
+                        "eth_blockNumber" => json!({"jsonrpc": "2.0", "id": id, "result": "0x10"}),
+                        "eth_chainId" => json!({"jsonrpc": "2.0", "id": id, "result": "0x1"}),
+                        _ => {
+                            let error = json!({"code": -32601, "message": "method not found"});
+                            json!({"jsonrpc": "2.0", "id": id, "error": error})
+                        }
+                    }
+                })
+                .collect();
+            Value::Array(responses)
+        })
+        .await;
+        let provider = Provider::new(url);
+
+        let mut batch = provider.batch();
+        let block_number = batch.add_request("eth_blockNumber", ()).unwrap();
+        let unknown = batch.add_request("eth_unknown", ()).unwrap();
+        let chain_id = batch.add_request("eth_chainId", ()).unwrap();
+        let gas_price = batch.add_request("eth_gasPrice", ()).unwrap();
+        assert_eq!(batch.len(), 4);
+
+        let responses = batch.send().await.unwrap();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses.get::<U64>(block_number).unwrap(), U64::from(16));
+        assert_eq!(responses.get::<U64>(chain_id).unwrap(), U64::from(1));
+        match responses.get::<U64>(unknown).unwrap_err() {
+            ClientError::JsonRpcError(err) => assert_eq!(err.code, -32601),
+            err => panic!("unexpected error {err:?}"),
+        }
+        assert!(matches!(
+            responses.get::<U64>(gas_price),
+            Err(ClientError::MissingBatchResponse(3))
+        ));
+    }
+
+    #[tokio::test]
+    async fn batch_rejected() {
+        let url = serve_once(|_| {
+            let error = json!({"code": -32600, "message": "batches are not supported"});
+            json!({"jsonrpc": "2.0", "id": null, "error": error})
+        })
+        .await;
+        let provider = Provider::new(url);
+
+        let mut batch = provider.batch();
+        batch.add_request("eth_blockNumber", ()).unwrap();
+        match batch.send().await.unwrap_err() {
+            ClientError::JsonRpcError(err) => assert_eq!(err.code, -32600),
+            err => panic!("unexpected error {err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limited_with_retry_after() {
+        let url = serve_once_with("429 Too Many Requests", "retry-after: 7\r\n", |_, request| {
+            let error = json!({"code": 429, "message": "Your app has exceeded its compute units per second capacity"});
+            json!({"jsonrpc": "2.0", "id": request["id"], "error": error})
+        })
+        .await;
+
+        let provider = Provider::new(url);
+        let err = provider.request::<_, U64>("eth_blockNumber", ()).await.unwrap_err();
+        match err {
+            ClientError::RateLimited { retry_after, text } => {
+                assert_eq!(retry_after, Some(Duration::from_secs(7)));
+                assert!(text.contains("compute units"));
+            }
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_configured_headers() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let url = serve_once_with("200 OK", "", move |head, request| {
+            tx.send(head.to_lowercase()).unwrap();
+            json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"})
+        })
+        .await;
+
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = calls.clone();
+        let provider = Provider::builder(url)
+            .basic_auth("admin", "secret")
+            .header("x-api-key", "key")
+            .header_provider(move || {
+                let call = counter.fetch_add(1, Ordering::SeqCst);
+                let mut headers = HeaderMap::new();
+                headers.insert("x-session", HeaderValue::from(call));
+                headers
+            })
+            .build()
+            .unwrap();
+        let block: U64 = provider.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block, U64::from(1));
+
+        let head = rx.recv().unwrap();
+        let auth = general_purpose::STANDARD.encode("admin:secret").to_lowercase();
+        assert!(head.contains(&format!("authorization: basic {auth}")));
+        assert!(head.contains("x-api-key: key"));
+        assert!(head.contains("x-session: 0"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn sends_through_proxy() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let proxy = serve_once_with("200 OK", "", move |head, request| {
+            tx.send(head.lines().next().unwrap().to_string()).unwrap();
+            json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"})
+        })
+        .await;
+
+        let provider = Provider::builder(Url::parse("http://node.invalid:8545").unwrap())
+            .proxy(reqwest::Proxy::http(proxy.as_str()).unwrap())
+            .pool_max_idle_per_host(4)
+            .pool_idle_timeout(Some(Duration::from_secs(30)))
+            .tcp_keepalive(Some(Duration::from_secs(60)))
+            .http1_only()
+            .build()
+            .unwrap();
+        let block: U64 = provider.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block, U64::from(1));
+        assert_eq!(rx.recv().unwrap(), "POST http://node.invalid:8545/ HTTP/1.1");
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let url = Url::parse("http://localhost:8545").unwrap();
+        let err = Provider::builder(url.clone()).header("x-api-key", "line\nbreak").build();
+        assert!(matches!(err, Err(HttpClientError::InvalidHeader(_))));
+        let err = Provider::builder(url).header("not a name", "value").build();
+        assert!(matches!(err, Err(HttpClientError::InvalidHeaderName(_))));
+    }
+
+    #[tokio::test]
+    async fn authenticates_with_jwt() {
+        let secret = JwtSecret::new([0x11; 32]);
+        let (tx, rx) = std::sync::mpsc::channel();
+        let url = serve_once_with("200 OK", "", move |head, request| {
+            let token = head.lines().find_map(|line| {
+                line.to_lowercase()
+                    .starts_with("authorization: bearer ")
+                    .then(|| line[22..].to_string())
+            });
+            tx.send(token).unwrap();
+            json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"})
+        })
+        .await;
+
+        let provider = Provider::new_with_jwt(url, secret.clone());
+        let block: U64 = provider.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block, U64::from(1));
+
+        let token = rx.recv().unwrap().expect("missing authorization header");
+        let (message, _) = token.rsplit_once('.').unwrap();
+        let claims = message.split('.').nth(1).unwrap();
+        let claims: Value =
+            serde_json::from_slice(&general_purpose::URL_SAFE_NO_PAD.decode(claims).unwrap())
+                .unwrap();
+        assert_eq!(secret.token_at(claims["iat"].as_u64().unwrap()), token);
+    }
+
+    #[tokio::test]
+    async fn signs_requests_for_flashbots() {
+        let key = SigningKey::from_bytes(&[0x11; 32]).unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let url = serve_once_with("200 OK", "", move |head, request| {
+            let signature = head.lines().find_map(|line| {
+                line.to_lowercase()
+                    .starts_with("x-flashbots-signature: ")
+                    .then(|| line[23..].to_string())
+            });
+            tx.send(signature).unwrap();
+            json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"})
+        })
+        .await;
+
+        let provider = Provider::new_with_flashbots_signer(url, key.clone());
+        let block: U64 = provider.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block, U64::from(1));
+
+        let signature = rx.recv().unwrap().expect("missing signature header");
+        let (address, signature) = signature.split_once(':').unwrap();
+        let address: ethers_core::types::Address = address.parse().unwrap();
+        assert_eq!(address, secret_key_to_address(&key));
+
+        let body = serde_json::to_vec(&Request::new(1, "eth_blockNumber", ())).unwrap();
+        let message = format!("0x{}", hex::encode(keccak256(body)));
+        let signature: ethers_core::types::Signature = signature.parse().unwrap();
+        assert_eq!(signature.recover(message).unwrap(), address);
+    }
+
+    #[test]
+    fn can_parse_retry_after() {
+        assert_eq!(parse_retry_after(" 120 "), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+        let in_a_minute =
+            httpdate::fmt_http_date(std::time::SystemTime::now() + Duration::from_secs(60));
+        let backoff = parse_retry_after(&in_a_minute).unwrap();
+        assert!(backoff > Duration::from_secs(55) && backoff <= Duration::from_secs(60));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
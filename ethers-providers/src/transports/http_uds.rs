@@ -0,0 +1,235 @@
+use super::{
+    common::{Authorization, JwtSecret, Request},
+    http::{decode_response, retry_after, ClientError},
+};
+use crate::{provider::ProviderError, JsonRpcClient};
+use async_trait::async_trait;
+use hyper::{
+    client::conn,
+    header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, HOST},
+    Body, Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+use thiserror::Error;
+use tokio::net::UnixStream;
+
+/// A JSON-RPC Client over HTTP on a Unix domain socket.
+///
+/// Unlike [`Ipc`](crate::Ipc), which exchanges bare JSON-RPC messages over the socket, this
+/// speaks HTTP for nodes that serve their HTTP endpoint on a socket. Responses are handled like
+/// responses of the [`Http`](crate::Http) transport. Every request opens a new connection.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::U64;
+/// use ethers_providers::{HttpUds, JsonRpcClient};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let provider = HttpUds::new("/var/run/geth-http.sock");
+/// let block_number: U64 = provider.request("eth_blockNumber", ()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HttpUds {
+    id: AtomicU64,
+    path: PathBuf,
+    /// Headers added to every request
+    headers: HeaderMap,
+    /// Secret used to sign a token for every request, if set
+    jwt: Option<JwtSecret>,
+}
+
+#[derive(Error, Debug)]
+/// Error thrown when sending an HTTP request over a Unix domain socket
+pub enum HttpUdsError {
+    /// Thrown if connecting to the socket failed
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+
+    /// Thrown if the HTTP request failed
+    #[error(transparent)]
+    HyperError(#[from] hyper::Error),
+
+    /// Thrown if the `Authorization` header is invalid
+    #[error(transparent)]
+    InvalidHeader(#[from] hyper::header::InvalidHeaderValue),
+
+    /// Thrown if the response is an error or could not be parsed, like for the
+    /// [`Http`](crate::Http) transport
+    #[error(transparent)]
+    ClientError(#[from] ClientError),
+}
+
+impl From<HttpUdsError> for ProviderError {
+    fn from(src: HttpUdsError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(src))
+    }
+}
+
+impl HttpUds {
+    /// Initializes a new client for the socket at the given path
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            id: AtomicU64::new(1),
+            path: path.as_ref().to_path_buf(),
+            headers: HeaderMap::new(),
+            jwt: None,
+        }
+    }
+
+    /// Initializes a new client that authenticates every request with the given `Authorization`
+    /// header
+    pub fn new_with_auth(
+        path: impl AsRef<Path>,
+        auth: Authorization,
+    ) -> Result<Self, HttpUdsError> {
+        let mut value = HeaderValue::from_str(&auth.to_string())?;
+        value.set_sensitive(true);
+        let mut provider = Self::new(path);
+        provider.headers.insert(AUTHORIZATION, value);
+        Ok(provider)
+    }
+
+    /// Initializes a new client that authenticates every request with a JSON Web Token signed
+    /// with the given secret, as required by the Engine API of execution clients
+    pub fn new_with_jwt(path: impl AsRef<Path>, secret: JwtSecret) -> Self {
+        Self { jwt: Some(secret), ..Self::new(path) }
+    }
+
+    /// The path of the socket to which requests are made
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns a POST request of the payload with the configured headers, authenticated with a
+    /// fresh token if a JWT secret is set
+    fn post<T: Serialize>(&self, payload: &T) -> Result<hyper::Request<Body>, HttpUdsError> {
+        let body = serde_json::to_vec(payload)
+            .map_err(|err| ClientError::SerdeJson { err, text: "request".to_string() })?;
+        let mut request = hyper::Request::new(Body::from(body));
+        *request.method_mut() = Method::POST;
+        *request.uri_mut() = hyper::Uri::from_static("/");
+        let headers = request.headers_mut();
+        headers.extend(self.headers.clone());
+        headers.insert(HOST, HeaderValue::from_static("localhost"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(secret) = self.jwt.as_ref() {
+            let mut value = HeaderValue::from_str(&format!("Bearer {}", secret.token()))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for HttpUds {
+    type Error = HttpUdsError;
+
+    /// Sends a POST request with the provided method and the params serialized as JSON
+    /// over HTTP on the socket
+    async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, HttpUdsError> {
+        let next_id = self.id.fetch_add(1, Ordering::SeqCst);
+        let request = self.post(&Request::new(next_id, method, params))?;
+
+        let stream = UnixStream::connect(&self.path).await?;
+        let (mut sender, connection) = conn::handshake(stream).await?;
+        tokio::spawn(async move {
+            if let Err(err) = connection.await {
+                tracing::debug!(?err, "HTTP connection on unix socket failed");
+            }
+        });
+
+        let res = sender.send_request(request).await?;
+        let (parts, body) = res.into_parts();
+        let body = hyper::body::to_bytes(body).await?;
+        if parts.status == StatusCode::TOO_MANY_REQUESTS {
+            let text = String::from_utf8_lossy(&body).to_string();
+            let err = ClientError::RateLimited { retry_after: retry_after(&parts.headers), text };
+            return Err(err.into())
+        }
+
+        Ok(decode_response(&body)?)
+    }
+}
+
+impl Clone for HttpUds {
+    fn clone(&self) -> Self {
+        Self {
+            id: AtomicU64::new(1),
+            path: self.path.clone(),
+            headers: self.headers.clone(),
+            jwt: self.jwt.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server;
+    use ethers_core::types::U64;
+    use serde_json::{json, Value};
+    use tempfile::TempDir;
+
+    /// Serves a single HTTP request on a socket in `dir`, responding with the given status and
+    /// the result of `respond` for the head and body of the request.
+    fn serve_once(
+        dir: &TempDir,
+        status: &'static str,
+        respond: impl FnOnce(&str, Value) -> Value + Send + 'static,
+    ) -> PathBuf {
+        let path = dir.path().join("http.sock");
+        test_server::serve_once_uds(&path, status, move |request| {
+            respond(&request.head, request.json()).to_string()
+        });
+        path
+    }
+
+    #[tokio::test]
+    async fn requests_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let path = serve_once(&dir, "200 OK", move |head, request| {
+            tx.send(head.to_lowercase()).unwrap();
+            json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"})
+        });
+
+        let provider = HttpUds::new_with_auth(path, Authorization::bearer("token")).unwrap();
+        let block: U64 = provider.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block, U64::from(1));
+        let head = rx.recv().unwrap();
+        assert!(head.starts_with("post / http/1.1"));
+        assert!(head.contains("authorization: bearer token"));
+    }
+
+    #[tokio::test]
+    async fn returns_rpc_and_rate_limit_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = serve_once(
+            &dir,
+            "200 OK",
+            |_, request| json!({"jsonrpc": "2.0", "id": request["id"], "error": {"code": -32601, "message": "method not found"}}),
+        );
+        let err = HttpUds::new(path).request::<_, U64>("eth_foo", ()).await.unwrap_err();
+        assert!(
+            matches!(err, HttpUdsError::ClientError(ClientError::JsonRpcError(ref err)) if err.code == -32601)
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = serve_once(&dir, "429 Too Many Requests", |_, _| json!({}));
+        let err = HttpUds::new(path).request::<_, U64>("eth_blockNumber", ()).await.unwrap_err();
+        assert!(matches!(err, HttpUdsError::ClientError(ClientError::RateLimited { .. })));
+    }
+}
@@ -279,6 +279,10 @@ impl JsonRpcClient for Ipc {
         // Parse JSON response.
         Ok(serde_json::from_str(res.get())?)
     }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        Some(self)
+    }
 }
 
 impl PubsubClient for Ipc {
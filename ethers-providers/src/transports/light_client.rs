@@ -0,0 +1,557 @@
+//! A [JsonRpcClient] that verifies the state served by an untrusted node against a trusted block
+//! hash.
+//!
+//! Headers are verified by hashing their fields and following their parent hashes back from the
+//! trusted checkpoint, account and storage reads are verified with the Merkle proofs of
+//! `eth_getProof` against the state root of a verified header.
+
+use crate::{provider::ProviderError, JsonRpcClient};
+use async_trait::async_trait;
+use ethers_core::{
+    types::{
+        Address, Block, BlockId, BlockNumber, Bytes, EIP1186ProofResponse, ProofError, TxHash,
+        H256, U256, U64,
+    },
+    utils::keccak256,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt::Debug, sync::Mutex};
+use thiserror::Error;
+use tracing::trace;
+
+/// The default of how many blocks before the checkpoint can be verified,
+/// see [`LightClient::max_depth`]
+const DEFAULT_MAX_DEPTH: u64 = 8192;
+
+/// A [JsonRpcClient] that wraps an untrusted node and verifies its responses to state reads
+/// against a trusted checkpoint, so a wallet doesn't have to trust the operator of the node.
+///
+/// The checkpoint is the hash of a block the caller trusts, e.g. the `block_hash` of the execution
+/// payload of the finalized beacon block of a consensus light client or of a trusted
+/// [`BeaconClient`](crate::beacon::BeaconClient). It becomes the latest block of the client:
+/// `eth_blockNumber` returns its number and reads at `latest`, `safe`, `finalized` or `pending`
+/// are served at it. Use [`LightClient::set_checkpoint`] to move it forward.
+///
+/// These methods are verified, with requests to the inner client for the headers and proofs they
+/// need:
+///
+/// - `eth_getBalance`, `eth_getTransactionCount`, `eth_getCode`, `eth_getStorageAt` and
+///   `eth_getProof` at the checkpoint, at a block number before it or at the hash of one of its
+///   ancestors, which is verified by following the parent hashes back from the checkpoint
+/// - `eth_blockNumber`
+///
+/// The result of `eth_call` is **not** verified, that would require executing the call locally
+/// against proven state. The call is only pinned to the hash of the verified block, so an honest
+/// node executes it on the verified state. All other requests are forwarded unverified.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_core::types::{Address, H256};
+/// use ethers_providers::{Http, LightClient, Middleware, Provider};
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let rpc: Http = "https://eth.llamarpc.com".parse()?;
+/// // e.g. the finalized block of a consensus light client
+/// let checkpoint: H256 =
+///     "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3".parse()?;
+/// let provider = Provider::new(LightClient::new(rpc, checkpoint));
+///
+/// let balance = provider.get_balance(Address::zero(), None).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct LightClient<C> {
+    inner: C,
+    trusted: Mutex<Trusted>,
+    max_depth: u64,
+}
+
+/// The checkpoint and the verified headers
+#[derive(Debug)]
+struct Trusted {
+    checkpoint: H256,
+    /// The verified ancestors of the checkpoint and the checkpoint itself, by number
+    headers: BTreeMap<u64, Header>,
+}
+
+/// The fields of a verified header needed to verify others and to verify state
+#[derive(Clone, Copy, Debug)]
+struct Header {
+    number: u64,
+    hash: H256,
+    parent_hash: H256,
+    state_root: H256,
+}
+
+impl<C: JsonRpcClient> LightClient<C> {
+    /// Creates a client that verifies the responses of `inner` against the block with the
+    /// `checkpoint` hash
+    pub fn new(inner: C, checkpoint: H256) -> Self {
+        Self {
+            inner,
+            trusted: Mutex::new(Trusted { checkpoint, headers: BTreeMap::new() }),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Sets how many blocks before the checkpoint can be verified (default: 8192).
+    ///
+    /// Verifying a block requests every header between it and the closest verified header after
+    /// it, reads of older blocks fail.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: u64) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Trusts the block with the given hash from now on, e.g. a newer finalized block.
+    ///
+    /// The headers that were verified against the previous checkpoint are discarded.
+    pub fn set_checkpoint(&self, checkpoint: H256) {
+        let mut trusted = self.trusted.lock().unwrap();
+        if trusted.checkpoint != checkpoint {
+            *trusted = Trusted { checkpoint, headers: BTreeMap::new() };
+        }
+    }
+
+    /// Returns the hash of the trusted checkpoint
+    pub fn checkpoint(&self) -> H256 {
+        self.trusted.lock().unwrap().checkpoint
+    }
+
+    /// Returns the inner, untrusted client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    async fn inner_request<T, R>(&self, method: &str, params: T) -> Result<R, LightClientError>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        self.inner
+            .request(method, params)
+            .await
+            .map_err(|err| LightClientError::ProviderError(err.into()))
+    }
+
+    /// Fetches the block with the given hash and checks that its fields hash to it
+    async fn fetch_header(&self, hash: H256) -> Result<Header, LightClientError> {
+        let block: Option<Block<TxHash>> =
+            self.inner_request("eth_getBlockByHash", (hash, false)).await?;
+        let block = block.ok_or(LightClientError::BlockNotFound(hash))?;
+        let actual = block.header_hash();
+        if actual != hash {
+            return Err(LightClientError::HeaderMismatch { expected: hash, actual })
+        }
+        let number = block.number.ok_or(LightClientError::BlockNotFound(hash))?.as_u64();
+        trace!(number, ?hash, "verified header");
+        Ok(Header { number, hash, parent_hash: block.parent_hash, state_root: block.state_root })
+    }
+
+    /// Stores a verified header, unless the checkpoint changed in the meantime
+    fn insert(&self, checkpoint: H256, header: Header) {
+        let mut trusted = self.trusted.lock().unwrap();
+        if trusted.checkpoint == checkpoint {
+            trusted.headers.insert(header.number, header);
+        }
+    }
+
+    /// Returns the header of the checkpoint
+    async fn head(&self) -> Result<Header, LightClientError> {
+        let checkpoint = {
+            let trusted = self.trusted.lock().unwrap();
+            if let Some((_, header)) = trusted.headers.iter().next_back() {
+                return Ok(*header)
+            }
+            trusted.checkpoint
+        };
+        let header = self.fetch_header(checkpoint).await?;
+        self.insert(checkpoint, header);
+        Ok(header)
+    }
+
+    /// Returns the verified header of the ancestor of the checkpoint with the given number
+    async fn header_by_number(&self, number: u64) -> Result<Header, LightClientError> {
+        let head = self.head().await?;
+        if number > head.number {
+            return Err(LightClientError::AfterCheckpoint { number, checkpoint: head.number })
+        }
+        let mut depth_checked = false;
+        loop {
+            let (checkpoint, closest) = {
+                let trusted = self.trusted.lock().unwrap();
+                let closest = trusted.headers.range(number..).next().map(|(_, header)| *header);
+                (trusted.checkpoint, closest.unwrap_or(head))
+            };
+            if closest.number == number {
+                return Ok(closest)
+            }
+            if !depth_checked {
+                if closest.number - number > self.max_depth {
+                    return Err(LightClientError::TooDeep { number, checkpoint: head.number })
+                }
+                depth_checked = true;
+            }
+            let parent = self.fetch_header(closest.parent_hash).await?;
+            if parent.number + 1 != closest.number {
+                return Err(LightClientError::BlockNotFound(closest.parent_hash))
+            }
+            self.insert(checkpoint, parent);
+        }
+    }
+
+    /// Returns the verified header of the ancestor of the checkpoint with the given hash
+    async fn header_by_hash(&self, hash: H256) -> Result<Header, LightClientError> {
+        let checkpoint = self.checkpoint();
+        let header = self.fetch_header(hash).await?;
+        let head = self.head().await?;
+        if header.number >= head.number {
+            if header.number == head.number && header.hash == head.hash {
+                return Ok(head)
+            }
+            return Err(LightClientError::NotAncestor(hash))
+        }
+        // the header is an ancestor if the verified header after it links to it
+        let child = self.header_by_number(header.number + 1).await?;
+        if child.parent_hash != hash {
+            return Err(LightClientError::NotAncestor(hash))
+        }
+        self.insert(checkpoint, header);
+        Ok(header)
+    }
+
+    /// Returns the verified header of the given block
+    async fn header(&self, block: Option<BlockId>) -> Result<Header, LightClientError> {
+        match block.unwrap_or_else(|| BlockNumber::Latest.into()) {
+            BlockId::Hash(hash) => {
+                let known = {
+                    let trusted = self.trusted.lock().unwrap();
+                    trusted.headers.values().find(|header| header.hash == hash).copied()
+                };
+                match known {
+                    Some(header) => Ok(header),
+                    None => self.header_by_hash(hash).await,
+                }
+            }
+            BlockId::Number(BlockNumber::Number(number)) => {
+                self.header_by_number(number.as_u64()).await
+            }
+            BlockId::Number(BlockNumber::Earliest) => self.header_by_number(0).await,
+            BlockId::Number(_) => self.head().await,
+        }
+    }
+
+    /// Fetches and verifies the proof of the account and the given storage slots
+    async fn proof(
+        &self,
+        address: Address,
+        keys: Vec<H256>,
+        header: &Header,
+    ) -> Result<EIP1186ProofResponse, LightClientError> {
+        let proof: EIP1186ProofResponse = self
+            .inner_request("eth_getProof", (address, &keys, BlockId::Hash(header.hash)))
+            .await?;
+        if proof.address != address ||
+            proof.storage_proof.iter().map(|proof| proof.key).ne(keys.iter().copied())
+        {
+            return Err(LightClientError::ProofMismatch)
+        }
+        proof.verify(header.state_root)?;
+        Ok(proof)
+    }
+
+    /// Serves a request that is verified
+    async fn verified(&self, method: &str, params: Value) -> Result<Value, LightClientError> {
+        let result = match method {
+            "eth_blockNumber" => serde_json::to_value(U64::from(self.head().await?.number))?,
+            "eth_getBalance" | "eth_getTransactionCount" => {
+                let header = self.header(param(&params, 1)?).await?;
+                let proof = self.proof(required(&params, 0)?, vec![], &header).await?;
+                if method == "eth_getBalance" {
+                    serde_json::to_value(proof.balance)?
+                } else {
+                    serde_json::to_value(proof.nonce)?
+                }
+            }
+            "eth_getStorageAt" => {
+                let header = self.header(param(&params, 2)?).await?;
+                let slot: U256 = required(&params, 1)?;
+                let mut key = H256::zero();
+                slot.to_big_endian(key.as_bytes_mut());
+                let proof = self.proof(required(&params, 0)?, vec![key], &header).await?;
+                let mut value = H256::zero();
+                proof.storage_proof[0].value.to_big_endian(value.as_bytes_mut());
+                serde_json::to_value(value)?
+            }
+            "eth_getCode" => {
+                let header = self.header(param(&params, 1)?).await?;
+                let address: Address = required(&params, 0)?;
+                let proof = self.proof(address, vec![], &header).await?;
+                let code: Bytes = self
+                    .inner_request("eth_getCode", (address, BlockId::Hash(header.hash)))
+                    .await?;
+                if H256(keccak256(&code)) != proof.code_hash {
+                    return Err(LightClientError::ProofMismatch)
+                }
+                serde_json::to_value(code)?
+            }
+            "eth_getProof" => {
+                let header = self.header(param(&params, 2)?).await?;
+                let proof = self
+                    .proof(required(&params, 0)?, param(&params, 1)?.unwrap_or_default(), &header)
+                    .await?;
+                serde_json::to_value(proof)?
+            }
+            "eth_call" => {
+                let header = self.header(param(&params, 1)?).await?;
+                let tx: Value = required(&params, 0)?;
+                self.inner_request("eth_call", (tx, BlockId::Hash(header.hash))).await?
+            }
+            _ => return Err(LightClientError::UnverifiedMethod(method.to_string())),
+        };
+        Ok(result)
+    }
+}
+
+/// Returns the param at `index`, `None` if it's missing or `null`
+fn param<T: DeserializeOwned>(params: &Value, index: usize) -> Result<Option<T>, LightClientError> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => Ok(Some(serde_json::from_value(value.clone())?)),
+    }
+}
+
+/// Returns the param at `index`, which must be present
+fn required<T: DeserializeOwned>(params: &Value, index: usize) -> Result<T, LightClientError> {
+    param(params, index)?.ok_or(LightClientError::MissingParam(index))
+}
+
+/// Error thrown by the [`LightClient`]
+#[derive(Error, Debug)]
+pub enum LightClientError {
+    /// The inner client failed
+    #[error(transparent)]
+    ProviderError(ProviderError),
+    /// Thrown if the params or the result of a request could not be (de)serialized
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+    /// The node doesn't know a block that is needed for the verification
+    #[error("block {0:?} not found")]
+    BlockNotFound(H256),
+    /// The fields of a header returned by the node don't hash to the expected hash
+    #[error("header of block {expected:?} hashes to {actual:?}")]
+    HeaderMismatch {
+        /// The hash the header was requested or referenced by
+        expected: H256,
+        /// The hash of the fields of the header
+        actual: H256,
+    },
+    /// A block that is not the checkpoint or one of its ancestors was requested by hash
+    #[error("block {0:?} is not an ancestor of the checkpoint")]
+    NotAncestor(H256),
+    /// A block after the checkpoint was requested, which can't be verified
+    #[error("block {number} is after the checkpoint {checkpoint}")]
+    AfterCheckpoint {
+        /// The number of the requested block
+        number: u64,
+        /// The number of the checkpoint
+        checkpoint: u64,
+    },
+    /// A block more than [`LightClient::max_depth`] blocks before the closest verified header was
+    /// requested
+    #[error("block {number} is too far before the checkpoint {checkpoint}")]
+    TooDeep {
+        /// The number of the requested block
+        number: u64,
+        /// The number of the checkpoint
+        checkpoint: u64,
+    },
+    /// A Merkle proof returned by the node is invalid
+    #[error(transparent)]
+    Proof(#[from] ProofError),
+    /// The node returned a proof or code for something else than what was requested
+    #[error("the proof does not match the request")]
+    ProofMismatch,
+    /// A required param of the request is missing
+    #[error("missing param {0}")]
+    MissingParam(usize),
+    /// A method that the client doesn't verify was passed to the verification
+    #[error("method {0} is not verified")]
+    UnverifiedMethod(String),
+}
+
+impl From<LightClientError> for ProviderError {
+    fn from(src: LightClientError) -> Self {
+        match src {
+            LightClientError::ProviderError(err) => err,
+            LightClientError::SerdeJson(err) => err.into(),
+            _ => ProviderError::JsonRpcClientError(Box::new(src)),
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for LightClient<C>
+where
+    C: JsonRpcClient,
+{
+    type Error = LightClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match method {
+            "eth_blockNumber" |
+            "eth_getBalance" |
+            "eth_getTransactionCount" |
+            "eth_getStorageAt" |
+            "eth_getCode" |
+            "eth_getProof" |
+            "eth_call" => {
+                let params = serde_json::to_value(params)?;
+                let result = self.verified(method, params).await?;
+                Ok(serde_json::from_value(result)?)
+            }
+            _ => self.inner_request(method, params).await,
+        }
+    }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.inner.as_pubsub()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "celo"))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, Provider};
+    use ethers_core::utils::rlp::RlpStream;
+
+    /// Returns a post-London header with the given fields and its hash
+    fn block(number: u64, parent_hash: H256, state_root: H256) -> Block<TxHash> {
+        let mut block = Block {
+            number: Some(number.into()),
+            parent_hash,
+            state_root,
+            base_fee_per_gas: Some(7.into()),
+            ..Default::default()
+        };
+        block.hash = Some(block.header_hash());
+        block
+    }
+
+    /// A trie with the single account `address`, its state root and the proof of the account
+    fn account(address: Address, balance: U256) -> (H256, EIP1186ProofResponse) {
+        let proof = EIP1186ProofResponse {
+            address,
+            balance,
+            nonce: 1.into(),
+            code_hash: H256(keccak256([])),
+            storage_hash: "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+                .parse()
+                .unwrap(),
+            ..Default::default()
+        };
+        let mut value = RlpStream::new_list(4);
+        value.append(&proof.nonce);
+        value.append(&proof.balance);
+        value.append(&proof.storage_hash);
+        value.append(&proof.code_hash);
+        // a leaf with the whole path, which is even
+        let mut path = vec![0x20];
+        path.extend_from_slice(&keccak256(address));
+        let mut leaf = RlpStream::new_list(2);
+        leaf.append(&path);
+        leaf.append(&value.out().to_vec());
+        let leaf = leaf.out().to_vec();
+        let root = H256(keccak256(&leaf));
+        (root, EIP1186ProofResponse { account_proof: vec![leaf.into()], ..proof })
+    }
+
+    #[tokio::test]
+    async fn verifies_balances() {
+        let address = Address::repeat_byte(1);
+        let (state_root, proof) = account(address, 100.into());
+        let parent = block(9, H256::repeat_byte(9), state_root);
+        let head = block(10, parent.hash.unwrap(), H256::repeat_byte(3));
+
+        let mock = MockProvider::new();
+        mock.push(proof.clone()).unwrap();
+        mock.push(parent.clone()).unwrap();
+        mock.push(head.clone()).unwrap();
+        let provider = Provider::new(LightClient::new(mock, head.hash.unwrap()));
+
+        let balance = provider.get_balance(address, Some(9u64.into())).await.unwrap();
+        assert_eq!(balance, 100.into());
+        assert_eq!(provider.get_block_number().await.unwrap(), 10.into());
+
+        let mock = provider.as_ref().inner();
+        mock.assert_request("eth_getBlockByHash", (head.hash.unwrap(), false)).unwrap();
+        mock.assert_request("eth_getBlockByHash", (parent.hash.unwrap(), false)).unwrap();
+        mock.assert_request(
+            "eth_getProof",
+            (address, Vec::<H256>::new(), BlockId::Hash(parent.hash.unwrap())),
+        )
+        .unwrap();
+
+        // a node lying about the balance is caught
+        let (_, lie) = account(address, 1_000.into());
+        mock.push(lie).unwrap();
+        let err = provider.get_balance(address, Some(9u64.into())).await.unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn verifies_ancestors_by_hash() {
+        let address = Address::repeat_byte(1);
+        let (state_root, proof) = account(address, 100.into());
+        let parent = block(9, H256::repeat_byte(9), state_root);
+        let head = block(10, parent.hash.unwrap(), H256::repeat_byte(3));
+
+        let mock = MockProvider::new();
+        mock.push(proof).unwrap();
+        mock.push(head.clone()).unwrap();
+        mock.push(parent.clone()).unwrap();
+        let provider = Provider::new(LightClient::new(mock, head.hash.unwrap()));
+
+        let at = BlockId::Hash(parent.hash.unwrap());
+        let balance = provider.get_balance(address, Some(at)).await.unwrap();
+        assert_eq!(balance, 100.into());
+
+        // a well-formed header that the checkpoint doesn't descend from is rejected
+        let (state_root, _) = account(address, 1_000.into());
+        for other in
+            [block(9, H256::repeat_byte(8), state_root), block(10, H256::zero(), state_root)]
+        {
+            provider.as_ref().inner().push(other.clone()).unwrap();
+            let at = BlockId::Hash(other.hash.unwrap());
+            let err = provider.get_balance(address, Some(at)).await.unwrap_err();
+            assert!(err.to_string().contains("not an ancestor"), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_forged_headers() {
+        let head = block(10, H256::repeat_byte(9), H256::repeat_byte(3));
+        let forged = Block { state_root: H256::repeat_byte(4), ..head.clone() };
+
+        let mock = MockProvider::new();
+        mock.push(forged).unwrap();
+        let provider = Provider::new(LightClient::new(mock, head.hash.unwrap()));
+        let err = provider.get_balance(Address::zero(), None).await.unwrap_err();
+        assert!(err.to_string().contains("hashes to"), "{err}");
+
+        // blocks after the checkpoint can't be verified
+        provider.as_ref().inner().push(head).unwrap();
+        let err = provider.get_balance(Address::zero(), Some(11u64.into())).await.unwrap_err();
+        assert!(err.to_string().contains("after the checkpoint"), "{err}");
+    }
+}
@@ -1,11 +1,16 @@
-use crate::{JsonRpcClient, ProviderError};
+use crate::{JsonRpcClient, JsonRpcError, ProviderError};
 use async_trait::async_trait;
+use futures_timer::Delay;
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 use std::{
     borrow::Borrow,
     collections::VecDeque,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
 use thiserror::Error;
 
@@ -20,9 +25,47 @@ enum MockParams {
 
 #[derive(Clone, Debug)]
 /// Mock transport used in test environments.
+///
+/// Responses can either be pushed to a queue with [`MockProvider::push`], which are popped in LIFO
+/// order regardless of the request, or stubbed for matching requests with
+/// [`MockProvider::expect`]. Stubs take precedence over the queue. Both can also answer with a
+/// JSON-RPC error, see [`MockProvider::push_error`] and [`MockExpectation::returns_error`].
 pub struct MockProvider {
     requests: Arc<Mutex<VecDeque<(String, MockParams)>>>,
-    responses: Arc<Mutex<VecDeque<Value>>>,
+    responses: Arc<Mutex<VecDeque<Result<Value, JsonRpcError>>>>,
+    expectations: Arc<Mutex<Vec<Stub>>>,
+}
+
+/// A stubbed response for matching requests
+#[derive(Debug)]
+struct Stub {
+    method: String,
+    /// The params to match, any params match if `None`
+    params: Option<Value>,
+    response: Result<Value, JsonRpcError>,
+    /// How long to wait before responding
+    delay: Option<Duration>,
+    /// How often the stub is expected to be called, at least once if `None`
+    times: Option<usize>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Stub {
+    fn matches(&self, method: &str, params: &MockParams) -> bool {
+        if self.method != method {
+            return false
+        }
+        if let Some(times) = self.times {
+            if self.calls.load(Ordering::SeqCst) >= times {
+                return false
+            }
+        }
+        match (&self.params, params) {
+            (None, _) => true,
+            (Some(expected), MockParams::Value(params)) => expected == params,
+            (Some(_), MockParams::Zst) => false,
+        }
+    }
 }
 
 impl Default for MockProvider {
@@ -48,10 +91,23 @@ impl JsonRpcClient for MockProvider {
         } else {
             MockParams::Value(serde_json::to_value(params)?)
         };
+        let stubbed = self.expectations.lock().unwrap().iter().find_map(|stub| {
+            stub.matches(method, &params).then(|| {
+                stub.calls.fetch_add(1, Ordering::SeqCst);
+                (stub.response.clone(), stub.delay)
+            })
+        });
         self.requests.lock().unwrap().push_back((method.to_owned(), params));
-        let mut data = self.responses.lock().unwrap();
-        let element = data.pop_back().ok_or(MockError::EmptyResponses)?;
-        let res: R = serde_json::from_value(element)?;
+        let element = match stubbed {
+            Some((element, delay)) => {
+                if let Some(delay) = delay {
+                    Delay::new(delay).await;
+                }
+                element
+            }
+            None => self.responses.lock().unwrap().pop_back().ok_or(MockError::EmptyResponses)?,
+        };
+        let res: R = serde_json::from_value(element.map_err(MockError::JsonRpcError)?)?;
 
         Ok(res)
     }
@@ -83,15 +139,169 @@ impl MockProvider {
         Self {
             requests: Arc::new(Mutex::new(VecDeque::new())),
             responses: Arc::new(Mutex::new(VecDeque::new())),
+            expectations: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
-    /// Pushes the data to the responses
+    /// Stubs the response for requests to `method`
+    ///
+    /// Requests are matched against the stubs in the order the stubs were added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_core::types::U64;
+    /// use ethers_providers::{JsonRpcClient, MockProvider};
+    ///
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mock = MockProvider::new();
+    /// let balance = mock
+    ///     .expect("eth_getBalance")
+    ///     .with_params(("0x0000000000000000000000000000000000000000", "latest"))
+    ///     .times(1)
+    ///     .returns(U64::from(100))?;
+    ///
+    /// let res: U64 = mock
+    ///     .request("eth_getBalance", ("0x0000000000000000000000000000000000000000", "latest"))
+    ///     .await?;
+    /// assert_eq!(res, U64::from(100));
+    /// assert_eq!(balance.calls(), 1);
+    /// mock.verify()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn expect(&self, method: impl Into<String>) -> MockExpectation<'_> {
+        MockExpectation {
+            mock: self,
+            method: method.into(),
+            params: None,
+            times: None,
+            delay: None,
+        }
+    }
+
+    /// Checks that every stub added with [`MockProvider::expect`] was called as often as
+    /// expected
+    pub fn verify(&self) -> Result<(), MockError> {
+        for stub in self.expectations.lock().unwrap().iter() {
+            let calls = stub.calls.load(Ordering::SeqCst);
+            let met = match stub.times {
+                Some(times) => calls == times,
+                None => calls > 0,
+            };
+            if !met {
+                return Err(MockError::UnmetExpectation {
+                    method: stub.method.clone(),
+                    expected: stub.times,
+                    calls,
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes the data to the responses.
+    ///
+    /// The last pushed response answers the next request, so the responses to a sequence of
+    /// requests are pushed in reverse order.
     pub fn push<T: Serialize + Send + Sync, K: Borrow<T>>(&self, data: K) -> Result<(), MockError> {
         let value = serde_json::to_value(data.borrow())?;
-        self.responses.lock().unwrap().push_back(value);
+        self.responses.lock().unwrap().push_back(Ok(value));
         Ok(())
     }
+
+    /// Pushes an error to the responses, which is returned like an error response of the node
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ethers_providers::{JsonRpcError, Middleware, Provider};
+    ///
+    /// # async fn foo() {
+    /// let (provider, mock) = Provider::mocked();
+    /// mock.push_error(JsonRpcError { code: -32000, message: "nonce too low".into(), data: None });
+    ///
+    /// let err = provider.get_block_number().await.unwrap_err();
+    /// assert_eq!(err.as_error_response().unwrap().message, "nonce too low");
+    /// # }
+    /// ```
+    pub fn push_error(&self, err: JsonRpcError) {
+        self.responses.lock().unwrap().push_back(Err(err));
+    }
+}
+
+/// Builder for a stubbed response of the [`MockProvider`], see [`MockProvider::expect`]
+#[derive(Debug)]
+#[must_use = "expectations do nothing unless `returns` is called"]
+pub struct MockExpectation<'a> {
+    mock: &'a MockProvider,
+    method: String,
+    params: Option<Result<Value, serde_json::Error>>,
+    times: Option<usize>,
+    delay: Option<Duration>,
+}
+
+impl<'a> MockExpectation<'a> {
+    /// Only matches requests with these params
+    pub fn with_params<T: Serialize>(mut self, params: T) -> Self {
+        self.params = Some(serde_json::to_value(params));
+        self
+    }
+
+    /// Sets how often the stub is expected to be called
+    ///
+    /// The stub no longer matches once it was called `times` times.
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// Delays the response of matching requests, e.g. to test timeouts
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Adds the stub, returning `response` for all matching requests
+    pub fn returns<T: Serialize>(self, response: T) -> Result<MockExpectationHandle, MockError> {
+        let response = serde_json::to_value(response)?;
+        self.stub(Ok(response))
+    }
+
+    /// Adds the stub, failing all matching requests with the error response `err`
+    pub fn returns_error(self, err: JsonRpcError) -> Result<MockExpectationHandle, MockError> {
+        self.stub(Err(err))
+    }
+
+    fn stub(
+        self,
+        response: Result<Value, JsonRpcError>,
+    ) -> Result<MockExpectationHandle, MockError> {
+        let params = self.params.transpose()?;
+        let calls = Arc::new(AtomicUsize::new(0));
+        self.mock.expectations.lock().unwrap().push(Stub {
+            method: self.method,
+            params,
+            response,
+            delay: self.delay,
+            times: self.times,
+            calls: calls.clone(),
+        });
+        Ok(MockExpectationHandle { calls })
+    }
+}
+
+/// Tracks the calls of a stub added with [`MockProvider::expect`]
+#[derive(Debug, Clone)]
+pub struct MockExpectationHandle {
+    calls: Arc<AtomicUsize>,
+}
+
+impl MockExpectationHandle {
+    /// Returns how often the stub was called
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
 }
 
 #[derive(Error, Debug)]
@@ -105,6 +315,17 @@ pub enum MockError {
 
     #[error("empty responses array, please push some responses")]
     EmptyResponses,
+
+    #[error(transparent)]
+    /// An error response pushed with [`MockProvider::push_error`] or
+    /// [`MockExpectation::returns_error`]
+    JsonRpcError(JsonRpcError),
+
+    #[error(
+        "expected `{method}` to be called {}, but it was called {calls} times",
+        expected.map(|times| format!("{times} times")).unwrap_or_else(|| "at least once".to_string())
+    )]
+    UnmetExpectation { method: String, expected: Option<usize>, calls: usize },
 }
 
 impl From<MockError> for ProviderError {
@@ -113,12 +334,84 @@ impl From<MockError> for ProviderError {
     }
 }
 
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use pubsub::MockPubsub;
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod pubsub {
+    use super::*;
+    use crate::{PubsubClient, PubsubClientWrapper};
+    use ethers_core::types::U256;
+    use futures_util::stream::{self, Iter};
+    use serde_json::value::RawValue;
+    use std::{collections::HashMap, fmt::Debug, vec::IntoIter};
+
+    /// A pubsub client of the tests, which answers requests with its [`MockProvider`] and yields
+    /// the notifications queued for a subscription id when it is subscribed to
+    #[derive(Debug, Clone, Default)]
+    pub(crate) struct MockPubsub {
+        pub mock: MockProvider,
+        notifications: Arc<Mutex<HashMap<U256, Vec<String>>>>,
+        /// The ids of the removed subscriptions, in order
+        pub unsubscribed: Arc<Mutex<Vec<U256>>>,
+    }
+
+    impl MockPubsub {
+        /// Queues the notification for the subscription with the id
+        pub fn notify<T: Serialize>(&self, id: impl Into<U256>, notification: T) {
+            let notification = serde_json::to_string(&notification).unwrap();
+            self.notifications.lock().unwrap().entry(id.into()).or_default().push(notification);
+        }
+    }
+
+    #[async_trait]
+    impl JsonRpcClient for MockPubsub {
+        type Error = MockError;
+
+        async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+        where
+            T: Debug + Serialize + Send + Sync,
+            R: DeserializeOwned + Send,
+        {
+            self.mock.request(method, params).await
+        }
+
+        fn as_pubsub(&self) -> Option<&dyn PubsubClientWrapper> {
+            Some(self)
+        }
+    }
+
+    impl PubsubClient for MockPubsub {
+        type NotificationStream = Iter<IntoIter<Box<RawValue>>>;
+
+        fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, MockError> {
+            let notifications = self
+                .notifications
+                .lock()
+                .unwrap()
+                .remove(&id.into())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|notification| RawValue::from_string(notification).unwrap())
+                .collect::<Vec<_>>();
+            Ok(stream::iter(notifications))
+        }
+
+        fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), MockError> {
+            self.unsubscribed.lock().unwrap().push(id.into());
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg(not(target_arch = "wasm32"))]
 mod tests {
     use super::*;
     use crate::Middleware;
-    use ethers_core::types::U64;
+    use ethers_core::types::{Address, U256, U64};
 
     #[tokio::test]
     async fn pushes_request_and_response() {
@@ -159,4 +452,85 @@ mod tests {
         let block = provider.get_block_number().await.unwrap();
         assert_eq!(block.as_u64(), 12);
     }
+
+    #[tokio::test]
+    async fn stubs_matching_requests() {
+        let (provider, mock) = crate::Provider::mocked();
+        let block = mock.expect("eth_blockNumber").returns(U64::from(12)).unwrap();
+        let latest = mock
+            .expect("eth_getBalance")
+            .with_params((Address::zero(), "latest"))
+            .times(2)
+            .returns(U256::from(100))
+            .unwrap();
+        let historic = mock
+            .expect("eth_getBalance")
+            .with_params((Address::zero(), "0x1"))
+            .returns(U256::from(1))
+            .unwrap();
+
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 12);
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 12);
+        let balance = provider.get_balance(Address::zero(), None).await.unwrap();
+        assert_eq!(balance, U256::from(100));
+        let balance = provider.get_balance(Address::zero(), Some(1u64.into())).await.unwrap();
+        assert_eq!(balance, U256::from(1));
+
+        assert_eq!(block.calls(), 2);
+        assert_eq!(latest.calls(), 1);
+        assert_eq!(historic.calls(), 1);
+        let err = mock.verify().unwrap_err();
+        assert!(matches!(err, MockError::UnmetExpectation { expected: Some(2), calls: 1, .. }));
+
+        let _ = provider.get_balance(Address::zero(), None).await.unwrap();
+        mock.verify().unwrap();
+
+        // exhausted stubs no longer match and fall back to the queued responses
+        mock.push(U256::from(42)).unwrap();
+        let balance = provider.get_balance(Address::zero(), None).await.unwrap();
+        assert_eq!(balance, U256::from(42));
+        assert_eq!(latest.calls(), 2);
+
+        // stubbed requests are recorded as well
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn unmatched_stubs_fail_verification() {
+        let mock = MockProvider::new();
+        mock.expect("eth_chainId").returns(U64::from(1)).unwrap();
+        let err = mock.verify().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected `eth_chainId` to be called at least once, but it was called 0 times"
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_error_responses() {
+        let (provider, mock) = crate::Provider::mocked();
+        let err =
+            |message: &str| JsonRpcError { code: -32000, message: message.into(), data: None };
+        mock.expect("eth_chainId").returns_error(err("stubbed")).unwrap();
+        mock.push(U64::from(1)).unwrap();
+        mock.push_error(err("queued"));
+
+        let res = provider.get_chainid().await.unwrap_err();
+        assert_eq!(res.as_error_response().unwrap().message, "stubbed");
+        let res = provider.get_block_number().await.unwrap_err();
+        assert_eq!(res.as_error_response().unwrap().message, "queued");
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+    }
+
+    #[tokio::test]
+    async fn delays_stubbed_responses() {
+        let (provider, mock) = crate::Provider::mocked();
+        mock.expect("eth_blockNumber")
+            .delay(Duration::from_millis(50))
+            .returns(U64::from(1))
+            .unwrap();
+        let provider = provider.request_timeout(Duration::from_millis(10));
+        let err = provider.get_block_number().await.unwrap_err();
+        assert!(matches!(err, ProviderError::Timeout(_)));
+    }
 }
@@ -1,8 +1,16 @@
 mod common;
-pub use common::Authorization;
+pub use common::{Authorization, JsonRpcError, JwtError, JwtSecret};
 
 mod http;
-pub use self::http::{ClientError as HttpClientError, Provider as Http};
+pub use self::http::{
+    BatchRequest, BatchResponse, Builder as HttpBuilder, ClientError as HttpClientError,
+    FlashbotsSigner, Provider as Http,
+};
+
+#[cfg(all(feature = "uds", unix))]
+mod http_uds;
+#[cfg(all(feature = "uds", unix))]
+pub use http_uds::{HttpUds, HttpUdsError};
 
 #[cfg(all(feature = "ipc", any(unix, windows)))]
 mod ipc;
@@ -12,16 +20,52 @@ pub use ipc::{Ipc, IpcError};
 #[cfg(feature = "ws")]
 mod ws;
 #[cfg(feature = "ws")]
-pub use ws::{ClientError as WsClientError, Ws};
+pub(crate) use ws::report_lag;
+#[cfg(feature = "ws")]
+pub use ws::{
+    BufferPolicy, ClientError as WsClientError, Lagged, ReconnectEvent, Ws, WsSubscription,
+};
+
+#[cfg(all(feature = "light-client", not(feature = "celo")))]
+mod light_client;
+#[cfg(all(feature = "light-client", not(feature = "celo")))]
+pub use light_client::{LightClient, LightClientError};
+
+mod graphql;
+pub use graphql::{Graphql, GraphqlError};
 
 mod quorum;
-pub use quorum::{JsonRpcClientWrapper, Quorum, QuorumError, QuorumProvider, WeightedProvider};
+pub use quorum::{
+    JsonRpcClientWrapper, PubsubClientWrapper, Quorum, QuorumError, QuorumProvider,
+    WeightedProvider,
+};
 
 mod rw;
 pub use rw::{RwClient, RwClientError};
 
+mod routing;
+pub use routing::{RoutingClient, RoutingClientBuilder};
+
+mod fallback;
+pub use fallback::{FallbackProvider, FallbackProviderBuilder, DEFAULT_HEALTH_CHECK_INTERVAL};
+
+mod singleflight;
+pub use singleflight::{SharedError, SingleflightClient, DEDUPLICATED};
+
+mod rate_limit;
+pub use rate_limit::{RateLimitedClient, ALCHEMY_COMPUTE_UNITS};
+
+mod traced;
+pub use traced::{
+    redact_secrets, Payload, RedactHook, TracingClient, REDACTED, SECRET_PARAMS_METHODS,
+    SECRET_RESPONSE_METHODS,
+};
+
 mod retry;
 pub use retry::*;
 
 mod mock;
-pub use mock::{MockError, MockProvider};
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use mock::MockPubsub;
+pub use mock::{MockError, MockExpectation, MockExpectationHandle, MockProvider};
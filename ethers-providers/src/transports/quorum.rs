@@ -6,9 +6,14 @@ use futures_util::{future::join_all, FutureExt, StreamExt};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{value::RawValue, Value};
 use std::{
+    collections::{HashMap, HashSet, VecDeque},
     fmt::Debug,
     future::Future,
     pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     task::{Context, Poll},
 };
 use thiserror::Error;
@@ -70,6 +75,36 @@ use thiserror::Error;
 /// # Ok(())
 /// # }
 /// ```
+///
+/// # Subscriptions
+///
+/// If the inner providers are [`PubsubClient`]s, the `QuorumProvider` is one as well, and
+/// [`JsonRpcClient::as_pubsub`] returns it once all type-erased providers support subscriptions.
+/// `eth_subscribe` is sent to every provider and succeeds once the providers that accepted the
+/// subscription reach the quorum weight. Since every node hands out its own subscription id, the
+/// caller receives a local id that the `QuorumProvider` maps to the id of each provider.
+/// Notifications are only yielded once the weight of the providers that sent the same
+/// notification reaches the quorum, e.g. to require 2-of-3 agreement on new heads:
+///
+/// ```no_run
+/// use ethers_providers::{Middleware, Provider, Quorum, QuorumProvider, WeightedProvider, Ws};
+/// use futures_util::StreamExt;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let quorum = QuorumProvider::builder()
+///     .add_provider(WeightedProvider::new(Ws::connect("ws://localhost:8545").await?))
+///     .add_provider(WeightedProvider::new(Ws::connect("ws://localhost:8546").await?))
+///     .add_provider(WeightedProvider::new(Ws::connect("ws://localhost:8547").await?))
+///     .quorum(Quorum::ProviderCount(2))
+///     .build();
+/// let provider = Provider::quorum(quorum);
+/// let mut blocks = provider.subscribe_blocks().await?;
+/// while let Some(block) = blocks.next().await {
+///     println!("{:?}", block.hash);
+/// }
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug, Clone)]
 pub struct QuorumProvider<T = Box<dyn JsonRpcClientWrapper>> {
     /// What kind of quorum is required
@@ -78,6 +113,34 @@ pub struct QuorumProvider<T = Box<dyn JsonRpcClientWrapper>> {
     quorum_weight: u64,
     /// All the internal providers this providers runs
     providers: Vec<WeightedProvider<T>>,
+    /// Subscriptions handed out by this provider
+    subscriptions: Arc<Subscriptions>,
+}
+
+/// Maps the subscription ids returned by the `QuorumProvider` to the ids of the individual
+/// providers.
+#[derive(Debug, Default)]
+struct Subscriptions {
+    /// The next local subscription id
+    next_id: AtomicU64,
+    /// Local subscription id -> `(provider index, provider subscription id)`
+    ids: Mutex<HashMap<U256, Vec<(usize, U256)>>>,
+}
+
+impl Subscriptions {
+    fn insert(&self, ids: Vec<(usize, U256)>) -> U256 {
+        let id = U256::from(self.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+        self.ids.lock().unwrap().insert(id, ids);
+        id
+    }
+
+    fn get(&self, id: &U256) -> Option<Vec<(usize, U256)>> {
+        self.ids.lock().unwrap().get(id).cloned()
+    }
+
+    fn remove(&self, id: &U256) -> Option<Vec<(usize, U256)>> {
+        self.ids.lock().unwrap().remove(id)
+    }
 }
 
 impl QuorumProvider<Box<dyn JsonRpcClientWrapper>> {
@@ -154,7 +217,12 @@ impl<T> QuorumProviderBuilder<T> {
 
     pub fn build(self) -> QuorumProvider<T> {
         let quorum_weight = self.quorum.weight(&self.providers);
-        QuorumProvider { quorum: self.quorum, quorum_weight, providers: self.providers }
+        QuorumProvider {
+            quorum: self.quorum,
+            quorum_weight,
+            providers: self.providers,
+            subscriptions: Default::default(),
+        }
     }
 }
 
@@ -211,6 +279,56 @@ impl<T: JsonRpcClientWrapper> QuorumProvider<T> {
             _ => {}
         }
     }
+
+    /// Sends `eth_subscribe` to all providers and returns the local subscription id if the
+    /// providers that accepted the subscription reached the quorum.
+    async fn subscribe_all(&self, params: QuorumParams) -> Result<U256, ProviderError> {
+        let responses = join_all(self.providers.iter().map(|provider| {
+            let params = params.clone();
+            async move {
+                let id = provider.inner.request("eth_subscribe", params).await?;
+                serde_json::from_value::<U256>(id).map_err(ProviderError::from)
+            }
+        }))
+        .await;
+
+        let mut ids = Vec::new();
+        let mut errors = Vec::new();
+        let mut weight = 0;
+        for (idx, res) in responses.into_iter().enumerate() {
+            match res {
+                Ok(id) => {
+                    weight += self.providers[idx].weight;
+                    ids.push((idx, id));
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+
+        if weight < self.quorum_weight {
+            // don't leak the subscriptions that were accepted
+            self.unsubscribe_all(&ids).await;
+            let values = ids.into_iter().filter_map(|(_, id)| serde_json::to_value(id).ok());
+            return Err(QuorumError::NoQuorumReached { values: values.collect(), errors }.into())
+        }
+
+        Ok(self.subscriptions.insert(ids))
+    }
+
+    /// Sends `eth_unsubscribe` for every provider's subscription id and returns the weight of
+    /// the providers that confirmed it.
+    async fn unsubscribe_all(&self, ids: &[(usize, U256)]) -> u64 {
+        join_all(ids.iter().map(|(idx, id)| async move {
+            let params = QuorumParams::Value(serde_json::json!([id]));
+            match self.providers[*idx].inner.request("eth_unsubscribe", params).await {
+                Ok(Value::Bool(true)) => self.providers[*idx].weight,
+                _ => 0,
+            }
+        }))
+        .await
+        .into_iter()
+        .sum()
+    }
 }
 
 /// Determines when the provider reached a quorum
@@ -365,10 +483,17 @@ impl From<QuorumError> for ProviderError {
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait JsonRpcClientWrapper: Send + Sync + Debug {
     async fn request(&self, method: &str, params: QuorumParams) -> Result<Value, ProviderError>;
+
+    /// Returns the client as a [`PubsubClientWrapper`] if it supports subscriptions, see
+    /// [`JsonRpcClient::as_pubsub`]
+    fn as_pubsub_wrapper(&self) -> Option<&dyn PubsubClientWrapper> {
+        None
+    }
 }
-type NotificationStream =
+pub(crate) type NotificationStream =
     Box<dyn futures_core::Stream<Item = Box<RawValue>> + Send + Unpin + 'static>;
 
+/// A type-erased [`PubsubClient`]
 pub trait PubsubClientWrapper: JsonRpcClientWrapper {
     /// Add a subscription to this transport
     fn subscribe(&self, id: U256) -> Result<NotificationStream, ProviderError>;
@@ -389,6 +514,10 @@ impl<C: JsonRpcClient> JsonRpcClientWrapper for C {
 
         Ok(fut.await.map_err(C::Error::into)?)
     }
+
+    fn as_pubsub_wrapper(&self) -> Option<&dyn PubsubClientWrapper> {
+        JsonRpcClient::as_pubsub(self)
+    }
 }
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -396,6 +525,10 @@ impl JsonRpcClientWrapper for Box<dyn JsonRpcClientWrapper> {
     async fn request(&self, method: &str, params: QuorumParams) -> Result<Value, ProviderError> {
         self.as_ref().request(method, params).await
     }
+
+    fn as_pubsub_wrapper(&self) -> Option<&dyn PubsubClientWrapper> {
+        self.as_ref().as_pubsub_wrapper()
+    }
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -404,6 +537,10 @@ impl JsonRpcClientWrapper for Box<dyn PubsubClientWrapper> {
     async fn request(&self, method: &str, params: QuorumParams) -> Result<Value, ProviderError> {
         self.as_ref().request(method, params).await
     }
+
+    fn as_pubsub_wrapper(&self) -> Option<&dyn PubsubClientWrapper> {
+        Some(self.as_ref())
+    }
 }
 
 impl<C: PubsubClient> PubsubClientWrapper for C
@@ -450,6 +587,28 @@ where
         };
         self.normalize_request(method, &mut params).await;
 
+        match method {
+            "eth_subscribe" => {
+                let id = self.subscribe_all(params).await?;
+                return Ok(serde_json::from_value(serde_json::to_value(id)?)?)
+            }
+            "eth_unsubscribe" => {
+                let ids = match &params {
+                    QuorumParams::Value(Value::Array(arr)) => arr
+                        .first()
+                        .and_then(|id| serde_json::from_value::<U256>(id.clone()).ok())
+                        .and_then(|id| self.subscriptions.get(&id)),
+                    _ => None,
+                };
+                // subscriptions that were not created by this provider are forwarded as is
+                if let Some(ids) = ids {
+                    let confirmed = self.unsubscribe_all(&ids).await >= self.quorum_weight;
+                    return Ok(serde_json::from_value(Value::Bool(confirmed))?)
+                }
+            }
+            _ => {}
+        }
+
         let requests = self
             .providers
             .iter()
@@ -464,29 +623,96 @@ where
         let value = QuorumRequest::new(self, requests).await?;
         Ok(serde_json::from_value(value)?)
     }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.providers
+            .iter()
+            .all(|provider| provider.inner.as_pubsub_wrapper().is_some())
+            .then_some(self as _)
+    }
 }
 
 // A stream that returns a value and the weight of its provider
 type WeightedNotificationStream =
     Pin<Box<dyn futures_core::Stream<Item = (Box<RawValue>, u64)> + Send + Unpin + 'static>>;
 
+/// The maximum number of notifications that are tracked while waiting for a quorum
+const MAX_TRACKED_NOTIFICATIONS: usize = 128;
+
 /// A Subscription stream that only yields the next value if the underlying
 /// providers reached quorum.
+///
+/// All provider streams are polled independently, so a provider that falls behind or stops
+/// sending notifications does not stall the stream as long as the others still reach the quorum.
+/// Every notification is yielded at most once.
 pub struct QuorumStream {
     // Weight required to reach quorum
     quorum_weight: u64,
     /// The different notifications with their cumulative weight
-    responses: Vec<(Box<RawValue>, u64)>,
-    /// All provider notification streams
-    active: Vec<WeightedNotificationStream>,
-    /// Provider streams that already yielded a new value and are waiting for
-    /// active to finish
-    benched: Vec<WeightedNotificationStream>,
+    responses: VecDeque<(Value, Box<RawValue>, u64)>,
+    /// Notifications that already reached quorum
+    yielded: HashSet<String>,
+    /// Order in which notifications were yielded, used to bound `yielded`
+    yielded_order: VecDeque<String>,
+    /// Notifications that reached quorum and are ready to be returned
+    ready: VecDeque<Box<RawValue>>,
+    /// All provider notification streams that did not finish yet
+    streams: Vec<WeightedNotificationStream>,
 }
 
 impl QuorumStream {
     fn new(quorum_weight: u64, notifications: Vec<WeightedNotificationStream>) -> Self {
-        Self { quorum_weight, responses: Vec::new(), active: notifications, benched: Vec::new() }
+        Self {
+            quorum_weight,
+            responses: VecDeque::new(),
+            yielded: HashSet::new(),
+            yielded_order: VecDeque::new(),
+            ready: VecDeque::new(),
+            streams: notifications,
+        }
+    }
+
+    /// Adds the weight of a notification and queues it once it reached the quorum
+    fn on_notification(&mut self, val: Box<RawValue>, response_weight: u64) {
+        // compare the parsed value so formatting differences between nodes don't matter
+        let parsed = serde_json::from_str::<Value>(val.get())
+            .unwrap_or_else(|_| Value::String(val.get().to_string()));
+        let key = parsed.to_string();
+        if self.yielded.contains(&key) {
+            return
+        }
+
+        let weight = if let Some(pos) = self.responses.iter().position(|(v, _, _)| v == &parsed) {
+            let weight = {
+                let (_, _, weight) = &mut self.responses[pos];
+                *weight += response_weight;
+                *weight
+            };
+            if weight >= self.quorum_weight {
+                self.responses.remove(pos);
+            }
+            weight
+        } else {
+            if response_weight < self.quorum_weight {
+                if self.responses.len() >= MAX_TRACKED_NOTIFICATIONS {
+                    self.responses.pop_front();
+                }
+                self.responses.push_back((parsed, val, response_weight));
+                return
+            }
+            response_weight
+        };
+
+        if weight >= self.quorum_weight {
+            if self.yielded_order.len() >= MAX_TRACKED_NOTIFICATIONS {
+                if let Some(old) = self.yielded_order.pop_front() {
+                    self.yielded.remove(&old);
+                }
+            }
+            self.yielded.insert(key.clone());
+            self.yielded_order.push_back(key);
+            self.ready.push_back(val);
+        }
     }
 }
 
@@ -496,44 +722,26 @@ impl Stream for QuorumStream {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
 
-        if this.active.is_empty() {
-            std::mem::swap(&mut this.active, &mut this.benched);
-        }
-
-        for n in (0..this.active.len()).rev() {
-            let mut stream = this.active.swap_remove(n);
-
-            match stream.poll_next_unpin(cx) {
-                Poll::Ready(Some((val, response_weight))) => {
-                    if let Some((_, weight)) =
-                        this.responses.iter_mut().find(|(v, _)| val.get() == v.get())
-                    {
-                        *weight += response_weight;
-                        if *weight >= this.quorum_weight {
-                            // reached quorum with multiple notification
-                            this.benched.push(stream);
-                            return Poll::Ready(Some(val))
-                        } else {
-                            this.responses.push((val, response_weight));
-                        }
-                    } else if response_weight >= this.quorum_weight {
-                        // reached quorum with single notification
-                        this.benched.push(stream);
-                        return Poll::Ready(Some(val))
-                    } else {
-                        this.responses.push((val, response_weight));
+        for n in (0..this.streams.len()).rev() {
+            loop {
+                match this.streams[n].poll_next_unpin(cx) {
+                    Poll::Ready(Some((val, weight))) => this.on_notification(val, weight),
+                    Poll::Ready(None) => {
+                        drop(this.streams.swap_remove(n));
+                        break
                     }
-
-                    this.benched.push(stream);
-                }
-                Poll::Ready(None) => {}
-                _ => {
-                    this.active.push(stream);
+                    Poll::Pending => break,
                 }
             }
+            if !this.ready.is_empty() {
+                break
+            }
         }
 
-        if this.active.is_empty() && this.benched.is_empty() {
+        if let Some(val) = this.ready.pop_front() {
+            return Poll::Ready(Some(val))
+        }
+        if this.streams.is_empty() {
             return Poll::Ready(None)
         }
         Poll::Pending
@@ -542,16 +750,21 @@ impl Stream for QuorumStream {
 
 impl<C> PubsubClient for QuorumProvider<C>
 where
-    C: PubsubClientWrapper,
+    C: JsonRpcClientWrapper,
 {
     type NotificationStream = QuorumStream;
 
     fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
         let id = id.into();
-        let mut notifications = Vec::with_capacity(self.providers.len());
-        for provider in &self.providers {
+        let ids = self
+            .subscriptions
+            .get(&id)
+            .unwrap_or_else(|| (0..self.providers.len()).map(|idx| (idx, id)).collect());
+        let mut notifications = Vec::with_capacity(ids.len());
+        for (idx, id) in ids {
+            let provider = &self.providers[idx];
             let weight = provider.weight;
-            let fut = provider.inner.subscribe(id)?.map(move |val| (val, weight));
+            let fut = pubsub(&provider.inner)?.subscribe(id)?.map(move |val| (val, weight));
             notifications.push(Box::pin(fut) as WeightedNotificationStream);
         }
         Ok(QuorumStream::new(self.quorum_weight, notifications))
@@ -559,13 +772,27 @@ where
 
     fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
         let id = id.into();
-        for provider in &self.providers {
-            provider.inner.unsubscribe(id)?;
+        let ids = self
+            .subscriptions
+            .remove(&id)
+            .unwrap_or_else(|| (0..self.providers.len()).map(|idx| (idx, id)).collect());
+        for (idx, id) in ids {
+            pubsub(&self.providers[idx].inner)?.unsubscribe(id)?;
         }
         Ok(())
     }
 }
 
+/// Returns the client as a [`PubsubClientWrapper`], or an error if it doesn't support
+/// subscriptions
+pub(crate) fn pubsub<C: JsonRpcClientWrapper>(
+    client: &C,
+) -> Result<&dyn PubsubClientWrapper, ProviderError> {
+    client
+        .as_pubsub_wrapper()
+        .ok_or_else(|| ProviderError::UnsupportedMethod("eth_subscribe".to_string()))
+}
+
 /// Helper type that can be used to pass through the `params` value.
 /// This is necessary because the wrapper provider is supposed to skip the `params` if it's of
 /// size 0, see `crate::transports::common::Request`
@@ -579,8 +806,10 @@ pub enum QuorumParams {
 #[cfg(not(target_arch = "wasm32"))]
 mod tests {
     use super::{Quorum, QuorumProvider, WeightedProvider};
-    use crate::{Middleware, MockProvider, Provider};
-    use ethers_core::types::U64;
+    use crate::{Middleware, MockProvider, MockPubsub, Provider, SubscriptionStream};
+    use ethers_core::types::{U256, U64};
+    use futures_util::StreamExt;
+    use serde_json::Value;
 
     async fn test_quorum(q: Quorum) {
         let num = 5u64;
@@ -648,4 +877,58 @@ mod tests {
     async fn all_quorum() {
         test_quorum(Quorum::All).await
     }
+
+    #[tokio::test]
+    async fn subscription_quorum() {
+        // every provider hands out its own subscription id and sees a slightly different feed
+        let feeds = [(1u64, vec![1, 2]), (2, vec![1, 3]), (3, vec![2])];
+        let mut providers = Vec::new();
+        let mut clients = Vec::new();
+        for (id, notifications) in feeds {
+            let client = MockPubsub::default();
+            client.mock.push(U256::from(id)).unwrap();
+            for notification in notifications {
+                client.notify(id, notification);
+            }
+            providers.push(WeightedProvider::new(client.clone()));
+            clients.push(client);
+        }
+        let quorum = QuorumProvider::builder()
+            .add_providers(providers)
+            .quorum(Quorum::ProviderCount(2))
+            .build();
+        let provider = Provider::quorum(quorum);
+
+        let stream: SubscriptionStream<_, Value> = provider.subscribe(["newHeads"]).await.unwrap();
+        let local_id = stream.id;
+        // only the notifications at least 2 providers agree on are yielded, each of them once
+        let values = stream.collect::<Vec<_>>().await;
+        assert_eq!(values, vec![Value::from(1), Value::from(2)]);
+
+        // dropping the stream removes the subscription from every provider using its own id
+        for (idx, client) in clients.iter().enumerate() {
+            assert_eq!(*client.unsubscribed.lock().unwrap(), vec![U256::from(idx + 1)]);
+        }
+        assert!(provider.as_ref().subscriptions.get(&local_id).is_none());
+    }
+
+    #[tokio::test]
+    async fn subscription_without_quorum() {
+        let mut providers = Vec::new();
+        for id in 1..=3u64 {
+            let client = MockPubsub::default();
+            if id == 1 {
+                client.mock.push(U256::from(id)).unwrap();
+            }
+            providers.push(WeightedProvider::new(client));
+        }
+        let quorum = QuorumProvider::builder()
+            .add_providers(providers)
+            .quorum(Quorum::ProviderCount(2))
+            .build();
+        let provider = Provider::quorum(quorum);
+
+        let res = provider.subscribe::<_, Value>(["newHeads"]).await;
+        assert!(res.is_err());
+    }
 }
@@ -0,0 +1,262 @@
+//! A [JsonRpcClient] implementation that keeps requests under a rate limit
+
+use crate::{provider::ProviderError, JsonRpcClient};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::HashMap, fmt::Debug, sync::Mutex, time::Duration};
+
+#[cfg(not(target_arch = "wasm32"))]
+use futures_timer::Delay;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::{Delay, Instant};
+
+/// The compute units of the most common methods, as priced by
+/// [Alchemy](https://docs.alchemy.com/reference/compute-unit-costs)
+pub const ALCHEMY_COMPUTE_UNITS: &[(&str, u64)] = &[
+    ("net_version", 0),
+    ("eth_chainId", 0),
+    ("eth_blockNumber", 10),
+    ("eth_feeHistory", 10),
+    ("eth_maxPriorityFeePerGas", 10),
+    ("eth_getTransactionReceipt", 15),
+    ("eth_getBlockByNumber", 16),
+    ("eth_getBlockByHash", 16),
+    ("eth_getStorageAt", 17),
+    ("eth_getTransactionByHash", 17),
+    ("eth_gasPrice", 19),
+    ("eth_getBalance", 19),
+    ("eth_call", 26),
+    ("eth_getCode", 26),
+    ("eth_getTransactionCount", 26),
+    ("eth_getLogs", 75),
+    ("eth_estimateGas", 87),
+    ("eth_sendRawTransaction", 250),
+    ("debug_traceTransaction", 309),
+    ("debug_traceCall", 309),
+];
+
+/// A token bucket that refills at a constant rate up to its capacity.
+///
+/// Requests reserve their cost even if the bucket doesn't hold enough tokens, and wait until the
+/// debt is refilled, so waiting requests are served in order.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl Bucket {
+    fn new(per_second: u64, burst: u64) -> Self {
+        let capacity = burst.max(1) as f64;
+        Self {
+            capacity,
+            per_second: per_second.max(1) as f64,
+            tokens: capacity,
+            refilled: Instant::now(),
+        }
+    }
+
+    /// Takes `cost` tokens, returning how long to wait until they are available
+    fn reserve(&mut self, cost: u64, now: Instant) -> Duration {
+        let elapsed = if now > self.refilled { (now - self.refilled).as_secs_f64() } else { 0.0 };
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.refilled = now;
+
+        // costs above the capacity would never fit into the bucket
+        self.tokens -= (cost as f64).min(self.capacity);
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_second)
+        }
+    }
+}
+
+/// A client that delays requests to stay under the rate limits of the endpoint, instead of
+/// retrying them once the endpoint rejected them with `429 Too Many Requests`.
+///
+/// Requests are limited by a global token bucket that refills with a number of units per second,
+/// where every request costs the weight of its method, and optionally per method to a number of
+/// requests per second. Weights default to `1`, so the global limit counts requests, or can be set
+/// to the compute units of providers like Alchemy, see [`RateLimitedClient::alchemy`].
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{Http, Middleware, Provider, RateLimitedClient};
+/// use std::str::FromStr;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let http = Http::from_str("https://eth-mainnet.g.alchemy.com/v2/<key>")?;
+/// // 330 compute units per second, at most 5 `eth_getLogs` requests per second
+/// let client = RateLimitedClient::alchemy(http, 330).method_limit("eth_getLogs", 5, 5);
+/// let provider = Provider::new(client);
+/// let block_number = provider.get_block_number().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RateLimitedClient<C> {
+    inner: C,
+    global: Mutex<Bucket>,
+    methods: HashMap<String, Mutex<Bucket>>,
+    weights: HashMap<String, u64>,
+    default_weight: u64,
+}
+
+impl<C> RateLimitedClient<C> {
+    /// Wraps the client, allowing `per_second` units per second, with bursts of as many units
+    pub fn new(inner: C, per_second: u64) -> Self {
+        Self {
+            inner,
+            global: Mutex::new(Bucket::new(per_second, per_second)),
+            methods: HashMap::new(),
+            weights: HashMap::new(),
+            default_weight: 1,
+        }
+    }
+
+    /// Wraps the client, allowing `per_second` compute units per second, with the weights of
+    /// [`ALCHEMY_COMPUTE_UNITS`] and `20` units for all other methods
+    pub fn alchemy(inner: C, per_second: u64) -> Self {
+        ALCHEMY_COMPUTE_UNITS
+            .iter()
+            .fold(Self::new(inner, per_second), |client, (method, units)| {
+                client.weight(*method, *units)
+            })
+            .default_weight(20)
+    }
+
+    /// Sets the size of the bursts of the global limit (default: the units per second)
+    #[must_use]
+    pub fn burst(mut self, burst: u64) -> Self {
+        let per_second = self.global.get_mut().unwrap().per_second as u64;
+        self.global = Mutex::new(Bucket::new(per_second, burst));
+        self
+    }
+
+    /// Limits requests of the method to `per_second` requests per second, with bursts of `burst`
+    /// requests, in addition to the global limit
+    #[must_use]
+    pub fn method_limit(mut self, method: impl Into<String>, per_second: u64, burst: u64) -> Self {
+        self.methods.insert(method.into(), Mutex::new(Bucket::new(per_second, burst)));
+        self
+    }
+
+    /// Sets the units that requests of the method cost of the global limit
+    #[must_use]
+    pub fn weight(mut self, method: impl Into<String>, units: u64) -> Self {
+        self.weights.insert(method.into(), units);
+        self
+    }
+
+    /// Sets the units that requests of methods without a weight cost (default: `1`)
+    #[must_use]
+    pub fn default_weight(mut self, units: u64) -> Self {
+        self.default_weight = units;
+        self
+    }
+
+    /// Returns the units that requests of the method cost
+    pub fn weight_of(&self, method: &str) -> u64 {
+        self.weights.get(method).copied().unwrap_or(self.default_weight)
+    }
+
+    /// Returns the wrapped client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns how long a request of the method has to wait, reserving its cost of the limits
+    fn reserve(&self, method: &str) -> Duration {
+        let now = Instant::now();
+        let global = self.global.lock().unwrap().reserve(self.weight_of(method), now);
+        let method = match self.methods.get(method) {
+            Some(bucket) => bucket.lock().unwrap().reserve(1, now),
+            None => Duration::ZERO,
+        };
+        global.max(method)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for RateLimitedClient<C>
+where
+    C: JsonRpcClient,
+{
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let wait = self.reserve(method);
+        if !wait.is_zero() {
+            tracing::trace!(method, ?wait, "delaying rate limited request");
+            let _ = Delay::new(wait).await;
+        }
+        self.inner.request(method, params).await.map_err(Into::into)
+    }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.inner.as_pubsub()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, Provider};
+    use ethers_core::types::U64;
+
+    #[test]
+    fn refills_buckets() {
+        let mut bucket = Bucket::new(10, 2);
+        let start = bucket.refilled;
+        assert_eq!(bucket.reserve(1, start), Duration::ZERO);
+        assert_eq!(bucket.reserve(1, start), Duration::ZERO);
+        // waits for the next token
+        assert_eq!(bucket.reserve(1, start), Duration::from_millis(100));
+        // and the one after, since the previous request reserved its token
+        assert_eq!(bucket.reserve(1, start), Duration::from_millis(200));
+
+        // refills up to the capacity
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(2, later), Duration::ZERO);
+        assert_eq!(bucket.reserve(1, later), Duration::from_millis(100));
+
+        // costs above the capacity are capped
+        let mut bucket = Bucket::new(10, 2);
+        assert_eq!(bucket.reserve(250, start), Duration::ZERO);
+    }
+
+    #[test]
+    fn weighs_methods() {
+        let client = RateLimitedClient::alchemy(MockProvider::new(), 330);
+        assert_eq!(client.weight_of("eth_blockNumber"), 10);
+        assert_eq!(client.weight_of("eth_sendRawTransaction"), 250);
+        assert_eq!(client.weight_of("eth_newFilter"), 20);
+    }
+
+    #[tokio::test]
+    async fn delays_requests_over_the_limit() {
+        let mock = MockProvider::new();
+        let client =
+            RateLimitedClient::new(mock.clone(), 1_000).method_limit("eth_blockNumber", 20, 1);
+        let provider = Provider::new(client);
+
+        mock.push(U64::from(2)).unwrap();
+        mock.push(U64::from(1)).unwrap();
+        let start = Instant::now();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(1));
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(2));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
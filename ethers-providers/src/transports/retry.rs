@@ -1,5 +1,8 @@
 //! A [JsonRpcClient] implementation that retries requests filtered by [RetryPolicy]
 //! with an exponential backoff.
+//!
+//! If the endpoint tells how long to wait, e.g. with a `Retry-After` header or in the body of a
+//! rate limit error, the next attempt is scheduled accordingly.
 
 use super::{common::JsonRpcError, http::ClientError};
 use crate::{provider::ProviderError, JsonRpcClient};
@@ -13,6 +16,11 @@ use std::{
 use thiserror::Error;
 use tracing::trace;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::Instant;
+
 /// [RetryPolicy] defines logic for which [JsonRpcClient::Error] instances should
 /// the client retry the request and try to recover from.
 pub trait RetryPolicy<E>: Send + Sync + Debug {
@@ -20,6 +28,8 @@ pub trait RetryPolicy<E>: Send + Sync + Debug {
     fn should_retry(&self, error: &E) -> bool;
 
     /// Providers may include the `backoff` in the error response directly
+    ///
+    /// If a hint is returned, the next attempt is scheduled after exactly this duration.
     fn backoff_hint(&self, error: &E) -> Option<Duration>;
 }
 
@@ -43,6 +53,7 @@ pub trait RetryPolicy<E>: Send + Sync + Debug {
 ///     .rate_limit_retries(10)
 ///     .timeout_retries(3)
 ///     .initial_backoff(Duration::from_millis(500))
+///     .retry_budget(Duration::from_secs(60))
 ///     .build(http, Box::new(HttpRateLimitRetryPolicy::default()));
 /// # }
 /// ```
@@ -64,6 +75,8 @@ where
     initial_backoff: Duration,
     /// available CPU per second
     compute_units_per_second: u64,
+    /// The maximum time spent retrying a single request
+    retry_budget: Option<Duration>,
 }
 
 impl<T> RetryClient<T>
@@ -112,6 +125,29 @@ where
         self.compute_units_per_second = cpus;
         self
     }
+
+    /// Computes the next backoff if the error did not include a hint, taking into account the
+    /// compute budget of the endpoint
+    fn compute_backoff(&self, ahead_in_queue: u64) -> Duration {
+        let current_queued_requests = self.requests_enqueued.load(Ordering::SeqCst) as u64;
+
+        // requests are usually weighted and can vary from 10 CU to several 100 CU, cheaper
+        // requests are more common some example alchemy weights:
+        // - `eth_getStorageAt`: 17
+        // - `eth_getBlockByNumber`: 16
+        // - `eth_newFilter`: 20
+        //
+        // (coming from forking mode) assuming here that storage request will be the driver
+        // for Rate limits we choose `17` as the average cost of any request
+        const AVG_COST: u64 = 17u64;
+        let seconds_to_wait_for_compute_budget = compute_unit_offset_in_secs(
+            AVG_COST,
+            self.compute_units_per_second,
+            current_queued_requests,
+            ahead_in_queue,
+        );
+        self.initial_backoff + Duration::from_secs(seconds_to_wait_for_compute_budget)
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -124,6 +160,8 @@ pub struct RetryClientBuilder {
     initial_backoff: Duration,
     /// available CPU per second
     compute_units_per_second: u64,
+    /// The maximum time spent retrying a single request
+    retry_budget: Option<Duration>,
 }
 
 // === impl RetryClientBuilder ===
@@ -157,6 +195,15 @@ impl RetryClientBuilder {
         self
     }
 
+    /// Sets the maximum time that is spent retrying a single request
+    ///
+    /// If the next backoff would exceed this budget, the request fails with
+    /// [`RetryClientError::TimeoutError`] instead.
+    pub fn retry_budget(mut self, retry_budget: Duration) -> Self {
+        self.retry_budget = Some(retry_budget);
+        self
+    }
+
     /// Creates the `RetryClient` with the configured settings
     pub fn build<T>(self, client: T, policy: Box<dyn RetryPolicy<T::Error>>) -> RetryClient<T>
     where
@@ -168,6 +215,7 @@ impl RetryClientBuilder {
             rate_limit_retries,
             initial_backoff,
             compute_units_per_second,
+            retry_budget,
         } = self;
         RetryClient {
             inner: client,
@@ -177,6 +225,7 @@ impl RetryClientBuilder {
             rate_limit_retries,
             initial_backoff,
             compute_units_per_second,
+            retry_budget,
         }
     }
 }
@@ -191,6 +240,7 @@ impl Default for RetryClientBuilder {
             initial_backoff: Duration::from_millis(1000),
             // alchemy max cpus <https://github.com/alchemyplatform/alchemy-docs/blob/master/documentation/compute-units.md#rate-limits-cups>
             compute_units_per_second: 330,
+            retry_budget: None,
         }
     }
 }
@@ -259,6 +309,7 @@ where
 
         let mut rate_limit_retry_number: u32 = 0;
         let mut timeout_retries: u32 = 0;
+        let started = Instant::now();
 
         loop {
             let err;
@@ -284,33 +335,22 @@ where
                 rate_limit_retry_number += 1;
                 if rate_limit_retry_number > self.rate_limit_retries {
                     trace!("request timed out after {} retries", self.rate_limit_retries);
+                    self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
                     return Err(RetryClientError::TimeoutError)
                 }
 
-                let current_queued_requests = self.requests_enqueued.load(Ordering::SeqCst) as u64;
-
-                // try to extract the requested backoff from the error or compute the next backoff
-                // based on retry count
-                let mut next_backoff = self.policy.backoff_hint(&err).unwrap_or_else(|| {
-                    Duration::from_millis(self.initial_backoff.as_millis() as u64)
-                });
-
-                // requests are usually weighted and can vary from 10 CU to several 100 CU, cheaper
-                // requests are more common some example alchemy weights:
-                // - `eth_getStorageAt`: 17
-                // - `eth_getBlockByNumber`: 16
-                // - `eth_newFilter`: 20
-                //
-                // (coming from forking mode) assuming here that storage request will be the driver
-                // for Rate limits we choose `17` as the average cost of any request
-                const AVG_COST: u64 = 17u64;
-                let seconds_to_wait_for_compute_budget = compute_unit_offset_in_secs(
-                    AVG_COST,
-                    self.compute_units_per_second,
-                    current_queued_requests,
-                    ahead_in_queue,
-                );
-                next_backoff += Duration::from_secs(seconds_to_wait_for_compute_budget);
+                let next_backoff = self
+                    .policy
+                    .backoff_hint(&err)
+                    .unwrap_or_else(|| self.compute_backoff(ahead_in_queue));
+
+                if let Some(budget) = self.retry_budget {
+                    if started.elapsed() + next_backoff > budget {
+                        trace!("retry budget of {:?} exhausted", budget);
+                        self.requests_enqueued.fetch_sub(1, Ordering::SeqCst);
+                        return Err(RetryClientError::TimeoutError)
+                    }
+                }
 
                 trace!("retrying and backing off for {:?}", next_backoff);
 
@@ -335,6 +375,10 @@ where
             }
         }
     }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.inner.as_pubsub()
+    }
 }
 
 /// Implements [RetryPolicy] that will retry requests that errored with
@@ -342,6 +386,9 @@ where
 ///
 /// Infura often fails with a `"header not found"` rpc error which is apparently linked to load
 /// balancing, which are retried as well.
+///
+/// The backoff is taken from the `Retry-After` header of the response or from the rate limit
+/// error body if the provider includes it, like infura's `backoff_seconds`.
 #[derive(Debug, Default)]
 pub struct HttpRateLimitRetryPolicy;
 
@@ -378,30 +425,43 @@ impl RetryPolicy<ClientError> for HttpRateLimitRetryPolicy {
                 err.status() == Some(http::StatusCode::TOO_MANY_REQUESTS)
             }
             ClientError::JsonRpcError(err) => should_retry_json_rpc_error(err),
-            ClientError::SerdeJson { text, .. } => {
-                // some providers send invalid JSON RPC in the error case (no `id:u64`), but the
-                // text should be a `JsonRpcError`
-                #[derive(Deserialize)]
-                struct Resp {
-                    error: JsonRpcError,
-                }
+            ClientError::SerdeJson { text, .. } => json_rpc_error_from_text(text)
+                .map_or(false, |err| should_retry_json_rpc_error(&err)),
+            ClientError::MissingBatchResponse(_) | ClientError::SignerError(_) => false,
+            ClientError::RateLimited { .. } => true,
+        }
+    }
 
-                if let Ok(resp) = serde_json::from_str::<Resp>(text) {
-                    return should_retry_json_rpc_error(&resp.error)
-                }
-                false
+    fn backoff_hint(&self, error: &ClientError) -> Option<Duration> {
+        match error {
+            ClientError::JsonRpcError(err) => json_rpc_backoff_hint(err),
+            ClientError::SerdeJson { text, .. } => {
+                json_rpc_error_from_text(text).as_ref().and_then(json_rpc_backoff_hint)
             }
+            ClientError::RateLimited { retry_after, text } => retry_after.or_else(|| {
+                json_rpc_error_from_text(text).as_ref().and_then(json_rpc_backoff_hint)
+            }),
+            _ => None,
         }
     }
+}
 
-    fn backoff_hint(&self, error: &ClientError) -> Option<Duration> {
-        if let ClientError::JsonRpcError(JsonRpcError { data, .. }) = error {
-            let data = data.as_ref()?;
+/// Some providers send invalid JSON RPC in the error case (no `id:u64`), but the text may
+/// still contain a `JsonRpcError`
+fn json_rpc_error_from_text(text: &str) -> Option<JsonRpcError> {
+    #[derive(Deserialize)]
+    struct Resp {
+        error: JsonRpcError,
+    }
+    serde_json::from_str::<Resp>(text).ok().map(|resp| resp.error)
+}
 
-            // if daily rate limit exceeded, infura returns the requested backoff in the error
-            // response
-            let backoff_seconds = &data["rate"]["backoff_seconds"];
-            // infura rate limit error
+/// Extracts the requested backoff from a rate limit error
+fn json_rpc_backoff_hint(err: &JsonRpcError) -> Option<Duration> {
+    if let Some(data) = err.data.as_ref() {
+        // if daily rate limit exceeded, infura returns the requested backoff in the error
+        // response, per second rate limit errors include it at the top level of `data`
+        for backoff_seconds in [&data["rate"]["backoff_seconds"], &data["backoff_seconds"]] {
             if let Some(seconds) = backoff_seconds.as_u64() {
                 return Some(Duration::from_secs(seconds))
             }
@@ -409,9 +469,30 @@ impl RetryPolicy<ClientError> for HttpRateLimitRetryPolicy {
                 return Some(Duration::from_secs(seconds as u64 + 1))
             }
         }
-
-        None
     }
+    backoff_from_message(&err.message)
+}
+
+/// Parses a backoff from messages like `"rate limit exceeded, try again in 3s"`
+fn backoff_from_message(message: &str) -> Option<Duration> {
+    let message = message.to_lowercase();
+    let start = ["try again in ", "try again after ", "retry in ", "retry after "]
+        .iter()
+        .find_map(|prefix| message.find(prefix).map(|pos| pos + prefix.len()))?;
+    let rest = &message[start..];
+    let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+    let value = rest[..end].parse::<f64>().ok()?;
+    let unit = rest[end..].trim_start();
+    let seconds = if unit.starts_with("ms") || unit.starts_with("milli") {
+        value / 1000.
+    } else if unit.starts_with("min") || unit.starts_with("m ") || unit == "m" {
+        value * 60.
+    } else if unit.starts_with('s') {
+        value
+    } else {
+        return None
+    };
+    Some(Duration::from_secs_f64(seconds))
 }
 
 /// Calculates an offset in seconds by taking into account the number of currently queued requests,
@@ -559,4 +640,72 @@ mod tests {
         let should_retry = HttpRateLimitRetryPolicy::default().should_retry(&err);
         assert!(should_retry);
     }
+
+    #[test]
+    fn can_extract_backoff_from_rate_limited_response() {
+        let err = ClientError::RateLimited {
+            retry_after: Some(Duration::from_secs(3)),
+            text: "Too Many Requests".to_string(),
+        };
+        assert!(HttpRateLimitRetryPolicy.should_retry(&err));
+        assert_eq!(HttpRateLimitRetryPolicy.backoff_hint(&err), Some(Duration::from_secs(3)));
+
+        // infura per second rate limit error without a `Retry-After` header
+        let text = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32005,"message":"project ID request rate exceeded","data":{"see":"https://infura.io/dashboard","current_rps":13.333,"allowed_rps":10.0,"backoff_seconds":30.0}}}"#;
+        let err = ClientError::RateLimited { retry_after: None, text: text.to_string() };
+        // fractional backoffs are rounded up
+        assert_eq!(HttpRateLimitRetryPolicy.backoff_hint(&err), Some(Duration::from_secs(31)));
+
+        let err = ClientError::RateLimited { retry_after: None, text: String::new() };
+        assert!(HttpRateLimitRetryPolicy.should_retry(&err));
+        assert!(HttpRateLimitRetryPolicy.backoff_hint(&err).is_none());
+    }
+
+    #[test]
+    fn can_extract_backoff_from_message() {
+        let err = ClientError::JsonRpcError(JsonRpcError {
+            code: 429,
+            message: "Too many requests, try again in 1.5s".to_string(),
+            data: None,
+        });
+        assert_eq!(HttpRateLimitRetryPolicy.backoff_hint(&err), Some(Duration::from_millis(1500)));
+
+        assert_eq!(backoff_from_message("Retry after 250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(backoff_from_message("retry in 2 seconds"), Some(Duration::from_secs(2)));
+        assert_eq!(backoff_from_message("try again after 1 minute"), Some(Duration::from_secs(60)));
+        assert_eq!(backoff_from_message("rate limit exceeded"), None);
+        assert_eq!(backoff_from_message("try again in a bit"), None);
+    }
+
+    #[derive(Debug)]
+    struct AlwaysRetry(Duration);
+
+    impl RetryPolicy<crate::MockError> for AlwaysRetry {
+        fn should_retry(&self, _: &crate::MockError) -> bool {
+            true
+        }
+
+        fn backoff_hint(&self, _: &crate::MockError) -> Option<Duration> {
+            Some(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn stops_retrying_when_budget_is_exhausted() {
+        let mock = crate::MockProvider::new();
+        let client = RetryClientBuilder::default()
+            .rate_limit_retries(100)
+            .retry_budget(Duration::from_millis(250))
+            .build(mock.clone(), Box::new(AlwaysRetry(Duration::from_millis(100))));
+
+        let res = client.request::<_, u64>("eth_blockNumber", ()).await;
+        assert!(matches!(res, Err(RetryClientError::TimeoutError)));
+
+        // the initial attempt and the two retries that fit into the budget
+        for _ in 0..3 {
+            mock.assert_request("eth_blockNumber", ()).unwrap();
+        }
+        assert!(mock.assert_request("eth_blockNumber", ()).is_err());
+        assert_eq!(client.requests_enqueued.load(Ordering::SeqCst), 0);
+    }
 }
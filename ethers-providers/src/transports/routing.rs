@@ -0,0 +1,205 @@
+//! A [JsonRpcClient] implementation that routes requests to different clients based on the
+//! prefix of the requested method
+
+use super::quorum::{JsonRpcClientWrapper, QuorumParams};
+use crate::{provider::ProviderError, JsonRpcClient};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+/// A client that dispatches each request to one of several clients depending on its method.
+///
+/// Every route maps a method prefix, like `"debug_"`, or a full method name, like
+/// `"eth_sendRawTransaction"`, to a client. If several prefixes match a method, the longest one
+/// wins. Requests that don't match any route are sent to the default client.
+///
+/// `eth_unsubscribe` is sent to the client of `eth_subscribe`, which also serves the
+/// notifications if it supports subscriptions, see [`JsonRpcClient::as_pubsub`].
+///
+/// # Example
+///
+/// Send transactions to a dedicated endpoint, `debug_*` and `trace_*` calls to an archive node and
+/// everything else to a full node.
+///
+/// ```no_run
+/// use ethers_providers::{Http, Middleware, Provider, RoutingClient};
+/// use std::str::FromStr;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = RoutingClient::dyn_rpc(Box::new(Http::from_str("http://full-node:8545")?))
+///     .route(["eth_sendRawTransaction"], Box::new(Http::from_str("http://relay:8545")?))
+///     .route(["debug_", "trace_"], Box::new(Http::from_str("http://archive-node:8545")?))
+///     .build();
+/// let provider = Provider::new(client);
+/// let block_number = provider.get_block_number().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RoutingClient<T = Box<dyn JsonRpcClientWrapper>> {
+    /// The client used if no route matches
+    default: T,
+    /// All clients that requests can be routed to
+    clients: Vec<T>,
+    /// Method prefixes and the index of their client, sorted by descending prefix length
+    routes: Vec<(String, usize)>,
+}
+
+impl RoutingClient<Box<dyn JsonRpcClientWrapper>> {
+    /// Create a `RoutingClient` for different `JsonRpcClient` types
+    pub fn dyn_rpc(
+        default: Box<dyn JsonRpcClientWrapper>,
+    ) -> RoutingClientBuilder<Box<dyn JsonRpcClientWrapper>> {
+        Self::builder(default)
+    }
+}
+
+impl<T> RoutingClient<T> {
+    /// Returns a `RoutingClientBuilder` that sends all unmatched requests to `default`
+    pub fn builder(default: T) -> RoutingClientBuilder<T> {
+        RoutingClientBuilder { default, clients: Vec::new(), routes: Vec::new() }
+    }
+
+    /// Returns the client used if no route matches
+    pub fn default_client(&self) -> &T {
+        &self.default
+    }
+
+    /// Returns the client the given method is routed to
+    pub fn client_for(&self, method: &str) -> &T {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| method.starts_with(prefix.as_str()))
+            .map(|(_, idx)| &self.clients[*idx])
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Builder for a [`RoutingClient`]
+#[derive(Debug)]
+pub struct RoutingClientBuilder<T> {
+    default: T,
+    clients: Vec<T>,
+    routes: Vec<(String, usize)>,
+}
+
+impl<T> RoutingClientBuilder<T> {
+    /// Routes all methods starting with one of the `prefixes` to `client`
+    ///
+    /// If a prefix was already routed to another client, the new route replaces it.
+    pub fn route<I, S>(mut self, prefixes: I, client: T) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let idx = self.clients.len();
+        self.clients.push(client);
+        for prefix in prefixes {
+            let prefix = prefix.into();
+            self.routes.retain(|(p, _)| *p != prefix);
+            self.routes.push((prefix, idx));
+        }
+        self
+    }
+
+    /// Creates the `RoutingClient` with the configured routes
+    pub fn build(self) -> RoutingClient<T> {
+        let RoutingClientBuilder { default, clients, mut routes } = self;
+        // the longest matching prefix wins
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        RoutingClient { default, clients, routes }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for RoutingClient<C>
+where
+    C: JsonRpcClientWrapper,
+{
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let params = if std::mem::size_of::<T>() == 0 {
+            // we don't want `()` to become `"null"`.
+            QuorumParams::Zst
+        } else {
+            QuorumParams::Value(serde_json::to_value(params)?)
+        };
+        // subscriptions are cancelled on the client that created them
+        let route = if method == "eth_unsubscribe" { "eth_subscribe" } else { method };
+        let value = self.client_for(route).request(method, params).await?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.client_for("eth_subscribe").as_pubsub_wrapper()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, Provider};
+    use ethers_core::types::{Bytes, H256, U256, U64};
+
+    #[tokio::test]
+    async fn routes_by_method_prefix() {
+        let full = MockProvider::new();
+        let relay = MockProvider::new();
+        let archive = MockProvider::new();
+        let client = RoutingClient::builder(full.clone())
+            .route(["eth_sendRawTransaction"], relay.clone())
+            .route(["debug_", "trace_"], archive.clone())
+            .build();
+        let provider = Provider::new(client);
+
+        full.push(U64::from(42)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(42));
+        full.assert_request("eth_blockNumber", ()).unwrap();
+
+        let tx = Bytes::from(vec![1, 2, 3]);
+        relay.push(H256::zero()).unwrap();
+        let pending = provider.send_raw_transaction(tx.clone()).await.unwrap();
+        assert_eq!(pending.tx_hash(), H256::zero());
+        relay.assert_request("eth_sendRawTransaction", [tx]).unwrap();
+
+        archive.push::<Vec<()>, _>(Vec::new()).unwrap();
+        assert!(provider.trace_transaction(H256::zero()).await.unwrap().is_empty());
+        archive.assert_request("trace_transaction", [H256::zero()]).unwrap();
+
+        assert!(full.assert_request("eth_blockNumber", ()).is_err());
+    }
+
+    #[tokio::test]
+    async fn unsubscribes_on_the_client_of_subscriptions() {
+        let (full, ws) = (MockProvider::new(), MockProvider::new());
+        let client =
+            RoutingClient::builder(full.clone()).route(["eth_subscribe"], ws.clone()).build();
+        assert!(JsonRpcClient::as_pubsub(&client).is_none());
+        let provider = Provider::new(client);
+
+        ws.push(true).unwrap();
+        let unsubscribed: bool = provider.request("eth_unsubscribe", [U256::one()]).await.unwrap();
+        assert!(unsubscribed);
+        ws.assert_request("eth_unsubscribe", [U256::one()]).unwrap();
+        assert!(full.assert_request("eth_unsubscribe", [U256::one()]).is_err());
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let client = RoutingClient::builder(0)
+            .route(["eth_"], 1)
+            .route(["eth_sendRawTransaction"], 2)
+            .route(["eth_"], 3)
+            .build();
+        assert_eq!(*client.client_for("eth_sendRawTransaction"), 2);
+        assert_eq!(*client.client_for("eth_call"), 3);
+        assert_eq!(*client.client_for("net_version"), 0);
+    }
+}
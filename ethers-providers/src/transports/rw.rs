@@ -117,4 +117,9 @@ where
             _ => self.r.request(method, params).await.map_err(RwClientError::Read),
         }
     }
+
+    /// Subscriptions are read operations, they are served by the _read_ client
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.r.as_pubsub()
+    }
 }
@@ -0,0 +1,330 @@
+//! A [JsonRpcClient] implementation that shares one in-flight request between concurrent
+//! identical requests
+
+use super::quorum::QuorumParams;
+use crate::{provider::ProviderError, JsonRpcClient};
+use async_trait::async_trait;
+use futures_util::future::{FutureExt, Shared};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+
+/// The read-only methods that are deduplicated, requests of other methods are always sent since
+/// they may change state or every call is expected to have an effect
+pub const DEDUPLICATED: &[&str] = &[
+    "web3_clientVersion",
+    "net_version",
+    "net_listening",
+    "net_peerCount",
+    "eth_chainId",
+    "eth_protocolVersion",
+    "eth_syncing",
+    "eth_mining",
+    "eth_hashrate",
+    "eth_accounts",
+    "eth_blockNumber",
+    "eth_gasPrice",
+    "eth_maxPriorityFeePerGas",
+    "eth_feeHistory",
+    "eth_getBalance",
+    "eth_getCode",
+    "eth_getStorageAt",
+    "eth_getProof",
+    "eth_getTransactionCount",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getBlockReceipts",
+    "eth_getBlockTransactionCountByHash",
+    "eth_getBlockTransactionCountByNumber",
+    "eth_getUncleByBlockHashAndIndex",
+    "eth_getUncleByBlockNumberAndIndex",
+    "eth_getUncleCountByBlockHash",
+    "eth_getUncleCountByBlockNumber",
+    "eth_getTransactionByHash",
+    "eth_getTransactionByBlockHashAndIndex",
+    "eth_getTransactionByBlockNumberAndIndex",
+    "eth_getTransactionReceipt",
+    "eth_getLogs",
+    "eth_getFilterLogs",
+    "eth_call",
+    "eth_estimateGas",
+    "eth_createAccessList",
+    "txpool_content",
+    "txpool_inspect",
+    "txpool_status",
+    "trace_call",
+    "trace_callMany",
+    "trace_rawTransaction",
+    "trace_replayTransaction",
+    "trace_replayBlockTransactions",
+    "trace_block",
+    "trace_filter",
+    "trace_get",
+    "trace_transaction",
+    "debug_traceTransaction",
+    "debug_traceCall",
+    "debug_traceBlockByNumber",
+    "debug_traceBlockByHash",
+];
+
+type SharedResult = Result<Value, Arc<ProviderError>>;
+
+#[cfg(not(target_arch = "wasm32"))]
+type SharedRequest = Shared<Pin<Box<dyn Future<Output = SharedResult> + Send>>>;
+#[cfg(target_arch = "wasm32")]
+type SharedRequest = Shared<Pin<Box<dyn Future<Output = SharedResult>>>>;
+
+/// A client that sends concurrent requests with the same method and params only once.
+///
+/// While a request is in flight, identical requests wait for its response instead of being
+/// sent, e.g. many tasks asking for the latest block share one `eth_blockNumber` request. Once
+/// the response arrived, the next identical request is sent again, responses are not cached.
+/// Only read-only methods are deduplicated, see [`DEDUPLICATED`], requests that may change state,
+/// like sending transactions or installing filters, are always sent.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{Http, Middleware, Provider, SingleflightClient};
+/// use std::str::FromStr;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = SingleflightClient::new(Http::from_str("http://localhost:8545")?);
+/// let provider = Provider::new(client);
+/// // sends a single request
+/// let (a, b) = futures_util::join!(provider.get_block_number(), provider.get_block_number());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SingleflightClient<C> {
+    inner: Arc<C>,
+    /// The requests in flight by method and params
+    in_flight: Arc<Mutex<HashMap<String, SharedRequest>>>,
+}
+
+impl<C> SingleflightClient<C> {
+    /// Wraps the client
+    pub fn new(inner: C) -> Self {
+        Self { inner: Arc::new(inner), in_flight: Default::default() }
+    }
+
+    /// Returns the wrapped client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns whether concurrent requests of the method are deduplicated
+    pub fn is_deduplicated(method: &str) -> bool {
+        DEDUPLICATED.contains(&method)
+    }
+
+    /// Returns the number of requests in flight
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+}
+
+/// An error shared by the requests waiting for the same response
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct SharedError(Arc<ProviderError>);
+
+impl SharedError {
+    /// Returns the error of the shared response
+    pub fn get(&self) -> &ProviderError {
+        &self.0
+    }
+}
+
+/// Returns the error of a shared response, the original error for the last request waiting for
+/// it and copies of the same kind for the others. Errors that can't be copied are wrapped in a
+/// [`SharedError`].
+fn unshare(err: Arc<ProviderError>) -> ProviderError {
+    let err = match Arc::try_unwrap(err) {
+        Ok(err) => return err,
+        Err(err) => err,
+    };
+    if let Some(err) = err.as_error_response() {
+        return ProviderError::JsonRpcClientError(Box::new(err.clone()))
+    }
+    match &*err {
+        ProviderError::EnsError(name) => ProviderError::EnsError(name.clone()),
+        ProviderError::EnsNotOwned(name) => ProviderError::EnsNotOwned(name.clone()),
+        ProviderError::SerdeJson(inner) => {
+            ProviderError::SerdeJson(serde::de::Error::custom(inner))
+        }
+        ProviderError::HexError(inner) => ProviderError::HexError(*inner),
+        ProviderError::CustomError(message) => ProviderError::CustomError(message.clone()),
+        ProviderError::UnsupportedRPC => ProviderError::UnsupportedRPC,
+        ProviderError::UnsupportedNodeClient => ProviderError::UnsupportedNodeClient,
+        ProviderError::SignerUnavailable => ProviderError::SignerUnavailable,
+        ProviderError::CcipReadError(message) => ProviderError::CcipReadError(message.clone()),
+        ProviderError::Timeout(timeout) => ProviderError::Timeout(*timeout),
+        ProviderError::UnsupportedMethod(method) => {
+            ProviderError::UnsupportedMethod(method.clone())
+        }
+        ProviderError::JsonRpcClientError(_) | ProviderError::HTTPError(_) => {
+            ProviderError::JsonRpcClientError(Box::new(SharedError(err)))
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for SingleflightClient<C>
+where
+    C: JsonRpcClient + 'static,
+{
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        if !Self::is_deduplicated(method) {
+            return self.inner.request(method, params).await.map_err(Into::into)
+        }
+
+        let params = if std::mem::size_of::<T>() == 0 {
+            // we don't want `()` to become `"null"`.
+            QuorumParams::Zst
+        } else {
+            QuorumParams::Value(serde_json::to_value(params)?)
+        };
+        let key = match &params {
+            QuorumParams::Value(params) => format!("{method}:{params}"),
+            QuorumParams::Zst => method.to_string(),
+        };
+
+        let request = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let inner = self.inner.clone();
+                let in_flight = self.in_flight.clone();
+                let method = method.to_string();
+                let request = async move {
+                    let res = match params {
+                        QuorumParams::Value(params) => inner.request(&method, params).await,
+                        QuorumParams::Zst => inner.request(&method, ()).await,
+                    };
+                    in_flight.lock().unwrap().remove(&key);
+                    res.map_err(|err| Arc::new(err.into()))
+                };
+                #[cfg(not(target_arch = "wasm32"))]
+                let request = request.boxed();
+                #[cfg(target_arch = "wasm32")]
+                let request = request.boxed_local();
+                request.shared()
+            })
+            .clone();
+
+        let value = request.await.map_err(unshare)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.inner.as_pubsub()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{JsonRpcError, Middleware, MockError, MockProvider, Provider};
+    use ethers_core::types::{Bytes, TxHash, U64};
+    use std::time::Duration;
+
+    const DELAY: Duration = Duration::from_millis(10);
+
+    fn error() -> JsonRpcError {
+        JsonRpcError { code: 3, message: "failed".to_string(), data: None }
+    }
+
+    #[tokio::test]
+    async fn shares_concurrent_requests() {
+        let mock = MockProvider::new();
+        let block_numbers = (1..=3u64)
+            .map(|n| mock.expect("eth_blockNumber").times(1).delay(DELAY).returns(U64::from(n)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let block =
+            mock.expect("eth_getBlockByNumber").delay(DELAY).returns_error(error()).unwrap();
+        let sent = mock.expect("eth_sendRawTransaction").delay(DELAY).returns(TxHash::zero());
+        let sent = sent.unwrap();
+        let provider = Provider::new(SingleflightClient::new(mock));
+
+        let (a, b) = futures_util::join!(provider.get_block_number(), provider.get_block_number());
+        assert_eq!((a.unwrap(), b.unwrap()), (U64::from(1), U64::from(1)));
+        assert_eq!(provider.as_ref().in_flight(), 0);
+
+        // different requests are sent separately
+        let (a, b) = futures_util::join!(provider.get_block(1u64), provider.get_block_number());
+        assert!(a.is_err());
+        assert_eq!(b.unwrap(), U64::from(2));
+
+        // completed requests are sent again
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(3));
+
+        // transactions are always sent
+        let tx = Bytes::from(vec![1]);
+        let (a, b) = futures_util::join!(
+            provider.send_raw_transaction(tx.clone()),
+            provider.send_raw_transaction(tx)
+        );
+        assert!(a.is_ok() && b.is_ok());
+        assert!(block_numbers.iter().all(|stub| stub.calls() == 1));
+        assert_eq!(block.calls(), 1);
+        assert_eq!(sent.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn shares_errors() {
+        let mock = MockProvider::new();
+        let stub = mock.expect("eth_blockNumber").delay(DELAY).returns_error(error()).unwrap();
+        let provider = Provider::new(SingleflightClient::new(mock));
+        let (a, b) = futures_util::join!(provider.get_block_number(), provider.get_block_number());
+        assert_eq!(a.unwrap_err().as_error_response().unwrap().code, 3);
+        assert_eq!(b.unwrap_err().as_error_response().unwrap().code, 3);
+        assert_eq!(stub.calls(), 1);
+    }
+
+    #[test]
+    fn keeps_kinds_of_shared_errors() {
+        let err = Arc::new(ProviderError::Timeout(DELAY));
+        assert!(matches!(unshare(err.clone()), ProviderError::Timeout(DELAY)));
+        assert!(matches!(unshare(err), ProviderError::Timeout(DELAY)));
+
+        let err = Arc::new(ProviderError::JsonRpcClientError(Box::new(MockError::EmptyResponses)));
+        let shared = match unshare(err.clone()) {
+            ProviderError::JsonRpcClientError(shared) => shared,
+            err => panic!("unexpected error {err:?}"),
+        };
+        let shared = shared.downcast_ref::<SharedError>().unwrap();
+        assert!(Arc::ptr_eq(&shared.0, &err));
+        assert_eq!(shared.to_string(), MockError::EmptyResponses.to_string());
+    }
+
+    #[test]
+    fn skips_state_changing_methods() {
+        assert!(SingleflightClient::<MockProvider>::is_deduplicated("eth_call"));
+        assert!(SingleflightClient::<MockProvider>::is_deduplicated("eth_getBlockByNumber"));
+        assert!(!SingleflightClient::<MockProvider>::is_deduplicated("eth_sendRawTransaction"));
+        assert!(!SingleflightClient::<MockProvider>::is_deduplicated("eth_getFilterChanges"));
+        assert!(!SingleflightClient::<MockProvider>::is_deduplicated("anvil_mine"));
+        assert!(!SingleflightClient::<MockProvider>::is_deduplicated("debug_setHead"));
+        assert!(!SingleflightClient::<MockProvider>::is_deduplicated("engine_newPayloadV2"));
+    }
+}
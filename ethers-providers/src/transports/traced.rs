@@ -0,0 +1,343 @@
+//! A [JsonRpcClient] implementation that logs every request and response with `tracing`
+
+use crate::{provider::ProviderError, JsonRpcClient};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{fmt, fmt::Debug, sync::Arc};
+use tracing::Level;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use wasm_timer::Instant;
+
+/// The methods whose params are redacted by [`redact_secrets`], since they hold signed
+/// transactions, passwords or keys
+pub const SECRET_PARAMS_METHODS: &[&str] = &[
+    "eth_sendRawTransaction",
+    "eth_sendPrivateTransaction",
+    "eth_sendBundle",
+    "eth_callBundle",
+    "personal_unlockAccount",
+    "personal_importRawKey",
+    "personal_sendTransaction",
+    "personal_signTransaction",
+    "personal_sign",
+];
+
+/// The methods whose responses are redacted by [`redact_secrets`], since they hold signed
+/// transactions that weren't sent yet
+pub const SECRET_RESPONSE_METHODS: &[&str] =
+    &["eth_signTransaction", "personal_signTransaction", "eth_sign", "personal_sign"];
+
+/// The value that redacted params and responses are replaced with
+pub const REDACTED: &str = "<redacted>";
+
+/// The part of a request that a redaction hook is applied to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// The params of the request
+    Params,
+    /// The result of the response
+    Response,
+}
+
+/// A hook that redacts secrets from the params or the response of a request before they are
+/// logged
+pub type RedactHook = Arc<dyn Fn(&str, Payload, &mut Value) + Send + Sync>;
+
+/// The default redaction hook, which replaces the params of the [`SECRET_PARAMS_METHODS`] and
+/// the responses of the [`SECRET_RESPONSE_METHODS`] with [`REDACTED`]
+pub fn redact_secrets(method: &str, payload: Payload, value: &mut Value) {
+    let methods = match payload {
+        Payload::Params => SECRET_PARAMS_METHODS,
+        Payload::Response => SECRET_RESPONSE_METHODS,
+    };
+    if methods.contains(&method) {
+        *value = Value::String(REDACTED.to_string());
+    }
+}
+
+/// A client that logs the method, params, response and duration of every request at the
+/// configured level, as events of the `ethers_providers::rpc` target.
+///
+/// Params and responses are passed through the redaction hooks before they are logged, by
+/// default [`redact_secrets`]. Headers of the inner transport, like the `Authorization` header,
+/// are never logged. Params and responses are only serialized if the configured level is enabled,
+/// failed requests are logged without them if only the `WARN` level is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use ethers_providers::{Http, Middleware, Provider, TracingClient};
+/// use std::str::FromStr;
+/// use tracing::Level;
+///
+/// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = TracingClient::new(Http::from_str("http://localhost:8545")?)
+///     .level(Level::INFO)
+///     .redact(|method, _, params| {
+///         if method == "eth_call" {
+///             *params = serde_json::Value::Null;
+///         }
+///     });
+/// let provider = Provider::new(client);
+/// let block_number = provider.get_block_number().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TracingClient<C> {
+    inner: C,
+    /// The level of the events of successful requests
+    level: Level,
+    /// Whether responses are logged
+    responses: bool,
+    /// Applied in order to params and responses before they are logged
+    hooks: Vec<RedactHook>,
+}
+
+impl<C> TracingClient<C> {
+    /// Wraps the client, logging requests at the `DEBUG` level with their responses and
+    /// redacting secrets with [`redact_secrets`]
+    pub fn new(inner: C) -> Self {
+        Self { inner, level: Level::DEBUG, responses: true, hooks: vec![Arc::new(redact_secrets)] }
+    }
+
+    /// Sets the level of the events of successful requests, failed requests are logged at the
+    /// `WARN` level unless the level is `ERROR` (default: `DEBUG`)
+    #[must_use]
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Sets whether responses are logged, or only the method and params (default: `true`)
+    #[must_use]
+    pub fn responses(mut self, responses: bool) -> Self {
+        self.responses = responses;
+        self
+    }
+
+    /// Adds a redaction hook, applied after the previously added hooks
+    #[must_use]
+    pub fn redact(
+        mut self,
+        hook: impl Fn(&str, Payload, &mut Value) + Send + Sync + 'static,
+    ) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Removes all redaction hooks, including [`redact_secrets`]
+    #[must_use]
+    pub fn without_redaction(mut self) -> Self {
+        self.hooks.clear();
+        self
+    }
+
+    /// Returns the wrapped client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Returns the value with all redaction hooks applied
+    fn redacted(&self, method: &str, payload: Payload, mut value: Value) -> Value {
+        for hook in &self.hooks {
+            hook(method, payload, &mut value);
+        }
+        value
+    }
+}
+
+impl<C: Debug> Debug for TracingClient<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TracingClient")
+            .field("inner", &self.inner)
+            .field("level", &self.level)
+            .field("responses", &self.responses)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+/// Emits an event at a level only known at runtime
+macro_rules! event {
+    ($level:expr, $($args:tt)*) => {
+        match $level {
+            Level::ERROR => tracing::error!(target: "ethers_providers::rpc", $($args)*),
+            Level::WARN => tracing::warn!(target: "ethers_providers::rpc", $($args)*),
+            Level::INFO => tracing::info!(target: "ethers_providers::rpc", $($args)*),
+            Level::DEBUG => tracing::debug!(target: "ethers_providers::rpc", $($args)*),
+            Level::TRACE => tracing::trace!(target: "ethers_providers::rpc", $($args)*),
+        }
+    };
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C> JsonRpcClient for TracingClient<C>
+where
+    C: JsonRpcClient,
+{
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let failure_level = if self.level == Level::ERROR { Level::ERROR } else { Level::WARN };
+        let enabled = tracing::level_enabled!(self.level);
+        if !enabled && !tracing::level_enabled!(failure_level) {
+            return self.inner.request(method, params).await.map_err(Into::into)
+        }
+
+        // failures are still logged if only their level is enabled, but without the params
+        let params_value = if enabled {
+            Some(self.redacted(method, Payload::Params, serde_json::to_value(&params)?))
+        } else {
+            None
+        };
+        let start = Instant::now();
+        let res: Result<Value, ProviderError> =
+            self.inner.request(method, params).await.map_err(Into::into);
+        let elapsed = start.elapsed();
+
+        match (res, params_value) {
+            (Ok(value), Some(params_value)) => {
+                if self.responses {
+                    let response = self.redacted(method, Payload::Response, value.clone());
+                    event!(
+                        self.level,
+                        method,
+                        params = %params_value,
+                        response = %response,
+                        ?elapsed,
+                        "rpc request"
+                    );
+                } else {
+                    event!(self.level, method, params = %params_value, ?elapsed, "rpc request");
+                }
+                Ok(serde_json::from_value(value)?)
+            }
+            (Ok(value), None) => Ok(serde_json::from_value(value)?),
+            (Err(err), Some(params_value)) => {
+                event!(
+                    failure_level,
+                    method,
+                    params = %params_value,
+                    %err,
+                    ?elapsed,
+                    "rpc request failed"
+                );
+                Err(err)
+            }
+            (Err(err), None) => {
+                event!(failure_level, method, %err, ?elapsed, "rpc request failed");
+                Err(err)
+            }
+        }
+    }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        self.inner.as_pubsub()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::{Middleware, MockProvider, Provider};
+    use ethers_core::types::{Bytes, H256, U64};
+    use serde_json::json;
+
+    #[test]
+    fn redacts_secrets() {
+        let client = TracingClient::new(MockProvider::new());
+        let raw = json!(["0x02f8"]);
+        assert_eq!(
+            client.redacted("eth_sendRawTransaction", Payload::Params, raw.clone()),
+            json!(REDACTED)
+        );
+        assert_eq!(client.redacted("eth_sendRawTransaction", Payload::Response, raw.clone()), raw);
+        assert_eq!(
+            client.redacted("eth_signTransaction", Payload::Response, raw.clone()),
+            json!(REDACTED)
+        );
+        assert_eq!(client.redacted("eth_call", Payload::Params, raw.clone()), raw);
+
+        let client = client.redact(|method, payload, value| {
+            if method == "eth_call" && payload == Payload::Params {
+                value[0] = Value::Null;
+            }
+        });
+        assert_eq!(client.redacted("eth_call", Payload::Params, raw.clone()), json!([null]));
+
+        let client = client.without_redaction();
+        assert_eq!(client.redacted("eth_sendRawTransaction", Payload::Params, raw.clone()), raw);
+    }
+
+    #[tokio::test]
+    async fn passes_requests_through() {
+        let mock = MockProvider::new();
+        let provider = Provider::new(TracingClient::new(mock.clone()).level(Level::ERROR));
+
+        mock.push(U64::from(7)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(7));
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+
+        let tx = Bytes::from(vec![1, 2, 3]);
+        mock.push(H256::zero()).unwrap();
+        let pending = provider.send_raw_transaction(tx.clone()).await.unwrap();
+        assert_eq!(pending.tx_hash(), H256::zero());
+        mock.assert_request("eth_sendRawTransaction", [tx]).unwrap();
+
+        assert!(provider.get_block_number().await.is_err());
+    }
+
+    /// Enables events up to the level
+    struct MaxLevel(Level);
+
+    impl tracing::Subscriber for MaxLevel {
+        fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+            *metadata.level() <= self.0
+        }
+
+        fn max_level_hint(&self) -> Option<tracing::level_filters::LevelFilter> {
+            Some(self.0.into())
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[tokio::test]
+    async fn serializes_params_only_if_the_level_is_enabled() {
+        let _guard = tracing::subscriber::set_default(MaxLevel(Level::WARN));
+        let redacted = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = redacted.clone();
+        let mock = MockProvider::new();
+        let client = TracingClient::new(mock.clone()).redact(move |_, _, _| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+        let provider = Provider::new(client);
+
+        mock.push(U64::from(7)).unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(7));
+        assert!(provider.get_block_number().await.is_err());
+        assert_eq!(redacted.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+}
@@ -10,16 +10,20 @@ use futures_channel::{mpsc, oneshot};
 use futures_util::{
     sink::{Sink, SinkExt},
     stream::{Fuse, Stream, StreamExt},
+    task::AtomicWaker,
 };
 use serde::{de::DeserializeOwned, Serialize};
-use serde_json::value::RawValue;
+use serde_json::{value::RawValue, Value};
 use std::{
-    collections::{btree_map::Entry, BTreeMap},
+    collections::{BTreeMap, VecDeque},
     fmt::{self, Debug},
+    future::Future,
+    pin::Pin,
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex,
     },
+    task::{Context, Poll},
 };
 use thiserror::Error;
 use tracing::trace;
@@ -79,19 +83,235 @@ if_not_wasm! {
     use tracing::{debug, error, warn};
     use http::Request as HttpRequest;
     use tungstenite::client::IntoClientRequest;
+    use std::time::Duration;
+
+    /// The delay before the first attempt to re-establish a dropped connection, doubled after
+    /// every failed attempt.
+    const RECONNECT_BACKOFF: Duration = Duration::from_millis(100);
+    /// The maximum delay between two attempts to re-establish a dropped connection.
+    const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(10);
+
+    /// Pings the node periodically and treats the connection as dropped if a ping isn't answered
+    /// in time, see [`Ws::set_keepalive`].
+    struct Keepalive {
+        interval: Duration,
+        timeout: Duration,
+        next_ping: tokio::time::Instant,
+        /// When the pending ping times out, if a ping is waiting for a pong
+        pong_deadline: Option<tokio::time::Instant>,
+    }
+
+    impl Keepalive {
+        fn new(interval: Duration, timeout: Duration) -> Self {
+            Self {
+                interval,
+                timeout,
+                next_ping: tokio::time::Instant::now() + interval,
+                pong_deadline: None,
+            }
+        }
+
+        /// Returns when the next ping is due or the pending ping times out
+        fn deadline(&self) -> tokio::time::Instant {
+            self.pong_deadline.map_or(self.next_ping, |deadline| deadline.min(self.next_ping))
+        }
+    }
 }
 
 type Pending = oneshot::Sender<Result<Box<RawValue>, JsonRpcError>>;
-type Subscription = mpsc::UnboundedSender<Box<RawValue>>;
+
+/// What happens to the notifications of a subscription whose buffer is full, see
+/// [`Ws::with_subscription_buffer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BufferPolicy {
+    /// Drops the oldest buffered notification to make room for the new one
+    DropOldest,
+    /// Drops the new notification
+    DropNewest,
+}
+
+/// The maximum number of buffered notifications of a subscription and what happens when the
+/// buffer is full
+#[derive(Clone, Copy, Debug)]
+struct BufferLimit {
+    capacity: usize,
+    policy: BufferPolicy,
+}
+
+/// The buffered notifications of a subscription, shared by the [`Subscription`] of the `WsServer`
+/// and the [`WsSubscription`] of the consumer
+#[derive(Debug, Default)]
+struct Buffer {
+    state: Mutex<BufferState>,
+    /// Woken when a notification is buffered or dropped, or the `WsServer` drops the subscription
+    consumer: AtomicWaker,
+}
+
+#[derive(Debug, Default)]
+struct BufferState {
+    notifications: VecDeque<Box<RawValue>>,
+    /// Unbounded if `None`
+    limit: Option<BufferLimit>,
+    /// The number of notifications dropped since the consumer last asked
+    lagged: u64,
+    /// Set once the server dropped the subscription, the stream ends after the buffered
+    /// notifications
+    closed: bool,
+    /// Set once the consumer dropped its stream
+    disconnected: bool,
+}
+
+/// The sending half of a subscription's buffer, held by the `WsServer`
+#[derive(Debug)]
+struct Subscription(Arc<Buffer>);
+
+impl Subscription {
+    /// Buffers the notification according to the buffer's policy without waiting for the
+    /// consumer, fails if the consumer dropped its stream
+    fn send(&self, notification: Box<RawValue>) -> Result<(), ()> {
+        let mut state = self.0.state.lock().unwrap();
+        if state.disconnected {
+            return Err(())
+        }
+        match state.limit {
+            Some(limit) if state.notifications.len() >= limit.capacity => match limit.policy {
+                BufferPolicy::DropOldest => {
+                    state.notifications.pop_front();
+                    state.notifications.push_back(notification);
+                    state.lagged += 1;
+                }
+                BufferPolicy::DropNewest => state.lagged += 1,
+            },
+            _ => state.notifications.push_back(notification),
+        }
+        drop(state);
+        self.0.consumer.wake();
+        Ok(())
+    }
+
+    /// Ends the consumer's stream after the buffered notifications
+    fn close_channel(&self) {
+        self.0.state.lock().unwrap().closed = true;
+        self.0.consumer.wake();
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.close_channel();
+    }
+}
+
+/// An error of the streams returned by [`WsSubscription::report_lag`] and
+/// [`SubscriptionStream::report_lag`](crate::SubscriptionStream::report_lag): the number of
+/// notifications that were dropped because the buffer of the subscription was full.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("the subscription lagged behind, {0} notifications were dropped")]
+pub struct Lagged(pub u64);
+
+/// The notifications of a subscription of a [`Ws`] client, created with
+/// [`Ws::subscribe_with_buffer`] or [`PubsubClient::subscribe`].
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct WsSubscription(Arc<Buffer>);
+
+impl WsSubscription {
+    fn new(limit: Option<BufferLimit>) -> (Subscription, Self) {
+        let buffer = Arc::new(Buffer::default());
+        buffer.state.lock().unwrap().limit = limit;
+        (Subscription(buffer.clone()), Self(buffer))
+    }
+
+    /// Returns the number of notifications that were dropped since the last call because the
+    /// buffer was full
+    pub fn take_lagged(&self) -> u64 {
+        std::mem::take(&mut self.0.state.lock().unwrap().lagged)
+    }
+
+    /// Returns a stream of the notifications that yields [`Lagged`] with the number of dropped
+    /// notifications before the next notification whenever the buffer was full
+    pub fn report_lag(self) -> impl Stream<Item = Result<Box<RawValue>, Lagged>> + Send + Unpin {
+        report_lag(self, |stream| stream.take_lagged())
+    }
+}
+
+/// Yields [`Lagged`] before the next item of the stream whenever `take_lagged` returns dropped
+/// notifications
+pub(crate) fn report_lag<S: Stream + Unpin>(
+    mut stream: S,
+    take_lagged: impl Fn(&S) -> u64 + Unpin,
+) -> impl Stream<Item = Result<S::Item, Lagged>> + Unpin {
+    futures_util::stream::poll_fn(move |cx| match take_lagged(&stream) {
+        0 => stream.poll_next_unpin(cx).map(|item| item.map(Ok)),
+        lagged => Poll::Ready(Some(Err(Lagged(lagged)))),
+    })
+}
+
+impl Stream for WsSubscription {
+    type Item = Box<RawValue>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut state = self.0.state.lock().unwrap();
+        if let Some(notification) = state.notifications.pop_front() {
+            return Poll::Ready(Some(notification))
+        }
+        if state.closed {
+            return Poll::Ready(None)
+        }
+        self.0.consumer.register(cx.waker());
+        Poll::Pending
+    }
+}
+
+impl Drop for WsSubscription {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().disconnected = true;
+    }
+}
+
+type Connect<S> =
+    Box<dyn Fn() -> Pin<Box<dyn Future<Output = Result<S, ClientError>> + Send>> + Send + Sync>;
 
 /// Instructions for the `WsServer`.
 enum Instruction {
     /// JSON-RPC request
-    Request { id: u64, request: String, sender: Pending },
+    Request { id: u64, request: String, kind: RequestKind, sender: Pending },
     /// Create a new subscription
     Subscribe { id: U256, sink: Subscription },
     /// Cancel an existing subscription
     Unsubscribe { id: U256 },
+    /// Listen for reconnects of the connection
+    Listen { sink: mpsc::UnboundedSender<ReconnectEvent> },
+    /// Ping the node every `interval` and expect a pong within `timeout`
+    #[cfg(not(target_arch = "wasm32"))]
+    Keepalive { interval: Duration, timeout: Duration },
+}
+
+/// The requests the `WsServer` needs to keep track of to restore subscriptions after reconnecting.
+enum RequestKind {
+    /// Any request that is not related to subscriptions
+    Call,
+    /// `eth_subscribe` with the given params
+    Subscribe(Value),
+    /// `eth_unsubscribe` of the given subscription
+    Unsubscribe(U256),
+}
+
+/// An event emitted by a [`Ws`] client that re-establishes dropped connections, see
+/// [`Ws::reconnect_events`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReconnectEvent {
+    /// The connection dropped and was re-established after the given number of attempts.
+    ///
+    /// Requests that were in flight when the connection dropped fail.
+    Reconnected { attempts: usize },
+    /// The subscription `id` was re-issued on the new connection.
+    ///
+    /// Its notifications are still delivered to the stream of `id`, and unsubscribing from `id`
+    /// cancels `server_id` instead.
+    Resubscribed { id: U256, server_id: U256 },
+    /// The subscription `id` could not be re-issued on the new connection, its stream ended.
+    ResubscribeFailed { id: U256 },
 }
 
 /// A JSON-RPC Client over Websockets.
@@ -110,6 +330,8 @@ enum Instruction {
 pub struct Ws {
     id: Arc<AtomicU64>,
     instructions: mpsc::UnboundedSender<Instruction>,
+    /// The buffer limit of new subscriptions, unbounded if `None`
+    buffer: Option<BufferLimit>,
 }
 
 impl Debug for Ws {
@@ -129,7 +351,22 @@ impl Ws {
         // Spawn the server
         WsServer::new(ws, stream).spawn();
 
-        Self { id: Arc::new(AtomicU64::new(1)), instructions: sink }
+        Self { id: Arc::new(AtomicU64::new(1)), instructions: sink, buffer: None }
+    }
+
+    fn new_with_reconnects<S>(ws: S, connect: Connect<S>, reconnects: usize) -> Self
+    where
+        S: 'static
+            + Send
+            + Sync
+            + Stream<Item = WsStreamItem>
+            + Sink<Message, Error = WsError>
+            + Unpin,
+    {
+        let (sink, stream) = mpsc::unbounded();
+        WsServer::new(ws, stream).reconnects(connect, reconnects).spawn();
+
+        Self { id: Arc::new(AtomicU64::new(1)), instructions: sink, buffer: None }
     }
 
     /// Returns true if the WS connection is active, false otherwise
@@ -167,6 +404,123 @@ impl Ws {
         Self::connect(request).await
     }
 
+    /// Initializes a new WebSocket Client that re-establishes the connection when it drops, with
+    /// up to `reconnects` attempts in a row and an exponential backoff between them.
+    ///
+    /// Active subscriptions are re-issued on the new connection and keep their ids, so their
+    /// streams keep receiving notifications. Use [`Ws::reconnect_events`] to observe reconnects.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_providers::Ws;
+    ///
+    /// let ws = Ws::connect_with_reconnects("ws://localhost:8545", 10).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn connect_with_reconnects(
+        uri: impl IntoClientRequest + Unpin,
+        reconnects: usize,
+    ) -> Result<Self, ClientError> {
+        let request = uri.into_client_request()?;
+        let (uri, headers) = (request.uri().clone(), request.headers().clone());
+        let (ws, _) = connect_async(request).await?;
+
+        let connect: Connect<_> = Box::new(move || {
+            let mut request = HttpRequest::new(());
+            *request.uri_mut() = uri.clone();
+            *request.headers_mut() = headers.clone();
+            Box::pin(async move { Ok(connect_async(request).await?.0) })
+        });
+        Ok(Self::new_with_reconnects(ws, connect, reconnects))
+    }
+
+    /// Returns a stream of the [`ReconnectEvent`]s of this client, which never yields anything
+    /// unless the client was created with [`Ws::connect_with_reconnects`].
+    pub fn reconnect_events(&self) -> Result<mpsc::UnboundedReceiver<ReconnectEvent>, ClientError> {
+        let (sink, stream) = mpsc::unbounded();
+        self.send(Instruction::Listen { sink })?;
+        Ok(stream)
+    }
+
+    /// Pings the node every `interval` and treats the connection as dropped if the node doesn't
+    /// answer a ping within `timeout`.
+    ///
+    /// This keeps idle connections alive through load balancers and proxies that close silent
+    /// connections, and detects dead connections that would otherwise stall requests and
+    /// subscriptions. A dropped connection is re-established if the client was created with
+    /// [`Ws::connect_with_reconnects`], otherwise all subscription streams end.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_providers::Ws;
+    /// use std::time::Duration;
+    ///
+    /// let ws = Ws::connect_with_reconnects("ws://localhost:8545", 10).await?;
+    /// ws.set_keepalive(Duration::from_secs(30), Duration::from_secs(10))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_keepalive(&self, interval: Duration, timeout: Duration) -> Result<(), ClientError> {
+        self.send(Instruction::Keepalive { interval, timeout })
+    }
+
+    /// Buffers at most `capacity` notifications of every subscription created afterwards and
+    /// applies the `policy` once a buffer is full, by default the buffers are unbounded.
+    ///
+    /// This stops slow consumers of busy subscriptions from taking up unbounded memory. Use
+    /// [`WsSubscription::take_lagged`] or [`SubscriptionStream::take_lagged`] to learn about
+    /// dropped notifications, the capacity is at least 1.
+    ///
+    /// [`SubscriptionStream::take_lagged`]: crate::SubscriptionStream::take_lagged
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn foo() -> Result<(), Box<dyn std::error::Error>> {
+    /// use ethers_providers::{BufferPolicy, Ws};
+    ///
+    /// let ws = Ws::connect("ws://localhost:8545").await?
+    ///     .with_subscription_buffer(1024, BufferPolicy::DropOldest);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_subscription_buffer(mut self, capacity: usize, policy: BufferPolicy) -> Self {
+        self.buffer = Some(BufferLimit { capacity: capacity.max(1), policy });
+        self
+    }
+
+    /// Like [`PubsubClient::subscribe`], but buffers at most `capacity` notifications of the
+    /// subscription and applies the `policy` once the buffer is full, see
+    /// [`Ws::with_subscription_buffer`].
+    pub fn subscribe_with_buffer(
+        &self,
+        id: impl Into<U256>,
+        capacity: usize,
+        policy: BufferPolicy,
+    ) -> Result<WsSubscription, ClientError> {
+        self.subscribe_with_limit(
+            id.into(),
+            Some(BufferLimit { capacity: capacity.max(1), policy }),
+        )
+    }
+
+    fn subscribe_with_limit(
+        &self,
+        id: U256,
+        limit: Option<BufferLimit>,
+    ) -> Result<WsSubscription, ClientError> {
+        let (sink, stream) = WsSubscription::new(limit);
+        self.send(Instruction::Subscribe { id, sink })?;
+        Ok(stream)
+    }
+
     fn send(&self, msg: Instruction) -> Result<(), ClientError> {
         self.instructions.unbounded_send(msg).map_err(to_client_error)
     }
@@ -184,11 +538,22 @@ impl JsonRpcClient for Ws {
     ) -> Result<R, ClientError> {
         let next_id = self.id.fetch_add(1, Ordering::SeqCst);
 
+        // subscriptions are tracked to restore them after reconnecting
+        let kind = match method {
+            "eth_subscribe" => RequestKind::Subscribe(serde_json::to_value(&params)?),
+            "eth_unsubscribe" => serde_json::to_value(&params)?
+                .get(0)
+                .and_then(|id| serde_json::from_value(id.clone()).ok())
+                .map_or(RequestKind::Call, RequestKind::Unsubscribe),
+            _ => RequestKind::Call,
+        };
+
         // send the message
         let (sender, receiver) = oneshot::channel();
         let payload = Instruction::Request {
             id: next_id,
             request: serde_json::to_string(&Request::new(next_id, method, params))?,
+            kind,
             sender,
         };
 
@@ -201,15 +566,17 @@ impl JsonRpcClient for Ws {
         // parse it
         Ok(serde_json::from_str(res.get())?)
     }
+
+    fn as_pubsub(&self) -> Option<&dyn crate::PubsubClientWrapper> {
+        Some(self)
+    }
 }
 
 impl PubsubClient for Ws {
-    type NotificationStream = mpsc::UnboundedReceiver<Box<RawValue>>;
+    type NotificationStream = WsSubscription;
 
     fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, ClientError> {
-        let (sink, stream) = mpsc::unbounded();
-        self.send(Instruction::Subscribe { id: id.into(), sink })?;
-        Ok(stream)
+        self.subscribe_with_limit(id.into(), self.buffer)
     }
 
     fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), ClientError> {
@@ -223,6 +590,25 @@ struct WsServer<S> {
 
     pending: BTreeMap<u64, Pending>,
     subscriptions: BTreeMap<U256, Subscription>,
+
+    /// Re-establishes the connection, if it should be re-established when it drops
+    connect: Option<Connect<S>>,
+    /// The maximum number of attempts in a row to re-establish the connection
+    reconnects: usize,
+    /// The id of the next request sent by the server itself, counting down to not collide with
+    /// the ids of the client
+    next_id: u64,
+    /// The params of pending `eth_subscribe` requests
+    pending_subscribes: BTreeMap<u64, Value>,
+    /// The params of active subscriptions, to re-issue them after reconnecting
+    subscription_params: BTreeMap<U256, Value>,
+    /// The subscriptions that are re-issued, by the id of their `eth_subscribe` request
+    resubscribing: BTreeMap<u64, U256>,
+    /// Maps the ids of re-issued subscriptions on the node to their original ids
+    aliases: BTreeMap<U256, U256>,
+    listeners: Vec<mpsc::UnboundedSender<ReconnectEvent>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    keepalive: Option<Keepalive>,
 }
 
 impl<S> WsServer<S>
@@ -238,9 +624,26 @@ where
             instructions: requests.fuse(),
             pending: BTreeMap::default(),
             subscriptions: BTreeMap::default(),
+            connect: None,
+            reconnects: 0,
+            next_id: u64::MAX,
+            pending_subscribes: BTreeMap::default(),
+            subscription_params: BTreeMap::default(),
+            resubscribing: BTreeMap::default(),
+            aliases: BTreeMap::default(),
+            listeners: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            keepalive: None,
         }
     }
 
+    /// Re-establishes the connection with `connect` when it drops
+    fn reconnects(mut self, connect: Connect<S>, reconnects: usize) -> Self {
+        self.connect = Some(connect);
+        self.reconnects = reconnects;
+        self
+    }
+
     /// Returns whether the all work has been completed.
     ///
     /// If this method returns `true`, then the `instructions` channel has been closed and all
@@ -263,6 +666,9 @@ where
 
                 if let Err(e) = self.tick().await {
                     error!("Received a WebSocket error: {:?}", e);
+                    if e.is_disconnect() && self.reconnect().await.is_ok() {
+                        continue
+                    }
                     self.close_all_subscriptions();
                     break
                 }
@@ -285,17 +691,109 @@ where
         }
     }
 
+    /// Re-establishes the dropped connection and re-issues the active subscriptions
+    async fn reconnect(&mut self) -> Result<(), ClientError> {
+        let connect = self.connect.as_ref().ok_or(ClientError::UnexpectedClose)?;
+
+        let mut attempts = 0;
+        let ws = loop {
+            #[cfg(not(target_arch = "wasm32"))]
+            tokio::time::sleep(std::cmp::min(
+                RECONNECT_BACKOFF * 2u32.saturating_pow(attempts as u32),
+                MAX_RECONNECT_BACKOFF,
+            ))
+            .await;
+
+            attempts += 1;
+            match connect().await {
+                Ok(ws) => break ws,
+                Err(err) if attempts < self.reconnects => {
+                    warn!("Failed to reconnect, attempt {}: {:?}", attempts, err);
+                }
+                Err(err) => {
+                    error!("Failed to reconnect after {} attempts: {:?}", attempts, err);
+                    return Err(err)
+                }
+            }
+        };
+        debug!("reconnected after {} attempts", attempts);
+        self.ws = ws.fuse();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(keepalive) = self.keepalive.take() {
+            self.keepalive = Some(Keepalive::new(keepalive.interval, keepalive.timeout));
+        }
+
+        // responses to requests sent over the dropped connection never arrive
+        self.pending.clear();
+        self.pending_subscribes.clear();
+        self.resubscribing.clear();
+        self.aliases.clear();
+        self.emit(ReconnectEvent::Reconnected { attempts });
+
+        let ids: Vec<U256> = self.subscriptions.keys().copied().collect();
+        for id in ids {
+            let params = match self.subscription_params.get(&id) {
+                Some(params) => params.clone(),
+                None => {
+                    warn!("Cannot re-issue subscription with id {:?}", id);
+                    if let Some(sub) = self.subscriptions.remove(&id) {
+                        sub.close_channel();
+                    }
+                    self.emit(ReconnectEvent::ResubscribeFailed { id });
+                    continue
+                }
+            };
+            let request_id = self.next_id;
+            self.next_id -= 1;
+            let request =
+                serde_json::to_string(&Request::new(request_id, "eth_subscribe", params))?;
+            self.resubscribing.insert(request_id, id);
+            // a failure drops the new connection as well, which is handled by the next tick
+            if let Err(e) = self.ws.send(Message::Text(request)).await {
+                error!("WS connection error: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, event: ReconnectEvent) {
+        self.listeners.retain(|listener| listener.unbounded_send(event.clone()).is_ok());
+    }
+
     // dispatch an RPC request
     async fn service_request(
         &mut self,
         id: u64,
-        request: String,
+        mut request: String,
+        kind: RequestKind,
         sender: Pending,
     ) -> Result<(), ClientError> {
         if self.pending.insert(id, sender).is_some() {
             warn!("Replacing a pending request with id {:?}", id);
         }
 
+        if self.connect.is_some() {
+            match kind {
+                RequestKind::Call => {}
+                RequestKind::Subscribe(params) => {
+                    self.pending_subscribes.insert(id, params);
+                }
+                RequestKind::Unsubscribe(sub_id) => {
+                    self.subscription_params.remove(&sub_id);
+                    // a re-issued subscription has a different id on the node
+                    let alias = self.aliases.iter().find(|(_, id)| **id == sub_id);
+                    if let Some(server_id) = alias.map(|(server_id, _)| *server_id) {
+                        self.aliases.remove(&server_id);
+                        request = serde_json::to_string(&Request::new(
+                            id,
+                            "eth_unsubscribe",
+                            [server_id],
+                        ))?;
+                    }
+                }
+            }
+        }
+
         if let Err(e) = self.ws.send(Message::Text(request)).await {
             error!("WS connection error: {:?}", e);
             self.pending.remove(&id);
@@ -322,12 +820,45 @@ where
     /// Dispatch an outgoing message
     async fn service(&mut self, instruction: Instruction) -> Result<(), ClientError> {
         match instruction {
-            Instruction::Request { id, request, sender } => {
-                self.service_request(id, request, sender).await
+            Instruction::Request { id, request, kind, sender } => {
+                self.service_request(id, request, kind, sender).await
             }
             Instruction::Subscribe { id, sink } => self.service_subscribe(id, sink).await,
             Instruction::Unsubscribe { id } => self.service_unsubscribe(id).await,
+            Instruction::Listen { sink } => {
+                self.listeners.push(sink);
+                Ok(())
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            Instruction::Keepalive { interval, timeout } => {
+                self.keepalive = Some(Keepalive::new(interval, timeout));
+                Ok(())
+            }
+        }
+    }
+
+    /// Sends the next ping, or fails if the pending ping timed out
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn service_keepalive(&mut self) -> Result<(), ClientError> {
+        let now = tokio::time::Instant::now();
+        let keepalive = match self.keepalive.as_mut() {
+            Some(keepalive) => keepalive,
+            None => return Ok(()),
+        };
+        if let Some(deadline) = keepalive.pong_deadline {
+            if deadline <= now {
+                return Err(ClientError::PongTimeout(keepalive.timeout))
+            }
+        }
+        if keepalive.next_ping <= now {
+            keepalive.next_ping = now + keepalive.interval;
+            if keepalive.pong_deadline.is_none() {
+                keepalive.pong_deadline = Some(now + keepalive.timeout);
+            }
+            trace!("sending keepalive ping");
+            self.ws.send(Message::Ping(Vec::new())).await?;
         }
+        Ok(())
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -341,9 +872,22 @@ where
         let (id, result) = match serde_json::from_str(&inner)? {
             Response::Success { id, result } => (id, Ok(result.to_owned())),
             Response::Error { id, error } => (id, Err(error)),
-            Response::Notification { params, .. } => return self.handle_notification(params),
+            Response::Notification { params, .. } => return self.handle_notification(params).await,
         };
 
+        if let Some(sub_id) = self.resubscribing.remove(&id) {
+            self.handle_resubscribe(sub_id, result);
+            return Ok(())
+        }
+
+        if let Some(params) = self.pending_subscribes.remove(&id) {
+            if let Some(sub_id) =
+                result.as_ref().ok().and_then(|res| serde_json::from_str(res.get()).ok())
+            {
+                self.subscription_params.insert(sub_id, params);
+            }
+        }
+
         if let Some(request) = self.pending.remove(&id) {
             if !request.is_canceled() {
                 request.send(result).map_err(to_client_error)?;
@@ -353,15 +897,32 @@ where
         Ok(())
     }
 
-    fn handle_notification(&mut self, params: Params<'_>) -> Result<(), ClientError> {
-        let id = params.subscription;
-        if let Entry::Occupied(stream) = self.subscriptions.entry(id) {
-            if let Err(err) = stream.get().unbounded_send(params.result.to_owned()) {
-                if err.is_disconnected() {
-                    // subscription channel was closed on the receiver end
-                    stream.remove();
+    fn handle_resubscribe(&mut self, id: U256, result: Result<Box<RawValue>, JsonRpcError>) {
+        match result.ok().and_then(|res| serde_json::from_str::<U256>(res.get()).ok()) {
+            Some(server_id) => {
+                if server_id != id {
+                    self.aliases.insert(server_id, id);
+                }
+                self.emit(ReconnectEvent::Resubscribed { id, server_id });
+            }
+            None => {
+                error!("Failed to re-issue subscription with id {:?}", id);
+                self.subscription_params.remove(&id);
+                if let Some(sub) = self.subscriptions.remove(&id) {
+                    sub.close_channel();
                 }
-                return Err(to_client_error(err))
+                self.emit(ReconnectEvent::ResubscribeFailed { id });
+            }
+        }
+    }
+
+    async fn handle_notification(&mut self, params: Params<'_>) -> Result<(), ClientError> {
+        let id = self.aliases.get(&params.subscription).copied().unwrap_or(params.subscription);
+        if let Some(stream) = self.subscriptions.get(&id) {
+            if stream.send(params.result.to_owned()).is_err() {
+                // subscription channel was closed on the receiver end
+                self.subscriptions.remove(&id);
+                return Err(ClientError::ChannelError(format!("subscription {id:?} was dropped")))
             }
         }
 
@@ -382,7 +943,13 @@ where
             Message::Text(inner) => self.handle_text(inner).await,
             Message::Frame(_) => Ok(()), // Server is allowed to send Raw frames
             Message::Ping(inner) => self.handle_ping(inner).await,
-            Message::Pong(_) => Ok(()), // Server is allowed to send unsolicited pongs.
+            Message::Pong(_) => {
+                // Server is allowed to send unsolicited pongs.
+                if let Some(keepalive) = self.keepalive.as_mut() {
+                    keepalive.pong_deadline = None;
+                }
+                Ok(())
+            }
             Message::Close(Some(frame)) => Err(ClientError::WsClosed(frame)),
             Message::Close(None) => Err(ClientError::UnexpectedClose),
             Message::Binary(buf) => Err(ClientError::UnexpectedBinary(buf)),
@@ -414,11 +981,24 @@ where
     #[allow(clippy::single_match)]
     #[cfg(not(target_arch = "wasm32"))]
     async fn tick(&mut self) -> Result<(), ClientError> {
+        let deadline = self.keepalive.as_ref().map(Keepalive::deadline);
+        let keepalive = async move {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => futures_util::future::pending().await,
+            }
+        };
+        futures_util::pin_mut!(keepalive);
+
         futures_util::select! {
             // Handle requests
             instruction = self.instructions.select_next_some() => {
                 self.service(instruction).await?;
             },
+            // Ping the node
+            _ = futures_util::FutureExt::fuse(keepalive) => {
+                self.service_keepalive().await?;
+            },
             // Handle ws messages
             resp = self.ws.next() => match resp {
                 Some(Ok(resp)) => self.handle(resp).await?,
@@ -480,6 +1060,11 @@ pub enum ClientError {
     #[error("WebSocket connection closed unexpectedly")]
     UnexpectedClose,
 
+    /// The node didn't answer a keepalive ping in time
+    #[error("No pong received within {0:?}")]
+    #[cfg(not(target_arch = "wasm32"))]
+    PongTimeout(Duration),
+
     /// Could not create an auth header for websocket handshake
     #[error(transparent)]
     #[cfg(not(target_arch = "wasm32"))]
@@ -496,6 +1081,17 @@ pub enum ClientError {
     RequestError(#[from] http::Error),
 }
 
+impl ClientError {
+    /// Returns whether this error means that the connection dropped.
+    fn is_disconnect(&self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if matches!(self, ClientError::WsClosed(_) | ClientError::PongTimeout(_)) {
+            return true
+        }
+        matches!(self, ClientError::UnexpectedClose | ClientError::TungsteniteError(_))
+    }
+}
+
 impl From<ClientError> for ProviderError {
     fn from(src: ClientError) -> Self {
         ProviderError::JsonRpcClientError(Box::new(src))
@@ -542,6 +1138,157 @@ mod tests {
         assert_eq!(blocks, vec![1, 2, 3]);
     }
 
+    #[tokio::test]
+    async fn reconnects_and_restores_subscriptions() {
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // a node that drops the first connection after a notification
+        let node = tokio::spawn(async move {
+            let recv = |msg: Option<Result<Message, WsError>>| -> Value {
+                serde_json::from_str(msg.unwrap().unwrap().to_text().unwrap()).unwrap()
+            };
+            let respond = |id: &Value, result: &str| {
+                Message::Text(format!(r#"{{"jsonrpc":"2.0","id":{id},"result":{result}}}"#))
+            };
+            let notify = |sub: &str| {
+                Message::Text(format!(
+                    r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"subscription":"{sub}","result":"{sub}"}}}}"#
+                ))
+            };
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let req = recv(ws.next().await);
+            assert_eq!(req["params"], serde_json::json!(["newHeads"]));
+            ws.send(respond(&req["id"], r#""0x1""#)).await.unwrap();
+            // the client registered the subscription once it sends the next request
+            let req = recv(ws.next().await);
+            ws.send(respond(&req["id"], r#""0x7""#)).await.unwrap();
+            ws.send(notify("0x1")).await.unwrap();
+            ws.close(None).await.unwrap();
+
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let req = recv(ws.next().await);
+            assert_eq!(req["method"], "eth_subscribe");
+            assert_eq!(req["params"], serde_json::json!(["newHeads"]));
+            ws.send(respond(&req["id"], r#""0x2""#)).await.unwrap();
+            ws.send(notify("0x2")).await.unwrap();
+            let req = recv(ws.next().await);
+            assert_eq!(req["method"], "eth_unsubscribe");
+            assert_eq!(req["params"], serde_json::json!(["0x2"]));
+            ws.send(respond(&req["id"], "true")).await.unwrap();
+            ws
+        });
+
+        let ws = Ws::connect_with_reconnects(format!("ws://{addr}"), 3).await.unwrap();
+        let mut events = ws.reconnect_events().unwrap();
+        let id: U256 = ws.request("eth_subscribe", ["newHeads"]).await.unwrap();
+        let mut stream = ws.subscribe(id).unwrap();
+        let _: U256 = ws.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().get(), r#""0x1""#);
+
+        assert_eq!(events.next().await, Some(ReconnectEvent::Reconnected { attempts: 1 }));
+        assert_eq!(
+            events.next().await,
+            Some(ReconnectEvent::Resubscribed { id, server_id: 2.into() })
+        );
+        assert_eq!(stream.next().await.unwrap().get(), r#""0x2""#);
+
+        let unsubscribed: bool = ws.request("eth_unsubscribe", [id]).await.unwrap();
+        assert!(unsubscribed);
+        node.await.unwrap();
+    }
+
+    /// Subscribes with the given buffer to a node that pushes 4 notifications before answering
+    /// the next request, returns the notifications and lag reports of the subscription
+    async fn buffered_notifications(policy: BufferPolicy) -> Vec<Result<String, Lagged>> {
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let node = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            for result in [r#""0x1""#, r#""0x2""#] {
+                let req: Value =
+                    serde_json::from_str(ws.next().await.unwrap().unwrap().to_text().unwrap())
+                        .unwrap();
+                if req["method"] == "eth_blockNumber" {
+                    for n in 1..=4 {
+                        ws.send(Message::Text(format!(
+                            r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":{{"subscription":"0x1","result":{n}}}}}"#
+                        )))
+                        .await
+                        .unwrap();
+                    }
+                }
+                let id = &req["id"];
+                ws.send(Message::Text(format!(
+                    r#"{{"jsonrpc":"2.0","id":{id},"result":{result}}}"#
+                )))
+                .await
+                .unwrap();
+            }
+            ws
+        });
+
+        let ws = Ws::connect(format!("ws://{addr}")).await.unwrap();
+        let id: U256 = ws.request("eth_subscribe", ["newHeads"]).await.unwrap();
+        let stream = ws.subscribe_with_buffer(id, 2, policy).unwrap();
+        // the response is not delayed by the full buffer, whatever the policy
+        let _: U256 = ws.request("eth_blockNumber", ()).await.unwrap();
+        let _ws = node.await.unwrap();
+        stream.report_lag().take(3).map(|n| n.map(|n| n.get().to_string())).collect().await
+    }
+
+    #[tokio::test]
+    async fn applies_subscription_buffer_policies() {
+        let notifications = buffered_notifications(BufferPolicy::DropOldest).await;
+        assert_eq!(notifications, vec![Err(Lagged(2)), Ok("3".into()), Ok("4".into())]);
+
+        let notifications = buffered_notifications(BufferPolicy::DropNewest).await;
+        assert_eq!(notifications, vec![Err(Lagged(2)), Ok("1".into()), Ok("2".into())]);
+    }
+
+    #[tokio::test]
+    async fn keepalive_detects_dead_connections() {
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // a node that answers 3 pings and then stops reading, so it never sends another pong
+        let node = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let mut pings = 0;
+            while pings < 3 {
+                if let Message::Ping(_) = ws.next().await.unwrap().unwrap() {
+                    pings += 1;
+                }
+            }
+            ws
+        });
+
+        let ws = Ws::connect(format!("ws://{addr}")).await.unwrap();
+        ws.set_keepalive(Duration::from_millis(20), Duration::from_millis(250)).unwrap();
+        let _ws = node.await.unwrap();
+        assert!(ws.ready());
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while ws.ready() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
     #[tokio::test]
     async fn deserialization_fails() {
         let anvil = Anvil::new().block_time(1u64).spawn();
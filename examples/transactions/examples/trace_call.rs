@@ -26,6 +26,7 @@ async fn main() -> Result<()> {
                 )),
                 ..Default::default()
             },
+            ..Default::default()
         };
         let traces = client.debug_trace_call(tx, Some(block), options).await?;
         println!("{traces:?}");